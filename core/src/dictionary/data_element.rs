@@ -216,6 +216,84 @@ impl VirtualVr {
     }
 }
 
+/// The value multiplicity (VM) of an attribute,
+/// as specified by the data dictionary.
+///
+/// It describes the number of values
+/// which a data element of that attribute may contain.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub enum ValueMultiplicity {
+    /// Exactly this number of values,
+    /// such as `1` or `3`
+    Exactly(u32),
+    /// Between the two given numbers of values (inclusive),
+    /// such as `1-3`
+    Between(u32, u32),
+    /// At least this number of values,
+    /// such as `1-n` or `2-n`
+    AtLeast(u32),
+    /// A non-zero multiple of this number of values,
+    /// such as `2-2n` or `3-3n`
+    MultipleOf(u32),
+}
+
+impl ValueMultiplicity {
+    /// Check whether the given number of values
+    /// is in conformance with this value multiplicity.
+    pub fn contains(self, count: u32) -> bool {
+        match self {
+            ValueMultiplicity::Exactly(n) => count == n,
+            ValueMultiplicity::Between(min, max) => (min..=max).contains(&count),
+            ValueMultiplicity::AtLeast(min) => count >= min,
+            ValueMultiplicity::MultipleOf(k) => count >= k && count % k == 0,
+        }
+    }
+}
+
+impl std::fmt::Display for ValueMultiplicity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueMultiplicity::Exactly(n) => write!(f, "{}", n),
+            ValueMultiplicity::Between(min, max) => write!(f, "{}-{}", min, max),
+            ValueMultiplicity::AtLeast(min) => write!(f, "{}-n", min),
+            ValueMultiplicity::MultipleOf(k) => write!(f, "{}-{}n", k, k),
+        }
+    }
+}
+
+/// An error returned when parsing an invalid value multiplicity.
+#[derive(Debug, Snafu)]
+#[snafu(display("invalid value multiplicity `{}`", vm))]
+pub struct ValueMultiplicityParseError {
+    vm: String,
+    backtrace: Backtrace,
+}
+
+impl FromStr for ValueMultiplicity {
+    type Err = ValueMultiplicityParseError;
+
+    /// Parse a value multiplicity as written in the data dictionary,
+    /// such as `1`, `1-3`, `1-n`, or `2-2n`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |s: &str| -> Option<Self> {
+            let Some((min, max)) = s.split_once('-') else {
+                return s.parse().ok().map(ValueMultiplicity::Exactly);
+            };
+            let min: u32 = min.parse().ok()?;
+            if max == "n" {
+                return Some(ValueMultiplicity::AtLeast(min));
+            }
+            if let Some(k) = max.strip_suffix('n') {
+                let k: u32 = k.parse().ok()?;
+                return (k == min && k > 0).then_some(ValueMultiplicity::MultipleOf(k));
+            }
+            let max: u32 = max.parse().ok()?;
+            (min <= max).then_some(ValueMultiplicity::Between(min, max))
+        };
+        parse(s.trim()).context(ValueMultiplicityParseSnafu { vm: s })
+    }
+}
+
 /// An error during attribute selector parsing
 #[derive(Debug, Snafu)]
 pub struct ParseSelectorError(ParseSelectorErrorInner);
@@ -287,6 +365,38 @@ pub trait DataDictionary {
     /// [3]: https://docs.rs/dicom-dictionary-std/0.5.0
     fn by_name(&self, name: &str) -> Option<&Self::Entry>;
 
+    /// Fetch a private data element entry
+    /// by the identifier of its private creator and its tag.
+    ///
+    /// Private data elements are only meaningful
+    /// within the block reserved by their creator,
+    /// so only the group and the two rightmost digits of the element
+    /// should be taken into account:
+    /// `(0009,1010)` and `(0009,1110)` refer to the same attribute
+    /// if both blocks were reserved by the same private creator.
+    ///
+    /// The default implementation does not know of any private attributes
+    /// and always returns `None`.
+    fn by_private_tag(&self, private_creator: &str, tag: Tag) -> Option<&Self::Entry> {
+        let _ = (private_creator, tag);
+        None
+    }
+
+    /// Fetch a private data element entry by its alias,
+    /// alongside the identifier of its private creator.
+    ///
+    /// The tag of the entry describes the attribute
+    /// as if its block was reserved at `(gggg,0010)`,
+    /// which means that only the group
+    /// and the two rightmost digits of the element are relevant.
+    ///
+    /// The default implementation does not know of any private attributes
+    /// and always returns `None`.
+    fn private_by_name(&self, name: &str) -> Option<(&str, &Self::Entry)> {
+        let _ = name;
+        None
+    }
+
     /// Fetch an entry by its alias or by DICOM tag expression.
     ///
     /// This method accepts a tag descriptor in any of the following formats:
@@ -405,6 +515,13 @@ pub trait DataDictionaryEntry {
     /// in which the representation of a value
     /// depends on surrounding context.
     fn vr(&self) -> VirtualVr;
+
+    /// The value multiplicity of the attribute, if known.
+    ///
+    /// The default implementation returns `None`.
+    fn vm(&self) -> Option<ValueMultiplicity> {
+        None
+    }
 }
 
 /// A data type for a dictionary entry with full ownership.
@@ -416,6 +533,31 @@ pub struct DataDictionaryEntryBuf {
     pub alias: String,
     /// The _typical_  value representation of the attribute
     pub vr: VirtualVr,
+    /// The value multiplicity of the attribute, if known
+    pub vm: Option<ValueMultiplicity>,
+}
+
+impl DataDictionaryEntryBuf {
+    /// Create a dictionary entry
+    /// without a known value multiplicity.
+    ///
+    /// Use [`with_vm`](Self::with_vm) to set the value multiplicity.
+    pub fn new(tag: TagRange, alias: impl Into<String>, vr: VirtualVr) -> Self {
+        DataDictionaryEntryBuf {
+            tag,
+            alias: alias.into(),
+            vr,
+            vm: None,
+        }
+    }
+
+    /// Set the value multiplicity of this entry.
+    pub fn with_vm(self, vm: ValueMultiplicity) -> Self {
+        DataDictionaryEntryBuf {
+            vm: Some(vm),
+            ..self
+        }
+    }
 }
 
 impl DataDictionaryEntry for DataDictionaryEntryBuf {
@@ -428,6 +570,9 @@ impl DataDictionaryEntry for DataDictionaryEntryBuf {
     fn vr(&self) -> VirtualVr {
         self.vr
     }
+    fn vm(&self) -> Option<ValueMultiplicity> {
+        self.vm
+    }
 }
 
 /// A data type for a dictionary entry with a string slice for its alias.
@@ -439,6 +584,33 @@ pub struct DataDictionaryEntryRef<'a> {
     pub alias: &'a str,
     /// The extended value representation descriptor of the attribute
     pub vr: VirtualVr,
+    /// The value multiplicity of the attribute, if known
+    pub vm: Option<ValueMultiplicity>,
+}
+
+impl<'a> DataDictionaryEntryRef<'a> {
+    /// Create a dictionary entry
+    /// without a known value multiplicity.
+    ///
+    /// Use [`with_vm`](Self::with_vm) to set the value multiplicity.
+    pub const fn new(tag: TagRange, alias: &'a str, vr: VirtualVr) -> Self {
+        DataDictionaryEntryRef {
+            tag,
+            alias,
+            vr,
+            vm: None,
+        }
+    }
+
+    /// Set the value multiplicity of this entry.
+    pub const fn with_vm(self, vm: ValueMultiplicity) -> Self {
+        DataDictionaryEntryRef {
+            tag: self.tag,
+            alias: self.alias,
+            vr: self.vr,
+            vm: Some(vm),
+        }
+    }
 }
 
 impl DataDictionaryEntry for DataDictionaryEntryRef<'_> {
@@ -451,6 +623,9 @@ impl DataDictionaryEntry for DataDictionaryEntryRef<'_> {
     fn vr(&self) -> VirtualVr {
         self.vr
     }
+    fn vm(&self) -> Option<ValueMultiplicity> {
+        self.vm
+    }
 }
 
 /// Utility data structure that resolves to a DICOM attribute tag
@@ -487,8 +662,12 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::TagRange;
+    use super::{
+        DataDictionaryEntry, DataDictionaryEntryBuf, DataDictionaryEntryRef, TagRange,
+        ValueMultiplicity,
+    };
     use crate::header::Tag;
+    use crate::VR;
 
     #[test]
     fn test_parse_tag_range() {
@@ -504,4 +683,73 @@ mod tests {
         let tag: TagRange = "1234,56xx".parse().unwrap();
         assert_eq!(tag, TagRange::Element100(Tag(0x1234, 0x5600)));
     }
+
+    #[test]
+    fn test_parse_value_multiplicity() {
+        use ValueMultiplicity::*;
+
+        for (text, vm) in [
+            ("1", Exactly(1)),
+            ("16", Exactly(16)),
+            ("1-3", Between(1, 3)),
+            ("1-n", AtLeast(1)),
+            ("2-n", AtLeast(2)),
+            ("2-2n", MultipleOf(2)),
+            ("3-3n", MultipleOf(3)),
+        ] {
+            assert_eq!(text.parse::<ValueMultiplicity>().unwrap(), vm);
+            assert_eq!(vm.to_string(), text);
+        }
+
+        for text in ["", "n", "x", "3-1", "2-3n", "0-0n", "1-", "-1"] {
+            assert!(
+                text.parse::<ValueMultiplicity>().is_err(),
+                "{:?} should not be a valid VM",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_value_multiplicity_contains() {
+        use ValueMultiplicity::*;
+
+        // 1
+        assert!(Exactly(1).contains(1));
+        assert!(!Exactly(1).contains(0));
+        assert!(!Exactly(1).contains(2));
+        // 1-3
+        assert!(Between(1, 3).contains(1));
+        assert!(Between(1, 3).contains(3));
+        assert!(!Between(1, 3).contains(4));
+        // 1-n
+        assert!(AtLeast(1).contains(1));
+        assert!(AtLeast(1).contains(500));
+        assert!(!AtLeast(1).contains(0));
+        // 2-2n
+        assert!(MultipleOf(2).contains(2));
+        assert!(MultipleOf(2).contains(6));
+        assert!(!MultipleOf(2).contains(3));
+        assert!(!MultipleOf(2).contains(0));
+        // 3-3n
+        assert!(MultipleOf(3).contains(3));
+        assert!(MultipleOf(3).contains(9));
+        assert!(!MultipleOf(3).contains(4));
+    }
+
+    #[test]
+    fn test_entry_constructors() {
+        let tag = TagRange::Single(Tag(0x0008, 0x0008));
+
+        let entry = DataDictionaryEntryRef::new(tag, "ImageType", VR::CS.into());
+        assert_eq!(entry.vm(), None);
+        let entry = entry.with_vm(ValueMultiplicity::AtLeast(2));
+        assert_eq!(entry.alias(), "ImageType");
+        assert_eq!(entry.vm(), Some(ValueMultiplicity::AtLeast(2)));
+
+        let entry = DataDictionaryEntryBuf::new(tag, "ImageType", VR::CS.into())
+            .with_vm(ValueMultiplicity::AtLeast(2));
+        assert_eq!(entry.tag_range(), tag);
+        assert_eq!(entry.vm(), Some(ValueMultiplicity::AtLeast(2)));
+    }
 }
@@ -0,0 +1,182 @@
+//! This module contains a data dictionary
+//! composed of two other dictionaries.
+
+use super::DataDictionary;
+use crate::header::Tag;
+
+/// A data element dictionary which looks up attributes in two dictionaries,
+/// giving precedence to the first one.
+///
+/// This is mostly useful to extend the standard data dictionary
+/// with a dictionary of private attributes,
+/// so that both standard and private attributes
+/// are recognized by the same DICOM object.
+///
+/// Both dictionaries must provide the same type of entry.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::dictionary::{MergedDictionary, DataDictionary};
+/// # use dicom_core::dictionary::stub::StubDataDictionary;
+/// let dict = MergedDictionary::new(StubDataDictionary, StubDataDictionary);
+/// assert!(dict.by_name("PatientName").is_none());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MergedDictionary<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> MergedDictionary<A, B> {
+    /// Create a new merged dictionary.
+    /// Entries in `first` take precedence over those in `second`.
+    pub fn new(first: A, second: B) -> Self {
+        MergedDictionary { first, second }
+    }
+
+    /// Obtain a reference to the dictionary which is looked up first.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Obtain a reference to the dictionary which is looked up second.
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// Split the merged dictionary into its two parts.
+    pub fn into_parts(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A, B> DataDictionary for MergedDictionary<A, B>
+where
+    A: DataDictionary,
+    B: DataDictionary<Entry = A::Entry>,
+{
+    type Entry = A::Entry;
+
+    fn by_tag(&self, tag: Tag) -> Option<&Self::Entry> {
+        self.first.by_tag(tag).or_else(|| self.second.by_tag(tag))
+    }
+
+    fn by_name(&self, name: &str) -> Option<&Self::Entry> {
+        self.first
+            .by_name(name)
+            .or_else(|| self.second.by_name(name))
+    }
+
+    fn by_private_tag(&self, private_creator: &str, tag: Tag) -> Option<&Self::Entry> {
+        self.first
+            .by_private_tag(private_creator, tag)
+            .or_else(|| self.second.by_private_tag(private_creator, tag))
+    }
+
+    fn private_by_name(&self, name: &str) -> Option<(&str, &Self::Entry)> {
+        self.first
+            .private_by_name(name)
+            .or_else(|| self.second.private_by_name(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MergedDictionary;
+    use crate::dictionary::{
+        DataDictionary, DataDictionaryEntry, DataDictionaryEntryRef, TagRange, VirtualVr,
+    };
+    use crate::{Tag, VR};
+
+    static PUBLIC: DataDictionaryEntryRef<'static> = DataDictionaryEntryRef::new(
+        TagRange::Single(Tag(0x0010, 0x0010)),
+        "PatientName",
+        VirtualVr::Exact(VR::PN),
+    );
+
+    static PRIVATE: DataDictionaryEntryRef<'static> = DataDictionaryEntryRef::new(
+        TagRange::Single(Tag(0x0009, 0x0001)),
+        "AcmeSerial",
+        VirtualVr::Exact(VR::LO),
+    );
+
+    /// A dictionary with a single public attribute.
+    struct PublicDictionary;
+
+    impl DataDictionary for PublicDictionary {
+        type Entry = DataDictionaryEntryRef<'static>;
+
+        fn by_tag(&self, tag: Tag) -> Option<&Self::Entry> {
+            Some(&PUBLIC).filter(|_| tag == Tag(0x0010, 0x0010))
+        }
+
+        fn by_name(&self, name: &str) -> Option<&Self::Entry> {
+            Some(&PUBLIC).filter(|_| name == "PatientName")
+        }
+    }
+
+    /// A dictionary with a single private attribute.
+    struct PrivateDictionary;
+
+    impl DataDictionary for PrivateDictionary {
+        type Entry = DataDictionaryEntryRef<'static>;
+
+        fn by_tag(&self, _: Tag) -> Option<&Self::Entry> {
+            None
+        }
+
+        fn by_name(&self, _: &str) -> Option<&Self::Entry> {
+            None
+        }
+
+        fn by_private_tag(&self, private_creator: &str, tag: Tag) -> Option<&Self::Entry> {
+            Some(&PRIVATE).filter(|_| {
+                private_creator == "ACME 1.0"
+                    && tag.group() == 0x0009
+                    && tag.element() & 0xFF == 0x01
+            })
+        }
+
+        fn private_by_name(&self, name: &str) -> Option<(&str, &Self::Entry)> {
+            Some(("ACME 1.0", &PRIVATE)).filter(|_| name == "AcmeSerial")
+        }
+    }
+
+    #[test]
+    fn merged_dictionary_looks_up_both() {
+        let dict = MergedDictionary::new(PublicDictionary, PrivateDictionary);
+
+        assert_eq!(
+            dict.by_name("PatientName").map(|e| e.tag()),
+            Some(Tag(0x0010, 0x0010))
+        );
+        assert_eq!(
+            dict.by_tag(Tag(0x0010, 0x0010)).map(|e| e.alias()),
+            Some("PatientName")
+        );
+        assert!(dict.by_name("AcmeSerial").is_none());
+
+        assert_eq!(
+            dict.by_private_tag("ACME 1.0", Tag(0x0009, 0x1101))
+                .map(|e| e.alias()),
+            Some("AcmeSerial")
+        );
+        assert!(dict.by_private_tag("OTHER", Tag(0x0009, 0x1001)).is_none());
+        assert_eq!(
+            dict.private_by_name("AcmeSerial").map(|(c, _)| c),
+            Some("ACME 1.0")
+        );
+
+        // precedence goes to the first dictionary
+        let dict = MergedDictionary::new(PrivateDictionary, PublicDictionary);
+        assert_eq!(
+            dict.by_tag(Tag(0x0010, 0x0010)).map(|e| e.alias()),
+            Some("PatientName")
+        );
+        assert_eq!(
+            dict.private_by_name("AcmeSerial").map(|(_, e)| e.tag()),
+            Some(Tag(0x0009, 0x0001))
+        );
+    }
+}
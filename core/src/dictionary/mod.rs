@@ -3,12 +3,14 @@
 //! The standard data dictionary is available in the [`dicom-dictionary-std`] crate.
 
 mod data_element;
+mod merged;
 pub mod stub;
 mod uid;
 
 pub use data_element::{
     DataDictionary, DataDictionaryEntry, DataDictionaryEntryBuf, DataDictionaryEntryRef, TagByName,
-    TagRange, VirtualVr,
+    TagRange, ValueMultiplicity, ValueMultiplicityParseError, VirtualVr,
 };
 
+pub use merged::MergedDictionary;
 pub use uid::{UidDictionary, UidDictionaryEntry, UidDictionaryEntryRef, UidType};
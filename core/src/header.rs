@@ -4,7 +4,7 @@
 
 use crate::value::{
     CastValueError, ConvertValueError, DataSetSequence, DicomDate, DicomDateTime, DicomTime,
-    InMemFragment, PrimitiveValue, Value, C,
+    InMemFragment, MultiConvertError, PrimitiveValue, Value, C,
 };
 use num_traits::NumCast;
 use snafu::{ensure, Backtrace, Snafu};
@@ -451,6 +451,48 @@ where
         self.value().to_multi_float64()
     }
 
+    /// Retrieve and convert the value of the data element
+    /// into a sequence of double-precision floating point numbers,
+    /// reporting which component failed to convert if any.
+    ///
+    /// If the value is a primitive, it will be converted into
+    /// a vector of numbers as described in [`PrimitiveValue::to_multi_f64`].
+    ///
+    /// Returns an error if the value is not primitive.
+    pub fn to_multi_f64(&self) -> Result<Vec<f64>, MultiConvertError> {
+        self.value().to_multi_f64()
+    }
+
+    /// Retrieve and convert the value of the data element
+    /// into a sequence of double-precision floating point numbers,
+    /// substituting components which cannot be converted with NaN.
+    ///
+    /// See [`PrimitiveValue::to_multi_f64_lossy`].
+    pub fn to_multi_f64_lossy(&self) -> Vec<f64> {
+        self.value().to_multi_f64_lossy()
+    }
+
+    /// Retrieve and convert the value of the data element
+    /// into a sequence of 64-bit signed integers,
+    /// reporting which component failed to convert if any.
+    ///
+    /// If the value is a primitive, it will be converted into
+    /// a vector of integers as described in [`PrimitiveValue::to_multi_i64`].
+    ///
+    /// Returns an error if the value is not primitive.
+    pub fn to_multi_i64(&self) -> Result<Vec<i64>, MultiConvertError> {
+        self.value().to_multi_i64()
+    }
+
+    /// Retrieve and convert the value of the data element
+    /// into a sequence of 64-bit signed integers,
+    /// substituting components which cannot be converted with `None`.
+    ///
+    /// See [`PrimitiveValue::to_multi_i64_lossy`].
+    pub fn to_multi_i64_lossy(&self) -> Vec<Option<i64>> {
+        self.value().to_multi_i64_lossy()
+    }
+
     /// Retrieve and convert the primitive value into a date.
     ///
     /// If the value is a primitive, it will be converted into
@@ -631,6 +673,18 @@ impl<I, P> DataElement<I, P> {
         self.value().string()
     }
 
+    /// Get a single string value
+    /// without its trailing padding (space and null characters).
+    ///
+    /// If it contains multiple strings,
+    /// only the first one is returned.
+    /// Leading characters are always kept.
+    ///
+    /// An error is returned if the variant is not compatible.
+    pub fn trimmed_str(&self) -> Result<&str, CastValueError> {
+        self.value().trimmed_str()
+    }
+
     /// Get the inner sequence of string values
     /// if the variant is either `Str` or `Strs`.
     ///
@@ -1,5 +1,6 @@
 //! Helper module for handling pixel encapsulation into fragments
 use crate::value::{InMemFragment, PixelFragmentSequence, C};
+use snafu::Snafu;
 
 /// Represents the fragments of a single frame.
 ///
@@ -125,9 +126,118 @@ impl From<Vec<Fragments>> for PixelFragmentSequence<InMemFragment> {
     }
 }
 
+/// Policy for maintaining the basic offset table of a pixel sequence
+/// when its fragments are modified.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum OffsetTablePolicy {
+    /// Clear the basic offset table,
+    /// so that it is written empty.
+    Invalidate,
+    /// Recompute the basic offset table
+    /// from the lengths of the resulting fragments.
+    ///
+    /// An empty offset table remains empty.
+    #[default]
+    Recompute,
+}
+
+/// A mismatch between the offset table of a pixel sequence
+/// and its fragments.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+#[non_exhaustive]
+pub enum BotMismatch {
+    /// The first offset is not zero
+    #[snafu(display("First offset is {} instead of 0", offset))]
+    FirstOffsetNotZero { offset: u64 },
+    /// An offset is not greater than the previous one
+    #[snafu(display("Offset #{} is not greater than the previous offset", index))]
+    NotIncreasing { index: usize },
+    /// An offset does not point to the start of a fragment
+    #[snafu(display(
+        "Offset #{} ({}) does not point to the start of a fragment",
+        index,
+        offset
+    ))]
+    NotAtFragmentBoundary { index: usize, offset: u64 },
+}
+
+/// Check whether the basic offset table of a pixel sequence
+/// is consistent with its fragments.
+///
+/// Each offset in the table must point to the start of a fragment item,
+/// relative to the first fragment,
+/// the first offset must be zero,
+/// and the offsets must be strictly increasing.
+/// An empty offset table is always consistent.
+pub fn check_offset_table<P>(sequence: &PixelFragmentSequence<P>) -> Result<(), BotMismatch>
+where
+    P: AsRef<[u8]>,
+{
+    if sequence.offset_table.is_empty() {
+        return Ok(());
+    }
+    let offsets: Vec<u64> = sequence
+        .offset_table
+        .iter()
+        .map(|&offset| u64::from(offset))
+        .collect();
+    let fragment_lengths: Vec<u64> = sequence
+        .fragments
+        .iter()
+        .map(|fragment| fragment.as_ref().len() as u64)
+        .collect();
+    frame_fragment_indices(&offsets, &fragment_lengths).map(|_| ())
+}
+
+/// The length of a fragment item in bytes,
+/// including the item header.
+pub(crate) fn fragment_item_len<P>(fragment: &P) -> u64
+where
+    P: AsRef<[u8]>,
+{
+    8 + fragment.as_ref().len() as u64
+}
+
+/// Obtain the index of the first fragment of each frame
+/// according to the given offset table,
+/// be it a basic or an extended offset table,
+/// and the length of each fragment (excluding its item header).
+///
+/// The same rules as in [`check_offset_table`] apply,
+/// except that an empty offset table yields no frames.
+pub fn frame_fragment_indices(
+    offsets: &[u64],
+    fragment_lengths: &[u64],
+) -> Result<Vec<usize>, BotMismatch> {
+    // the offset of each fragment item relative to the first one
+    let fragment_offsets: Vec<u64> = fragment_lengths
+        .iter()
+        .scan(0_u64, |position, len| {
+            let current = *position;
+            *position += 8 + len;
+            Some(current)
+        })
+        .collect();
+
+    let mut indices = Vec::with_capacity(offsets.len());
+    for (index, &offset) in offsets.iter().enumerate() {
+        if index == 0 && offset != 0 {
+            return FirstOffsetNotZeroSnafu { offset }.fail();
+        }
+        if index > 0 && offset <= offsets[index - 1] {
+            return NotIncreasingSnafu { index }.fail();
+        }
+        let fragment_index = fragment_offsets
+            .binary_search(&offset)
+            .map_err(|_| BotMismatch::NotAtFragmentBoundary { index, offset })?;
+        indices.push(fragment_index);
+    }
+    Ok(indices)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::value::fragments::Fragments;
+    use crate::value::fragments::{check_offset_table, BotMismatch, Fragments, OffsetTablePolicy};
     use crate::value::{InMemFragment, PixelFragmentSequence};
 
     #[test]
@@ -215,4 +325,103 @@ mod tests {
         assert_eq!(fragment_sequence.offset_table[0], 0);
         assert_eq!(fragment_sequence.offset_table[1], 12); // 8 separator bytes + 4 data bytes
     }
+
+    #[test]
+    fn test_check_offset_table() {
+        // fragments with item lengths 12, 14 and 10
+        let fragments: Vec<InMemFragment> = vec![vec![0; 4], vec![1; 6], vec![2; 2]];
+
+        let seq = PixelFragmentSequence::new(vec![], fragments.clone());
+        assert_eq!(check_offset_table(&seq), Ok(()));
+        let seq = PixelFragmentSequence::new(vec![0, 12, 26], fragments.clone());
+        assert_eq!(check_offset_table(&seq), Ok(()));
+        // frames of more than one fragment
+        let seq = PixelFragmentSequence::new(vec![0, 26], fragments.clone());
+        assert_eq!(check_offset_table(&seq), Ok(()));
+
+        let seq = PixelFragmentSequence::new(vec![12, 26], fragments.clone());
+        assert_eq!(
+            check_offset_table(&seq),
+            Err(BotMismatch::FirstOffsetNotZero { offset: 12 })
+        );
+        let seq = PixelFragmentSequence::new(vec![0, 26, 12], fragments.clone());
+        assert_eq!(
+            check_offset_table(&seq),
+            Err(BotMismatch::NotIncreasing { index: 2 })
+        );
+        let seq = PixelFragmentSequence::new(vec![0, 16], fragments.clone());
+        assert_eq!(
+            check_offset_table(&seq),
+            Err(BotMismatch::NotAtFragmentBoundary {
+                index: 1,
+                offset: 16
+            })
+        );
+        // past the last fragment
+        let seq = PixelFragmentSequence::new(vec![0, 36], fragments);
+        assert_eq!(
+            check_offset_table(&seq),
+            Err(BotMismatch::NotAtFragmentBoundary {
+                index: 1,
+                offset: 36
+            })
+        );
+    }
+
+    #[test]
+    fn test_push_fragment() {
+        let mut seq: PixelFragmentSequence<InMemFragment> =
+            PixelFragmentSequence::new(vec![0], vec![vec![0; 4]]);
+        seq.push_fragment(vec![1; 6], OffsetTablePolicy::Recompute);
+        assert_eq!(seq.offset_table(), &[0, 12]);
+        seq.push_fragment(vec![2; 2], OffsetTablePolicy::Recompute);
+        assert_eq!(seq.offset_table(), &[0, 12, 26]);
+        assert_eq!(seq.fragments().len(), 3);
+        assert_eq!(check_offset_table(&seq), Ok(()));
+
+        seq.push_fragment(vec![3; 2], OffsetTablePolicy::Invalidate);
+        assert!(seq.offset_table().is_empty());
+        assert_eq!(seq.fragments().len(), 4);
+
+        // an empty offset table remains empty
+        seq.push_fragment(vec![4; 2], OffsetTablePolicy::Recompute);
+        assert!(seq.offset_table().is_empty());
+        assert_eq!(seq.fragments().len(), 5);
+    }
+
+    #[test]
+    fn test_remove_frame() {
+        // 3 frames, the second one in two fragments
+        let fragments: Vec<InMemFragment> = vec![vec![0; 4], vec![1; 6], vec![1; 2], vec![2; 2]];
+        let mut seq = PixelFragmentSequence::new(vec![0, 12, 36], fragments.clone());
+
+        let removed = seq.remove_frame(1, OffsetTablePolicy::Recompute);
+        assert_eq!(removed, Some(vec![vec![1; 6], vec![1; 2]]));
+        assert_eq!(seq.offset_table(), &[0, 12]);
+        assert_eq!(seq.fragments(), &[vec![0; 4], vec![2; 2]]);
+        assert_eq!(check_offset_table(&seq), Ok(()));
+
+        let removed = seq.remove_frame(0, OffsetTablePolicy::Recompute);
+        assert_eq!(removed, Some(vec![vec![0; 4]]));
+        assert_eq!(seq.offset_table(), &[0]);
+        assert_eq!(seq.remove_frame(1, OffsetTablePolicy::Recompute), None);
+
+        let mut seq = PixelFragmentSequence::new(vec![0, 12, 36], fragments.clone());
+        let removed = seq.remove_frame(2, OffsetTablePolicy::Invalidate);
+        assert_eq!(removed, Some(vec![vec![2; 2]]));
+        assert!(seq.offset_table().is_empty());
+        assert_eq!(seq.fragments().len(), 3);
+
+        // without an offset table, each fragment is a frame
+        let mut seq = PixelFragmentSequence::new(vec![], fragments.clone());
+        let removed = seq.remove_frame(1, OffsetTablePolicy::Recompute);
+        assert_eq!(removed, Some(vec![vec![1; 6]]));
+        assert!(seq.offset_table().is_empty());
+        assert_eq!(seq.fragments().len(), 3);
+
+        // frames cannot be identified through an inconsistent table
+        let mut seq = PixelFragmentSequence::new(vec![0, 16], fragments);
+        assert_eq!(seq.remove_frame(0, OffsetTablePolicy::Recompute), None);
+        assert_eq!(seq.fragments().len(), 4);
+    }
 }
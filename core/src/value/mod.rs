@@ -3,7 +3,7 @@
 use crate::header::{EmptyObject, HasLength, Length, Tag};
 use num_traits::NumCast;
 use smallvec::SmallVec;
-use std::{borrow::Cow, str::FromStr};
+use std::{borrow::Cow, convert::TryFrom, str::FromStr};
 
 pub mod deserialize;
 pub mod fragments;
@@ -14,13 +14,16 @@ pub mod range;
 pub mod serialize;
 
 pub use self::deserialize::Error as DeserializeError;
+pub use self::fragments::{
+    check_offset_table, frame_fragment_indices, BotMismatch, OffsetTablePolicy,
+};
 pub use self::partial::{DicomDate, DicomDateTime, DicomTime, PreciseDateTime};
 pub use self::person_name::PersonName;
 pub use self::range::{AsRange, DateRange, DateTimeRange, TimeRange};
 
 pub use self::primitive::{
-    CastValueError, ConvertValueError, InvalidValueReadError, ModifyValueError, PrimitiveValue,
-    ValueType,
+    CastValueError, ConvertValueError, InvalidValueReadError, ModifyValueError,
+    MultiConvertError, PrimitiveValue, ValueType,
 };
 
 /// An aggregation of one or more elements in a value.
@@ -29,6 +32,22 @@ pub type C<T> = SmallVec<[T; 2]>;
 /// Type alias for the in-memory pixel data fragment data.
 pub type InMemFragment = Vec<u8>;
 
+/// Remove the trailing padding of a textual value,
+/// namely any trailing space and null characters.
+///
+/// Leading characters are always kept.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::value::trim_padding;
+/// assert_eq!(trim_padding("1.2.840.10008.1.2.1\0"), "1.2.840.10008.1.2.1");
+/// assert_eq!(trim_padding(" RGB "), " RGB");
+/// ```
+pub fn trim_padding(s: &str) -> &str {
+    s.trim_end_matches([' ', '\0'])
+}
+
 /// A trait for a value that maps to a DICOM element data value.
 pub trait DicomValueType: HasLength {
     /// Retrieve the specific type of this value.
@@ -247,6 +266,28 @@ impl<I, P> Value<I, P> {
         }
     }
 
+    /// Gets a reference to the pixel data sequence.
+    ///
+    /// Returns `None` if the value is not a pixel data sequence.
+    pub fn pixel_sequence(&self) -> Option<&PixelFragmentSequence<P>> {
+        match self {
+            Value::PixelSequence(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Gets a mutable reference to the pixel data sequence,
+    /// so that fragments can be added or removed
+    /// while keeping the basic offset table consistent.
+    ///
+    /// Returns `None` if the value is not a pixel data sequence.
+    pub fn pixel_sequence_mut(&mut self) -> Option<&mut PixelFragmentSequence<P>> {
+        match self {
+            Value::PixelSequence(v) => Some(v),
+            _ => None,
+        }
+    }
+
     /// Shorten this value by removing trailing elements
     /// to fit the given limit.
     ///
@@ -527,6 +568,72 @@ where
         }
     }
 
+    /// Retrieve and convert the primitive value
+    /// into a sequence of double-precision floating point numbers,
+    /// reporting which component failed to convert if any.
+    ///
+    /// If the value is a primitive, it will be converted into
+    /// a vector of numbers as described in [`PrimitiveValue::to_multi_f64`].
+    pub fn to_multi_f64(&self) -> Result<Vec<f64>, MultiConvertError> {
+        match self {
+            Value::Primitive(v) => v.to_multi_f64(),
+            _ => Err(MultiConvertError {
+                requested: "float64",
+                original: self.value_type(),
+                index: 0,
+                value: String::new(),
+                cause: None,
+            }),
+        }
+    }
+
+    /// Retrieve and convert the primitive value
+    /// into a sequence of double-precision floating point numbers,
+    /// substituting components which cannot be converted with NaN.
+    ///
+    /// If the value is a primitive, it will be converted into
+    /// a vector of numbers as described in [`PrimitiveValue::to_multi_f64_lossy`].
+    /// Otherwise, an empty vector is returned.
+    pub fn to_multi_f64_lossy(&self) -> Vec<f64> {
+        match self {
+            Value::Primitive(v) => v.to_multi_f64_lossy(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Retrieve and convert the primitive value
+    /// into a sequence of 64-bit signed integers,
+    /// reporting which component failed to convert if any.
+    ///
+    /// If the value is a primitive, it will be converted into
+    /// a vector of integers as described in [`PrimitiveValue::to_multi_i64`].
+    pub fn to_multi_i64(&self) -> Result<Vec<i64>, MultiConvertError> {
+        match self {
+            Value::Primitive(v) => v.to_multi_i64(),
+            _ => Err(MultiConvertError {
+                requested: "integer",
+                original: self.value_type(),
+                index: 0,
+                value: String::new(),
+                cause: None,
+            }),
+        }
+    }
+
+    /// Retrieve and convert the primitive value
+    /// into a sequence of 64-bit signed integers,
+    /// substituting components which cannot be converted with `None`.
+    ///
+    /// If the value is a primitive, it will be converted into
+    /// a vector of integers as described in [`PrimitiveValue::to_multi_i64_lossy`].
+    /// Otherwise, an empty vector is returned.
+    pub fn to_multi_i64_lossy(&self) -> Vec<Option<i64>> {
+        match self {
+            Value::Primitive(v) => v.to_multi_i64_lossy(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Retrieve and convert the primitive value into a `DicomDate`.
     ///
     /// If the value is a primitive, it will be converted into
@@ -753,6 +860,18 @@ impl<I, P> Value<I, P> {
         }
     }
 
+    /// Get a single string value
+    /// without its trailing padding (space and null characters).
+    ///
+    /// If it contains multiple strings,
+    /// only the first one is returned.
+    /// Leading characters are always kept.
+    ///
+    /// An error is returned if the variant is not compatible.
+    pub fn trimmed_str(&self) -> Result<&str, CastValueError> {
+        self.string().map(trim_padding)
+    }
+
     /// Get the inner sequence of string values
     /// if the variant is either `Str` or `Strs`.
     ///
@@ -1058,6 +1177,79 @@ impl<P> PixelFragmentSequence<P> {
     }
 }
 
+impl<P> PixelFragmentSequence<P>
+where
+    P: AsRef<[u8]>,
+{
+    /// Append a fragment containing a new frame,
+    /// maintaining the basic offset table according to the given policy.
+    ///
+    /// If the offset table was inconsistent with the fragments,
+    /// it is cleared regardless of the policy.
+    pub fn push_fragment(&mut self, fragment: P, policy: OffsetTablePolicy) {
+        let recompute = policy == OffsetTablePolicy::Recompute
+            && !self.offset_table.is_empty()
+            && fragments::check_offset_table(self).is_ok();
+        if recompute {
+            let offset: u64 = self
+                .fragments
+                .iter()
+                .map(fragments::fragment_item_len)
+                .sum();
+            match u32::try_from(offset) {
+                Ok(offset) => self.offset_table.push(offset),
+                Err(_) => self.offset_table.clear(),
+            }
+        } else {
+            self.offset_table.clear();
+        }
+        self.fragments.push(fragment);
+    }
+
+    /// Remove all fragments of the frame at the given index,
+    /// maintaining the basic offset table according to the given policy.
+    ///
+    /// Frames are identified through the basic offset table,
+    /// or as one fragment per frame if the table is empty.
+    /// Returns the fragments removed,
+    /// or `None` if the frame does not exist
+    /// or the offset table is inconsistent with the fragments.
+    pub fn remove_frame(&mut self, index: usize, policy: OffsetTablePolicy) -> Option<Vec<P>> {
+        let starts = if self.offset_table.is_empty() {
+            (0..self.fragments.len()).collect()
+        } else {
+            let offsets: Vec<u64> = self.offset_table.iter().map(|&o| u64::from(o)).collect();
+            let fragment_lengths: Vec<u64> = self
+                .fragments
+                .iter()
+                .map(|fragment| fragment.as_ref().len() as u64)
+                .collect();
+            fragments::frame_fragment_indices(&offsets, &fragment_lengths).ok()?
+        };
+        let start = *starts.get(index)?;
+        let end = starts
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.fragments.len());
+        let removed: Vec<P> = self.fragments.drain(start..end).collect();
+
+        match policy {
+            OffsetTablePolicy::Invalidate => self.offset_table.clear(),
+            OffsetTablePolicy::Recompute if !self.offset_table.is_empty() => {
+                let removed_len: u64 = removed.iter().map(fragments::fragment_item_len).sum();
+                self.offset_table.remove(index);
+                for offset in &mut self.offset_table[index..] {
+                    // the offsets of the following frames
+                    // are greater than the removed length
+                    *offset -= removed_len as u32;
+                }
+            }
+            OffsetTablePolicy::Recompute => {}
+        }
+        Some(removed)
+    }
+}
+
 impl<T, F, P> From<(T, F)> for PixelFragmentSequence<P>
 where
     T: Into<C<u32>>,
@@ -1422,4 +1614,23 @@ mod tests {
         let fragments = v.into_fragments().unwrap();
         assert_eq!(&fragments[..], &[vec![0x55; 128]]);
     }
+
+    #[test]
+    fn trimmed_str() {
+        // trailing null character in a UI value
+        let value: Value = dicom_value!(Str, "1.2.840.10008.1.2.1\0").into();
+        assert_eq!(value.trimmed_str().unwrap(), "1.2.840.10008.1.2.1");
+
+        // trailing space in a CS value
+        let value: Value = dicom_value!(Strs, ["RGB ", "OTHER "]).into();
+        assert_eq!(value.trimmed_str().unwrap(), "RGB");
+
+        // leading spaces are kept
+        let value: Value = dicom_value!(Str, "  MONOCHROME2 \0").into();
+        assert_eq!(value.trimmed_str().unwrap(), "  MONOCHROME2");
+
+        // only textual values are accepted
+        let value: Value = dicom_value!(U16, [1]).into();
+        assert!(value.trimmed_str().is_err());
+    }
 }
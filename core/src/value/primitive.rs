@@ -179,6 +179,54 @@ impl std::error::Error for ConvertValueError {
     }
 }
 
+/// An error type for a failed attempt at converting
+/// each value of a multi-valued primitive into a number.
+///
+/// Unlike [`ConvertValueError`],
+/// this error identifies the first component which could not be converted.
+#[derive(Debug)]
+pub struct MultiConvertError {
+    /// The value format requested
+    pub requested: &'static str,
+    /// The value's original representation
+    pub original: ValueType,
+    /// The index of the first component which could not be converted
+    pub index: usize,
+    /// The offending component in text form
+    /// (empty if it cannot be represented as text)
+    pub value: String,
+    /// The reason why the conversion was unsuccessful,
+    /// or none if a conversion from the given original representation
+    /// is not possible
+    pub cause: Option<Box<InvalidValueReadError>>,
+}
+
+impl Display for MultiConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "could not convert component #{} `{}` of {:?} to a {}: ",
+            self.index, self.value, self.original, self.requested
+        )?;
+        if let Some(cause) = &self.cause {
+            write!(f, "{}", cause)?;
+        } else {
+            write!(f, "conversion not possible")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MultiConvertError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause.as_ref().map(|x| x as _)
+    }
+}
+
+/// The outcome of converting a single component of a multi-valued primitive,
+/// keeping the offending text and cause on failure.
+type ComponentResult<T> = std::result::Result<T, (String, Option<Box<InvalidValueReadError>>)>;
+
 pub type Result<T, E = InvalidValueReadError> = std::result::Result<T, E>;
 
 // Re-exported from chrono
@@ -1951,6 +1999,205 @@ impl PrimitiveValue {
         }
     }
 
+    /// Retrieve a sequence of double-precision floating point numbers
+    /// from this value,
+    /// reporting which component failed to convert if any.
+    ///
+    /// Text values are parsed component by component,
+    /// with leading and trailing whitespace and null characters ignored,
+    /// same as in [`to_float64`](Self::to_float64).
+    /// Numbers are converted to `f64`.
+    ///
+    /// See [`to_multi_f64_lossy`](Self::to_multi_f64_lossy)
+    /// for a conversion which does not stop at the first bad component.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::dicom_value;
+    /// assert_eq!(
+    ///     dicom_value!(Strs, ["1.0", " -2.5", "0 "]).to_multi_f64().ok(),
+    ///     Some(vec![1., -2.5, 0.]),
+    /// );
+    ///
+    /// let err = dicom_value!(Strs, ["1.0", "abc", "0.0"])
+    ///     .to_multi_f64()
+    ///     .unwrap_err();
+    /// assert_eq!(err.index, 1);
+    /// assert_eq!(err.value, "abc");
+    /// ```
+    pub fn to_multi_f64(&self) -> Result<Vec<f64>, MultiConvertError> {
+        self.f64_components()
+            .into_iter()
+            .enumerate()
+            .map(|(index, component)| {
+                component.map_err(|(value, cause)| MultiConvertError {
+                    requested: "float64",
+                    original: self.value_type(),
+                    index,
+                    value,
+                    cause,
+                })
+            })
+            .collect()
+    }
+
+    /// Retrieve a sequence of double-precision floating point numbers
+    /// from this value,
+    /// substituting components which cannot be converted with NaN.
+    ///
+    /// Conversion follows the same rules as
+    /// [`to_multi_f64`](Self::to_multi_f64).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::dicom_value;
+    /// let values = dicom_value!(Strs, ["1.0", "abc", "0.0"]).to_multi_f64_lossy();
+    /// assert_eq!(values.len(), 3);
+    /// assert_eq!(values[0], 1.);
+    /// assert!(values[1].is_nan());
+    /// assert_eq!(values[2], 0.);
+    /// ```
+    pub fn to_multi_f64_lossy(&self) -> Vec<f64> {
+        self.f64_components()
+            .into_iter()
+            .map(|component| component.unwrap_or(f64::NAN))
+            .collect()
+    }
+
+    /// Retrieve a sequence of 64-bit signed integers from this value,
+    /// reporting which component failed to convert if any.
+    ///
+    /// Text values are parsed component by component,
+    /// with leading and trailing whitespace and null characters ignored,
+    /// same as in [`to_int`](Self::to_int).
+    /// Integers are converted to `i64` if they fit.
+    /// Floating point numbers are not truncated into integers,
+    /// resulting in an error instead.
+    ///
+    /// See [`to_multi_i64_lossy`](Self::to_multi_i64_lossy)
+    /// for a conversion which does not stop at the first bad component.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::dicom_value;
+    /// assert_eq!(
+    ///     dicom_value!(Strs, ["5050", "-23 "]).to_multi_i64().ok(),
+    ///     Some(vec![5050, -23]),
+    /// );
+    ///
+    /// let err = dicom_value!(Strs, ["1", "2", "3.5"])
+    ///     .to_multi_i64()
+    ///     .unwrap_err();
+    /// assert_eq!(err.index, 2);
+    /// assert_eq!(err.value, "3.5");
+    /// ```
+    pub fn to_multi_i64(&self) -> Result<Vec<i64>, MultiConvertError> {
+        self.i64_components()
+            .into_iter()
+            .enumerate()
+            .map(|(index, component)| {
+                component.map_err(|(value, cause)| MultiConvertError {
+                    requested: "integer",
+                    original: self.value_type(),
+                    index,
+                    value,
+                    cause,
+                })
+            })
+            .collect()
+    }
+
+    /// Retrieve a sequence of 64-bit signed integers from this value,
+    /// substituting components which cannot be converted with `None`.
+    ///
+    /// Conversion follows the same rules as
+    /// [`to_multi_i64`](Self::to_multi_i64).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::dicom_value;
+    /// assert_eq!(
+    ///     dicom_value!(Strs, ["1", "x", "3"]).to_multi_i64_lossy(),
+    ///     vec![Some(1), None, Some(3)],
+    /// );
+    /// ```
+    pub fn to_multi_i64_lossy(&self) -> Vec<Option<i64>> {
+        self.i64_components()
+            .into_iter()
+            .map(|component| component.ok())
+            .collect()
+    }
+
+    /// Convert each component of this value into an `f64`.
+    fn f64_components(&self) -> Vec<ComponentResult<f64>> {
+        fn parse(s: &str) -> ComponentResult<f64> {
+            s.trim_matches(whitespace_or_null)
+                .parse()
+                .context(ParseFloatSnafu)
+                .map_err(|err| (s.to_string(), Some(Box::from(err))))
+        }
+
+        match self {
+            PrimitiveValue::Empty => Vec::new(),
+            PrimitiveValue::Str(s) => vec![parse(s)],
+            PrimitiveValue::Strs(s) => s.iter().map(|v| parse(v)).collect(),
+            PrimitiveValue::U8(v) => v.iter().map(|v| Ok(f64::from(*v))).collect(),
+            PrimitiveValue::I16(v) => v.iter().map(|v| Ok(f64::from(*v))).collect(),
+            PrimitiveValue::U16(v) => v.iter().map(|v| Ok(f64::from(*v))).collect(),
+            PrimitiveValue::I32(v) => v.iter().map(|v| Ok(f64::from(*v))).collect(),
+            PrimitiveValue::U32(v) => v.iter().map(|v| Ok(f64::from(*v))).collect(),
+            PrimitiveValue::I64(v) => v.iter().map(|v| Ok(*v as f64)).collect(),
+            PrimitiveValue::U64(v) => v.iter().map(|v| Ok(*v as f64)).collect(),
+            PrimitiveValue::F32(v) => v.iter().map(|v| Ok(f64::from(*v))).collect(),
+            PrimitiveValue::F64(v) => v.iter().map(|v| Ok(*v)).collect(),
+            _ => vec![Err((self.to_str().into_owned(), None))],
+        }
+    }
+
+    /// Convert each component of this value into an `i64`.
+    fn i64_components(&self) -> Vec<ComponentResult<i64>> {
+        use std::convert::TryFrom;
+
+        fn parse(s: &str) -> ComponentResult<i64> {
+            s.trim_matches(whitespace_or_null)
+                .parse()
+                .context(ParseIntegerSnafu)
+                .map_err(|err| (s.to_string(), Some(Box::from(err))))
+        }
+
+        match self {
+            PrimitiveValue::Empty => Vec::new(),
+            PrimitiveValue::Str(s) => vec![parse(s)],
+            PrimitiveValue::Strs(s) => s.iter().map(|v| parse(v)).collect(),
+            PrimitiveValue::U8(v) => v.iter().map(|v| Ok(i64::from(*v))).collect(),
+            PrimitiveValue::I16(v) => v.iter().map(|v| Ok(i64::from(*v))).collect(),
+            PrimitiveValue::U16(v) => v.iter().map(|v| Ok(i64::from(*v))).collect(),
+            PrimitiveValue::I32(v) => v.iter().map(|v| Ok(i64::from(*v))).collect(),
+            PrimitiveValue::U32(v) => v.iter().map(|v| Ok(i64::from(*v))).collect(),
+            PrimitiveValue::I64(v) => v.iter().map(|v| Ok(*v)).collect(),
+            PrimitiveValue::U64(v) => v
+                .iter()
+                .map(|v| {
+                    i64::try_from(*v).map_err(|_| {
+                        let value = v.to_string();
+                        let cause = NarrowConvertSnafu {
+                            value: value.clone(),
+                        }
+                        .build();
+                        (value, Some(Box::from(cause)))
+                    })
+                })
+                .collect(),
+            PrimitiveValue::F32(v) => v.iter().map(|v| Err((v.to_string(), None))).collect(),
+            PrimitiveValue::F64(v) => v.iter().map(|v| Err((v.to_string(), None))).collect(),
+            _ => vec![Err((self.to_str().into_owned(), None))],
+        }
+    }
+
     /// Retrieve a single `chrono::NaiveDate` from this value.
     ///
     /// Please note, that this is a shortcut to obtain a usable date from a primitive value.
@@ -3352,6 +3599,18 @@ impl PrimitiveValue {
         }
     }
 
+    /// Get a single string value
+    /// without its trailing padding (space and null characters).
+    ///
+    /// If it contains multiple strings,
+    /// only the first one is returned.
+    /// Leading characters are always kept.
+    ///
+    /// An error is returned if the variant is not compatible.
+    pub fn trimmed_str(&self) -> Result<&str, CastValueError> {
+        self.string().map(super::trim_padding)
+    }
+
     /// Get the inner sequence of string values
     /// if the variant is either `Str` or `Strs`.
     ///
@@ -4638,6 +4897,115 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn primitive_value_to_multi_f64() {
+        assert_eq!(
+            PrimitiveValue::Empty.to_multi_f64().unwrap(),
+            Vec::<f64>::new()
+        );
+
+        // clean values
+        assert_eq!(
+            dicom_value!(Strs, ["1.0", "0.0", "-0.5", "0", "1", "2.5e1"])
+                .to_multi_f64()
+                .unwrap(),
+            vec![1., 0., -0.5, 0., 1., 25.],
+        );
+        assert_eq!(
+            dicom_value!(F32, [1.5, 2.]).to_multi_f64().unwrap(),
+            vec![1.5, 2.],
+        );
+        assert_eq!(
+            dicom_value!(U16, [1, 2, 3]).to_multi_f64().unwrap(),
+            vec![1., 2., 3.],
+        );
+
+        // padded values, same as in single value conversion
+        let value = dicom_value!(Strs, [" 1.0", "2.0 ", "3.0\0"]);
+        assert_eq!(value.to_multi_f64().unwrap(), vec![1., 2., 3.]);
+        assert_eq!(value.to_float64().unwrap(), 1.);
+        assert_eq!(
+            PrimitiveValue::from("-6.75 \0").to_multi_f64().unwrap(),
+            vec![-6.75]
+        );
+
+        // partially corrupt values
+        let value = dicom_value!(Strs, ["1.0", "abc", "0.0", "x"]);
+        let err = value.to_multi_f64().unwrap_err();
+        assert_eq!(err.requested, "float64");
+        assert_eq!(err.original, ValueType::Strs);
+        assert_eq!(err.index, 1);
+        assert_eq!(err.value, "abc");
+        assert!(matches!(
+            err.cause.as_deref(),
+            Some(InvalidValueReadError::ParseFloat { .. })
+        ));
+
+        let values = value.to_multi_f64_lossy();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values[0], 1.);
+        assert!(values[1].is_nan());
+        assert_eq!(values[2], 0.);
+        assert!(values[3].is_nan());
+
+        // values which cannot be numbers at all
+        let err = PrimitiveValue::Date(smallvec![DicomDate::from_ymd(2021, 1, 1).unwrap()])
+            .to_multi_f64()
+            .unwrap_err();
+        assert_eq!(err.index, 0);
+        assert!(err.cause.is_none());
+    }
+
+    #[test]
+    fn primitive_value_to_multi_i64() {
+        assert_eq!(
+            PrimitiveValue::Empty.to_multi_i64().unwrap(),
+            Vec::<i64>::new()
+        );
+
+        // clean values
+        assert_eq!(
+            dicom_value!(Strs, ["5050", "-23", "0"])
+                .to_multi_i64()
+                .unwrap(),
+            vec![5050, -23, 0],
+        );
+        assert_eq!(
+            dicom_value!(I32, [1, -2, 3]).to_multi_i64().unwrap(),
+            vec![1, -2, 3],
+        );
+
+        // padded values, same as in single value conversion
+        let value = dicom_value!(Strs, [" 16", "32 ", "64\0"]);
+        assert_eq!(value.to_multi_i64().unwrap(), vec![16, 32, 64]);
+        assert_eq!(value.to_int::<i64>().unwrap(), 16);
+
+        // partially corrupt values
+        let value = dicom_value!(Strs, ["1", "2", "3.5", "zz"]);
+        let err = value.to_multi_i64().unwrap_err();
+        assert_eq!(err.requested, "integer");
+        assert_eq!(err.index, 2);
+        assert_eq!(err.value, "3.5");
+        assert!(matches!(
+            err.cause.as_deref(),
+            Some(InvalidValueReadError::ParseInteger { .. })
+        ));
+        assert_eq!(
+            value.to_multi_i64_lossy(),
+            vec![Some(1), Some(2), None, None]
+        );
+
+        // out of range
+        let err = dicom_value!(U64, [1, u64::MAX]).to_multi_i64().unwrap_err();
+        assert_eq!(err.index, 1);
+        assert_eq!(err.value, u64::MAX.to_string());
+
+        // floats are not truncated
+        let err = dicom_value!(F64, [1.5]).to_multi_i64().unwrap_err();
+        assert_eq!(err.index, 0);
+        assert!(err.cause.is_none());
+    }
+
     #[test]
     fn primitive_value_to_naive_date() {
         // to NaiveDate
@@ -215,7 +215,9 @@ where
 
     f.write_all(
         b"\n\
-    use dicom_core::dictionary::{DataDictionaryEntryRef, TagRange, TagRange::*, VirtualVr::*};\n\
+    use dicom_core::dictionary::{\n    \
+    DataDictionaryEntryRef, TagRange, TagRange::*, ValueMultiplicity::*, VirtualVr::*,\n\
+    };\n\
     use dicom_core::Tag;\n\
     use dicom_core::VR::*;\n\n",
     )?;
@@ -287,11 +289,33 @@ where
 
         writeln!(
             f,
-            "    E {{ tag: {}, alias: \"{}\", vr: {}{}{} }}, // {}",
-            tag_set, e.alias, vr1, vr2, vr3, e.obs
+            "    E {{ tag: {}, alias: \"{}\", vr: {}{}{}, vm: Some({}) }}, // {}",
+            tag_set,
+            e.alias,
+            vr1,
+            vr2,
+            vr3,
+            vm_declaration(&e.vm),
+            e.obs
         )?;
     }
     f.write_all(b"];\n")?;
 
     Ok(())
 }
+
+/// Map a value multiplicity from the standard (e.g. `1`, `1-3`, `2-2n`)
+/// to its `ValueMultiplicity` declaration.
+fn vm_declaration(vm: &str) -> String {
+    let Some((min, max)) = vm.split_once('-') else {
+        return format!("Exactly({})", vm);
+    };
+    if max == "n" {
+        format!("AtLeast({})", min)
+    } else if let Some(k) = max.strip_suffix('n') {
+        assert_eq!(k, min, "unsupported value multiplicity: {}", vm);
+        format!("MultipleOf({})", k)
+    } else {
+        format!("Between({}, {})", min, max)
+    }
+}
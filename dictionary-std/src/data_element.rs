@@ -1,7 +1,9 @@
 //! Data element dictionary implementation
 
 use crate::tags::ENTRIES;
-use dicom_core::dictionary::{DataDictionary, DataDictionaryEntryRef, TagRange::*, VirtualVr};
+use dicom_core::dictionary::{
+    DataDictionary, DataDictionaryEntryRef, TagRange::*, ValueMultiplicity, VirtualVr,
+};
 use dicom_core::header::Tag;
 use dicom_core::VR;
 use once_cell::sync::Lazy;
@@ -70,6 +72,7 @@ static GROUP_LENGTH_ENTRY: DataDictionaryEntryRef<'static> = DataDictionaryEntry
     tag: GroupLength,
     alias: "GenericGroupLength",
     vr: VirtualVr::Exact(VR::UL),
+    vm: Some(ValueMultiplicity::Exactly(1)),
 };
 
 /// Generic Private Creator dictionary entry.
@@ -77,6 +80,7 @@ static PRIVATE_CREATOR_ENTRY: DataDictionaryEntryRef<'static> = DataDictionaryEn
     tag: PrivateCreator,
     alias: "PrivateCreator",
     vr: VirtualVr::Exact(VR::LO),
+    vm: Some(ValueMultiplicity::Exactly(1)),
 };
 
 /// A data element dictionary which consults
@@ -172,7 +176,9 @@ mod tests {
     use crate::tags;
 
     use super::StandardDataDictionary;
-    use dicom_core::dictionary::{DataDictionary, DataDictionaryEntryRef, TagRange::*, VirtualVr};
+    use dicom_core::dictionary::{
+        DataDictionary, DataDictionaryEntryRef, TagRange::*, ValueMultiplicity, VirtualVr,
+    };
     use dicom_core::header::{Tag, VR};
     use dicom_core::ops::AttributeSelector;
 
@@ -188,6 +194,7 @@ mod tests {
                 tag: Single(Tag(0x0010, 0x0010)),
                 alias: "PatientName",
                 vr: VR::PN.into(),
+                vm: Some(ValueMultiplicity::Exactly(1)),
             })
         );
 
@@ -197,6 +204,7 @@ mod tests {
                 tag: Single(Tag(0x0008, 0x0060)),
                 alias: "Modality",
                 vr: VR::CS.into(),
+                vm: Some(ValueMultiplicity::Exactly(1)),
             })
         );
 
@@ -222,6 +230,19 @@ mod tests {
         assert_eq!(overlay_data.tag, Group100(Tag(0x6000, 0x3000)));
         assert_eq!(overlay_data.alias, "OverlayData");
         assert!(overlay_data.vr == VirtualVr::Ox);
+
+        // value multiplicity
+        let image_type = dict
+            .by_name("ImageType")
+            .expect("Image Type attribute should exist");
+        assert_eq!(image_type.vm, Some(ValueMultiplicity::AtLeast(2)));
+        let calculated_frame_list = dict
+            .by_tag(Tag(0x0008, 0x1162))
+            .expect("Calculated Frame List attribute should exist");
+        assert_eq!(
+            calculated_frame_list.vm,
+            Some(ValueMultiplicity::MultipleOf(3))
+        );
     }
 
     #[test]
@@ -251,6 +272,7 @@ mod tests {
                 tag: Single(crate::tags::PATIENT_NAME),
                 alias: "PatientName",
                 vr: VR::PN.into(),
+                vm: Some(ValueMultiplicity::Exactly(1)),
             })
         );
 
@@ -260,6 +282,7 @@ mod tests {
                 tag: Single(crate::tags::MODALITY),
                 alias: "Modality",
                 vr: VR::CS.into(),
+                vm: Some(ValueMultiplicity::Exactly(1)),
             })
         );
 
@@ -269,6 +292,7 @@ mod tests {
                 tag: Single(crate::tags::OPERATORS_NAME),
                 alias: "OperatorsName",
                 vr: VR::PN.into(),
+                vm: Some(ValueMultiplicity::Exactly(1)),
             })
         );
 
@@ -292,6 +316,7 @@ mod tests {
                 tag: Single(FILE_META_INFORMATION_GROUP_LENGTH),
                 alias: "FileMetaInformationGroupLength",
                 vr: VR::UL.into(),
+                vm: Some(ValueMultiplicity::Exactly(1)),
             }),
         );
 
@@ -301,6 +326,7 @@ mod tests {
                 tag: Single(COMMAND_GROUP_LENGTH),
                 alias: "CommandGroupLength",
                 vr: VR::UL.into(),
+                vm: Some(ValueMultiplicity::Exactly(1)),
             }),
         );
 
@@ -312,6 +338,7 @@ mod tests {
                 tag: GroupLength,
                 alias: "GenericGroupLength",
                 vr: VR::UL.into(),
+                vm: Some(ValueMultiplicity::Exactly(1)),
             }),
         );
 
@@ -321,6 +348,7 @@ mod tests {
                 tag: GroupLength,
                 alias: "GenericGroupLength",
                 vr: VR::UL.into(),
+                vm: Some(ValueMultiplicity::Exactly(1)),
             }),
         );
     }
@@ -333,6 +361,7 @@ mod tests {
             tag: PrivateCreator,
             alias: "PrivateCreator",
             vr: VR::LO.into(),
+            vm: Some(ValueMultiplicity::Exactly(1)),
         };
 
         assert_eq!(dict.by_tag(Tag(0x0009, 0x0010)), Some(&private_creator));
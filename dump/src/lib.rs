@@ -39,7 +39,7 @@ use dicom_core::dictionary::UidDictionary;
 use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
 use dicom_core::header::Header;
 use dicom_core::value::{PrimitiveValue, Value as DicomValue};
-use dicom_core::VR;
+use dicom_core::{DataElement, Tag, VR};
 #[cfg(feature = "sop-class")]
 use dicom_dictionary_std::StandardSopClassDictionary;
 use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
@@ -124,6 +124,15 @@ pub struct DumpOptions {
     pub no_text_limit: bool,
     /// never trim out any values (implies `no_text_limit`)
     pub no_limit: bool,
+    /// the maximum sequence nesting level to print in full
+    /// (`None` means unlimited)
+    pub max_depth: Option<u32>,
+    /// only print the file meta group
+    pub meta_only: bool,
+    /// do not print the file meta group
+    pub skip_meta: bool,
+    /// print without width-dependent alignment nor trimming
+    pub fixed_layout: bool,
 }
 
 impl DumpOptions {
@@ -177,18 +186,73 @@ impl DumpOptions {
         self
     }
 
+    /// Set the maximum sequence nesting level to print in full.
+    ///
+    /// Items of sequences which are nested deeper than `max_depth`
+    /// are collapsed into a single summary line,
+    /// showing the number of levels and data elements hidden.
+    /// With a `max_depth` of 0,
+    /// no sequence items are printed at all.
+    ///
+    /// By default, all levels are printed.
+    pub fn max_depth(&mut self, max_depth: u32) -> &mut Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
     /// Set the output color mode.
     pub fn color_mode(&mut self, color: ColorMode) -> &mut Self {
         self.color = color;
         self
     }
 
+    /// Set whether to only print the file meta group
+    /// when dumping a DICOM file,
+    /// leaving out the main data set.
+    ///
+    /// Enabling this option disables [`skip_meta`](Self::skip_meta).
+    pub fn meta_only(&mut self, meta_only: bool) -> &mut Self {
+        self.meta_only = meta_only;
+        if meta_only {
+            self.skip_meta = false;
+        }
+        self
+    }
+
+    /// Set whether to leave out the file meta group
+    /// when dumping a DICOM file,
+    /// printing only the main data set.
+    ///
+    /// Enabling this option disables [`meta_only`](Self::meta_only).
+    pub fn skip_meta(&mut self, skip_meta: bool) -> &mut Self {
+        self.skip_meta = skip_meta;
+        if skip_meta {
+            self.meta_only = false;
+        }
+        self
+    }
+
+    /// Set whether to print in a fixed layout.
+    ///
+    /// In this layout,
+    /// the columns of each line are separated by a single space
+    /// without any padding,
+    /// and values are never trimmed (as with [`no_limit`](Self::no_limit)),
+    /// so that the output does not depend on the output width
+    /// and is the same across environments for the same input.
+    /// This is better suited for processing the text output
+    /// in other programs.
+    pub fn fixed_layout(&mut self, fixed_layout: bool) -> &mut Self {
+        self.fixed_layout = fixed_layout;
+        self
+    }
+
     /// Dump the contents of an open DICOM file to standard output.
     pub fn dump_file<D>(&self, obj: &FileDicomObject<InMemDicomObject<D>>) -> IoResult<()>
     where
         D: DataDictionary,
     {
-        self.dump_file_impl(stdout(), obj, true)
+        self.dump_file_impl(stdout(), obj, obj.dictionary(), true)
     }
 
     /// Dump the contents of an open DICOM file to the given writer.
@@ -200,17 +264,50 @@ impl DumpOptions {
     where
         D: DataDictionary,
     {
-        self.dump_file_impl(to, obj, false)
+        self.dump_file_impl(to, obj, obj.dictionary(), false)
     }
 
-    fn dump_file_impl<D>(
+    /// Dump the contents of an open DICOM file to standard output,
+    /// resolving attribute aliases with the given data dictionary
+    /// instead of the object's own dictionary.
+    pub fn dump_file_with_dict<D, Di>(
+        &self,
+        obj: &FileDicomObject<InMemDicomObject<D>>,
+        dict: &Di,
+    ) -> IoResult<()>
+    where
+        D: DataDictionary,
+        Di: DataDictionary,
+    {
+        self.dump_file_impl(stdout(), obj, dict, true)
+    }
+
+    /// Dump the contents of an open DICOM file to the given writer,
+    /// resolving attribute aliases with the given data dictionary
+    /// instead of the object's own dictionary.
+    pub fn dump_file_to_with_dict<D, Di>(
+        &self,
+        to: impl Write,
+        obj: &FileDicomObject<InMemDicomObject<D>>,
+        dict: &Di,
+    ) -> IoResult<()>
+    where
+        D: DataDictionary,
+        Di: DataDictionary,
+    {
+        self.dump_file_impl(to, obj, dict, false)
+    }
+
+    fn dump_file_impl<D, Di>(
         &self,
         mut to: impl Write,
         obj: &FileDicomObject<InMemDicomObject<D>>,
+        dict: &Di,
         to_stdout: bool,
     ) -> IoResult<()>
     where
         D: DataDictionary,
+        Di: DataDictionary,
     {
         match self.color {
             ColorMode::Never => owo_colors::set_override(false),
@@ -222,24 +319,50 @@ impl DumpOptions {
 
         let width = determine_width(self.width);
 
-        let (no_text_limit, no_limit) = if to_stdout {
+        let (no_text_limit, no_limit) = if to_stdout && !self.fixed_layout {
             (self.no_text_limit, self.no_limit)
         } else {
             (true, true)
         };
         match self.format {
             DumpFormat::Text => {
-                meta_dump(&mut to, meta, if no_limit { u32::MAX } else { width })?;
-
-                writeln!(to, "{:-<58}", "")?;
+                if !self.skip_meta {
+                    meta_dump(&mut to, meta, if no_limit { u32::MAX } else { width })?;
+                    if self.meta_only {
+                        return Ok(());
+                    }
+                    writeln!(to)?;
+                    writeln!(to, "{:-<58}", "")?;
+                }
 
-                dump(&mut to, obj, width, 0, no_text_limit, no_limit)?;
+                let settings = TextSettings {
+                    width,
+                    no_text_limit,
+                    no_limit,
+                    max_depth: self.max_depth,
+                    fixed_layout: self.fixed_layout,
+                    dict,
+                };
+                dump(&mut to, obj, &settings, 0, 0)?;
 
                 Ok(())
             },
             DumpFormat::Json => {
-                let json_obj = DicomJson::from(obj);
-                serde_json::to_writer_pretty(stdout(), &json_obj)?;
+                if self.meta_only {
+                    let meta_obj: InMemDicomObject = InMemDicomObject::from_element_iter(
+                        meta.to_element_iter().filter_map(|e| match e.value() {
+                            DicomValue::Primitive(value) => {
+                                Some(DataElement::new(e.tag(), e.vr(), value.clone()))
+                            }
+                            _ => None,
+                        }),
+                    );
+                    serde_json::to_writer_pretty(to, &DicomJson::from(&meta_obj))?;
+                } else if self.skip_meta {
+                    serde_json::to_writer_pretty(to, &DicomJson::from(&**obj))?;
+                } else {
+                    serde_json::to_writer_pretty(to, &DicomJson::from(obj))?;
+                }
                 Ok(())
             }
         }
@@ -252,7 +375,7 @@ impl DumpOptions {
     where
         D: DataDictionary,
     {
-        self.dump_object_impl(stdout(), obj, true)
+        self.dump_object_impl(stdout(), obj, obj.dictionary(), true)
     }
 
     /// Dump the contents of a DICOM object to the given writer.
@@ -261,17 +384,46 @@ impl DumpOptions {
     where
         D: DataDictionary,
     {
-        self.dump_object_impl(to, obj, false)
+        self.dump_object_impl(to, obj, obj.dictionary(), false)
+    }
+
+    /// Dump the contents of a DICOM object to standard output,
+    /// resolving attribute aliases with the given data dictionary
+    /// instead of the object's own dictionary.
+    pub fn dump_object_with_dict<D, Di>(&self, obj: &InMemDicomObject<D>, dict: &Di) -> IoResult<()>
+    where
+        D: DataDictionary,
+        Di: DataDictionary,
+    {
+        self.dump_object_impl(stdout(), obj, dict, true)
     }
 
-    fn dump_object_impl<D>(
+    /// Dump the contents of a DICOM object to the given writer,
+    /// resolving attribute aliases with the given data dictionary
+    /// instead of the object's own dictionary.
+    pub fn dump_object_to_with_dict<D, Di>(
+        &self,
+        to: impl Write,
+        obj: &InMemDicomObject<D>,
+        dict: &Di,
+    ) -> IoResult<()>
+    where
+        D: DataDictionary,
+        Di: DataDictionary,
+    {
+        self.dump_object_impl(to, obj, dict, false)
+    }
+
+    fn dump_object_impl<D, Di>(
         &self,
         mut to: impl Write,
         obj: &InMemDicomObject<D>,
+        dict: &Di,
         to_stdout: bool,
     ) -> IoResult<()>
     where
         D: DataDictionary,
+        Di: DataDictionary,
     {
         match self.format {
             DumpFormat::Text => {
@@ -284,13 +436,21 @@ impl DumpOptions {
 
                 let width = determine_width(self.width);
 
-                let (no_text_limit, no_limit) = if to_stdout {
+                let (no_text_limit, no_limit) = if to_stdout && !self.fixed_layout {
                     (self.no_text_limit, self.no_limit)
                 } else {
                     (true, true)
                 };
 
-                dump(&mut to, obj, width, 0, no_text_limit, no_limit)?;
+                let settings = TextSettings {
+                    width,
+                    no_text_limit,
+                    no_limit,
+                    max_depth: self.max_depth,
+                    fixed_layout: self.fixed_layout,
+                    dict,
+                };
+                dump(&mut to, obj, &settings, 0, 0)?;
 
                 Ok(())
             }
@@ -576,29 +736,70 @@ where
         )?;
     }
 
-    writeln!(to)?;
     Ok(())
 }
 
-fn dump<W, D>(
-    to: &mut W,
-    obj: &InMemDicomObject<D>,
+/// Settings of a text dump which apply to the whole object.
+struct TextSettings<'a, Di> {
+    /// the maximum output width
     width: u32,
-    depth: u32,
+    /// never trim out long text values
     no_text_limit: bool,
+    /// never trim out any values
     no_limit: bool,
+    /// the maximum sequence nesting level to print in full
+    max_depth: Option<u32>,
+    /// print columns without padding
+    fixed_layout: bool,
+    /// the data dictionary for resolving attribute aliases
+    dict: &'a Di,
+}
+
+fn dump<W, D, Di>(
+    to: &mut W,
+    obj: &InMemDicomObject<D>,
+    settings: &TextSettings<Di>,
+    depth: u32,
+    level: u32,
 ) -> IoResult<()>
 where
     W: ?Sized + Write,
     D: DataDictionary,
+    Di: DataDictionary,
 {
     for elem in obj {
-        dump_element(&mut *to, elem, width, depth, no_text_limit, no_limit)?;
+        let tag_alias = resolve_alias(settings.dict, obj, elem.tag());
+        dump_element_impl(&mut *to, elem, tag_alias, settings, depth, level)?;
     }
 
     Ok(())
 }
 
+/// Look up the alias of an attribute in the given data dictionary.
+///
+/// Private data elements are looked up
+/// by the identifier of the private creator
+/// which reserved their block in the enclosing data set `obj`,
+/// falling back to a plain look-up by tag.
+fn resolve_alias<'a, D, Di>(dict: &'a Di, obj: &InMemDicomObject<D>, tag: Tag) -> &'a str
+where
+    Di: DataDictionary,
+{
+    let private_entry = if tag.group() % 2 == 1 && tag.element() >= 0x1000 {
+        let creator_tag = Tag(tag.group(), tag.element() >> 8);
+        obj.into_iter()
+            .find(|e| e.tag() == creator_tag)
+            .and_then(|creator| creator.to_str().ok())
+            .and_then(|creator| dict.by_private_tag(creator.trim_end(), tag))
+    } else {
+        None
+    };
+    private_entry
+        .or_else(|| dict.by_tag(tag))
+        .map(DataDictionaryEntry::alias)
+        .unwrap_or("«Unknown Attribute»")
+}
+
 pub fn dump_element<W, D>(
     to: &mut W,
     elem: &InMemElement<D>,
@@ -611,11 +812,48 @@ where
     W: ?Sized + Write,
     D: DataDictionary,
 {
-    let indent = vec![b' '; (depth * 2) as usize];
+    let settings = TextSettings {
+        width,
+        no_text_limit,
+        no_limit,
+        max_depth: None,
+        fixed_layout: false,
+        dict: &StandardDataDictionary,
+    };
     let tag_alias = StandardDataDictionary
         .by_tag(elem.tag())
         .map(DataDictionaryEntry::alias)
         .unwrap_or("«Unknown Attribute»");
+    dump_element_impl(to, elem, tag_alias, &settings, depth, 0)
+}
+
+/// Dump a single data element with the given alias
+/// which belongs to a data set at the given nesting `level`
+/// (0 for the root data set).
+fn dump_element_impl<W, D, Di>(
+    to: &mut W,
+    elem: &InMemElement<D>,
+    tag_alias: &str,
+    settings: &TextSettings<Di>,
+    depth: u32,
+    level: u32,
+) -> IoResult<()>
+where
+    W: ?Sized + Write,
+    D: DataDictionary,
+    Di: DataDictionary,
+{
+    let TextSettings {
+        width,
+        no_text_limit,
+        no_limit,
+        max_depth,
+        fixed_layout,
+        ..
+    } = *settings;
+    // column widths for alignment, none in fixed layout
+    let (alias_width, len_width) = if fixed_layout { (0, 0) } else { (28, 3) };
+    let indent = vec![b' '; (depth * 2) as usize];
     to.write_all(&indent)?;
     let vm = match elem.vr() {
         VR::OB | VR::OW | VR::UN => 1,
@@ -626,22 +864,55 @@ where
         DicomValue::Sequence(seq) => {
             writeln!(
                 to,
-                "{} {:28} {} ({} Item{})",
+                "{} {:alias_width$} {} ({} Item{})",
                 DumpValue::TagNum(elem.tag()),
                 DumpValue::Alias(tag_alias),
                 elem.vr(),
                 vm,
                 if vm == 1 { "" } else { "s" },
             )?;
-            for item in seq.items() {
-                dump_item(&mut *to, item, width, depth + 2, no_text_limit, no_limit)?;
+            let items = seq.items();
+            if max_depth.map_or(true, |max_depth| level < max_depth) {
+                for (i, item) in items.iter().enumerate() {
+                    dump_item(
+                        &mut *to,
+                        item,
+                        (i + 1, items.len()),
+                        tag_alias,
+                        settings,
+                        depth + 2,
+                        level + 1,
+                    )?;
+                }
+            } else if !items.is_empty() {
+                // collapse all items into a single line
+                let (levels, elements) = nested_counts(items);
+                let summary = format!(
+                    "… (+ {} level{}, {} element{})",
+                    levels,
+                    if levels == 1 { "" } else { "s" },
+                    elements,
+                    if elements == 1 { "" } else { "s" },
+                );
+                let summary = if no_limit {
+                    Cow::from(summary)
+                } else {
+                    cut_str(&summary, width.saturating_sub(depth * 2 + 4))
+                };
+                to.write_all(&indent)?;
+                writeln!(
+                    to,
+                    "    {}",
+                    summary.if_supports_color(Stream::Stdout, |v| v.italic())
+                )?;
             }
             to.write_all(&indent)?;
             writeln!(
                 to,
-                "{} {}",
+                "{} {} ({})",
                 DumpValue::TagNum("(FFFE,E0DD)"),
                 DumpValue::Alias("SequenceDelimitationItem"),
+                tag_alias,
             )?;
         }
         DicomValue::PixelSequence(seq) => {
@@ -650,7 +921,7 @@ where
             let num_items = 1 + seq.fragments().len();
             writeln!(
                 to,
-                "{} {:28} {} (PixelSequence, {} Item{})",
+                "{} {:alias_width$} {} (PixelSequence, {} Item{})",
                 DumpValue::TagNum(elem.tag()),
                 "PixelData".bold(),
                 vr,
@@ -669,11 +940,12 @@ where
             );
             writeln!(
                 to,
-                "  {} offset table ({:>2}, {:>2} bytes): {}",
+                "  {} offset table ({:>count_width$}, {:>count_width$} bytes): {}",
                 DumpValue::TagNum("(FFFE,E000)"),
                 offset_table.len(),
                 byte_len,
                 summary,
+                count_width = len_width.saturating_sub(1),
             )?;
 
             // write compressed fragments
@@ -687,7 +959,7 @@ where
                 );
                 writeln!(
                     to,
-                    "  {} pi ({:>3} bytes): {}",
+                    "  {} pi ({:>len_width$} bytes): {}",
                     DumpValue::TagNum("(FFFE,E000)"),
                     byte_len,
                     summary
@@ -699,7 +971,7 @@ where
             let byte_len = elem.header().len.0;
             writeln!(
                 to,
-                "{} {:28} {} ({},{:>3} bytes): {}",
+                "{} {:alias_width$} {} ({},{:>len_width$} bytes): {}",
                 DumpValue::TagNum(elem.tag()),
                 DumpValue::Alias(tag_alias),
                 vr,
@@ -719,37 +991,67 @@ where
     Ok(())
 }
 
-fn dump_item<W, D>(
+/// Dump a sequence item,
+/// given its 1-based position in the sequence and the total number of items,
+/// as well as the keyword of the owning sequence.
+fn dump_item<W, D, Di>(
     to: &mut W,
     item: &InMemDicomObject<D>,
-    width: u32,
+    (index, count): (usize, usize),
+    sequence_alias: &str,
+    settings: &TextSettings<Di>,
     depth: u32,
-    no_text_limit: bool,
-    no_limit: bool,
+    level: u32,
 ) -> IoResult<()>
 where
     W: ?Sized + Write,
     D: DataDictionary,
+    Di: DataDictionary,
 {
     let indent: String = "  ".repeat(depth as usize);
     writeln!(
         to,
-        "{}{} na {}",
+        "{}{} {} #{} (of {})",
         indent,
         DumpValue::TagNum("(FFFE,E000)"),
         DumpValue::Alias("Item"),
+        index,
+        count,
     )?;
-    dump(to, item, width, depth + 1, no_text_limit, no_limit)?;
+    dump(to, item, settings, depth + 1, level)?;
     writeln!(
         to,
-        "{}{} {}",
+        "{}{} {} ({})",
         indent,
         DumpValue::TagNum("(FFFE,E00D)"),
         DumpValue::Alias("ItemDelimitationItem"),
+        sequence_alias,
     )?;
     Ok(())
 }
 
+/// Count the number of nesting levels
+/// and the total number of data elements
+/// contained in the given sequence items.
+fn nested_counts<D>(items: &[InMemDicomObject<D>]) -> (u32, usize) {
+    if items.is_empty() {
+        return (0, 0);
+    }
+    let mut levels = 0;
+    let mut elements = 0;
+    for item in items {
+        for elem in item {
+            elements += 1;
+            if let DicomValue::Sequence(seq) = elem.value() {
+                let (nested_levels, nested_elements) = nested_counts(seq.items());
+                levels = levels.max(nested_levels);
+                elements += nested_elements;
+            }
+        }
+    }
+    (levels + 1, elements)
+}
+
 fn value_summary(
     value: &PrimitiveValue,
     vr: VR,
@@ -973,9 +1275,14 @@ fn determine_width(user_width: Option<u32>) -> u32 {
 #[cfg(test)]
 mod tests {
 
-    use dicom_core::{value::DicomDate, DataElement, PrimitiveValue, VR};
-    use dicom_dictionary_std::tags;
-    use dicom_object::{FileMetaTableBuilder, InMemDicomObject};
+    use dicom_core::dictionary::{
+        DataDictionary, DataDictionaryEntryRef, MergedDictionary, TagRange, VirtualVr,
+    };
+    use dicom_core::value::{DataSetSequence, DicomDate};
+    use dicom_core::{DataElement, PrimitiveValue, Tag, VR};
+    use dicom_dictionary_std::{tags, StandardDataDictionary};
+    use dicom_object::mem::InMemElement;
+    use dicom_object::{FileDicomObject, FileMetaTableBuilder, InMemDicomObject};
 
     use super::whitespace_or_null;
     use crate::{ColorMode, DumpOptions};
@@ -1045,6 +1352,92 @@ mod tests {
         assert_eq!(&parts[..3], &["(0008,0018)", "SOPInstanceUID", "UI"]);
     }
 
+    fn file_object() -> FileDicomObject<InMemDicomObject> {
+        InMemDicomObject::from_element_iter(vec![DataElement::new(
+            tags::SOP_INSTANCE_UID,
+            VR::UI,
+            PrimitiveValue::from("1.2.888.123"),
+        )])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                // Implicit VR Little Endian
+                .transfer_syntax("1.2.840.10008.1.2")
+                // Computed Radiography Image Storage
+                .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn dump_file_meta_only() {
+        let file = file_object();
+
+        let mut out = Vec::new();
+        DumpOptions::new()
+            .color_mode(ColorMode::Never)
+            .meta_only(true)
+            .dump_file_to(&mut out, &file)
+            .unwrap();
+
+        let lines: Vec<_> = std::str::from_utf8(&out)
+            .expect("output is not valid UTF-8")
+            .lines()
+            .collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("Media Storage SOP Class UID: "));
+        assert_eq!(
+            lines[2],
+            "Transfer Syntax: 1.2.840.10008.1.2 (Implicit VR Little Endian)"
+        );
+        assert!(lines[4].starts_with("Implementation version name: "));
+    }
+
+    #[test]
+    fn dump_file_skip_meta() {
+        let file = file_object();
+
+        let mut out = Vec::new();
+        DumpOptions::new()
+            .color_mode(ColorMode::Never)
+            .meta_only(true)
+            .skip_meta(true)
+            .dump_file_to(&mut out, &file)
+            .unwrap();
+
+        let lines: Vec<_> = std::str::from_utf8(&out)
+            .expect("output is not valid UTF-8")
+            .lines()
+            .collect();
+        assert_eq!(lines.len(), 1);
+        let parts: Vec<&str> = lines[0].split(" ").filter(|p| !p.is_empty()).collect();
+        assert_eq!(&parts[..3], &["(0008,0018)", "SOPInstanceUID", "UI"]);
+    }
+
+    #[test]
+    fn dump_fixed_layout() {
+        let file = file_object();
+
+        let dump = |width| {
+            let mut out = Vec::new();
+            DumpOptions::new()
+                .color_mode(ColorMode::Never)
+                .skip_meta(true)
+                .fixed_layout(true)
+                .width(width)
+                .dump_file_to(&mut out, &file)
+                .unwrap();
+            String::from_utf8(out).expect("output is not valid UTF-8")
+        };
+
+        let out = dump(120);
+        assert_eq!(
+            out.lines().next().unwrap(),
+            "(0008,0018) SOPInstanceUID UI (1,12 bytes): \"1.2.888.123\""
+        );
+        // the output does not depend on the width
+        assert_eq!(dump(20), out);
+    }
+
     #[test]
     fn dump_object_to_covers_properties() {
         // create object
@@ -1115,6 +1508,240 @@ mod tests {
         }
     }
 
+    /// Create an object with three levels of nested sequences:
+    /// 2 studies with 3 series each, with 2 images per series.
+    fn nested_object() -> InMemDicomObject {
+        let image = || {
+            InMemDicomObject::from_element_iter([DataElement::new(
+                tags::REFERENCED_SOP_INSTANCE_UID,
+                VR::UI,
+                PrimitiveValue::from("1.2.888.123.3"),
+            )])
+        };
+        let series = || {
+            InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::SERIES_INSTANCE_UID,
+                    VR::UI,
+                    PrimitiveValue::from("1.2.888.123.2"),
+                ),
+                DataElement::new(
+                    tags::REFERENCED_IMAGE_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(vec![image(), image()]),
+                ),
+            ])
+        };
+        let study = || {
+            InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::REFERENCED_SOP_CLASS_UID,
+                    VR::UI,
+                    PrimitiveValue::from("1.2.840.10008.3.1.2.3.1"),
+                ),
+                DataElement::new(
+                    tags::REFERENCED_SERIES_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(vec![series(), series(), series()]),
+                ),
+            ])
+        };
+        InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::REFERENCED_STUDY_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![study(), study()]),
+            ),
+            DataElement::new(tags::PATIENT_ID, VR::LO, PrimitiveValue::from("P1")),
+        ])
+    }
+
+    #[test]
+    fn dump_nested_sequences_with_item_numbers() {
+        let obj = nested_object();
+
+        let mut out = Vec::new();
+        DumpOptions::new()
+            .color_mode(ColorMode::Never)
+            .dump_object_to(&mut out, &obj)
+            .unwrap();
+
+        let out = std::str::from_utf8(&out).expect("output is not valid UTF-8");
+        let lines: Vec<_> = out.lines().collect();
+
+        assert!(lines[0].starts_with("(0008,1110) ReferencedStudySequence"));
+        assert_eq!(lines[1], "    (FFFE,E000) Item #1 (of 2)");
+        assert!(lines.iter().any(|l| *l == "    (FFFE,E000) Item #2 (of 2)"));
+        assert!(lines
+            .iter()
+            .any(|l| l.trim_start() == "(FFFE,E000) Item #3 (of 3)"));
+        // 2 studies + 6 series + 12 images
+        assert_eq!(lines.iter().filter(|l| l.contains(" Item #")).count(), 20);
+
+        // delimiters name the sequence which they close
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|l| l.trim_start()
+                    == "(FFFE,E00D) ItemDelimitationItem (ReferencedImageSequence)")
+                .count(),
+            12
+        );
+        assert!(lines.contains(&"    (FFFE,E00D) ItemDelimitationItem (ReferencedStudySequence)"));
+        assert!(lines
+            .iter()
+            .any(|l| l.trim_start()
+                == "(FFFE,E0DD) SequenceDelimitationItem (ReferencedSeriesSequence)"));
+        assert_eq!(
+            lines[lines.len() - 2],
+            "(FFFE,E0DD) SequenceDelimitationItem (ReferencedStudySequence)"
+        );
+        assert!(lines[lines.len() - 1].starts_with("(0010,0020) PatientID"));
+    }
+
+    #[test]
+    fn dump_nested_sequences_with_max_depth() {
+        let obj = nested_object();
+
+        let mut out = Vec::new();
+        DumpOptions::new()
+            .color_mode(ColorMode::Never)
+            .max_depth(1)
+            .dump_object_to(&mut out, &obj)
+            .unwrap();
+
+        let out = std::str::from_utf8(&out).expect("output is not valid UTF-8");
+        let lines: Vec<_> = out.lines().collect();
+
+        // only the study items are printed
+        let items: Vec<_> = lines.iter().filter(|l| l.contains(" Item #")).collect();
+        assert_eq!(
+            items,
+            [
+                &"    (FFFE,E000) Item #1 (of 2)",
+                &"    (FFFE,E000) Item #2 (of 2)"
+            ]
+        );
+
+        // each series sequence is collapsed into a summary
+        let summaries: Vec<_> = lines.iter().filter(|l| l.contains('…')).collect();
+        assert_eq!(
+            summaries,
+            [
+                &"          … (+ 2 levels, 12 elements)",
+                &"          … (+ 2 levels, 12 elements)"
+            ]
+        );
+        let i = lines.iter().position(|l| l.contains('…')).unwrap();
+        assert!(lines[i - 1]
+            .trim_start()
+            .starts_with("(0008,1115) ReferencedSeriesSequence"));
+        assert_eq!(
+            lines[i + 1].trim_start(),
+            "(FFFE,E0DD) SequenceDelimitationItem (ReferencedSeriesSequence)"
+        );
+        assert!(lines[lines.len() - 1].starts_with("(0010,0020) PatientID"));
+
+        // collapse everything
+        let mut out = Vec::new();
+        DumpOptions::new()
+            .color_mode(ColorMode::Never)
+            .max_depth(0)
+            .dump_object_to(&mut out, &obj)
+            .unwrap();
+        let out = std::str::from_utf8(&out).expect("output is not valid UTF-8");
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], "    … (+ 3 levels, 28 elements)");
+    }
+
+    /// A toy dictionary of private attributes
+    /// reserved by the creator `ACME 1.0`.
+    #[derive(Debug, Clone, Copy)]
+    struct AcmeDictionary;
+
+    static ACME_SERIAL: DataDictionaryEntryRef<'static> = DataDictionaryEntryRef::new(
+        TagRange::Single(Tag(0x0009, 0x0001)),
+        "AcmeSerial",
+        VirtualVr::Exact(VR::LO),
+    );
+
+    impl DataDictionary for AcmeDictionary {
+        type Entry = DataDictionaryEntryRef<'static>;
+
+        fn by_tag(&self, _: Tag) -> Option<&Self::Entry> {
+            None
+        }
+
+        fn by_name(&self, _: &str) -> Option<&Self::Entry> {
+            None
+        }
+
+        fn by_private_tag(&self, private_creator: &str, tag: Tag) -> Option<&Self::Entry> {
+            Some(&ACME_SERIAL).filter(|_| {
+                private_creator == "ACME 1.0" && tag.group() == 0x0009 && tag.element() & 0xFF == 1
+            })
+        }
+    }
+
+    fn private_elements<D>() -> Vec<InMemElement<D>> {
+        vec![
+            DataElement::new(Tag(0x0009, 0x0010), VR::LO, PrimitiveValue::from("OTHER")),
+            DataElement::new(
+                Tag(0x0009, 0x0011),
+                VR::LO,
+                PrimitiveValue::from("ACME 1.0"),
+            ),
+            DataElement::new(
+                Tag(0x0009, 0x1001),
+                VR::LO,
+                PrimitiveValue::from("not ours"),
+            ),
+            DataElement::new(Tag(0x0009, 0x1101), VR::LO, PrimitiveValue::from("X-42")),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::from("Doe^John")),
+        ]
+    }
+
+    fn alias_of<'a>(lines: &[&'a str], tag: &str) -> &'a str {
+        let line = lines
+            .iter()
+            .find(|l| l.starts_with(tag))
+            .unwrap_or_else(|| panic!("no line for {}", tag));
+        line[tag.len()..].split_whitespace().next().unwrap()
+    }
+
+    #[test]
+    fn dump_private_attributes_with_custom_dictionary() {
+        let dict = MergedDictionary::new(StandardDataDictionary, AcmeDictionary);
+        let obj = InMemDicomObject::from_iter_with_dict(private_elements(), dict);
+
+        let mut out = Vec::new();
+        DumpOptions::new()
+            .color_mode(ColorMode::Never)
+            .dump_object_to(&mut out, &obj)
+            .unwrap();
+        let out = std::str::from_utf8(&out).expect("output is not valid UTF-8");
+        let lines: Vec<_> = out.lines().collect();
+
+        assert_eq!(alias_of(&lines, "(0009,1101)"), "AcmeSerial");
+        // same element number, but in the block of another creator
+        assert_eq!(alias_of(&lines, "(0009,1001)"), "«Unknown");
+        assert_eq!(alias_of(&lines, "(0010,0010)"), "PatientName");
+
+        // the dictionary may also be supplied explicitly
+        let obj = InMemDicomObject::from_element_iter(private_elements());
+        let dict = MergedDictionary::new(StandardDataDictionary, AcmeDictionary);
+        let mut out = Vec::new();
+        DumpOptions::new()
+            .color_mode(ColorMode::Never)
+            .dump_object_to_with_dict(&mut out, &obj, &dict)
+            .unwrap();
+        let out = std::str::from_utf8(&out).expect("output is not valid UTF-8");
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(alias_of(&lines, "(0009,1101)"), "AcmeSerial");
+        assert_eq!(alias_of(&lines, "(0010,0010)"), "PatientName");
+    }
+
     #[test]
     fn dump_json() {
         // create object
@@ -39,6 +39,21 @@ struct App {
     /// or if output type is json
     #[clap(short = 'w', long = "width")]
     width: Option<u32>,
+    /// The maximum sequence nesting level to print in full
+    /// (deeper items are collapsed into a summary line)
+    #[clap(long = "max-depth")]
+    max_depth: Option<u32>,
+    /// Only print the file meta group
+    #[clap(long = "meta-only", conflicts_with = "skip_meta")]
+    meta_only: bool,
+    /// Do not print the file meta group
+    #[clap(long = "skip-meta")]
+    skip_meta: bool,
+    /// Print columns separated by a single space,
+    /// without alignment nor trimming
+    /// (stable across terminal widths, better for diffing)
+    #[clap(long = "fixed-layout")]
+    fixed_layout: bool,
     /// The color mode
     #[clap(long = "color", default_value = "auto")]
     color: ColorMode,
@@ -68,6 +83,10 @@ fn run() -> Result<(), Whatever> {
         no_text_limit,
         no_limit,
         width,
+        max_depth,
+        meta_only,
+        skip_meta,
+        fixed_layout,
         color,
         fail_first,
         format,
@@ -83,8 +102,14 @@ fn run() -> Result<(), Whatever> {
         // No limit when output is not a terminal
         .no_limit(if !is_terminal() { true } else {no_limit})
         .width(width)
+        .meta_only(meta_only)
+        .skip_meta(skip_meta)
+        .fixed_layout(fixed_layout)
         .color_mode(color)
         .format(format);
+    if let Some(max_depth) = max_depth {
+        options.max_depth(max_depth);
+    }
     let fail_first = filenames.len() == 1 || fail_first;
     let mut errors: i32 = 0;
 
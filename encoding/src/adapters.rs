@@ -12,9 +12,14 @@
 //! [`PixelDataReader`] and [`PixelDataWriter`]
 //! to be able to decode and encode imaging data, respectively.
 
-use dicom_core::{ops::AttributeOp, value::C};
-use snafu::Snafu;
+use dicom_core::{
+    ops::AttributeOp,
+    value::{frame_fragment_indices, C},
+};
+use snafu::{OptionExt, Snafu};
 use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::ops::Range;
 
 /// The possible error conditions when decoding (reading) pixel data.
 ///
@@ -120,6 +125,384 @@ pub struct RawPixelData {
     pub offset_table: C<u32>,
 }
 
+impl RawPixelData {
+    /// Describe the layout of these fragments of encapsulated pixel data
+    /// and their basic offset table,
+    /// for an image with the given number of frames.
+    ///
+    /// The extended offset table is not part of the raw pixel data,
+    /// use [`FragmentLayout::from_object`] to include it.
+    pub fn layout(&self, number_of_frames: u32) -> FragmentLayout {
+        FragmentLayout::from_fragments(number_of_frames, &self.fragments[..], &self.offset_table)
+    }
+
+    /// Retrieve the encoded data of a single frame (0-based)
+    /// of encapsulated pixel data with the given layout,
+    /// gathering all fragments which make up the frame.
+    ///
+    /// Frames are located through the extended offset table
+    /// if the layout has one.
+    /// See [`encapsulated_frame_data`] for more details.
+    pub fn frame_data(&self, layout: &FragmentLayout, frame: u32) -> Option<Cow<[u8]>> {
+        FrameExtractionStrategy::determine(layout).frame_data(&self.fragments[..], layout, frame)
+    }
+}
+
+/// The strategy for attributing the fragments of encapsulated pixel data
+/// to the frames of an image.
+///
+/// The strategy of some pixel data is chosen by [`determine`](Self::determine)
+/// based on its [fragment layout](FragmentLayout):
+///
+/// - native pixel data is [`Native`](Self::Native);
+/// - if there are as many fragments as frames,
+///   the strategy is [`OneFragmentPerFrame`](Self::OneFragmentPerFrame);
+/// - otherwise, the fragments of a single-frame image
+///   are [all part of that frame](Self::SingleFrameAllFragments);
+/// - otherwise, frames are located through the
+///   [basic or extended offset table](Self::OffsetTable).
+///
+/// The extended offset table is preferred over the basic offset table
+/// if both are present.
+/// A multi-frame image with more fragments than frames
+/// and an empty basic offset table cannot have its frames located,
+/// so all fragments are attributed to the first frame.
+///
+/// [`determine_and_warn`](Self::determine_and_warn)
+/// also logs a warning where the offset tables
+/// disagree with each other or with the chosen strategy.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FrameExtractionStrategy {
+    /// Frames start at the fragments pointed by the offset table,
+    /// each frame spanning one or more fragments.
+    /// The extended offset table is used if present,
+    /// otherwise the basic offset table.
+    OffsetTable,
+    /// Each fragment holds exactly one frame.
+    OneFragmentPerFrame,
+    /// All fragments make up the first and only frame.
+    SingleFrameAllFragments,
+    /// The pixel data is native (not encapsulated),
+    /// so frames are consecutive byte ranges of the same size
+    /// and no fragments are involved.
+    Native,
+}
+
+impl FrameExtractionStrategy {
+    /// Determine the strategy for extracting frames
+    /// from pixel data with the given layout.
+    ///
+    /// Nothing is logged,
+    /// so this is suitable for locating individual frames.
+    pub fn determine(layout: &FragmentLayout) -> Self {
+        Self::determine_impl(layout, false)
+    }
+
+    /// Determine the strategy for extracting frames
+    /// from pixel data with the given layout,
+    /// logging a warning where the offset tables
+    /// disagree with each other or with the chosen strategy.
+    ///
+    /// This is meant to be called once per object,
+    /// such as before extracting all of its frames,
+    /// so that the same warnings are not repeated for each frame.
+    pub fn determine_and_warn(layout: &FragmentLayout) -> Self {
+        Self::determine_impl(layout, true)
+    }
+
+    fn determine_impl(layout: &FragmentLayout, warn: bool) -> Self {
+        let Some(fragment_lengths) = &layout.fragment_lengths else {
+            return FrameExtractionStrategy::Native;
+        };
+        let number_of_fragments = fragment_lengths.len();
+        let number_of_frames = layout.number_of_frames as usize;
+        if warn
+            && !layout.offset_table.is_empty()
+            && !layout.extended_offset_table.is_empty()
+            && !layout
+                .offset_table
+                .iter()
+                .map(|&o| u64::from(o))
+                .eq(layout.extended_offset_table.iter().copied())
+        {
+            tracing::warn!(
+                "Basic offset table conflicts with the extended offset table, \
+                 using the extended offset table"
+            );
+        }
+        let offset_table = layout.offsets();
+
+        if number_of_fragments == number_of_frames {
+            if warn && !offset_table.is_empty() && !layout.offset_table_is_consistent() {
+                tracing::warn!("Offset table disagrees with one fragment per frame, ignoring it");
+            }
+            FrameExtractionStrategy::OneFragmentPerFrame
+        } else if number_of_frames <= 1 {
+            if warn && offset_table.len() > 1 {
+                tracing::warn!(
+                    "Offset table has {} entries for a single frame, ignoring it",
+                    offset_table.len()
+                );
+            }
+            FrameExtractionStrategy::SingleFrameAllFragments
+        } else if offset_table.is_empty() {
+            if warn {
+                tracing::warn!(
+                    "Cannot locate {} frames in {} fragments without an offset table, \
+                     attributing all fragments to the first frame",
+                    number_of_frames,
+                    number_of_fragments
+                );
+            }
+            FrameExtractionStrategy::SingleFrameAllFragments
+        } else {
+            if warn && !layout.offset_table_is_consistent() {
+                tracing::warn!(
+                    "Offset table is inconsistent with {} frames in {} fragments",
+                    number_of_frames,
+                    number_of_fragments
+                );
+            }
+            FrameExtractionStrategy::OffsetTable
+        }
+    }
+
+    /// Obtain the range of indices of the fragments
+    /// which make up the given frame (0-based)
+    /// of pixel data with the given layout,
+    /// in accordance to this strategy.
+    ///
+    /// Returns `None` if no fragments can be attributed to the frame,
+    /// or if the pixel data is native.
+    /// With an offset table which is not in increasing order,
+    /// a frame is only attributed fragments
+    /// if its offset is past the offsets of all previous frames
+    /// and before the offset of the next frame,
+    /// so that no two frames share the same fragments.
+    pub fn frame_fragments(self, layout: &FragmentLayout, frame: u32) -> Option<Range<usize>> {
+        let fragment_lengths = layout.fragment_lengths.as_deref()?;
+        let frame = frame as usize;
+        match self {
+            FrameExtractionStrategy::Native => None,
+            FrameExtractionStrategy::OneFragmentPerFrame => {
+                (frame < fragment_lengths.len()).then(|| frame..frame + 1)
+            }
+            FrameExtractionStrategy::SingleFrameAllFragments => {
+                (frame == 0 && !fragment_lengths.is_empty()).then(|| 0..fragment_lengths.len())
+            }
+            FrameExtractionStrategy::OffsetTable => {
+                let offset_table = layout.offsets();
+                let base_offset = match offset_table.get(frame) {
+                    Some(&offset) => offset,
+                    None if frame == 0 => 0,
+                    None => return None,
+                };
+                let next_offset = offset_table.get(frame + 1).copied();
+                if offset_table[..frame]
+                    .iter()
+                    .any(|&offset| offset >= base_offset)
+                    || matches!(next_offset, Some(next) if next <= base_offset)
+                {
+                    return None;
+                }
+
+                // take the fragments starting between this frame's offset
+                // and the next frame's offset
+                let mut range: Option<Range<usize>> = None;
+                for (i, position) in fragment_positions(fragment_lengths).enumerate() {
+                    if position < base_offset {
+                        continue;
+                    }
+                    if matches!(next_offset, Some(next) if position >= next) {
+                        break;
+                    }
+                    match &mut range {
+                        Some(range) => range.end = i + 1,
+                        None => range = Some(i..i + 1),
+                    }
+                }
+                range
+            }
+        }
+    }
+
+    /// Retrieve the encoded data of a single frame (0-based)
+    /// from the given fragments of pixel data with the given layout,
+    /// gathering all fragments which make up the frame
+    /// in accordance to this strategy.
+    ///
+    /// The data is borrowed if the frame is in a single fragment.
+    /// Returns `None` if no fragments can be attributed to the frame.
+    pub fn frame_data<'a, F>(
+        self,
+        fragments: &'a [F],
+        layout: &FragmentLayout,
+        frame: u32,
+    ) -> Option<Cow<'a, [u8]>>
+    where
+        F: AsRef<[u8]>,
+    {
+        let range = self.frame_fragments(layout, frame)?;
+        match fragments.get(range)? {
+            [fragment] => Some(Cow::Borrowed(fragment.as_ref())),
+            fragments => {
+                let mut data = Vec::new();
+                for fragment in fragments {
+                    data.extend_from_slice(fragment.as_ref());
+                }
+                Some(Cow::Owned(data))
+            }
+        }
+    }
+}
+
+/// The layout of the fragments of some pixel data,
+/// which is what decides how frames are extracted from them
+/// (see [`FrameExtractionStrategy`]).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FragmentLayout {
+    /// The number of frames in the image.
+    pub number_of_frames: u32,
+    /// The length in bytes of each fragment, excluding item headers,
+    /// or `None` if the pixel data is native.
+    pub fragment_lengths: Option<Vec<u64>>,
+    /// The basic offset table, empty if there is none.
+    pub offset_table: Vec<u32>,
+    /// The extended offset table, empty if there is none.
+    /// If present, it takes precedence over the basic offset table.
+    pub extended_offset_table: Vec<u64>,
+}
+
+impl FragmentLayout {
+    /// Describe the layout of native pixel data.
+    pub fn native(number_of_frames: u32) -> Self {
+        FragmentLayout {
+            number_of_frames,
+            fragment_lengths: None,
+            offset_table: Vec::new(),
+            extended_offset_table: Vec::new(),
+        }
+    }
+
+    /// Describe the layout of the given fragments of encapsulated pixel data
+    /// and their basic offset table.
+    pub fn from_fragments<F>(number_of_frames: u32, fragments: &[F], offset_table: &[u32]) -> Self
+    where
+        F: AsRef<[u8]>,
+    {
+        FragmentLayout {
+            number_of_frames,
+            fragment_lengths: Some(
+                fragments
+                    .iter()
+                    .map(|fragment| fragment.as_ref().len() as u64)
+                    .collect(),
+            ),
+            offset_table: offset_table.to_vec(),
+            extended_offset_table: Vec::new(),
+        }
+    }
+
+    /// Attach the given extended offset table to this layout,
+    /// with the offset of each frame in the same terms
+    /// as the basic offset table.
+    /// An empty table means that there is no extended offset table.
+    pub fn with_extended_offset_table(mut self, extended_offset_table: Vec<u64>) -> Self {
+        self.extended_offset_table = extended_offset_table;
+        self
+    }
+
+    /// Describe the layout of the pixel data in the given object,
+    /// without decoding it.
+    ///
+    /// An object without _Number of Frames_ is assumed to have one frame.
+    /// The extended offset table is included if the object has one.
+    /// Returns `None` if the object has no pixel data.
+    pub fn from_object<O>(src: &O) -> Option<Self>
+    where
+        O: PixelDataObject + ?Sized,
+    {
+        let number_of_frames = src.number_of_frames().unwrap_or(1);
+        let number_of_fragments = src.number_of_fragments()? as usize;
+        let Some(offset_table) = src.offset_table() else {
+            return Some(Self::native(number_of_frames));
+        };
+        let fragment_lengths = (0..number_of_fragments)
+            .map(|i| src.fragment(i).map(|f| f.len() as u64))
+            .collect::<Option<Vec<_>>>()?;
+        Some(FragmentLayout {
+            number_of_frames,
+            fragment_lengths: Some(fragment_lengths),
+            offset_table: offset_table.into_owned(),
+            extended_offset_table: src
+                .extended_offset_table()
+                .map(Cow::into_owned)
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Obtain the offset of each frame,
+    /// from the extended offset table if present,
+    /// or from the basic offset table otherwise.
+    fn offsets(&self) -> Cow<[u64]> {
+        if self.extended_offset_table.is_empty() {
+            Cow::Owned(self.offset_table.iter().map(|&o| u64::from(o)).collect())
+        } else {
+            Cow::Borrowed(&self.extended_offset_table)
+        }
+    }
+
+    /// Check whether the offset table in effect
+    /// (see [`FrameExtractionStrategy::OffsetTable`])
+    /// has exactly one entry per frame,
+    /// with the first frame at offset 0,
+    /// and each frame starting at the beginning of a different fragment.
+    ///
+    /// Always `false` for native pixel data.
+    pub fn offset_table_is_consistent(&self) -> bool {
+        let Some(fragment_lengths) = &self.fragment_lengths else {
+            return false;
+        };
+        let offset_table = self.offsets();
+        !offset_table.is_empty()
+            && offset_table.len() == self.number_of_frames as usize
+            && frame_fragment_indices(&offset_table, fragment_lengths).is_ok()
+    }
+}
+
+/// Iterate over the position of each fragment's item header
+/// relative to the first one,
+/// given the length of each fragment.
+fn fragment_positions(fragment_lengths: &[u64]) -> impl Iterator<Item = u64> + '_ {
+    fragment_lengths.iter().scan(0, |position, len| {
+        let current = *position;
+        // each fragment is preceded by an 8-byte item header
+        *position += len + 8;
+        Some(current)
+    })
+}
+
+/// Retrieve the encoded data of a single frame (0-based)
+/// from the given fragments of encapsulated pixel data
+/// and their basic offset table,
+/// gathering all fragments which make up the frame
+/// as decided by the [frame extraction strategy](FrameExtractionStrategy).
+///
+/// The data is borrowed if the frame is in a single fragment.
+/// Returns `None` if no fragments could be attributed to the frame.
+pub fn encapsulated_frame_data<'a, F>(
+    fragments: &'a [F],
+    offset_table: &[u32],
+    number_of_frames: u32,
+    frame: u32,
+) -> Option<Cow<'a, [u8]>>
+where
+    F: AsRef<[u8]>,
+{
+    let layout = FragmentLayout::from_fragments(number_of_frames, fragments, offset_table);
+    FrameExtractionStrategy::determine(&layout).frame_data(fragments, &layout, frame)
+}
+
 /// A DICOM object trait to be interpreted as pixel data.
 ///
 /// This trait extends the concept of DICOM object
@@ -177,11 +560,131 @@ pub trait PixelDataObject {
     /// or `None` if no offset table is available.
     fn offset_table(&self) -> Option<Cow<[u32]>>;
 
+    /// Return the 64-bit offset of each frame
+    /// in the object's _Extended Offset Table_,
+    /// or `None` if the object has no extended offset table.
+    ///
+    /// When available, it takes precedence over the basic offset table
+    /// for locating frames.
+    /// The default implementation returns `None`.
+    fn extended_offset_table(&self) -> Option<Cow<[u64]>> {
+        None
+    }
+
+    /// Return the 64-bit length of each frame
+    /// in the object's _Extended Offset Table Lengths_,
+    /// or `None` if the object has no extended offset table.
+    ///
+    /// The default implementation returns `None`.
+    fn extended_offset_table_lengths(&self) -> Option<Cow<[u64]>> {
+        None
+    }
+
     /// Should return either a byte slice/vector if the pixel data is native
     /// or the list of byte fragments and offset table if encapsulated.
     ///
     /// Returns `None` if no pixel data is found.
     fn raw_pixel_data(&self) -> Option<RawPixelData>;
+
+    /// Return the encoded length in bytes of each frame
+    /// of encapsulated pixel data,
+    /// without decoding them.
+    ///
+    /// The length of a frame is the sum of the lengths
+    /// of the fragments which make up that frame,
+    /// excluding item headers.
+    /// Fragments are attributed to frames
+    /// as decided by the [frame extraction strategy](FrameExtractionStrategy),
+    /// unless the object provides the length of each frame
+    /// in the _Extended Offset Table Lengths_.
+    ///
+    /// Returns `None` for native pixel data,
+    /// where all frames have the same length,
+    /// or if the fragments cannot be attributed to frames,
+    /// such as when there are multiple fragments per frame
+    /// but the basic offset table is empty or
+    /// [inconsistent](FragmentLayout::offset_table_is_consistent).
+    fn encoded_frame_lengths(&self) -> Option<Vec<u64>> {
+        let layout = FragmentLayout::from_object(self)?;
+        let fragment_lengths = layout.fragment_lengths.as_deref()?;
+        if !layout.extended_offset_table.is_empty() {
+            if let Some(lengths) = self.extended_offset_table_lengths() {
+                if lengths.len() == layout.number_of_frames as usize {
+                    return Some(lengths.into_owned());
+                }
+            }
+        }
+        let strategy = FrameExtractionStrategy::determine_and_warn(&layout);
+        match strategy {
+            FrameExtractionStrategy::Native => return None,
+            FrameExtractionStrategy::SingleFrameAllFragments if layout.number_of_frames > 1 => {
+                return None
+            }
+            FrameExtractionStrategy::OffsetTable if !layout.offset_table_is_consistent() => {
+                return None
+            }
+            _ => {}
+        }
+        (0..layout.number_of_frames)
+            .map(|frame| {
+                let range = strategy.frame_fragments(&layout, frame)?;
+                Some(fragment_lengths[range].iter().sum())
+            })
+            .collect()
+    }
+
+    /// Return the total encoded length in bytes
+    /// of encapsulated pixel data,
+    /// as the sum of the lengths of all fragments,
+    /// excluding item headers and the basic offset table.
+    ///
+    /// Returns `None` for native pixel data.
+    fn encoded_total_length(&self) -> Option<u64> {
+        self.offset_table()?;
+        let nr_fragments = self.number_of_fragments()? as usize;
+        (0..nr_fragments)
+            .map(|i| self.fragment(i).map(|f| f.len() as u64))
+            .sum()
+    }
+}
+
+/// Custom options when decoding a frame of encapsulated pixel data.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct DecodeFrameOptions {
+    /// The number of resolution levels to discard while decoding,
+    /// where each level halves the width and height of the frame.
+    /// A value of 0 requests the full resolution.
+    ///
+    /// This is only a hint:
+    /// adapters for encodings without multiple resolution levels
+    /// (or which do not support this feature)
+    /// decode the frame at full resolution.
+    pub resolution_level: u32,
+}
+
+impl DecodeFrameOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of resolution levels to discard while decoding.
+    pub fn resolution_level(mut self, resolution_level: u32) -> Self {
+        self.resolution_level = resolution_level;
+        self
+    }
+}
+
+/// The dimensions of a frame effectively decoded by a pixel data reader,
+/// which may be smaller than those declared in the DICOM object
+/// if decoded at a lower resolution level
+/// (see [`DecodeFrameOptions::resolution_level`]).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DecodedFrameSize {
+    /// The number of rows
+    pub rows: u16,
+    /// The number of columns
+    pub cols: u16,
 }
 
 /// Custom options when encoding pixel data into an encapsulated form.
@@ -209,6 +712,18 @@ pub struct EncodeOptions {
     /// If this option is not specified,
     /// the actual effort is decided by the underlying adapter.
     pub effort: Option<u8>,
+
+    /// The maximum absolute error allowed for each sample
+    /// in a near-lossless encoding,
+    /// such as the `NEAR` parameter of JPEG-LS.
+    /// A value of 0 requests a lossless encoding.
+    /// If supported, this takes precedence over `quality`.
+    ///
+    /// Encoders are not required to support this option,
+    /// and encoders of lossless transfer syntaxes ignore it.
+    /// If this option is not specified,
+    /// the error range is derived from the quality.
+    pub near_lossless: Option<u8>,
 }
 
 impl EncodeOptions {
@@ -217,6 +732,101 @@ impl EncodeOptions {
     }
 }
 
+/// Description of the native pixel data to be encoded,
+/// used to query whether a pixel data writer is able to encode it
+/// (see [`PixelDataWriter::supports`]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EncodeSourceProperties {
+    /// The number of rows
+    pub rows: u16,
+    /// The number of columns
+    pub cols: u16,
+    /// The number of samples per pixel
+    pub samples_per_pixel: u16,
+    /// The number of bits allocated per sample
+    pub bits_allocated: u16,
+    /// The number of bits stored per sample
+    pub bits_stored: u16,
+    /// The photometric interpretation
+    pub photometric_interpretation: String,
+}
+
+impl EncodeSourceProperties {
+    /// Collect the encoding source properties of a pixel data object.
+    ///
+    /// Returns `None` if any of the required attributes is missing.
+    pub fn from_object(src: &dyn PixelDataObject) -> Option<Self> {
+        Some(EncodeSourceProperties {
+            rows: src.rows()?,
+            cols: src.cols()?,
+            samples_per_pixel: src.samples_per_pixel()?,
+            bits_allocated: src.bits_allocated()?,
+            bits_stored: src.bits_stored()?,
+            photometric_interpretation: src.photometric_interpretation()?.to_string(),
+        })
+    }
+}
+
+/// A conversion which the pixel data needs to undergo
+/// before it can be encoded by a pixel data writer.
+///
+/// These conversions are expected to be performed
+/// by the caller of the writer,
+/// such as a DICOM object transcoder.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EncodeConversion {
+    /// Convert RGB samples to the `YBR_FULL` color space.
+    RgbToYbrFull,
+    /// Reduce the precision of each sample to 8 bits,
+    /// resulting in 8 bits allocated and 8 bits stored.
+    ReduceTo8Bit,
+}
+
+/// A property of the pixel data
+/// which a pixel data writer may be unable to encode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EncodeProperty {
+    /// Bits Allocated
+    BitsAllocated,
+    /// Bits Stored
+    BitsStored,
+    /// Samples per Pixel
+    SamplesPerPixel,
+    /// Photometric Interpretation
+    PhotometricInterpretation,
+    /// the size of each frame (Rows and Columns)
+    FrameSize,
+}
+
+impl std::fmt::Display for EncodeProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EncodeProperty::BitsAllocated => "bits allocated",
+            EncodeProperty::BitsStored => "bits stored",
+            EncodeProperty::SamplesPerPixel => "samples per pixel",
+            EncodeProperty::PhotometricInterpretation => "photometric interpretation",
+            EncodeProperty::FrameSize => "frame size",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The level of support of a pixel data writer
+/// for encoding pixel data with certain properties.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SupportLevel {
+    /// The pixel data can be encoded as is.
+    Supported,
+    /// The pixel data can be encoded
+    /// after applying the given conversions in order.
+    Partial(Vec<EncodeConversion>),
+    /// The pixel data cannot be encoded
+    /// because of the given property.
+    Unsupported(EncodeProperty),
+}
+
 /// Trait object responsible for decoding
 /// pixel data based on the transfer syntax.
 ///
@@ -245,6 +855,10 @@ pub trait PixelDataReader {
     /// and images in _MONOCHROME2_ continue to be in _MONOCHROME2_).
     fn decode(&self, src: &dyn PixelDataObject, dst: &mut Vec<u8>) -> DecodeResult<()> {
         let frames = src.number_of_frames().unwrap_or(1);
+        // report any issues with the offset table once for the whole object
+        if let Some(layout) = FragmentLayout::from_object(src) {
+            FrameExtractionStrategy::determine_and_warn(&layout);
+        }
         for frame in 0..frames {
             self.decode_frame(src, frame, dst)?;
         }
@@ -282,6 +896,34 @@ pub trait PixelDataReader {
         frame: u32,
         dst: &mut Vec<u8>,
     ) -> DecodeResult<()>;
+
+    /// Decode a single frame of the given DICOM object
+    /// containing encapsulated pixel data,
+    /// as in [`decode_frame`](PixelDataReader::decode_frame),
+    /// while taking the given decoding options into account.
+    ///
+    /// Returns the dimensions of the frame effectively decoded,
+    /// which may differ from those of the object
+    /// when decoding at a lower resolution level.
+    ///
+    /// The default implementation ignores the options
+    /// and decodes the frame at full resolution.
+    fn decode_frame_with_options(
+        &self,
+        src: &dyn PixelDataObject,
+        frame: u32,
+        _options: &DecodeFrameOptions,
+        dst: &mut Vec<u8>,
+    ) -> DecodeResult<DecodedFrameSize> {
+        let rows = src
+            .rows()
+            .context(decode_error::MissingAttributeSnafu { name: "Rows" })?;
+        let cols = src
+            .cols()
+            .context(decode_error::MissingAttributeSnafu { name: "Columns" })?;
+        self.decode_frame(src, frame, dst)?;
+        Ok(DecodedFrameSize { rows, cols })
+    }
 }
 
 /// Trait object responsible for encoding
@@ -290,6 +932,21 @@ pub trait PixelDataReader {
 /// A transfer syntax with support for creating compressed pixel data
 /// would implement these methods.
 pub trait PixelDataWriter {
+    /// Check whether this writer is able to encode
+    /// native pixel data with the given properties,
+    /// and which conversions would be necessary beforehand.
+    ///
+    /// Callers are expected to consult this method
+    /// before calling [`encode`](Self::encode)
+    /// or [`encode_frame`](Self::encode_frame).
+    ///
+    /// The default implementation reports all pixel data as supported,
+    /// deferring any further validation to the encoding methods.
+    fn supports(&self, props: &EncodeSourceProperties) -> SupportLevel {
+        let _ = props;
+        SupportLevel::Supported
+    }
+
     /// Encode a DICOM object's image into the format supported by this adapter,
     /// writing a byte stream of pixel data fragment values
     /// to the given vector `dst`
@@ -297,6 +954,8 @@ pub trait PixelDataWriter {
     ///
     /// New data is appended to `dst` and `offset_table`,
     /// which are not cleared before writing.
+    /// The offsets are relative to the item of the first fragment written,
+    /// and are left out if they do not fit in 32 bits.
     ///
     /// All implementations are required to support
     /// writing the object's pixel data when it is in a _native encoding_.
@@ -318,12 +977,24 @@ pub trait PixelDataWriter {
     ) -> EncodeResult<Vec<AttributeOp>> {
         let frames = src.number_of_frames().unwrap_or(1);
         let mut out = Vec::new();
+        let mut offsets = Vec::with_capacity(frames as usize);
+        let mut offset = 0_u64;
         for frame in 0..frames {
             let mut frame_data = Vec::new();
             out = self.encode_frame(src, frame, options.clone(), &mut frame_data)?;
-            offset_table.push(frame_data.len() as u32 + 8 * (frame + 1));
+            offsets.push(offset);
+            // each fragment item has an 8-byte header and an even length
+            let len = frame_data.len() as u64;
+            offset += 8 + len + len % 2;
             dst.push(frame_data);
         }
+        if let Ok(offsets) = offsets
+            .into_iter()
+            .map(u32::try_from)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            offset_table.extend(offsets);
+        }
         Ok(out)
     }
 
@@ -446,3 +1117,289 @@ impl PixelDataWriter for crate::transfer_syntax::NeverAdapter {
         unreachable!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create the layout of encapsulated pixel data
+    /// with fragments of the given lengths.
+    fn layout(
+        number_of_frames: u32,
+        fragment_lengths: &[u64],
+        offset_table: &[u32],
+    ) -> FragmentLayout {
+        FragmentLayout {
+            number_of_frames,
+            fragment_lengths: Some(fragment_lengths.to_vec()),
+            offset_table: offset_table.to_vec(),
+            extended_offset_table: Vec::new(),
+        }
+    }
+
+    /// Collect the fragment ranges of all frames
+    fn all_frame_fragments(layout: &FragmentLayout) -> Vec<Option<Range<usize>>> {
+        let strategy = FrameExtractionStrategy::determine(layout);
+        (0..layout.number_of_frames)
+            .map(|frame| strategy.frame_fragments(layout, frame))
+            .collect()
+    }
+
+    #[test]
+    fn strategy_native() {
+        let layout = FragmentLayout::native(4);
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::Native
+        );
+        assert_eq!(all_frame_fragments(&layout), vec![None; 4]);
+        assert!(!layout.offset_table_is_consistent());
+    }
+
+    #[test]
+    fn strategy_one_fragment_per_frame() {
+        // empty basic offset table
+        let layout = layout(3, &[10, 20, 30], &[]);
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::OneFragmentPerFrame
+        );
+        assert_eq!(
+            all_frame_fragments(&layout),
+            vec![Some(0..1), Some(1..2), Some(2..3)]
+        );
+        assert_eq!(
+            FrameExtractionStrategy::OneFragmentPerFrame.frame_fragments(&layout, 3),
+            None
+        );
+
+        // matching basic offset table
+        let layout = self::layout(3, &[10, 20, 30], &[0, 18, 46]);
+        assert!(layout.offset_table_is_consistent());
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::OneFragmentPerFrame
+        );
+
+        // the basic offset table disagrees, but is ignored
+        let layout = self::layout(3, &[10, 20, 30], &[0, 20, 46]);
+        assert!(!layout.offset_table_is_consistent());
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::OneFragmentPerFrame
+        );
+        assert_eq!(
+            all_frame_fragments(&layout),
+            vec![Some(0..1), Some(1..2), Some(2..3)]
+        );
+
+        // single frame in a single fragment
+        let layout = self::layout(1, &[64], &[0]);
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::OneFragmentPerFrame
+        );
+        assert_eq!(all_frame_fragments(&layout), vec![Some(0..1)]);
+    }
+
+    #[test]
+    fn strategy_single_frame_all_fragments() {
+        let layout = layout(1, &[10, 20, 30], &[]);
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::SingleFrameAllFragments
+        );
+        assert_eq!(all_frame_fragments(&layout), vec![Some(0..3)]);
+        assert_eq!(
+            FrameExtractionStrategy::SingleFrameAllFragments.frame_fragments(&layout, 1),
+            None
+        );
+
+        // with a basic offset table
+        let layout = self::layout(1, &[10, 20, 30], &[0]);
+        assert!(layout.offset_table_is_consistent());
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::SingleFrameAllFragments
+        );
+        assert_eq!(all_frame_fragments(&layout), vec![Some(0..3)]);
+
+        // too many entries in the basic offset table, ignored
+        let layout = self::layout(1, &[10, 20, 30], &[0, 18]);
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::SingleFrameAllFragments
+        );
+        assert_eq!(all_frame_fragments(&layout), vec![Some(0..3)]);
+
+        // no frames declared
+        let layout = self::layout(0, &[10, 20], &[]);
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::SingleFrameAllFragments
+        );
+
+        // no fragments at all
+        let layout = self::layout(1, &[], &[]);
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::SingleFrameAllFragments
+        );
+        assert_eq!(all_frame_fragments(&layout), vec![None]);
+    }
+
+    #[test]
+    fn strategy_multi_frame_without_offset_table() {
+        // frames cannot be located,
+        // so everything goes to the first frame
+        let layout = layout(3, &[10, 12, 20, 30], &[]);
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::SingleFrameAllFragments
+        );
+        assert_eq!(all_frame_fragments(&layout), vec![Some(0..4), None, None]);
+
+        // fewer fragments than frames
+        let layout = self::layout(3, &[10], &[]);
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::SingleFrameAllFragments
+        );
+        assert_eq!(all_frame_fragments(&layout), vec![Some(0..1), None, None]);
+    }
+
+    #[test]
+    fn strategy_offset_table() {
+        // fragment positions: 0, 18, 38, 66, 104
+        let fragment_lengths = [10, 12, 20, 30, 4];
+        let layout = layout(3, &fragment_lengths, &[0, 38, 66]);
+        assert!(layout.offset_table_is_consistent());
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::OffsetTable
+        );
+        assert_eq!(
+            all_frame_fragments(&layout),
+            vec![Some(0..2), Some(2..3), Some(3..5)]
+        );
+        assert_eq!(
+            FrameExtractionStrategy::OffsetTable.frame_fragments(&layout, 3),
+            None
+        );
+
+        // offset in the middle of a fragment:
+        // the frame starts at the next fragment
+        let layout = self::layout(3, &fragment_lengths, &[0, 20, 66]);
+        assert!(!layout.offset_table_is_consistent());
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::OffsetTable
+        );
+        assert_eq!(
+            all_frame_fragments(&layout),
+            vec![Some(0..2), Some(2..3), Some(3..5)]
+        );
+
+        // first frame not at offset 0:
+        // leading fragments are not part of any frame
+        let layout = self::layout(2, &fragment_lengths, &[18, 66]);
+        assert!(!layout.offset_table_is_consistent());
+        assert_eq!(all_frame_fragments(&layout), vec![Some(1..3), Some(3..5)]);
+
+        // missing entries in the offset table
+        let layout = self::layout(3, &fragment_lengths, &[0, 38]);
+        assert!(!layout.offset_table_is_consistent());
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::OffsetTable
+        );
+        assert_eq!(
+            all_frame_fragments(&layout),
+            vec![Some(0..2), Some(2..5), None]
+        );
+
+        // offset beyond the last fragment
+        let layout = self::layout(3, &fragment_lengths, &[0, 38, 200]);
+        assert!(!layout.offset_table_is_consistent());
+        assert_eq!(
+            all_frame_fragments(&layout),
+            vec![Some(0..2), Some(2..5), None]
+        );
+
+        // decreasing offsets:
+        // frames out of order are not attributed fragments
+        // which belong to previous frames
+        let layout = self::layout(3, &fragment_lengths, &[0, 66, 38]);
+        assert!(!layout.offset_table_is_consistent());
+        assert_eq!(all_frame_fragments(&layout), vec![Some(0..3), None, None]);
+        let layout = self::layout(4, &fragment_lengths, &[0, 66, 18, 38]);
+        assert_eq!(
+            all_frame_fragments(&layout),
+            vec![Some(0..3), None, None, None]
+        );
+    }
+
+    #[test]
+    fn strategy_extended_offset_table() {
+        // fragment positions: 0, 18, 38, 66, 104
+        let fragment_lengths = [10, 12, 20, 30, 4];
+        let layout = layout(3, &fragment_lengths, &[]).with_extended_offset_table(vec![0, 38, 66]);
+        assert!(layout.offset_table_is_consistent());
+        assert_eq!(
+            FrameExtractionStrategy::determine(&layout),
+            FrameExtractionStrategy::OffsetTable
+        );
+        assert_eq!(
+            all_frame_fragments(&layout),
+            vec![Some(0..2), Some(2..3), Some(3..5)]
+        );
+
+        // conflicting basic offset table, the extended one prevails
+        let layout = self::layout(3, &fragment_lengths, &[0, 18, 38])
+            .with_extended_offset_table(vec![0, 38, 66]);
+        assert!(layout.offset_table_is_consistent());
+        assert_eq!(
+            all_frame_fragments(&layout),
+            vec![Some(0..2), Some(2..3), Some(3..5)]
+        );
+
+        // frames located through the extended offset table
+        let raw = RawPixelData {
+            fragments: vec![vec![1; 10], vec![2; 12], vec![3; 20]].into(),
+            offset_table: Default::default(),
+        };
+        let layout = raw.layout(2).with_extended_offset_table(vec![0, 38]);
+        let frame = raw.frame_data(&layout, 0).unwrap();
+        assert_eq!(frame.len(), 22);
+        let frame = raw.frame_data(&layout, 1).unwrap();
+        assert_eq!(&*frame, &[3; 20]);
+    }
+
+    #[test]
+    fn raw_pixel_data_frame_data() {
+        let raw = RawPixelData {
+            fragments: vec![vec![1; 10], vec![2; 12], vec![3; 20]].into(),
+            offset_table: vec![0, 38].into(),
+        };
+        let two_frames = raw.layout(2);
+        assert_eq!(two_frames, layout(2, &[10, 12, 20], &[0, 38]));
+
+        // frame in multiple fragments
+        let frame = raw.frame_data(&two_frames, 0).unwrap();
+        assert!(matches!(frame, Cow::Owned(_)));
+        assert_eq!(frame.len(), 22);
+        assert_eq!(&frame[..10], &[1; 10]);
+        assert_eq!(&frame[10..], &[2; 12]);
+
+        // frame in a single fragment is borrowed
+        let frame = raw.frame_data(&two_frames, 1).unwrap();
+        assert!(matches!(frame, Cow::Borrowed(_)));
+        assert_eq!(&*frame, &[3; 20]);
+
+        assert_eq!(raw.frame_data(&two_frames, 2), None);
+
+        // one fragment per frame
+        let frame = raw.frame_data(&raw.layout(3), 2).unwrap();
+        assert_eq!(&*frame, &[3; 20]);
+    }
+}
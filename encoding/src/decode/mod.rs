@@ -2,10 +2,11 @@
 
 use self::explicit_le::ExplicitVRLittleEndianDecoder;
 use self::implicit_le::{ImplicitVRLittleEndianDecoder, StandardImplicitVRLittleEndianDecoder};
+use byteordered::byteorder::{BigEndian, ByteOrder, LittleEndian};
 use byteordered::Endianness;
-use dicom_core::header::{DataElementHeader, SequenceItemHeader};
+use dicom_core::header::{DataElementHeader, Length, SequenceItemHeader};
 use dicom_core::Tag;
-use snafu::{Backtrace, Snafu};
+use snafu::{Backtrace, ResultExt, Snafu};
 use std::io::{self, Read};
 
 pub mod basic;
@@ -651,10 +652,97 @@ where
     }
 }
 
+/// Read and decode a sequence item header
+/// (item, item delimiter, or sequence delimiter),
+/// recognizing headers encoded in either byte order.
+///
+/// The header is first interpreted in the given byte order.
+/// If it is not an item header in that byte order
+/// but it is one in the opposite byte order,
+/// which some non-conformant implementations produce
+/// for encapsulated pixel data in big endian data sets,
+/// the header is interpreted in the opposite byte order.
+///
+/// Returns the item header
+/// alongside the byte order in which it was encoded.
+pub fn decode_item_header_any_order<S>(
+    source: &mut S,
+    endianness: Endianness,
+) -> Result<(SequenceItemHeader, Endianness)>
+where
+    S: ?Sized + Read,
+{
+    let mut buf = [0u8; 8];
+    source.read_exact(&mut buf).context(ReadItemHeaderSnafu)?;
+
+    let read_fields = |endianness| match endianness {
+        Endianness::Little => (
+            LittleEndian::read_u16(&buf[0..2]),
+            LittleEndian::read_u16(&buf[2..4]),
+            LittleEndian::read_u32(&buf[4..8]),
+        ),
+        Endianness::Big => (
+            BigEndian::read_u16(&buf[0..2]),
+            BigEndian::read_u16(&buf[2..4]),
+            BigEndian::read_u32(&buf[4..8]),
+        ),
+    };
+
+    let opposite = match endianness {
+        Endianness::Little => Endianness::Big,
+        Endianness::Big => Endianness::Little,
+    };
+    let fields = read_fields(endianness);
+    let swapped = read_fields(opposite);
+    let (endianness, (group, element, len)) = if fields.0 != 0xFFFE && swapped.0 == 0xFFFE {
+        (opposite, swapped)
+    } else {
+        (endianness, fields)
+    };
+
+    let header =
+        SequenceItemHeader::new((group, element), Length(len)).context(BadSequenceHeaderSnafu)?;
+    Ok((header, endianness))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn decode_item_header_in_either_byte_order() {
+        // item with length 16, little endian
+        let le: &[u8] = &[0xFE, 0xFF, 0x00, 0xE0, 0x10, 0x00, 0x00, 0x00];
+        // item with length 16, big endian
+        let be: &[u8] = &[0xFF, 0xFE, 0xE0, 0x00, 0x00, 0x00, 0x00, 0x10];
+        // sequence delimiter, big endian
+        let be_delimiter: &[u8] = &[0xFF, 0xFE, 0xE0, 0xDD, 0x00, 0x00, 0x00, 0x00];
+
+        let item = SequenceItemHeader::Item { len: Length(16) };
+
+        for endianness in [Endianness::Little, Endianness::Big] {
+            assert_eq!(
+                decode_item_header_any_order(&mut &le[..], endianness).unwrap(),
+                (item, Endianness::Little)
+            );
+            assert_eq!(
+                decode_item_header_any_order(&mut &be[..], endianness).unwrap(),
+                (item, Endianness::Big)
+            );
+            assert_eq!(
+                decode_item_header_any_order(&mut &be_delimiter[..], endianness).unwrap(),
+                (SequenceItemHeader::SequenceDelimiter, Endianness::Big)
+            );
+        }
+
+        // not an item header in any byte order
+        let bad: &[u8] = &[0x08, 0x00, 0x16, 0x00, 0x10, 0x00, 0x00, 0x00];
+        assert!(matches!(
+            decode_item_header_any_order(&mut &bad[..], Endianness::Big),
+            Err(Error::BadSequenceHeader { .. })
+        ));
+    }
+
     fn is_decode_from<T: DecodeFrom<dyn Read>>(_decoder: &T) {}
 
     #[allow(unused)]
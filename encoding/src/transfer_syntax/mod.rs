@@ -32,7 +32,9 @@ use crate::encode::{
     explicit_be::ExplicitVRBigEndianEncoder, explicit_le::ExplicitVRLittleEndianEncoder,
     implicit_le::ImplicitVRLittleEndianEncoder, EncodeTo, EncoderFor,
 };
+use std::cell::RefCell;
 use std::io::{Read, Write};
+use std::rc::Rc;
 
 pub use byteordered::Endianness;
 
@@ -348,6 +350,38 @@ pub type DynDataRWAdapter = Box<
         + Sync,
 >;
 
+/// Pass a whole data set through the writer of a data set adapter,
+/// obtaining the adapted bytes
+/// (such as the deflated data set
+/// in _Deflated Explicit VR Little Endian_).
+///
+/// The adapted writer is dropped before returning,
+/// so that its output is complete.
+pub fn adapt_bytes(adapter: &DynDataRWAdapter, data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let sink = SharedSink::default();
+    let mut writer = adapter.adapt_writer(Box::new(sink.clone()));
+    writer.write_all(data)?;
+    // adapted writers may only complete their output when dropped
+    drop(writer);
+    Ok(sink.0.take())
+}
+
+/// A byte sink which can be recovered
+/// after being handed over to a data set adapter.
+#[derive(Clone, Default)]
+struct SharedSink(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 impl<T, R, W> DataRWAdapter<R, W> for &'_ T
 where
     T: DataRWAdapter<R, W>,
@@ -503,6 +537,12 @@ impl<D, R, W> TransferSyntax<D, R, W> {
         self.byte_order
     }
 
+    /// Check whether this transfer syntax uses an explicit value representation
+    /// in data element headers.
+    pub const fn explicit_vr(&self) -> bool {
+        self.explicit_vr
+    }
+
     /// Obtain this transfer syntax' codec specification.
     pub fn codec(&self) -> &Codec<D, R, W> {
         &self.codec
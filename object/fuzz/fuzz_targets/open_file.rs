@@ -20,10 +20,14 @@ fn fuzz(data: &[u8]) -> Result<(), Box<dyn Error>> {
     for g in 0..=0x07FF {
         obj.remove_element(dicom_object::Tag(g, 0x0000));
     }
-    // serialize object back to bytes
+    // serialize object back to bytes,
+    // as is even if the meta group does not match the data set
     let mut bytes = Vec::new();
-    obj.write_all(&mut bytes)
-        .expect("writing DICOM file should always be successful");
+    obj.write_all_with_options(
+        &mut bytes,
+        dicom_object::WriteOptions::new().skip_consistency_checks(),
+    )
+    .expect("writing DICOM file should always be successful");
 
     // deserialize back to object
     let obj2 = dicom_object::from_reader(bytes.as_slice())
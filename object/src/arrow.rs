@@ -0,0 +1,560 @@
+//! Conversion of DICOM objects into [Apache Arrow] record batches.
+//!
+//! This module is only available with the `arrow` Cargo feature.
+//!
+//! An [`AttributeSchema`] declares which attributes to extract,
+//! under which column names and with which data types.
+//! [`to_record_batch`] then builds one row per DICOM object,
+//! which can be written to Parquet or any other columnar format
+//! supported by the Arrow ecosystem.
+//!
+//! - Multi-valued attributes can be mapped to lists of strings
+//!   ([`ColumnType::Utf8List`]).
+//!   Text columns join all values with a backslash,
+//!   whereas numeric and date columns take the first value.
+//! - Missing attributes, empty values,
+//!   and values which cannot be converted to the column type
+//!   become nulls.
+//! - Selectors into sequences without an explicit item index,
+//!   such as `(tags::REFERENCED_IMAGE_SEQUENCE, tags::REFERENCED_SOP_INSTANCE_UID)`,
+//!   take the first item of the sequence.
+//!
+//! [Apache Arrow]: https://arrow.apache.org
+//!
+//! # Example
+//!
+//! ```
+//! # use dicom_core::{DataElement, VR};
+//! # use dicom_dictionary_std::tags;
+//! # use dicom_object::InMemDicomObject;
+//! use dicom_object::arrow::{to_record_batch, AttributeSchema, ColumnType};
+//!
+//! let obj = InMemDicomObject::from_element_iter([
+//!     DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+//!     DataElement::new(tags::ROWS, VR::US, dicom_core::PrimitiveValue::from(512_u16)),
+//! ]);
+//!
+//! let schema = AttributeSchema::new()
+//!     .with_column("PatientName", tags::PATIENT_NAME, ColumnType::Utf8)
+//!     .with_column("Rows", tags::ROWS, ColumnType::Int64)
+//!     .with_column("StudyDate", tags::STUDY_DATE, ColumnType::Date32);
+//!
+//! let batch = to_record_batch(&[&obj], &schema)?;
+//! assert_eq!(batch.num_rows(), 1);
+//! assert_eq!(batch.num_columns(), 3);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+use crate::mem::InMemDicomObject;
+use arrow::array::{
+    ArrayRef, Date32Builder, Float64Builder, Int64Builder, ListBuilder, StringBuilder,
+};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use dicom_core::chrono::Datelike;
+use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
+use dicom_core::ops::{AttributeSelector, AttributeSelectorStep};
+use dicom_core::value::Value;
+use dicom_core::{PrimitiveValue, VR};
+use snafu::{Backtrace, ResultExt, Snafu};
+use std::sync::Arc;
+
+/// An error which may occur when converting DICOM objects
+/// into an Arrow record batch.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum Error {
+    /// Could not build record batch
+    BuildRecordBatch {
+        source: ArrowError,
+        backtrace: Backtrace,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The number of days from the Common Era to the Unix epoch.
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// The data type of a column in an [`AttributeSchema`].
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ColumnType {
+    /// UTF-8 text, with multiple values joined by a backslash
+    Utf8,
+    /// 64-bit signed integer
+    Int64,
+    /// 64-bit floating point number
+    Float64,
+    /// Date as the number of days since the Unix epoch
+    Date32,
+    /// List of UTF-8 text values,
+    /// one per value of the attribute
+    Utf8List,
+}
+
+impl ColumnType {
+    /// Obtain the Arrow data type of a column of this type.
+    pub fn data_type(self) -> DataType {
+        match self {
+            ColumnType::Utf8 => DataType::Utf8,
+            ColumnType::Int64 => DataType::Int64,
+            ColumnType::Float64 => DataType::Float64,
+            ColumnType::Date32 => DataType::Date32,
+            ColumnType::Utf8List => {
+                DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)))
+            }
+        }
+    }
+
+    /// Determine a suitable column type for values of the given VR,
+    /// or `None` if values of this VR should not be extracted
+    /// (sequences and binary data).
+    fn from_vr(vr: VR) -> Option<Self> {
+        match vr {
+            VR::IS | VR::SS | VR::US | VR::SL | VR::UL | VR::SV | VR::UV => Some(ColumnType::Int64),
+            VR::DS | VR::FL | VR::FD => Some(ColumnType::Float64),
+            VR::DA => Some(ColumnType::Date32),
+            VR::SQ | VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN => None,
+            _ => Some(ColumnType::Utf8),
+        }
+    }
+
+    /// Combine two column types inferred for the same attribute.
+    fn merge(self, other: ColumnType) -> Self {
+        use ColumnType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Utf8List, _) | (_, Utf8List) => Utf8List,
+            (Int64, Float64) | (Float64, Int64) => Float64,
+            _ => Utf8,
+        }
+    }
+}
+
+/// A column declared in an [`AttributeSchema`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeColumn {
+    name: String,
+    selector: AttributeSelector,
+    column_type: ColumnType,
+}
+
+impl AttributeColumn {
+    /// Create a new column description.
+    pub fn new(
+        name: impl Into<String>,
+        selector: impl Into<AttributeSelector>,
+        column_type: ColumnType,
+    ) -> Self {
+        AttributeColumn {
+            name: name.into(),
+            selector: selector.into(),
+            column_type,
+        }
+    }
+
+    /// The name of the column.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The selector of the attribute to extract into this column.
+    pub fn selector(&self) -> &AttributeSelector {
+        &self.selector
+    }
+
+    /// The data type of the column.
+    pub fn column_type(&self) -> ColumnType {
+        self.column_type
+    }
+
+    /// Obtain the Arrow field of this column.
+    pub fn field(&self) -> Field {
+        Field::new(&self.name, self.column_type.data_type(), true)
+    }
+}
+
+/// A mapping from DICOM attributes to the columns of a record batch.
+///
+/// Columns appear in the record batch in the order in which they were added.
+/// All columns are nullable.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AttributeSchema {
+    columns: Vec<AttributeColumn>,
+}
+
+impl AttributeSchema {
+    /// Create a new schema without any columns.
+    pub fn new() -> Self {
+        AttributeSchema::default()
+    }
+
+    /// Add a column to the schema.
+    pub fn with_column(
+        mut self,
+        name: impl Into<String>,
+        selector: impl Into<AttributeSelector>,
+        column_type: ColumnType,
+    ) -> Self {
+        self.push_column(AttributeColumn::new(name, selector, column_type));
+        self
+    }
+
+    /// Add a column to the schema in place.
+    pub fn push_column(&mut self, column: AttributeColumn) {
+        self.columns.push(column);
+    }
+
+    /// Obtain the columns of this schema.
+    pub fn columns(&self) -> &[AttributeColumn] {
+        &self.columns
+    }
+
+    /// Obtain the equivalent Arrow schema.
+    pub fn to_arrow_schema(&self) -> Schema {
+        Schema::new(
+            self.columns
+                .iter()
+                .map(AttributeColumn::field)
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    /// Infer a schema from a sample of DICOM objects.
+    ///
+    /// A column is declared for every attribute found in the sample,
+    /// in order of first appearance,
+    /// named after its keyword in the object's data dictionary
+    /// (or after its tag if the attribute is unknown).
+    /// The column type is derived from the value representation,
+    /// and attributes with more than one value in any of the objects
+    /// are mapped to lists of strings.
+    ///
+    /// Attributes in sequences are flattened from the first item,
+    /// with the keywords of each step joined by a dot
+    /// (such as `ReferencedImageSequence.ReferencedSOPInstanceUID`).
+    /// Binary attributes are skipped.
+    pub fn infer<D>(objects: &[&InMemDicomObject<D>]) -> Self
+    where
+        D: DataDictionary,
+        D: Clone,
+    {
+        let mut schema = AttributeSchema::new();
+        for obj in objects {
+            schema.infer_from(obj, obj.dictionary(), &mut Vec::new(), "");
+        }
+        schema
+    }
+
+    fn infer_from<D, Di>(
+        &mut self,
+        obj: &InMemDicomObject<D>,
+        dict: &Di,
+        path: &mut Vec<AttributeSelectorStep>,
+        prefix: &str,
+    ) where
+        D: DataDictionary,
+        D: Clone,
+        Di: DataDictionary,
+    {
+        for elem in obj {
+            let tag = elem.tag();
+            let name = match dict.by_tag(tag) {
+                Some(entry) => format!("{}{}", prefix, entry.alias()),
+                None => format!("{}{}", prefix, tag),
+            };
+
+            match elem.value() {
+                Value::Sequence(seq) => {
+                    if let Some(item) = seq.items().first() {
+                        path.push(AttributeSelectorStep::Nested { tag, item: 0 });
+                        self.infer_from(item, dict, path, &format!("{}.", name));
+                        path.pop();
+                    }
+                }
+                Value::PixelSequence(_) => {}
+                Value::Primitive(value) => {
+                    let Some(mut column_type) = ColumnType::from_vr(elem.vr()) else {
+                        continue;
+                    };
+                    if value.multiplicity() > 1 {
+                        column_type = ColumnType::Utf8List;
+                    }
+
+                    let selector = AttributeSelector::new(
+                        path.iter()
+                            .copied()
+                            .chain([AttributeSelectorStep::Tag(tag)]),
+                    )
+                    .expect("selector should have at least one step");
+
+                    match self.columns.iter_mut().find(|c| c.selector == selector) {
+                        Some(column) => {
+                            column.column_type = column.column_type.merge(column_type);
+                        }
+                        None => self.push_column(AttributeColumn::new(name, selector, column_type)),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Build an Arrow record batch from the given DICOM objects,
+/// with one row per object and one column per attribute in the schema.
+///
+/// See the [module-level documentation](self)
+/// for how attribute values are mapped to columns.
+pub fn to_record_batch<D>(
+    objects: &[&InMemDicomObject<D>],
+    schema: &AttributeSchema,
+) -> Result<RecordBatch>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    let arrays = schema
+        .columns
+        .iter()
+        .map(|column| build_column(objects, column))
+        .collect();
+
+    RecordBatch::try_new_with_options(
+        Arc::new(schema.to_arrow_schema()),
+        arrays,
+        &RecordBatchOptions::new().with_row_count(Some(objects.len())),
+    )
+    .context(BuildRecordBatchSnafu)
+}
+
+fn build_column<D>(objects: &[&InMemDicomObject<D>], column: &AttributeColumn) -> ArrayRef
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    let values = objects
+        .iter()
+        .map(|obj| primitive_at(obj, &column.selector));
+
+    match column.column_type {
+        ColumnType::Utf8 => {
+            let mut builder = StringBuilder::new();
+            for value in values {
+                builder.append_option(value.map(|v| v.to_str()));
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnType::Int64 => {
+            let mut builder = Int64Builder::new();
+            for value in values {
+                builder.append_option(value.and_then(|v| v.to_int::<i64>().ok()));
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnType::Float64 => {
+            let mut builder = Float64Builder::new();
+            for value in values {
+                builder.append_option(value.and_then(|v| v.to_float64().ok()));
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnType::Date32 => {
+            let mut builder = Date32Builder::new();
+            for value in values {
+                let days = value
+                    .and_then(|v| v.to_date().ok())
+                    .and_then(|date| date.to_naive_date().ok())
+                    .map(|date| date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE);
+                builder.append_option(days);
+            }
+            Arc::new(builder.finish())
+        }
+        ColumnType::Utf8List => {
+            let mut builder = ListBuilder::new(StringBuilder::new());
+            for value in values {
+                match value {
+                    Some(value) => {
+                        for s in value.to_multi_str().iter() {
+                            builder.values().append_value(s);
+                        }
+                        builder.append(true);
+                    }
+                    None => builder.append_null(),
+                }
+            }
+            Arc::new(builder.finish())
+        }
+    }
+}
+
+/// Fetch the non-empty primitive value at the given selector.
+fn primitive_at<'a, D>(
+    obj: &'a InMemDicomObject<D>,
+    selector: &AttributeSelector,
+) -> Option<&'a PrimitiveValue>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    obj.value_at(selector.clone())
+        .ok()?
+        .primitive()
+        .filter(|v| v.multiplicity() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Array, AsArray};
+    use arrow::datatypes::{Date32Type, Float64Type, Int64Type};
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement};
+    use dicom_dictionary_std::tags;
+
+    fn sample_objects() -> Vec<InMemDicomObject> {
+        vec![
+            InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::IMAGE_TYPE,
+                    VR::CS,
+                    dicom_value!(Strs, ["ORIGINAL", "PRIMARY"]),
+                ),
+                DataElement::new(tags::STUDY_DATE, VR::DA, "20240102"),
+                DataElement::new(
+                    tags::REFERENCED_IMAGE_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                        DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "1.2.3.4\0"),
+                    ])]),
+                ),
+                DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+                DataElement::new(tags::SLICE_THICKNESS, VR::DS, "2.5 "),
+                DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [512])),
+            ]),
+            InMemDicomObject::from_element_iter([
+                DataElement::new(tags::IMAGE_TYPE, VR::CS, "DERIVED"),
+                DataElement::new(tags::PATIENT_NAME, VR::PN, "Roe^Jane"),
+                DataElement::new(tags::SLICE_THICKNESS, VR::DS, "1"),
+            ]),
+            InMemDicomObject::from_element_iter([
+                DataElement::new(tags::STUDY_DATE, VR::DA, "19700101"),
+                DataElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::Empty),
+                DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [256])),
+                DataElement::new(tags::PIXEL_DATA, VR::OB, dicom_value!(U8, [0, 0, 0, 0])),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn record_batch_from_objects() {
+        let objects = sample_objects();
+        let objects: Vec<_> = objects.iter().collect();
+
+        let schema = AttributeSchema::new()
+            .with_column("PatientName", tags::PATIENT_NAME, ColumnType::Utf8)
+            .with_column("Rows", tags::ROWS, ColumnType::Int64)
+            .with_column("SliceThickness", tags::SLICE_THICKNESS, ColumnType::Float64)
+            .with_column("StudyDate", tags::STUDY_DATE, ColumnType::Date32)
+            .with_column("ImageType", tags::IMAGE_TYPE, ColumnType::Utf8List)
+            .with_column(
+                "ReferencedSOPInstanceUID",
+                (
+                    tags::REFERENCED_IMAGE_SEQUENCE,
+                    tags::REFERENCED_SOP_INSTANCE_UID,
+                ),
+                ColumnType::Utf8,
+            );
+
+        let batch = to_record_batch(&objects, &schema).unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(batch.schema().as_ref(), &schema.to_arrow_schema());
+
+        let patient_name = batch.column(0).as_string::<i32>();
+        assert_eq!(patient_name.value(0), "Doe^John");
+        assert_eq!(patient_name.value(1), "Roe^Jane");
+        assert!(patient_name.is_null(2));
+
+        let rows = batch.column(1).as_primitive::<Int64Type>();
+        assert_eq!(rows.value(0), 512);
+        assert!(rows.is_null(1));
+        assert_eq!(rows.value(2), 256);
+
+        let slice_thickness = batch.column(2).as_primitive::<Float64Type>();
+        assert_eq!(slice_thickness.value(0), 2.5);
+        assert_eq!(slice_thickness.value(1), 1.);
+        assert!(slice_thickness.is_null(2));
+
+        let study_date = batch.column(3).as_primitive::<Date32Type>();
+        // 2024-01-02 is 19724 days after the Unix epoch
+        assert_eq!(study_date.value(0), 19_724);
+        assert!(study_date.is_null(1));
+        assert_eq!(study_date.value(2), 0);
+
+        let image_type = batch.column(4).as_list::<i32>();
+        let first = image_type.value(0);
+        let first = first.as_string::<i32>();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first.value(0), "ORIGINAL");
+        assert_eq!(first.value(1), "PRIMARY");
+        let second = image_type.value(1);
+        let second = second.as_string::<i32>();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second.value(0), "DERIVED");
+        assert!(image_type.is_null(2));
+
+        let referenced_uid = batch.column(5).as_string::<i32>();
+        assert_eq!(referenced_uid.value(0), "1.2.3.4");
+        assert!(referenced_uid.is_null(1));
+        assert!(referenced_uid.is_null(2));
+    }
+
+    #[test]
+    fn infer_schema_from_objects() {
+        let objects = sample_objects();
+        let objects: Vec<_> = objects.iter().collect();
+
+        let schema = AttributeSchema::infer(&objects);
+        let columns: Vec<_> = schema
+            .columns()
+            .iter()
+            .map(|c| (c.name(), c.column_type()))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                ("ImageType", ColumnType::Utf8List),
+                ("StudyDate", ColumnType::Date32),
+                (
+                    "ReferencedImageSequence.ReferencedSOPInstanceUID",
+                    ColumnType::Utf8
+                ),
+                ("PatientName", ColumnType::Utf8),
+                ("SliceThickness", ColumnType::Float64),
+                ("Rows", ColumnType::Int64),
+            ]
+        );
+        assert_eq!(
+            schema.columns()[2].selector(),
+            &AttributeSelector::from((
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                0,
+                tags::REFERENCED_SOP_INSTANCE_UID
+            )),
+        );
+
+        let batch = to_record_batch(&objects, &schema).unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(batch.num_columns(), 6);
+    }
+
+    #[test]
+    fn record_batch_without_columns() {
+        let objects = sample_objects();
+        let objects: Vec<_> = objects.iter().collect();
+
+        let batch = to_record_batch(&objects, &AttributeSchema::new()).unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(batch.num_columns(), 0);
+    }
+}
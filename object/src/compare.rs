@@ -0,0 +1,543 @@
+//! Structural comparison of in-memory DICOM objects.
+//!
+//! [`InMemDicomObject::deep_compare`] walks two objects side by side
+//! and reports every difference found,
+//! each one identified by the [attribute selector](AttributeSelector)
+//! of the element concerned.
+//! This is useful for test assertions and for validating migrations,
+//! where some attributes are expected to change
+//! (such as timestamps and generated UIDs)
+//! and floating point values may suffer from rounding errors.
+//!
+//! ```
+//! # use dicom_core::{DataElement, VR};
+//! # use dicom_dictionary_std::tags;
+//! # use dicom_object::InMemDicomObject;
+//! use dicom_object::compare::{CompareOptions, DifferenceKind};
+//!
+//! let left = InMemDicomObject::from_element_iter([
+//!     DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.1"),
+//!     DataElement::new(tags::SLICE_THICKNESS, VR::DS, "2.5"),
+//!     DataElement::new(tags::MODALITY, VR::CS, "CT"),
+//! ]);
+//! let right = InMemDicomObject::from_element_iter([
+//!     DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.2"),
+//!     DataElement::new(tags::SLICE_THICKNESS, VR::DS, "2.500001"),
+//!     DataElement::new(tags::MODALITY, VR::CS, "MR"),
+//! ]);
+//!
+//! let options = CompareOptions::new()
+//!     .ignore_tag(tags::SOP_INSTANCE_UID)
+//!     .float_tolerance(1e-4);
+//! let differences = left.deep_compare(&right, &options);
+//!
+//! assert_eq!(differences.len(), 1);
+//! assert_eq!(differences[0].selector.last_tag(), tags::MODALITY);
+//! assert!(matches!(differences[0].kind, DifferenceKind::ValueMismatch { .. }));
+//! ```
+use std::collections::BTreeSet;
+use std::fmt;
+
+use dicom_core::header::{HasLength, Header};
+use dicom_core::ops::{AttributeSelector, AttributeSelectorStep};
+use dicom_core::value::Value;
+use dicom_core::{DataDictionary, PrimitiveValue, Tag, VR};
+
+use crate::mem::InMemElement;
+use crate::InMemDicomObject;
+
+/// Options for the comparison of two DICOM objects.
+///
+/// By default,
+/// no attributes are ignored,
+/// floating point values need to be numerically equal,
+/// and an absent attribute is different from an empty one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompareOptions {
+    /// the tags of the attributes to leave out, at any nesting level
+    ignore_tags: BTreeSet<Tag>,
+    /// the absolute tolerance for floating point values
+    float_tolerance: f64,
+    /// whether an empty attribute is equivalent to an absent one
+    absent_as_empty: bool,
+}
+
+impl CompareOptions {
+    /// Create a new set of comparison options with the defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Leave out the attribute with the given tag from the comparison,
+    /// both at the root and inside sequence items.
+    pub fn ignore_tag(mut self, tag: Tag) -> Self {
+        self.ignore_tags.insert(tag);
+        self
+    }
+
+    /// Leave out all attributes with the given tags from the comparison,
+    /// both at the root and inside sequence items.
+    pub fn ignore_tags(mut self, tags: impl IntoIterator<Item = Tag>) -> Self {
+        self.ignore_tags.extend(tags);
+        self
+    }
+
+    /// Set the absolute tolerance when comparing floating point values,
+    /// which applies to attributes with the value representations
+    /// FD, FL, DS, OD, and OF.
+    ///
+    /// Values of these attributes are always compared numerically,
+    /// so that `"1.0"` and `"1"` are equal decimal strings
+    /// regardless of tolerance.
+    pub fn float_tolerance(mut self, tolerance: f64) -> Self {
+        self.float_tolerance = tolerance;
+        self
+    }
+
+    /// Set whether an attribute with an empty value
+    /// (or a sequence without items)
+    /// is equivalent to the attribute being absent.
+    pub fn absent_as_empty(mut self, absent_as_empty: bool) -> Self {
+        self.absent_as_empty = absent_as_empty;
+        self
+    }
+}
+
+/// A difference found between two DICOM objects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// the selector of the attribute which differs
+    pub selector: AttributeSelector,
+    /// the kind of difference
+    pub kind: DifferenceKind,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.selector, self.kind)
+    }
+}
+
+/// The kind of difference found in an attribute.
+///
+/// "Left" refers to the object on which the comparison was called,
+/// "right" to the object given as argument.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DifferenceKind {
+    /// The attribute is only present in the right object
+    MissingLeft,
+    /// The attribute is only present in the left object
+    MissingRight,
+    /// The attribute has a different value representation
+    VrMismatch { left: VR, right: VR },
+    /// The attribute has a different primitive value
+    ValueMismatch {
+        left: PrimitiveValue,
+        right: PrimitiveValue,
+    },
+    /// The sequence has a different number of items
+    ItemCountMismatch { left: usize, right: usize },
+    /// The attribute holds a different kind of value
+    /// (primitive value, data set sequence, or encapsulated pixel data)
+    ValueKindMismatch,
+    /// The encapsulated pixel data has a different offset table or fragments
+    FragmentMismatch,
+}
+
+impl fmt::Display for DifferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DifferenceKind::MissingLeft => f.write_str("missing in left object"),
+            DifferenceKind::MissingRight => f.write_str("missing in right object"),
+            DifferenceKind::VrMismatch { left, right } => {
+                write!(f, "VR {} != {}", left, right)
+            }
+            DifferenceKind::ValueMismatch { left, right } => {
+                write!(f, "value [{}] != [{}]", left.to_str(), right.to_str())
+            }
+            DifferenceKind::ItemCountMismatch { left, right } => {
+                write!(f, "{} items != {} items", left, right)
+            }
+            DifferenceKind::ValueKindMismatch => f.write_str("different kinds of value"),
+            DifferenceKind::FragmentMismatch => f.write_str("different pixel data fragments"),
+        }
+    }
+}
+
+impl<D> InMemDicomObject<D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    /// Compare this object with another one, element by element,
+    /// returning all differences found.
+    ///
+    /// Sequences are compared item by item, by index,
+    /// with differences inside items reported
+    /// by the full selector of the nested attribute.
+    /// An empty list means that both objects are equivalent
+    /// under the given options.
+    ///
+    /// See the [`compare`](crate::compare) module for an example.
+    pub fn deep_compare(&self, other: &Self, options: &CompareOptions) -> Vec<Difference> {
+        let mut differences = Vec::new();
+        compare_objects(self, other, options, &[], &mut differences);
+        differences
+    }
+
+    /// Check whether this object is equivalent to another one
+    /// under the given comparison options.
+    ///
+    /// This is the same as checking whether
+    /// [`deep_compare`](Self::deep_compare) finds no differences.
+    pub fn is_equivalent(&self, other: &Self, options: &CompareOptions) -> bool {
+        self.deep_compare(other, options).is_empty()
+    }
+}
+
+fn compare_objects<D>(
+    left: &InMemDicomObject<D>,
+    right: &InMemDicomObject<D>,
+    options: &CompareOptions,
+    path: &[AttributeSelectorStep],
+    differences: &mut Vec<Difference>,
+) where
+    D: DataDictionary,
+    D: Clone,
+{
+    let tags: BTreeSet<Tag> = left.tags().chain(right.tags()).collect();
+
+    for tag in tags {
+        if options.ignore_tags.contains(&tag) {
+            continue;
+        }
+
+        let kind = match (left.get(tag), right.get(tag)) {
+            (Some(l), Some(r)) => {
+                compare_elements(l, r, options, path, differences);
+                continue;
+            }
+            (Some(l), None) => {
+                if options.absent_as_empty && is_empty_element(l) {
+                    continue;
+                }
+                DifferenceKind::MissingRight
+            }
+            (None, Some(r)) => {
+                if options.absent_as_empty && is_empty_element(r) {
+                    continue;
+                }
+                DifferenceKind::MissingLeft
+            }
+            (None, None) => unreachable!("tag should be in at least one of the objects"),
+        };
+        differences.push(Difference {
+            selector: selector_at(path, AttributeSelectorStep::Tag(tag)),
+            kind,
+        });
+    }
+}
+
+fn compare_elements<D>(
+    left: &InMemElement<D>,
+    right: &InMemElement<D>,
+    options: &CompareOptions,
+    path: &[AttributeSelectorStep],
+    differences: &mut Vec<Difference>,
+) where
+    D: DataDictionary,
+    D: Clone,
+{
+    let tag = left.tag();
+    let selector = || selector_at(path, AttributeSelectorStep::Tag(tag));
+
+    if left.vr() != right.vr() {
+        differences.push(Difference {
+            selector: selector(),
+            kind: DifferenceKind::VrMismatch {
+                left: left.vr(),
+                right: right.vr(),
+            },
+        });
+        return;
+    }
+
+    match (left.value(), right.value()) {
+        (Value::Primitive(l), Value::Primitive(r)) => {
+            if !primitive_values_match(left.vr(), l, r, options) {
+                differences.push(Difference {
+                    selector: selector(),
+                    kind: DifferenceKind::ValueMismatch {
+                        left: l.clone(),
+                        right: r.clone(),
+                    },
+                });
+            }
+        }
+        (Value::Sequence(l), Value::Sequence(r)) => {
+            let (l, r) = (l.items(), r.items());
+            if l.len() != r.len() {
+                differences.push(Difference {
+                    selector: selector(),
+                    kind: DifferenceKind::ItemCountMismatch {
+                        left: l.len(),
+                        right: r.len(),
+                    },
+                });
+            }
+
+            // compare the items present on both sides
+            let mut item_path = path.to_vec();
+            for (item, (l, r)) in l.iter().zip(r).enumerate() {
+                item_path.push(AttributeSelectorStep::Nested {
+                    tag,
+                    item: item as u32,
+                });
+                compare_objects(l, r, options, &item_path, differences);
+                item_path.pop();
+            }
+        }
+        (Value::PixelSequence(l), Value::PixelSequence(r)) => {
+            if l.offset_table() != r.offset_table() || l.fragments() != r.fragments() {
+                differences.push(Difference {
+                    selector: selector(),
+                    kind: DifferenceKind::FragmentMismatch,
+                });
+            }
+        }
+        _ => {
+            differences.push(Difference {
+                selector: selector(),
+                kind: DifferenceKind::ValueKindMismatch,
+            });
+        }
+    }
+}
+
+/// Check whether two primitive values of the given VR are equivalent,
+/// comparing floating point values numerically.
+fn primitive_values_match(
+    vr: VR,
+    left: &PrimitiveValue,
+    right: &PrimitiveValue,
+    options: &CompareOptions,
+) -> bool {
+    if left == right {
+        return true;
+    }
+
+    if !matches!(vr, VR::FD | VR::FL | VR::DS | VR::OD | VR::OF) {
+        return false;
+    }
+
+    match (left.to_multi_float64(), right.to_multi_float64()) {
+        (Ok(l), Ok(r)) => {
+            l.len() == r.len()
+                && l.iter().zip(&r).all(|(a, b)| {
+                    a == b || (a - b).abs() <= options.float_tolerance || (a.is_nan() && b.is_nan())
+                })
+        }
+        // not all values are numbers, they were already compared as they are
+        _ => false,
+    }
+}
+
+/// Check whether the element has an empty value or no sequence items.
+fn is_empty_element<D>(element: &InMemElement<D>) -> bool {
+    match element.value() {
+        Value::Primitive(v) => v.is_empty(),
+        Value::Sequence(seq) => seq.items().is_empty(),
+        Value::PixelSequence(seq) => seq.fragments().is_empty(),
+    }
+}
+
+fn selector_at(path: &[AttributeSelectorStep], last: AttributeSelectorStep) -> AttributeSelector {
+    AttributeSelector::new(path.iter().cloned().chain(std::iter::once(last)))
+        .expect("selector should end with a tag")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompareOptions, DifferenceKind};
+    use crate::InMemDicomObject;
+    use dicom_core::ops::AttributeSelector;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::tags;
+
+    fn base_object() -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.1"),
+            DataElement::new(tags::INSTANCE_CREATION_TIME, VR::TM, "101010"),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+            DataElement::new(tags::SLICE_THICKNESS, VR::DS, "2.5"),
+            DataElement::new(
+                tags::PIXEL_SPACING,
+                VR::DS,
+                dicom_value!(Strs, ["0.5", "0.5"]),
+            ),
+            DataElement::new(
+                tags::IMAGE_POSITION_PATIENT,
+                VR::FD,
+                dicom_value!(F64, [-120.0, -80.0, 15.25]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn identical_objects_are_equivalent() {
+        let obj = base_object();
+        assert!(obj
+            .deep_compare(&obj.clone(), &CompareOptions::new())
+            .is_empty());
+        assert!(obj.is_equivalent(&obj, &CompareOptions::default()));
+    }
+
+    #[test]
+    fn compare_with_numeric_tolerance() {
+        let left = base_object();
+        let mut right = base_object();
+        right.put(DataElement::new(tags::SLICE_THICKNESS, VR::DS, "2.50000"));
+        right.put(DataElement::new(
+            tags::PIXEL_SPACING,
+            VR::DS,
+            dicom_value!(Strs, ["0.5", "0.50001"]),
+        ));
+        right.put(DataElement::new(
+            tags::IMAGE_POSITION_PATIENT,
+            VR::FD,
+            dicom_value!(F64, [-120.0, -80.0, 15.250001]),
+        ));
+
+        // equal decimal strings match regardless of tolerance
+        let differences = left.deep_compare(&right, &CompareOptions::new());
+        let tags: Vec<_> = differences.iter().map(|d| d.selector.last_tag()).collect();
+        assert_eq!(tags, [tags::IMAGE_POSITION_PATIENT, tags::PIXEL_SPACING]);
+        assert_eq!(
+            differences[1].kind,
+            DifferenceKind::ValueMismatch {
+                left: dicom_value!(Strs, ["0.5", "0.5"]),
+                right: dicom_value!(Strs, ["0.5", "0.50001"]),
+            }
+        );
+
+        let options = CompareOptions::new().float_tolerance(1e-4);
+        assert!(left.is_equivalent(&right, &options));
+
+        // tolerance does not apply to a different number of values
+        right.put(DataElement::new(
+            tags::PIXEL_SPACING,
+            VR::DS,
+            PrimitiveValue::from("0.5"),
+        ));
+        let differences = left.deep_compare(&right, &options);
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].selector.last_tag(), tags::PIXEL_SPACING);
+    }
+
+    #[test]
+    fn compare_with_ignore_list_and_empty_attributes() {
+        let left = base_object();
+        let mut right = base_object();
+        right.put(DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.2"));
+        right.remove_element(tags::INSTANCE_CREATION_TIME);
+        right.put(DataElement::new(
+            tags::PATIENT_BIRTH_DATE,
+            VR::DA,
+            PrimitiveValue::Empty,
+        ));
+        right.put(DataElement::new(tags::PATIENT_SEX, VR::CS, "O"));
+
+        let differences = left.deep_compare(&right, &CompareOptions::new());
+        let kinds: Vec<_> = differences
+            .iter()
+            .map(|d| (d.selector.last_tag(), d.kind.clone()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (tags::INSTANCE_CREATION_TIME, DifferenceKind::MissingRight),
+                (
+                    tags::SOP_INSTANCE_UID,
+                    DifferenceKind::ValueMismatch {
+                        left: "2.25.1".into(),
+                        right: "2.25.2".into(),
+                    }
+                ),
+                (tags::PATIENT_BIRTH_DATE, DifferenceKind::MissingLeft),
+                (tags::PATIENT_SEX, DifferenceKind::MissingLeft),
+            ]
+        );
+
+        let options = CompareOptions::new()
+            .ignore_tags([tags::SOP_INSTANCE_UID, tags::INSTANCE_CREATION_TIME])
+            .absent_as_empty(true);
+        let differences = left.deep_compare(&right, &options);
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].selector.last_tag(), tags::PATIENT_SEX);
+        assert_eq!(differences[0].kind, DifferenceKind::MissingLeft);
+
+        // VR mismatches are reported without comparing values
+        right.put(DataElement::new(tags::SLICE_THICKNESS, VR::FD, 2.5_f64));
+        let differences = left.deep_compare(&right, &options);
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[1].selector.last_tag(), tags::SLICE_THICKNESS);
+        assert_eq!(
+            differences[1].kind,
+            DifferenceKind::VrMismatch {
+                left: VR::DS,
+                right: VR::FD,
+            }
+        );
+    }
+
+    #[test]
+    fn compare_nested_sequences() {
+        let item = |code: &str| {
+            InMemDicomObject::from_element_iter([
+                DataElement::new(tags::CODE_VALUE, VR::SH, code),
+                DataElement::new(tags::CODING_SCHEME_DESIGNATOR, VR::SH, "DCM"),
+                DataElement::new(tags::INSTANCE_CREATION_TIME, VR::TM, code),
+            ])
+        };
+        let with_items = |items: Vec<InMemDicomObject>| {
+            let mut obj = base_object();
+            obj.put(DataElement::new(
+                tags::PROCEDURE_CODE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(items),
+            ));
+            obj
+        };
+
+        let left = with_items(vec![item("113100"), item("113101")]);
+        let right = with_items(vec![item("113100"), item("113107"), item("113108")]);
+
+        let options = CompareOptions::new().ignore_tag(tags::INSTANCE_CREATION_TIME);
+        let differences = left.deep_compare(&right, &options);
+        assert_eq!(differences.len(), 2);
+
+        assert_eq!(
+            differences[0].selector,
+            AttributeSelector::from(tags::PROCEDURE_CODE_SEQUENCE),
+        );
+        assert_eq!(
+            differences[0].kind,
+            DifferenceKind::ItemCountMismatch { left: 2, right: 3 }
+        );
+
+        assert_eq!(
+            differences[1].selector,
+            AttributeSelector::from((tags::PROCEDURE_CODE_SEQUENCE, 1, tags::CODE_VALUE)),
+        );
+        assert_eq!(
+            differences[1].to_string(),
+            "(0008,1032)[1].(0008,0100): value [113101] != [113107]",
+        );
+
+        // same items
+        let right = with_items(vec![item("113100"), item("113101")]);
+        assert!(left.is_equivalent(&right, &options));
+    }
+}
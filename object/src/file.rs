@@ -44,8 +44,9 @@ where
 /// Create a `OpenFileOptions`,
 /// call adaptor methods in a chain,
 /// and finish the operation with
-/// either [`open_file()`](OpenFileOptions::open_file)
-/// or [`from_reader()`](OpenFileOptions::from_reader).
+/// either [`open_file()`](OpenFileOptions::open_file),
+/// [`from_reader()`](OpenFileOptions::from_reader),
+/// or [`from_bytes()`](OpenFileOptions::from_bytes).
 ///
 /// ```no_run
 /// # use dicom_object::OpenFileOptions;
@@ -57,11 +58,14 @@ where
 #[derive(Debug, Default, Clone)]
 #[non_exhaustive]
 pub struct OpenFileOptions<D = StandardDataDictionary, T = TransferSyntaxRegistry> {
-    data_dictionary: D,
-    ts_index: T,
-    read_until: Option<Tag>,
-    read_preamble: ReadPreamble,
-    odd_length: OddLengthStrategy,
+    pub(crate) data_dictionary: D,
+    pub(crate) ts_index: T,
+    pub(crate) read_until: Option<Tag>,
+    pub(crate) read_preamble: ReadPreamble,
+    pub(crate) odd_length: OddLengthStrategy,
+    pub(crate) detect_transfer_syntax: bool,
+    pub(crate) preserve_element_order: bool,
+    pub(crate) debug_trace: Option<usize>,
 }
 
 impl OpenFileOptions {
@@ -102,6 +106,63 @@ impl<D, T> OpenFileOptions<D, T> {
         self
     }
 
+    /// Set whether to detect the transfer syntax of the data set
+    /// when it does not match the one declared in the file meta group.
+    ///
+    /// Some files declare _Explicit VR Little Endian_
+    /// while the data set is actually encoded in _Implicit VR Little Endian_,
+    /// or vice versa.
+    /// When enabled,
+    /// the header of the first data set element is examined,
+    /// and the data set is read with the other transfer syntax
+    /// if its encoding only makes sense under that one,
+    /// emitting a warning.
+    /// The transfer syntax effectively used is available through
+    /// [`effective_transfer_syntax`](crate::FileDicomObject::effective_transfer_syntax),
+    /// while the file meta group is kept as is.
+    ///
+    /// This is disabled by default,
+    /// in which case reading such files
+    /// usually fails with a parse error.
+    pub fn detect_transfer_syntax(mut self, detect: bool) -> Self {
+        self.detect_transfer_syntax = detect;
+        self
+    }
+
+    /// Set whether to record the original order of the data elements,
+    /// in the root data set and in all sequence items.
+    ///
+    /// Data sets are expected to be in ascending tag order,
+    /// but some applications produce elements out of order.
+    /// When enabled,
+    /// the objects read [preserve insertion order](crate::InMemDicomObject::with_insertion_order),
+    /// so that they can be written back in the same order
+    /// with [`WriteOptions::preserve_element_order`](crate::WriteOptions::preserve_element_order).
+    /// Elements are still looked up by tag as usual.
+    ///
+    /// This is disabled by default.
+    pub fn preserve_element_order(mut self, preserve: bool) -> Self {
+        self.preserve_element_order = preserve;
+        self
+    }
+
+    /// Enable a diagnostic mode which traces the parsing of the data set,
+    /// keeping the headers of the last `window` data elements parsed
+    /// along with their byte offsets.
+    ///
+    /// If reading the data set fails,
+    /// the error returned carries this trace
+    /// and the raw bytes surrounding the point of failure,
+    /// available through [`ReadError::parse_trace`].
+    /// See the [`trace`](crate::trace) module for more details.
+    ///
+    /// This is disabled by default,
+    /// in which case parsing has no tracing overhead.
+    pub fn debug_trace(mut self, window: usize) -> Self {
+        self.debug_trace = Some(window);
+        self
+    }
+
     /// Set the transfer syntax index to use when reading the file.
     pub fn transfer_syntax_index<Tr>(self, ts_index: Tr) -> OpenFileOptions<D, Tr>
     where
@@ -113,6 +174,9 @@ impl<D, T> OpenFileOptions<D, T> {
             read_preamble: self.read_preamble,
             ts_index,
             odd_length: self.odd_length,
+            detect_transfer_syntax: self.detect_transfer_syntax,
+            preserve_element_order: self.preserve_element_order,
+            debug_trace: self.debug_trace,
         }
     }
 
@@ -126,6 +190,11 @@ impl<D, T> OpenFileOptions<D, T> {
     }
 
     /// Set the data element dictionary to use when reading the file.
+    ///
+    /// To recognize private attributes
+    /// in addition to the standard ones,
+    /// combine the standard data dictionary with a private dictionary
+    /// using [`MergedDictionary`](dicom_core::dictionary::MergedDictionary).
     pub fn dictionary<Di>(self, dict: Di) -> OpenFileOptions<Di, T>
     where
         Di: DataDictionary,
@@ -137,6 +206,9 @@ impl<D, T> OpenFileOptions<D, T> {
             read_preamble: self.read_preamble,
             ts_index: self.ts_index,
             odd_length: self.odd_length,
+            detect_transfer_syntax: self.detect_transfer_syntax,
+            preserve_element_order: self.preserve_element_order,
+            debug_trace: self.debug_trace,
         }
     }
 
@@ -148,14 +220,7 @@ impl<D, T> OpenFileOptions<D, T> {
         D: Clone,
         T: TransferSyntaxIndex,
     {
-        DefaultDicomObject::open_file_with_all_options(
-            path,
-            self.data_dictionary,
-            self.ts_index,
-            self.read_until,
-            self.read_preamble,
-            self.odd_length,
-        )
+        DefaultDicomObject::open_file_with_all_options(path, self)
     }
 
     /// Obtain a DICOM object by reading from a byte source.
@@ -170,14 +235,23 @@ impl<D, T> OpenFileOptions<D, T> {
         D: Clone,
         T: TransferSyntaxIndex,
     {
-        DefaultDicomObject::from_reader_with_all_options(
-            from,
-            self.data_dictionary,
-            self.ts_index,
-            self.read_until,
-            self.read_preamble,
-            self.odd_length,
-        )
+        DefaultDicomObject::from_reader_with_all_options(from, self)
+    }
+
+    /// Obtain a DICOM object from a buffer
+    /// holding the full contents of a DICOM file.
+    ///
+    /// Unlike [`from_reader`](Self::from_reader),
+    /// values are read directly from the buffer
+    /// without going through an intermediate buffered reader.
+    /// The preamble is detected in the same way.
+    pub fn from_bytes(self, bytes: &[u8]) -> Result<DefaultDicomObject<D>>
+    where
+        D: DataDictionary,
+        D: Clone,
+        T: TransferSyntaxIndex,
+    {
+        DefaultDicomObject::from_bytes_with_all_options(bytes, self)
     }
 }
 
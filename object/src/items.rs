@@ -0,0 +1,903 @@
+//! Builders of sequence items defined by common macros of the standard.
+//!
+//! Coded concepts and references to other SOP instances
+//! are found in the items of many sequences.
+//! This module provides small types for building these items
+//! and for reading them back:
+//!
+//! - [`CodeItem`] for the basic coded entry attributes
+//!   of the _Code Sequence Macro_ (PS3.3 Table 8.8-1);
+//! - [`ReferencedSop`] for the _SOP Instance Reference Macro_
+//!   (PS3.3 Table 10-11).
+//!
+//! References to other instances can also be gathered from a whole object
+//! into [`InstanceReference`] values,
+//! through [`InMemDicomObject::references`],
+//! and resolved across a set of objects with a [`ReferenceGraph`].
+//!
+//! Values are checked against the length limits
+//! of their value representations and the syntax of UIDs
+//! when converting to and from an [`InMemDicomObject`].
+//!
+//! ```
+//! # use dicom_object::items::{CodeItem, ReferencedSop};
+//! use dicom_core::value::DataSetSequence;
+//! use dicom_core::{DataElement, VR};
+//! use dicom_dictionary_std::{tags, uids};
+//! use dicom_object::InMemDicomObject;
+//! use std::convert::TryFrom;
+//!
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let code = CodeItem::new("T-04000", "SRT", "Breast").with_version("1.0");
+//! let reference = ReferencedSop::new(uids::CT_IMAGE_STORAGE, "2.25.123456789");
+//!
+//! let obj = InMemDicomObject::from_element_iter([
+//!     DataElement::new(
+//!         tags::ANATOMIC_REGION_SEQUENCE,
+//!         VR::SQ,
+//!         DataSetSequence::from(vec![code.to_item()?]),
+//!     ),
+//!     DataElement::new(
+//!         tags::REFERENCED_IMAGE_SEQUENCE,
+//!         VR::SQ,
+//!         DataSetSequence::from(vec![reference.to_item()?]),
+//!     ),
+//! ]);
+//!
+//! let items = obj.get(tags::ANATOMIC_REGION_SEQUENCE).unwrap().items().unwrap();
+//! assert_eq!(CodeItem::try_from(&items[0])?, code);
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+use dicom_core::ops::{AttributeSelector, AttributeSelectorStep};
+use dicom_core::value::ConvertValueError;
+use dicom_core::{DataDictionary, DataElement, Tag, VR};
+use dicom_dictionary_std::tags;
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::ops::ControlFlow;
+
+use crate::query::is_valid_uid;
+use crate::InMemDicomObject;
+
+/// An error which may occur when converting a sequence item.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum ItemError {
+    /// Value of attribute {tag} has {len} characters, more than the {max} allowed for {vr}
+    ValueTooLong {
+        tag: Tag,
+        vr: VR,
+        len: usize,
+        max: usize,
+    },
+    /// Value of attribute {tag} must not contain a backslash
+    Backslash { tag: Tag },
+    /// Invalid UID `{uid}` in attribute {tag}
+    InvalidUid { tag: Tag, uid: String },
+    /// Missing attribute {tag}
+    MissingAttribute { tag: Tag },
+    /// Could not read the value of attribute {tag}
+    ConvertValue { tag: Tag, source: ConvertValueError },
+}
+
+pub type Result<T, E = ItemError> = std::result::Result<T, E>;
+
+/// A coded concept,
+/// as described by the basic coded entry attributes
+/// of the _Code Sequence Macro_.
+///
+/// The code value is recorded in _Code Value_ (SH),
+/// so it cannot be longer than 16 characters.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CodeItem {
+    value: String,
+    scheme_designator: String,
+    meaning: String,
+    scheme_version: Option<String>,
+}
+
+impl CodeItem {
+    /// Create a coded concept
+    /// from its code value, coding scheme designator, and code meaning.
+    pub fn new(
+        value: impl Into<String>,
+        scheme_designator: impl Into<String>,
+        meaning: impl Into<String>,
+    ) -> Self {
+        CodeItem {
+            value: value.into(),
+            scheme_designator: scheme_designator.into(),
+            meaning: meaning.into(),
+            scheme_version: None,
+        }
+    }
+
+    /// Set the version of the coding scheme.
+    pub fn with_version(mut self, scheme_version: impl Into<String>) -> Self {
+        self.scheme_version = Some(scheme_version.into());
+        self
+    }
+
+    /// The code value
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The coding scheme designator
+    pub fn scheme_designator(&self) -> &str {
+        &self.scheme_designator
+    }
+
+    /// The code meaning
+    pub fn meaning(&self) -> &str {
+        &self.meaning
+    }
+
+    /// The coding scheme version, if any
+    pub fn scheme_version(&self) -> Option<&str> {
+        self.scheme_version.as_deref()
+    }
+
+    /// Check the values of this coded concept
+    /// against the constraints of their value representations.
+    pub fn validate(&self) -> Result<()> {
+        check_text(tags::CODE_VALUE, VR::SH, &self.value)?;
+        check_text(
+            tags::CODING_SCHEME_DESIGNATOR,
+            VR::SH,
+            &self.scheme_designator,
+        )?;
+        if let Some(scheme_version) = &self.scheme_version {
+            check_text(tags::CODING_SCHEME_VERSION, VR::SH, scheme_version)?;
+        }
+        check_text(tags::CODE_MEANING, VR::LO, &self.meaning)
+    }
+
+    /// Build a sequence item with the attributes of this coded concept.
+    pub fn to_item(&self) -> Result<InMemDicomObject> {
+        self.validate()?;
+        let mut item = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::CODE_VALUE, VR::SH, self.value.as_str()),
+            DataElement::new(
+                tags::CODING_SCHEME_DESIGNATOR,
+                VR::SH,
+                self.scheme_designator.as_str(),
+            ),
+            DataElement::new(tags::CODE_MEANING, VR::LO, self.meaning.as_str()),
+        ]);
+        if let Some(scheme_version) = &self.scheme_version {
+            item.put_str(tags::CODING_SCHEME_VERSION, VR::SH, scheme_version);
+        }
+        Ok(item)
+    }
+}
+
+impl TryFrom<&CodeItem> for InMemDicomObject {
+    type Error = ItemError;
+
+    fn try_from(code: &CodeItem) -> Result<Self> {
+        code.to_item()
+    }
+}
+
+impl<D> TryFrom<&InMemDicomObject<D>> for CodeItem
+where
+    D: DataDictionary + Clone,
+{
+    type Error = ItemError;
+
+    fn try_from(item: &InMemDicomObject<D>) -> Result<Self> {
+        let code = CodeItem {
+            value: required_str(item, tags::CODE_VALUE)?,
+            scheme_designator: required_str(item, tags::CODING_SCHEME_DESIGNATOR)?,
+            meaning: required_str(item, tags::CODE_MEANING)?,
+            scheme_version: optional_str(item, tags::CODING_SCHEME_VERSION)?,
+        };
+        code.validate()?;
+        Ok(code)
+    }
+}
+
+/// A reference to a SOP instance,
+/// as described by the _SOP Instance Reference Macro_.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReferencedSop {
+    sop_class_uid: String,
+    sop_instance_uid: String,
+}
+
+impl ReferencedSop {
+    /// Create a reference to the SOP instance
+    /// with the given SOP class UID and SOP instance UID.
+    pub fn new(sop_class_uid: impl Into<String>, sop_instance_uid: impl Into<String>) -> Self {
+        ReferencedSop {
+            sop_class_uid: sop_class_uid.into(),
+            sop_instance_uid: sop_instance_uid.into(),
+        }
+    }
+
+    /// The SOP class UID of the referenced instance
+    pub fn sop_class_uid(&self) -> &str {
+        &self.sop_class_uid
+    }
+
+    /// The SOP instance UID of the referenced instance
+    pub fn sop_instance_uid(&self) -> &str {
+        &self.sop_instance_uid
+    }
+
+    /// Check that both UIDs are valid.
+    pub fn validate(&self) -> Result<()> {
+        check_uid(tags::REFERENCED_SOP_CLASS_UID, &self.sop_class_uid)?;
+        check_uid(tags::REFERENCED_SOP_INSTANCE_UID, &self.sop_instance_uid)
+    }
+
+    /// Build a sequence item with the attributes of this reference.
+    pub fn to_item(&self) -> Result<InMemDicomObject> {
+        self.validate()?;
+        Ok(InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::REFERENCED_SOP_CLASS_UID,
+                VR::UI,
+                self.sop_class_uid.as_str(),
+            ),
+            DataElement::new(
+                tags::REFERENCED_SOP_INSTANCE_UID,
+                VR::UI,
+                self.sop_instance_uid.as_str(),
+            ),
+        ]))
+    }
+}
+
+impl TryFrom<&ReferencedSop> for InMemDicomObject {
+    type Error = ItemError;
+
+    fn try_from(reference: &ReferencedSop) -> Result<Self> {
+        reference.to_item()
+    }
+}
+
+impl<D> TryFrom<&InMemDicomObject<D>> for ReferencedSop
+where
+    D: DataDictionary + Clone,
+{
+    type Error = ItemError;
+
+    fn try_from(item: &InMemDicomObject<D>) -> Result<Self> {
+        let reference = ReferencedSop {
+            sop_class_uid: required_str(item, tags::REFERENCED_SOP_CLASS_UID)?,
+            sop_instance_uid: required_str(item, tags::REFERENCED_SOP_INSTANCE_UID)?,
+        };
+        reference.validate()?;
+        Ok(reference)
+    }
+}
+
+/// The sequences in which references to other instances are looked for.
+const REFERENCE_SEQUENCES: [Tag; 3] = [
+    tags::REFERENCED_IMAGE_SEQUENCE,
+    tags::SOURCE_IMAGE_SEQUENCE,
+    tags::REFERENCED_INSTANCE_SEQUENCE,
+];
+
+/// A reference from an object to another SOP instance,
+/// as found in an item of a _Referenced Image Sequence_,
+/// _Source Image Sequence_, or _Referenced Instance Sequence_.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceReference {
+    /// The SOP class UID of the referenced instance
+    pub sop_class_uid: String,
+    /// The SOP instance UID of the referenced instance
+    pub sop_instance_uid: String,
+    /// The referenced frame numbers, starting at 1,
+    /// or `None` if the reference is to the whole instance
+    pub frames: Option<Vec<u32>>,
+    /// The purpose of the reference, if declared
+    pub purpose: Option<CodeItem>,
+    /// The selector of the _Referenced SOP Instance UID_
+    /// of this reference in the referring object
+    pub path: AttributeSelector,
+}
+
+/// Collect the references to other instances in an object,
+/// at any depth.
+pub(crate) fn collect_references<D>(obj: &InMemDicomObject<D>) -> Vec<InstanceReference>
+where
+    D: DataDictionary + Clone,
+{
+    // locate the reference sequences first
+    let mut sequences: Vec<Vec<AttributeSelectorStep>> = Vec::new();
+    let _ = obj.walk(|path, header| {
+        if REFERENCE_SEQUENCES.contains(&header.tag) {
+            sequences.push(path.to_vec());
+        }
+        ControlFlow::Continue(())
+    });
+
+    let mut references = Vec::new();
+    for path in sequences {
+        let Some(selector) = AttributeSelector::new(path.iter().cloned()) else {
+            continue;
+        };
+        let tag = selector.last_tag();
+        let Some(items) = obj.entry_at(selector).ok().and_then(|e| e.items()) else {
+            continue;
+        };
+
+        for (i, item) in items.iter().enumerate() {
+            // skip items without valid UIDs
+            let Ok(sop) = ReferencedSop::try_from(item) else {
+                continue;
+            };
+            let frames = item
+                .get(tags::REFERENCED_FRAME_NUMBER)
+                .and_then(|e| e.to_multi_int::<u32>().ok())
+                .filter(|frames| !frames.is_empty());
+            let purpose = item
+                .get(tags::PURPOSE_OF_REFERENCE_CODE_SEQUENCE)
+                .and_then(|e| e.items())
+                .and_then(|items| items.first())
+                .and_then(|code| CodeItem::try_from(code).ok());
+
+            let mut steps = path.clone();
+            *steps.last_mut().unwrap() = AttributeSelectorStep::Nested {
+                tag,
+                item: i as u32,
+            };
+            steps.push(AttributeSelectorStep::Tag(
+                tags::REFERENCED_SOP_INSTANCE_UID,
+            ));
+
+            references.push(InstanceReference {
+                sop_class_uid: sop.sop_class_uid,
+                sop_instance_uid: sop.sop_instance_uid,
+                frames,
+                purpose,
+                path: AttributeSelector::new(steps).expect("selector should end with a tag step"),
+            });
+        }
+    }
+    references
+}
+
+/// The references between the instances of a set of objects.
+///
+/// Objects are identified by their _SOP Instance UID_.
+/// The graph can be used to look up
+/// both the references made by an object
+/// and the objects which refer to a given instance.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_object::InMemDicomObject;
+/// use dicom_object::items::ReferenceGraph;
+///
+/// # let objects: Vec<InMemDicomObject> = vec![];
+/// let graph = ReferenceGraph::new(&objects);
+/// for referrer in graph.referenced_by("2.25.123456789") {
+///     println!("referenced by {}", referrer);
+/// }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReferenceGraph {
+    /// the references made by each object
+    references: BTreeMap<String, Vec<InstanceReference>>,
+    /// the objects referring to each instance
+    referrers: BTreeMap<String, Vec<String>>,
+}
+
+impl ReferenceGraph {
+    /// Build the reference graph of the given objects.
+    ///
+    /// Objects without a _SOP Instance UID_ are ignored.
+    pub fn new<'a, I, D>(objects: I) -> Self
+    where
+        I: IntoIterator<Item = &'a InMemDicomObject<D>>,
+        D: DataDictionary + Clone + 'a,
+    {
+        let mut graph = ReferenceGraph::default();
+        for obj in objects {
+            let Some(sop_instance_uid) = obj
+                .get(tags::SOP_INSTANCE_UID)
+                .and_then(|e| e.to_str().ok())
+                .map(|uid| {
+                    uid.trim_end_matches(|c: char| c == '\0' || c == ' ')
+                        .to_string()
+                })
+            else {
+                continue;
+            };
+
+            let references = obj.references();
+            for reference in &references {
+                let referrers = graph
+                    .referrers
+                    .entry(reference.sop_instance_uid.clone())
+                    .or_default();
+                if !referrers.contains(&sop_instance_uid) {
+                    referrers.push(sop_instance_uid.clone());
+                }
+            }
+            graph
+                .references
+                .entry(sop_instance_uid)
+                .or_default()
+                .extend(references);
+        }
+        graph
+    }
+
+    /// Iterate over the SOP instance UIDs of the objects in the graph.
+    pub fn instances(&self) -> impl Iterator<Item = &str> {
+        self.references.keys().map(|uid| uid.as_str())
+    }
+
+    /// Check whether the object with the given SOP instance UID
+    /// is part of the graph.
+    pub fn contains(&self, sop_instance_uid: &str) -> bool {
+        self.references.contains_key(sop_instance_uid)
+    }
+
+    /// Retrieve the references made by the object
+    /// with the given SOP instance UID.
+    ///
+    /// Returns an empty slice if the object is not in the graph.
+    pub fn references_from(&self, sop_instance_uid: &str) -> &[InstanceReference] {
+        self.references
+            .get(sop_instance_uid)
+            .map_or(&[], |references| references.as_slice())
+    }
+
+    /// Retrieve the SOP instance UIDs of the objects
+    /// which refer to the instance with the given SOP instance UID,
+    /// in the order in which they were given.
+    ///
+    /// The referenced instance does not need to be in the graph.
+    pub fn referenced_by(&self, sop_instance_uid: &str) -> &[String] {
+        self.referrers
+            .get(sop_instance_uid)
+            .map_or(&[], |referrers| referrers.as_slice())
+    }
+}
+
+/// Check a single text value against the length limit of its VR.
+fn check_text(tag: Tag, vr: VR, value: &str) -> Result<()> {
+    let max = match vr {
+        VR::SH => 16,
+        VR::LO => 64,
+        _ => usize::MAX,
+    };
+    let len = value.chars().count();
+    ensure!(len <= max, ValueTooLongSnafu { tag, vr, len, max });
+    ensure!(!value.contains('\\'), BackslashSnafu { tag });
+    Ok(())
+}
+
+fn check_uid(tag: Tag, uid: &str) -> Result<()> {
+    let uid = uid.trim_end_matches('\0');
+    ensure!(
+        is_valid_uid(uid),
+        InvalidUidSnafu {
+            tag,
+            uid: uid.to_string(),
+        }
+    );
+    Ok(())
+}
+
+fn required_str<D>(item: &InMemDicomObject<D>, tag: Tag) -> Result<String>
+where
+    D: DataDictionary + Clone,
+{
+    optional_str(item, tag)?.context(MissingAttributeSnafu { tag })
+}
+
+fn optional_str<D>(item: &InMemDicomObject<D>, tag: Tag) -> Result<Option<String>>
+where
+    D: DataDictionary + Clone,
+{
+    item.get(tag)
+        .map(|e| {
+            e.to_str()
+                .map(|value| value.into_owned())
+                .context(ConvertValueSnafu { tag })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::header::Header;
+    use dicom_core::value::{DataSetSequence, PrimitiveValue};
+    use dicom_dictionary_std::uids;
+
+    #[test]
+    fn code_item_round_trip() {
+        let code = CodeItem::new("121071", "DCM", "Finding");
+        let item = code.to_item().unwrap();
+        let elements: Vec<_> = item
+            .iter()
+            .map(|e| (e.tag(), e.vr(), e.to_str().unwrap().into_owned()))
+            .collect();
+        assert_eq!(
+            elements,
+            vec![
+                (tags::CODE_VALUE, VR::SH, "121071".to_string()),
+                (tags::CODING_SCHEME_DESIGNATOR, VR::SH, "DCM".to_string()),
+                (tags::CODE_MEANING, VR::LO, "Finding".to_string()),
+            ]
+        );
+        assert_eq!(CodeItem::try_from(&item).unwrap(), code);
+
+        let code = CodeItem::new("T-04000", "SRT", "Breast").with_version("1.1");
+        let item = InMemDicomObject::try_from(&code).unwrap();
+        assert_eq!(
+            item.get(tags::CODING_SCHEME_VERSION)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "1.1"
+        );
+        let parsed = CodeItem::try_from(&item).unwrap();
+        assert_eq!(parsed.scheme_version(), Some("1.1"));
+        assert_eq!(parsed, code);
+    }
+
+    #[test]
+    fn code_item_length_limits() {
+        // 16 characters is the limit of SH
+        let code = CodeItem::new("0123456789ABCDEF", "DCM", "Sixteen");
+        assert!(code.to_item().is_ok());
+
+        let code = CodeItem::new("0123456789ABCDEFG", "DCM", "Seventeen");
+        assert!(matches!(
+            code.to_item(),
+            Err(ItemError::ValueTooLong {
+                tag: tags::CODE_VALUE,
+                vr: VR::SH,
+                len: 17,
+                max: 16,
+            })
+        ));
+
+        // the limit is in characters, not bytes
+        let code = CodeItem::new("1", "DCM", "é".repeat(64));
+        assert!(code.validate().is_ok());
+        let code = CodeItem::new("1", "DCM", "é".repeat(65));
+        assert!(matches!(
+            code.validate(),
+            Err(ItemError::ValueTooLong {
+                tag: tags::CODE_MEANING,
+                vr: VR::LO,
+                len: 65,
+                max: 64,
+            })
+        ));
+
+        let code = CodeItem::new("1", "DCM", "Meaning").with_version("version 1.0.0-beta");
+        assert!(matches!(
+            code.to_item(),
+            Err(ItemError::ValueTooLong {
+                tag: tags::CODING_SCHEME_VERSION,
+                ..
+            })
+        ));
+
+        let code = CodeItem::new("1", "DCM", "A\\B");
+        assert!(matches!(
+            code.to_item(),
+            Err(ItemError::Backslash {
+                tag: tags::CODE_MEANING
+            })
+        ));
+    }
+
+    #[test]
+    fn code_item_from_invalid_item() {
+        let item: InMemDicomObject = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::CODE_VALUE, VR::SH, "121071"),
+            DataElement::new(tags::CODE_MEANING, VR::LO, "Finding"),
+        ]);
+        assert!(matches!(
+            CodeItem::try_from(&item),
+            Err(ItemError::MissingAttribute {
+                tag: tags::CODING_SCHEME_DESIGNATOR
+            })
+        ));
+
+        let item: InMemDicomObject = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::CODE_VALUE, VR::SH, "121071"),
+            DataElement::new(tags::CODING_SCHEME_DESIGNATOR, VR::SH, "DCM"),
+            DataElement::new(tags::CODE_MEANING, VR::LO, "F".repeat(65)),
+        ]);
+        assert!(matches!(
+            CodeItem::try_from(&item),
+            Err(ItemError::ValueTooLong {
+                tag: tags::CODE_MEANING,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn referenced_sop_round_trip() {
+        let reference = ReferencedSop::new(uids::CT_IMAGE_STORAGE, "2.25.123456789");
+        let item = reference.to_item().unwrap();
+        assert_eq!(
+            item.get(tags::REFERENCED_SOP_CLASS_UID)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            uids::CT_IMAGE_STORAGE
+        );
+        assert_eq!(
+            item.get(tags::REFERENCED_SOP_INSTANCE_UID)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "2.25.123456789"
+        );
+        assert_eq!(ReferencedSop::try_from(&item).unwrap(), reference);
+
+        // UIDs padded to even length are read back without padding
+        let item: InMemDicomObject = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::REFERENCED_SOP_CLASS_UID,
+                VR::UI,
+                "1.2.840.10008.1.1\0",
+            ),
+            DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "1.2.3.4"),
+        ]);
+        let reference = ReferencedSop::try_from(&item).unwrap();
+        assert_eq!(reference.sop_class_uid(), "1.2.840.10008.1.1");
+        assert_eq!(reference.sop_instance_uid(), "1.2.3.4");
+    }
+
+    #[test]
+    fn referenced_sop_invalid_uids() {
+        let reference = ReferencedSop::new(uids::CT_IMAGE_STORAGE, "1.2.3.abc");
+        assert!(matches!(
+            reference.to_item(),
+            Err(ItemError::InvalidUid {
+                tag: tags::REFERENCED_SOP_INSTANCE_UID,
+                ..
+            })
+        ));
+
+        let reference = ReferencedSop::new("", "1.2.3");
+        assert!(matches!(
+            reference.to_item(),
+            Err(ItemError::InvalidUid {
+                tag: tags::REFERENCED_SOP_CLASS_UID,
+                ..
+            })
+        ));
+
+        // 65 characters is more than allowed for a UID
+        let uid = format!("1.{}", "2".repeat(63));
+        let reference = ReferencedSop::new(uids::CT_IMAGE_STORAGE, uid);
+        assert!(matches!(
+            reference.validate(),
+            Err(ItemError::InvalidUid { .. })
+        ));
+
+        let item: InMemDicomObject = InMemDicomObject::from_element_iter([DataElement::new(
+            tags::REFERENCED_SOP_CLASS_UID,
+            VR::UI,
+            uids::CT_IMAGE_STORAGE,
+        )]);
+        assert!(matches!(
+            ReferencedSop::try_from(&item),
+            Err(ItemError::MissingAttribute {
+                tag: tags::REFERENCED_SOP_INSTANCE_UID
+            })
+        ));
+    }
+
+    fn reference_item(
+        sop_instance_uid: &str,
+        frames: Option<&[&str]>,
+        purpose: Option<&CodeItem>,
+    ) -> InMemDicomObject {
+        let mut item = ReferencedSop::new(uids::CT_IMAGE_STORAGE, sop_instance_uid)
+            .to_item()
+            .unwrap();
+        if let Some(frames) = frames {
+            item.put(DataElement::new(
+                tags::REFERENCED_FRAME_NUMBER,
+                VR::IS,
+                PrimitiveValue::Strs(frames.iter().map(|f| f.to_string()).collect()),
+            ));
+        }
+        if let Some(purpose) = purpose {
+            item.put(DataElement::new(
+                tags::PURPOSE_OF_REFERENCE_CODE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![purpose.to_item().unwrap()]),
+            ));
+        }
+        item
+    }
+
+    /// A presentation state applying to two images of a series,
+    /// the first one only in some of its frames
+    fn gsps_object() -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::SOP_CLASS_UID,
+                VR::UI,
+                uids::GRAYSCALE_SOFTCOPY_PRESENTATION_STATE_STORAGE,
+            ),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.1"),
+            DataElement::new(
+                tags::REFERENCED_SERIES_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(
+                        tags::REFERENCED_IMAGE_SEQUENCE,
+                        VR::SQ,
+                        DataSetSequence::from(vec![
+                            reference_item("2.25.10", Some(&["1", "3"][..]), None),
+                            reference_item("2.25.11", None, None),
+                        ]),
+                    ),
+                    DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, "2.25.9"),
+                ])]),
+            ),
+        ])
+    }
+
+    /// A derived image referring to its sources
+    /// at the top level and in its functional groups,
+    /// and to the presentation state
+    fn derived_object() -> InMemDicomObject {
+        let purpose = CodeItem::new(
+            "121322",
+            "DCM",
+            "Source image for image processing operation",
+        );
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "2.25.20"),
+            DataElement::new(
+                tags::REFERENCED_INSTANCE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![reference_item("2.25.1", None, None)]),
+            ),
+            DataElement::new(
+                tags::SOURCE_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![
+                    // items without a referenced SOP instance are skipped
+                    InMemDicomObject::new_empty(),
+                    reference_item("2.25.10", None, Some(&purpose)),
+                ]),
+            ),
+            DataElement::new(
+                tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(
+                        tags::DERIVATION_IMAGE_SEQUENCE,
+                        VR::SQ,
+                        DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                            DataElement::new(
+                                tags::SOURCE_IMAGE_SEQUENCE,
+                                VR::SQ,
+                                DataSetSequence::from(vec![reference_item(
+                                    "2.25.11",
+                                    Some(&["2"][..]),
+                                    None,
+                                )]),
+                            ),
+                        ])]),
+                    ),
+                ])]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn gsps_references() {
+        let references = gsps_object().references();
+        assert_eq!(
+            references,
+            vec![
+                InstanceReference {
+                    sop_class_uid: uids::CT_IMAGE_STORAGE.to_string(),
+                    sop_instance_uid: "2.25.10".to_string(),
+                    frames: Some(vec![1, 3]),
+                    purpose: None,
+                    path: AttributeSelector::from((
+                        tags::REFERENCED_SERIES_SEQUENCE,
+                        0,
+                        tags::REFERENCED_IMAGE_SEQUENCE,
+                        0,
+                        tags::REFERENCED_SOP_INSTANCE_UID,
+                    )),
+                },
+                InstanceReference {
+                    sop_class_uid: uids::CT_IMAGE_STORAGE.to_string(),
+                    sop_instance_uid: "2.25.11".to_string(),
+                    frames: None,
+                    purpose: None,
+                    path: AttributeSelector::from((
+                        tags::REFERENCED_SERIES_SEQUENCE,
+                        0,
+                        tags::REFERENCED_IMAGE_SEQUENCE,
+                        1,
+                        tags::REFERENCED_SOP_INSTANCE_UID,
+                    )),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn derived_image_references() {
+        let references = derived_object().references();
+        let summary: Vec<_> = references
+            .iter()
+            .map(|r| (r.sop_instance_uid.as_str(), r.frames.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("2.25.1", None),
+                ("2.25.10", None),
+                ("2.25.11", Some(vec![2])),
+            ]
+        );
+
+        assert_eq!(
+            references[1].purpose.as_ref().map(|code| code.value()),
+            Some("121322")
+        );
+        assert_eq!(
+            references[1].path,
+            AttributeSelector::from((
+                tags::SOURCE_IMAGE_SEQUENCE,
+                1,
+                tags::REFERENCED_SOP_INSTANCE_UID,
+            ))
+        );
+        assert_eq!(
+            references[2].path,
+            AttributeSelector::from((
+                tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+                0,
+                tags::DERIVATION_IMAGE_SEQUENCE,
+                0,
+                tags::SOURCE_IMAGE_SEQUENCE,
+                0,
+                tags::REFERENCED_SOP_INSTANCE_UID,
+            ))
+        );
+    }
+
+    #[test]
+    fn reference_graph() {
+        let objects = vec![gsps_object(), derived_object()];
+        let graph = ReferenceGraph::new(&objects);
+
+        assert_eq!(
+            graph.instances().collect::<Vec<_>>(),
+            vec!["2.25.1", "2.25.20"]
+        );
+        assert!(graph.contains("2.25.1"));
+        assert!(!graph.contains("2.25.10"));
+        assert_eq!(graph.references_from("2.25.1").len(), 2);
+        assert_eq!(graph.references_from("2.25.20").len(), 3);
+        assert!(graph.references_from("2.25.10").is_empty());
+
+        assert_eq!(graph.referenced_by("2.25.10"), &["2.25.1", "2.25.20"]);
+        assert_eq!(graph.referenced_by("2.25.11"), &["2.25.1", "2.25.20"]);
+        assert_eq!(graph.referenced_by("2.25.1"), &["2.25.20"]);
+        assert!(graph.referenced_by("2.25.20").is_empty());
+    }
+}
@@ -133,30 +133,63 @@
 //! # }
 //! # run().unwrap();
 //! ```
+//!
+//! Two in-memory objects can be compared element by element
+//! with [`deep_compare`](InMemDicomObject::deep_compare),
+//! which reports structured differences
+//! under the options in the [`compare`] module.
+//!
+//! Query identifiers for the query/retrieve service class
+//! can be composed and validated
+//! with the builders in the [`query`] module.
+//!
+//! Common sequence items,
+//! such as coded concepts and references to SOP instances,
+//! can be built and read back with the types in the [`items`] module.
+//!
+//! The UIDs of a set of objects can be replaced consistently,
+//! keeping the references between them valid,
+//! with the [`UidRemapper`](remap::UidRemapper) in the [`remap`] module.
+//!
+//! Enable the `arrow` Cargo feature
+//! to convert collections of DICOM objects
+//! into [Apache Arrow](https://arrow.apache.org) record batches
+//! (see the `arrow` module).
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod compare;
 pub mod file;
+pub mod items;
 pub mod mem;
 pub mod meta;
 pub mod ops;
+pub mod query;
+pub mod remap;
 pub mod tokens;
+pub mod trace;
 
 pub use crate::file::{from_reader, open_file, OpenFileOptions};
 pub use crate::mem::InMemDicomObject;
 pub use crate::meta::{FileMetaTable, FileMetaTableBuilder};
+use dicom_core::dictionary::ValueMultiplicity;
 use dicom_core::ops::AttributeSelector;
 use dicom_core::DataDictionary;
 pub use dicom_core::Tag;
+use dicom_core::VR;
 pub use dicom_dictionary_std::StandardDataDictionary;
+pub use dicom_parser::dataset::SequenceLengthStrategy;
 
 /// The default implementation of a root DICOM object.
 pub type DefaultDicomObject<D = StandardDataDictionary> = FileDicomObject<mem::InMemDicomObject<D>>;
 
 use dicom_core::header::{GroupNumber, Header};
 use dicom_encoding::adapters::{PixelDataObject, RawPixelData};
-use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
-use dicom_parser::dataset::{DataSetWriter, IntoTokens};
+use dicom_encoding::transfer_syntax::{Codec, TransferSyntaxIndex};
+use dicom_parser::dataset::write::DataSetWriterOptions;
+use dicom_parser::dataset::{DataSetWriter, IntoTokens, IntoTokensOptions};
 use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
 use smallvec::SmallVec;
-use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
 use std::borrow::Cow;
 use std::fs::File;
 use std::io::{BufWriter, Write};
@@ -222,6 +255,11 @@ pub enum ReadError {
         backtrace: Backtrace,
         source: std::io::Error,
     },
+    /// Could not read the first bytes of the data set
+    ReadDataSetHead {
+        backtrace: Backtrace,
+        source: std::io::Error,
+    },
     #[snafu(display("Could not parse meta group data set"))]
     ParseMetaDataSet {
         #[snafu(backtrace)]
@@ -254,6 +292,24 @@ pub enum ReadError {
     },
     #[snafu(display("Premature data set end"))]
     PrematureEnd { backtrace: Backtrace },
+    #[snafu(display("Could not read data set near offset {}", trace.offset()))]
+    TracedParse {
+        trace: Box<crate::trace::ParseTrace>,
+        #[snafu(source(from(ReadError, Box::new)))]
+        source: Box<ReadError>,
+    },
+}
+
+impl ReadError {
+    /// Retrieve the trace of the data set parsing process
+    /// which led to this error,
+    /// if [debug tracing](OpenFileOptions::debug_trace) was enabled.
+    pub fn parse_trace(&self) -> Option<&crate::trace::ParseTrace> {
+        match self {
+            ReadError::TracedParse { trace, .. } => Some(trace),
+            _ => None,
+        }
+    }
 }
 
 /// An error which may occur when writing a DICOM object
@@ -293,6 +349,61 @@ pub enum WriteError {
     },
     #[snafu(display("Unsupported transfer syntax `{}`", uid))]
     WriteUnsupportedTransferSyntax { uid: String, backtrace: Backtrace },
+    #[snafu(display(
+        "Media Storage SOP Class UID `{}` does not match SOP Class UID `{}`",
+        meta,
+        dataset
+    ))]
+    SopClassMismatch {
+        /// the Media Storage SOP Class UID in the file meta group
+        meta: String,
+        /// the SOP Class UID in the data set
+        dataset: String,
+        backtrace: Backtrace,
+    },
+    #[snafu(display(
+        "Media Storage SOP Instance UID `{}` does not match SOP Instance UID `{}`",
+        meta,
+        dataset
+    ))]
+    SopInstanceMismatch {
+        /// the Media Storage SOP Instance UID in the file meta group
+        meta: String,
+        /// the SOP Instance UID in the data set
+        dataset: String,
+        backtrace: Backtrace,
+    },
+    #[snafu(display(
+        "Transfer syntax `{}` does not match the {} pixel data in the data set",
+        uid,
+        if *encapsulated { "encapsulated" } else { "native" }
+    ))]
+    PixelDataEncapsulationMismatch {
+        /// the transfer syntax UID in the file meta group
+        uid: String,
+        /// whether the pixel data in the data set is encapsulated
+        encapsulated: bool,
+        backtrace: Backtrace,
+    },
+}
+
+/// A data element whose number of values
+/// does not conform to the value multiplicity (VM)
+/// specified by the data dictionary for its attribute.
+///
+/// See [`InMemDicomObject::put_checked`]
+/// and [`InMemDicomObject::validate_vm`].
+#[derive(Debug, Clone, PartialEq, Snafu)]
+#[snafu(display("{} {} should have {} values, but found {}", alias, tag, vm, count))]
+pub struct VmViolation {
+    /// the tag of the offending data element
+    pub tag: Tag,
+    /// the attribute's alias in the data dictionary
+    pub alias: String,
+    /// the value multiplicity specified by the data dictionary
+    pub vm: ValueMultiplicity,
+    /// the actual number of values in the data element
+    pub count: u32,
 }
 
 /// An error which may occur during private element look-up or insertion
@@ -384,6 +495,66 @@ pub enum AccessByNameError {
     NoSuchAttributeName { name: String, backtrace: Backtrace },
 }
 
+/// An error which may occur when retrieving the value of an attribute
+/// through one of the typed accessors of a DICOM object,
+/// such as [`string_required`](crate::InMemDicomObject::string_required).
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum AttributeValueError {
+    /// Data element {tag} is missing or empty
+    MissingOrEmpty { tag: Tag, backtrace: Backtrace },
+    /// Could not convert value of data element {tag}
+    ConvertValue {
+        tag: Tag,
+        #[snafu(source(from(dicom_core::value::ConvertValueError, Box::from)))]
+        source: Box<dicom_core::value::ConvertValueError>,
+        backtrace: Backtrace,
+    },
+}
+
+/// An error which may occur when reinterpreting a data element
+/// of unknown value representation (UN) under another VR,
+/// such as through [`reinterpret_as`](crate::mem::ReinterpretElement::reinterpret_as).
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum ReinterpretError {
+    #[snafu(display("Element {} has VR {}, not UN", tag, vr))]
+    NotUnknown {
+        tag: Tag,
+        vr: VR,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Element {} does not hold raw bytes", tag))]
+    NotRawBytes { tag: Tag, backtrace: Backtrace },
+    #[snafu(display("Cannot reinterpret element {} as {}", tag, vr))]
+    UnsupportedVr {
+        tag: Tag,
+        vr: VR,
+        backtrace: Backtrace,
+    },
+    #[snafu(display(
+        "Length {} of element {} is not a multiple of {} bytes as required by {}",
+        len,
+        tag,
+        size,
+        vr
+    ))]
+    InvalidValueLength {
+        tag: Tag,
+        vr: VR,
+        len: u32,
+        size: u32,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Could not decode element {} as {}", tag, vr))]
+    DecodeValue {
+        tag: Tag,
+        vr: VR,
+        source: dicom_parser::stateful::decode::Error,
+        backtrace: Backtrace,
+    },
+}
+
 #[derive(Debug, Snafu)]
 #[non_exhaustive]
 pub enum WithMetaError {
@@ -400,6 +571,89 @@ pub enum WithMetaError {
     },
 }
 
+/// The set of options for writing a DICOM object.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct WriteOptions {
+    /// The convention for the lengths of data set sequences and their items
+    pub sequence_lengths: SequenceLengthStrategy,
+    /// Whether to write a DICOM file
+    /// without checking that the file meta group
+    /// is consistent with the data set
+    pub skip_consistency_checks: bool,
+    /// Whether to write the elements of each data set
+    /// in their recorded insertion order
+    pub preserve_element_order: bool,
+}
+
+impl WriteOptions {
+    /// Create the default set of writing options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the strategy for the lengths of sequences and items.
+    ///
+    /// Some applications only accept sequences of defined length,
+    /// whereas others require undefined lengths with delimiters.
+    /// By default, lengths are preserved as they are in the object.
+    pub fn sequence_lengths(mut self, sequence_lengths: SequenceLengthStrategy) -> Self {
+        self.sequence_lengths = sequence_lengths;
+        self
+    }
+
+    /// Write DICOM files without checking
+    /// that the file meta group is consistent with the data set.
+    ///
+    /// By default, writing a file fails
+    /// if the checks in [`FileDicomObject::check_consistency`] do not pass.
+    pub fn skip_consistency_checks(mut self) -> Self {
+        self.skip_consistency_checks = true;
+        self
+    }
+
+    /// Write the elements of each data set
+    /// in the order in which they were read or inserted,
+    /// for objects which [preserve insertion order](InMemDicomObject::with_insertion_order).
+    ///
+    /// By default, elements are written in ascending tag order,
+    /// as mandated by the standard.
+    /// Combined with
+    /// [`OpenFileOptions::preserve_element_order`](crate::OpenFileOptions::preserve_element_order),
+    /// this allows writing back data sets with elements out of order
+    /// exactly as they were read.
+    pub fn preserve_element_order(mut self) -> Self {
+        self.preserve_element_order = true;
+        self
+    }
+
+    fn into_tokens_options(self) -> IntoTokensOptions {
+        IntoTokensOptions::default()
+            .sequence_lengths(self.sequence_lengths)
+            .preserve_element_order(self.preserve_element_order)
+    }
+
+    fn writer_options(self) -> DataSetWriterOptions {
+        DataSetWriterOptions::default().sequence_lengths(self.sequence_lengths)
+    }
+}
+
+/// Access to the attributes of a DICOM data set
+/// which need to be consistent with the file meta group.
+///
+/// See [`FileDicomObject::check_consistency`].
+pub trait MetaConsistency {
+    /// Retrieve the _SOP Class UID_ of the data set, if present.
+    fn sop_class_uid(&self) -> Option<Cow<'_, str>>;
+
+    /// Retrieve the _SOP Instance UID_ of the data set, if present.
+    fn sop_instance_uid(&self) -> Option<Cow<'_, str>>;
+
+    /// Check whether the _Pixel Data_ of the data set is encapsulated,
+    /// returning `None` if the data set has no pixel data.
+    fn encapsulated_pixel_data(&self) -> Option<bool>;
+}
+
 /// A root DICOM object retrieved from a standard DICOM file,
 /// containing additional information from the file meta group
 /// in a separate table value.
@@ -407,6 +661,9 @@ pub enum WithMetaError {
 pub struct FileDicomObject<O> {
     meta: FileMetaTable,
     obj: O,
+    /// the UID of the transfer syntax used to read the data set,
+    /// if different from the one declared in the meta group
+    detected_transfer_syntax: Option<String>,
 }
 
 impl<O> FileDicomObject<O> {
@@ -439,6 +696,29 @@ impl<O> FileDicomObject<O> {
         self.meta.update_information_group_length();
     }
 
+    /// Retrieve the UID of the transfer syntax
+    /// which was effectively used to read the data set.
+    ///
+    /// This is the transfer syntax declared in the file meta group,
+    /// unless the data set was found to be encoded differently
+    /// when opened with
+    /// [transfer syntax detection](crate::OpenFileOptions::detect_transfer_syntax).
+    pub fn effective_transfer_syntax(&self) -> &str {
+        match &self.detected_transfer_syntax {
+            Some(uid) => uid,
+            None => self.meta.transfer_syntax(),
+        }
+    }
+
+    /// Check whether the data set was read
+    /// with a transfer syntax other than the one declared
+    /// in the file meta group.
+    ///
+    /// See [`effective_transfer_syntax`](Self::effective_transfer_syntax).
+    pub fn transfer_syntax_mismatch(&self) -> bool {
+        self.detected_transfer_syntax.is_some()
+    }
+
     /// Retrieve the inner DICOM object structure, discarding the meta table.
     pub fn into_inner(self) -> O {
         self.obj
@@ -448,12 +728,80 @@ impl<O> FileDicomObject<O> {
 impl<O> FileDicomObject<O>
 where
     for<'a> &'a O: IntoTokens,
+    O: MetaConsistency,
 {
+    /// Check whether the file meta group is consistent with the data set:
+    ///
+    /// - the _Media Storage SOP Class UID_ must be equal
+    ///   to the data set's _SOP Class UID_;
+    /// - the _Media Storage SOP Instance UID_ must be equal
+    ///   to the data set's _SOP Instance UID_;
+    /// - the pixel data must be encapsulated
+    ///   if and only if the transfer syntax encapsulates pixel data.
+    ///
+    /// Attributes missing from the data set are not checked,
+    /// nor are empty media storage UIDs,
+    /// which are inferred from the data set when reading the file.
+    /// The pixel data is not checked if the transfer syntax is not known.
+    /// These checks are done automatically when writing a DICOM file,
+    /// unless [skipped](WriteOptions::skip_consistency_checks).
+    pub fn check_consistency(&self) -> Result<(), WriteError> {
+        let trim = |uid: &str| {
+            uid.trim_end_matches(|c: char| c == '\0' || c == ' ')
+                .to_string()
+        };
+
+        let meta = trim(self.meta.media_storage_sop_class_uid());
+        if let Some(uid) = self.obj.sop_class_uid().filter(|_| !meta.is_empty()) {
+            let dataset = trim(&uid);
+            ensure!(meta == dataset, SopClassMismatchSnafu { meta, dataset });
+        }
+
+        let meta = trim(self.meta.media_storage_sop_instance_uid());
+        if let Some(uid) = self.obj.sop_instance_uid().filter(|_| !meta.is_empty()) {
+            let dataset = trim(&uid);
+            ensure!(meta == dataset, SopInstanceMismatchSnafu { meta, dataset });
+        }
+
+        let ts = TransferSyntaxRegistry.get(self.meta.transfer_syntax());
+        if let (Some(ts), Some(encapsulated)) = (ts, self.obj.encapsulated_pixel_data()) {
+            ensure!(
+                matches!(ts.codec(), Codec::EncapsulatedPixelData(..)) == encapsulated,
+                PixelDataEncapsulationMismatchSnafu {
+                    uid: ts.uid(),
+                    encapsulated,
+                }
+            );
+        }
+
+        Ok(())
+    }
+
     /// Write the entire object as a DICOM file
     /// into the given file path.
     /// Preamble, magic code, and file meta group will be included
     /// before the inner object.
+    ///
+    /// Fails if the file meta group is not
+    /// [consistent](Self::check_consistency) with the data set.
     pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), WriteError> {
+        self.write_to_file_with_options(path, WriteOptions::default())
+    }
+
+    /// Write the entire object as a DICOM file
+    /// into the given file path,
+    /// with the given writing options.
+    /// Preamble, magic code, and file meta group will be included
+    /// before the inner object.
+    pub fn write_to_file_with_options<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: WriteOptions,
+    ) -> Result<(), WriteError> {
+        if !options.skip_consistency_checks {
+            self.check_consistency()?;
+        }
+
         let path = path.as_ref();
         let file = File::create(path).context(WriteFileSnafu { filename: path })?;
         let mut to = BufWriter::new(file);
@@ -469,27 +817,34 @@ where
         // write meta group
         self.meta.write(&mut to).context(PrintMetaDataSetSnafu)?;
 
-        // prepare encoder
-        let ts = TransferSyntaxRegistry
-            .get(&self.meta.transfer_syntax)
-            .with_context(|| WriteUnsupportedTransferSyntaxSnafu {
-                uid: self.meta.transfer_syntax.clone(),
-            })?;
-        let mut dset_writer = DataSetWriter::with_ts(to, ts).context(CreatePrinterSnafu)?;
-
-        // We use the default options, because only the inner object knows if something needs to change
-        dset_writer
-            .write_sequence((&self.obj).into_tokens())
-            .context(PrintDataSetSnafu)?;
-
-        Ok(())
+        self.write_dataset_impl(to, options)
     }
 
     /// Write the entire object as a DICOM file
     /// into the given writer.
     /// Preamble, magic code, and file meta group will be included
     /// before the inner object.
+    ///
+    /// Fails if the file meta group is not
+    /// [consistent](Self::check_consistency) with the data set.
     pub fn write_all<W: Write>(&self, to: W) -> Result<(), WriteError> {
+        self.write_all_with_options(to, WriteOptions::default())
+    }
+
+    /// Write the entire object as a DICOM file
+    /// into the given writer,
+    /// with the given writing options.
+    /// Preamble, magic code, and file meta group will be included
+    /// before the inner object.
+    pub fn write_all_with_options<W: Write>(
+        &self,
+        to: W,
+        options: WriteOptions,
+    ) -> Result<(), WriteError> {
+        if !options.skip_consistency_checks {
+            self.check_consistency()?;
+        }
+
         let mut to = BufWriter::new(to);
 
         // write preamble
@@ -501,20 +856,7 @@ where
         // write meta group
         self.meta.write(&mut to).context(PrintMetaDataSetSnafu)?;
 
-        // prepare encoder
-        let ts = TransferSyntaxRegistry
-            .get(&self.meta.transfer_syntax)
-            .with_context(|| WriteUnsupportedTransferSyntaxSnafu {
-                uid: self.meta.transfer_syntax.clone(),
-            })?;
-        let mut dset_writer = DataSetWriter::with_ts(to, ts).context(CreatePrinterSnafu)?;
-
-        // We use the default options, because only the inner object knows if something needs to change
-        dset_writer
-            .write_sequence((&self.obj).into_tokens())
-            .context(PrintDataSetSnafu)?;
-
-        Ok(())
+        self.write_dataset_impl(to, options)
     }
 
     /// Write the file meta group set into the given writer.
@@ -529,19 +871,36 @@ where
     ///
     /// The transfer syntax is selected from the file meta table.
     pub fn write_dataset<W: Write>(&self, to: W) -> Result<(), WriteError> {
-        let to = BufWriter::new(to);
+        self.write_dataset_with_options(to, WriteOptions::default())
+    }
 
+    /// Write the inner data set into the given writer,
+    /// with the given writing options,
+    /// without preamble, magic code, nor file meta group.
+    ///
+    /// The transfer syntax is selected from the file meta table.
+    pub fn write_dataset_with_options<W: Write>(
+        &self,
+        to: W,
+        options: WriteOptions,
+    ) -> Result<(), WriteError> {
+        self.write_dataset_impl(BufWriter::new(to), options)
+    }
+
+    fn write_dataset_impl<W: Write>(&self, to: W, options: WriteOptions) -> Result<(), WriteError> {
         // prepare encoder
         let ts = TransferSyntaxRegistry
-            .get(&self.meta.transfer_syntax)
+            .get(self.meta.transfer_syntax())
             .with_context(|| WriteUnsupportedTransferSyntaxSnafu {
                 uid: self.meta.transfer_syntax.clone(),
             })?;
-        let mut dset_writer = DataSetWriter::with_ts(to, ts).context(CreatePrinterSnafu)?;
+        let mut dset_writer = DataSetWriter::with_ts_options(to, ts, options.writer_options())
+            .context(CreatePrinterSnafu)?;
 
-        // write object
+        // only the inner object knows whether its sequence lengths are still valid,
+        // so it may override the given options
         dset_writer
-            .write_sequence((&self.obj).into_tokens())
+            .write_sequence((&self.obj).into_tokens_with_options(options.into_tokens_options()))
             .context(PrintDataSetSnafu)?;
 
         Ok(())
@@ -675,9 +1034,8 @@ where
 
     fn photometric_interpretation(&self) -> Option<&str> {
         self.get(dicom_dictionary_std::tags::PHOTOMETRIC_INTERPRETATION)?
-            .string()
+            .trimmed_str()
             .ok()
-            .map(|s| s.trim_end())
     }
 
     /// Return the NumberOfFrames attribute or None if it is not set
@@ -723,6 +1081,22 @@ where
         }
     }
 
+    /// Return the ExtendedOffsetTable attribute or None if it is not set
+    fn extended_offset_table(&self) -> Option<Cow<[u64]>> {
+        self.get(dicom_dictionary_std::tags::EXTENDED_OFFSET_TABLE)?
+            .to_multi_int()
+            .ok()
+            .map(Cow::Owned)
+    }
+
+    /// Return the ExtendedOffsetTableLengths attribute or None if it is not set
+    fn extended_offset_table_lengths(&self) -> Option<Cow<[u64]>> {
+        self.get(dicom_dictionary_std::tags::EXTENDED_OFFSET_TABLE_LENGTHS)?
+            .to_multi_int()
+            .ok()
+            .map(Cow::Owned)
+    }
+
     /// Should return either a byte slice/vector if native pixel data
     /// or byte fragments if encapsulated.
     /// Returns None if no pixel data is found
@@ -753,10 +1127,15 @@ where
 
 #[cfg(test)]
 mod tests {
-    use dicom_core::{DataElement, PrimitiveValue, VR};
+    use dicom_core::value::PixelFragmentSequence;
+    use dicom_core::{DataElement, Length, PrimitiveValue, VR};
+    use dicom_encoding::adapters::{FragmentLayout, PixelDataObject};
 
     use crate::meta::FileMetaTableBuilder;
-    use crate::{AccessError, FileDicomObject, InMemDicomObject};
+    use crate::{
+        AccessError, FileDicomObject, InMemDicomObject, SequenceLengthStrategy, WriteError,
+        WriteOptions,
+    };
 
     fn assert_type_not_too_large<T>(max_size: usize) {
         let size = std::mem::size_of::<T>();
@@ -889,6 +1268,102 @@ mod tests {
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn write_dataset_with_sequence_length_strategies() {
+        use dicom_core::value::DataSetSequence;
+        use dicom_dictionary_std::tags;
+        use dicom_transfer_syntax_registry::entries::EXPLICIT_VR_LITTLE_ENDIAN;
+
+        const SEQUENCE_DELIMITER: [u8; 4] = [0xfe, 0xff, 0xdd, 0xe0];
+
+        // sequences built in memory have undefined lengths
+        let code = InMemDicomObject::from_element_iter([DataElement::new(
+            tags::CODE_VALUE,
+            VR::SH,
+            PrimitiveValue::from("T1"),
+        )]);
+        let request = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::SCHEDULED_PROCEDURE_STEP_DESCRIPTION,
+                VR::LO,
+                PrimitiveValue::from("ABCD"),
+            ),
+            DataElement::new(
+                tags::SCHEDULED_PROTOCOL_CODE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![code]),
+            ),
+        ]);
+        let obj = InMemDicomObject::from_element_iter([DataElement::new(
+            tags::REQUEST_ATTRIBUTES_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![request]),
+        )])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7")
+                .media_storage_sop_instance_uid("1.2.23456789")
+                .transfer_syntax(EXPLICIT_VR_LITTLE_ENDIAN.uid()),
+        )
+        .unwrap();
+
+        let write = |strategy| {
+            let mut out = Vec::new();
+            obj.write_dataset_with_options(
+                &mut out,
+                WriteOptions::new().sequence_lengths(strategy),
+            )
+            .unwrap();
+            out
+        };
+        let count_delimiters =
+            |data: &[u8]| data.windows(4).filter(|w| *w == SEQUENCE_DELIMITER).count();
+
+        let preserved = write(SequenceLengthStrategy::PreserveOriginal);
+        assert_eq!(preserved, {
+            let mut out = Vec::new();
+            obj.write_dataset(&mut out).unwrap();
+            out
+        });
+        assert_eq!(count_delimiters(&preserved), 2);
+
+        let undefined = write(SequenceLengthStrategy::AllUndefined);
+        assert_eq!(count_delimiters(&undefined), 2);
+
+        let defined = write(SequenceLengthStrategy::AllDefined);
+        assert_eq!(count_delimiters(&defined), 0);
+        // the two sequence delimiters and two item delimiters are gone
+        assert_eq!(defined.len(), undefined.len() - 4 * 8);
+        // outer sequence length: item (8 + 42)
+        assert_eq!(&defined[8..12], &50_u32.to_le_bytes());
+        // outer item length: description (12) + inner sequence (12 + 18)
+        assert_eq!(&defined[16..20], &42_u32.to_le_bytes());
+
+        let ts = EXPLICIT_VR_LITTLE_ENDIAN.erased();
+        for data in [preserved, undefined, defined] {
+            let obj2 = InMemDicomObject::read_dataset_with_ts(&data[..], &ts).unwrap();
+            let request = &obj2
+                .get(tags::REQUEST_ATTRIBUTES_SEQUENCE)
+                .unwrap()
+                .items()
+                .unwrap()[0];
+            assert_eq!(
+                request
+                    .get(tags::SCHEDULED_PROCEDURE_STEP_DESCRIPTION)
+                    .unwrap()
+                    .to_str()
+                    .unwrap(),
+                "ABCD",
+            );
+            let code = &request
+                .get(tags::SCHEDULED_PROTOCOL_CODE_SEQUENCE)
+                .unwrap()
+                .items()
+                .unwrap()[0];
+            assert_eq!(code.get(tags::CODE_VALUE).unwrap().to_str().unwrap(), "T1");
+        }
+    }
+
     #[test]
     pub fn file_dicom_can_update_meta() {
         let meta = FileMetaTableBuilder::new()
@@ -911,4 +1386,381 @@ mod tests {
             Some("SOMETHING"),
         );
     }
+
+    /// Create a file object with the given number of frames
+    /// and encapsulated pixel data.
+    fn encapsulated_object(
+        number_of_frames: u32,
+        offset_table: Vec<u32>,
+        fragments: Vec<Vec<u8>>,
+    ) -> FileDicomObject<InMemDicomObject> {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(
+                dicom_dictionary_std::tags::NUMBER_OF_FRAMES,
+                VR::IS,
+                PrimitiveValue::from(number_of_frames.to_string()),
+            ),
+            DataElement::new_with_len(
+                dicom_dictionary_std::tags::PIXEL_DATA,
+                VR::OB,
+                Length::UNDEFINED,
+                PixelFragmentSequence::new(offset_table, fragments),
+            ),
+        ])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7")
+                .media_storage_sop_instance_uid("1.2.23456789")
+                // JPEG baseline
+                .transfer_syntax("1.2.840.10008.1.2.4.50"),
+        )
+        .unwrap()
+    }
+
+    /// Encoded frame lengths are reported from the fragments
+    /// without decoding them.
+    #[test]
+    fn encoded_frame_lengths_from_fragments() {
+        // one fragment per frame, empty basic offset table
+        let obj = encapsulated_object(3, vec![], vec![vec![1; 10], vec![2; 20], vec![3; 30]]);
+        assert_eq!(obj.encoded_frame_lengths(), Some(vec![10, 20, 30]));
+        assert_eq!(obj.encoded_total_length(), Some(60));
+
+        // single frame in multiple fragments, empty basic offset table
+        let obj = encapsulated_object(1, vec![], vec![vec![1; 10], vec![2; 20], vec![3; 30]]);
+        assert_eq!(obj.encoded_frame_lengths(), Some(vec![60]));
+        assert_eq!(obj.encoded_total_length(), Some(60));
+
+        // multiple fragments per frame, grouped by the basic offset table
+        let fragments = vec![
+            vec![1; 10],
+            vec![1; 12],
+            vec![2; 20],
+            vec![3; 30],
+            vec![3; 4],
+        ];
+        let obj = encapsulated_object(3, vec![0, 38, 66], fragments);
+        let lengths = obj.encoded_frame_lengths().unwrap();
+        assert_eq!(lengths, vec![22, 20, 34]);
+        assert_eq!(lengths.iter().sum::<u64>(), 76);
+        assert_eq!(obj.encoded_total_length(), Some(76));
+
+        // multiple fragments per frame, empty basic offset table
+        let fragments = vec![vec![1; 10], vec![1; 12], vec![2; 20], vec![3; 30]];
+        let obj = encapsulated_object(3, vec![], fragments.clone());
+        assert_eq!(obj.encoded_frame_lengths(), None);
+        assert_eq!(obj.encoded_total_length(), Some(72));
+
+        // frame offset in the middle of a fragment
+        let obj = encapsulated_object(3, vec![0, 20, 66], fragments);
+        assert_eq!(obj.encoded_frame_lengths(), None);
+    }
+
+    /// Frames are located through the extended offset table,
+    /// and their lengths are taken from the extended offset table lengths.
+    #[test]
+    fn encoded_frame_lengths_from_extended_offset_table() {
+        let fragments = vec![
+            vec![1; 10],
+            vec![1; 12],
+            vec![2; 20],
+            vec![3; 30],
+            vec![3; 4],
+        ];
+        let mut obj = encapsulated_object(3, vec![], fragments);
+        obj.put(DataElement::new(
+            dicom_dictionary_std::tags::EXTENDED_OFFSET_TABLE,
+            VR::OV,
+            PrimitiveValue::U64(vec![0, 38, 66].into()),
+        ));
+
+        let raw = obj.raw_pixel_data().unwrap();
+        let layout = FragmentLayout::from_object(&obj).unwrap();
+        assert_eq!(layout.extended_offset_table, vec![0, 38, 66]);
+        assert_eq!(raw.frame_data(&layout, 1).as_deref(), Some(&[2; 20][..]));
+        assert_eq!(raw.frame_data(&layout, 2).unwrap().len(), 34);
+        assert_eq!(obj.encoded_frame_lengths(), Some(vec![22, 20, 34]));
+
+        obj.put(DataElement::new(
+            dicom_dictionary_std::tags::EXTENDED_OFFSET_TABLE_LENGTHS,
+            VR::OV,
+            PrimitiveValue::U64(vec![22, 20, 33].into()),
+        ));
+        assert_eq!(obj.encoded_frame_lengths(), Some(vec![22, 20, 33]));
+    }
+
+    /// Encoded lengths are not reported for native pixel data.
+    #[test]
+    fn encoded_frame_lengths_native() {
+        let obj = InMemDicomObject::from_element_iter([DataElement::new(
+            dicom_dictionary_std::tags::PIXEL_DATA,
+            VR::OB,
+            PrimitiveValue::from(vec![0_u8; 64]),
+        )])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7")
+                .media_storage_sop_instance_uid("1.2.23456789")
+                .transfer_syntax("1.2.840.10008.1.2.1"),
+        )
+        .unwrap();
+
+        assert_eq!(obj.encoded_frame_lengths(), None);
+        assert_eq!(obj.encoded_total_length(), None);
+    }
+
+    fn sop_object(sop_class_uid: &str, sop_instance_uid: &str) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(
+                dicom_dictionary_std::tags::SOP_CLASS_UID,
+                VR::UI,
+                PrimitiveValue::from(sop_class_uid),
+            ),
+            DataElement::new(
+                dicom_dictionary_std::tags::SOP_INSTANCE_UID,
+                VR::UI,
+                PrimitiveValue::from(sop_instance_uid),
+            ),
+        ])
+    }
+
+    fn sop_meta(sop_class_uid: &str, sop_instance_uid: &str, ts: &str) -> crate::FileMetaTable {
+        FileMetaTableBuilder::new()
+            .media_storage_sop_class_uid(sop_class_uid)
+            .media_storage_sop_instance_uid(sop_instance_uid)
+            .transfer_syntax(ts)
+            .build()
+            .unwrap()
+    }
+
+    /// Writing a file fails if the SOP class or instance UIDs
+    /// in the meta group do not match the data set.
+    #[test]
+    fn write_checks_sop_consistency() {
+        // CT Image Storage in meta group, MR Image Storage in data set
+        let obj = sop_object("1.2.840.10008.5.1.4.1.1.4", "1.2.3.4").with_exact_meta(sop_meta(
+            "1.2.840.10008.5.1.4.1.1.2",
+            "1.2.3.4",
+            "1.2.840.10008.1.2.1",
+        ));
+        let mut out = Vec::new();
+        let err = obj.write_all(&mut out).unwrap_err();
+        match err {
+            WriteError::SopClassMismatch { meta, dataset, .. } => {
+                assert_eq!(meta, "1.2.840.10008.5.1.4.1.1.2");
+                assert_eq!(dataset, "1.2.840.10008.5.1.4.1.1.4");
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+        // nothing was written
+        assert!(out.is_empty());
+
+        let obj = sop_object("1.2.840.10008.5.1.4.1.1.2", "1.2.3.5").with_exact_meta(sop_meta(
+            "1.2.840.10008.5.1.4.1.1.2",
+            "1.2.3.4",
+            "1.2.840.10008.1.2.1",
+        ));
+        let err = obj.write_all(Vec::new()).unwrap_err();
+        match err {
+            WriteError::SopInstanceMismatch { meta, dataset, .. } => {
+                assert_eq!(meta, "1.2.3.4");
+                assert_eq!(dataset, "1.2.3.5");
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+        assert!(err_to_string_mentions(
+            &obj.check_consistency().unwrap_err(),
+            &["1.2.3.4", "1.2.3.5"]
+        ));
+
+        // UIDs with padding are still consistent
+        let obj = sop_object("1.2.840.10008.5.1.4.1.1.2\0", "1.2.3.4\0").with_exact_meta(sop_meta(
+            "1.2.840.10008.5.1.4.1.1.2",
+            "1.2.3.4",
+            "1.2.840.10008.1.2.1",
+        ));
+        obj.write_all(Vec::new()).unwrap();
+    }
+
+    fn err_to_string_mentions(err: &WriteError, values: &[&str]) -> bool {
+        let msg = err.to_string();
+        values.iter().all(|v| msg.contains(v))
+    }
+
+    /// Writing a file fails if the transfer syntax in the meta group
+    /// does not match the encapsulation of the pixel data.
+    #[test]
+    fn write_checks_pixel_data_encapsulation() {
+        // native pixel data with JPEG baseline
+        let mut obj = sop_object("1.2.840.10008.5.1.4.1.1.7", "1.2.3.4").with_exact_meta(sop_meta(
+            "1.2.840.10008.5.1.4.1.1.7",
+            "1.2.3.4",
+            "1.2.840.10008.1.2.4.50",
+        ));
+        obj.put(DataElement::new(
+            dicom_dictionary_std::tags::PIXEL_DATA,
+            VR::OB,
+            PrimitiveValue::from(vec![0_u8; 64]),
+        ));
+        let err = obj.write_all(Vec::new()).unwrap_err();
+        assert!(matches!(
+            &err,
+            WriteError::PixelDataEncapsulationMismatch {
+                uid,
+                encapsulated: false,
+                ..
+            } if uid == "1.2.840.10008.1.2.4.50"
+        ));
+        assert!(err_to_string_mentions(
+            &err,
+            &["1.2.840.10008.1.2.4.50", "native"]
+        ));
+
+        // encapsulated pixel data with explicit VR little endian
+        let mut obj = encapsulated_object(1, vec![], vec![vec![0xFF; 16]]);
+        obj.update_meta(|meta| meta.transfer_syntax = "1.2.840.10008.1.2.1".to_string());
+        let err = obj.write_all(Vec::new()).unwrap_err();
+        assert!(matches!(
+            err,
+            WriteError::PixelDataEncapsulationMismatch {
+                encapsulated: true,
+                ..
+            }
+        ));
+
+        // consistent
+        let obj = encapsulated_object(1, vec![], vec![vec![0xFF; 16]]);
+        obj.check_consistency().unwrap();
+    }
+
+    /// Consistency checks can be skipped when writing.
+    #[test]
+    fn write_skip_consistency_checks() {
+        let obj = sop_object("1.2.840.10008.5.1.4.1.1.4", "1.2.3.5").with_exact_meta(sop_meta(
+            "1.2.840.10008.5.1.4.1.1.2",
+            "1.2.3.4",
+            "1.2.840.10008.1.2.1",
+        ));
+        assert!(obj.check_consistency().is_err());
+
+        let mut out = Vec::new();
+        obj.write_all_with_options(&mut out, WriteOptions::new().skip_consistency_checks())
+            .unwrap();
+        assert!(!out.is_empty());
+
+        // meta group is written as is
+        let saved = crate::from_reader(&out[128..]).unwrap();
+        assert_eq!(
+            saved.meta().media_storage_sop_class_uid(),
+            "1.2.840.10008.5.1.4.1.1.2"
+        );
+    }
+
+    /// A data set with elements out of order
+    /// can be written back exactly as it was read.
+    #[test]
+    fn write_dataset_preserving_element_order() {
+        use dicom_dictionary_std::tags;
+        use dicom_transfer_syntax_registry::entries::EXPLICIT_VR_LITTLE_ENDIAN;
+
+        #[rustfmt::skip]
+        static DATASET: &[u8] = &[
+            // (0010,0010) PatientName
+            0x10, 0x00, 0x10, 0x00, b'P', b'N', 0x08, 0x00,
+            b'D', b'o', b'e', b'^', b'J', b'o', b'h', b'n',
+            // (0008,0060) Modality
+            0x08, 0x00, 0x60, 0x00, b'C', b'S', 0x02, 0x00, b'O', b'T',
+            // (0040,0275) RequestAttributesSequence, undefined length
+            0x40, 0x00, 0x75, 0x02, b'S', b'Q', 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF,
+            // item, undefined length
+            0xFE, 0xFF, 0x00, 0xE0, 0xFF, 0xFF, 0xFF, 0xFF,
+            // (0040,0009) ScheduledProcedureStepID
+            0x40, 0x00, 0x09, 0x00, b'S', b'H', 0x04, 0x00, b'S', b'P', b'S', b'1',
+            // (0040,0007) ScheduledProcedureStepDescription
+            0x40, 0x00, 0x07, 0x00, b'L', b'O', 0x04, 0x00, b'A', b'B', b'C', b'D',
+            // item delimiter
+            0xFE, 0xFF, 0x0D, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // sequence delimiter
+            0xFE, 0xFF, 0xDD, 0xE0, 0x00, 0x00, 0x00, 0x00,
+            // (0008,0020) StudyDate
+            0x08, 0x00, 0x20, 0x00, b'D', b'A', 0x08, 0x00,
+            b'2', b'0', b'2', b'4', b'0', b'1', b'0', b'1',
+        ];
+
+        let meta = FileMetaTableBuilder::new()
+            .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7")
+            .media_storage_sop_instance_uid("1.2.23456789")
+            .transfer_syntax(EXPLICIT_VR_LITTLE_ENDIAN.uid())
+            .build()
+            .unwrap();
+        let mut file = b"DICM".to_vec();
+        meta.write(&mut file).unwrap();
+        file.extend_from_slice(DATASET);
+
+        let write = |obj: &FileDicomObject<InMemDicomObject>, options| {
+            let mut out = Vec::new();
+            obj.write_dataset_with_options(&mut out, options).unwrap();
+            out
+        };
+
+        let obj = crate::OpenFileOptions::new()
+            .preserve_element_order(true)
+            .from_reader(&file[..])
+            .unwrap();
+        assert!(obj.preserves_insertion_order());
+        let item = &obj
+            .get(tags::REQUEST_ATTRIBUTES_SEQUENCE)
+            .unwrap()
+            .items()
+            .unwrap()[0];
+        assert!(item.preserves_insertion_order());
+        assert_eq!(
+            item.get(tags::SCHEDULED_PROCEDURE_STEP_DESCRIPTION)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "ABCD",
+        );
+
+        // written back in the original order
+        let out = write(&obj, WriteOptions::new().preserve_element_order());
+        assert_eq!(out, DATASET);
+
+        // written in ascending tag order otherwise
+        let sorted = write(&obj, WriteOptions::new());
+        assert_eq!(sorted.len(), DATASET.len());
+        assert_eq!(&sorted[..4], &[0x08, 0x00, 0x20, 0x00]);
+        let obj2 = crate::from_reader(&file[..]).unwrap();
+        assert!(!obj2.preserves_insertion_order());
+        assert_eq!(
+            write(&obj2, WriteOptions::new().preserve_element_order()),
+            sorted
+        );
+    }
+
+    /// The file meta group can be fixed to match the data set.
+    #[test]
+    fn with_exact_meta_autofix_copies_sop_uids() {
+        let obj =
+            sop_object("1.2.840.10008.5.1.4.1.1.4", "1.2.3.5").with_exact_meta_autofix(sop_meta(
+                "1.2.840.10008.5.1.4.1.1.2",
+                "1.2.3.4",
+                "1.2.840.10008.1.2.1",
+            ));
+        assert_eq!(
+            obj.meta().media_storage_sop_class_uid(),
+            "1.2.840.10008.5.1.4.1.1.4"
+        );
+        assert_eq!(obj.meta().media_storage_sop_instance_uid(), "1.2.3.5");
+        obj.check_consistency().unwrap();
+
+        let mut out = Vec::new();
+        obj.write_all(&mut out).unwrap();
+        let saved = crate::from_reader(&out[128..]).unwrap();
+        assert_eq!(
+            saved.meta().media_storage_sop_class_uid(),
+            "1.2.840.10008.5.1.4.1.1.4"
+        );
+        assert_eq!(saved.meta().media_storage_sop_instance_uid(), "1.2.3.5");
+    }
 }
@@ -39,44 +39,54 @@
 use dicom_core::ops::{
     ApplyOp, AttributeAction, AttributeOp, AttributeSelector, AttributeSelectorStep,
 };
-use dicom_parser::dataset::read::{DataSetReaderOptions, OddLengthStrategy};
+use dicom_parser::dataset::read::DataSetReaderOptions;
 use itertools::Itertools;
 use smallvec::SmallVec;
 use snafu::{ensure, OptionExt, ResultExt};
 use std::borrow::Cow;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::ops::ControlFlow;
 use std::path::Path;
 use std::{collections::BTreeMap, io::Write};
 
-use crate::file::ReadPreamble;
+use crate::file::{OpenFileOptions, ReadPreamble};
+use crate::items::InstanceReference;
 use crate::ops::{
     ApplyError, ApplyResult, IncompatibleTypesSnafu, ModifySnafu, UnsupportedActionSnafu,
 };
+use crate::trace;
 use crate::{meta::FileMetaTable, FileMetaTableBuilder};
 use crate::{
-    AccessByNameError, AccessError, AtAccessError, BuildMetaTableSnafu, CreateParserSnafu,
-    CreatePrinterSnafu, DicomObject, ElementNotFoundSnafu, FileDicomObject, InvalidGroupSnafu,
-    MissingElementValueSnafu, MissingLeafElementSnafu, NoSpaceSnafu, NoSuchAttributeNameSnafu,
-    NoSuchDataElementAliasSnafu, NoSuchDataElementTagSnafu, NotASequenceSnafu, OpenFileSnafu,
-    ParseMetaDataSetSnafu, ParseSopAttributeSnafu, PrematureEndSnafu, PrepareMetaTableSnafu,
-    PrintDataSetSnafu, PrivateCreatorNotFoundSnafu, PrivateElementError, ReadError, ReadFileSnafu,
-    ReadPreambleBytesSnafu, ReadTokenSnafu, ReadUnsupportedTransferSyntaxSnafu,
-    UnexpectedTokenSnafu, WithMetaError, WriteError,
+    AccessByNameError, AccessError, AtAccessError, AttributeValueError, BuildMetaTableSnafu,
+    ConvertValueSnafu, CreateParserSnafu, CreatePrinterSnafu, DecodeValueSnafu, DicomObject,
+    ElementNotFoundSnafu, FileDicomObject, InvalidGroupSnafu, InvalidValueLengthSnafu,
+    MetaConsistency, MissingElementValueSnafu, MissingLeafElementSnafu, MissingOrEmptySnafu,
+    NoSpaceSnafu, NoSuchAttributeNameSnafu, NoSuchDataElementAliasSnafu, NoSuchDataElementTagSnafu,
+    NotASequenceSnafu, NotRawBytesSnafu, NotUnknownSnafu, OpenFileSnafu, ParseMetaDataSetSnafu,
+    ParseSopAttributeSnafu, PrematureEndSnafu, PrepareMetaTableSnafu, PrintDataSetSnafu,
+    PrivateCreatorNotFoundSnafu, PrivateElementError, ReadDataSetHeadSnafu, ReadError,
+    ReadFileSnafu, ReadPreambleBytesSnafu, ReadTokenSnafu, ReadUnsupportedTransferSyntaxSnafu,
+    ReinterpretError, UnexpectedTokenSnafu, UnsupportedVrSnafu, VmViolation, WithMetaError,
+    WriteError,
 };
 use dicom_core::dictionary::{DataDictionary, DataDictionaryEntry};
-use dicom_core::header::{GroupNumber, HasLength, Header};
-use dicom_core::value::{DataSetSequence, PixelFragmentSequence, Value, ValueType, C};
+use dicom_core::header::{DataElementHeader, GroupNumber, HasLength, Header};
+use dicom_core::value::{
+    trim_padding, ConvertValueError, DataSetSequence, DicomDate, PixelFragmentSequence, Value,
+    ValueType, C,
+};
 use dicom_core::{DataElement, Length, PrimitiveValue, Tag, VR};
 use dicom_dictionary_std::{tags, StandardDataDictionary};
 use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
-use dicom_encoding::{encode::EncodeTo, text::SpecificCharacterSet, TransferSyntax};
+use dicom_encoding::{encode::EncodeTo, text::SpecificCharacterSet, Endianness, TransferSyntax};
 use dicom_parser::dataset::{DataSetReader, DataToken, IntoTokensOptions};
 use dicom_parser::{
     dataset::{read::Error as ParserError, DataSetWriter, IntoTokens},
+    stateful::decode::StatefulDecoder,
     StatefulDecode,
 };
-use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+use dicom_transfer_syntax_registry::{entries, TransferSyntaxRegistry};
 
 /// A full in-memory DICOM data element.
 pub type InMemElement<D = StandardDataDictionary> = DataElement<InMemDicomObject<D>, InMemFragment>;
@@ -88,6 +98,40 @@ type Result<T, E = AccessError> = std::result::Result<T, E>;
 
 type ParserResult<T> = std::result::Result<T, ParserError>;
 
+/// Obtain the number of values in a data element
+/// for the purpose of value multiplicity validation,
+/// or `None` if the element is not subject to it.
+fn value_count<I, P>(elt: &DataElement<I, P>) -> Option<u32> {
+    match elt.value() {
+        Value::Primitive(v) => match (elt.vr(), v) {
+            // the number of bytes or words is not the number of values
+            (VR::OB | VR::OD | VR::OF | VR::OL | VR::OV | VR::OW | VR::UN, _) => None,
+            _ if v.calculate_byte_len() == 0 => None,
+            // these are never multi-valued, backslashes included
+            (VR::LT | VR::ST | VR::UT | VR::UR, _) => Some(v.multiplicity()),
+            // a single string may hold several values separated by backslashes
+            (_, PrimitiveValue::Str(s)) => Some(s.split('\\').count() as u32),
+            (_, PrimitiveValue::Strs(s)) => {
+                Some(s.iter().map(|s| s.split('\\').count() as u32).sum())
+            }
+            _ => Some(v.multiplicity()),
+        },
+        Value::Sequence(_) | Value::PixelSequence(_) => None,
+    }
+}
+
+/// Whether the given value is empty
+/// under the rule of the typed accessors of [`InMemDicomObject`],
+/// such as [`string_opt`](InMemDicomObject::string_opt).
+fn is_empty_value<I, P>(value: &Value<I, P>) -> bool {
+    match value {
+        Value::Primitive(PrimitiveValue::Str(s)) => trim_padding(s).is_empty(),
+        Value::Primitive(PrimitiveValue::Strs(s)) => s.iter().all(|s| trim_padding(s).is_empty()),
+        Value::Primitive(v) => v.multiplicity() == 0,
+        Value::Sequence(_) | Value::PixelSequence(_) => false,
+    }
+}
+
 /// A DICOM object that is fully contained in memory.
 ///
 /// See the [module-level documentation](self)
@@ -106,6 +150,9 @@ pub struct InMemDicomObject<D = StandardDataDictionary> {
     /// because changing the character set may change the length in bytes of
     /// stored text. It has to be public for now because we need
     pub(crate) charset_changed: bool,
+    /// The tags of the elements in the order in which they were inserted,
+    /// if the object preserves insertion order.
+    order: Option<Vec<Tag>>,
 }
 
 impl<D> PartialEq for InMemDicomObject<D> {
@@ -165,6 +212,17 @@ impl FileDicomObject<InMemDicomObject<StandardDataDictionary>> {
     {
         Self::from_reader_with_dict(src, StandardDataDictionary)
     }
+
+    /// Create a DICOM object from a buffer
+    /// holding the full contents of a DICOM file.
+    ///
+    /// The same file structure as in [`from_reader`](Self::from_reader)
+    /// is assumed,
+    /// but values are read directly from the buffer
+    /// without going through an intermediate buffered reader.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReadError> {
+        Self::from_bytes_with_dict(bytes, StandardDataDictionary)
+    }
 }
 
 impl InMemDicomObject<StandardDataDictionary> {
@@ -175,6 +233,7 @@ impl InMemDicomObject<StandardDataDictionary> {
             dict: StandardDataDictionary,
             len: Length::UNDEFINED,
             charset_changed: false,
+            order: None,
         }
     }
 
@@ -277,10 +336,30 @@ where
                 dict,
                 len: Length::UNDEFINED,
                 charset_changed: false,
+                order: None,
             },
+            detected_transfer_syntax: None,
         }
     }
 
+    /// Reinterpret the elements of unknown value representation (UN)
+    /// whose attribute is known to the given data element dictionary,
+    /// using the byte order of the transfer syntax in the file meta group.
+    ///
+    /// Little endian is assumed
+    /// if the transfer syntax is not in the registry.
+    /// See [`InMemDicomObject::promote_un_elements`] for more details.
+    pub fn promote_un_elements<Di>(&mut self, dict: &Di) -> usize
+    where
+        Di: DataDictionary,
+    {
+        let endianness = TransferSyntaxRegistry
+            .get(self.meta.transfer_syntax())
+            .map(|ts| ts.endianness())
+            .unwrap_or(Endianness::Little);
+        self.obj.promote_un_elements_with(dict, endianness)
+    }
+
     /// Create a DICOM object by reading from a file.
     ///
     /// This function assumes the standard file encoding structure:
@@ -312,11 +391,9 @@ where
     {
         Self::open_file_with_all_options(
             path,
-            dict,
-            ts_index,
-            None,
-            ReadPreamble::Auto,
-            Default::default(),
+            OpenFileOptions::new()
+                .dictionary(dict)
+                .transfer_syntax_index(ts_index),
         )
     }
 
@@ -345,18 +422,116 @@ where
         Ok(ReadPreamble::Auto)
     }
 
-    pub(crate) fn open_file_with_all_options<P, R>(
-        path: P,
+    // examine the header of the first data set element
+    // to detect whether it was encoded in explicit VR little endian
+    // while declared as implicit VR little endian, or vice versa,
+    // returning the transfer syntax actually in use if so
+    fn detect_transfer_syntax<'a, R>(
+        ts_index: &'a R,
+        declared: &TransferSyntax,
+        head: &[u8],
+    ) -> Option<&'a TransferSyntax>
+    where
+        R: TransferSyntaxIndex,
+    {
+        let other = if declared.uid() == entries::EXPLICIT_VR_LITTLE_ENDIAN.uid() {
+            entries::IMPLICIT_VR_LITTLE_ENDIAN.uid()
+        } else if declared.uid() == entries::IMPLICIT_VR_LITTLE_ENDIAN.uid() {
+            entries::EXPLICIT_VR_LITTLE_ENDIAN.uid()
+        } else {
+            return None;
+        };
+
+        if head.len() < 8 {
+            return None;
+        }
+        // the data set should not start with file meta group elements
+        let group = u16::from_le_bytes([head[0], head[1]]);
+        if group <= 0x0002 {
+            return None;
+        }
+
+        // in explicit VR, the tag is followed by the VR letters,
+        // whereas in implicit VR it is followed by a 32-bit length
+        let looks_explicit = VR::from_binary([head[4], head[5]]).is_some();
+        if looks_explicit == declared.explicit_vr() {
+            return None;
+        }
+
+        let ts = ts_index.get(other)?;
+        tracing::warn!(
+            "Data set is encoded in {} instead of the declared transfer syntax {}, reading it as such",
+            ts.name(),
+            declared.name(),
+        );
+        Some(ts)
+    }
+
+    /// Read the data set which follows the file meta group
+    /// from the given byte source,
+    /// tracing the parsing process if `debug_trace` is set.
+    fn read_data_set<S>(
+        source: S,
+        ts: &TransferSyntax,
+        options: DataSetReaderOptions,
         dict: D,
-        ts_index: R,
         read_until: Option<Tag>,
-        mut read_preamble: ReadPreamble,
-        odd_length: OddLengthStrategy,
+        preserve_element_order: bool,
+        debug_trace: Option<usize>,
+    ) -> Result<InMemDicomObject<D>, ReadError>
+    where
+        S: Read,
+    {
+        match debug_trace {
+            Some(window) => trace::read_traced(source, ts, options, window, |dataset| {
+                InMemDicomObject::build_object(
+                    dataset,
+                    dict,
+                    false,
+                    Length::UNDEFINED,
+                    read_until,
+                    preserve_element_order,
+                )
+            }),
+            None => {
+                let mut dataset = DataSetReader::new_with_ts_cs_options(
+                    source,
+                    ts,
+                    SpecificCharacterSet::default(),
+                    options,
+                )
+                .context(CreateParserSnafu)?;
+                InMemDicomObject::build_object(
+                    &mut dataset,
+                    dict,
+                    false,
+                    Length::UNDEFINED,
+                    read_until,
+                    preserve_element_order,
+                )
+            }
+        }
+    }
+
+    pub(crate) fn open_file_with_all_options<P, R>(
+        path: P,
+        options: OpenFileOptions<D, R>,
     ) -> Result<Self, ReadError>
     where
         P: AsRef<Path>,
         R: TransferSyntaxIndex,
     {
+        let OpenFileOptions {
+            data_dictionary: dict,
+            ts_index,
+            read_until,
+            mut read_preamble,
+            odd_length,
+            detect_transfer_syntax,
+            preserve_element_order,
+            debug_trace,
+        } = options;
+
         let path = path.as_ref();
         let mut file =
             BufReader::new(File::open(path).with_context(|_| OpenFileSnafu { filename: path })?);
@@ -377,22 +552,28 @@ where
         let mut meta = FileMetaTable::from_reader(&mut file).context(ParseMetaDataSetSnafu)?;
 
         // read rest of data according to metadata, feed it to object
-        if let Some(ts) = ts_index.get(&meta.transfer_syntax) {
+        if let Some(ts) = ts_index.get(meta.transfer_syntax()) {
+            let mut head = Vec::new();
+            let detected_ts = if detect_transfer_syntax {
+                (&mut file)
+                    .take(8)
+                    .read_to_end(&mut head)
+                    .with_context(|_| ReadFileSnafu { filename: path })?;
+                Self::detect_transfer_syntax(&ts_index, ts, &head)
+            } else {
+                None
+            };
+
             let mut options = DataSetReaderOptions::default();
             options.odd_length = odd_length;
-            let mut dataset = DataSetReader::new_with_ts_cs_options(
-                file,
-                ts,
-                SpecificCharacterSet::default(),
+            let obj = Self::read_data_set(
+                Cursor::new(head).chain(file),
+                detected_ts.unwrap_or(ts),
                 options,
-            )
-            .context(CreateParserSnafu)?;
-            let obj = InMemDicomObject::build_object(
-                &mut dataset,
                 dict,
-                false,
-                Length::UNDEFINED,
                 read_until,
+                preserve_element_order,
+                debug_trace,
             )?;
 
             // if Media Storage SOP Class UID is empty attempt to infer from SOP Class UID
@@ -417,7 +598,11 @@ where
                 }
             }
 
-            Ok(FileDicomObject { meta, obj })
+            Ok(FileDicomObject {
+                meta,
+                obj,
+                detected_transfer_syntax: detected_ts.map(|ts| ts.uid().to_string()),
+            })
         } else {
             ReadUnsupportedTransferSyntaxSnafu {
                 uid: meta.transfer_syntax,
@@ -460,26 +645,109 @@ where
     {
         Self::from_reader_with_all_options(
             src,
-            dict,
-            ts_index,
-            None,
-            ReadPreamble::Auto,
-            Default::default(),
+            OpenFileOptions::new()
+                .dictionary(dict)
+                .transfer_syntax_index(ts_index),
+        )
+    }
+
+    /// Create a DICOM object from a buffer
+    /// holding the full contents of a DICOM file,
+    /// using the given dictionary for name lookup.
+    pub fn from_bytes_with_dict(bytes: &[u8], dict: D) -> Result<Self, ReadError> {
+        Self::from_bytes_with_all_options(
+            bytes,
+            OpenFileOptions::new()
+                .dictionary(dict)
+                .transfer_syntax_index(TransferSyntaxRegistry),
         )
     }
 
+    pub(crate) fn from_bytes_with_all_options<R>(
+        bytes: &[u8],
+        options: OpenFileOptions<D, R>,
+    ) -> Result<Self, ReadError>
+    where
+        R: TransferSyntaxIndex,
+    {
+        let OpenFileOptions {
+            data_dictionary: dict,
+            ts_index,
+            read_until,
+            read_preamble,
+            odd_length,
+            detect_transfer_syntax,
+            preserve_element_order,
+            debug_trace,
+        } = options;
+
+        let skip_preamble = match read_preamble {
+            ReadPreamble::Always => true,
+            ReadPreamble::Never => false,
+            ReadPreamble::Auto => bytes.len() >= 132 && &bytes[128..132] == b"DICM",
+        };
+
+        let mut data = bytes;
+        if skip_preamble {
+            let mut buf = [0u8; 128];
+            // skip the preamble
+            data.read_exact(&mut buf).context(ReadPreambleBytesSnafu)?;
+        }
+
+        // read metadata header
+        let meta = FileMetaTable::from_reader(&mut data).context(ParseMetaDataSetSnafu)?;
+
+        // read rest of data according to metadata, feed it to object
+        if let Some(ts) = ts_index.get(meta.transfer_syntax()) {
+            let detected_ts = if detect_transfer_syntax {
+                Self::detect_transfer_syntax(&ts_index, ts, data)
+            } else {
+                None
+            };
+
+            let mut options = DataSetReaderOptions::default();
+            options.odd_length = odd_length;
+            let obj = Self::read_data_set(
+                data,
+                detected_ts.unwrap_or(ts),
+                options,
+                dict,
+                read_until,
+                preserve_element_order,
+                debug_trace,
+            )?;
+            Ok(FileDicomObject {
+                meta,
+                obj,
+                detected_transfer_syntax: detected_ts.map(|ts| ts.uid().to_string()),
+            })
+        } else {
+            ReadUnsupportedTransferSyntaxSnafu {
+                uid: meta.transfer_syntax,
+            }
+            .fail()
+        }
+    }
+
     pub(crate) fn from_reader_with_all_options<'s, S, R>(
         src: S,
-        dict: D,
-        ts_index: R,
-        read_until: Option<Tag>,
-        mut read_preamble: ReadPreamble,
-        odd_length: OddLengthStrategy,
+        options: OpenFileOptions<D, R>,
     ) -> Result<Self, ReadError>
     where
         S: Read + 's,
         R: TransferSyntaxIndex,
     {
+        let OpenFileOptions {
+            data_dictionary: dict,
+            ts_index,
+            read_until,
+            mut read_preamble,
+            odd_length,
+            detect_transfer_syntax,
+            preserve_element_order,
+            debug_trace,
+        } = options;
+
         let mut file = BufReader::new(src);
 
         if read_preamble == ReadPreamble::Auto {
@@ -497,23 +765,34 @@ where
         let meta = FileMetaTable::from_reader(&mut file).context(ParseMetaDataSetSnafu)?;
 
         // read rest of data according to metadata, feed it to object
-        if let Some(ts) = ts_index.get(&meta.transfer_syntax) {
+        if let Some(ts) = ts_index.get(meta.transfer_syntax()) {
+            let mut head = Vec::new();
+            let detected_ts = if detect_transfer_syntax {
+                (&mut file)
+                    .take(8)
+                    .read_to_end(&mut head)
+                    .context(ReadDataSetHeadSnafu)?;
+                Self::detect_transfer_syntax(&ts_index, ts, &head)
+            } else {
+                None
+            };
+
             let mut options = DataSetReaderOptions::default();
             options.odd_length = odd_length;
-            let mut dataset = DataSetReader::new_with_ts_options(
-                file,
-                ts,
+            let obj = Self::read_data_set(
+                Cursor::new(head).chain(file),
+                detected_ts.unwrap_or(ts),
                 options,
-            )
-            .context(CreateParserSnafu)?;
-            let obj = InMemDicomObject::build_object(
-                &mut dataset,
                 dict,
-                false,
-                Length::UNDEFINED,
                 read_until,
+                preserve_element_order,
+                debug_trace,
             )?;
-            Ok(FileDicomObject { meta, obj })
+            Ok(FileDicomObject {
+                meta,
+                obj,
+                detected_transfer_syntax: detected_ts.map(|ts| ts.uid().to_string()),
+            })
         } else {
             ReadUnsupportedTransferSyntaxSnafu {
                 uid: meta.transfer_syntax,
@@ -533,7 +812,9 @@ impl FileDicomObject<InMemDicomObject<StandardDataDictionary>> {
                 dict: StandardDataDictionary,
                 len: Length::UNDEFINED,
                 charset_changed: false,
+                order: None,
             },
+            detected_transfer_syntax: None,
         }
     }
 }
@@ -550,6 +831,7 @@ where
             dict,
             len: Length::UNDEFINED,
             charset_changed: false,
+            order: None,
         }
     }
 
@@ -564,6 +846,7 @@ where
             dict,
             len: Length::UNDEFINED,
             charset_changed: false,
+            order: None,
         })
     }
 
@@ -578,6 +861,7 @@ where
             dict,
             len: Length::UNDEFINED,
             charset_changed: false,
+            order: None,
         }
     }
 
@@ -615,6 +899,7 @@ where
             dict,
             len: Length::UNDEFINED,
             charset_changed: false,
+            order: None,
         }
     }
 
@@ -627,7 +912,7 @@ where
         D: DataDictionary,
     {
         let mut dataset = DataSetReader::new(decoder, Default::default());
-        InMemDicomObject::build_object(&mut dataset, dict, false, Length::UNDEFINED, None)
+        InMemDicomObject::build_object(&mut dataset, dict, false, Length::UNDEFINED, None, false)
     }
 
     /// Read an object from a source,
@@ -664,7 +949,7 @@ where
     {
         let from = BufReader::new(from);
         let mut dataset = DataSetReader::new_with_ts_cs(from, ts, cs).context(CreateParserSnafu)?;
-        InMemDicomObject::build_object(&mut dataset, dict, false, Length::UNDEFINED, None)
+        InMemDicomObject::build_object(&mut dataset, dict, false, Length::UNDEFINED, None, false)
     }
 
     // Standard methods follow. They are not placed as a trait implementation
@@ -751,6 +1036,156 @@ where
         }
     }
 
+    /// Retrieve the value of a DICOM element as a string,
+    /// if it exists and is not empty.
+    ///
+    /// The trailing padding of each value
+    /// (space and null characters) is removed,
+    /// and multiple values are joined with a backslash (`'\\'`),
+    /// as in [`to_str`](dicom_core::value::Value::to_str).
+    ///
+    /// `None` is returned if the element does not exist,
+    /// or if its value is empty.
+    /// A value is considered empty if it has no values,
+    /// or if all of its textual values are empty
+    /// once their trailing padding is removed.
+    /// The other typed accessors in this family,
+    /// such as [`u16_opt`](InMemDicomObject::u16_opt),
+    /// follow the same rule.
+    pub fn string_opt(&self, tag: Tag) -> Result<Option<String>, AttributeValueError> {
+        self.value_opt(tag, |value| value.to_str().map(Cow::into_owned))
+    }
+
+    /// Retrieve the value of a DICOM element as a string.
+    ///
+    /// An error is returned if the element does not exist
+    /// or its value is empty,
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn string_required(&self, tag: Tag) -> Result<String, AttributeValueError> {
+        self.string_opt(tag)?.context(MissingOrEmptySnafu { tag })
+    }
+
+    /// Retrieve the first value of a DICOM element
+    /// as an unsigned 16-bit integer,
+    /// if it exists and is not empty.
+    ///
+    /// `None` is returned if the element does not exist,
+    /// or if its value is empty
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn u16_opt(&self, tag: Tag) -> Result<Option<u16>, AttributeValueError> {
+        self.value_opt(tag, |value| value.to_int())
+    }
+
+    /// Retrieve the first value of a DICOM element
+    /// as an unsigned 16-bit integer.
+    ///
+    /// An error is returned if the element does not exist
+    /// or its value is empty,
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn u16_required(&self, tag: Tag) -> Result<u16, AttributeValueError> {
+        self.u16_opt(tag)?.context(MissingOrEmptySnafu { tag })
+    }
+
+    /// Retrieve the first value of a DICOM element
+    /// as an unsigned 32-bit integer,
+    /// if it exists and is not empty.
+    ///
+    /// `None` is returned if the element does not exist,
+    /// or if its value is empty
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn u32_opt(&self, tag: Tag) -> Result<Option<u32>, AttributeValueError> {
+        self.value_opt(tag, |value| value.to_int())
+    }
+
+    /// Retrieve the first value of a DICOM element
+    /// as an unsigned 32-bit integer.
+    ///
+    /// An error is returned if the element does not exist
+    /// or its value is empty,
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn u32_required(&self, tag: Tag) -> Result<u32, AttributeValueError> {
+        self.u32_opt(tag)?.context(MissingOrEmptySnafu { tag })
+    }
+
+    /// Retrieve the first value of a DICOM element
+    /// as a double precision floating point number,
+    /// if it exists and is not empty.
+    ///
+    /// `None` is returned if the element does not exist,
+    /// or if its value is empty
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn f64_opt(&self, tag: Tag) -> Result<Option<f64>, AttributeValueError> {
+        self.value_opt(tag, |value| value.to_float64())
+    }
+
+    /// Retrieve the first value of a DICOM element
+    /// as a double precision floating point number.
+    ///
+    /// An error is returned if the element does not exist
+    /// or its value is empty,
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn f64_required(&self, tag: Tag) -> Result<f64, AttributeValueError> {
+        self.f64_opt(tag)?.context(MissingOrEmptySnafu { tag })
+    }
+
+    /// Retrieve the first value of a DICOM element as a tag,
+    /// if it exists and is not empty.
+    ///
+    /// `None` is returned if the element does not exist,
+    /// or if its value is empty
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn tag_opt(&self, tag: Tag) -> Result<Option<Tag>, AttributeValueError> {
+        self.value_opt(tag, |value| {
+            value.to_tag().map_err(|e| ConvertValueError {
+                requested: e.requested,
+                original: e.got,
+                cause: None,
+            })
+        })
+    }
+
+    /// Retrieve the first value of a DICOM element as a tag.
+    ///
+    /// An error is returned if the element does not exist
+    /// or its value is empty,
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn tag_required(&self, tag: Tag) -> Result<Tag, AttributeValueError> {
+        self.tag_opt(tag)?.context(MissingOrEmptySnafu { tag })
+    }
+
+    /// Retrieve the first value of a DICOM element as a [`DicomDate`],
+    /// if it exists and is not empty.
+    ///
+    /// `None` is returned if the element does not exist,
+    /// or if its value is empty
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn date_opt(&self, tag: Tag) -> Result<Option<DicomDate>, AttributeValueError> {
+        self.value_opt(tag, |value| value.to_date())
+    }
+
+    /// Retrieve the first value of a DICOM element as a [`DicomDate`].
+    ///
+    /// An error is returned if the element does not exist
+    /// or its value is empty,
+    /// as described in [`string_opt`](InMemDicomObject::string_opt).
+    pub fn date_required(&self, tag: Tag) -> Result<DicomDate, AttributeValueError> {
+        self.date_opt(tag)?.context(MissingOrEmptySnafu { tag })
+    }
+
+    /// Convert the value of a DICOM element which might not exist,
+    /// yielding `None` if the element is missing or its value is empty.
+    fn value_opt<T, F>(&self, tag: Tag, convert: F) -> Result<Option<T>, AttributeValueError>
+    where
+        F: FnOnce(&Value<InMemDicomObject<D>, InMemFragment>) -> Result<T, ConvertValueError>,
+    {
+        match self.get(tag) {
+            Some(elem) if !is_empty_value(elem.value()) => convert(elem.value())
+                .map(Some)
+                .context(ConvertValueSnafu { tag }),
+            _ => Ok(None),
+        }
+    }
+
     fn find_private_creator(&self, group: GroupNumber, creator: &str) -> Option<&Tag> {
         let range = Tag(group, 0)..Tag(group, 0xFF);
         for (tag, elem) in self.entries.range(range) {
@@ -835,7 +1270,98 @@ where
     pub fn put_element(&mut self, elt: InMemElement<D>) -> Option<InMemElement<D>> {
         self.len = Length::UNDEFINED;
         self.invalidate_if_charset_changed(elt.tag());
-        self.entries.insert(elt.tag(), elt)
+        let tag = elt.tag();
+        let old = self.entries.insert(tag, elt);
+        if old.is_none() {
+            self.record_insertion(tag);
+        }
+        old
+    }
+
+    /// Insert a data element to the object,
+    /// replacing any previous element of the same attribute,
+    /// but only if its number of values conforms to
+    /// the value multiplicity specified by the data dictionary.
+    /// Backslash-separated values in a single string are counted individually,
+    /// except in LT, ST, UT, and UR.
+    ///
+    /// Elements without a value,
+    /// sequences, pixel data fragments, binary values,
+    /// and attributes without a known value multiplicity
+    /// are always accepted.
+    /// Otherwise, the object is left unchanged
+    /// and the violation is returned.
+    ///
+    /// ## Example
+    /// ```
+    /// # use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// let mut obj = InMemDicomObject::new_empty();
+    /// // Patient's Name has a value multiplicity of 1
+    /// let patient_name = DataElement::new(
+    ///     tags::PATIENT_NAME,
+    ///     VR::PN,
+    ///     PrimitiveValue::from("Doe^John"),
+    /// );
+    /// assert!(obj.put_checked(patient_name).is_ok());
+    ///
+    /// let patient_names = DataElement::new(
+    ///     tags::PATIENT_NAME,
+    ///     VR::PN,
+    ///     dicom_value!(Strs, ["Doe^John", "Doe^Jane"]),
+    /// );
+    /// let violation = obj.put_checked(patient_names).unwrap_err();
+    /// assert_eq!(violation.count, 2);
+    /// assert_eq!(obj.get(tags::PATIENT_NAME).unwrap().to_str().unwrap(), "Doe^John");
+    /// ```
+    pub fn put_checked(&mut self, elt: InMemElement<D>) -> Result<(), VmViolation> {
+        if let Some(violation) = self.check_vm(&elt) {
+            return Err(violation);
+        }
+        self.put_element(elt);
+        Ok(())
+    }
+
+    /// Check all data elements in the object,
+    /// including those in nested sequence items,
+    /// against the value multiplicity specified by the data dictionary,
+    /// returning all violations found.
+    ///
+    /// See [`put_checked`](Self::put_checked)
+    /// for the elements which are not subject to this validation.
+    pub fn validate_vm(&self) -> Vec<VmViolation> {
+        let mut violations = Vec::new();
+        self.validate_vm_into(&mut violations);
+        violations
+    }
+
+    fn validate_vm_into(&self, violations: &mut Vec<VmViolation>) {
+        for elt in self.entries.values() {
+            violations.extend(self.check_vm(elt));
+            if let Some(items) = elt.items() {
+                for item in items {
+                    item.validate_vm_into(violations);
+                }
+            }
+        }
+    }
+
+    /// Check a single data element against the value multiplicity
+    /// specified by this object's data dictionary.
+    fn check_vm(&self, elt: &InMemElement<D>) -> Option<VmViolation> {
+        let count = value_count(elt)?;
+        let entry = self.dict.by_tag(elt.tag())?;
+        let vm = entry.vm()?;
+        if vm.contains(count) {
+            return None;
+        }
+        Some(VmViolation {
+            tag: elt.tag(),
+            alias: entry.alias().to_string(),
+            vm,
+            count,
+        })
     }
 
     /// Insert a private element into the dataset, replacing (and returning) any
@@ -930,6 +1456,7 @@ where
     pub fn remove_element(&mut self, tag: Tag) -> bool {
         if self.entries.remove(&tag).is_some() {
             self.len = Length::UNDEFINED;
+            self.forget_insertion(tag);
             true
         } else {
             false
@@ -943,6 +1470,7 @@ where
         Ok(self.entries.remove(&tag).is_some()).map(|removed| {
             if removed {
                 self.len = Length::UNDEFINED;
+                self.forget_insertion(tag);
             }
             removed
         })
@@ -954,6 +1482,7 @@ where
             .remove(&tag)
             .map(|e| {
                 self.len = Length::UNDEFINED;
+                self.forget_insertion(tag);
                 e
             })
             .context(NoSuchDataElementTagSnafu { tag })
@@ -965,6 +1494,7 @@ where
     pub fn take(&mut self, tag: Tag) -> Option<InMemElement<D>> {
         self.entries.remove(&tag).map(|e| {
             self.len = Length::UNDEFINED;
+            self.forget_insertion(tag);
             e
         })
     }
@@ -979,6 +1509,7 @@ where
             .remove(&tag)
             .map(|e| {
                 self.len = Length::UNDEFINED;
+                self.forget_insertion(tag);
                 e
             })
             .with_context(|| NoSuchDataElementAliasSnafu {
@@ -994,6 +1525,10 @@ where
     /// and those for which `f(&element)` returns `false` are removed.
     pub fn retain(&mut self, mut f: impl FnMut(&InMemElement<D>) -> bool) {
         self.entries.retain(|_, elem| f(elem));
+        let entries = &self.entries;
+        if let Some(order) = &mut self.order {
+            order.retain(|tag| entries.contains_key(tag));
+        }
         self.len = Length::UNDEFINED;
     }
 
@@ -1498,6 +2033,115 @@ where
         }
     }
 
+    /// Reinterpret the elements of unknown value representation (UN)
+    /// whose attribute is known to the given data element dictionary,
+    /// including those in nested data set sequences.
+    ///
+    /// Values are assumed to be encoded in little endian.
+    /// Use [`promote_un_elements_with`](Self::promote_un_elements_with)
+    /// to specify another byte order.
+    /// When working with a [`FileDicomObject`],
+    /// prefer [`FileDicomObject::promote_un_elements`],
+    /// which takes the byte order from the file's transfer syntax.
+    ///
+    /// Only attributes with an exact value representation
+    /// in the dictionary are considered.
+    /// Elements which cannot be reinterpreted
+    /// (see [`ReinterpretElement`])
+    /// are left untouched.
+    /// Returns the number of elements promoted.
+    pub fn promote_un_elements<Di>(&mut self, dict: &Di) -> usize
+    where
+        Di: DataDictionary,
+    {
+        self.promote_un_elements_with(dict, Endianness::Little)
+    }
+
+    /// Reinterpret the elements of unknown value representation (UN)
+    /// whose attribute is known to the given data element dictionary,
+    /// assuming that the values are encoded in the given byte order.
+    ///
+    /// See [`promote_un_elements`](Self::promote_un_elements)
+    /// for more details.
+    pub fn promote_un_elements_with<Di>(&mut self, dict: &Di, endianness: Endianness) -> usize
+    where
+        Di: DataDictionary,
+    {
+        self.promote_un_elements_impl(dict, endianness, SpecificCharacterSet::default())
+    }
+
+    fn promote_un_elements_impl<Di>(
+        &mut self,
+        dict: &Di,
+        endianness: Endianness,
+        charset: SpecificCharacterSet,
+    ) -> usize
+    where
+        Di: DataDictionary,
+    {
+        // items may declare their own character set
+        let charset = self
+            .get(tags::SPECIFIC_CHARACTER_SET)
+            .and_then(|e| e.strings().ok()?.first())
+            .and_then(|code| SpecificCharacterSet::from_code(code))
+            .unwrap_or(charset);
+
+        let mut promoted = 0;
+        let un_tags: Vec<Tag> = self
+            .entries
+            .values()
+            .filter(|e| e.vr() == VR::UN)
+            .map(|e| e.tag())
+            .collect();
+        for tag in un_tags {
+            let Some(vr) = self.known_vr(dict, tag) else {
+                continue;
+            };
+            let result = self.entries[&tag].reinterpret_as_with(vr, endianness, charset.clone());
+            match result {
+                Ok(elem) => {
+                    self.entries.insert(tag, elem);
+                    promoted += 1;
+                }
+                Err(e) => {
+                    tracing::warn!("Could not promote UN element {} to {}: {}", tag, vr, e);
+                }
+            }
+        }
+
+        for elem in self.entries.values_mut() {
+            if elem.value().items().is_none() {
+                continue;
+            }
+            for item in elem.items_mut().into_iter().flatten() {
+                promoted += item.promote_un_elements_impl(dict, endianness, charset.clone());
+            }
+        }
+
+        promoted
+    }
+
+    /// Look up the exact value representation of the attribute
+    /// with the given tag in the given dictionary,
+    /// resolving the private creator of private attributes.
+    fn known_vr<Di>(&self, dict: &Di, tag: Tag) -> Option<VR>
+    where
+        Di: DataDictionary,
+    {
+        let entry = if tag.group() % 2 == 1 && tag.element() >= 0x1000 {
+            let creator = self.get(Tag(tag.group(), tag.element() >> 8))?;
+            let creator = if creator.vr() == VR::UN {
+                creator.reinterpret_as(VR::LO).ok()?
+            } else {
+                creator.clone()
+            };
+            dict.by_private_tag(creator.trimmed_str().ok()?, tag)?
+        } else {
+            dict.by_tag(tag)?
+        };
+        entry.vr().exact()
+    }
+
     fn invalidate_if_charset_changed(&mut self, tag: Tag) {
         if tag == tags::SPECIFIC_CHARACTER_SET {
             self.charset_changed = true;
@@ -1816,7 +2460,33 @@ where
     /// and _Media Storage SOP Class UID_
     /// are not updated based on the receiving data set.
     pub fn with_exact_meta(self, meta: FileMetaTable) -> FileDicomObject<Self> {
-        FileDicomObject { meta, obj: self }
+        FileDicomObject {
+            meta,
+            obj: self,
+            detected_transfer_syntax: None,
+        }
+    }
+
+    /// Encapsulate this object to contain a file meta group
+    /// as described by the given table,
+    /// fixing it to be consistent with the receiving data set.
+    ///
+    /// Unlike [`with_exact_meta`](Self::with_exact_meta),
+    /// the _Media Storage SOP Instance UID_
+    /// and _Media Storage SOP Class UID_
+    /// are replaced with the values of
+    /// _SOP Instance UID_ and _SOP Class UID_
+    /// if they are present in this object,
+    /// and the information group length is recalculated.
+    pub fn with_exact_meta_autofix(self, mut meta: FileMetaTable) -> FileDicomObject<Self> {
+        if let Some(uid) = self.sop_instance_uid() {
+            meta.media_storage_sop_instance_uid = uid.into_owned();
+        }
+        if let Some(uid) = self.sop_class_uid() {
+            meta.media_storage_sop_class_uid = uid.into_owned();
+        }
+        meta.update_information_group_length();
+        self.with_exact_meta(meta)
     }
 
     /// Encapsulate this object to contain a file meta group,
@@ -1828,7 +2498,9 @@ where
     /// and the _Media Storage SOP Class UID_.
     /// The last two will be filled with the values of
     /// _SOP Instance UID_ and _SOP Class UID_
-    /// if they are present in this object.
+    /// if they are present in this object,
+    /// so that the file meta group is
+    /// [consistent](FileDicomObject::check_consistency) with the data set.
     ///
     /// # Example
     ///
@@ -1867,6 +2539,7 @@ where
         Ok(FileDicomObject {
             meta: meta.build().context(BuildMetaTableSnafu)?,
             obj: self,
+            detected_transfer_syntax: None,
         })
     }
 
@@ -1880,28 +2553,155 @@ where
         self.entries.keys().copied()
     }
 
-    // private methods
-
-    /// Build an object by consuming a data set parser.
-    fn build_object<I>(
-        dataset: &mut I,
-        dict: D,
-        in_item: bool,
-        len: Length,
-        read_until: Option<Tag>,
-    ) -> Result<Self, ReadError>
+    /// Visit the headers of all elements in this object,
+    /// including those nested in sequence items,
+    /// in depth-first order.
+    ///
+    /// For each element,
+    /// the visitor receives the path of selector steps to it
+    /// and the element's header.
+    /// The last step of the path is always a [tag](AttributeSelectorStep::Tag),
+    /// preceded by one [nested](AttributeSelectorStep::Nested) step
+    /// per enclosing sequence item,
+    /// so the nesting depth of the element is `path.len() - 1`.
+    /// A sequence element is visited before the elements in its items.
+    /// Pixel data fragments are not visited.
+    ///
+    /// No values are cloned during the traversal.
+    /// The visitor may stop it early by returning [`ControlFlow::Break`],
+    /// in which case this method also returns `ControlFlow::Break`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::{DataElement, VR};
+    /// # use dicom_dictionary_std::tags;
+    /// # use dicom_object::InMemDicomObject;
+    /// use std::ops::ControlFlow;
+    ///
+    /// # let obj = InMemDicomObject::from_element_iter([
+    /// #     DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+    /// # ]);
+    /// // count the elements at each depth
+    /// let mut counts = vec![];
+    /// let _ = obj.walk(|path, _header| {
+    ///     let depth = path.len() - 1;
+    ///     if counts.len() <= depth {
+    ///         counts.resize(depth + 1, 0);
+    ///     }
+    ///     counts[depth] += 1;
+    ///     ControlFlow::Continue(())
+    /// });
+    /// # assert_eq!(counts, vec![1]);
+    /// ```
+    pub fn walk<F>(&self, mut visitor: F) -> ControlFlow<()>
     where
-        I: ?Sized + Iterator<Item = ParserResult<DataToken>>,
+        F: FnMut(&[AttributeSelectorStep], &DataElementHeader) -> ControlFlow<()>,
     {
-        let mut entries: BTreeMap<Tag, InMemElement<D>> = BTreeMap::new();
-        // perform a structured parsing of incoming tokens
-        while let Some(token) = dataset.next() {
-            let elem = match token.context(ReadTokenSnafu)? {
-                DataToken::PixelSequenceStart => {
-                    // stop reading if reached `read_until` tag
-                    if read_until
-                        .map(|t| t <= Tag(0x7fe0, 0x0010))
-                        .unwrap_or(false)
+        let mut path = SmallVec::new();
+        self.walk_impl(&mut path, &mut visitor)
+    }
+
+    /// Collect the references to other SOP instances in this object.
+    ///
+    /// All items of _Referenced Image Sequence_,
+    /// _Source Image Sequence_,
+    /// and _Referenced Instance Sequence_ are gathered,
+    /// wherever they appear in the object,
+    /// including in functional groups and content sequences.
+    /// Items without a valid referenced SOP class UID and SOP instance UID
+    /// are skipped.
+    ///
+    /// See [`ReferenceGraph`](crate::items::ReferenceGraph)
+    /// for resolving references across a set of objects.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use dicom_core::value::DataSetSequence;
+    /// # use dicom_core::{DataElement, VR};
+    /// # use dicom_dictionary_std::{tags, uids};
+    /// # use dicom_object::InMemDicomObject;
+    /// # use dicom_object::items::ReferencedSop;
+    /// let source = ReferencedSop::new(uids::CT_IMAGE_STORAGE, "2.25.123456789");
+    /// let obj = InMemDicomObject::from_element_iter([
+    ///     DataElement::new(
+    ///         tags::SOURCE_IMAGE_SEQUENCE,
+    ///         VR::SQ,
+    ///         DataSetSequence::from(vec![source.to_item()?]),
+    ///     ),
+    /// ]);
+    ///
+    /// let references = obj.references();
+    /// assert_eq!(references.len(), 1);
+    /// assert_eq!(references[0].sop_instance_uid, "2.25.123456789");
+    /// assert_eq!(references[0].frames, None);
+    /// # Ok::<_, dicom_object::items::ItemError>(())
+    /// ```
+    pub fn references(&self) -> Vec<InstanceReference> {
+        crate::items::collect_references(self)
+    }
+
+    // private methods
+
+    /// Visit the headers of all elements in this object,
+    /// where `path` holds the steps to the enclosing item.
+    fn walk_impl<F>(
+        &self,
+        path: &mut SmallVec<[AttributeSelectorStep; 4]>,
+        visitor: &mut F,
+    ) -> ControlFlow<()>
+    where
+        F: FnMut(&[AttributeSelectorStep], &DataElementHeader) -> ControlFlow<()>,
+    {
+        for elem in self.entries.values() {
+            let tag = elem.tag();
+            path.push(AttributeSelectorStep::Tag(tag));
+            visitor(path, elem.header())?;
+            if let Some(items) = elem.items() {
+                for (i, item) in items.iter().enumerate() {
+                    *path.last_mut().unwrap() = AttributeSelectorStep::Nested {
+                        tag,
+                        item: i as u32,
+                    };
+                    item.walk_impl(path, visitor)?;
+                }
+            }
+            path.pop();
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Build an object by consuming a data set parser.
+    ///
+    /// If `preserve_order` is `true`,
+    /// the order of the elements read is recorded
+    /// in the object and in all nested items.
+    fn build_object<I>(
+        dataset: &mut I,
+        dict: D,
+        in_item: bool,
+        len: Length,
+        read_until: Option<Tag>,
+        preserve_order: bool,
+    ) -> Result<Self, ReadError>
+    where
+        I: ?Sized + Iterator<Item = ParserResult<DataToken>>,
+    {
+        let mut entries: BTreeMap<Tag, InMemElement<D>> = BTreeMap::new();
+        let mut order = if preserve_order {
+            Some(Vec::new())
+        } else {
+            None
+        };
+        // perform a structured parsing of incoming tokens
+        while let Some(token) = dataset.next() {
+            let elem = match token.context(ReadTokenSnafu)? {
+                DataToken::PixelSequenceStart => {
+                    // stop reading if reached `read_until` tag
+                    if read_until
+                        .map(|t| t <= Tag(0x7fe0, 0x0010))
+                        .unwrap_or(false)
                     {
                         break;
                     }
@@ -1935,7 +2735,8 @@ where
                     }
 
                     // delegate sequence building to another function
-                    let items = Self::build_sequence(tag, len, &mut *dataset, &dict)?;
+                    let items =
+                        Self::build_sequence(tag, len, &mut *dataset, &dict, preserve_order)?;
                     DataElement::new_with_len(
                         tag,
                         VR::SQ,
@@ -1950,11 +2751,17 @@ where
                         dict,
                         len,
                         charset_changed: false,
+                        order,
                     });
                 }
                 token => return UnexpectedTokenSnafu { token }.fail(),
             };
-            entries.insert(elem.tag(), elem);
+            let tag = elem.tag();
+            if entries.insert(tag, elem).is_none() {
+                if let Some(order) = &mut order {
+                    order.push(tag);
+                }
+            }
         }
 
         Ok(InMemDicomObject {
@@ -1962,6 +2769,7 @@ where
             dict,
             len,
             charset_changed: false,
+            order,
         })
     }
 
@@ -2028,6 +2836,7 @@ where
         _len: Length,
         dataset: &mut I,
         dict: &D,
+        preserve_order: bool,
     ) -> Result<C<InMemDicomObject<D>>, ReadError>
     where
         I: ?Sized + Iterator<Item = ParserResult<DataToken>>,
@@ -2042,6 +2851,7 @@ where
                         true,
                         len,
                         None,
+                        preserve_order,
                     )?);
                 }
                 DataToken::SequenceEnd => {
@@ -2056,10 +2866,140 @@ where
     }
 
     fn lookup_name(&self, name: &str) -> Result<Tag, AccessByNameError> {
-        self.dict
-            .by_name(name)
-            .context(NoSuchAttributeNameSnafu { name })
-            .map(|e| e.tag())
+        if let Some(e) = self.dict.by_name(name) {
+            return Ok(e.tag());
+        }
+
+        // look for a private attribute in the block reserved by its creator
+        let (creator, e) = self
+            .dict
+            .private_by_name(name)
+            .context(NoSuchAttributeNameSnafu { name })?;
+        let tag = e.tag();
+        let block = self
+            .find_private_creator(tag.group(), creator)
+            .with_context(|| NoSuchDataElementAliasSnafu {
+                tag,
+                alias: name.to_string(),
+            })?
+            .element();
+        Ok(Tag(tag.group(), (block << 8) | (tag.element() & 0x00FF)))
+    }
+}
+
+impl<D> InMemDicomObject<D> {
+    /// Obtain a reference to the data element dictionary
+    /// used by this object for attribute name lookup.
+    pub fn dictionary(&self) -> &D {
+        &self.dict
+    }
+
+    /// Make this object preserve the order in which its elements are inserted.
+    ///
+    /// Elements are otherwise only kept in ascending tag order,
+    /// which is the order mandated by the standard for encoded data sets.
+    /// Once enabled,
+    /// the object also records the tags of its elements in insertion order,
+    /// so that it can be written back in that order
+    /// with [`WriteOptions::preserve_element_order`](crate::WriteOptions::preserve_element_order).
+    /// The elements already in the object are recorded in ascending tag order.
+    /// Replacing an element keeps its position,
+    /// whereas removing it also removes it from the record.
+    /// Access to elements by tag is not affected.
+    ///
+    /// This does not apply to the items of the object's sequences.
+    /// To read objects which preserve the order of their elements,
+    /// see [`OpenFileOptions::preserve_element_order`](crate::OpenFileOptions::preserve_element_order).
+    pub fn with_insertion_order(mut self) -> Self {
+        if self.order.is_none() {
+            self.order = Some(self.entries.keys().copied().collect());
+        }
+        self
+    }
+
+    /// Check whether this object preserves
+    /// the order in which its elements were inserted.
+    pub fn preserves_insertion_order(&self) -> bool {
+        self.order.is_some()
+    }
+
+    /// Obtain an iterator over the elements of this object
+    /// in the order in which they were inserted,
+    /// or in ascending tag order
+    /// if the object does not [preserve insertion order](Self::with_insertion_order).
+    pub fn iter_in_insertion_order(&self) -> Elements<'_, D> {
+        self.elements(true)
+    }
+
+    /// Convert this object into an iterator of its elements
+    /// in the order in which they were inserted,
+    /// or in ascending tag order
+    /// if the object does not [preserve insertion order](Self::with_insertion_order).
+    pub fn into_iter_in_insertion_order(self) -> Iter<D> {
+        self.into_elements(true)
+    }
+
+    /// Obtain an iterator over the elements of this object,
+    /// in insertion order if requested and recorded.
+    pub(crate) fn elements(&self, insertion_order: bool) -> Elements<'_, D> {
+        let inner = match &self.order {
+            Some(order) if insertion_order => ElementsInner::Recorded {
+                order: order.iter(),
+                entries: &self.entries,
+            },
+            _ => ElementsInner::ByTag(self.entries.values()),
+        };
+        Elements { inner }
+    }
+
+    /// Convert this object into an iterator of its elements,
+    /// in insertion order if requested and recorded.
+    pub(crate) fn into_elements(self, insertion_order: bool) -> Iter<D> {
+        let inner = match self.order {
+            Some(order) if insertion_order => IterInner::Recorded {
+                order: order.into_iter(),
+                entries: self.entries,
+            },
+            _ => IterInner::ByTag(self.entries.into_iter()),
+        };
+        Iter { inner }
+    }
+
+    /// Record the given tag as the last one inserted,
+    /// if the object preserves insertion order
+    /// and the tag is not recorded yet.
+    fn record_insertion(&mut self, tag: Tag) {
+        if let Some(order) = &mut self.order {
+            if !order.contains(&tag) {
+                order.push(tag);
+            }
+        }
+    }
+
+    /// Remove the given tag from the record of insertion order, if any.
+    fn forget_insertion(&mut self, tag: Tag) {
+        if let Some(order) = &mut self.order {
+            order.retain(|t| *t != tag);
+        }
+    }
+}
+
+impl<D> MetaConsistency for InMemDicomObject<D>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    fn sop_class_uid(&self) -> Option<Cow<'_, str>> {
+        self.get(tags::SOP_CLASS_UID)?.value().to_str().ok()
+    }
+
+    fn sop_instance_uid(&self) -> Option<Cow<'_, str>> {
+        self.get(tags::SOP_INSTANCE_UID)?.value().to_str().ok()
+    }
+
+    fn encapsulated_pixel_data(&self) -> Option<bool> {
+        self.get(tags::PIXEL_DATA)
+            .map(|e| matches!(e.value(), Value::PixelSequence(_)))
     }
 }
 
@@ -2090,31 +3030,90 @@ impl<D> IntoIterator for InMemDicomObject<D> {
     type IntoIter = Iter<D>;
 
     fn into_iter(self) -> Self::IntoIter {
-        Iter {
-            inner: self.entries.into_iter(),
-        }
+        self.into_elements(false)
     }
 }
 
 /// Base iterator type for an in-memory DICOM object.
 #[derive(Debug)]
 pub struct Iter<D> {
-    inner: ::std::collections::btree_map::IntoIter<Tag, InMemElement<D>>,
+    inner: IterInner<D>,
+}
+
+#[derive(Debug)]
+enum IterInner<D> {
+    /// elements in ascending tag order
+    ByTag(::std::collections::btree_map::IntoIter<Tag, InMemElement<D>>),
+    /// elements in recorded insertion order
+    Recorded {
+        order: ::std::vec::IntoIter<Tag>,
+        entries: BTreeMap<Tag, InMemElement<D>>,
+    },
 }
 
 impl<D> Iterator for Iter<D> {
     type Item = InMemElement<D>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|x| x.1)
+        match &mut self.inner {
+            IterInner::ByTag(inner) => inner.next().map(|x| x.1),
+            IterInner::Recorded { order, entries } => order.find_map(|tag| entries.remove(&tag)),
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.inner.size_hint()
+        match &self.inner {
+            IterInner::ByTag(inner) => inner.size_hint(),
+            IterInner::Recorded { entries, .. } => (entries.len(), Some(entries.len())),
+        }
     }
 
     fn count(self) -> usize {
-        self.inner.count()
+        match self.inner {
+            IterInner::ByTag(inner) => inner.count(),
+            IterInner::Recorded { entries, .. } => entries.len(),
+        }
+    }
+}
+
+/// Iterator over references to the elements of an in-memory DICOM object,
+/// either in ascending tag order or in insertion order.
+///
+/// See [`InMemDicomObject::iter_in_insertion_order`].
+#[derive(Debug)]
+pub struct Elements<'a, D> {
+    inner: ElementsInner<'a, D>,
+}
+
+#[derive(Debug)]
+enum ElementsInner<'a, D> {
+    /// elements in ascending tag order
+    ByTag(::std::collections::btree_map::Values<'a, Tag, InMemElement<D>>),
+    /// elements in recorded insertion order
+    Recorded {
+        order: ::std::slice::Iter<'a, Tag>,
+        entries: &'a BTreeMap<Tag, InMemElement<D>>,
+    },
+}
+
+impl<'a, D> Iterator for Elements<'a, D> {
+    type Item = &'a InMemElement<D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            ElementsInner::ByTag(inner) => inner.next(),
+            ElementsInner::Recorded { order, entries } => {
+                let entries = *entries;
+                order.find_map(|tag| entries.get(tag))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            ElementsInner::ByTag(inner) => inner.size_hint(),
+            ElementsInner::Recorded { order, .. } => (0, Some(order.len())),
+        }
     }
 }
 
@@ -2124,7 +3123,122 @@ impl<D> Extend<InMemElement<D>> for InMemDicomObject<D> {
         I: IntoIterator<Item = InMemElement<D>>,
     {
         self.len = Length::UNDEFINED;
-        self.entries.extend(iter.into_iter().map(|e| (e.tag(), e)))
+        for elem in iter {
+            let tag = elem.tag();
+            if self.entries.insert(tag, elem).is_none() {
+                self.record_insertion(tag);
+            }
+        }
+    }
+}
+
+/// Extension methods for reinterpreting in-memory data elements
+/// of unknown value representation (UN).
+///
+/// Elements are read as UN when the data set was encoded in implicit VR
+/// and the attribute was not known to the data element dictionary,
+/// or when the source declared them as UN explicitly.
+/// If the real value representation is known,
+/// the raw bytes can be parsed again under that VR.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::{DataElement, PrimitiveValue, VR};
+/// # use dicom_dictionary_std::tags;
+/// # use dicom_object::mem::{InMemElement, ReinterpretElement};
+/// let elem: InMemElement = DataElement::new(
+///     tags::ROWS,
+///     VR::UN,
+///     PrimitiveValue::from(vec![0x00_u8, 0x02]),
+/// );
+/// let elem = elem.reinterpret_as(VR::US)?;
+/// assert_eq!(elem.vr(), VR::US);
+/// assert_eq!(elem.to_int::<u16>()?, 512);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub trait ReinterpretElement: Sized {
+    /// Parse the value of this UN element again
+    /// under the given value representation,
+    /// assuming little endian byte order and the default character set.
+    ///
+    /// A new element is returned,
+    /// so this element remains untouched if reinterpretation fails.
+    fn reinterpret_as(&self, vr: VR) -> Result<Self, ReinterpretError> {
+        self.reinterpret_as_with(vr, Endianness::Little, SpecificCharacterSet::default())
+    }
+
+    /// Parse the value of this UN element again
+    /// under the given value representation,
+    /// byte order, and character set.
+    ///
+    /// The byte order should be that of the transfer syntax
+    /// in which the element was originally encoded.
+    /// An error is returned if the element is not UN,
+    /// if the target VR is SQ or UN,
+    /// or if the value length is not a multiple of
+    /// the size of a single value in the target VR.
+    fn reinterpret_as_with(
+        &self,
+        vr: VR,
+        endianness: Endianness,
+        charset: SpecificCharacterSet,
+    ) -> Result<Self, ReinterpretError>;
+}
+
+impl<D> ReinterpretElement for InMemElement<D> {
+    fn reinterpret_as_with(
+        &self,
+        vr: VR,
+        endianness: Endianness,
+        charset: SpecificCharacterSet,
+    ) -> Result<Self, ReinterpretError> {
+        let tag = self.tag();
+        ensure!(self.vr() == VR::UN, NotUnknownSnafu { tag, vr: self.vr() });
+        ensure!(
+            !matches!(vr, VR::SQ | VR::UN),
+            UnsupportedVrSnafu { tag, vr }
+        );
+
+        let bytes: &[u8] = match self.value() {
+            Value::Primitive(PrimitiveValue::U8(bytes)) => bytes,
+            Value::Primitive(PrimitiveValue::Empty) => &[],
+            _ => return NotRawBytesSnafu { tag }.fail(),
+        };
+
+        if let Some(size) = fixed_value_size(vr) {
+            ensure!(
+                bytes.len() % size == 0,
+                InvalidValueLengthSnafu {
+                    tag,
+                    vr,
+                    len: bytes.len() as u32,
+                    size: size as u32,
+                }
+            );
+        }
+
+        let ts = match endianness {
+            Endianness::Little => entries::EXPLICIT_VR_LITTLE_ENDIAN,
+            Endianness::Big => entries::EXPLICIT_VR_BIG_ENDIAN,
+        };
+        let header = DataElementHeader::new(tag, vr, Length(bytes.len() as u32));
+        let value = StatefulDecoder::new_with(bytes, &ts, charset, 0)
+            .and_then(|mut decoder| decoder.read_value(&header))
+            .context(DecodeValueSnafu { tag, vr })?;
+
+        Ok(DataElement::new(tag, vr, value))
+    }
+}
+
+/// The size in bytes of a single value in the given VR,
+/// if all of its values have the same size.
+fn fixed_value_size(vr: VR) -> Option<usize> {
+    match vr {
+        VR::US | VR::SS | VR::OW => Some(2),
+        VR::UL | VR::SL | VR::FL | VR::AT | VR::OL | VR::OF => Some(4),
+        VR::FD | VR::SV | VR::UV | VR::OD | VR::OV => Some(8),
+        _ => None,
     }
 }
 
@@ -2136,8 +3250,12 @@ fn even_len(l: u32) -> u32 {
 mod tests {
     use super::*;
     use crate::open_file;
+    use crate::OpenFileOptions;
     use byteordered::Endianness;
     use dicom_core::chrono::FixedOffset;
+    use dicom_core::dictionary::{
+        DataDictionaryEntryRef, MergedDictionary, TagRange, ValueMultiplicity, VirtualVr,
+    };
     use dicom_core::value::{DicomDate, DicomDateTime, DicomTime};
     use dicom_core::{dicom_value, header::DataElementHeader};
     use dicom_encoding::{
@@ -2462,6 +3580,309 @@ mod tests {
         );
     }
 
+    /// Create the contents of a DICOM file (without preamble)
+    /// whose data set is encoded in `actual_ts`
+    /// but whose file meta group declares `declared_ts`.
+    fn mismatched_ts_file(declared_ts: &str, actual_ts: &TransferSyntax) -> Vec<u8> {
+        let sop_uid = "1.4.645.212121";
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, sop_uid),
+            DataElement::new(tags::MODALITY, VR::CS, "CR"),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"),
+        ]);
+        let meta = FileMetaTableBuilder::new()
+            .transfer_syntax(declared_ts)
+            // Computed Radiography image storage
+            .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1")
+            .media_storage_sop_instance_uid(sop_uid)
+            .build()
+            .unwrap();
+
+        let mut bytes = b"DICM".to_vec();
+        bytes.extend(meta.to_bytes().unwrap());
+        obj.write_dataset_with_ts(&mut bytes, actual_ts).unwrap();
+        bytes
+    }
+
+    /// Files with a data set in explicit VR little endian
+    /// declared as implicit VR little endian, and vice versa,
+    /// can only be read with transfer syntax detection.
+    #[test]
+    fn open_file_with_mismatched_transfer_syntax() {
+        let explicit_vr_le = entries::EXPLICIT_VR_LITTLE_ENDIAN.erased();
+        let implicit_vr_le = entries::IMPLICIT_VR_LITTLE_ENDIAN.erased();
+
+        for (declared_ts, actual_ts) in [
+            (&implicit_vr_le, &explicit_vr_le),
+            (&explicit_vr_le, &implicit_vr_le),
+        ] {
+            let bytes = mismatched_ts_file(declared_ts.uid(), actual_ts);
+
+            // fails in strict mode
+            assert!(OpenFileOptions::new().from_reader(&bytes[..]).is_err());
+
+            // succeeds in detection mode
+            let obj = OpenFileOptions::new()
+                .detect_transfer_syntax(true)
+                .from_reader(&bytes[..])
+                .unwrap();
+            assert_eq!(obj.meta().transfer_syntax(), declared_ts.uid());
+            assert_eq!(obj.effective_transfer_syntax(), actual_ts.uid());
+            assert!(obj.transfer_syntax_mismatch());
+            assert_eq!(obj.get(tags::MODALITY).unwrap().to_str().unwrap(), "CR");
+            assert_eq!(
+                obj.get(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+                "Doe^John"
+            );
+
+            // same from a byte buffer
+            let obj = OpenFileOptions::new()
+                .detect_transfer_syntax(true)
+                .from_bytes(&bytes)
+                .unwrap();
+            assert_eq!(obj.effective_transfer_syntax(), actual_ts.uid());
+            assert_eq!(obj.get(tags::MODALITY).unwrap().to_str().unwrap(), "CR");
+
+            // and from a file
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("mismatched_ts.dcm");
+            std::fs::write(&path, &bytes).unwrap();
+
+            assert!(OpenFileOptions::new().open_file(&path).is_err());
+            let obj = OpenFileOptions::new()
+                .detect_transfer_syntax(true)
+                .open_file(&path)
+                .unwrap();
+            assert_eq!(obj.effective_transfer_syntax(), actual_ts.uid());
+            assert_eq!(
+                obj.get(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+                "Doe^John"
+            );
+        }
+    }
+
+    /// A parse error in debug trace mode
+    /// carries the last headers parsed and the bytes around the failure.
+    #[test]
+    fn open_file_with_debug_trace() {
+        let explicit_vr_le = entries::EXPLICIT_VR_LITTLE_ENDIAN.erased();
+        let mut bytes = mismatched_ts_file(explicit_vr_le.uid(), &explicit_vr_le);
+
+        // corrupt the length of Patient Name to go past the end of the file
+        let header_bytes = |len: u16| {
+            let [l0, l1] = len.to_le_bytes();
+            [0x10, 0x00, 0x10, 0x00, b'P', b'N', l0, l1]
+        };
+        let pos = bytes.windows(8).position(|w| w == header_bytes(8)).unwrap();
+        bytes[pos..pos + 8].copy_from_slice(&header_bytes(0x7FFF));
+
+        // no trace by default
+        let err = OpenFileOptions::new().from_reader(&bytes[..]).unwrap_err();
+        assert!(err.parse_trace().is_none());
+
+        let err = OpenFileOptions::new()
+            .debug_trace(8)
+            .from_reader(&bytes[..])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Could not read data set near offset 40");
+        let trace = err.parse_trace().unwrap();
+
+        // SOP Instance UID and Modality precede Patient Name
+        let headers: Vec<_> = trace
+            .headers()
+            .iter()
+            .map(|h| (h.offset, h.tag, h.vr, h.len))
+            .collect();
+        assert_eq!(
+            headers,
+            vec![
+                (0, tags::SOP_INSTANCE_UID, VR::UI, Length(14)),
+                (22, tags::MODALITY, VR::CS, Length(2)),
+                (32, tags::PATIENT_NAME, VR::PN, Length(0x7FFF)),
+            ]
+        );
+
+        // failed reading the value of Patient Name
+        assert_eq!(trace.offset(), 40);
+        assert_eq!(trace.bytes_offset(), 8);
+        assert_eq!(&trace.bytes()[..2], b"1.");
+        assert_eq!(&trace.bytes()[24..32], &header_bytes(0x7FFF));
+        assert_eq!(&trace.bytes()[32..], b"Doe^John");
+        let hexdump = trace.hexdump();
+        assert!(
+            hexdump.contains("00000018  60 00 43 53 02 00 43 52 10 00 10 00 50 4e ff 7f"),
+            "unexpected hex dump:\n{}",
+            hexdump
+        );
+        assert!(hexdump.contains("00000028  44 6f 65 5e 4a 6f 68 6e"));
+        assert!(hexdump.contains("|Doe^John|"));
+
+        // the trace is also available when reading from a byte buffer
+        let err = OpenFileOptions::new()
+            .debug_trace(8)
+            .from_bytes(&bytes)
+            .unwrap_err();
+        assert_eq!(err.parse_trace().unwrap().offset(), 40);
+    }
+
+    /// Transfer syntax detection does not interfere
+    /// with files whose data set matches the declared transfer syntax.
+    #[test]
+    fn open_file_with_matching_transfer_syntax_detection() {
+        let explicit_vr_le = entries::EXPLICIT_VR_LITTLE_ENDIAN.erased();
+        let bytes = mismatched_ts_file(explicit_vr_le.uid(), &explicit_vr_le);
+
+        let obj = OpenFileOptions::new()
+            .detect_transfer_syntax(true)
+            .from_reader(&bytes[..])
+            .unwrap();
+        assert_eq!(obj.effective_transfer_syntax(), explicit_vr_le.uid());
+        assert!(!obj.transfer_syntax_mismatch());
+        assert_eq!(obj.get(tags::MODALITY).unwrap().to_str().unwrap(), "CR");
+    }
+
+    /// Encapsulated pixel data in explicit VR big endian
+    /// is written with big endian item headers and offset table,
+    /// and read back as it was.
+    #[test]
+    fn write_and_read_big_endian_pixel_sequence() {
+        let explicit_vr_be = entries::EXPLICIT_VR_BIG_ENDIAN.erased();
+        let sop_uid = "1.4.645.212121";
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, sop_uid),
+            DataElement::new_with_len(
+                tags::PIXEL_DATA,
+                VR::OB,
+                Length::UNDEFINED,
+                PixelFragmentSequence::new(vec![0, 16], vec![vec![0x99; 8], vec![0x88; 4]]),
+            ),
+        ]);
+
+        let mut data = Vec::new();
+        obj.write_dataset_with_ts(&mut data, &explicit_vr_be)
+            .unwrap();
+
+        #[rustfmt::skip]
+        let pixel_sequence: &[u8] = &[
+            // basic offset table
+            0xFF, 0xFE, 0xE0, 0x00, 0x00, 0x00, 0x00, 0x08,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+            // fragments
+            0xFF, 0xFE, 0xE0, 0x00, 0x00, 0x00, 0x00, 0x08,
+            0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x99,
+            0xFF, 0xFE, 0xE0, 0x00, 0x00, 0x00, 0x00, 0x04,
+            0x88, 0x88, 0x88, 0x88,
+            // sequence delimiter
+            0xFF, 0xFE, 0xE0, 0xDD, 0x00, 0x00, 0x00, 0x00,
+        ];
+        assert!(data.ends_with(pixel_sequence));
+
+        let check_pixel_data = |obj: &InMemDicomObject| {
+            let pixel_data = obj.get(tags::PIXEL_DATA).unwrap();
+            assert_eq!(pixel_data.offset_table(), Some(&[0, 16][..]));
+            assert_eq!(
+                pixel_data.fragments(),
+                Some(&[vec![0x99; 8], vec![0x88; 4]][..])
+            );
+        };
+
+        let read_obj = InMemDicomObject::read_dataset_with_ts(&data[..], &explicit_vr_be).unwrap();
+        check_pixel_data(&read_obj);
+
+        // same from a byte buffer
+        let meta = FileMetaTableBuilder::new()
+            .transfer_syntax(explicit_vr_be.uid())
+            .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.1")
+            .media_storage_sop_instance_uid(sop_uid)
+            .build()
+            .unwrap();
+        let mut bytes = b"DICM".to_vec();
+        bytes.extend(meta.to_bytes().unwrap());
+        bytes.extend(data);
+        let file_obj = OpenFileOptions::new().from_bytes(&bytes).unwrap();
+        check_pixel_data(&file_obj);
+    }
+
+    /// Modifying the fragments of encapsulated pixel data
+    /// never results in a file with an inconsistent basic offset table.
+    #[test]
+    fn write_modified_pixel_sequence_offset_table() {
+        use dicom_core::value::{check_offset_table, OffsetTablePolicy};
+
+        let sop_uid = "1.4.645.212122";
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, sop_uid),
+            DataElement::new_with_len(
+                tags::PIXEL_DATA,
+                VR::OB,
+                Length::UNDEFINED,
+                PixelFragmentSequence::new(vec![0, 16], vec![vec![0x99; 8], vec![0x88; 4]]),
+            ),
+        ]);
+        let mut file_object = obj
+            .with_meta(
+                FileMetaTableBuilder::default()
+                    // JPEG Baseline
+                    .transfer_syntax("1.2.840.10008.1.2.4.50")
+                    .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7")
+                    .media_storage_sop_instance_uid(sop_uid),
+            )
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join(format!("{}.dcm", sop_uid));
+        let write_and_read = |obj: &FileDicomObject<InMemDicomObject>| {
+            obj.write_to_file(&file_path).unwrap();
+            let saved = open_file(&file_path).unwrap();
+            let pixel_data = saved.get(tags::PIXEL_DATA).unwrap();
+            let seq = pixel_data.value().pixel_sequence().unwrap().clone();
+            assert_eq!(check_offset_table(&seq), Ok(()));
+            seq
+        };
+
+        // append a frame, recomputing the offset table
+        assert!(file_object.update_value(tags::PIXEL_DATA, |value| {
+            value
+                .pixel_sequence_mut()
+                .unwrap()
+                .push_fragment(vec![0x77; 6], OffsetTablePolicy::Recompute);
+        }));
+        let seq = write_and_read(&file_object);
+        assert_eq!(seq.offset_table(), &[0, 16, 28]);
+        assert_eq!(seq.fragments().len(), 3);
+
+        // remove the first frame, recomputing the offset table
+        file_object.update_value(tags::PIXEL_DATA, |value| {
+            let removed = value
+                .pixel_sequence_mut()
+                .unwrap()
+                .remove_frame(0, OffsetTablePolicy::Recompute);
+            assert_eq!(removed, Some(vec![vec![0x99; 8]]));
+        });
+        let seq = write_and_read(&file_object);
+        assert_eq!(seq.offset_table(), &[0, 12]);
+        assert_eq!(seq.fragments(), &[vec![0x88; 4], vec![0x77; 6]]);
+
+        // fragments modified directly leave a stale offset table,
+        // which is written empty
+        file_object.update_value(tags::PIXEL_DATA, |value| {
+            value.fragments_mut().unwrap().insert(0, vec![0x66; 2]);
+        });
+        let seq = write_and_read(&file_object);
+        assert!(seq.offset_table().is_empty());
+        assert_eq!(seq.fragments().len(), 3);
+
+        // invalidated offset tables are written empty
+        file_object.update_value(tags::PIXEL_DATA, |value| {
+            let pixel_sequence = value.pixel_sequence_mut().unwrap();
+            *pixel_sequence.offset_table_mut() = vec![0, 10, 22].into();
+            pixel_sequence.remove_frame(2, OffsetTablePolicy::Invalidate);
+        });
+        let seq = write_and_read(&file_object);
+        assert!(seq.offset_table().is_empty());
+        assert_eq!(seq.fragments(), &[vec![0x66; 2], vec![0x88; 4]]);
+    }
+
     /// Write a file from scratch, with exact file meta table.
     #[test]
     fn inmem_write_to_file_with_exact_meta() {
@@ -2597,20 +4018,263 @@ mod tests {
         assert_eq!(elem1, &another_patient_name);
     }
 
-    #[test]
-    fn inmem_object_get_by_name_opt() {
-        let another_patient_name = DataElement::new(
-            Tag(0x0010, 0x0010),
-            VR::PN,
-            PrimitiveValue::Str("Doe^John".to_string()),
+    #[test]
+    fn inmem_object_get_by_name_opt() {
+        let another_patient_name = DataElement::new(
+            Tag(0x0010, 0x0010),
+            VR::PN,
+            PrimitiveValue::Str("Doe^John".to_string()),
+        );
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(another_patient_name.clone());
+        let elem1 = obj.element_by_name_opt("PatientName").unwrap();
+        assert_eq!(elem1, Some(&another_patient_name));
+
+        // try a missing element, should return None
+        assert_eq!(obj.element_by_name_opt("PatientID").unwrap(), None);
+    }
+
+    #[test]
+    fn inmem_object_string_opt() {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::PATIENT_NAME, VR::PN, dicom_value!(Str, "Doe^John ")),
+            DataElement::new(tags::PATIENT_ID, VR::LO, dicom_value!(Str, "   ")),
+            DataElement::new(tags::PATIENT_SEX, VR::CS, PrimitiveValue::Empty),
+            DataElement::new(
+                tags::IMAGE_TYPE,
+                VR::CS,
+                dicom_value!(Strs, ["DERIVED", "PRIMARY "]),
+            ),
+            DataElement::new(
+                tags::OTHER_PATIENT_I_DS,
+                VR::LO,
+                dicom_value!(Strs, ["", "\0"]),
+            ),
+        ]);
+
+        // padded
+        assert_eq!(
+            obj.string_opt(tags::PATIENT_NAME).unwrap().as_deref(),
+            Some("Doe^John")
+        );
+        assert_eq!(obj.string_required(tags::PATIENT_NAME).unwrap(), "Doe^John");
+        // multi-valued
+        assert_eq!(
+            obj.string_opt(tags::IMAGE_TYPE).unwrap().as_deref(),
+            Some("DERIVED\\PRIMARY")
+        );
+
+        // missing and empty
+        for tag in [
+            tags::STUDY_ID,
+            tags::PATIENT_ID,
+            tags::PATIENT_SEX,
+            tags::OTHER_PATIENT_I_DS,
+        ] {
+            assert_eq!(obj.string_opt(tag).unwrap(), None);
+            assert!(matches!(
+                obj.string_required(tag),
+                Err(AttributeValueError::MissingOrEmpty { tag: t, .. }) if t == tag
+            ));
+        }
+
+        // also available through file objects
+        let file_obj = obj
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .transfer_syntax("1.2.840.10008.1.2.1")
+                    .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7")
+                    .media_storage_sop_instance_uid("2.25.221314879990624101283043547144116927116"),
+            )
+            .unwrap();
+        assert_eq!(
+            file_obj.string_required(tags::PATIENT_NAME).unwrap(),
+            "Doe^John"
+        );
+        assert_eq!(file_obj.string_opt(tags::PATIENT_ID).unwrap(), None);
+    }
+
+    #[test]
+    fn inmem_object_int_opt() {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [512])),
+            DataElement::new(
+                tags::COLUMNS,
+                VR::US,
+                PrimitiveValue::U16(Default::default()),
+            ),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [16, 8])),
+            DataElement::new(tags::NUMBER_OF_FRAMES, VR::IS, dicom_value!(Str, "12 ")),
+            DataElement::new(tags::SERIES_NUMBER, VR::IS, dicom_value!(Str, " ")),
+            DataElement::new(
+                tags::REFERENCED_FRAME_NUMBER,
+                VR::IS,
+                dicom_value!(Strs, ["7 ", "8"]),
+            ),
+            DataElement::new(tags::SIMPLE_FRAME_LIST, VR::UL, dicom_value!(U32, [3, 4])),
+            DataElement::new(tags::INSTANCE_NUMBER, VR::IS, dicom_value!(Str, "one")),
+        ]);
+
+        assert_eq!(obj.u16_opt(tags::ROWS).unwrap(), Some(512));
+        assert_eq!(obj.u16_required(tags::ROWS).unwrap(), 512);
+        // padded
+        assert_eq!(obj.u16_opt(tags::NUMBER_OF_FRAMES).unwrap(), Some(12));
+        assert_eq!(obj.u32_opt(tags::NUMBER_OF_FRAMES).unwrap(), Some(12));
+        // multi-valued
+        assert_eq!(obj.u16_opt(tags::BITS_ALLOCATED).unwrap(), Some(16));
+        assert_eq!(obj.u32_opt(tags::REFERENCED_FRAME_NUMBER).unwrap(), Some(7));
+        assert_eq!(obj.u32_required(tags::SIMPLE_FRAME_LIST).unwrap(), 3);
+
+        // missing and empty
+        for tag in [
+            tags::PIXEL_REPRESENTATION,
+            tags::COLUMNS,
+            tags::SERIES_NUMBER,
+        ] {
+            assert_eq!(obj.u16_opt(tag).unwrap(), None);
+            assert_eq!(obj.u32_opt(tag).unwrap(), None);
+            assert!(matches!(
+                obj.u16_required(tag),
+                Err(AttributeValueError::MissingOrEmpty { tag: t, .. }) if t == tag
+            ));
+            assert!(matches!(
+                obj.u32_required(tag),
+                Err(AttributeValueError::MissingOrEmpty { tag: t, .. }) if t == tag
+            ));
+        }
+
+        // not convertible
+        assert!(matches!(
+            obj.u16_opt(tags::INSTANCE_NUMBER),
+            Err(AttributeValueError::ConvertValue {
+                tag: tags::INSTANCE_NUMBER,
+                ..
+            })
+        ));
+        assert!(matches!(
+            obj.u32_required(tags::INSTANCE_NUMBER),
+            Err(AttributeValueError::ConvertValue {
+                tag: tags::INSTANCE_NUMBER,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn inmem_object_f64_opt() {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::RESCALE_SLOPE, VR::DS, dicom_value!(Str, "2.5 ")),
+            DataElement::new(tags::RESCALE_INTERCEPT, VR::DS, dicom_value!(Str, "")),
+            DataElement::new(
+                tags::PIXEL_SPACING,
+                VR::DS,
+                dicom_value!(Strs, ["0.5", "0.25 "]),
+            ),
+            DataElement::new(tags::FRAME_TIME, VR::FD, dicom_value!(F64, [1.5, 2.])),
+        ]);
+
+        // padded
+        assert_eq!(obj.f64_opt(tags::RESCALE_SLOPE).unwrap(), Some(2.5));
+        assert_eq!(obj.f64_required(tags::RESCALE_SLOPE).unwrap(), 2.5);
+        // multi-valued
+        assert_eq!(obj.f64_opt(tags::PIXEL_SPACING).unwrap(), Some(0.5));
+        assert_eq!(obj.f64_required(tags::FRAME_TIME).unwrap(), 1.5);
+
+        // missing and empty
+        for tag in [tags::SLICE_THICKNESS, tags::RESCALE_INTERCEPT] {
+            assert_eq!(obj.f64_opt(tag).unwrap(), None);
+            assert!(matches!(
+                obj.f64_required(tag),
+                Err(AttributeValueError::MissingOrEmpty { tag: t, .. }) if t == tag
+            ));
+        }
+    }
+
+    #[test]
+    fn inmem_object_tag_opt() {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::FRAME_INCREMENT_POINTER,
+                VR::AT,
+                dicom_value!(Tags, [tags::FRAME_TIME, tags::SLICE_LOCATION]),
+            ),
+            DataElement::new(
+                tags::SELECTOR_AT_VALUE,
+                VR::AT,
+                PrimitiveValue::Tags(Default::default()),
+            ),
+            DataElement::new(tags::PATIENT_NAME, VR::PN, dicom_value!(Str, "Doe^John")),
+        ]);
+
+        // multi-valued
+        assert_eq!(
+            obj.tag_opt(tags::FRAME_INCREMENT_POINTER).unwrap(),
+            Some(tags::FRAME_TIME)
+        );
+        assert_eq!(
+            obj.tag_required(tags::FRAME_INCREMENT_POINTER).unwrap(),
+            tags::FRAME_TIME
+        );
+
+        // missing and empty
+        for tag in [tags::DIMENSION_INDEX_POINTER, tags::SELECTOR_AT_VALUE] {
+            assert_eq!(obj.tag_opt(tag).unwrap(), None);
+            assert!(matches!(
+                obj.tag_required(tag),
+                Err(AttributeValueError::MissingOrEmpty { tag: t, .. }) if t == tag
+            ));
+        }
+
+        // not convertible
+        assert!(matches!(
+            obj.tag_opt(tags::PATIENT_NAME),
+            Err(AttributeValueError::ConvertValue {
+                tag: tags::PATIENT_NAME,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn inmem_object_date_opt() {
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::STUDY_DATE, VR::DA, dicom_value!(Str, "20240102 ")),
+            DataElement::new(
+                tags::SERIES_DATE,
+                VR::DA,
+                dicom_value!(Date, [DicomDate::from_ymd(2023, 6, 30).unwrap()]),
+            ),
+            DataElement::new(tags::PATIENT_BIRTH_DATE, VR::DA, dicom_value!(Str, "  ")),
+            DataElement::new(
+                tags::CALIBRATION_DATE,
+                VR::DA,
+                dicom_value!(Strs, ["20200101", "20210101"]),
+            ),
+        ]);
+
+        // padded
+        assert_eq!(
+            obj.date_opt(tags::STUDY_DATE).unwrap(),
+            Some(DicomDate::from_ymd(2024, 1, 2).unwrap())
+        );
+        assert_eq!(
+            obj.date_required(tags::SERIES_DATE).unwrap(),
+            DicomDate::from_ymd(2023, 6, 30).unwrap()
+        );
+        // multi-valued
+        assert_eq!(
+            obj.date_opt(tags::CALIBRATION_DATE).unwrap(),
+            Some(DicomDate::from_ymd(2020, 1, 1).unwrap())
         );
-        let mut obj = InMemDicomObject::new_empty();
-        obj.put(another_patient_name.clone());
-        let elem1 = obj.element_by_name_opt("PatientName").unwrap();
-        assert_eq!(elem1, Some(&another_patient_name));
 
-        // try a missing element, should return None
-        assert_eq!(obj.element_by_name_opt("PatientID").unwrap(), None);
+        // missing and empty
+        for tag in [tags::ACQUISITION_DATE, tags::PATIENT_BIRTH_DATE] {
+            assert_eq!(obj.date_opt(tag).unwrap(), None);
+            assert!(matches!(
+                obj.date_required(tag),
+                Err(AttributeValueError::MissingOrEmpty { tag: t, .. }) if t == tag
+            ));
+        }
     }
 
     #[test]
@@ -2799,6 +4463,7 @@ mod tests {
             false,
             Length::UNDEFINED,
             None,
+            false,
         )
         .unwrap();
 
@@ -2915,6 +4580,7 @@ mod tests {
             false,
             Length::UNDEFINED,
             None,
+            false,
         )
         .unwrap();
 
@@ -3023,6 +4689,7 @@ mod tests {
             false,
             Length::UNDEFINED,
             None,
+            false,
         )
         .unwrap();
 
@@ -3502,6 +5169,7 @@ mod tests {
             dict: StandardDataDictionary,
             len: Length(1),
             charset_changed: false,
+            order: None,
         };
 
         assert!(obj.length().is_defined());
@@ -4011,4 +5679,638 @@ mod tests {
             "No space available in group 0x0009"
         );
     }
+
+    /// A toy dictionary of private attributes
+    /// reserved by the creator `ACME 1.0`.
+    #[derive(Debug, Clone, Copy)]
+    struct AcmeDictionary;
+
+    static ACME_SERIAL: DataDictionaryEntryRef<'static> = DataDictionaryEntryRef::new(
+        TagRange::Single(Tag(0x0009, 0x0001)),
+        "AcmeSerial",
+        VirtualVr::Exact(VR::LO),
+    );
+
+    impl DataDictionary for AcmeDictionary {
+        type Entry = DataDictionaryEntryRef<'static>;
+
+        fn by_tag(&self, _: Tag) -> Option<&Self::Entry> {
+            None
+        }
+
+        fn by_name(&self, _: &str) -> Option<&Self::Entry> {
+            None
+        }
+
+        fn by_private_tag(&self, private_creator: &str, tag: Tag) -> Option<&Self::Entry> {
+            Some(&ACME_SERIAL).filter(|_| {
+                private_creator == "ACME 1.0" && tag.group() == 0x0009 && tag.element() & 0xFF == 1
+            })
+        }
+
+        fn private_by_name(&self, name: &str) -> Option<(&str, &Self::Entry)> {
+            Some(("ACME 1.0", &ACME_SERIAL)).filter(|_| name == "AcmeSerial")
+        }
+    }
+
+    #[test]
+    fn private_elements_by_name_with_merged_dictionary() {
+        let dict = MergedDictionary::new(StandardDataDictionary, AcmeDictionary);
+        let mut ds = InMemDicomObject::from_iter_with_dict(
+            vec![
+                DataElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::from("Doe^John")),
+                DataElement::new(Tag(0x0009, 0x0010), VR::LO, PrimitiveValue::from("OTHER")),
+            ],
+            dict,
+        );
+
+        // standard attributes are still recognized
+        assert_eq!(
+            ds.element_by_name("PatientName").unwrap().to_str().unwrap(),
+            "Doe^John"
+        );
+
+        // creator not in the data set yet
+        assert!(matches!(
+            ds.element_by_name("AcmeSerial"),
+            Err(AccessByNameError::NoSuchDataElementAlias { .. })
+        ));
+
+        ds.put_private_element(
+            0x0009,
+            "ACME 1.0",
+            0x01,
+            VR::LO,
+            PrimitiveValue::from("X-42"),
+        )
+        .unwrap();
+        let elem = ds.element_by_name("AcmeSerial").unwrap();
+        assert_eq!(elem.tag(), Tag(0x0009, 0x1101));
+        assert_eq!(elem.to_str().unwrap(), "X-42");
+
+        // unknown to both dictionaries
+        assert!(matches!(
+            ds.element_by_name("NotAnAttribute"),
+            Err(AccessByNameError::NoSuchAttributeName { .. })
+        ));
+    }
+
+    #[test]
+    fn reinterpret_un_element() {
+        let elem: InMemElement = DataElement::new(
+            tags::ROWS,
+            VR::UN,
+            PrimitiveValue::from(vec![0x00_u8, 0x02]),
+        );
+        // numeric conversion is not possible while in UN
+        assert!(elem.to_int::<u16>().is_err());
+
+        let promoted = elem.reinterpret_as(VR::US).unwrap();
+        assert_eq!(promoted.vr(), VR::US);
+        assert_eq!(promoted.to_int::<u16>().unwrap(), 512);
+
+        // same bytes in big endian
+        let promoted = elem
+            .reinterpret_as_with(VR::US, Endianness::Big, SpecificCharacterSet::default())
+            .unwrap();
+        assert_eq!(promoted.to_int::<u16>().unwrap(), 2);
+
+        // decimal strings
+        let elem: InMemElement = DataElement::new(
+            tags::PIXEL_SPACING,
+            VR::UN,
+            PrimitiveValue::from(b"1.5\\-2 ".to_vec()),
+        );
+        let promoted = elem.reinterpret_as(VR::DS).unwrap();
+        assert_eq!(promoted.vr(), VR::DS);
+        assert_eq!(promoted.to_multi_float64().unwrap(), vec![1.5, -2.]);
+
+        // odd number of bytes for US
+        let elem: InMemElement = DataElement::new(
+            tags::COLUMNS,
+            VR::UN,
+            PrimitiveValue::from(vec![0x00_u8, 0x02, 0x00]),
+        );
+        assert!(matches!(
+            elem.reinterpret_as(VR::US),
+            Err(ReinterpretError::InvalidValueLength {
+                len: 3,
+                size: 2,
+                ..
+            })
+        ));
+
+        // not UN
+        let elem: InMemElement =
+            DataElement::new(tags::ROWS, VR::US, PrimitiveValue::from(512_u16));
+        assert!(matches!(
+            elem.reinterpret_as(VR::UL),
+            Err(ReinterpretError::NotUnknown { vr: VR::US, .. })
+        ));
+
+        // sequences cannot be reinterpreted from raw bytes
+        let elem: InMemElement = DataElement::new(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            VR::UN,
+            PrimitiveValue::from(vec![0_u8; 8]),
+        );
+        assert!(matches!(
+            elem.reinterpret_as(VR::SQ),
+            Err(ReinterpretError::UnsupportedVr { vr: VR::SQ, .. })
+        ));
+    }
+
+    #[test]
+    fn promote_un_elements_in_object() {
+        let item = InMemDicomObject::from_element_iter([DataElement::new(
+            tags::PIXEL_SPACING,
+            VR::UN,
+            PrimitiveValue::from(b"0.5\\0.25".to_vec()),
+        )]);
+        let columns = DataElement::new(
+            tags::COLUMNS,
+            VR::UN,
+            PrimitiveValue::from(vec![0x00_u8, 0x02, 0x00]),
+        );
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::ROWS,
+                VR::UN,
+                PrimitiveValue::from(vec![0x00_u8, 0x02]),
+            ),
+            columns.clone(),
+            DataElement::new(
+                tags::SLICE_THICKNESS,
+                VR::UN,
+                PrimitiveValue::from(b"2.5 ".to_vec()),
+            ),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![item]),
+            ),
+            // private attribute without a private creator
+            DataElement::new(
+                Tag(0x0009, 0x1001),
+                VR::UN,
+                PrimitiveValue::from(vec![0x01_u8, 0x00]),
+            ),
+        ]);
+
+        assert_eq!(obj.promote_un_elements(&StandardDataDictionary), 3);
+
+        let rows = obj.get(tags::ROWS).unwrap();
+        assert_eq!(rows.vr(), VR::US);
+        assert_eq!(rows.to_int::<u16>().unwrap(), 512);
+
+        let slice_thickness = obj.get(tags::SLICE_THICKNESS).unwrap();
+        assert_eq!(slice_thickness.vr(), VR::DS);
+        assert_eq!(slice_thickness.to_float64().unwrap(), 2.5);
+
+        let pixel_spacing = obj
+            .value_at((tags::REFERENCED_IMAGE_SEQUENCE, 0, tags::PIXEL_SPACING))
+            .unwrap();
+        assert_eq!(pixel_spacing.to_multi_float64().unwrap(), vec![0.5, 0.25]);
+
+        // elements which could not be promoted are left untouched
+        assert_eq!(obj.get(tags::COLUMNS), Some(&columns));
+        assert_eq!(obj.get(Tag(0x0009, 0x1001)).unwrap().vr(), VR::UN);
+
+        // nothing left to promote
+        assert_eq!(obj.promote_un_elements(&StandardDataDictionary), 0);
+    }
+
+    #[test]
+    fn promote_un_private_elements() {
+        let dict = MergedDictionary::new(StandardDataDictionary, AcmeDictionary);
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                Tag(0x0009, 0x0010),
+                VR::UN,
+                PrimitiveValue::from(b"ACME 1.0".to_vec()),
+            ),
+            DataElement::new(
+                Tag(0x0009, 0x1001),
+                VR::UN,
+                PrimitiveValue::from(b"X-42".to_vec()),
+            ),
+        ]);
+
+        obj.promote_un_elements(&dict);
+
+        let serial = obj.get(Tag(0x0009, 0x1001)).unwrap();
+        assert_eq!(serial.vr(), VR::LO);
+        assert_eq!(serial.to_str().unwrap(), "X-42");
+    }
+
+    /// object with a sequence of two items,
+    /// the second one with a nested sequence
+    fn nested_object() -> InMemDicomObject {
+        let item_1 = InMemDicomObject::from_element_iter([DataElement::new(
+            tags::REFERENCED_SOP_INSTANCE_UID,
+            VR::UI,
+            PrimitiveValue::from("1.2.3.4"),
+        )]);
+        let item_2 = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::REFERENCED_SOP_INSTANCE_UID,
+                VR::UI,
+                PrimitiveValue::from("1.2.3.5"),
+            ),
+            DataElement::new(
+                tags::PURPOSE_OF_REFERENCE_CODE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(tags::CODE_VALUE, VR::SH, PrimitiveValue::from("121311")),
+                ])]),
+            ),
+        ]);
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::PATIENT_NAME, VR::PN, PrimitiveValue::from("Doe^John")),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![item_1, item_2]),
+            ),
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                PrimitiveValue::from(vec![0_u8; 4]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn walk_visits_nested_elements_in_order() {
+        let obj = nested_object();
+
+        let mut visited = vec![];
+        let flow = obj.walk(|path, header| {
+            assert_eq!(path.last(), Some(&AttributeSelectorStep::Tag(header.tag)));
+            visited.push((path.len() - 1, path.to_vec(), header.vr));
+            ControlFlow::Continue(())
+        });
+        assert_eq!(flow, ControlFlow::Continue(()));
+
+        let nested = |tag, item| AttributeSelectorStep::Nested { tag, item };
+        use AttributeSelectorStep::Tag as T;
+        assert_eq!(
+            visited,
+            vec![
+                (0, vec![T(tags::REFERENCED_IMAGE_SEQUENCE)], VR::SQ),
+                (
+                    1,
+                    vec![
+                        nested(tags::REFERENCED_IMAGE_SEQUENCE, 0),
+                        T(tags::REFERENCED_SOP_INSTANCE_UID)
+                    ],
+                    VR::UI
+                ),
+                (
+                    1,
+                    vec![
+                        nested(tags::REFERENCED_IMAGE_SEQUENCE, 1),
+                        T(tags::REFERENCED_SOP_INSTANCE_UID)
+                    ],
+                    VR::UI
+                ),
+                (
+                    1,
+                    vec![
+                        nested(tags::REFERENCED_IMAGE_SEQUENCE, 1),
+                        T(tags::PURPOSE_OF_REFERENCE_CODE_SEQUENCE)
+                    ],
+                    VR::SQ
+                ),
+                (
+                    2,
+                    vec![
+                        nested(tags::REFERENCED_IMAGE_SEQUENCE, 1),
+                        nested(tags::PURPOSE_OF_REFERENCE_CODE_SEQUENCE, 0),
+                        T(tags::CODE_VALUE)
+                    ],
+                    VR::SH
+                ),
+                (0, vec![T(tags::PATIENT_NAME)], VR::PN),
+                (0, vec![T(tags::PIXEL_DATA)], VR::OB),
+            ]
+        );
+
+        // paths can be turned into attribute selectors
+        let selector = AttributeSelector::new(visited[4].1.clone()).unwrap();
+        assert_eq!(obj.value_at(selector).unwrap().to_str().unwrap(), "121311");
+    }
+
+    #[test]
+    fn walk_stops_early() {
+        let obj = nested_object();
+
+        // stop at the first element of the second item
+        let mut visited = vec![];
+        let flow = obj.walk(|path, header| {
+            visited.push(header.tag);
+            if path.first()
+                == Some(&AttributeSelectorStep::Nested {
+                    tag: tags::REFERENCED_IMAGE_SEQUENCE,
+                    item: 1,
+                })
+            {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(flow, ControlFlow::Break(()));
+        assert_eq!(
+            visited,
+            vec![
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                tags::REFERENCED_SOP_INSTANCE_UID,
+                tags::REFERENCED_SOP_INSTANCE_UID,
+            ]
+        );
+
+        // stop right away
+        let mut count = 0;
+        let flow = obj.walk(|_, _| {
+            count += 1;
+            ControlFlow::Break(())
+        });
+        assert_eq!(flow, ControlFlow::Break(()));
+        assert_eq!(count, 1);
+
+        // nothing to visit
+        let flow = InMemDicomObject::new_empty().walk(|_, _| ControlFlow::Break(()));
+        assert_eq!(flow, ControlFlow::Continue(()));
+    }
+
+    /// Create an element with the given number of values
+    fn element_with_values(tag: Tag, vr: VR, count: u32) -> InMemElement {
+        let values: C<String> = (1..=count).map(|i| i.to_string()).collect();
+        DataElement::new(tag, vr, PrimitiveValue::Strs(values))
+    }
+
+    #[test]
+    fn put_checked_enforces_value_multiplicity() {
+        use ValueMultiplicity::*;
+
+        let cases: &[(Tag, VR, ValueMultiplicity, &[u32], &[u32])] = &[
+            // VM 1
+            (tags::PATIENT_NAME, VR::PN, Exactly(1), &[1], &[2, 5]),
+            // VM 2
+            (tags::PIXEL_SPACING, VR::DS, Exactly(2), &[2], &[1, 3]),
+            // VM 3
+            (
+                tags::IMAGE_POSITION_PATIENT,
+                VR::DS,
+                Exactly(3),
+                &[3],
+                &[2, 6],
+            ),
+            // VM 1-3
+            (tags::SHUTTER_SHAPE, VR::CS, Between(1, 3), &[1, 2, 3], &[4]),
+            // VM 1-n
+            (tags::WINDOW_CENTER, VR::DS, AtLeast(1), &[1, 2, 40], &[]),
+            // VM 2-n
+            (tags::IMAGE_TYPE, VR::CS, AtLeast(2), &[2, 3, 8], &[1]),
+            // VM 2-2n
+            (
+                tags::VERTICES_OF_THE_POLYGONAL_SHUTTER,
+                VR::IS,
+                MultipleOf(2),
+                &[2, 4, 10],
+                &[1, 3, 7],
+            ),
+            // VM 3-3n
+            (
+                tags::CALCULATED_FRAME_LIST,
+                VR::UL,
+                MultipleOf(3),
+                &[3, 6, 12],
+                &[1, 2, 4, 8],
+            ),
+        ];
+
+        for &(tag, vr, vm, passing, failing) in cases {
+            let mut obj = InMemDicomObject::new_empty();
+            for &count in passing {
+                assert_eq!(
+                    obj.put_checked(element_with_values(tag, vr, count)),
+                    Ok(()),
+                    "{} values should be accepted for VM {}",
+                    count,
+                    vm,
+                );
+                assert_eq!(obj.get(tag).unwrap().value().multiplicity(), count);
+            }
+            let last_count = passing.last().copied();
+
+            for &count in failing {
+                let violation = obj
+                    .put_checked(element_with_values(tag, vr, count))
+                    .unwrap_err();
+                assert_eq!(violation.tag, tag);
+                assert_eq!(violation.vm, vm);
+                assert_eq!(violation.count, count);
+                // object was left unchanged
+                assert_eq!(obj.get(tag).map(|e| e.value().multiplicity()), last_count);
+            }
+        }
+    }
+
+    #[test]
+    fn put_checked_counts_backslash_separated_values() {
+        let mut obj = InMemDicomObject::new_empty();
+
+        // VM 2-n
+        obj.put_checked(DataElement::new(
+            tags::IMAGE_TYPE,
+            VR::CS,
+            PrimitiveValue::from("ORIGINAL\\PRIMARY"),
+        ))
+        .unwrap();
+        assert_eq!(
+            obj.put_checked(DataElement::new(
+                tags::IMAGE_TYPE,
+                VR::CS,
+                PrimitiveValue::from("ORIGINAL"),
+            ))
+            .unwrap_err()
+            .count,
+            1
+        );
+
+        // VM 1
+        let violation = obj
+            .put_checked(DataElement::new(
+                tags::PATIENT_NAME,
+                VR::PN,
+                PrimitiveValue::from("Doe^John\\Doe^Jane"),
+            ))
+            .unwrap_err();
+        assert_eq!(violation.count, 2);
+
+        // text values are never split
+        obj.put_checked(DataElement::new(
+            tags::IMAGE_COMMENTS,
+            VR::LT,
+            PrimitiveValue::from("left\\right"),
+        ))
+        .unwrap();
+
+        assert_eq!(obj.iter().count(), 2);
+        assert_eq!(obj.validate_vm(), vec![]);
+    }
+
+    #[test]
+    fn put_checked_ignores_unconstrained_elements() {
+        let mut obj = InMemDicomObject::new_empty();
+
+        // empty values are always accepted
+        obj.put_checked(DataElement::new(
+            tags::PIXEL_SPACING,
+            VR::DS,
+            PrimitiveValue::Empty,
+        ))
+        .unwrap();
+        obj.put_checked(DataElement::new(
+            tags::IMAGE_POSITION_PATIENT,
+            VR::DS,
+            PrimitiveValue::from(""),
+        ))
+        .unwrap();
+
+        // binary values are not counted by their number of bytes
+        obj.put_checked(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PrimitiveValue::from(vec![0_u8; 16]),
+        ))
+        .unwrap();
+
+        // sequences are not counted by their number of items
+        obj.put_checked(DataElement::new(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![
+                InMemDicomObject::new_empty(),
+                InMemDicomObject::new_empty(),
+            ]),
+        ))
+        .unwrap();
+
+        // attributes not in the dictionary
+        obj.put_checked(element_with_values(Tag(0x0009, 0x1001), VR::LO, 3))
+            .unwrap();
+
+        assert_eq!(obj.iter().count(), 5);
+        assert_eq!(obj.validate_vm(), vec![]);
+    }
+
+    #[test]
+    fn validate_vm_reports_all_violations() {
+        // put does not validate
+        let mut obj = InMemDicomObject::from_element_iter([
+            element_with_values(tags::PATIENT_NAME, VR::PN, 3),
+            element_with_values(tags::IMAGE_TYPE, VR::CS, 2),
+            element_with_values(tags::PIXEL_SPACING, VR::DS, 1),
+        ]);
+        obj.put(DataElement::new(
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                element_with_values(tags::VERTICES_OF_THE_POLYGONAL_SHUTTER, VR::IS, 5),
+            ])]),
+        ));
+        assert_eq!(obj.iter().count(), 4);
+
+        assert_eq!(
+            obj.validate_vm(),
+            vec![
+                VmViolation {
+                    tag: tags::VERTICES_OF_THE_POLYGONAL_SHUTTER,
+                    alias: "VerticesOfThePolygonalShutter".to_string(),
+                    vm: ValueMultiplicity::MultipleOf(2),
+                    count: 5,
+                },
+                VmViolation {
+                    tag: tags::PATIENT_NAME,
+                    alias: "PatientName".to_string(),
+                    vm: ValueMultiplicity::Exactly(1),
+                    count: 3,
+                },
+                VmViolation {
+                    tag: tags::PIXEL_SPACING,
+                    alias: "PixelSpacing".to_string(),
+                    vm: ValueMultiplicity::Exactly(2),
+                    count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn insertion_order_is_preserved_on_request() {
+        let mut obj = InMemDicomObject::from_element_iter([DataElement::new(
+            tags::PATIENT_ID,
+            VR::LO,
+            "1234",
+        )])
+        .with_insertion_order();
+        assert!(obj.preserves_insertion_order());
+
+        obj.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"));
+        obj.put(DataElement::new(tags::MODALITY, VR::CS, "OT"));
+        obj.put(DataElement::new(tags::STUDY_DATE, VR::DA, "20240101"));
+        // replacing an element keeps its position
+        obj.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^Jane"));
+        // removing an element forgets its position
+        assert!(obj.remove_element(tags::MODALITY));
+        obj.put(DataElement::new(tags::MODALITY, VR::CS, "CT"));
+
+        let order: Vec<_> = obj.iter_in_insertion_order().map(|e| e.tag()).collect();
+        assert_eq!(
+            order,
+            [
+                tags::PATIENT_ID,
+                tags::PATIENT_NAME,
+                tags::STUDY_DATE,
+                tags::MODALITY,
+            ]
+        );
+
+        // other access is by tag as usual
+        let order: Vec<_> = obj.tags().collect();
+        assert_eq!(
+            order,
+            [
+                tags::STUDY_DATE,
+                tags::MODALITY,
+                tags::PATIENT_NAME,
+                tags::PATIENT_ID,
+            ]
+        );
+        assert_eq!(
+            obj.get(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+            "Doe^Jane"
+        );
+
+        obj.retain(|e| e.tag() != tags::STUDY_DATE);
+        let order: Vec<_> = obj
+            .into_iter_in_insertion_order()
+            .map(|e| e.tag())
+            .collect();
+        assert_eq!(
+            order,
+            [tags::PATIENT_ID, tags::PATIENT_NAME, tags::MODALITY]
+        );
+
+        // objects do not preserve insertion order by default
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(DataElement::new(tags::PATIENT_NAME, VR::PN, "Doe^John"));
+        obj.put(DataElement::new(tags::MODALITY, VR::CS, "OT"));
+        assert!(!obj.preserves_insertion_order());
+        let order: Vec<_> = obj.iter_in_insertion_order().map(|e| e.tag()).collect();
+        assert_eq!(order, [tags::MODALITY, tags::PATIENT_NAME]);
+    }
 }
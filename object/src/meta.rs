@@ -1,9 +1,9 @@
 //! Module containing data structures and readers of DICOM file meta information tables.
 use byteordered::byteorder::{ByteOrder, LittleEndian};
 use dicom_core::dicom_value;
-use dicom_core::header::{DataElement, EmptyObject, HasLength, Header};
+use dicom_core::header::{DataElement, DataElementHeader, EmptyObject, HasLength, Header};
 use dicom_core::ops::{ApplyOp, AttributeAction, AttributeOp, AttributeSelectorStep};
-use dicom_core::value::{PrimitiveValue, Value, ValueType};
+use dicom_core::value::{trim_padding, PrimitiveValue, Value, ValueType};
 use dicom_core::{Length, Tag, VR};
 use dicom_dictionary_std::tags;
 use dicom_encoding::decode::{self, DecodeFrom};
@@ -180,6 +180,175 @@ where
         .context(DecodeTextSnafu { name: text.name() })
 }
 
+/// Read the value of a file meta group data element
+/// right after its header,
+/// recording it in the given table builder.
+///
+/// Values of unknown attributes are consumed and discarded.
+fn read_element_value<S, T>(
+    file: &mut S,
+    elem: &DataElementHeader,
+    text: &T,
+    builder: FileMetaTableBuilder,
+) -> Result<FileMetaTableBuilder>
+where
+    S: Read,
+    T: TextCodec,
+{
+    let elem_len = match elem.length().get() {
+        None => {
+            return UndefinedValueLengthSnafu { tag: elem.tag() }.fail();
+        }
+        Some(len) => len,
+    };
+    let builder = match elem.tag() {
+        Tag(0x0002, 0x0001) => {
+            // Implementation Version
+            if elem.length() != Length(2) {
+                return UnexpectedDataValueLengthSnafu {
+                    tag: elem.tag(),
+                    length: elem.length(),
+                }
+                .fail();
+            }
+            let mut hbuf = [0u8; 2];
+            file.read_exact(&mut hbuf[..]).context(ReadValueDataSnafu)?;
+
+            builder.information_version(hbuf)
+        }
+        // Media Storage SOP Class UID
+        Tag(0x0002, 0x0002) => {
+            builder.media_storage_sop_class_uid(read_str_body(file, text, elem_len)?)
+        }
+        // Media Storage SOP Instance UID
+        Tag(0x0002, 0x0003) => {
+            builder.media_storage_sop_instance_uid(read_str_body(file, text, elem_len)?)
+        }
+        // Transfer Syntax
+        Tag(0x0002, 0x0010) => builder.transfer_syntax(read_str_body(file, text, elem_len)?),
+        // Implementation Class UID
+        Tag(0x0002, 0x0012) => {
+            builder.implementation_class_uid(read_str_body(file, text, elem_len)?)
+        }
+        Tag(0x0002, 0x0013) => {
+            // Implementation Version Name
+            let mut v = Vec::new();
+            v.try_reserve_exact(elem_len as usize)
+                .context(AllocationSizeSnafu)?;
+            v.resize(elem_len as usize, 0);
+            file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+
+            builder.implementation_version_name(
+                text.decode(&v)
+                    .context(DecodeTextSnafu { name: text.name() })?,
+            )
+        }
+        Tag(0x0002, 0x0016) => {
+            // Source Application Entity Title
+            let mut v = Vec::new();
+            v.try_reserve_exact(elem_len as usize)
+                .context(AllocationSizeSnafu)?;
+            v.resize(elem_len as usize, 0);
+            file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+
+            builder.source_application_entity_title(
+                text.decode(&v)
+                    .context(DecodeTextSnafu { name: text.name() })?,
+            )
+        }
+        Tag(0x0002, 0x0017) => {
+            // Sending Application Entity Title
+            let mut v = Vec::new();
+            v.try_reserve_exact(elem_len as usize)
+                .context(AllocationSizeSnafu)?;
+            v.resize(elem_len as usize, 0);
+            file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+
+            builder.sending_application_entity_title(
+                text.decode(&v)
+                    .context(DecodeTextSnafu { name: text.name() })?,
+            )
+        }
+        Tag(0x0002, 0x0018) => {
+            // Receiving Application Entity Title
+            let mut v = Vec::new();
+            v.try_reserve_exact(elem_len as usize)
+                .context(AllocationSizeSnafu)?;
+            v.resize(elem_len as usize, 0);
+            file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+
+            builder.receiving_application_entity_title(
+                text.decode(&v)
+                    .context(DecodeTextSnafu { name: text.name() })?,
+            )
+        }
+        Tag(0x0002, 0x0100) => {
+            // Private Information Creator UID
+            let mut v = Vec::new();
+            v.try_reserve_exact(elem_len as usize)
+                .context(AllocationSizeSnafu)?;
+            v.resize(elem_len as usize, 0);
+            file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+
+            builder.private_information_creator_uid(
+                text.decode(&v)
+                    .context(DecodeTextSnafu { name: text.name() })?,
+            )
+        }
+        Tag(0x0002, 0x0102) => {
+            // Private Information
+            let mut v = Vec::new();
+            v.try_reserve_exact(elem_len as usize)
+                .context(AllocationSizeSnafu)?;
+            v.resize(elem_len as usize, 0);
+            file.read_exact(&mut v).context(ReadValueDataSnafu)?;
+
+            builder.private_information(v)
+        }
+        tag @ Tag(0x0002, _) => {
+            // unknown tag, do nothing
+            // could be an unsupported or non-standard attribute
+            tracing::info!("Unknown tag {}", tag);
+            // consume value without saving it
+            let bytes_read = std::io::copy(
+                &mut file.by_ref().take(elem_len as u64),
+                &mut std::io::sink(),
+            )
+            .context(ReadValueDataSnafu)?;
+            if bytes_read != elem_len as u64 {
+                // reported element length longer than actual stream
+                return UnexpectedDataValueLengthSnafu {
+                    tag: elem.tag(),
+                    length: elem_len,
+                }
+                .fail();
+            }
+            builder
+        }
+        tag => {
+            // unexpected tag from another group! do nothing for now,
+            // but this could pose an issue up ahead (see #50)
+            tracing::warn!("Unexpected off-group tag {}", tag);
+            // consume value without saving it
+            let bytes_read = std::io::copy(
+                &mut file.by_ref().take(elem_len as u64),
+                &mut std::io::sink(),
+            )
+            .context(ReadValueDataSnafu)?;
+            if bytes_read != elem_len as u64 {
+                // reported element length longer than actual stream
+                return UnexpectedDataValueLengthSnafu {
+                    tag: elem.tag(),
+                    length: elem_len,
+                }
+                .fail();
+            }
+            builder
+        }
+    };
+    Ok(builder)
+}
+
 impl FileMetaTable {
     /// Construct a file meta group table
     /// by parsing a DICOM data set from a reader.
@@ -187,40 +356,83 @@ impl FileMetaTable {
         FileMetaTable::read_from(file)
     }
 
+    /// Construct a file meta group table
+    /// by parsing the file meta group data set at the start of the given bytes,
+    /// which must not include the preamble nor the `DICM` magic code.
+    ///
+    /// Data elements are read until the first one
+    /// which is not part of the file meta group (0002,xxxx),
+    /// or until the end of the input.
+    /// As such, _File Meta Information Group Length_ may be missing,
+    /// and its value is not trusted when present.
+    /// The group length of the resulting table
+    /// is always calculated from the other attributes.
+    ///
+    /// Returns the table and the number of bytes consumed,
+    /// which is also the position of anything following the file meta group.
+    pub fn read_from_bytes(bytes: &[u8]) -> Result<(Self, usize)> {
+        let decoder = decode::file_header_decoder();
+        let text = text::DefaultCharacterSetCodec;
+
+        let mut source = bytes;
+        let mut builder = FileMetaTableBuilder::new();
+        while source.len() >= 2 && LittleEndian::read_u16(source) == 0x0002 {
+            let (elem, _bytes_read) = decoder
+                .decode_header(&mut source)
+                .context(DecodeElementSnafu)?;
+            if elem.tag() == Tag(0x0002, 0x0000) {
+                // skip the group length, it will be recalculated
+                let len = elem
+                    .length()
+                    .get()
+                    .context(UndefinedValueLengthSnafu { tag: elem.tag() })?
+                    as usize;
+                ensure!(
+                    len <= source.len(),
+                    UnexpectedDataValueLengthSnafu {
+                        tag: elem.tag(),
+                        length: elem.length(),
+                    }
+                );
+                source = &source[len..];
+                continue;
+            }
+            builder = read_element_value(&mut source, &elem, &text, builder)?;
+        }
+
+        Ok((builder.build()?, bytes.len() - source.len()))
+    }
+
     /// Getter for the transfer syntax UID,
     /// with trailing characters already excluded.
     pub fn transfer_syntax(&self) -> &str {
-        self.transfer_syntax
-            .trim_end_matches(|c: char| c.is_whitespace() || c == '\0')
+        trim_padding(&self.transfer_syntax)
     }
 
     /// Getter for the media storage SOP instance UID,
     /// with trailing characters already excluded.
     pub fn media_storage_sop_instance_uid(&self) -> &str {
-        self.media_storage_sop_instance_uid
-            .trim_end_matches(|c: char| c.is_whitespace() || c == '\0')
+        trim_padding(&self.media_storage_sop_instance_uid)
     }
 
     /// Getter for the media storage SOP class UID,
     /// with trailing characters already excluded.
     pub fn media_storage_sop_class_uid(&self) -> &str {
-        self.media_storage_sop_class_uid
-            .trim_end_matches(|c: char| c.is_whitespace() || c == '\0')
+        trim_padding(&self.media_storage_sop_class_uid)
     }
 
     /// Getter for the implementation class UID,
     /// with trailing characters already excluded.
     pub fn implementation_class_uid(&self) -> &str {
-        self.implementation_class_uid
-            .trim_end_matches(|c: char| c.is_whitespace() || c == '\0')
+        trim_padding(&self.implementation_class_uid)
     }
 
     /// Getter for the private information creator UID,
     /// with trailing characters already excluded.
     pub fn private_information_creator_uid(&self) -> Option<&str> {
         self.private_information_creator_uid
-            .as_ref()
-            .map(|s| s.trim_end_matches(|c: char| c.is_whitespace() || c == '\0'))
+            .as_deref()
+            .map(trim_padding)
     }
 
     /// Set the file meta table's transfer syntax
@@ -230,10 +442,7 @@ impl FileMetaTable {
     /// to the given transfer syntax, without padding to even length.
     /// The information group length field is automatically recalculated.
     pub fn set_transfer_syntax<D, R, W>(&mut self, ts: &TransferSyntax<D, R, W>) {
-        self.transfer_syntax = ts
-            .uid()
-            .trim_end_matches(|c: char| c.is_whitespace() || c == '\0')
-            .to_string();
+        self.transfer_syntax = trim_padding(ts.uid()).to_string();
         self.update_information_group_length();
     }
 
@@ -517,154 +726,8 @@ impl FileMetaTable {
             let (elem, header_bytes_read) = decoder
                 .decode_header(&mut file)
                 .context(DecodeElementSnafu)?;
-            let elem_len = match elem.length().get() {
-                None => {
-                    return UndefinedValueLengthSnafu { tag: elem.tag() }.fail();
-                }
-                Some(len) => len,
-            };
-            builder = match elem.tag() {
-                Tag(0x0002, 0x0001) => {
-                    // Implementation Version
-                    if elem.length() != Length(2) {
-                        return UnexpectedDataValueLengthSnafu {
-                            tag: elem.tag(),
-                            length: elem.length(),
-                        }
-                        .fail();
-                    }
-                    let mut hbuf = [0u8; 2];
-                    file.read_exact(&mut hbuf[..]).context(ReadValueDataSnafu)?;
-
-                    builder.information_version(hbuf)
-                }
-                // Media Storage SOP Class UID
-                Tag(0x0002, 0x0002) => {
-                    builder.media_storage_sop_class_uid(read_str_body(&mut file, &text, elem_len)?)
-                }
-                // Media Storage SOP Instance UID
-                Tag(0x0002, 0x0003) => builder
-                    .media_storage_sop_instance_uid(read_str_body(&mut file, &text, elem_len)?),
-                // Transfer Syntax
-                Tag(0x0002, 0x0010) => {
-                    builder.transfer_syntax(read_str_body(&mut file, &text, elem_len)?)
-                }
-                // Implementation Class UID
-                Tag(0x0002, 0x0012) => {
-                    builder.implementation_class_uid(read_str_body(&mut file, &text, elem_len)?)
-                }
-                Tag(0x0002, 0x0013) => {
-                    // Implementation Version Name
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
-
-                    builder.implementation_version_name(
-                        text.decode(&v)
-                            .context(DecodeTextSnafu { name: text.name() })?,
-                    )
-                }
-                Tag(0x0002, 0x0016) => {
-                    // Source Application Entity Title
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
-
-                    builder.source_application_entity_title(
-                        text.decode(&v)
-                            .context(DecodeTextSnafu { name: text.name() })?,
-                    )
-                }
-                Tag(0x0002, 0x0017) => {
-                    // Sending Application Entity Title
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
-
-                    builder.sending_application_entity_title(
-                        text.decode(&v)
-                            .context(DecodeTextSnafu { name: text.name() })?,
-                    )
-                }
-                Tag(0x0002, 0x0018) => {
-                    // Receiving Application Entity Title
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
-
-                    builder.receiving_application_entity_title(
-                        text.decode(&v)
-                            .context(DecodeTextSnafu { name: text.name() })?,
-                    )
-                }
-                Tag(0x0002, 0x0100) => {
-                    // Private Information Creator UID
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
-
-                    builder.private_information_creator_uid(
-                        text.decode(&v)
-                            .context(DecodeTextSnafu { name: text.name() })?,
-                    )
-                }
-                Tag(0x0002, 0x0102) => {
-                    // Private Information
-                    let mut v = Vec::new();
-                    v.try_reserve_exact(elem_len as usize)
-                        .context(AllocationSizeSnafu)?;
-                    v.resize(elem_len as usize, 0);
-                    file.read_exact(&mut v).context(ReadValueDataSnafu)?;
-
-                    builder.private_information(v)
-                }
-                tag @ Tag(0x0002, _) => {
-                    // unknown tag, do nothing
-                    // could be an unsupported or non-standard attribute
-                    tracing::info!("Unknown tag {}", tag);
-                    // consume value without saving it
-                    let bytes_read =
-                        std::io::copy(&mut (&mut file).take(elem_len as u64), &mut std::io::sink())
-                            .context(ReadValueDataSnafu)?;
-                    if bytes_read != elem_len as u64 {
-                        // reported element length longer than actual stream
-                        return UnexpectedDataValueLengthSnafu {
-                            tag: elem.tag(),
-                            length: elem_len,
-                        }
-                        .fail();
-                    }
-                    builder
-                }
-                tag => {
-                    // unexpected tag from another group! do nothing for now,
-                    // but this could pose an issue up ahead (see #50)
-                    tracing::warn!("Unexpected off-group tag {}", tag);
-                    // consume value without saving it
-                    let bytes_read =
-                        std::io::copy(&mut (&mut file).take(elem_len as u64), &mut std::io::sink())
-                            .context(ReadValueDataSnafu)?;
-                    if bytes_read != elem_len as u64 {
-                        // reported element length longer than actual stream
-                        return UnexpectedDataValueLengthSnafu {
-                            tag: elem.tag(),
-                            length: elem_len,
-                        }
-                        .fail();
-                    }
-                    builder
-                }
-            };
+            let elem_len = elem.length().get().unwrap_or(0);
+            builder = read_element_value(&mut file, &elem, &text, builder)?;
             total_bytes_read = total_bytes_read
                 .saturating_add(header_bytes_read as u32)
                 .saturating_add(elem_len);
@@ -770,6 +833,20 @@ impl FileMetaTable {
         self.clone().into_element_iter()
     }
 
+    /// Encode the file meta group data set into a new byte vector,
+    /// without the preamble nor the `DICM` magic code.
+    ///
+    /// The _File Meta Information Group Length_ written
+    /// is calculated from the other attributes,
+    /// regardless of the current value of `information_group_length`.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut table = self.clone();
+        table.update_information_group_length();
+        let mut out = Vec::with_capacity(12 + table.information_group_length as usize);
+        table.write(&mut out)?;
+        Ok(out)
+    }
+
     pub fn write<W: Write>(&self, writer: W) -> Result<()> {
         let mut dset = DataSetWriter::new(
             writer,
@@ -1027,7 +1104,7 @@ mod tests {
     use dicom_core::ops::{AttributeAction, AttributeOp};
     use dicom_core::value::Value;
     use dicom_core::{dicom_value, DataElement, PrimitiveValue, Tag, VR};
-    use dicom_dictionary_std::tags;
+    use dicom_dictionary_std::{tags, uids};
 
     const TEST_META_1: &'static [u8] = &[
         // magic code
@@ -1203,6 +1280,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn padded_uids_are_trimmed_in_getters() {
+        let table = FileMetaTableBuilder::new()
+            // padded with a trailing null character
+            .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7\0")
+            .media_storage_sop_instance_uid("2.25.123456789 ")
+            // padded with a trailing space, as done by some implementations
+            .transfer_syntax("1.2.840.10008.1.2.1 ")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            table.media_storage_sop_class_uid(),
+            uids::SECONDARY_CAPTURE_IMAGE_STORAGE
+        );
+        assert_eq!(table.media_storage_sop_instance_uid(), "2.25.123456789");
+        assert_eq!(table.transfer_syntax(), uids::EXPLICIT_VR_LITTLE_ENDIAN);
+
+        // the padding is retained in the table itself
+        assert_eq!(table.transfer_syntax, "1.2.840.10008.1.2.1 ");
+
+        // and survives a round trip through its encoded form
+        let bytes = table.to_bytes().unwrap();
+        let (table, _) = FileMetaTable::read_from_bytes(&bytes).unwrap();
+        assert_eq!(
+            table.media_storage_sop_class_uid(),
+            uids::SECONDARY_CAPTURE_IMAGE_STORAGE
+        );
+        assert_eq!(table.transfer_syntax(), uids::EXPLICIT_VR_LITTLE_ENDIAN);
+    }
+
     #[test]
     fn read_meta_table_into_iter() {
         let table = FileMetaTable {
@@ -1413,4 +1521,104 @@ mod tests {
 
         assert_eq!(table.information_group_length, table2.information_group_length);
     }
+
+    fn sample_table() -> FileMetaTable {
+        FileMetaTableBuilder::new()
+            .media_storage_sop_class_uid("1.2.840.10008.5.1.4.1.1.7")
+            .media_storage_sop_instance_uid("2.25.137731752600317795446120660167595746868")
+            .transfer_syntax("1.2.840.10008.1.2.1")
+            .implementation_class_uid("2.25.305828488182831875890203105390285383139")
+            .implementation_version_name("MYTOOL100")
+            .source_application_entity_title("RUSTY")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn read_meta_table_from_bytes() {
+        // same as the reader, minus the magic code
+        let (table, consumed) = FileMetaTable::read_from_bytes(&TEST_META_1[4..]).unwrap();
+        assert_eq!(consumed, TEST_META_1.len() - 4);
+        assert_eq!(table.transfer_syntax(), "1.2.840.10008.1.2.1");
+        assert_eq!(table.implementation_class_uid(), "1.2.345.6.7890.1.234");
+        assert_eq!(table.source_application_entity_title.as_deref(), Some(""));
+        // group length is recalculated
+        assert_eq!(
+            table.information_group_length,
+            table.calculate_information_group_length()
+        );
+    }
+
+    #[test]
+    fn meta_table_bytes_round_trip() {
+        let table = sample_table();
+        let bytes = table.to_bytes().unwrap();
+        // group length element is written first
+        assert_eq!(&bytes[..8], &[0x02, 0x00, 0x00, 0x00, b'U', b'L', 4, 0]);
+        assert_eq!(
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+            bytes.len() - 12
+        );
+
+        let (table2, consumed) = FileMetaTable::read_from_bytes(&bytes).unwrap();
+        assert_eq!(consumed, bytes.len());
+        assert_eq!(table2, table);
+        assert_eq!(table2.to_bytes().unwrap(), bytes);
+
+        // an outdated group length in the table is not written
+        let mut table3 = table.clone();
+        table3.information_group_length = 2;
+        assert_eq!(table3.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn read_meta_table_from_padded_bytes() {
+        let meta = sample_table().to_bytes().unwrap();
+
+        // followed by a data set element: (0008,0016) UI
+        let mut bytes = meta.clone();
+        bytes.extend_from_slice(&[0x08, 0x00, 0x16, 0x00, b'U', b'I', 2, 0, b'1', 0]);
+        let (table, consumed) = FileMetaTable::read_from_bytes(&bytes).unwrap();
+        assert_eq!(consumed, meta.len());
+        assert_eq!(table, sample_table());
+
+        // followed by zero padding
+        let mut bytes = meta.clone();
+        bytes.extend_from_slice(&[0; 7]);
+        let (_, consumed) = FileMetaTable::read_from_bytes(&bytes).unwrap();
+        assert_eq!(consumed, meta.len());
+    }
+
+    #[test]
+    fn read_meta_table_from_bytes_with_bad_group_length() {
+        let meta = sample_table().to_bytes().unwrap();
+
+        // without group length
+        let (table, consumed) = FileMetaTable::read_from_bytes(&meta[12..]).unwrap();
+        assert_eq!(consumed, meta.len() - 12);
+        assert_eq!(table, sample_table());
+
+        // with a group length too short or too long
+        for declared in [10_u32, 0xFFFF] {
+            let mut bytes = meta.clone();
+            bytes[8..12].copy_from_slice(&declared.to_le_bytes());
+            bytes.extend_from_slice(&[0x08, 0x00, 0x16, 0x00, b'U', b'I', 2, 0, b'1', 0]);
+            let (table, consumed) = FileMetaTable::read_from_bytes(&bytes).unwrap();
+            assert_eq!(consumed, meta.len());
+            assert_eq!(table, sample_table());
+        }
+    }
+
+    #[test]
+    fn read_meta_table_from_truncated_bytes() {
+        let meta = sample_table().to_bytes().unwrap();
+
+        // cut in the middle of the last element value
+        assert!(FileMetaTable::read_from_bytes(&meta[..meta.len() - 2]).is_err());
+        // cut in the middle of an element header
+        assert!(FileMetaTable::read_from_bytes(&meta[..16]).is_err());
+        // group length only, transfer syntax is missing
+        assert!(FileMetaTable::read_from_bytes(&meta[..12]).is_err());
+        assert!(FileMetaTable::read_from_bytes(&[]).is_err());
+    }
 }
@@ -0,0 +1,776 @@
+//! Builders of query identifiers for the query/retrieve service class.
+//!
+//! A C-FIND request is accompanied by an identifier:
+//! a DICOM data set with the _Query/Retrieve Level_,
+//! the matching keys which the remote node should filter by,
+//! and the return keys (empty attributes) which it should fill in.
+//! [`StudyRootQuery`] and [`PatientRootQuery`] build these identifiers
+//! for the respective information models,
+//! validating them against the key tables in PS3.4 Annex C
+//! before producing an [`InMemDicomObject`].
+//!
+//! ```
+//! # use dicom_object::query::{QueryRetrieveLevel, StudyRootQuery};
+//! use dicom_core::chrono::NaiveDate;
+//! use dicom_core::value::DateRange;
+//! use dicom_dictionary_std::tags;
+//!
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let identifier = StudyRootQuery::new(QueryRetrieveLevel::Study)
+//!     .patient_name("Doe^J*")
+//!     .study_date_range(DateRange::from_start_to_end(
+//!         NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+//!         NaiveDate::from_ymd_opt(2024, 6, 30).unwrap(),
+//!     )?)
+//!     .return_key(tags::STUDY_INSTANCE_UID)
+//!     .return_key(tags::STUDY_DESCRIPTION)
+//!     .build()?;
+//!
+//! assert_eq!(
+//!     identifier.get(tags::STUDY_DATE).unwrap().to_str()?,
+//!     "20240101-20240630",
+//! );
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+//!
+//! Only the attributes listed in the key tables are checked
+//! against the query level.
+//! Any other attribute is taken as an optional key
+//! of the level being queried.
+use std::fmt;
+use std::marker::PhantomData;
+
+use dicom_core::chrono::{NaiveDate, NaiveTime, Timelike};
+use dicom_core::value::{DateRange, TimeRange};
+use dicom_core::{DataDictionary, DataElement, PrimitiveValue, Tag, VR};
+use dicom_dictionary_std::{tags, uids, StandardDataDictionary};
+use snafu::{ensure, Snafu};
+
+use crate::InMemDicomObject;
+
+/// An error which may occur when building a query identifier.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum QueryError {
+    /// Level {level} is not part of the {model} information model
+    UnsupportedLevel {
+        level: QueryRetrieveLevel,
+        model: &'static str,
+    },
+    /// Attribute {tag} is not a key of level {level} in the {model} information model
+    KeyNotAllowed {
+        tag: Tag,
+        level: QueryRetrieveLevel,
+        model: &'static str,
+    },
+    /// Missing unique key {tag} of level {level}
+    MissingUniqueKey { tag: Tag, level: QueryRetrieveLevel },
+    /// Unique key {tag} of level {level} must have a single value
+    MultipleUniqueKeys { tag: Tag, level: QueryRetrieveLevel },
+    /// Attribute {tag} with VR {vr} does not support wildcard matching
+    InvalidWildcard { tag: Tag, vr: VR },
+    /// Attribute {tag} does not support matching multiple values
+    MultipleValues { tag: Tag },
+    /// Invalid UID `{uid}` in attribute {tag}
+    InvalidUid { tag: Tag, uid: String },
+}
+
+pub type Result<T, E = QueryError> = std::result::Result<T, E>;
+
+/// A level of the query/retrieve information model hierarchy,
+/// as set in _Query/Retrieve Level_.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum QueryRetrieveLevel {
+    /// `PATIENT`
+    Patient,
+    /// `STUDY`
+    Study,
+    /// `SERIES`
+    Series,
+    /// `IMAGE`, which applies to composite object instances in general
+    Image,
+}
+
+impl QueryRetrieveLevel {
+    /// Obtain the code string of this level,
+    /// as used in _Query/Retrieve Level_.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QueryRetrieveLevel::Patient => "PATIENT",
+            QueryRetrieveLevel::Study => "STUDY",
+            QueryRetrieveLevel::Series => "SERIES",
+            QueryRetrieveLevel::Image => "IMAGE",
+        }
+    }
+
+    /// Obtain the unique key of this level.
+    pub fn unique_key(self) -> Tag {
+        match self {
+            QueryRetrieveLevel::Patient => tags::PATIENT_ID,
+            QueryRetrieveLevel::Study => tags::STUDY_INSTANCE_UID,
+            QueryRetrieveLevel::Series => tags::SERIES_INSTANCE_UID,
+            QueryRetrieveLevel::Image => tags::SOP_INSTANCE_UID,
+        }
+    }
+}
+
+impl fmt::Display for QueryRetrieveLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A query/retrieve information model,
+/// which determines the levels of the hierarchy.
+///
+/// This trait is sealed,
+/// implemented by [`PatientRoot`] and [`StudyRoot`].
+pub trait InformationModel: private::Sealed {
+    /// The name of the information model
+    const NAME: &'static str;
+
+    /// The SOP class UID for querying with this information model (C-FIND)
+    const FIND_SOP_CLASS_UID: &'static str;
+
+    /// The levels of the information model, from top to bottom
+    const LEVELS: &'static [QueryRetrieveLevel];
+
+    /// Obtain the level of the given attribute in this model,
+    /// or `None` if it is not in the key tables.
+    fn key_level(tag: Tag) -> Option<QueryRetrieveLevel>;
+}
+
+/// The Patient Root query/retrieve information model
+/// (PS3.4 C.6.1)
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct PatientRoot;
+
+impl private::Sealed for PatientRoot {}
+
+impl InformationModel for PatientRoot {
+    const NAME: &'static str = "Patient Root";
+    const FIND_SOP_CLASS_UID: &'static str =
+        uids::PATIENT_ROOT_QUERY_RETRIEVE_INFORMATION_MODEL_FIND;
+    const LEVELS: &'static [QueryRetrieveLevel] = &[
+        QueryRetrieveLevel::Patient,
+        QueryRetrieveLevel::Study,
+        QueryRetrieveLevel::Series,
+        QueryRetrieveLevel::Image,
+    ];
+
+    fn key_level(tag: Tag) -> Option<QueryRetrieveLevel> {
+        key_level(tag)
+    }
+}
+
+/// The Study Root query/retrieve information model
+/// (PS3.4 C.6.2),
+/// in which patient attributes are part of the study level
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct StudyRoot;
+
+impl private::Sealed for StudyRoot {}
+
+impl InformationModel for StudyRoot {
+    const NAME: &'static str = "Study Root";
+    const FIND_SOP_CLASS_UID: &'static str = uids::STUDY_ROOT_QUERY_RETRIEVE_INFORMATION_MODEL_FIND;
+    const LEVELS: &'static [QueryRetrieveLevel] = &[
+        QueryRetrieveLevel::Study,
+        QueryRetrieveLevel::Series,
+        QueryRetrieveLevel::Image,
+    ];
+
+    fn key_level(tag: Tag) -> Option<QueryRetrieveLevel> {
+        key_level(tag).map(|level| level.max(QueryRetrieveLevel::Study))
+    }
+}
+
+/// The level of the attributes in the key tables
+/// of the Patient Root information model.
+fn key_level(tag: Tag) -> Option<QueryRetrieveLevel> {
+    match tag {
+        tags::PATIENT_NAME
+        | tags::PATIENT_ID
+        | tags::ISSUER_OF_PATIENT_ID
+        | tags::PATIENT_BIRTH_DATE
+        | tags::PATIENT_SEX
+        | tags::NUMBER_OF_PATIENT_RELATED_STUDIES
+        | tags::NUMBER_OF_PATIENT_RELATED_SERIES
+        | tags::NUMBER_OF_PATIENT_RELATED_INSTANCES => Some(QueryRetrieveLevel::Patient),
+        tags::STUDY_DATE
+        | tags::STUDY_TIME
+        | tags::ACCESSION_NUMBER
+        | tags::STUDY_ID
+        | tags::STUDY_INSTANCE_UID
+        | tags::REFERRING_PHYSICIAN_NAME
+        | tags::STUDY_DESCRIPTION
+        | tags::MODALITIES_IN_STUDY
+        | tags::SOP_CLASSES_IN_STUDY
+        | tags::NUMBER_OF_STUDY_RELATED_SERIES
+        | tags::NUMBER_OF_STUDY_RELATED_INSTANCES => Some(QueryRetrieveLevel::Study),
+        tags::MODALITY
+        | tags::SERIES_NUMBER
+        | tags::SERIES_INSTANCE_UID
+        | tags::SERIES_DESCRIPTION
+        | tags::NUMBER_OF_SERIES_RELATED_INSTANCES => Some(QueryRetrieveLevel::Series),
+        tags::INSTANCE_NUMBER | tags::SOP_INSTANCE_UID | tags::SOP_CLASS_UID => {
+            Some(QueryRetrieveLevel::Image)
+        }
+        _ => None,
+    }
+}
+
+/// A query identifier builder
+/// for the Study Root information model.
+pub type StudyRootQuery = Query<StudyRoot>;
+
+/// A query identifier builder
+/// for the Patient Root information model.
+pub type PatientRootQuery = Query<PatientRoot>;
+
+/// A builder of query identifiers
+/// for the information model `M`.
+///
+/// See the [module-level documentation](self) for an example.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query<M> {
+    level: QueryRetrieveLevel,
+    /// the matching and return keys, in order of declaration
+    keys: Vec<(Tag, VR, PrimitiveValue)>,
+    model: PhantomData<M>,
+}
+
+impl<M: InformationModel> Query<M> {
+    /// Start a query at the given level.
+    pub fn new(level: QueryRetrieveLevel) -> Self {
+        Query {
+            level,
+            keys: Vec::new(),
+            model: PhantomData,
+        }
+    }
+
+    /// Obtain the level of this query.
+    pub fn level(&self) -> QueryRetrieveLevel {
+        self.level
+    }
+
+    /// Obtain the SOP class UID with which the identifier should be sent
+    /// in a C-FIND request.
+    pub fn sop_class_uid(&self) -> &'static str {
+        M::FIND_SOP_CLASS_UID
+    }
+
+    /// Match by _Patient's Name_,
+    /// which may contain the wildcards `*` and `?`.
+    pub fn patient_name(self, name: &str) -> Self {
+        self.key(tags::PATIENT_NAME, VR::PN, name.into())
+    }
+
+    /// Match by _Patient ID_.
+    pub fn patient_id(self, id: &str) -> Self {
+        self.key(tags::PATIENT_ID, VR::LO, id.into())
+    }
+
+    /// Match by a range of _Patient's Birth Date_.
+    pub fn patient_birth_date_range(self, range: DateRange) -> Self {
+        self.key(
+            tags::PATIENT_BIRTH_DATE,
+            VR::DA,
+            date_range_to_string(&range).into(),
+        )
+    }
+
+    /// Match by an exact _Study Date_.
+    pub fn study_date(self, date: NaiveDate) -> Self {
+        self.key(tags::STUDY_DATE, VR::DA, date_to_string(date).into())
+    }
+
+    /// Match by a range of _Study Date_.
+    pub fn study_date_range(self, range: DateRange) -> Self {
+        self.key(
+            tags::STUDY_DATE,
+            VR::DA,
+            date_range_to_string(&range).into(),
+        )
+    }
+
+    /// Match by a range of _Study Time_.
+    pub fn study_time_range(self, range: TimeRange) -> Self {
+        self.key(
+            tags::STUDY_TIME,
+            VR::TM,
+            time_range_to_string(&range).into(),
+        )
+    }
+
+    /// Match by _Accession Number_.
+    pub fn accession_number(self, accession_number: &str) -> Self {
+        self.key(tags::ACCESSION_NUMBER, VR::SH, accession_number.into())
+    }
+
+    /// Match by _Study Description_,
+    /// which may contain the wildcards `*` and `?`.
+    pub fn study_description(self, description: &str) -> Self {
+        self.key(tags::STUDY_DESCRIPTION, VR::LO, description.into())
+    }
+
+    /// Match by a single _Study Instance UID_.
+    pub fn study_instance_uid(self, uid: &str) -> Self {
+        self.study_instance_uids([uid])
+    }
+
+    /// Match by any of the given _Study Instance UID_ values.
+    pub fn study_instance_uids<I, S>(self, uids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.key(tags::STUDY_INSTANCE_UID, VR::UI, uid_list(uids))
+    }
+
+    /// Match by _Modality_.
+    pub fn modality(self, modality: &str) -> Self {
+        self.key(tags::MODALITY, VR::CS, modality.into())
+    }
+
+    /// Match by a single _Series Instance UID_.
+    pub fn series_instance_uid(self, uid: &str) -> Self {
+        self.series_instance_uids([uid])
+    }
+
+    /// Match by any of the given _Series Instance UID_ values.
+    pub fn series_instance_uids<I, S>(self, uids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.key(tags::SERIES_INSTANCE_UID, VR::UI, uid_list(uids))
+    }
+
+    /// Match by any of the given _SOP Instance UID_ values.
+    pub fn sop_instance_uids<I, S>(self, uids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.key(tags::SOP_INSTANCE_UID, VR::UI, uid_list(uids))
+    }
+
+    /// Match by any other attribute in its textual form,
+    /// with the value representation from the standard data dictionary.
+    ///
+    /// An empty value declares a return key instead.
+    pub fn matching_key(self, tag: Tag, value: &str) -> Self {
+        let value = if value.is_empty() {
+            PrimitiveValue::Empty
+        } else {
+            value.into()
+        };
+        self.key(tag, dictionary_vr(tag), value)
+    }
+
+    /// Declare an attribute to be returned by the remote node,
+    /// which is added to the identifier with an empty value.
+    ///
+    /// This replaces any matching key previously set on the same attribute.
+    pub fn return_key(self, tag: Tag) -> Self {
+        self.key(tag, dictionary_vr(tag), PrimitiveValue::Empty)
+    }
+
+    /// Declare multiple attributes to be returned by the remote node.
+    pub fn return_keys<I>(self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = Tag>,
+    {
+        tags.into_iter()
+            .fold(self, |query, tag| query.return_key(tag))
+    }
+
+    fn key(mut self, tag: Tag, vr: VR, value: PrimitiveValue) -> Self {
+        self.keys.retain(|(t, _, _)| *t != tag);
+        self.keys.push((tag, vr, value));
+        self
+    }
+
+    /// Validate the query and build the identifier.
+    pub fn build(self) -> Result<InMemDicomObject> {
+        let level = self.level;
+        ensure!(
+            M::LEVELS.contains(&level),
+            UnsupportedLevelSnafu {
+                level,
+                model: M::NAME,
+            }
+        );
+
+        for (tag, vr, value) in &self.keys {
+            if let Some(key_level) = M::key_level(*tag) {
+                // keys of upper levels are restricted to their unique keys
+                ensure!(
+                    key_level == level || (key_level < level && *tag == key_level.unique_key()),
+                    KeyNotAllowedSnafu {
+                        tag: *tag,
+                        level,
+                        model: M::NAME,
+                    }
+                );
+            }
+            check_value(*tag, *vr, value)?;
+        }
+
+        // each upper level is identified by a single unique key
+        for upper_level in M::LEVELS.iter().take_while(|l| **l < level) {
+            let tag = upper_level.unique_key();
+            let value = self
+                .keys
+                .iter()
+                .find(|(t, _, _)| *t == tag)
+                .map(|(_, _, value)| value);
+            match value {
+                None | Some(PrimitiveValue::Empty) => {
+                    return MissingUniqueKeySnafu {
+                        tag,
+                        level: *upper_level,
+                    }
+                    .fail();
+                }
+                Some(value) => ensure!(
+                    value.multiplicity() == 1,
+                    MultipleUniqueKeysSnafu {
+                        tag,
+                        level: *upper_level,
+                    }
+                ),
+            }
+        }
+
+        let mut obj = InMemDicomObject::new_empty();
+        obj.put(DataElement::new(
+            tags::QUERY_RETRIEVE_LEVEL,
+            VR::CS,
+            PrimitiveValue::from(level.as_str()),
+        ));
+        for (tag, vr, value) in self.keys {
+            obj.put(DataElement::new(tag, vr, value));
+        }
+        Ok(obj)
+    }
+}
+
+/// Check a key value against the matching rules of its VR.
+fn check_value(tag: Tag, vr: VR, value: &PrimitiveValue) -> Result<()> {
+    let values = match value {
+        PrimitiveValue::Str(value) => std::slice::from_ref(value),
+        PrimitiveValue::Strs(values) => &values[..],
+        _ => return Ok(()),
+    };
+
+    for value in values {
+        match vr {
+            // UID list matching
+            VR::UI => {
+                let uid = value.trim_end_matches('\0');
+                ensure!(
+                    is_valid_uid(uid),
+                    InvalidUidSnafu {
+                        tag,
+                        uid: uid.to_string(),
+                    }
+                );
+            }
+            // wild card matching
+            VR::AE | VR::CS | VR::LO | VR::LT | VR::PN | VR::SH | VR::ST | VR::UC | VR::UT => {
+                ensure!(!value.contains('\\'), MultipleValuesSnafu { tag });
+            }
+            _ => {
+                ensure!(
+                    !value.contains(['*', '?']),
+                    InvalidWildcardSnafu { tag, vr }
+                );
+            }
+        }
+    }
+    if vr != VR::UI {
+        ensure!(values.len() <= 1, MultipleValuesSnafu { tag });
+    }
+    Ok(())
+}
+
+/// Check whether the given text is a valid UID,
+/// made of numeric components separated by periods,
+/// with no wildcards.
+pub(crate) fn is_valid_uid(uid: &str) -> bool {
+    !uid.is_empty()
+        && uid.len() <= 64
+        && uid
+            .split('.')
+            .all(|c| !c.is_empty() && c.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn uid_list<I, S>(uids: I) -> PrimitiveValue
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    PrimitiveValue::Strs(uids.into_iter().map(Into::into).collect())
+}
+
+fn dictionary_vr(tag: Tag) -> VR {
+    StandardDataDictionary
+        .by_tag(tag)
+        .and_then(|e| e.vr.exact())
+        .unwrap_or(VR::UN)
+}
+
+fn date_to_string(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+fn time_to_string(time: NaiveTime) -> String {
+    if time.nanosecond() == 0 {
+        time.format("%H%M%S").to_string()
+    } else {
+        time.format("%H%M%S%.6f").to_string()
+    }
+}
+
+/// Render a date range per the DICOM range matching syntax
+/// (PS3.4 C.2.2.2.5).
+fn date_range_to_string(range: &DateRange) -> String {
+    match (range.start(), range.end()) {
+        (Some(start), Some(end)) if start == end => date_to_string(*start),
+        (start, end) => format!(
+            "{}-{}",
+            start.copied().map(date_to_string).unwrap_or_default(),
+            end.copied().map(date_to_string).unwrap_or_default(),
+        ),
+    }
+}
+
+/// Render a time range per the DICOM range matching syntax
+/// (PS3.4 C.2.2.2.5).
+fn time_range_to_string(range: &TimeRange) -> String {
+    match (range.start(), range.end()) {
+        (Some(start), Some(end)) if start == end => time_to_string(*start),
+        (start, end) => format!(
+            "{}-{}",
+            start.copied().map(time_to_string).unwrap_or_default(),
+            end.copied().map(time_to_string).unwrap_or_default(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::header::Header;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    /// Collect the identifier's elements as (tag, VR, text value)
+    fn elements(obj: &InMemDicomObject) -> Vec<(Tag, VR, String)> {
+        obj.iter()
+            .map(|e| (e.tag(), e.vr(), e.to_str().unwrap().into_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn study_level_query_with_date_range() {
+        let obj = StudyRootQuery::new(QueryRetrieveLevel::Study)
+            .patient_name("DOE^J*")
+            .study_date_range(
+                DateRange::from_start_to_end(date(2024, 1, 1), date(2024, 1, 31)).unwrap(),
+            )
+            .study_time_range(TimeRange::from_start(
+                NaiveTime::from_hms_opt(8, 30, 0).unwrap(),
+            ))
+            .return_keys([tags::STUDY_INSTANCE_UID, tags::STUDY_DESCRIPTION])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            elements(&obj),
+            vec![
+                (tags::STUDY_DATE, VR::DA, "20240101-20240131".to_string()),
+                (tags::STUDY_TIME, VR::TM, "083000-".to_string()),
+                (tags::QUERY_RETRIEVE_LEVEL, VR::CS, "STUDY".to_string()),
+                (tags::STUDY_DESCRIPTION, VR::LO, String::new()),
+                (tags::PATIENT_NAME, VR::PN, "DOE^J*".to_string()),
+                (tags::STUDY_INSTANCE_UID, VR::UI, String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn date_ranges_are_rendered() {
+        let cases = [
+            (DateRange::from_start(date(2020, 2, 29)), "20200229-"),
+            (DateRange::from_end(date(1999, 12, 31)), "-19991231"),
+            (
+                DateRange::from_start_to_end(date(2023, 5, 1), date(2023, 5, 1)).unwrap(),
+                "20230501",
+            ),
+        ];
+        for (range, expected) in cases {
+            let obj = PatientRootQuery::new(QueryRetrieveLevel::Patient)
+                .patient_birth_date_range(range)
+                .build()
+                .unwrap();
+            assert_eq!(
+                obj.get(tags::PATIENT_BIRTH_DATE).unwrap().to_str().unwrap(),
+                expected
+            );
+        }
+
+        let obj = StudyRootQuery::new(QueryRetrieveLevel::Study)
+            .study_date(date(2021, 7, 4))
+            .build()
+            .unwrap();
+        assert_eq!(
+            obj.get(tags::STUDY_DATE).unwrap().to_str().unwrap(),
+            "20210704"
+        );
+    }
+
+    #[test]
+    fn image_level_query_with_multiple_uids() {
+        let obj = PatientRootQuery::new(QueryRetrieveLevel::Image)
+            .patient_id("P-0001")
+            .study_instance_uid("1.2.3.4")
+            .series_instance_uid("1.2.3.4.5")
+            .sop_instance_uids(["1.2.3.4.5.6", "1.2.3.4.5.7"])
+            .return_key(tags::INSTANCE_NUMBER)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            elements(&obj),
+            vec![
+                (
+                    tags::SOP_INSTANCE_UID,
+                    VR::UI,
+                    "1.2.3.4.5.6\\1.2.3.4.5.7".to_string()
+                ),
+                (tags::QUERY_RETRIEVE_LEVEL, VR::CS, "IMAGE".to_string()),
+                (tags::PATIENT_ID, VR::LO, "P-0001".to_string()),
+                (tags::STUDY_INSTANCE_UID, VR::UI, "1.2.3.4".to_string()),
+                (tags::SERIES_INSTANCE_UID, VR::UI, "1.2.3.4.5".to_string()),
+                (tags::INSTANCE_NUMBER, VR::IS, String::new()),
+            ]
+        );
+        assert_eq!(
+            PatientRootQuery::new(QueryRetrieveLevel::Image).sop_class_uid(),
+            uids::PATIENT_ROOT_QUERY_RETRIEVE_INFORMATION_MODEL_FIND
+        );
+    }
+
+    #[test]
+    fn invalid_queries_are_rejected() {
+        // no patient level in study root
+        assert!(matches!(
+            StudyRootQuery::new(QueryRetrieveLevel::Patient).build(),
+            Err(QueryError::UnsupportedLevel {
+                level: QueryRetrieveLevel::Patient,
+                ..
+            })
+        ));
+
+        // study attributes cannot be matched at the series level
+        assert!(matches!(
+            StudyRootQuery::new(QueryRetrieveLevel::Series)
+                .study_instance_uid("1.2.3")
+                .study_description("CHEST*")
+                .build(),
+            Err(QueryError::KeyNotAllowed {
+                tag: tags::STUDY_DESCRIPTION,
+                level: QueryRetrieveLevel::Series,
+                ..
+            })
+        ));
+
+        // patient attributes belong to the study level in study root
+        assert!(StudyRootQuery::new(QueryRetrieveLevel::Study)
+            .patient_id("P-0001")
+            .build()
+            .is_ok());
+        assert!(matches!(
+            PatientRootQuery::new(QueryRetrieveLevel::Study)
+                .patient_id("P-0001")
+                .patient_name("DOE^JOHN")
+                .build(),
+            Err(QueryError::KeyNotAllowed {
+                tag: tags::PATIENT_NAME,
+                ..
+            })
+        ));
+
+        // the unique keys of upper levels are required
+        assert!(matches!(
+            PatientRootQuery::new(QueryRetrieveLevel::Series)
+                .patient_id("P-0001")
+                .modality("MR")
+                .build(),
+            Err(QueryError::MissingUniqueKey {
+                tag: tags::STUDY_INSTANCE_UID,
+                level: QueryRetrieveLevel::Study,
+            })
+        ));
+        assert!(matches!(
+            StudyRootQuery::new(QueryRetrieveLevel::Series)
+                .study_instance_uids(["1.2.3", "1.2.4"])
+                .build(),
+            Err(QueryError::MultipleUniqueKeys {
+                tag: tags::STUDY_INSTANCE_UID,
+                ..
+            })
+        ));
+
+        // wildcards are not supported in UIDs nor dates
+        assert!(matches!(
+            StudyRootQuery::new(QueryRetrieveLevel::Study)
+                .study_instance_uid("1.2.*")
+                .build(),
+            Err(QueryError::InvalidUid { .. })
+        ));
+        assert!(matches!(
+            StudyRootQuery::new(QueryRetrieveLevel::Study)
+                .matching_key(tags::STUDY_DATE, "2024*")
+                .build(),
+            Err(QueryError::InvalidWildcard {
+                tag: tags::STUDY_DATE,
+                vr: VR::DA,
+            })
+        ));
+
+        // a single patient name is matched at a time
+        assert!(matches!(
+            StudyRootQuery::new(QueryRetrieveLevel::Study)
+                .patient_name("DOE^JOHN\\DOE^JANE")
+                .build(),
+            Err(QueryError::MultipleValues {
+                tag: tags::PATIENT_NAME
+            })
+        ));
+    }
+
+    #[test]
+    fn return_key_replaces_matching_key() {
+        let obj = StudyRootQuery::new(QueryRetrieveLevel::Study)
+            .matching_key(tags::MODALITIES_IN_STUDY, "CT")
+            .return_key(tags::MODALITIES_IN_STUDY)
+            .build()
+            .unwrap();
+        let modalities = obj.get(tags::MODALITIES_IN_STUDY).unwrap();
+        assert_eq!(modalities.vr(), VR::CS);
+        assert_eq!(modalities.value().primitive(), Some(&PrimitiveValue::Empty));
+    }
+}
@@ -0,0 +1,510 @@
+//! Consistent replacement of UIDs across DICOM objects.
+//!
+//! A [`UidRemapper`] replaces the UIDs of the objects given to it,
+//! such as the study, series and SOP instance UIDs,
+//! keeping a single mapping from original to new UID
+//! for all of them.
+//! As such, the relationships between objects of the same study
+//! (a shared series or frame of reference,
+//! or a reference to another instance)
+//! remain valid after remapping,
+//! which is useful for de-identification
+//! and for the generation of test data.
+//!
+//! ```
+//! # use dicom_core::{DataElement, VR};
+//! # use dicom_dictionary_std::tags;
+//! # use dicom_object::InMemDicomObject;
+//! use dicom_object::remap::{UidRemapper, UidStrategy};
+//!
+//! let mut first = InMemDicomObject::from_element_iter([
+//!     DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, "1.2.3.4"),
+//!     DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "1.2.3.4.1"),
+//! ]);
+//! let mut second = InMemDicomObject::from_element_iter([
+//!     DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, "1.2.3.4"),
+//!     DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, "1.2.3.4.2"),
+//! ]);
+//!
+//! let mut remapper = UidRemapper::new(UidStrategy::Random);
+//! let delta = remapper.remap_object(&mut first);
+//! assert_eq!(delta.len(), 2);
+//! let delta = remapper.remap_object(&mut second);
+//! // the series UID was already mapped
+//! assert_eq!(delta.len(), 1);
+//!
+//! let series_uid = first.get(tags::SERIES_INSTANCE_UID).unwrap().to_str()?;
+//! assert!(series_uid.starts_with("2.25."));
+//! assert_eq!(
+//!     second.get(tags::SERIES_INSTANCE_UID).unwrap().to_str()?,
+//!     series_uid,
+//! );
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{BuildHasher, Hasher};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dicom_core::ops::{AttributeSelector, AttributeSelectorStep};
+use dicom_core::value::{PrimitiveValue, Value};
+use dicom_core::{DataDictionary, Tag, VR};
+use dicom_dictionary_std::tags;
+
+use crate::{FileDicomObject, InMemDicomObject};
+
+/// How a [`UidRemapper`] obtains new UIDs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UidStrategy {
+    /// Generate a new UID under the `2.25` root
+    /// out of a random (version 4) UUID.
+    ///
+    /// The random numbers are not cryptographically secure.
+    Random,
+    /// Derive a new UID under the `2.25` root
+    /// from a 128-bit hash of the original UID and the given salt,
+    /// so that the same original UID
+    /// is always mapped to the same new UID
+    /// across runs with the same salt.
+    Hashed(String),
+    /// Replace only the UIDs in the given map,
+    /// from original to new UID,
+    /// without generating new ones.
+    Explicit(BTreeMap<String, String>),
+}
+
+/// A replacer of UIDs across DICOM objects
+/// which keeps references between them valid.
+///
+/// Each UI element value found in the map of the remapper
+/// is replaced by its mapped UID,
+/// at any nesting level and in every position of multi-valued elements.
+/// Values of the elements with one of the _generate_ tags
+/// are added to the map if not already there,
+/// with a new UID obtained through the [strategy](UidStrategy).
+/// By default, these are
+/// _Study Instance UID_, _Series Instance UID_, _SOP Instance UID_
+/// and _Frame of Reference UID_.
+///
+/// Since the map is shared by all objects remapped,
+/// a reference from one object to another
+/// (such as a _Referenced SOP Instance UID_)
+/// is only replaced if the UID it refers to
+/// was mapped beforehand.
+/// To not depend on the order in which objects are remapped,
+/// include the referencing tags in the generate tags as well.
+///
+/// See the [module-level documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct UidRemapper {
+    /// how to obtain new UIDs
+    strategy: UidStrategy,
+    /// the tags of the elements whose UIDs are added to the map
+    generate_tags: BTreeSet<Tag>,
+    /// the UIDs replaced, from original to new UID
+    map: BTreeMap<String, String>,
+}
+
+impl UidRemapper {
+    /// Create a new UID remapper with the given strategy
+    /// and the default generate tags.
+    ///
+    /// With an [explicit](UidStrategy::Explicit) strategy,
+    /// the remapper starts with the given map.
+    pub fn new(strategy: UidStrategy) -> Self {
+        let map = match &strategy {
+            UidStrategy::Explicit(map) => map.clone(),
+            _ => BTreeMap::new(),
+        };
+        UidRemapper {
+            strategy,
+            generate_tags: BTreeSet::from([
+                tags::STUDY_INSTANCE_UID,
+                tags::SERIES_INSTANCE_UID,
+                tags::SOP_INSTANCE_UID,
+                tags::FRAME_OF_REFERENCE_UID,
+            ]),
+            map: map
+                .into_iter()
+                .map(|(original, new)| (trim_uid(&original).to_string(), new))
+                .collect(),
+        }
+    }
+
+    /// Add a tag to the set of tags
+    /// whose UIDs are added to the map.
+    pub fn generate_tag(mut self, tag: Tag) -> Self {
+        self.generate_tags.insert(tag);
+        self
+    }
+
+    /// Replace the set of tags
+    /// whose UIDs are added to the map.
+    pub fn generate_tags(mut self, tags: impl IntoIterator<Item = Tag>) -> Self {
+        self.generate_tags = tags.into_iter().collect();
+        self
+    }
+
+    /// Retrieve the full UID map,
+    /// from original to new UID.
+    pub fn mapping(&self) -> &BTreeMap<String, String> {
+        &self.map
+    }
+
+    /// Replace the UIDs of the given object.
+    ///
+    /// Returns the mappings added to the map
+    /// while remapping this object,
+    /// from original to new UID.
+    pub fn remap_object<D>(&mut self, obj: &mut InMemDicomObject<D>) -> BTreeMap<String, String>
+    where
+        D: DataDictionary,
+        D: Clone,
+    {
+        let mut paths = Vec::new();
+        let _ = obj.walk(|path, header| {
+            if header.vr == VR::UI {
+                paths.push(path.to_vec());
+            }
+            ControlFlow::Continue(())
+        });
+
+        // add all new mappings first,
+        // so that references to UIDs later in the object are replaced too
+        let mut delta = BTreeMap::new();
+        for path in &paths {
+            let Some(AttributeSelectorStep::Tag(tag)) = path.last() else {
+                continue;
+            };
+            if !self.generate_tags.contains(tag) {
+                continue;
+            }
+            for uid in uids_at(obj, path) {
+                if !uid.is_empty() && !self.map.contains_key(&uid) {
+                    if let Some(new_uid) = self.new_uid(&uid) {
+                        self.map.insert(uid.clone(), new_uid.clone());
+                        delta.insert(uid, new_uid);
+                    }
+                }
+            }
+        }
+
+        for path in paths {
+            let uids = uids_at(obj, &path);
+            if !uids.iter().any(|uid| self.map.contains_key(uid)) {
+                continue;
+            }
+            let new_uids: Vec<String> = uids
+                .into_iter()
+                .map(|uid| self.map.get(&uid).cloned().unwrap_or(uid))
+                .collect();
+            let Some(selector) = AttributeSelector::new(path) else {
+                continue;
+            };
+            let _ = obj.update_value_at(selector, |value| {
+                *value = Value::Primitive(PrimitiveValue::Strs(new_uids.iter().cloned().collect()));
+            });
+        }
+
+        delta
+    }
+
+    /// Replace the UIDs of the given file object,
+    /// including the media storage SOP instance UID in its meta group.
+    ///
+    /// Returns the mappings added to the map
+    /// while remapping this object,
+    /// from original to new UID.
+    pub fn remap_file_object<D>(
+        &mut self,
+        obj: &mut FileDicomObject<InMemDicomObject<D>>,
+    ) -> BTreeMap<String, String>
+    where
+        D: DataDictionary,
+        D: Clone,
+    {
+        let mut delta = self.remap_object(obj);
+
+        let uid = obj.meta().media_storage_sop_instance_uid().to_string();
+        let new_uid = match self.map.get(&uid) {
+            Some(new_uid) => Some(new_uid.clone()),
+            None if self
+                .generate_tags
+                .contains(&tags::MEDIA_STORAGE_SOP_INSTANCE_UID) =>
+            {
+                self.new_uid(&uid).map(|new_uid| {
+                    self.map.insert(uid.clone(), new_uid.clone());
+                    delta.insert(uid, new_uid.clone());
+                    new_uid
+                })
+            }
+            None => None,
+        };
+        if let Some(new_uid) = new_uid {
+            obj.update_meta(|meta| meta.media_storage_sop_instance_uid = new_uid);
+        }
+
+        delta
+    }
+
+    /// Obtain a new UID for the given original UID,
+    /// or `None` if the strategy does not generate UIDs.
+    fn new_uid(&self, uid: &str) -> Option<String> {
+        let value = match &self.strategy {
+            UidStrategy::Random => uuid_v4(random_u128()),
+            UidStrategy::Hashed(salt) => {
+                let mut bytes = salt.as_bytes().to_vec();
+                bytes.push(0);
+                bytes.extend_from_slice(uid.as_bytes());
+                uuid_v8(fnv1a_128(&bytes))
+            }
+            UidStrategy::Explicit(_) => return None,
+        };
+        Some(format!("2.25.{}", value))
+    }
+}
+
+/// Retrieve the UIDs of the element at the given path,
+/// without padding.
+fn uids_at<D>(obj: &InMemDicomObject<D>, path: &[AttributeSelectorStep]) -> Vec<String>
+where
+    D: DataDictionary,
+    D: Clone,
+{
+    let Some(selector) = AttributeSelector::new(path.iter().cloned()) else {
+        return Vec::new();
+    };
+    match obj.value_at(selector) {
+        Ok(Value::Primitive(value)) => value
+            .to_multi_str()
+            .iter()
+            .map(|uid| trim_uid(uid).to_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn trim_uid(uid: &str) -> &str {
+    uid.trim_end_matches(|c: char| c == '\0' || c.is_whitespace())
+}
+
+/// Obtain 128 random bits.
+fn random_u128() -> u128 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+
+    // each new random state is seeded differently
+    let mut half = || {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(count);
+        hasher.write_u64(nanos);
+        hasher.finish()
+    };
+    (u128::from(half()) << 64) | u128::from(half())
+}
+
+/// Set the version and variant bits of a UUID.
+fn uuid_with_version(value: u128, version: u128) -> u128 {
+    let value = (value & !(0xF << 76)) | (version << 76);
+    (value & !(0x3 << 62)) | (0x2 << 62)
+}
+
+/// Turn 128 random bits into a version 4 (random) UUID.
+fn uuid_v4(value: u128) -> u128 {
+    uuid_with_version(value, 4)
+}
+
+/// Turn 128 bits into a version 8 (custom) UUID.
+fn uuid_v8(value: u128) -> u128 {
+    uuid_with_version(value, 8)
+}
+
+/// The 128-bit FNV-1a hash of the given bytes.
+fn fnv1a_128(bytes: &[u8]) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e_07bb0142_62b82175_6295c58d;
+    const PRIME: u128 = 0x00000000_01000000_00000000_0000013b;
+    bytes.iter().fold(OFFSET_BASIS, |hash, b| {
+        (hash ^ u128::from(*b)).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::InMemElement;
+    use crate::FileMetaTableBuilder;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::DataElement;
+    use dicom_dictionary_std::uids;
+
+    /// a CT image of the given series, with a reference to another image
+    fn image(sop_instance_uid: &str, referenced_uid: &str) -> InMemDicomObject {
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SOP_CLASS_UID, VR::UI, uids::CT_IMAGE_STORAGE),
+            DataElement::new(tags::SOP_INSTANCE_UID, VR::UI, sop_instance_uid),
+            DataElement::new(tags::STUDY_INSTANCE_UID, VR::UI, "1.2.3"),
+            DataElement::new(tags::SERIES_INSTANCE_UID, VR::UI, "1.2.3.4\0"),
+            DataElement::new(tags::FRAME_OF_REFERENCE_UID, VR::UI, "1.2.3.9"),
+            DataElement::new(
+                tags::REFERENCED_IMAGE_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(
+                        tags::REFERENCED_SOP_CLASS_UID,
+                        VR::UI,
+                        uids::CT_IMAGE_STORAGE,
+                    ),
+                    DataElement::new(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, referenced_uid),
+                ])]),
+            ),
+            DataElement::new(
+                tags::RELATED_FRAME_OF_REFERENCE_UID,
+                VR::UI,
+                PrimitiveValue::Strs(["1.2.3.9".to_string(), "1.2.3.10".to_string()][..].into()),
+            ),
+        ])
+    }
+
+    fn str_at(obj: &InMemDicomObject, selector: impl Into<AttributeSelector>) -> String {
+        obj.value_at(selector)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn remap_preserves_shared_uids() {
+        let mut first = image("1.2.3.4.1", "1.2.3.4.2");
+        let mut second = image("1.2.3.4.2", "1.2.3.4.1");
+
+        let mut remapper = UidRemapper::new(UidStrategy::Random);
+        let delta = remapper.remap_object(&mut first);
+        // study, series, frame of reference and SOP instance
+        assert_eq!(delta.len(), 4);
+        assert!(delta.values().all(|uid| uid.starts_with("2.25.")));
+        let delta = remapper.remap_object(&mut second);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), vec!["1.2.3.4.2"]);
+        assert_eq!(remapper.mapping().len(), 5);
+
+        for tag in [
+            tags::STUDY_INSTANCE_UID,
+            tags::SERIES_INSTANCE_UID,
+            tags::FRAME_OF_REFERENCE_UID,
+        ] {
+            let uid = str_at(&first, tag);
+            assert!(uid.starts_with("2.25."));
+            assert_eq!(str_at(&second, tag), uid);
+        }
+        assert_ne!(
+            str_at(&first, tags::SOP_INSTANCE_UID),
+            str_at(&second, tags::SOP_INSTANCE_UID)
+        );
+
+        // the reference from the second image to the first one is kept,
+        let referenced = (
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            0,
+            tags::REFERENCED_SOP_INSTANCE_UID,
+        );
+        assert_eq!(
+            str_at(&second, referenced),
+            str_at(&first, tags::SOP_INSTANCE_UID)
+        );
+        // whereas the first image was remapped before the second one was known
+        assert_eq!(str_at(&first, referenced), "1.2.3.4.2");
+
+        // SOP class UIDs are left untouched
+        assert_eq!(str_at(&first, tags::SOP_CLASS_UID), uids::CT_IMAGE_STORAGE);
+
+        // multi-valued elements are remapped element-wise
+        let related = first
+            .get(tags::RELATED_FRAME_OF_REFERENCE_UID)
+            .unwrap()
+            .to_multi_str()
+            .unwrap()
+            .into_owned();
+        assert_eq!(
+            related,
+            vec![
+                str_at(&first, tags::FRAME_OF_REFERENCE_UID),
+                "1.2.3.10".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn remap_references_in_any_order() {
+        let mut first = image("1.2.3.4.1", "1.2.3.4.2");
+        let mut second = image("1.2.3.4.2", "1.2.3.4.1");
+
+        let mut remapper = UidRemapper::new(UidStrategy::Hashed("salt".to_string()))
+            .generate_tag(tags::REFERENCED_SOP_INSTANCE_UID);
+        remapper.remap_object(&mut first);
+        remapper.remap_object(&mut second);
+
+        let referenced = (
+            tags::REFERENCED_IMAGE_SEQUENCE,
+            0,
+            tags::REFERENCED_SOP_INSTANCE_UID,
+        );
+        assert_eq!(
+            str_at(&first, referenced),
+            str_at(&second, tags::SOP_INSTANCE_UID)
+        );
+        assert_eq!(
+            str_at(&second, referenced),
+            str_at(&first, tags::SOP_INSTANCE_UID)
+        );
+
+        // hashed UIDs are reproducible
+        let mut again = image("1.2.3.4.1", "1.2.3.4.2");
+        UidRemapper::new(UidStrategy::Hashed("salt".to_string())).remap_object(&mut again);
+        assert_eq!(
+            str_at(&again, tags::SOP_INSTANCE_UID),
+            str_at(&first, tags::SOP_INSTANCE_UID)
+        );
+        let mut salted = image("1.2.3.4.1", "1.2.3.4.2");
+        UidRemapper::new(UidStrategy::Hashed("pepper".to_string())).remap_object(&mut salted);
+        assert_ne!(
+            str_at(&salted, tags::SOP_INSTANCE_UID),
+            str_at(&first, tags::SOP_INSTANCE_UID)
+        );
+    }
+
+    #[test]
+    fn remap_explicit_and_file_meta() {
+        let map = BTreeMap::from([
+            ("1.2.3.4.1".to_string(), "9.8.7.1".to_string()),
+            ("1.2.3".to_string(), "9.8.7".to_string()),
+        ]);
+        let mut remapper = UidRemapper::new(UidStrategy::Explicit(map));
+
+        let mut obj = image("1.2.3.4.1", "1.2.3.4.2")
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                    .media_storage_sop_class_uid(uids::CT_IMAGE_STORAGE)
+                    .media_storage_sop_instance_uid("1.2.3.4.1"),
+            )
+            .unwrap();
+        let delta = remapper.remap_file_object(&mut obj);
+
+        assert!(delta.is_empty());
+        assert_eq!(obj.meta().media_storage_sop_instance_uid(), "9.8.7.1");
+        assert_eq!(str_at(&obj, tags::SOP_INSTANCE_UID), "9.8.7.1");
+        assert_eq!(str_at(&obj, tags::STUDY_INSTANCE_UID), "9.8.7");
+        // not in the map
+        assert_eq!(str_at(&obj, tags::SERIES_INSTANCE_UID), "1.2.3.4");
+
+        // the element length follows the new value
+        let elem: &InMemElement = obj.get(tags::STUDY_INSTANCE_UID).unwrap();
+        assert_eq!(elem.header().len.0, 6);
+    }
+}
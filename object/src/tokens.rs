@@ -1,5 +1,5 @@
 //! Conversion of DICOM objects into tokens.
-use crate::mem::InMemDicomObject;
+use crate::mem::{Elements, InMemDicomObject};
 use dicom_core::DataElement;
 use dicom_parser::dataset::{DataToken, IntoTokens, IntoTokensOptions};
 use std::collections::VecDeque;
@@ -95,7 +95,8 @@ impl<D> IntoTokens for InMemDicomObject<D> {
     fn into_tokens_with_options(self, mut options: IntoTokensOptions) -> Self::Iter {
         //This is required for recursing with the correct option
         options.force_invalidate_sq_length |= self.charset_changed;
-        InMemObjectTokens::new_with_options(self, options)
+        let preserve_order = options.preserve_element_order;
+        InMemObjectTokens::new_with_options(self.into_elements(preserve_order), options)
     }
 }
 
@@ -103,8 +104,7 @@ impl<'a, D> IntoTokens for &'a InMemDicomObject<D>
 where
     D: Clone,
 {
-    type Iter =
-        InMemObjectTokens<std::iter::Cloned<<&'a InMemDicomObject<D> as IntoIterator>::IntoIter>>;
+    type Iter = InMemObjectTokens<std::iter::Cloned<Elements<'a, D>>>;
 
     fn into_tokens(self) -> Self::Iter {
         self.into_tokens_with_options(Default::default())
@@ -113,6 +113,7 @@ where
     fn into_tokens_with_options(self, mut options: IntoTokensOptions) -> Self::Iter {
         options.force_invalidate_sq_length |= self.charset_changed;
 
-        InMemObjectTokens::new_with_options(self.into_iter().cloned(), options)
+        let preserve_order = options.preserve_element_order;
+        InMemObjectTokens::new_with_options(self.elements(preserve_order).cloned(), options)
     }
 }
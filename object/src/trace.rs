@@ -0,0 +1,331 @@
+//! Opt-in tracing of data set parsing,
+//! for diagnosing files which fail to parse.
+//!
+//! When enabled through [`OpenFileOptions::debug_trace`],
+//! the headers of the last data elements parsed
+//! are kept along with their byte offsets.
+//! If reading the data set fails,
+//! the error returned carries a [`ParseTrace`]
+//! with these headers
+//! and the raw bytes surrounding the point of failure,
+//! which can be retrieved with [`ReadError::parse_trace`].
+//!
+//! All offsets are in bytes from the start of the data set,
+//! which immediately follows the file meta group.
+//!
+//! ```no_run
+//! # use dicom_object::OpenFileOptions;
+//! match OpenFileOptions::new().debug_trace(16).open_file("corrupt.dcm") {
+//!     Ok(obj) => { /* ... */ }
+//!     Err(e) => {
+//!         eprintln!("{}", e);
+//!         if let Some(trace) = e.parse_trace() {
+//!             eprintln!("{}", trace);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! [`OpenFileOptions::debug_trace`]: crate::OpenFileOptions::debug_trace
+//! [`ReadError::parse_trace`]: crate::ReadError::parse_trace
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::Read;
+use std::rc::Rc;
+
+use dicom_core::{Length, Tag, VR};
+use dicom_encoding::text::SpecificCharacterSet;
+use dicom_encoding::TransferSyntax;
+use dicom_parser::dataset::read::DataSetReaderOptions;
+use dicom_parser::dataset::{DataSetReader, DataToken, Result as ParserResult};
+use snafu::{IntoError, ResultExt};
+
+use crate::{CreateParserSnafu, ReadError, TracedParseSnafu};
+
+/// The number of bytes kept on each side of the point of failure.
+const BYTES_AROUND: usize = 32;
+
+/// The header of a data element parsed while tracing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TracedHeader {
+    /// the offset of the header
+    pub offset: u64,
+    /// the data element tag
+    pub tag: Tag,
+    /// the value representation
+    pub vr: VR,
+    /// the declared value length
+    pub len: Length,
+}
+
+/// A record of the last steps in parsing a data set before it failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseTrace {
+    /// the headers of the last data elements parsed, in order
+    headers: Vec<TracedHeader>,
+    /// the offset of the token being read when parsing failed
+    offset: u64,
+    /// the offset of the first byte in `bytes`
+    bytes_offset: u64,
+    /// the bytes surrounding `offset`
+    bytes: Vec<u8>,
+}
+
+impl ParseTrace {
+    /// The headers of the last data elements parsed, in order.
+    ///
+    /// This includes the headers of sequences and of encapsulated pixel data.
+    pub fn headers(&self) -> &[TracedHeader] {
+        &self.headers
+    }
+
+    /// The offset at which the token being read when parsing failed starts.
+    ///
+    /// This is either the header or the value of a data element.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The raw bytes surrounding the point of failure,
+    /// up to 32 bytes before [`offset`](Self::offset) and 32 bytes from it.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The offset of the first byte in [`bytes`](Self::bytes).
+    pub fn bytes_offset(&self) -> u64 {
+        self.bytes_offset
+    }
+
+    /// Format the bytes surrounding the point of failure
+    /// as a hexadecimal dump of 16 bytes per line,
+    /// each line starting with the offset of its first byte.
+    pub fn hexdump(&self) -> String {
+        let mut out = String::new();
+        for (i, line) in self.bytes.chunks(16).enumerate() {
+            let offset = self.bytes_offset + (i * 16) as u64;
+            let hex: Vec<_> = line.iter().map(|b| format!("{:02x}", b)).collect();
+            let text: String = line
+                .iter()
+                .map(|b| {
+                    if b.is_ascii_graphic() || *b == b' ' {
+                        *b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            out.push_str(&format!(
+                "{:08x}  {:<47}  |{}|\n",
+                offset,
+                hex.join(" "),
+                text
+            ));
+        }
+        out
+    }
+}
+
+impl fmt::Display for ParseTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Last data element headers parsed:")?;
+        for header in &self.headers {
+            writeln!(
+                f,
+                "  {:08x}  {} {} {}",
+                header.offset, header.tag, header.vr, header.len
+            )?;
+        }
+        writeln!(f, "Bytes around offset {:08x}:", self.offset)?;
+        f.write_str(&self.hexdump())
+    }
+}
+
+/// The shared state of a traced data set parser.
+struct TraceState<R> {
+    /// the byte source of the data set
+    source: R,
+    /// the number of bytes read from the source
+    position: u64,
+    /// the last bytes read, up to `BYTES_AROUND`
+    recent: VecDeque<u8>,
+    /// the offset where the current token starts
+    mark: u64,
+    /// the last bytes read before `mark`
+    before: Vec<u8>,
+    /// the first bytes read after `mark`, up to `BYTES_AROUND`
+    after: Vec<u8>,
+    /// the headers of the last data elements parsed
+    headers: VecDeque<TracedHeader>,
+    /// the maximum number of headers to keep
+    window: usize,
+}
+
+impl<R> TraceState<R>
+where
+    R: Read,
+{
+    fn record(&mut self, data: &[u8]) {
+        self.position += data.len() as u64;
+
+        let room = BYTES_AROUND - self.after.len();
+        self.after.extend(data.iter().take(room));
+
+        let keep = data.len().min(BYTES_AROUND);
+        let excess = (self.recent.len() + keep).saturating_sub(BYTES_AROUND);
+        self.recent.drain(..excess);
+        self.recent.extend(&data[data.len() - keep..]);
+    }
+
+    /// Mark the start of a new token at the current position.
+    fn mark(&mut self) -> u64 {
+        self.mark = self.position;
+        self.before.clear();
+        self.before.extend(&self.recent);
+        self.after.clear();
+        self.mark
+    }
+
+    fn push_header(&mut self, header: TracedHeader) {
+        if self.window == 0 {
+            return;
+        }
+        if self.headers.len() == self.window {
+            self.headers.pop_front();
+        }
+        self.headers.push_back(header);
+    }
+
+    /// Collect the trace of the parsing process,
+    /// reading more bytes from the source
+    /// to fill in the bytes after the point of failure.
+    fn finish(&mut self) -> ParseTrace {
+        let missing = BYTES_AROUND - self.after.len();
+        let mut rest = Vec::with_capacity(missing);
+        // the source may well be broken at this point
+        let _ = (&mut self.source)
+            .take(missing as u64)
+            .read_to_end(&mut rest);
+        self.after.extend(rest);
+
+        let bytes_offset = self.mark - self.before.len() as u64;
+        let mut bytes = std::mem::take(&mut self.before);
+        bytes.append(&mut self.after);
+        ParseTrace {
+            headers: self.headers.iter().copied().collect(),
+            offset: self.mark,
+            bytes_offset,
+            bytes,
+        }
+    }
+}
+
+/// A byte source which records the bytes read into the trace state.
+struct TraceReader<R> {
+    state: Rc<RefCell<TraceState<R>>>,
+}
+
+impl<R> Read for TraceReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut state = self.state.borrow_mut();
+        let n = state.source.read(buf)?;
+        state.record(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// A token iterator which records the headers of the tokens read.
+struct TracedTokens<I, R> {
+    tokens: I,
+    state: Rc<RefCell<TraceState<R>>>,
+}
+
+impl<I, R> Iterator for TracedTokens<I, R>
+where
+    I: Iterator<Item = ParserResult<DataToken>>,
+    R: Read,
+{
+    type Item = ParserResult<DataToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.state.borrow_mut().mark();
+        let token = self.tokens.next()?;
+        let header = match &token {
+            Ok(DataToken::ElementHeader(header)) => Some(TracedHeader {
+                offset,
+                tag: header.tag,
+                vr: header.vr,
+                len: header.len,
+            }),
+            Ok(DataToken::SequenceStart { tag, len }) => Some(TracedHeader {
+                offset,
+                tag: *tag,
+                vr: VR::SQ,
+                len: *len,
+            }),
+            Ok(DataToken::PixelSequenceStart) => Some(TracedHeader {
+                offset,
+                tag: Tag(0x7FE0, 0x0010),
+                vr: VR::OB,
+                len: Length::UNDEFINED,
+            }),
+            _ => None,
+        };
+        if let Some(header) = header {
+            self.state.borrow_mut().push_header(header);
+        }
+        Some(token)
+    }
+}
+
+/// Read a data set from the given byte source with tracing,
+/// keeping up to `window` data element headers.
+///
+/// The tokens of the data set parser are consumed by `read`.
+/// If reading fails,
+/// the error is wrapped together with the trace of the parsing process.
+pub(crate) fn read_traced<S, T>(
+    source: S,
+    ts: &TransferSyntax,
+    options: DataSetReaderOptions,
+    window: usize,
+    read: impl FnOnce(&mut dyn Iterator<Item = ParserResult<DataToken>>) -> Result<T, ReadError>,
+) -> Result<T, ReadError>
+where
+    S: Read,
+{
+    let state = Rc::new(RefCell::new(TraceState {
+        source,
+        position: 0,
+        recent: VecDeque::with_capacity(BYTES_AROUND),
+        mark: 0,
+        before: Vec::with_capacity(BYTES_AROUND),
+        after: Vec::with_capacity(BYTES_AROUND),
+        headers: VecDeque::with_capacity(window),
+        window,
+    }));
+
+    let reader = TraceReader {
+        state: Rc::clone(&state),
+    };
+    let result =
+        DataSetReader::new_with_ts_cs_options(reader, ts, SpecificCharacterSet::default(), options)
+            .context(CreateParserSnafu)
+            .and_then(|tokens| {
+                read(&mut TracedTokens {
+                    tokens,
+                    state: Rc::clone(&state),
+                })
+            });
+    result.map_err(|e| {
+        let trace = state.borrow_mut().finish();
+        TracedParseSnafu {
+            trace: Box::new(trace),
+        }
+        .into_error(e)
+    })
+}
@@ -9,7 +9,7 @@ use dicom_encoding::text::SpecificCharacterSet;
 use dicom_object::{
     file::{OpenFileOptions, ReadPreamble},
     mem::InMemDicomObject,
-    open_file,
+    open_file, DefaultDicomObject,
 };
 #[test]
 fn test_ob_value_with_unknown_length() {
@@ -153,3 +153,33 @@ fn test_expl_vr_le_no_meta() {
         "1.2.333.4444.5.6.7.8.9",
     );
 }
+
+#[test]
+fn test_from_bytes() {
+    let path =
+        dicom_test_files::path("pydicom/CT_small.dcm").expect("test DICOM file should exist");
+    let bytes = std::fs::read(&path).unwrap();
+    let object = OpenFileOptions::new()
+        .from_bytes(&bytes)
+        .expect("Should read object from bytes");
+
+    let element = object.element(tags::PIXEL_DATA).unwrap();
+    assert_eq!(element.value().to_bytes().unwrap().len(), 128 * 128 * 2);
+    let element = object.element(tags::MODALITY).unwrap();
+    assert_eq!(element.value().to_str().unwrap(), "CT");
+
+    // the result is the same as reading from a byte source
+    let expected = open_file(&path).unwrap();
+    assert_eq!(object, expected);
+}
+
+#[test]
+fn test_from_bytes_encapsulated_pixel_data() {
+    let path =
+        dicom_test_files::path("pydicom/JPEG2000.dcm").expect("test DICOM file should exist");
+    let bytes = std::fs::read(&path).unwrap();
+    let object = DefaultDicomObject::from_bytes(&bytes).expect("Should read object from bytes");
+
+    let expected = open_file(&path).unwrap();
+    assert_eq!(object, expected);
+}
@@ -46,6 +46,10 @@
 //!
 //! [ts]: dicom_encoding::transfer_syntax
 //!
+//! The [`prelude`] module gathers the types and traits
+//! most commonly needed in these workflows,
+//! so that they can be imported at once.
+//!
 //! ## Advanced
 //!
 //! - To write DICOM network application entity software,
@@ -59,6 +63,17 @@
 //!   It might only be truly needed if
 //!   the `object` API is unfit or too inefficient for a certain task.
 //!
+//! ## Cargo features
+//!
+//! Most optional features of the member crates
+//! can be enabled through features of the same name in this crate,
+//! such as `image` and `ndarray` for pixel data conversion,
+//! `jpegxl`, `openjp2`, `openjpeg-sys`, `charls` and `gdcm`
+//! for additional pixel data codecs,
+//! `deflate` for the deflated transfer syntax,
+//! `arrow` for conversion into Arrow record batches,
+//! and `async`, `tls` and `async-tls` for network associations.
+//!
 //! ## More
 //!
 //! See the [DICOM-rs project repository][2]
@@ -79,3 +94,38 @@ pub use dicom_ul as ul;
 
 // re-export dicom_value macro
 pub use dicom_core::dicom_value;
+
+/// Prelude module.
+///
+/// You may import all symbols within for convenient usage of this library,
+/// including the traits for reading, manipulating,
+/// decoding and transcoding DICOM objects.
+///
+/// # Example
+///
+/// ```no_run
+/// use dicom::prelude::*;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut obj = open_file("00000001.dcm")?;
+/// obj.apply(AttributeOp::new(
+///     tags::PATIENT_NAME,
+///     AttributeAction::SetStr("Doe^John".into()),
+/// ))?;
+/// # Ok(())
+/// # }
+/// ```
+pub mod prelude {
+    pub use dicom_core::ops::{ApplyOp, AttributeAction, AttributeOp};
+    pub use dicom_core::prelude::*;
+    pub use dicom_core::PrimitiveValue;
+    pub use dicom_dictionary_std::{tags, uids};
+    pub use dicom_object::{
+        open_file, DefaultDicomObject, DicomObject, FileDicomObject, InMemDicomObject,
+        OpenFileOptions,
+    };
+    #[cfg(feature = "pixeldata")]
+    pub use dicom_pixeldata::{ConvertOptions, DecodeOptions, PixelDecoder, Transcode};
+    #[cfg(feature = "ul")]
+    pub use dicom_ul::{ClientAssociationOptions, ServerAssociationOptions};
+}
@@ -0,0 +1,74 @@
+//! Check that the main workflows are reachable
+//! through the paths of the `dicom` crate alone,
+//! and that the re-exported crates agree on the types they share.
+use dicom::prelude::*;
+
+/// Build a small object using only the facade paths.
+fn sample_object() -> DefaultDicomObject {
+    let mut obj = InMemDicomObject::new_empty();
+    obj.put(DataElement::new(
+        tags::SOP_INSTANCE_UID,
+        VR::UI,
+        PrimitiveValue::from("1.2.3.4"),
+    ));
+    obj.put(DataElement::new(
+        tags::SOP_CLASS_UID,
+        VR::UI,
+        PrimitiveValue::from(uids::SECONDARY_CAPTURE_IMAGE_STORAGE),
+    ));
+    obj.with_meta(
+        dicom::object::FileMetaTableBuilder::new()
+            .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+            .media_storage_sop_class_uid(uids::SECONDARY_CAPTURE_IMAGE_STORAGE)
+            .media_storage_sop_instance_uid("1.2.3.4"),
+    )
+    .unwrap()
+}
+
+#[test]
+fn facade_object_workflow() {
+    let mut obj = sample_object();
+    obj.apply(AttributeOp::new(
+        tags::PATIENT_NAME,
+        AttributeAction::SetStr("Doe^John".into()),
+    ))
+    .unwrap();
+    assert_eq!(
+        obj.element(tags::PATIENT_NAME).unwrap().to_str().unwrap(),
+        "Doe^John"
+    );
+
+    // the tag types of the member crates are the same type
+    let tag: dicom::core::Tag = dicom::object::Tag(0x0010, 0x0010);
+    assert_eq!(tag, tags::PATIENT_NAME);
+
+    // the transfer syntax registry and the object agree on the UID
+    let ts = dicom::transfer_syntax::entries::EXPLICIT_VR_LITTLE_ENDIAN.erased();
+    assert_eq!(ts.uid(), obj.meta().transfer_syntax());
+}
+
+#[cfg(feature = "pixeldata")]
+#[test]
+fn facade_pixeldata_traits() {
+    fn assert_pixel_traits<T: PixelDecoder + Transcode>() {}
+    assert_pixel_traits::<DefaultDicomObject>();
+
+    let _options: DecodeOptions = DecodeOptions::new();
+    let _options: ConvertOptions = ConvertOptions::new();
+
+    // the pixel data crate works on the facade's object types
+    let obj = sample_object();
+    assert!(obj.decode_pixel_data().is_err());
+}
+
+#[cfg(feature = "ul")]
+#[test]
+fn facade_ul_options() {
+    let _scu: ClientAssociationOptions<'static> = ClientAssociationOptions::new()
+        .calling_ae_title("FACADE-SCU")
+        .with_abstract_syntax(uids::VERIFICATION);
+    let _scp = ServerAssociationOptions::new()
+        .accept_any()
+        .with_abstract_syntax(uids::VERIFICATION);
+    let _pdu: Option<dicom::ul::Pdu> = None;
+}
@@ -111,6 +111,9 @@ pub struct LazyDataSetReader<S> {
     hard_break: bool,
     /// last decoded header
     last_header: Option<DataElementHeader>,
+    /// whether the item headers of the current pixel sequence
+    /// are encoded in the byte order opposite to the transfer syntax
+    pixel_data_byte_swapped: bool,
 }
 
 impl<R> LazyDataSetReader<DynStatefulDecoder<R>> {
@@ -136,6 +139,7 @@ impl<R> LazyDataSetReader<DynStatefulDecoder<R>> {
             in_sequence: false,
             hard_break: false,
             last_header: None,
+            pixel_data_byte_swapped: false,
         })
     }
 }
@@ -153,10 +157,26 @@ where
             in_sequence: false,
             hard_break: false,
             last_header: None,
+            pixel_data_byte_swapped: false,
         }
     }
 }
 
+impl<S> LazyDataSetReader<S> {
+    /// Check whether the item headers of the encapsulated pixel data
+    /// last entered by this reader
+    /// are encoded in the byte order opposite to the transfer syntax.
+    ///
+    /// This is not conformant to the standard,
+    /// but such data can still be read.
+    /// In that case,
+    /// the values of the basic offset table
+    /// are expected to be in the same byte order as the item headers.
+    pub fn pixel_data_byte_swapped(&self) -> bool {
+        self.pixel_data_byte_swapped
+    }
+}
+
 impl<S> LazyDataSetReader<S>
 where
     S: StatefulDecode,
@@ -240,7 +260,14 @@ where
         if self.in_sequence {
             // at sequence level, expecting item header
 
-            match self.parser.decode_item_header() {
+            let header = if self.seq_delimiters.last().is_some_and(|t| t.pixel_data) {
+                self.parser
+                    .decode_pixel_item_header()
+                    .map(|(header, _)| header)
+            } else {
+                self.parser.decode_item_header()
+            };
+            match header {
                 Ok(header) => {
                     match header {
                         SequenceItemHeader::Item { len } => {
@@ -310,9 +337,17 @@ where
                 self.last_header = None;
 
                 // encapsulated pixel data, expecting offset table
-                match self.parser.decode_item_header() {
-                    Ok(header) => match header {
+                match self.parser.decode_pixel_item_header() {
+                    Ok((header, byte_swapped)) => match header {
                         SequenceItemHeader::Item { len } => {
+                            self.pixel_data_byte_swapped = byte_swapped;
+                            if byte_swapped {
+                                tracing::warn!(
+                                    "Encapsulated pixel data at position {} has item headers in the wrong byte order",
+                                    bytes_read,
+                                );
+                            }
+
                             // entered a new item
                             self.in_sequence = false;
                             self.push_sequence_token(SeqTokenType::Item, len, true);
@@ -1,7 +1,7 @@
 //! Interpretation of DICOM data sets as streams of tokens.
 use crate::stateful::decode;
 use dicom_core::header::{DataElementHeader, HasLength, Length, VR};
-use dicom_core::value::{DicomValueType, PrimitiveValue};
+use dicom_core::value::{check_offset_table, DicomValueType, PrimitiveValue};
 use dicom_core::{value::Value, DataElement, Tag};
 use snafu::{OptionExt, ResultExt, Snafu};
 use std::default::Default;
@@ -417,6 +417,33 @@ pub enum SeqTokenType {
     Item,
 }
 
+/// The convention for encoding the lengths
+/// of data set sequences and their items.
+///
+/// This does not apply to encapsulated pixel data,
+/// which is always encoded with an undefined length
+/// as mandated by the standard.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum SequenceLengthStrategy {
+    /// Keep the lengths of sequences and items as they are,
+    /// whether defined or undefined.
+    ///
+    /// This is the default strategy.
+    #[default]
+    PreserveOriginal,
+    /// Give all sequences and items a defined length,
+    /// without sequence or item delimiters.
+    ///
+    /// Since lengths depend on how the nested content is encoded,
+    /// token generation leaves them undefined,
+    /// and the [`DataSetWriter`] calculates them from the inside out.
+    AllDefined,
+    /// Give all sequences and items an undefined length,
+    /// closing each one with a sequence or item delimiter.
+    AllUndefined,
+}
+
 /// Options for token generation
 #[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
 #[non_exhaustive]
@@ -432,14 +459,46 @@ pub struct IntoTokensOptions {
     /// is left at the implementation's discretion.
     /// either be recalculated or marked as undefined.
     pub force_invalidate_sq_length: bool,
+    /// The convention for the lengths of sequences and items.
+    ///
+    /// Any strategy other than [`PreserveOriginal`](SequenceLengthStrategy::PreserveOriginal)
+    /// makes token generation produce sequences and items of undefined length.
+    pub sequence_lengths: SequenceLengthStrategy,
+    /// Whether to produce the elements of each data set
+    /// in the order in which they were originally read or inserted,
+    /// if the data set keeps a record of it,
+    /// instead of ascending tag order.
+    pub preserve_element_order: bool,
 }
 
 impl IntoTokensOptions {
     pub fn new(force_invalidate_sq_length: bool) -> Self {
         IntoTokensOptions {
             force_invalidate_sq_length,
+            sequence_lengths: SequenceLengthStrategy::default(),
+            preserve_element_order: false,
         }
     }
+
+    /// Replace the strategy for the lengths of sequences and items.
+    pub fn sequence_lengths(mut self, sequence_lengths: SequenceLengthStrategy) -> Self {
+        self.sequence_lengths = sequence_lengths;
+        self
+    }
+
+    /// Set whether to produce elements in their original order,
+    /// if recorded.
+    pub fn preserve_element_order(mut self, preserve: bool) -> Self {
+        self.preserve_element_order = preserve;
+        self
+    }
+
+    /// Whether the lengths of data set sequences and items
+    /// should be replaced with undefined lengths.
+    fn undefined_sq_length(&self) -> bool {
+        self.force_invalidate_sq_length
+            || self.sequence_lengths != SequenceLengthStrategy::PreserveOriginal
+    }
 }
 
 /// A trait for converting structured DICOM data into a stream of data tokens.
@@ -526,7 +585,7 @@ where
                 // data element header token
 
                 let mut header = *elem.header();
-                if options.force_invalidate_sq_length && elem.vr() == VR::SQ {
+                if options.undefined_sq_length() && elem.vr() == VR::SQ {
                     header.len = Length::UNDEFINED;
                 }
 
@@ -563,7 +622,7 @@ where
                                 (None, DataElementTokens::End)
                             },
                             Value::Sequence(seq) => {
-                                let seq = if options.force_invalidate_sq_length {
+                                let seq = if options.undefined_sq_length() {
                                     seq.into_items().into_vec().into()
                                 } else {
                                     seq
@@ -586,7 +645,19 @@ where
                     DataToken::PixelSequenceStart => {
                         match elem.into_value() {
                             Value::PixelSequence(seq) => {
-                                let (offset_table, fragments) = seq.into_parts();
+                                // never write an offset table
+                                // which does not match the fragments
+                                let consistent = match check_offset_table(&seq) {
+                                    Ok(()) => true,
+                                    Err(e) => {
+                                        tracing::warn!("Basic offset table does not match the pixel data fragments ({}), writing an empty table", e);
+                                        false
+                                    }
+                                };
+                                let (mut offset_table, fragments) = seq.into_parts();
+                                if !consistent {
+                                    offset_table.clear();
+                                }
                                 (
                                     // begin pixel sequence
                                     Some(DataToken::PixelSequenceStart),
@@ -776,10 +847,16 @@ where
     where
         O: IntoTokens<Iter = T>,
     {
-        let len = if len.0 != 0 && options.force_invalidate_sq_length {
-            Length::UNDEFINED
-        } else {
-            len
+        let len = match options.sequence_lengths {
+            SequenceLengthStrategy::PreserveOriginal
+                if len.0 != 0 && options.force_invalidate_sq_length =>
+            {
+                Length::UNDEFINED
+            }
+            SequenceLengthStrategy::PreserveOriginal => len,
+            SequenceLengthStrategy::AllDefined | SequenceLengthStrategy::AllUndefined => {
+                Length::UNDEFINED
+            }
         };
         ItemTokens::Start {
             len,
@@ -1335,4 +1412,31 @@ mod tests {
         // other than that there are no guarantees about the output
         let _ = e.into_tokens().collect::<Vec<_>>();
     }
+
+    /// A basic offset table inconsistent with the fragments
+    /// is converted to an empty offset table
+    #[test]
+    fn pixel_sequence_with_stale_offset_table_into_tokens() {
+        let e: DataElement = DataElement::new_with_len(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            Length::UNDEFINED,
+            // second offset points to the middle of the first fragment
+            PixelFragmentSequence::new(vec![0, 16], vec![vec![0x55; 16], vec![0x66; 4]]),
+        );
+        let tokens = e.into_tokens().collect::<Vec<_>>();
+        assert_eq!(tokens[0], DataToken::PixelSequenceStart);
+        assert_eq!(tokens[1], DataToken::ItemStart { len: Length(0) });
+        assert_eq!(tokens[2], DataToken::ItemEnd);
+
+        let e: DataElement = DataElement::new_with_len(
+            Tag(0x7FE0, 0x0010),
+            VR::OB,
+            Length::UNDEFINED,
+            PixelFragmentSequence::new(vec![0, 24], vec![vec![0x55; 16], vec![0x66; 4]]),
+        );
+        let tokens = e.into_tokens().collect::<Vec<_>>();
+        assert_eq!(tokens[1], DataToken::ItemStart { len: Length(8) });
+        assert_eq!(tokens[2], DataToken::OffsetTable(vec![0, 24]));
+    }
 }
@@ -189,6 +189,9 @@ pub struct DataSetReader<S> {
     /// whether the reader is expecting the first item value of a pixel sequence next
     /// (offset table)
     offset_table_next: bool,
+    /// whether the item headers of the current pixel sequence
+    /// are encoded in the byte order opposite to the transfer syntax
+    pixel_data_byte_swapped: bool,
     /// whether a check for a sequence or item delimitation is pending
     delimiter_check_pending: bool,
     /// a stack of delimiters
@@ -260,6 +263,7 @@ impl<R> DataSetReader<DynStatefulDecoder<R>> {
             seq_delimiters: Vec::new(),
             delimiter_check_pending: false,
             offset_table_next: false,
+            pixel_data_byte_swapped: false,
             in_sequence: false,
             hard_break: false,
             last_header: None,
@@ -277,6 +281,7 @@ impl<S> DataSetReader<S> {
             seq_delimiters: Vec::new(),
             delimiter_check_pending: false,
             offset_table_next: false,
+            pixel_data_byte_swapped: false,
             in_sequence: false,
             hard_break: false,
             last_header: None,
@@ -315,7 +320,14 @@ where
         if self.in_sequence {
             // at sequence level, expecting item header
 
-            match self.parser.decode_item_header() {
+            let header = if self.seq_delimiters.last().is_some_and(|t| t.pixel_data) {
+                self.parser
+                    .decode_pixel_item_header()
+                    .map(|(header, _)| header)
+            } else {
+                self.parser.decode_item_header()
+            };
+            match header {
                 Ok(header) => {
                     match header {
                         SequenceItemHeader::Item { len } => {
@@ -414,7 +426,15 @@ where
 
                 Some(
                     match self.parser.read_u32_to_vec(len as u32, &mut offset_table) {
-                        Ok(()) => Ok(DataToken::OffsetTable(offset_table)),
+                        Ok(()) => {
+                            if self.pixel_data_byte_swapped {
+                                // offsets are in the same byte order as the item headers
+                                for offset in &mut offset_table {
+                                    *offset = offset.swap_bytes();
+                                }
+                            }
+                            Ok(DataToken::OffsetTable(offset_table))
+                        }
                         Err(e) => Err(e).context(ReadItemValueSnafu { len: len as u32 }),
                     },
                 )
@@ -437,8 +457,8 @@ where
                 self.last_header = None;
 
                 // encapsulated pixel data, expecting offset table
-                match self.parser.decode_item_header() {
-                    Ok(header) => match header {
+                match self.parser.decode_pixel_item_header() {
+                    Ok((header, byte_swapped)) => match header {
                         SequenceItemHeader::Item { len } => {
                             let len = match self.sanitize_length(len) {
                                 Some(len) => len,
@@ -453,6 +473,14 @@ where
                                 }
                             };
 
+                            self.pixel_data_byte_swapped = byte_swapped;
+                            if byte_swapped {
+                                tracing::warn!(
+                                    "Encapsulated pixel data at position {} has item headers in the wrong byte order",
+                                    self.parser.position() - 8,
+                                );
+                            }
+
                             // entered a new item
                             self.in_sequence = false;
                             self.push_sequence_token(SeqTokenType::Item, len, true);
@@ -718,9 +746,10 @@ mod tests {
     use dicom_core::header::{DataElementHeader, Length};
     use dicom_core::value::PrimitiveValue;
     use dicom_core::{Tag, VR};
-    use dicom_encoding::decode::basic::LittleEndianBasicDecoder;
+    use dicom_encoding::decode::basic::{BigEndianBasicDecoder, LittleEndianBasicDecoder};
     use dicom_encoding::decode::{
-        explicit_le::ExplicitVRLittleEndianDecoder, implicit_le::ImplicitVRLittleEndianDecoder,
+        explicit_be::ExplicitVRBigEndianDecoder, explicit_le::ExplicitVRLittleEndianDecoder,
+        implicit_le::ImplicitVRLittleEndianDecoder,
     };
     use dicom_encoding::text::SpecificCharacterSet;
 
@@ -754,6 +783,21 @@ mod tests {
         validate_read_data(&data, parser, ground_truth)
     }
 
+    fn validate_read_data_explicit_vr_be<I>(data: &[u8], ground_truth: I)
+    where
+        I: IntoIterator<Item = DataToken>,
+    {
+        let mut cursor = data;
+        let parser = StatefulDecoder::new(
+            &mut cursor,
+            ExplicitVRBigEndianDecoder::default(),
+            BigEndianBasicDecoder,
+            SpecificCharacterSet::default(),
+        );
+
+        validate_read_data(data, parser, ground_truth)
+    }
+
     fn validate_read_data<I, D>(data: &[u8], parser: D, ground_truth: I)
     where
         I: IntoIterator<Item = DataToken>,
@@ -1193,6 +1237,102 @@ mod tests {
         validate_read_data_explicit_vr(DATA, ground_truth);
     }
 
+    /// Ground truth of the encapsulated pixel data
+    /// in the big endian data sets below
+    fn big_endian_pixeldata_ground_truth() -> Vec<DataToken> {
+        vec![
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(8) },
+            DataToken::OffsetTable(vec![0, 16]),
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(8) },
+            DataToken::ItemValue(vec![0x99; 8]),
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(4) },
+            DataToken::ItemValue(vec![0x88; 4]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0xfffc, 0xfffc),
+                VR::OB,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U8([0x00; 2].as_ref().into())),
+        ]
+    }
+
+    #[test]
+    fn read_encapsulated_pixeldata_big_endian() {
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x7f, 0xe0, 0x00, 0x10, // (7FE0, 0010) PixelData
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 -- Basic offset table
+            0xff, 0xfe, 0xe0, 0x00, // item start tag
+            0x00, 0x00, 0x00, 0x08, // item length: 8
+            0x00, 0x00, 0x00, 0x00, // 0
+            0x00, 0x00, 0x00, 0x10, // 16
+            // -- 28 -- First fragment
+            0xff, 0xfe, 0xe0, 0x00, // item start tag
+            0x00, 0x00, 0x00, 0x08, // item length: 8
+            0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x99,
+            // -- 44 -- Second fragment
+            0xff, 0xfe, 0xe0, 0x00, // item start tag
+            0x00, 0x00, 0x00, 0x04, // item length: 4
+            0x88, 0x88, 0x88, 0x88,
+            // -- 56 -- End of pixel data
+            0xff, 0xfe, 0xe0, 0xdd, // sequence end tag
+            0x00, 0x00, 0x00, 0x00,
+            // -- 64 -- padding
+            0xff, 0xfc, 0xff, 0xfc, // (fffc,fffc) DataSetTrailingPadding
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0x00, 0x00, 0x00, 0x02, // length: 2
+            0x00, 0x00,
+        ];
+
+        validate_read_data_explicit_vr_be(DATA, big_endian_pixeldata_ground_truth());
+    }
+
+    #[test]
+    fn read_encapsulated_pixeldata_big_endian_with_little_endian_items() {
+        // non-conformant: item headers and offset table
+        // are in little endian, but the rest is in big endian
+        #[rustfmt::skip]
+        static DATA: &[u8] = &[
+            0x7f, 0xe0, 0x00, 0x10, // (7FE0, 0010) PixelData
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 -- Basic offset table
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0x08, 0x00, 0x00, 0x00, // item length: 8
+            0x00, 0x00, 0x00, 0x00, // 0
+            0x10, 0x00, 0x00, 0x00, // 16
+            // -- 28 -- First fragment
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0x08, 0x00, 0x00, 0x00, // item length: 8
+            0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x99,
+            // -- 44 -- Second fragment
+            0xfe, 0xff, 0x00, 0xe0, // item start tag
+            0x04, 0x00, 0x00, 0x00, // item length: 4
+            0x88, 0x88, 0x88, 0x88,
+            // -- 56 -- End of pixel data
+            0xfe, 0xff, 0xdd, 0xe0, // sequence end tag
+            0x00, 0x00, 0x00, 0x00,
+            // -- 64 -- padding
+            0xff, 0xfc, 0xff, 0xfc, // (fffc,fffc) DataSetTrailingPadding
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0x00, 0x00, 0x00, 0x02, // length: 2
+            0x00, 0x00,
+        ];
+
+        validate_read_data_explicit_vr_be(DATA, big_endian_pixeldata_ground_truth());
+    }
+
     #[test]
     fn read_dataset_in_dataset() {
         #[rustfmt::skip]
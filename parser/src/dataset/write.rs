@@ -6,9 +6,9 @@
 //! to a writer.
 //! In this process, the writer will also adapt values
 //! to the necessary DICOM encoding rules.
-use crate::dataset::{DataToken, SeqTokenType};
-use crate::stateful::encode::StatefulEncoder;
-use dicom_core::{DataElementHeader, Length, Tag, VR};
+use crate::dataset::{DataToken, SeqTokenType, SequenceLengthStrategy};
+use crate::stateful::encode::{encoded_value_len, StatefulEncoder};
+use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
 use dicom_encoding::encode::EncodeTo;
 use dicom_encoding::text::SpecificCharacterSet;
 use dicom_encoding::transfer_syntax::DynEncoder;
@@ -67,6 +67,16 @@ pub enum Error {
         #[snafu(backtrace)]
         source: crate::stateful::encode::Error,
     },
+
+    #[snafu(display("Could not calculate the encoded length of element {}", tag))]
+    CalculateLength {
+        tag: Tag,
+        source: dicom_encoding::text::EncodeTextError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Sequence or item is too long ({} bytes) for a defined length", len))]
+    LengthOverflow { len: u64, backtrace: Backtrace },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -79,16 +89,46 @@ struct SeqToken {
     /// The length of the value, as indicated by the starting element,
     /// can be unknown.
     len: Length,
+    /// Whether it is the start of encapsulated pixel data.
+    pixel_data: bool,
+}
+
+/// The set of options for the data set writer.
+#[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct DataSetWriterOptions {
+    /// The convention for the lengths of sequences and items
+    pub sequence_lengths: SequenceLengthStrategy,
+}
+
+impl DataSetWriterOptions {
+    /// Replace the strategy for the lengths of sequences and items.
+    pub fn sequence_lengths(mut self, sequence_lengths: SequenceLengthStrategy) -> Self {
+        self.sequence_lengths = sequence_lengths;
+        self
+    }
 }
 
 /// A stateful device for printing a DICOM data set in sequential order.
 /// This is analogous to the `DatasetReader` type for converting data
 /// set tokens to bytes.
+///
+/// With [`SequenceLengthStrategy::AllDefined`],
+/// the tokens of each sequence are held back until the sequence ends,
+/// so that the lengths of the sequence and its items can be calculated.
 #[derive(Debug)]
 pub struct DataSetWriter<W, E, T = SpecificCharacterSet> {
     printer: StatefulEncoder<W, E, T>,
     seq_tokens: Vec<SeqToken>,
     last_de: Option<DataElementHeader>,
+    options: DataSetWriterOptions,
+    /// whether the encoder uses explicit VR,
+    /// only relevant when sequence lengths need to be calculated
+    explicit_vr: bool,
+    /// tokens of a sequence waiting for its lengths to be calculated
+    pending: Vec<DataToken>,
+    /// the sequence nesting level of the pending tokens
+    pending_depth: usize,
 }
 
 impl<'w, W: 'w> DataSetWriter<W, DynEncoder<'w, W>>
@@ -98,15 +138,18 @@ where
     /// Create a new data set writer
     /// with the given transfer syntax specifier.
     pub fn with_ts(to: W, ts: &TransferSyntax) -> Result<Self> {
-        let encoder = ts.encoder_for().context(UnsupportedTransferSyntaxSnafu {
-            ts_uid: ts.uid(),
-            ts_alias: ts.name(),
-        })?;
-        Ok(DataSetWriter::new_with_codec(
-            to,
-            encoder,
-            SpecificCharacterSet::default(),
-        ))
+        Self::with_ts_cs_options(to, ts, SpecificCharacterSet::default(), Default::default())
+    }
+
+    /// Create a new data set writer
+    /// with the given transfer syntax specifier
+    /// and writer options.
+    pub fn with_ts_options(
+        to: W,
+        ts: &TransferSyntax,
+        options: DataSetWriterOptions,
+    ) -> Result<Self> {
+        Self::with_ts_cs_options(to, ts, SpecificCharacterSet::default(), options)
     }
 
     /// Create a new data set writer
@@ -117,11 +160,27 @@ where
     /// can override the character set with the presence of a
     /// _Specific Character Set_ data element.
     pub fn with_ts_cs(to: W, ts: &TransferSyntax, charset: SpecificCharacterSet) -> Result<Self> {
+        Self::with_ts_cs_options(to, ts, charset, Default::default())
+    }
+
+    /// Create a new data set writer
+    /// with the given transfer syntax specifier,
+    /// the specific character set to assume by default,
+    /// and writer options.
+    pub fn with_ts_cs_options(
+        to: W,
+        ts: &TransferSyntax,
+        charset: SpecificCharacterSet,
+        options: DataSetWriterOptions,
+    ) -> Result<Self> {
         let encoder = ts.encoder_for().context(UnsupportedTransferSyntaxSnafu {
             ts_uid: ts.uid(),
             ts_alias: ts.name(),
         })?;
-        Ok(DataSetWriter::new_with_codec(to, encoder, charset))
+        let mut writer = DataSetWriter::new_with_codec(to, encoder, charset);
+        writer.options = options;
+        writer.explicit_vr = ts.explicit_vr();
+        Ok(writer)
     }
 }
 
@@ -131,6 +190,10 @@ impl<W, E> DataSetWriter<W, E> {
             printer: StatefulEncoder::new(to, encoder, SpecificCharacterSet::default()),
             seq_tokens: Vec::new(),
             last_de: None,
+            options: Default::default(),
+            explicit_vr: true,
+            pending: Vec::new(),
+            pending_depth: 0,
         }
     }
 }
@@ -141,6 +204,10 @@ impl<W, E, T> DataSetWriter<W, E, T> {
             printer: StatefulEncoder::new(to, encoder, text),
             seq_tokens: Vec::new(),
             last_de: None,
+            options: Default::default(),
+            explicit_vr: true,
+            pending: Vec::new(),
+            pending_depth: 0,
         }
     }
 }
@@ -165,25 +232,94 @@ where
 
     /// Feed the given data set token for writing the data set.
     pub fn write(&mut self, token: DataToken) -> Result<()> {
+        if self.options.sequence_lengths == SequenceLengthStrategy::AllDefined
+            && (!self.pending.is_empty() || matches!(token, DataToken::SequenceStart { .. }))
+        {
+            // hold back the sequence until it ends
+            match token {
+                DataToken::SequenceStart { .. } | DataToken::PixelSequenceStart => {
+                    self.pending_depth += 1;
+                }
+                DataToken::SequenceEnd => {
+                    self.pending_depth = self.pending_depth.saturating_sub(1);
+                }
+                _ => {}
+            }
+            self.pending.push(token);
+            if self.pending_depth == 0 {
+                let mut tokens = std::mem::take(&mut self.pending);
+                self.define_lengths(&mut tokens)?;
+                for token in tokens {
+                    self.write_token(token)?;
+                }
+            }
+            return Ok(());
+        }
+
+        self.write_token(token)
+    }
+
+    /// Retrieve a writer for the raw bytes of the next value,
+    /// which are written to the output as is.
+    ///
+    /// If an element header was fed last,
+    /// it is written first with its original length,
+    /// which the bytes written must match.
+    /// Otherwise, the bytes are taken as the value
+    /// of the pixel data item which was just started.
+    /// Padding to an even length is up to the caller.
+    ///
+    /// This is not supported while sequences are being held back
+    /// for their lengths to be calculated.
+    pub(crate) fn raw_value_writer(&mut self) -> Result<RawValueWriter<'_, W, E>> {
+        debug_assert!(self.pending.is_empty());
+        if let Some(header) = self.last_de.take() {
+            self.printer
+                .encode_element_header(header)
+                .context(WriteHeaderSnafu { tag: header.tag })?;
+        }
+        Ok(RawValueWriter {
+            printer: &mut self.printer,
+        })
+    }
+
+    fn write_token(&mut self, token: DataToken) -> Result<()> {
         // adjust the logic of sequence printing:
         // explicit length sequences or items should not print
         // the respective delimiter
 
+        let undefined_lengths =
+            self.options.sequence_lengths == SequenceLengthStrategy::AllUndefined;
+
         match token {
-            DataToken::SequenceStart { len, .. } => {
+            DataToken::SequenceStart { tag, len } => {
+                let len = if undefined_lengths {
+                    Length::UNDEFINED
+                } else {
+                    len
+                };
                 self.seq_tokens.push(SeqToken {
                     typ: SeqTokenType::Sequence,
                     len,
+                    pixel_data: false,
                 });
-                self.write_impl(&token)?;
+                self.write_impl(&DataToken::SequenceStart { tag, len })?;
                 Ok(())
             }
             DataToken::ItemStart { len } => {
+                // items of encapsulated pixel data always keep their length
+                let in_pixel_data = self.seq_tokens.last().map_or(false, |t| t.pixel_data);
+                let len = if undefined_lengths && !in_pixel_data {
+                    Length::UNDEFINED
+                } else {
+                    len
+                };
                 self.seq_tokens.push(SeqToken {
                     typ: SeqTokenType::Item,
                     len,
+                    pixel_data: false,
                 });
-                self.write_impl(&token)?;
+                self.write_impl(&DataToken::ItemStart { len })?;
                 Ok(())
             }
             DataToken::ItemEnd => {
@@ -215,6 +351,7 @@ where
                 self.seq_tokens.push(SeqToken {
                     typ: SeqTokenType::Sequence,
                     len: Length::UNDEFINED,
+                    pixel_data: true,
                 });
                 self.write_impl(&token)
             }
@@ -224,6 +361,120 @@ where
         }
     }
 
+    /// Calculate the lengths of all sequences and items
+    /// in the given tokens of a full sequence,
+    /// replacing them in the respective start tokens.
+    fn define_lengths(&self, tokens: &mut [DataToken]) -> Result<()> {
+        let explicit_vr = self.explicit_vr;
+        let header_len = |vr: VR| -> u64 {
+            match vr {
+                VR::OB
+                | VR::OD
+                | VR::OF
+                | VR::OL
+                | VR::OW
+                | VR::SQ
+                | VR::UC
+                | VR::UR
+                | VR::UT
+                | VR::UN
+                    if explicit_vr =>
+                {
+                    12
+                }
+                _ => 8,
+            }
+        };
+        let defined_len = |len: u64| -> Result<Length> {
+            if len >= u64::from(u32::MAX) {
+                return LengthOverflowSnafu { len }.fail();
+            }
+            Ok(Length(len as u32))
+        };
+
+        // follow changes of character set like the printer does
+        let mut charset = self.printer.text().clone();
+        // the position of each open sequence or item start token,
+        // and the length of its content so far
+        let mut open: Vec<(usize, u64)> = Vec::new();
+        let mut last_de = None;
+
+        for i in 0..tokens.len() {
+            let in_pixel_data = matches!(
+                open.last().map(|(start, _)| &tokens[*start]),
+                Some(DataToken::PixelSequenceStart)
+            );
+
+            let len = match tokens[i] {
+                DataToken::SequenceStart { .. } | DataToken::PixelSequenceStart => {
+                    open.push((i, 0));
+                    continue;
+                }
+                // items of encapsulated pixel data have a defined length
+                DataToken::ItemStart { .. } if in_pixel_data => 8,
+                DataToken::ItemEnd if in_pixel_data => 0,
+                DataToken::ItemStart { .. } => {
+                    open.push((i, 0));
+                    continue;
+                }
+                DataToken::ItemEnd => {
+                    let (start, len) = open.pop().context(UnexpectedTokenSnafu {
+                        token: DataToken::ItemEnd,
+                    })?;
+                    tokens[start] = DataToken::ItemStart {
+                        len: defined_len(len)?,
+                    };
+                    8 + len
+                }
+                DataToken::SequenceEnd => {
+                    let (start, len) = open.pop().context(UnexpectedTokenSnafu {
+                        token: DataToken::SequenceEnd,
+                    })?;
+                    if let DataToken::SequenceStart { tag, .. } = tokens[start] {
+                        tokens[start] = DataToken::SequenceStart {
+                            tag,
+                            len: defined_len(len)?,
+                        };
+                        header_len(VR::SQ) + len
+                    } else {
+                        // pixel data header, fragments, and sequence delimiter
+                        header_len(VR::OB) + len + 8
+                    }
+                }
+                DataToken::ElementHeader(header) => {
+                    last_de = Some(header);
+                    continue;
+                }
+                DataToken::PrimitiveValue(ref value) => {
+                    let header = last_de.take().with_context(|| UnexpectedTokenSnafu {
+                        token: tokens[i].clone(),
+                    })?;
+                    let value_len = encoded_value_len(&charset, header.vr, value)
+                        .context(CalculateLengthSnafu { tag: header.tag })?;
+                    if header.tag == Tag(0x0008, 0x0005) {
+                        let name = match value {
+                            PrimitiveValue::Str(name) => Some(name.as_str()),
+                            PrimitiveValue::Strs(names) => names.first().map(|n| n.as_str()),
+                            _ => None,
+                        };
+                        if let Some(cs) = name.and_then(SpecificCharacterSet::from_code) {
+                            charset = cs;
+                        }
+                    }
+                    header_len(header.vr) + u64::from(value_len)
+                }
+                DataToken::ItemValue(ref data) => (data.len() as u64 + 1) & !1,
+                DataToken::OffsetTable(ref table) => table.len() as u64 * 4,
+            };
+
+            if let Some((_, content_len)) = open.last_mut() {
+                *content_len += len;
+            }
+        }
+
+        Ok(())
+    }
+
     fn write_impl(&mut self, token: &DataToken) -> Result<()> {
         match token {
             DataToken::ElementHeader(header) => {
@@ -280,16 +531,42 @@ where
     }
 }
 
+/// A writer of raw value bytes into the output of a [`DataSetWriter`],
+/// created by `DataSetWriter::raw_value_writer`.
+#[derive(Debug)]
+pub(crate) struct RawValueWriter<'a, W, E> {
+    printer: &'a mut StatefulEncoder<W, E>,
+}
+
+impl<W, E> Write for RawValueWriter<'_, W, E>
+where
+    W: Write,
+    E: EncodeTo<W>,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.printer
+            .write_raw_bytes(buf)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::DataToken;
-    use super::DataSetWriter;
+    use super::{DataSetWriter, DataSetWriterOptions};
+    use crate::dataset::SequenceLengthStrategy;
     use dicom_core::{
         header::{DataElementHeader, Length},
         value::PrimitiveValue,
         Tag, VR,
     };
     use dicom_encoding::encode::{explicit_le::ExplicitVRLittleEndianEncoder, EncoderFor};
+    use dicom_encoding::transfer_syntax::{Codec, TransferSyntax};
 
     fn validate_dataset_writer<I>(tokens: I, ground_truth: &[u8])
     where
@@ -304,6 +581,187 @@ mod tests {
         assert_eq!(raw_out, ground_truth);
     }
 
+    fn validate_dataset_writer_with_options<I>(
+        tokens: I,
+        options: DataSetWriterOptions,
+        ground_truth: &[u8],
+    ) where
+        I: IntoIterator<Item = DataToken>,
+    {
+        let ts: TransferSyntax = TransferSyntax::new_ele(
+            "1.2.840.10008.1.2.1",
+            "Explicit VR Little Endian",
+            Codec::None,
+        );
+        let mut raw_out: Vec<u8> = vec![];
+        let mut dset_writer = DataSetWriter::with_ts_options(&mut raw_out, &ts, options).unwrap();
+
+        dset_writer.write_sequence(tokens).unwrap();
+
+        assert_eq!(raw_out, ground_truth);
+    }
+
+    /// Tokens of a sequence with one item,
+    /// containing a text element and another sequence with one item,
+    /// followed by an element at the root.
+    fn nested_sequence_tokens(
+        outer_len: Length,
+        outer_item_len: Length,
+        inner_len: Length,
+        inner_item_len: Length,
+    ) -> Vec<DataToken> {
+        vec![
+            DataToken::SequenceStart {
+                tag: Tag(0x0040, 0x0275),
+                len: outer_len,
+            },
+            DataToken::ItemStart {
+                len: outer_item_len,
+            },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0040, 0x0007),
+                vr: VR::LO,
+                len: Length(4),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::from("ABCD")),
+            DataToken::SequenceStart {
+                tag: Tag(0x0040, 0x0008),
+                len: inner_len,
+            },
+            DataToken::ItemStart {
+                len: inner_item_len,
+            },
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0008, 0x0100),
+                vr: VR::SH,
+                len: Length(2),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::from("T1")),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ElementHeader(DataElementHeader {
+                tag: Tag(0x0020, 0x4000),
+                vr: VR::LT,
+                len: Length(4),
+            }),
+            DataToken::PrimitiveValue(PrimitiveValue::Str("TEST".into())),
+        ]
+    }
+
+    #[rustfmt::skip]
+    static NESTED_SEQUENCE_DEFINED: &[u8] = &[
+        0x40, 0x00, 0x75, 0x02, // sequence tag: (0040,0275) RequestAttributesSequence
+        b'S', b'Q', // VR
+        0x00, 0x00, // reserved
+        0x32, 0x00, 0x00, 0x00, // length: 50
+        // -- 12 --
+        0xfe, 0xff, 0x00, 0xe0, // item start tag
+        0x2a, 0x00, 0x00, 0x00, // item length: 42
+        // -- 20 --
+        0x40, 0x00, 0x07, 0x00, b'L', b'O', 0x04, 0x00, // (0040,0007) ScheduledProcedureStepDescription, len = 4
+        b'A', b'B', b'C', b'D', // value = "ABCD"
+        // -- 32 --
+        0x40, 0x00, 0x08, 0x00, // sequence tag: (0040,0008) ScheduledProtocolCodeSequence
+        b'S', b'Q', // VR
+        0x00, 0x00, // reserved
+        0x12, 0x00, 0x00, 0x00, // length: 18
+        // -- 44 --
+        0xfe, 0xff, 0x00, 0xe0, // item start tag
+        0x0a, 0x00, 0x00, 0x00, // item length: 10
+        // -- 52 --
+        0x08, 0x00, 0x00, 0x01, b'S', b'H', 0x02, 0x00, b'T', b'1', // (0008,0100) CodeValue, len = 2, value = "T1"
+        // -- 62 --
+        0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+        b'T', b'E', b'S', b'T', // value = "TEST"
+    ];
+
+    #[rustfmt::skip]
+    static NESTED_SEQUENCE_UNDEFINED: &[u8] = &[
+        0x40, 0x00, 0x75, 0x02, // sequence tag: (0040,0275) RequestAttributesSequence
+        b'S', b'Q', // VR
+        0x00, 0x00, // reserved
+        0xff, 0xff, 0xff, 0xff, // length: undefined
+        // -- 12 --
+        0xfe, 0xff, 0x00, 0xe0, // item start tag
+        0xff, 0xff, 0xff, 0xff, // item length: undefined
+        // -- 20 --
+        0x40, 0x00, 0x07, 0x00, b'L', b'O', 0x04, 0x00, // (0040,0007) ScheduledProcedureStepDescription, len = 4
+        b'A', b'B', b'C', b'D', // value = "ABCD"
+        // -- 32 --
+        0x40, 0x00, 0x08, 0x00, // sequence tag: (0040,0008) ScheduledProtocolCodeSequence
+        b'S', b'Q', // VR
+        0x00, 0x00, // reserved
+        0xff, 0xff, 0xff, 0xff, // length: undefined
+        // -- 44 --
+        0xfe, 0xff, 0x00, 0xe0, // item start tag
+        0xff, 0xff, 0xff, 0xff, // item length: undefined
+        // -- 52 --
+        0x08, 0x00, 0x00, 0x01, b'S', b'H', 0x02, 0x00, b'T', b'1', // (0008,0100) CodeValue, len = 2, value = "T1"
+        // -- 62 --
+        0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+        0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+        0xfe, 0xff, 0x0d, 0xe0, 0x00, 0x00, 0x00, 0x00, // item end
+        0xfe, 0xff, 0xdd, 0xe0, 0x00, 0x00, 0x00, 0x00, // sequence end
+        // -- 94 --
+        0x20, 0x00, 0x00, 0x40, b'L', b'T', 0x04, 0x00, // (0020,4000) ImageComments, len = 4
+        b'T', b'E', b'S', b'T', // value = "TEST"
+    ];
+
+    #[test]
+    fn write_nested_sequence_preserve_original() {
+        let options = DataSetWriterOptions::default();
+        validate_dataset_writer_with_options(
+            nested_sequence_tokens(Length(50), Length(42), Length(18), Length(10)),
+            options,
+            NESTED_SEQUENCE_DEFINED,
+        );
+        validate_dataset_writer_with_options(
+            nested_sequence_tokens(
+                Length::UNDEFINED,
+                Length::UNDEFINED,
+                Length::UNDEFINED,
+                Length::UNDEFINED,
+            ),
+            options,
+            NESTED_SEQUENCE_UNDEFINED,
+        );
+    }
+
+    #[test]
+    fn write_nested_sequence_all_defined() {
+        let options =
+            DataSetWriterOptions::default().sequence_lengths(SequenceLengthStrategy::AllDefined);
+        validate_dataset_writer_with_options(
+            nested_sequence_tokens(
+                Length::UNDEFINED,
+                Length::UNDEFINED,
+                Length::UNDEFINED,
+                Length::UNDEFINED,
+            ),
+            options,
+            NESTED_SEQUENCE_DEFINED,
+        );
+        // wrong lengths are recalculated too
+        validate_dataset_writer_with_options(
+            nested_sequence_tokens(Length(8), Length::UNDEFINED, Length(0), Length(2)),
+            options,
+            NESTED_SEQUENCE_DEFINED,
+        );
+    }
+
+    #[test]
+    fn write_nested_sequence_all_undefined() {
+        let options =
+            DataSetWriterOptions::default().sequence_lengths(SequenceLengthStrategy::AllUndefined);
+        validate_dataset_writer_with_options(
+            nested_sequence_tokens(Length(50), Length(42), Length(18), Length(10)),
+            options,
+            NESTED_SEQUENCE_UNDEFINED,
+        );
+    }
+
     #[test]
     fn write_sequence_explicit() {
         let tokens = vec![
@@ -600,4 +1058,80 @@ mod tests {
 
         validate_dataset_writer(tokens, GROUND_TRUTH);
     }
+
+    #[test]
+    fn write_encapsulated_pixeldata_big_endian_roundtrip() {
+        use crate::dataset::read::DataSetReader;
+        use dicom_encoding::transfer_syntax::Endianness;
+
+        let ts: TransferSyntax = TransferSyntax::new(
+            "1.2.840.10008.1.2.2",
+            "Explicit VR Big Endian",
+            Endianness::Big,
+            true,
+            Codec::None,
+        );
+
+        let tokens = vec![
+            DataToken::PixelSequenceStart,
+            DataToken::ItemStart { len: Length(8) },
+            DataToken::OffsetTable(vec![0, 16]),
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(8) },
+            DataToken::ItemValue(vec![0x99; 8]),
+            DataToken::ItemEnd,
+            DataToken::ItemStart { len: Length(4) },
+            DataToken::ItemValue(vec![0x88; 4]),
+            DataToken::ItemEnd,
+            DataToken::SequenceEnd,
+            DataToken::ElementHeader(DataElementHeader::new(
+                Tag(0xfffc, 0xfffc),
+                VR::OB,
+                Length(2),
+            )),
+            DataToken::PrimitiveValue(PrimitiveValue::U8([0x00; 2].as_ref().into())),
+        ];
+
+        #[rustfmt::skip]
+        static GROUND_TRUTH: &[u8] = &[
+            0x7f, 0xe0, 0x00, 0x10, // (7FE0, 0010) PixelData
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0xff, 0xff, 0xff, 0xff, // length: undefined
+            // -- 12 -- Basic offset table
+            0xff, 0xfe, 0xe0, 0x00, // item start tag
+            0x00, 0x00, 0x00, 0x08, // item length: 8
+            0x00, 0x00, 0x00, 0x00, // 0
+            0x00, 0x00, 0x00, 0x10, // 16
+            // -- 28 -- First fragment
+            0xff, 0xfe, 0xe0, 0x00, // item start tag
+            0x00, 0x00, 0x00, 0x08, // item length: 8
+            0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x99, 0x99,
+            // -- 44 -- Second fragment
+            0xff, 0xfe, 0xe0, 0x00, // item start tag
+            0x00, 0x00, 0x00, 0x04, // item length: 4
+            0x88, 0x88, 0x88, 0x88,
+            // -- 56 -- End of pixel data
+            0xff, 0xfe, 0xe0, 0xdd, // sequence end tag
+            0x00, 0x00, 0x00, 0x00,
+            // -- 64 -- padding
+            0xff, 0xfc, 0xff, 0xfc, // (fffc,fffc) DataSetTrailingPadding
+            b'O', b'B', // VR
+            0x00, 0x00, // reserved
+            0x00, 0x00, 0x00, 0x02, // length: 2
+            0x00, 0x00,
+        ];
+
+        let mut raw_out: Vec<u8> = vec![];
+        let mut dset_writer = DataSetWriter::with_ts(&mut raw_out, &ts).unwrap();
+        dset_writer.write_sequence(tokens.clone()).unwrap();
+        assert_eq!(raw_out, GROUND_TRUTH);
+
+        // read it back
+        let dset_reader = DataSetReader::new_with_ts(&raw_out[..], &ts).unwrap();
+        let read_tokens = dset_reader
+            .collect::<Result<Vec<_>, _>>()
+            .expect("should read all tokens back");
+        assert_eq!(read_tokens, tokens);
+    }
 }
@@ -9,6 +9,7 @@
 //! For a more intuitive, object-oriented API, please see the `dicom-object`
 //! crate.
 pub mod dataset;
+pub mod pipeline;
 pub mod stateful;
 
 mod util;
@@ -0,0 +1,539 @@
+//! Single-pass transformation of DICOM data sets.
+//!
+//! This module provides [`copy_transform`],
+//! which copies a data set from a reader to a writer
+//! while removing or replacing data elements
+//! according to a set of [`TransformRules`],
+//! such as for de-identification.
+//!
+//! Data set tokens are read lazily,
+//! so that only the values which need to be interpreted
+//! are loaded into memory.
+//! All other values, including native pixel data
+//! and the fragments of encapsulated pixel data,
+//! are copied through in small chunks,
+//! keeping memory usage bounded
+//! regardless of the size of the data set.
+use crate::dataset::lazy_read::{self, LazyDataSetReader};
+use crate::dataset::write::{self, DataSetWriter, DataSetWriterOptions};
+use crate::dataset::{self, DataToken, LazyDataToken, SequenceLengthStrategy};
+use crate::stateful::decode::{self, DynStatefulDecoder};
+use dicom_core::{PrimitiveValue, Tag, VR};
+use dicom_encoding::text::SpecificCharacterSet;
+use dicom_encoding::TransferSyntax;
+use snafu::{Backtrace, ResultExt, Snafu};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{Read, Write};
+
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum Error {
+    #[snafu(display("Could not create decoder"))]
+    CreateDecoder {
+        #[snafu(backtrace)]
+        source: decode::Error,
+    },
+    #[snafu(display("Could not create data set writer"))]
+    CreateWriter {
+        #[snafu(backtrace)]
+        source: write::Error,
+    },
+    #[snafu(display("Could not read data set token"))]
+    ReadToken {
+        #[snafu(backtrace)]
+        source: lazy_read::Error,
+    },
+    #[snafu(display("Could not read value of element {}", tag))]
+    ReadValue {
+        tag: Tag,
+        source: dataset::Error,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Could not skip value"))]
+    SkipValue {
+        #[snafu(backtrace)]
+        source: decode::Error,
+    },
+    #[snafu(display("Could not copy value of element {}", tag))]
+    CopyValue {
+        tag: Tag,
+        source: dataset::Error,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Could not copy pixel data fragment"))]
+    CopyFragment {
+        source: dataset::Error,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Could not write value padding"))]
+    WritePadding {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Could not write data set token"))]
+    WriteToken {
+        #[snafu(backtrace)]
+        source: write::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// A function mapping a UID in the given attribute to a new UID,
+/// or to `None` to keep the original one.
+type UidMap = dyn Fn(Tag, &str) -> Option<String> + Send + Sync;
+
+/// The set of transformations to apply in [`copy_transform`].
+///
+/// Rules are matched by attribute tag
+/// at any level of the data set,
+/// including inside of sequence items.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_core::Tag;
+/// # use dicom_parser::pipeline::TransformRules;
+/// let rules = TransformRules::new()
+///     // Patient's Name
+///     .remove(Tag(0x0010, 0x0010))
+///     // Patient ID
+///     .replace(Tag(0x0010, 0x0020), "ANONYMOUS")
+///     .remap_uids(|_tag, uid| uid.strip_prefix("1.2.3.").map(|id| format!("2.25.{}", id)));
+/// ```
+#[derive(Default)]
+pub struct TransformRules {
+    /// tags of the elements to remove
+    remove: BTreeSet<Tag>,
+    /// new values of the elements to replace
+    replace: BTreeMap<Tag, PrimitiveValue>,
+    /// function for remapping UIDs
+    uid_map: Option<Box<UidMap>>,
+}
+
+impl fmt::Debug for TransformRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransformRules")
+            .field("remove", &self.remove)
+            .field("replace", &self.replace)
+            .field("uid_map", &self.uid_map.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl TransformRules {
+    /// Create an empty set of rules,
+    /// which copies the data set as is.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove the data elements with the given tag.
+    ///
+    /// Removing a sequence removes all of its items,
+    /// and removing _Pixel Data_ also removes encapsulated pixel data.
+    /// Removal takes precedence over the other rules.
+    pub fn remove(mut self, tag: Tag) -> Self {
+        self.remove.insert(tag);
+        self
+    }
+
+    /// Replace the value of the data elements with the given tag,
+    /// keeping their value representation.
+    ///
+    /// Only elements with a primitive value are replaced,
+    /// sequences with this tag are copied as is.
+    pub fn replace(mut self, tag: Tag, value: impl Into<PrimitiveValue>) -> Self {
+        self.replace.insert(tag, value.into());
+        self
+    }
+
+    /// Map every UID in the data set through the given function,
+    /// which receives the tag of the attribute and the original UID
+    /// and returns the new UID, or `None` to keep the original one.
+    ///
+    /// The function applies to all data elements with the UI value representation
+    /// which are not removed or replaced.
+    pub fn remap_uids<F>(mut self, map: F) -> Self
+    where
+        F: Fn(Tag, &str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.uid_map = Some(Box::new(map));
+        self
+    }
+
+    fn removes(&self, tag: Tag) -> bool {
+        self.remove.contains(&tag)
+    }
+
+    fn remap_uid_value(&self, tag: Tag, value: PrimitiveValue) -> PrimitiveValue {
+        let Some(map) = &self.uid_map else {
+            return value;
+        };
+        let remap = |uid: String| map(tag, uid.trim_end_matches('\0')).unwrap_or(uid);
+        match value {
+            PrimitiveValue::Str(uid) => PrimitiveValue::Str(remap(uid)),
+            PrimitiveValue::Strs(uids) => {
+                PrimitiveValue::Strs(uids.into_iter().map(remap).collect())
+            }
+            value => value,
+        }
+    }
+}
+
+/// Copy a DICOM data set from `from` to `to` in a single pass,
+/// applying the given transformation rules along the way.
+///
+/// Both sides are encoded in the given transfer syntax,
+/// and the source is expected to start at the first data set element
+/// (with no preamble or file meta group).
+/// Values are copied as is unless a rule applies to them,
+/// so only the values being replaced or remapped,
+/// and the _Specific Character Set_,
+/// are ever read into memory.
+///
+/// Since the content of a sequence may change in size,
+/// all sequences and items are written with undefined length,
+/// as in [`SequenceLengthStrategy::AllUndefined`].
+/// Other data elements are written with their exact length.
+pub fn copy_transform<R, W>(
+    from: R,
+    to: W,
+    ts: &TransferSyntax,
+    rules: &TransformRules,
+) -> Result<()>
+where
+    R: Read,
+    W: Write,
+{
+    let parser = DynStatefulDecoder::new_with(from, ts, SpecificCharacterSet::default(), 0)
+        .context(CreateDecoderSnafu)?;
+    let mut reader = LazyDataSetReader::new(parser);
+    let options =
+        DataSetWriterOptions::default().sequence_lengths(SequenceLengthStrategy::AllUndefined);
+    let mut writer = DataSetWriter::with_ts_options(to, ts, options).context(CreateWriterSnafu)?;
+
+    // the nesting level inside of a sequence being removed
+    let mut removing = 0_usize;
+
+    while let Some(token) = reader.advance() {
+        let token = token.context(ReadTokenSnafu)?;
+
+        if removing > 0 {
+            match token {
+                LazyDataToken::SequenceStart { .. } | LazyDataToken::PixelSequenceStart => {
+                    removing += 1;
+                }
+                LazyDataToken::SequenceEnd => {
+                    removing -= 1;
+                }
+                _ => {}
+            }
+            token.skip().context(SkipValueSnafu)?;
+            continue;
+        }
+
+        match token {
+            LazyDataToken::SequenceStart { tag, .. } if rules.removes(tag) => {
+                removing = 1;
+            }
+            LazyDataToken::PixelSequenceStart if rules.removes(Tag(0x7FE0, 0x0010)) => {
+                removing = 1;
+            }
+            LazyDataToken::SequenceStart { tag, len } => {
+                writer
+                    .write(DataToken::SequenceStart { tag, len })
+                    .context(WriteTokenSnafu)?;
+            }
+            LazyDataToken::PixelSequenceStart => {
+                writer
+                    .write(DataToken::PixelSequenceStart)
+                    .context(WriteTokenSnafu)?;
+            }
+            LazyDataToken::SequenceEnd => {
+                writer
+                    .write(DataToken::SequenceEnd)
+                    .context(WriteTokenSnafu)?;
+            }
+            LazyDataToken::ItemStart { len } => {
+                writer
+                    .write(DataToken::ItemStart { len })
+                    .context(WriteTokenSnafu)?;
+            }
+            LazyDataToken::ItemEnd => {
+                writer.write(DataToken::ItemEnd).context(WriteTokenSnafu)?;
+            }
+            // the decision is made once the value comes up
+            LazyDataToken::ElementHeader(_) => {}
+            token @ LazyDataToken::LazyValue { header, .. } => {
+                let tag = header.tag;
+
+                if rules.removes(tag) {
+                    token.skip().context(SkipValueSnafu)?;
+                    continue;
+                }
+
+                let value = if let Some(value) = rules.replace.get(&tag) {
+                    token.skip().context(SkipValueSnafu)?;
+                    Some(value.clone())
+                } else if header.vr == VR::UI && rules.uid_map.is_some() {
+                    let value = token.into_value().context(ReadValueSnafu { tag })?;
+                    Some(rules.remap_uid_value(tag, value))
+                } else if tag == Tag(0x0008, 0x0005) {
+                    // let both sides know of the character set in use
+                    Some(token.into_value().context(ReadValueSnafu { tag })?)
+                } else {
+                    writer
+                        .write(DataToken::ElementHeader(header))
+                        .context(WriteTokenSnafu)?;
+                    let mut out = writer.raw_value_writer().context(WriteTokenSnafu)?;
+                    token
+                        .read_value_into(&mut out)
+                        .context(CopyValueSnafu { tag })?;
+                    if header.len.0 % 2 != 0 {
+                        out.write_all(&[0]).context(WritePaddingSnafu)?;
+                    }
+                    None
+                };
+
+                if let Some(value) = value {
+                    writer
+                        .write(DataToken::ElementHeader(header))
+                        .context(WriteTokenSnafu)?;
+                    writer
+                        .write(DataToken::PrimitiveValue(value))
+                        .context(WriteTokenSnafu)?;
+                }
+            }
+            token @ LazyDataToken::LazyItemValue { len, .. } => {
+                let mut out = writer.raw_value_writer().context(WriteTokenSnafu)?;
+                token.read_value_into(&mut out).context(CopyFragmentSnafu)?;
+                if len % 2 != 0 {
+                    out.write_all(&[0]).context(WritePaddingSnafu)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copy_transform, TransformRules};
+    use crate::dataset::read::DataSetReader;
+    use crate::dataset::write::{DataSetWriter, DataSetWriterOptions};
+    use crate::dataset::{DataToken, SequenceLengthStrategy};
+    use dicom_core::{DataElementHeader, Length, PrimitiveValue, Tag, VR};
+    use dicom_dictionary_std::tags;
+    use dicom_encoding::transfer_syntax::{Codec, TransferSyntax};
+    use std::cell::Cell;
+    use std::io::{Read, Write};
+
+    fn explicit_vr_le() -> TransferSyntax {
+        TransferSyntax::new_ele(
+            "1.2.840.10008.1.2.1",
+            "Explicit VR Little Endian",
+            Codec::None,
+        )
+    }
+
+    /// Encode the given tokens with defined sequence and item lengths.
+    fn encode(tokens: Vec<DataToken>) -> Vec<u8> {
+        let mut out = Vec::new();
+        {
+            let options = DataSetWriterOptions::default()
+                .sequence_lengths(SequenceLengthStrategy::AllDefined);
+            let mut writer = DataSetWriter::with_ts_options(&mut out, &explicit_vr_le(), options)
+                .expect("should create data set writer");
+            writer.write_sequence(tokens).expect("should encode tokens");
+        }
+        out
+    }
+
+    fn decode(data: &[u8]) -> Vec<DataToken> {
+        DataSetReader::new_with_ts(data, &explicit_vr_le())
+            .expect("should create data set reader")
+            .collect::<Result<_, _>>()
+            .expect("should decode tokens")
+    }
+
+    fn element(tag: Tag, vr: VR, value: PrimitiveValue) -> [DataToken; 2] {
+        let len = value.calculate_byte_len() as u32;
+        [
+            DataToken::ElementHeader(DataElementHeader::new(tag, vr, Length(len))),
+            DataToken::PrimitiveValue(value),
+        ]
+    }
+
+    fn sequence_start(tag: Tag) -> [DataToken; 2] {
+        [
+            DataToken::SequenceStart {
+                tag,
+                len: Length::UNDEFINED,
+            },
+            DataToken::ItemStart {
+                len: Length::UNDEFINED,
+            },
+        ]
+    }
+
+    const SEQUENCE_END: [DataToken; 2] = [DataToken::ItemEnd, DataToken::SequenceEnd];
+
+    #[test]
+    fn copy_transform_nested_removal() {
+        let patient_name = || element(tags::PATIENT_NAME, VR::PN, "Doe^John".into());
+
+        let input = encode(
+            [
+                &element(tags::SOP_INSTANCE_UID, VR::UI, "1.2.3.40".into())[..],
+                &sequence_start(tags::REFERENCED_STUDY_SEQUENCE),
+                &element(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "1.2.3.60".into()),
+                &SEQUENCE_END,
+                &patient_name(),
+                &element(tags::PATIENT_ID, VR::LO, "ID01".into()),
+                // level 1
+                &sequence_start(tags::REQUEST_ATTRIBUTES_SEQUENCE),
+                &patient_name(),
+                // level 2
+                &sequence_start(tags::SCHEDULED_PROTOCOL_CODE_SEQUENCE),
+                &element(tags::CODE_VALUE, VR::SH, "T1".into()),
+                // level 3
+                &sequence_start(tags::PROTOCOL_CONTEXT_SEQUENCE),
+                &element(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "1.2.3.50".into()),
+                &patient_name(),
+                &SEQUENCE_END,
+                &SEQUENCE_END,
+                &SEQUENCE_END,
+            ]
+            .concat(),
+        );
+
+        let rules = TransformRules::new()
+            .remove(tags::PATIENT_NAME)
+            .remove(tags::REFERENCED_STUDY_SEQUENCE)
+            .replace(tags::PATIENT_ID, "ANON")
+            .remap_uids(|_tag, uid| match uid {
+                "1.2.3.40" => Some("2.25.400".to_string()),
+                "1.2.3.50" => Some("2.25.500".to_string()),
+                _ => None,
+            });
+
+        let mut output = Vec::new();
+        copy_transform(&input[..], &mut output, &explicit_vr_le(), &rules)
+            .expect("should copy data set");
+
+        let expected = [
+            &element(tags::SOP_INSTANCE_UID, VR::UI, "2.25.400".into())[..],
+            &element(tags::PATIENT_ID, VR::LO, "ANON".into()),
+            &sequence_start(tags::REQUEST_ATTRIBUTES_SEQUENCE),
+            &sequence_start(tags::SCHEDULED_PROTOCOL_CODE_SEQUENCE),
+            &element(tags::CODE_VALUE, VR::SH, "T1".into()),
+            &sequence_start(tags::PROTOCOL_CONTEXT_SEQUENCE),
+            &element(tags::REFERENCED_SOP_INSTANCE_UID, VR::UI, "2.25.500".into()),
+            &SEQUENCE_END,
+            &SEQUENCE_END,
+            &SEQUENCE_END,
+        ]
+        .concat();
+
+        let tokens = decode(&output);
+        assert_eq!(tokens, expected);
+
+        // the sequences were given undefined lengths
+        assert!(tokens.iter().all(|token| match token {
+            DataToken::SequenceStart { len, .. } | DataToken::ItemStart { len } => {
+                len.is_undefined()
+            }
+            _ => true,
+        }));
+    }
+
+    /// A reader which keeps track of the number of bytes read.
+    struct CountingReader<'a, R> {
+        inner: R,
+        bytes_read: &'a Cell<u64>,
+    }
+
+    impl<R: Read> Read for CountingReader<'_, R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.bytes_read.set(self.bytes_read.get() + n as u64);
+            Ok(n)
+        }
+    }
+
+    /// A writer which records how far ahead
+    /// the reading side ever was of the bytes written.
+    struct CountingWriter<'a> {
+        data: Vec<u8>,
+        bytes_read: &'a Cell<u64>,
+        max_lag: u64,
+    }
+
+    impl Write for CountingWriter<'_> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let lag = self.bytes_read.get().saturating_sub(self.data.len() as u64);
+            self.max_lag = self.max_lag.max(lag);
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn copy_transform_streams_pixel_data() {
+        const PIXEL_DATA_LEN: usize = 4 << 20;
+        let pixel_data: Vec<u8> = (0..PIXEL_DATA_LEN).map(|i| (i % 251) as u8).collect();
+
+        let input = encode(
+            [
+                &element(tags::PATIENT_NAME, VR::PN, "Doe^John".into())[..],
+                &element(tags::ROWS, VR::US, PrimitiveValue::from(1024_u16)),
+                &element(
+                    tags::PIXEL_DATA,
+                    VR::OB,
+                    PrimitiveValue::from(pixel_data.clone()),
+                ),
+            ]
+            .concat(),
+        );
+
+        let bytes_read = Cell::new(0);
+        let reader = CountingReader {
+            inner: &input[..],
+            bytes_read: &bytes_read,
+        };
+        let mut writer = CountingWriter {
+            data: Vec::new(),
+            bytes_read: &bytes_read,
+            max_lag: 0,
+        };
+        let rules = TransformRules::new().remove(tags::PATIENT_NAME);
+        copy_transform(reader, &mut writer, &explicit_vr_le(), &rules)
+            .expect("should copy data set");
+
+        assert_eq!(bytes_read.get(), input.len() as u64);
+        // apart from the removed element,
+        // the reader is never more than a small chunk ahead of the writer
+        assert!(
+            writer.max_lag <= 64 * 1024,
+            "pixel data was buffered: reader was {} bytes ahead",
+            writer.max_lag
+        );
+
+        let tokens = decode(&writer.data);
+        assert_eq!(
+            tokens,
+            [
+                &element(tags::ROWS, VR::US, PrimitiveValue::from(1024_u16))[..],
+                &element(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(pixel_data)),
+            ]
+            .concat(),
+        );
+    }
+}
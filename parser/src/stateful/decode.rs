@@ -11,7 +11,7 @@ use dicom_core::value::PrimitiveValue;
 use dicom_dictionary_std::StandardDataDictionary;
 use dicom_encoding::decode::basic::{BasicDecoder, LittleEndianBasicDecoder};
 use dicom_encoding::decode::explicit_le::ExplicitVRLittleEndianDecoder;
-use dicom_encoding::decode::{BasicDecode, DecodeFrom};
+use dicom_encoding::decode::{decode_item_header_any_order, BasicDecode, DecodeFrom};
 use dicom_encoding::text::{
     validate_da, validate_dt, validate_tm, DefaultCharacterSetCodec, SpecificCharacterSet,
     TextCodec, TextValidationOutcome,
@@ -142,6 +142,18 @@ pub trait StatefulDecode {
     /// Same as `Decode::decode_item_header` over the bound source.
     fn decode_item_header(&mut self) -> Result<SequenceItemHeader>;
 
+    /// Decode the header of an item or delimiter
+    /// in an encapsulated pixel data sequence,
+    /// also accepting item headers in the byte order
+    /// opposite to the one of the transfer syntax.
+    ///
+    /// Alongside the header,
+    /// returns `true` if it was encoded in the opposite byte order.
+    /// The default implementation is equivalent to `decode_item_header`.
+    fn decode_pixel_item_header(&mut self) -> Result<(SequenceItemHeader, bool)> {
+        self.decode_item_header().map(|header| (header, false))
+    }
+
     /// Eagerly read the following data in the source as a primitive data
     /// value. When reading values in text form, a conversion to a more
     /// maleable type is attempted. Namely, numbers in text form (IS, DS) are
@@ -833,6 +845,10 @@ where
         (**self).decode_item_header()
     }
 
+    fn decode_pixel_item_header(&mut self) -> Result<(SequenceItemHeader, bool)> {
+        (**self).decode_pixel_item_header()
+    }
+
     fn read_value(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
         (**self).read_value(header)
     }
@@ -920,6 +936,17 @@ where
             .map_err(From::from)
     }
 
+    fn decode_pixel_item_header(&mut self) -> Result<(SequenceItemHeader, bool)> {
+        let expected = self.basic.endianness();
+        let (header, endianness) = decode_item_header_any_order(&mut self.from, expected).context(
+            DecodeItemHeaderSnafu {
+                position: self.position,
+            },
+        )?;
+        self.position += 8;
+        Ok((header, endianness != expected))
+    }
+
     fn read_value(&mut self, header: &DataElementHeader) -> Result<PrimitiveValue> {
         if header.length() == Length(0) {
             return Ok(PrimitiveValue::Empty);
@@ -7,7 +7,7 @@ use dicom_core::{value::PrimitiveValue, DataElementHeader, Length, Tag, VR};
 use dicom_encoding::transfer_syntax::DynEncoder;
 use dicom_encoding::{
     encode::EncodeTo,
-    text::{DefaultCharacterSetCodec, SpecificCharacterSet, TextCodec},
+    text::{DefaultCharacterSetCodec, EncodeTextError, SpecificCharacterSet, TextCodec},
     TransferSyntax,
 };
 use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
@@ -76,6 +76,11 @@ impl<W, E, T> StatefulEncoder<W, E, T> {
             buffer: Vec::with_capacity(128),
         }
     }
+
+    /// Retrieve the text codec currently in use for textual values.
+    pub(crate) fn text(&self) -> &T {
+        &self.text
+    }
 }
 
 impl<'s> DynStatefulEncoder<'s> {
@@ -421,6 +426,43 @@ fn even_len(l: u32) -> u32 {
     (l + 1) & !1
 }
 
+/// Calculate the length of a primitive value
+/// as encoded by a [`StatefulEncoder`]
+/// using the given character set for textual values,
+/// including padding.
+pub(crate) fn encoded_value_len(
+    charset: &SpecificCharacterSet,
+    vr: VR,
+    value: &PrimitiveValue,
+) -> std::result::Result<u32, EncodeTextError> {
+    let text_len = |text: &str| match vr {
+        VR::AE | VR::AS | VR::CS | VR::DA | VR::DS | VR::DT | VR::IS | VR::TM | VR::UI => {
+            // these VRs always use the default character repertoire
+            DefaultCharacterSetCodec
+                .encode(text)
+                .map(|bytes| bytes.len())
+        }
+        _ => charset.encode(text).map(|bytes| bytes.len()),
+    };
+
+    let len = match value {
+        PrimitiveValue::Str(text) => text_len(text)?,
+        PrimitiveValue::Strs(texts) => {
+            // values are separated by backslashes
+            let mut len = texts.len().saturating_sub(1);
+            for text in texts.iter() {
+                len += text_len(text)?;
+            }
+            len
+        }
+        PrimitiveValue::Empty => 0,
+        // binary DS and IS values are written as text
+        _ if matches!(vr, VR::DS | VR::IS) => value.to_str().len(),
+        _ => value.calculate_byte_len(),
+    };
+    Ok(even_len(len as u32))
+}
+
 #[cfg(test)]
 mod tests {
     use dicom_core::{
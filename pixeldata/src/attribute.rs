@@ -1,17 +1,21 @@
 //! Utility module for fetching key attributes from a DICOM object.
 
-use dicom_core::{header::HasLength, DataDictionary, Tag};
+use dicom_core::{header::HasLength, value::trim_padding, DataDictionary, PrimitiveValue, Tag};
 use dicom_dictionary_std::tags;
 use dicom_object::{mem::InMemElement, FileDicomObject, InMemDicomObject};
 use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+use std::convert::Infallible;
 use std::fmt;
+use std::str::FromStr;
+
+use crate::transform::{ModalityLut, PaletteColorLut, PixelPadding, VoiLut};
 
 /// An enum for a DICOM attribute which can be retrieved
 /// for the purposes of decoding pixel data.
 ///
 /// Since the set of attributes needed is more constrained,
 /// this is a more compact representation than a tag or a static string.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum AttributeName {
     Columns,
@@ -30,12 +34,22 @@ pub enum AttributeName {
     VoiLutFunction,
     WindowCenter,
     WindowWidth,
+    LutDescriptor,
+    LutData,
+    RedPaletteColorLookupTableDescriptor,
+    GreenPaletteColorLookupTableDescriptor,
+    BluePaletteColorLookupTableDescriptor,
+    RedPaletteColorLookupTableData,
+    GreenPaletteColorLookupTableData,
+    BluePaletteColorLookupTableData,
 }
 
 impl std::fmt::Display for AttributeName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             AttributeName::VoiLutFunction => f.write_str("VOILUTFunction"),
+            AttributeName::LutDescriptor => f.write_str("LUTDescriptor"),
+            AttributeName::LutData => f.write_str("LUTData"),
             _ => std::fmt::Debug::fmt(self, f),
         }
     }
@@ -82,6 +96,11 @@ pub enum GetAttributeError {
 
 pub type Result<T, E = GetAttributeError> = std::result::Result<T, E>;
 
+/// Create the error for a required attribute which is missing.
+pub(crate) fn missing_required(name: AttributeName) -> GetAttributeError {
+    MissingRequiredSnafu { name }.build()
+}
+
 /// Get the Columns from the DICOM object
 pub fn cols<D: DataDictionary + Clone>(obj: &FileDicomObject<InMemDicomObject<D>>) -> Result<u16> {
     retrieve_required_u16(obj, tags::COLUMNS, AttributeName::Columns)
@@ -113,11 +132,11 @@ pub fn voi_lut_function<D: DataDictionary + Clone>(
             .iter()
             .map(|el| {
                 (*el)
-                    .string()
+                    .trimmed_str()
                     .context(CastValueSnafu {
                         name: AttributeName::VoiLutFunction,
                     })
-                    .map(|v| v.trim().to_string())
+                    .map(|v| v.to_string())
             })
             .collect::<Result<Vec<_>>>()?;
         Ok(Some(res))
@@ -126,6 +145,22 @@ pub fn voi_lut_function<D: DataDictionary + Clone>(
     }
 }
 
+/// Get the values of a multi-valued VOILUTFunction from the DICOM object,
+/// each describing the function of the alternative window level
+/// at the same position.
+///
+/// Returns `None` if the attribute is missing or single-valued,
+/// in which case the single function applies to all window levels.
+pub fn window_voi_lut_functions<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+) -> Option<Vec<String>> {
+    let values = obj.element(tags::VOILUT_FUNCTION).ok()?.strings().ok()?;
+    if values.len() < 2 {
+        return None;
+    }
+    Some(values.iter().map(|v| trim_padding(v).to_string()).collect())
+}
+
 /// Get the SamplesPerPixel from the DICOM object
 pub fn samples_per_pixel<D: DataDictionary + Clone>(
     obj: &FileDicomObject<InMemDicomObject<D>>,
@@ -154,14 +189,41 @@ pub fn high_bit<D: DataDictionary + Clone>(
     retrieve_required_u16(obj, tags::HIGH_BIT, AttributeName::HighBit)
 }
 
-/// Get the PixelData element from the DICOM object
+/// Get the PixelData element from the DICOM object,
+/// falling back to _Float Pixel Data_ or _Double Float Pixel Data_
+/// if there is no _Pixel Data_
 pub fn pixel_data<D: DataDictionary + Clone>(
     obj: &FileDicomObject<InMemDicomObject<D>>,
 ) -> Result<&InMemElement<D>> {
     let name = AttributeName::PixelData;
-    obj.element_opt(tags::PIXEL_DATA)
-        .context(RetrieveSnafu { name })?
-        .context(MissingRequiredSnafu { name })
+    for tag in [
+        tags::PIXEL_DATA,
+        tags::FLOAT_PIXEL_DATA,
+        tags::DOUBLE_FLOAT_PIXEL_DATA,
+    ] {
+        if let Some(elem) = obj.element_opt(tag).context(RetrieveSnafu { name })? {
+            return Ok(elem);
+        }
+    }
+    MissingRequiredSnafu { name }.fail()
+}
+
+/// Get the format of the floating point samples in the DICOM object,
+/// if it has _Float Pixel Data_ or _Double Float Pixel Data_
+/// instead of _Pixel Data_
+pub fn float_sample_format<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+) -> Option<SampleFormat> {
+    let has = |tag| matches!(obj.element_opt(tag), Ok(Some(_)));
+    if has(tags::PIXEL_DATA) {
+        None
+    } else if has(tags::FLOAT_PIXEL_DATA) {
+        Some(SampleFormat::Float32)
+    } else if has(tags::DOUBLE_FLOAT_PIXEL_DATA) {
+        Some(SampleFormat::Float64)
+    } else {
+        None
+    }
 }
 
 fn get_from_shared<D: DataDictionary + Clone>(
@@ -296,48 +358,341 @@ pub fn number_of_frames<D: DataDictionary + Clone>(
     Ok(integer as u32)
 }
 
-/// Retrieve the WindowCenter from the DICOM object if it exists.
-pub fn window_center<D: DataDictionary + Clone>(
+/// Values of a multi-valued attribute which may be defined
+/// for all frames or for each frame individually.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameValues {
+    /// Values defined in the main data set
+    /// or in the shared functional groups,
+    /// applicable to all frames
+    Shared(Vec<f64>),
+    /// Values defined in the per-frame functional groups,
+    /// one list of values for each frame
+    PerFrame(Vec<Vec<f64>>),
+}
+
+impl FrameValues {
+    /// Turn into one list of values for each frame,
+    /// where shared values become a single list.
+    pub fn into_per_frame(self) -> Vec<Vec<f64>> {
+        match self {
+            FrameValues::Shared(values) => vec![values],
+            FrameValues::PerFrame(values) => values,
+        }
+    }
+}
+
+fn multi_float64_values<D: DataDictionary + Clone>(
     obj: &FileDicomObject<InMemDicomObject<D>>,
-) -> Option<Vec<f64>> {
-    let wc = obj
-        .get(tags::WINDOW_CENTER)
-        .and_then(|e| {
-            vec![e.to_float64().ok()]
-                .into_iter()
-                .collect::<Option<Vec<f64>>>()
-        })
+    tag: Tag,
+) -> Option<FrameValues> {
+    obj.get(tag)
+        .and_then(|e| e.to_multi_float64().ok())
+        .map(FrameValues::Shared)
         .or_else(|| {
-            get_from_per_frame(obj, [tags::FRAME_VOILUT_SEQUENCE, tags::WINDOW_CENTER])
-                .and_then(|v| v.into_iter().map(|el| el.to_float64().ok()).collect())
+            get_from_per_frame(obj, [tags::FRAME_VOILUT_SEQUENCE, tag])
+                .and_then(|v| {
+                    v.into_iter()
+                        .map(|el| el.to_multi_float64().ok())
+                        .collect::<Option<Vec<_>>>()
+                })
+                .filter(|values| !values.is_empty())
+                .map(FrameValues::PerFrame)
         })
         .or_else(|| {
-            get_from_shared(obj, [tags::FRAME_VOILUT_SEQUENCE, tags::WINDOW_CENTER])
-                .and_then(|v| v.into_iter().map(|el| el.to_float64().ok()).collect())
-        });
-    wc
+            get_from_shared(obj, [tags::FRAME_VOILUT_SEQUENCE, tag])
+                .and_then(|mut v| v.next())
+                .and_then(|el| el.to_multi_float64().ok())
+                .map(FrameValues::Shared)
+        })
+}
+
+/// Retrieve the WindowCenter from the DICOM object if it exists.
+///
+/// Multiple values in the same frame are alternative window centers.
+pub fn window_center<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+) -> Option<FrameValues> {
+    multi_float64_values(obj, tags::WINDOW_CENTER)
 }
 
 /// Retrieve the WindowWidth from the DICOM object if it exists.
+///
+/// Multiple values in the same frame are alternative window widths.
 pub fn window_width<D: DataDictionary + Clone>(
     obj: &FileDicomObject<InMemDicomObject<D>>,
-) -> Option<Vec<f64>> {
-    let ww = obj
-        .get(tags::WINDOW_WIDTH)
-        .and_then(|e| {
-            vec![e.to_float64().ok()]
-                .into_iter()
-                .collect::<Option<Vec<f64>>>()
-        })
-        .or_else(|| {
-            get_from_per_frame(obj, [tags::FRAME_VOILUT_SEQUENCE, tags::WINDOW_WIDTH])
-                .and_then(|v| v.into_iter().map(|el| el.to_float64().ok()).collect())
+) -> Option<FrameValues> {
+    multi_float64_values(obj, tags::WINDOW_WIDTH)
+}
+
+/// Retrieve the Pixel Padding Value and the Pixel Padding Range Limit
+/// from the DICOM object, if the padding value exists.
+///
+/// Both values are interpreted as signed
+/// if `signed` is true (_Pixel Representation_ is 1),
+/// regardless of their value representation.
+pub fn pixel_padding<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+    signed: bool,
+) -> Option<PixelPadding> {
+    let stored_value = |tag| {
+        let value = obj.get(tag)?.to_int::<i32>().ok()?;
+        Some(if signed {
+            i32::from(value as u16 as i16)
+        } else {
+            i32::from(value as u16)
         })
-        .or_else(|| {
-            get_from_shared(obj, [tags::FRAME_VOILUT_SEQUENCE, tags::WINDOW_WIDTH])
-                .and_then(|v| v.into_iter().map(|el| el.to_float64().ok()).collect())
-        });
-    ww
+    };
+    Some(PixelPadding {
+        value: stored_value(tags::PIXEL_PADDING_VALUE)?,
+        range_limit: stored_value(tags::PIXEL_PADDING_RANGE_LIMIT),
+    })
+}
+
+/// Retrieve the tabular Modality LUT in the Modality LUT Sequence
+/// of the DICOM object, if it defines one.
+///
+/// The first value mapped by the table is interpreted as signed
+/// if `signed` is true (_Pixel Representation_ is 1),
+/// regardless of the value representation of the _LUT Descriptor_.
+pub fn modality_lut_sequence<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+    signed: bool,
+) -> Result<Option<ModalityLut>> {
+    let Some(item) = obj
+        .get(tags::MODALITY_LUT_SEQUENCE)
+        .and_then(|e| e.items())
+        .and_then(|items| items.first())
+    else {
+        return Ok(None);
+    };
+    let (entries, first_mapped, bits) = lut_descriptor(
+        item.get(tags::LUT_DESCRIPTOR),
+        AttributeName::LutDescriptor,
+        signed,
+    )?;
+    let data = lut_data(
+        item.get(tags::LUT_DATA),
+        AttributeName::LutData,
+        entries,
+        bits,
+    )?;
+
+    let text = |tag| {
+        item.get(tag)
+            .and_then(|e| e.trimmed_str().ok())
+            .map(|s| s.to_string())
+    };
+
+    Ok(Some(ModalityLut {
+        first_mapped,
+        bits,
+        data,
+        lut_type: text(tags::MODALITY_LUT_TYPE),
+        explanation: text(tags::LUT_EXPLANATION),
+    }))
+}
+
+/// Retrieve the tabular VOI LUTs in the VOI LUT Sequence of the DICOM object,
+/// or an empty list if the object does not define any.
+///
+/// The first value mapped by each table is interpreted as signed
+/// if `signed` is true (_Pixel Representation_ is 1),
+/// regardless of the value representation of the _LUT Descriptor_.
+pub fn voi_lut_sequence<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+    signed: bool,
+) -> Result<Vec<VoiLut>> {
+    let Some(items) = obj.get(tags::VOILUT_SEQUENCE).and_then(|e| e.items()) else {
+        return Ok(Vec::new());
+    };
+    items.iter().map(|item| voi_lut(item, signed)).collect()
+}
+
+fn voi_lut<D: DataDictionary + Clone>(item: &InMemDicomObject<D>, signed: bool) -> Result<VoiLut> {
+    let (entries, first_mapped, bits) = lut_descriptor(
+        item.get(tags::LUT_DESCRIPTOR),
+        AttributeName::LutDescriptor,
+        signed,
+    )?;
+    let data = lut_data(
+        item.get(tags::LUT_DATA),
+        AttributeName::LutData,
+        entries,
+        bits,
+    )?;
+
+    let explanation = item
+        .get(tags::LUT_EXPLANATION)
+        .and_then(|e| e.trimmed_str().ok())
+        .map(|s| s.to_string());
+
+    Ok(VoiLut {
+        first_mapped,
+        bits,
+        data,
+        explanation,
+    })
+}
+
+/// Get the red, green, and blue palette color lookup tables
+/// from the DICOM object,
+/// as required by the _PALETTE COLOR_ photometric interpretation.
+///
+/// The first value mapped by the tables is interpreted as signed
+/// if `signed` is true (_Pixel Representation_ is 1),
+/// regardless of the value representation of the descriptors.
+/// Tables of 8-bit entries stored in the upper byte of 16-bit words
+/// are brought down to the lower byte.
+pub fn palette_color_lut<D: DataDictionary + Clone>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+    signed: bool,
+) -> Result<PaletteColorLut> {
+    let (red_entries, first_mapped, bits) = lut_descriptor(
+        obj.get(tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR),
+        AttributeName::RedPaletteColorLookupTableDescriptor,
+        signed,
+    )?;
+    let (green_entries, ..) = lut_descriptor(
+        obj.get(tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR),
+        AttributeName::GreenPaletteColorLookupTableDescriptor,
+        signed,
+    )?;
+    let (blue_entries, ..) = lut_descriptor(
+        obj.get(tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR),
+        AttributeName::BluePaletteColorLookupTableDescriptor,
+        signed,
+    )?;
+
+    let mut red = lut_data(
+        obj.get(tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DATA),
+        AttributeName::RedPaletteColorLookupTableData,
+        red_entries,
+        bits,
+    )?;
+    let mut green = lut_data(
+        obj.get(tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DATA),
+        AttributeName::GreenPaletteColorLookupTableData,
+        green_entries,
+        bits,
+    )?;
+    let mut blue = lut_data(
+        obj.get(tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DATA),
+        AttributeName::BluePaletteColorLookupTableData,
+        blue_entries,
+        bits,
+    )?;
+
+    if bits <= 8
+        && [&red, &green, &blue]
+            .iter()
+            .flat_map(|t| t.iter())
+            .any(|e| *e > 0xFF)
+    {
+        for entry in red.iter_mut().chain(&mut green).chain(&mut blue) {
+            *entry >>= 8;
+        }
+    }
+
+    Ok(PaletteColorLut {
+        first_mapped,
+        bits,
+        red,
+        green,
+        blue,
+    })
+}
+
+/// Interpret the given element as a LUT descriptor,
+/// yielding the number of entries,
+/// the first mapped value,
+/// and the number of bits of each entry.
+fn lut_descriptor<D: DataDictionary + Clone>(
+    elem: Option<&InMemElement<D>>,
+    name: AttributeName,
+    signed: bool,
+) -> Result<(usize, i32, u16)> {
+    let descriptor: Vec<i32> = elem
+        .context(MissingRequiredSnafu { name })?
+        .to_multi_int()
+        .context(ConvertValueSnafu { name })?;
+    let &[entries, first_mapped, bits] = &descriptor[..] else {
+        return InvalidValueSnafu {
+            name,
+            value: format!("{:?}", descriptor),
+        }
+        .fail();
+    };
+    // the number of entries is always unsigned,
+    // with 0 meaning 2^16 entries
+    let entries = match entries as u16 {
+        0 => 0x1_0000,
+        n => usize::from(n),
+    };
+    let first_mapped = if signed {
+        i32::from(first_mapped as u16 as i16)
+    } else {
+        i32::from(first_mapped as u16)
+    };
+    ensure!(
+        (1..=16).contains(&bits),
+        InvalidValueSnafu {
+            name,
+            value: bits.to_string(),
+        }
+    );
+    Ok((entries, first_mapped, bits as u16))
+}
+
+/// Interpret the given element as the data of a LUT
+/// with the given number of entries and bits per entry.
+fn lut_data<D: DataDictionary + Clone>(
+    elem: Option<&InMemElement<D>>,
+    name: AttributeName,
+    entries: usize,
+    bits: u16,
+) -> Result<Vec<u16>> {
+    let elem = elem.context(MissingRequiredSnafu { name })?;
+    let data: Vec<u16> = match elem.value().primitive() {
+        Some(PrimitiveValue::U16(words)) if bits <= 8 && words.len() * 2 == entries => {
+            // two 8-bit entries packed in each word
+            words
+                .iter()
+                .flat_map(|w| w.to_le_bytes())
+                .map(u16::from)
+                .collect()
+        }
+        Some(PrimitiveValue::U16(words)) => words.to_vec(),
+        Some(PrimitiveValue::I16(words)) => words.iter().map(|w| *w as u16).collect(),
+        Some(PrimitiveValue::U8(bytes)) => {
+            if bits <= 8 && bytes.len() == entries {
+                // one byte per 8-bit entry
+                bytes.iter().map(|b| u16::from(*b)).collect()
+            } else {
+                bytes
+                    .chunks_exact(2)
+                    .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                    .collect()
+            }
+        }
+        _ => elem.to_multi_int().context(ConvertValueSnafu { name })?,
+    };
+    ensure!(
+        !data.is_empty(),
+        InvalidValueSnafu {
+            name,
+            value: String::new(),
+        }
+    );
+    if data.len() != entries {
+        tracing::warn!(
+            "Expected {} entries in {}, found {}",
+            entries,
+            name,
+            data.len()
+        );
+    }
+    Ok(data)
 }
 
 #[inline]
@@ -366,6 +721,50 @@ pub enum PixelRepresentation {
     Signed = 1,
 }
 
+/// The numeric format of the pixel data samples,
+/// as implied by the _Pixel Representation_
+/// or by the presence of floating point pixel data.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum SampleFormat {
+    /// unsigned integer samples
+    Unsigned,
+    /// signed integer samples
+    Signed,
+    /// 32-bit floating point samples, from _Float Pixel Data_
+    Float32,
+    /// 64-bit floating point samples, from _Double Float Pixel Data_
+    Float64,
+}
+
+impl SampleFormat {
+    /// Whether the samples are floating point numbers.
+    #[inline]
+    pub fn is_float(self) -> bool {
+        matches!(self, SampleFormat::Float32 | SampleFormat::Float64)
+    }
+
+    /// The number of bits allocated for each floating point sample,
+    /// or `None` if the samples are integers.
+    #[inline]
+    pub fn float_bits(self) -> Option<u16> {
+        match self {
+            SampleFormat::Float32 => Some(32),
+            SampleFormat::Float64 => Some(64),
+            SampleFormat::Unsigned | SampleFormat::Signed => None,
+        }
+    }
+}
+
+impl From<PixelRepresentation> for SampleFormat {
+    fn from(pixel_representation: PixelRepresentation) -> Self {
+        match pixel_representation {
+            PixelRepresentation::Unsigned => SampleFormat::Unsigned,
+            PixelRepresentation::Signed => SampleFormat::Signed,
+        }
+    }
+}
+
 /// Get the PixelRepresentation from the DICOM object
 pub fn pixel_representation<D: DataDictionary + Clone>(
     obj: &FileDicomObject<InMemDicomObject<D>>,
@@ -407,7 +806,6 @@ impl fmt::Display for PlanarConfiguration {
 
 /// Get the PlanarConfiguration from the DICOM object,
 /// returning the standard planar configuration by default
-#[cfg(not(feature = "gdcm"))]
 pub fn planar_configuration<D: DataDictionary + Clone>(
     obj: &FileDicomObject<InMemDicomObject<D>>,
 ) -> Result<PlanarConfiguration> {
@@ -483,6 +881,12 @@ pub enum PhotometricInterpretation {
     /// horizontally and vertically at half the Y rate
     /// and as a result there are four times less CB and CR values than Y values.
     YbrPartial420,
+    /// `YBR_PARTIAL_422`:
+    /// The same as YBR_FULL_422 except that the Y, CB and CR values
+    /// are restricted to a partial range.
+    /// This photometric interpretation is retired,
+    /// but can still be found in existing data.
+    YbrPartial422,
     /// `YBR_ICT`:
     /// Irreversible Color Transformation.
     /// Pixel data represent a color image described by
@@ -525,6 +929,25 @@ impl PhotometricInterpretation {
             PhotometricInterpretation::Monochrome1 | PhotometricInterpretation::Monochrome2
         )
     }
+
+    /// Get the number of samples per pixel
+    /// which this photometric interpretation requires,
+    /// or `None` if it is not known.
+    pub fn samples_per_pixel(&self) -> Option<u16> {
+        match self {
+            PhotometricInterpretation::Monochrome1
+            | PhotometricInterpretation::Monochrome2
+            | PhotometricInterpretation::PaletteColor => Some(1),
+            PhotometricInterpretation::Rgb
+            | PhotometricInterpretation::YbrFull
+            | PhotometricInterpretation::YbrFull422
+            | PhotometricInterpretation::YbrPartial420
+            | PhotometricInterpretation::YbrPartial422
+            | PhotometricInterpretation::YbrIct
+            | PhotometricInterpretation::YbrRct => Some(3),
+            PhotometricInterpretation::Other(_) => None,
+        }
+    }
 }
 
 impl AsRef<str> for PhotometricInterpretation {
@@ -537,6 +960,7 @@ impl AsRef<str> for PhotometricInterpretation {
             PhotometricInterpretation::YbrFull => "YBR_FULL",
             PhotometricInterpretation::YbrFull422 => "YBR_FULL_422",
             PhotometricInterpretation::YbrPartial420 => "YBR_PARTIAL_420",
+            PhotometricInterpretation::YbrPartial422 => "YBR_PARTIAL_422",
             PhotometricInterpretation::YbrIct => "YBR_ICT",
             PhotometricInterpretation::YbrRct => "YBR_RCT",
             PhotometricInterpretation::Other(s) => s,
@@ -544,8 +968,10 @@ impl AsRef<str> for PhotometricInterpretation {
     }
 }
 
+/// Trailing padding (space and null characters) is ignored.
 impl From<String> for PhotometricInterpretation {
-    fn from(s: String) -> Self {
+    fn from(mut s: String) -> Self {
+        s.truncate(trim_padding(&s).len());
         match s.as_str() {
             "MONOCHROME1" => PhotometricInterpretation::Monochrome1,
             "MONOCHROME2" => PhotometricInterpretation::Monochrome2,
@@ -554,6 +980,7 @@ impl From<String> for PhotometricInterpretation {
             "YBR_FULL" => PhotometricInterpretation::YbrFull,
             "YBR_FULL_422" => PhotometricInterpretation::YbrFull422,
             "YBR_PARTIAL_420" => PhotometricInterpretation::YbrPartial420,
+            "YBR_PARTIAL_422" => PhotometricInterpretation::YbrPartial422,
             "YBR_ICT" => PhotometricInterpretation::YbrIct,
             "YBR_RCT" => PhotometricInterpretation::YbrRct,
             _ => PhotometricInterpretation::Other(s),
@@ -561,8 +988,10 @@ impl From<String> for PhotometricInterpretation {
     }
 }
 
+/// Trailing padding (space and null characters) is ignored.
 impl From<&str> for PhotometricInterpretation {
     fn from(s: &str) -> Self {
+        let s = trim_padding(s);
         match s {
             "MONOCHROME1" => PhotometricInterpretation::Monochrome1,
             "MONOCHROME2" => PhotometricInterpretation::Monochrome2,
@@ -571,6 +1000,7 @@ impl From<&str> for PhotometricInterpretation {
             "YBR_FULL" => PhotometricInterpretation::YbrFull,
             "YBR_FULL_422" => PhotometricInterpretation::YbrFull422,
             "YBR_PARTIAL_420" => PhotometricInterpretation::YbrPartial420,
+            "YBR_PARTIAL_422" => PhotometricInterpretation::YbrPartial422,
             "YBR_ICT" => PhotometricInterpretation::YbrIct,
             "YBR_RCT" => PhotometricInterpretation::YbrRct,
             _ => PhotometricInterpretation::Other(s.to_string()),
@@ -578,6 +1008,17 @@ impl From<&str> for PhotometricInterpretation {
     }
 }
 
+/// Trailing padding (space and null characters) is ignored.
+/// Parsing never fails,
+/// as unrecognized values are kept in [`Other`](PhotometricInterpretation::Other).
+impl FromStr for PhotometricInterpretation {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.into())
+    }
+}
+
 impl fmt::Display for PhotometricInterpretation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -588,6 +1029,7 @@ impl fmt::Display for PhotometricInterpretation {
             PhotometricInterpretation::YbrFull => f.write_str("YBR_FULL"),
             PhotometricInterpretation::YbrFull422 => f.write_str("YBR_FULL_422"),
             PhotometricInterpretation::YbrPartial420 => f.write_str("YBR_PARTIAL_420"),
+            PhotometricInterpretation::YbrPartial422 => f.write_str("YBR_PARTIAL_422"),
             PhotometricInterpretation::YbrIct => f.write_str("YBR_ICT"),
             PhotometricInterpretation::YbrRct => f.write_str("YBR_RCT"),
             PhotometricInterpretation::Other(s) => f.write_str(s),
@@ -604,15 +1046,18 @@ pub fn photometric_interpretation<D: DataDictionary + Clone>(
         .element_opt(tags::PHOTOMETRIC_INTERPRETATION)
         .context(RetrieveSnafu { name })?
         .context(MissingRequiredSnafu { name })?
-        .string()
+        .trimmed_str()
         .context(CastValueSnafu { name })?
-        .trim_matches(|c: char| c.is_whitespace() || c == '\0')
         .into())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::rescale_intercept;
+    use super::{
+        palette_color_lut, photometric_interpretation, pixel_padding, rescale_intercept,
+        voi_lut_sequence, AttributeName, GetAttributeError, PhotometricInterpretation,
+        PixelPadding,
+    };
     use dicom_core::{
         dicom_value,
         ops::{ApplyOp, AttributeAction, AttributeOp},
@@ -754,4 +1199,309 @@ mod tests {
         ));
         assert_eq!(rescale_intercept(&dcm), exp);
     }
+
+    #[test]
+    fn padded_photometric_interpretation() {
+        use dicom_encoding::adapters::PixelDataObject;
+
+        for padded in ["RGB ", "RGB\0"] {
+            let mut dcm = dummy_dicom();
+            dcm.put(DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, padded),
+            ));
+            assert_eq!(
+                photometric_interpretation(&dcm).unwrap(),
+                PhotometricInterpretation::Rgb
+            );
+            assert_eq!(
+                PixelDataObject::photometric_interpretation(&dcm),
+                Some("RGB")
+            );
+        }
+    }
+
+    #[test]
+    fn photometric_interpretation_from_padded_str() {
+        assert_eq!(
+            "RGB ".parse::<PhotometricInterpretation>(),
+            Ok(PhotometricInterpretation::Rgb)
+        );
+        assert_eq!(
+            PhotometricInterpretation::from(String::from("MONOCHROME2\0")),
+            PhotometricInterpretation::Monochrome2
+        );
+        assert_eq!(
+            PhotometricInterpretation::from("CUSTOM \0"),
+            PhotometricInterpretation::Other("CUSTOM".to_string())
+        );
+        // leading characters are never trimmed
+        assert_eq!(
+            " RGB".parse::<PhotometricInterpretation>(),
+            Ok(PhotometricInterpretation::Other(" RGB".to_string()))
+        );
+    }
+
+    #[test]
+    fn pixel_padding_signedness() {
+        let mut dcm = dummy_dicom();
+        assert_eq!(pixel_padding(&dcm, true), None);
+
+        // unsigned value on signed pixel data
+        dcm.put(DataElement::new(
+            tags::PIXEL_PADDING_VALUE,
+            VR::US,
+            dicom_value!(U16, [0xF830]),
+        ));
+        assert_eq!(
+            pixel_padding(&dcm, true),
+            Some(PixelPadding {
+                value: -2000,
+                range_limit: None,
+            })
+        );
+        assert_eq!(
+            pixel_padding(&dcm, false),
+            Some(PixelPadding {
+                value: 0xF830,
+                range_limit: None,
+            })
+        );
+
+        // signed values on signed pixel data
+        dcm.put(DataElement::new(
+            tags::PIXEL_PADDING_VALUE,
+            VR::SS,
+            dicom_value!(I16, [-2000]),
+        ));
+        dcm.put(DataElement::new(
+            tags::PIXEL_PADDING_RANGE_LIMIT,
+            VR::SS,
+            dicom_value!(I16, [-1025]),
+        ));
+        assert_eq!(
+            pixel_padding(&dcm, true),
+            Some(PixelPadding {
+                value: -2000,
+                range_limit: Some(-1025),
+            })
+        );
+    }
+
+    fn dicom_with_voi_lut(
+        descriptor: PrimitiveValue,
+        data: (VR, PrimitiveValue),
+    ) -> DefaultDicomObject {
+        let mut dcm = dummy_dicom();
+        let descriptor_vr = match descriptor {
+            PrimitiveValue::I16(_) => VR::SS,
+            _ => VR::US,
+        };
+        dcm.put(DataElement::new(
+            tags::VOILUT_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                DataElement::new(tags::LUT_DESCRIPTOR, descriptor_vr, descriptor),
+                DataElement::new(tags::LUT_EXPLANATION, VR::LO, dicom_value!(Str, "NORMAL ")),
+                DataElement::new(tags::LUT_DATA, data.0, data.1),
+            ])]),
+        ));
+        dcm
+    }
+
+    #[test]
+    fn voi_lut_sequence_absent() {
+        assert!(voi_lut_sequence(&dummy_dicom(), false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn voi_lut_sequence_first_mapped_value() {
+        // unsigned descriptor value on signed pixel data
+        let dcm = dicom_with_voi_lut(
+            dicom_value!(U16, [3, 0xFC00, 16]),
+            (VR::US, dicom_value!(U16, [0, 0x8000, 0xFFFF])),
+        );
+        let luts = voi_lut_sequence(&dcm, true).unwrap();
+        assert_eq!(luts.len(), 1);
+        assert_eq!(luts[0].first_mapped, -1024);
+        assert_eq!(luts[0].bits, 16);
+        assert_eq!(luts[0].data, vec![0, 0x8000, 0xFFFF]);
+        assert_eq!(luts[0].explanation.as_deref(), Some("NORMAL"));
+
+        // same descriptor on unsigned pixel data
+        let luts = voi_lut_sequence(&dcm, false).unwrap();
+        assert_eq!(luts[0].first_mapped, 0xFC00);
+
+        // signed descriptor value on signed pixel data
+        let dcm = dicom_with_voi_lut(
+            dicom_value!(I16, [3, -1024, 16]),
+            (VR::US, dicom_value!(U16, [0, 0x8000, 0xFFFF])),
+        );
+        let luts = voi_lut_sequence(&dcm, true).unwrap();
+        assert_eq!(luts[0].first_mapped, -1024);
+    }
+
+    #[test]
+    fn voi_lut_sequence_8bit_data() {
+        // one 8-bit entry per word
+        let dcm = dicom_with_voi_lut(
+            dicom_value!(U16, [4, 0, 8]),
+            (VR::US, dicom_value!(U16, [0, 0x40, 0x80, 0xFF])),
+        );
+        let luts = voi_lut_sequence(&dcm, false).unwrap();
+        assert_eq!(luts[0].bits, 8);
+        assert_eq!(luts[0].data, vec![0, 0x40, 0x80, 0xFF]);
+
+        // two 8-bit entries packed in each word
+        let dcm = dicom_with_voi_lut(
+            dicom_value!(U16, [4, 0, 8]),
+            (VR::OW, dicom_value!(U16, [0x4000, 0xFF80])),
+        );
+        let luts = voi_lut_sequence(&dcm, false).unwrap();
+        assert_eq!(luts[0].data, vec![0, 0x40, 0x80, 0xFF]);
+
+        // one byte per entry
+        let dcm = dicom_with_voi_lut(
+            dicom_value!(U16, [4, 0, 8]),
+            (VR::OB, dicom_value!(U8, [0, 0x40, 0x80, 0xFF])),
+        );
+        let luts = voi_lut_sequence(&dcm, false).unwrap();
+        assert_eq!(luts[0].data, vec![0, 0x40, 0x80, 0xFF]);
+    }
+
+    #[test]
+    fn voi_lut_sequence_invalid_descriptor() {
+        let dcm = dicom_with_voi_lut(
+            dicom_value!(U16, [4, 0]),
+            (VR::US, dicom_value!(U16, [0, 1, 2, 3])),
+        );
+        assert!(matches!(
+            voi_lut_sequence(&dcm, false),
+            Err(GetAttributeError::InvalidValue {
+                name: AttributeName::LutDescriptor,
+                ..
+            })
+        ));
+
+        let dcm = dicom_with_voi_lut(
+            dicom_value!(U16, [4, 0, 24]),
+            (VR::US, dicom_value!(U16, [0, 1, 2, 3])),
+        );
+        assert!(voi_lut_sequence(&dcm, false).is_err());
+    }
+
+    fn dicom_with_palette(
+        descriptor: [u16; 3],
+        red: &[u16],
+        green: &[u16],
+        blue: &[u16],
+    ) -> DefaultDicomObject {
+        let mut dcm = dummy_dicom();
+        for (descriptor_tag, data_tag, data) in [
+            (
+                tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+                tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+                red,
+            ),
+            (
+                tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+                tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+                green,
+            ),
+            (
+                tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+                tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+                blue,
+            ),
+        ] {
+            dcm.put(DataElement::new(
+                descriptor_tag,
+                VR::US,
+                PrimitiveValue::U16(descriptor.iter().copied().collect()),
+            ));
+            dcm.put(DataElement::new(
+                data_tag,
+                VR::OW,
+                PrimitiveValue::U16(data.iter().copied().collect()),
+            ));
+        }
+        dcm
+    }
+
+    #[test]
+    fn palette_color_lut_16bit() {
+        let dcm = dicom_with_palette(
+            [3, 0xFFFF, 16],
+            &[0, 0x8000, 0xFFFF],
+            &[0xFFFF, 0x8000, 0],
+            &[0x1000, 0x2000, 0x3000],
+        );
+        let lut = palette_color_lut(&dcm, true).unwrap();
+        assert_eq!(lut.first_mapped, -1);
+        assert_eq!(lut.bits, 16);
+        assert_eq!(lut.red, vec![0, 0x8000, 0xFFFF]);
+        assert_eq!(lut.green, vec![0xFFFF, 0x8000, 0]);
+        assert_eq!(lut.blue, vec![0x1000, 0x2000, 0x3000]);
+
+        let lut = palette_color_lut(&dcm, false).unwrap();
+        assert_eq!(lut.first_mapped, 0xFFFF);
+    }
+
+    #[test]
+    fn palette_color_lut_8bit_in_upper_byte() {
+        // 8-bit entries wrongly stored in the upper byte of each word
+        let dcm = dicom_with_palette(
+            [4, 0, 8],
+            &[0x0000, 0x4000, 0x8000, 0xFF00],
+            &[0x0000, 0x0000, 0x0000, 0x0000],
+            &[0xFF00, 0x8000, 0x4000, 0x0000],
+        );
+        let lut = palette_color_lut(&dcm, false).unwrap();
+        assert_eq!(lut.bits, 8);
+        assert_eq!(lut.red, vec![0, 0x40, 0x80, 0xFF]);
+        assert_eq!(lut.green, vec![0, 0, 0, 0]);
+        assert_eq!(lut.blue, vec![0xFF, 0x80, 0x40, 0]);
+
+        // 8-bit entries in the lower byte are kept as is
+        let dcm = dicom_with_palette(
+            [4, 0, 8],
+            &[0, 0x40, 0x80, 0xFF],
+            &[0, 0, 0, 0],
+            &[0xFF, 0x80, 0x40, 0],
+        );
+        let lut = palette_color_lut(&dcm, false).unwrap();
+        assert_eq!(lut.red, vec![0, 0x40, 0x80, 0xFF]);
+        assert_eq!(lut.blue, vec![0xFF, 0x80, 0x40, 0]);
+    }
+
+    #[test]
+    fn palette_color_lut_65536_entries() {
+        let table: Vec<u16> = (0..=u16::MAX).collect();
+        let dcm = dicom_with_palette([0, 0, 16], &table, &table, &table);
+        let lut = palette_color_lut(&dcm, false).unwrap();
+        assert_eq!(lut.red.len(), 0x1_0000);
+        assert_eq!(lut.apply(0x1234), [0x1234; 3]);
+    }
+
+    #[test]
+    fn palette_color_lut_missing() {
+        assert!(matches!(
+            palette_color_lut(&dummy_dicom(), false),
+            Err(GetAttributeError::MissingRequired {
+                name: AttributeName::RedPaletteColorLookupTableDescriptor,
+                ..
+            })
+        ));
+
+        let mut dcm = dicom_with_palette([2, 0, 16], &[0, 1], &[0, 1], &[0, 1]);
+        dcm.remove_element(tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DATA);
+        assert!(matches!(
+            palette_color_lut(&dcm, false),
+            Err(GetAttributeError::MissingRequired {
+                name: AttributeName::BluePaletteColorLookupTableData,
+                ..
+            })
+        ));
+    }
 }
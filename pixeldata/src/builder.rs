@@ -0,0 +1,520 @@
+//! Construction of native pixel data from multi-dimensional arrays.
+//!
+//! [`PixelDataBuilder`] takes an [`ndarray`] array of samples
+//! in the same layout as produced by
+//! [`DecodedPixelData::to_ndarray`](crate::DecodedPixelData::to_ndarray),
+//! that is, with the dimensions _(frames, rows, columns, samples)_,
+//! and writes the image pixel attributes
+//! and the _Pixel Data_ element into a DICOM object.
+//!
+//! The pixel data is always native (not encapsulated),
+//! with the color samples interleaved (_Planar Configuration_ 0),
+//! so the object should be written with a native transfer syntax,
+//! such as _Explicit VR Little Endian_.
+//!
+//! # Example
+//!
+//! ```
+//! use dicom_object::InMemDicomObject;
+//! use dicom_pixeldata::{ndarray::Array3, PixelDataBuilder};
+//!
+//! // a single 64x64 monochrome frame with a horizontal gradient
+//! let frame = Array3::from_shape_fn((64, 64, 1), |(_, x, _)| (x * 1000) as u16);
+//!
+//! let mut obj = InMemDicomObject::new_empty();
+//! PixelDataBuilder::from_ndarray_frame(frame)?
+//!     .bits_stored(16)
+//!     .apply_to(&mut obj)?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{PhotometricInterpretation, PixelRepresentation};
+use dicom_core::value::PrimitiveValue;
+use dicom_core::{DataDictionary, DataElement, Length, VR};
+use dicom_dictionary_std::tags;
+use dicom_object::InMemDicomObject;
+use ndarray::{ArrayBase, Axis, Data, Ix3, Ix4};
+use snafu::{ensure, Backtrace, Snafu};
+use std::convert::TryFrom;
+
+/// An error which may occur when building pixel data from an array.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum BuildPixelDataError {
+    #[snafu(display(
+        "Array has {} samples per pixel, but only 1 (monochrome) or 3 (color) are supported",
+        samples
+    ))]
+    InvalidSamplesPerPixel {
+        samples: usize,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Array has no {}", name))]
+    EmptyDimension {
+        name: &'static str,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Array has {} {}, more than the maximum of {}", len, name, max))]
+    DimensionTooLarge {
+        name: &'static str,
+        len: usize,
+        max: u64,
+        backtrace: Backtrace,
+    },
+    #[snafu(display(
+        "Photometric interpretation {} is not applicable to {} samples per pixel",
+        photometric_interpretation,
+        samples
+    ))]
+    InconsistentPhotometricInterpretation {
+        photometric_interpretation: String,
+        samples: u16,
+        backtrace: Backtrace,
+    },
+    #[snafu(display(
+        "Bits stored {} is not within 1 and bits allocated ({})",
+        bits_stored,
+        bits_allocated
+    ))]
+    InvalidBitsStored {
+        bits_stored: u16,
+        bits_allocated: u16,
+        backtrace: Backtrace,
+    },
+}
+
+pub type Result<T, E = BuildPixelDataError> = std::result::Result<T, E>;
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for i16 {}
+}
+
+/// A sample type which can be turned into native pixel data.
+///
+/// This trait is sealed,
+/// and implemented for `u8`, `u16`, and `i16`.
+pub trait PixelSample: Copy + private::Sealed {
+    /// The number of bits allocated to each sample.
+    const BITS_ALLOCATED: u16;
+
+    /// The pixel representation of the samples.
+    const PIXEL_REPRESENTATION: PixelRepresentation;
+
+    /// The value representation of the pixel data element.
+    const VR: VR;
+
+    /// Collect the samples into the value of the pixel data element.
+    fn into_value(samples: Vec<Self>) -> PrimitiveValue;
+}
+
+impl PixelSample for u8 {
+    const BITS_ALLOCATED: u16 = 8;
+    const PIXEL_REPRESENTATION: PixelRepresentation = PixelRepresentation::Unsigned;
+    const VR: VR = VR::OB;
+
+    fn into_value(mut samples: Vec<Self>) -> PrimitiveValue {
+        // keep the value length even
+        if samples.len() % 2 != 0 {
+            samples.push(0);
+        }
+        PrimitiveValue::from(samples)
+    }
+}
+
+impl PixelSample for u16 {
+    const BITS_ALLOCATED: u16 = 16;
+    const PIXEL_REPRESENTATION: PixelRepresentation = PixelRepresentation::Unsigned;
+    const VR: VR = VR::OW;
+
+    fn into_value(samples: Vec<Self>) -> PrimitiveValue {
+        PrimitiveValue::U16(samples.into())
+    }
+}
+
+impl PixelSample for i16 {
+    const BITS_ALLOCATED: u16 = 16;
+    const PIXEL_REPRESENTATION: PixelRepresentation = PixelRepresentation::Signed;
+    const VR: VR = VR::OW;
+
+    fn into_value(samples: Vec<Self>) -> PrimitiveValue {
+        // OW holds the two's complement bits of each sample
+        PrimitiveValue::U16(samples.into_iter().map(|v| v as u16).collect())
+    }
+}
+
+/// A builder of native pixel data and its image pixel attributes
+/// out of a multi-dimensional array.
+///
+/// See the [module-level documentation](self) for an example.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelDataBuilder {
+    rows: u16,
+    columns: u16,
+    /// the number of frames, or `None` if built from a single frame
+    number_of_frames: Option<u32>,
+    samples_per_pixel: u16,
+    bits_allocated: u16,
+    bits_stored: u16,
+    pixel_representation: PixelRepresentation,
+    photometric_interpretation: PhotometricInterpretation,
+    vr: VR,
+    value: PrimitiveValue,
+}
+
+impl PixelDataBuilder {
+    /// Create a pixel data builder from an array of samples
+    /// with the dimensions _(frames, rows, columns, samples)_.
+    ///
+    /// The photometric interpretation defaults to `MONOCHROME2`
+    /// for 1 sample per pixel and to `RGB` for 3 samples per pixel.
+    /// _Bits Stored_ defaults to the bits allocated to the sample type.
+    ///
+    /// Returns an error if the last dimension is neither 1 nor 3,
+    /// if any dimension is empty,
+    /// or if the rows, columns, or frames do not fit in their attributes.
+    pub fn from_ndarray<S>(array: ArrayBase<S, Ix4>) -> Result<Self>
+    where
+        S: Data,
+        S::Elem: PixelSample,
+    {
+        let (frames, rows, columns, samples) = array.dim();
+        let number_of_frames = check_dimension("frames", frames, i32::MAX as u64)? as u32;
+        let mut builder = Self::from_samples(&array, rows, columns, samples)?;
+        builder.number_of_frames = Some(number_of_frames);
+        Ok(builder)
+    }
+
+    /// Create a pixel data builder from an array of samples
+    /// of a single frame,
+    /// with the dimensions _(rows, columns, samples)_.
+    ///
+    /// This works like [`from_ndarray`](Self::from_ndarray),
+    /// except that _Number of Frames_ is left out of the object,
+    /// as is expected of single frame images.
+    pub fn from_ndarray_frame<S>(array: ArrayBase<S, Ix3>) -> Result<Self>
+    where
+        S: Data,
+        S::Elem: PixelSample,
+    {
+        let (rows, columns, samples) = array.dim();
+        let array = array.view().insert_axis(Axis(0));
+        Self::from_samples(&array, rows, columns, samples)
+    }
+
+    fn from_samples<S, T>(
+        array: &ArrayBase<S, Ix4>,
+        rows: usize,
+        columns: usize,
+        samples: usize,
+    ) -> Result<Self>
+    where
+        S: Data<Elem = T>,
+        T: PixelSample,
+    {
+        let rows = check_dimension("rows", rows, u16::MAX as u64)? as u16;
+        let columns = check_dimension("columns", columns, u16::MAX as u64)? as u16;
+        let photometric_interpretation = match samples {
+            1 => PhotometricInterpretation::Monochrome2,
+            3 => PhotometricInterpretation::Rgb,
+            _ => return InvalidSamplesPerPixelSnafu { samples }.fail(),
+        };
+
+        // iterating in logical order yields
+        // the samples of each pixel next to each other,
+        // whatever the memory layout of the array
+        let samples_data: Vec<T> = array.iter().copied().collect();
+
+        Ok(PixelDataBuilder {
+            rows,
+            columns,
+            number_of_frames: None,
+            samples_per_pixel: samples as u16,
+            bits_allocated: T::BITS_ALLOCATED,
+            bits_stored: T::BITS_ALLOCATED,
+            pixel_representation: T::PIXEL_REPRESENTATION,
+            photometric_interpretation,
+            vr: T::VR,
+            value: T::into_value(samples_data),
+        })
+    }
+
+    /// Override the photometric interpretation of the pixel data.
+    ///
+    /// Monochrome pixel data accepts `MONOCHROME1` and `MONOCHROME2`,
+    /// whereas color pixel data accepts `RGB` and `YBR_FULL`.
+    /// Other photometric interpretations are rejected
+    /// when applying the builder.
+    pub fn photometric_interpretation(
+        mut self,
+        photometric_interpretation: PhotometricInterpretation,
+    ) -> Self {
+        self.photometric_interpretation = photometric_interpretation;
+        self
+    }
+
+    /// Override the number of bits stored in each sample,
+    /// for when the sample values do not use the full range
+    /// of the sample type (e.g. 12-bit CT values in `i16`).
+    ///
+    /// _High Bit_ is always one less than the bits stored.
+    pub fn bits_stored(mut self, bits_stored: u16) -> Self {
+        self.bits_stored = bits_stored;
+        self
+    }
+
+    /// Get the number of rows of the pixel data.
+    pub fn rows(&self) -> u16 {
+        self.rows
+    }
+
+    /// Get the number of columns of the pixel data.
+    pub fn columns(&self) -> u16 {
+        self.columns
+    }
+
+    /// Get the number of frames of the pixel data.
+    pub fn number_of_frames(&self) -> u32 {
+        self.number_of_frames.unwrap_or(1)
+    }
+
+    /// Get the number of samples per pixel.
+    pub fn samples_per_pixel(&self) -> u16 {
+        self.samples_per_pixel
+    }
+
+    /// Write the image pixel attributes and the pixel data
+    /// into the given DICOM object,
+    /// replacing any previous values.
+    ///
+    /// This sets _Rows_, _Columns_, _Samples per Pixel_,
+    /// _Bits Allocated_, _Bits Stored_, _High Bit_,
+    /// _Pixel Representation_, _Photometric Interpretation_,
+    /// _Planar Configuration_ (for color pixel data only),
+    /// _Number of Frames_ (unless built from a single frame),
+    /// and _Pixel Data_.
+    pub fn apply_to<D>(self, obj: &mut InMemDicomObject<D>) -> Result<()>
+    where
+        D: DataDictionary + Clone,
+    {
+        let pi_ok = match self.samples_per_pixel {
+            1 => self.photometric_interpretation.is_monochrome(),
+            _ => matches!(
+                self.photometric_interpretation,
+                PhotometricInterpretation::Rgb | PhotometricInterpretation::YbrFull
+            ),
+        };
+        ensure!(
+            pi_ok,
+            InconsistentPhotometricInterpretationSnafu {
+                photometric_interpretation: self.photometric_interpretation.as_str(),
+                samples: self.samples_per_pixel,
+            }
+        );
+        ensure!(
+            self.bits_stored >= 1 && self.bits_stored <= self.bits_allocated,
+            InvalidBitsStoredSnafu {
+                bits_stored: self.bits_stored,
+                bits_allocated: self.bits_allocated,
+            }
+        );
+
+        let us = |tag, value: u16| DataElement::new(tag, VR::US, PrimitiveValue::from(value));
+        obj.put(us(tags::SAMPLES_PER_PIXEL, self.samples_per_pixel));
+        obj.put(DataElement::new(
+            tags::PHOTOMETRIC_INTERPRETATION,
+            VR::CS,
+            PrimitiveValue::from(self.photometric_interpretation.as_str()),
+        ));
+        if self.samples_per_pixel > 1 {
+            obj.put(us(tags::PLANAR_CONFIGURATION, 0));
+        } else {
+            obj.remove_element(tags::PLANAR_CONFIGURATION);
+        }
+        match self.number_of_frames {
+            Some(frames) => {
+                obj.put(DataElement::new(
+                    tags::NUMBER_OF_FRAMES,
+                    VR::IS,
+                    PrimitiveValue::from(frames.to_string()),
+                ));
+            }
+            None => {
+                obj.remove_element(tags::NUMBER_OF_FRAMES);
+            }
+        }
+        obj.put(us(tags::ROWS, self.rows));
+        obj.put(us(tags::COLUMNS, self.columns));
+        obj.put(us(tags::BITS_ALLOCATED, self.bits_allocated));
+        obj.put(us(tags::BITS_STORED, self.bits_stored));
+        obj.put(us(tags::HIGH_BIT, self.bits_stored - 1));
+        obj.put(us(
+            tags::PIXEL_REPRESENTATION,
+            self.pixel_representation as u16,
+        ));
+
+        let len = self.value.calculate_byte_len() as u32;
+        obj.put(DataElement::new_with_len(
+            tags::PIXEL_DATA,
+            self.vr,
+            Length::defined(len),
+            self.value,
+        ));
+        Ok(())
+    }
+}
+
+/// Check that an array dimension is not empty
+/// and that its length does not exceed `max`.
+fn check_dimension(name: &'static str, len: usize, max: u64) -> Result<u64> {
+    ensure!(len > 0, EmptyDimensionSnafu { name });
+    let value = u64::try_from(len).unwrap_or(u64::MAX);
+    ensure!(value <= max, DimensionTooLargeSnafu { name, len, max });
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PixelDecoder;
+    use dicom_dictionary_std::uids;
+    use dicom_object::FileMetaTableBuilder;
+    use ndarray::{Array3, Array4};
+
+    #[test]
+    fn build_monochrome_frames() {
+        let array = Array4::from_shape_fn((2, 3, 5, 1), |(f, r, c, _)| {
+            (f * 1000 + r * 10 + c) as i16 - 500
+        });
+        let mut obj = InMemDicomObject::new_empty();
+        PixelDataBuilder::from_ndarray(array.clone())
+            .unwrap()
+            .bits_stored(12)
+            .apply_to(&mut obj)
+            .unwrap();
+
+        let get = |tag| obj.get(tag).unwrap().to_int::<i32>().unwrap();
+        assert_eq!(get(tags::ROWS), 3);
+        assert_eq!(get(tags::COLUMNS), 5);
+        assert_eq!(get(tags::NUMBER_OF_FRAMES), 2);
+        assert_eq!(get(tags::SAMPLES_PER_PIXEL), 1);
+        assert_eq!(get(tags::BITS_ALLOCATED), 16);
+        assert_eq!(get(tags::BITS_STORED), 12);
+        assert_eq!(get(tags::HIGH_BIT), 11);
+        assert_eq!(get(tags::PIXEL_REPRESENTATION), 1);
+        assert!(obj.get(tags::PLANAR_CONFIGURATION).is_none());
+        assert_eq!(
+            obj.get(tags::PHOTOMETRIC_INTERPRETATION)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "MONOCHROME2"
+        );
+        let pixel_data = obj.get(tags::PIXEL_DATA).unwrap();
+        assert_eq!(pixel_data.vr(), VR::OW);
+        assert_eq!(pixel_data.header().len, Length(2 * 3 * 5 * 2));
+
+        // the pixel data decodes back into the same array
+        let obj = obj
+            .with_meta(
+                FileMetaTableBuilder::new()
+                    .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                    .media_storage_sop_class_uid(uids::CT_IMAGE_STORAGE)
+                    .media_storage_sop_instance_uid("2.25.1"),
+            )
+            .unwrap();
+        let decoded = obj.decode_pixel_data().unwrap();
+        let options = crate::ConvertOptions::new()
+            .with_modality_lut(crate::ModalityLutOption::None)
+            .with_voi_lut(crate::VoiLutOption::Identity);
+        let values = decoded.to_ndarray_with_options::<i16>(&options).unwrap();
+        assert_eq!(values, array);
+    }
+
+    #[test]
+    fn build_rgb_frame() {
+        // a non-standard layout must not affect the order of samples
+        let array = Array3::from_shape_fn((3, 2, 2), |(c, r, s)| (r * 100 + c * 10 + s) as u8);
+        let array = array.permuted_axes([1, 0, 2]);
+        let mut obj = InMemDicomObject::new_empty();
+        let builder = PixelDataBuilder::from_ndarray_frame(array.view()).unwrap();
+        assert_eq!(builder.rows(), 2);
+        assert_eq!(builder.columns(), 3);
+        assert_eq!(builder.number_of_frames(), 1);
+        assert_eq!(builder.samples_per_pixel(), 3);
+        builder.apply_to(&mut obj).unwrap();
+
+        assert!(obj.get(tags::NUMBER_OF_FRAMES).is_none());
+        let get = |tag| obj.get(tag).unwrap().to_int::<u16>().unwrap();
+        assert_eq!(get(tags::SAMPLES_PER_PIXEL), 3);
+        assert_eq!(get(tags::PLANAR_CONFIGURATION), 0);
+        assert_eq!(get(tags::BITS_ALLOCATED), 8);
+        assert_eq!(get(tags::HIGH_BIT), 7);
+        assert_eq!(get(tags::PIXEL_REPRESENTATION), 0);
+        assert_eq!(
+            obj.get(tags::PHOTOMETRIC_INTERPRETATION)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "RGB"
+        );
+
+        let pixel_data = obj.get(tags::PIXEL_DATA).unwrap();
+        assert_eq!(pixel_data.vr(), VR::OB);
+        let bytes = pixel_data.to_bytes().unwrap();
+        // 18 samples, padded to an even length
+        assert_eq!(bytes.len(), 18);
+        assert_eq!(&bytes[..6], &[0, 1, 2, 10, 11, 12]);
+        assert_eq!(&bytes[9..12], &[100, 101, 102]);
+    }
+
+    #[test]
+    fn reject_inconsistent_arrays() {
+        let array = Array4::<u16>::zeros((1, 4, 4, 2));
+        assert!(matches!(
+            PixelDataBuilder::from_ndarray(array),
+            Err(BuildPixelDataError::InvalidSamplesPerPixel { samples: 2, .. })
+        ));
+
+        let array = Array4::<u16>::zeros((0, 4, 4, 1));
+        assert!(matches!(
+            PixelDataBuilder::from_ndarray(array),
+            Err(BuildPixelDataError::EmptyDimension { name: "frames", .. })
+        ));
+
+        let array = Array3::<u8>::zeros((1, 70_000, 1));
+        assert!(matches!(
+            PixelDataBuilder::from_ndarray_frame(array),
+            Err(BuildPixelDataError::DimensionTooLarge {
+                name: "columns",
+                ..
+            })
+        ));
+
+        let mut obj = InMemDicomObject::new_empty();
+        let array = Array3::<u8>::zeros((2, 2, 3));
+        let err = PixelDataBuilder::from_ndarray_frame(array.view())
+            .unwrap()
+            .photometric_interpretation(PhotometricInterpretation::Monochrome2)
+            .apply_to(&mut obj)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BuildPixelDataError::InconsistentPhotometricInterpretation { samples: 3, .. }
+        ));
+        let err = PixelDataBuilder::from_ndarray_frame(array)
+            .unwrap()
+            .bits_stored(9)
+            .apply_to(&mut obj)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BuildPixelDataError::InvalidBitsStored { bits_stored: 9, .. }
+        ));
+        // nothing was written on failure
+        assert!(obj.get(tags::PIXEL_DATA).is_none());
+    }
+}
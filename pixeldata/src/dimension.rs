@@ -0,0 +1,570 @@
+//! Support for the multi-frame dimension organization
+//! of enhanced multi-frame objects.
+//!
+//! Enhanced objects such as Enhanced MR or Enhanced CT images
+//! declare their dimensions in the _Dimension Index Sequence_,
+//! and assign each frame a _Dimension Index Values_ attribute
+//! in the _Frame Content Sequence_ of its per-frame functional groups.
+//! [`DimensionIndex`] gathers all of this into a structure
+//! which can be used to select and order frames,
+//! such as when reconstructing a volume.
+
+use dicom_core::{value::trim_padding, DataDictionary, Tag};
+use dicom_dictionary_std::tags;
+use dicom_object::InMemDicomObject;
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+
+/// An error which may occur when reading the dimension organization
+/// of a multi-frame object.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum DimensionIndexError {
+    /// Missing Dimension Index Sequence
+    MissingDimensionIndexSequence { backtrace: Backtrace },
+
+    #[snafu(display("Missing Dimension Index Pointer in dimension #{}", dimension))]
+    MissingIndexPointer {
+        dimension: usize,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not read attribute `{}`", name))]
+    CastValue {
+        name: &'static str,
+        source: dicom_core::value::CastValueError,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not convert attribute `{}`", name))]
+    ConvertValue {
+        name: &'static str,
+        #[snafu(source(from(dicom_core::value::ConvertValueError, Box::from)))]
+        source: Box<dicom_core::value::ConvertValueError>,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Dimension #{} refers to undeclared dimension organization `{}`",
+        dimension,
+        uid
+    ))]
+    UnknownDimensionOrganization {
+        dimension: usize,
+        uid: String,
+        backtrace: Backtrace,
+    },
+
+    /// Missing Per-Frame Functional Groups Sequence
+    MissingPerFrameFunctionalGroups { backtrace: Backtrace },
+
+    #[snafu(display(
+        "Number of frames ({}) does not match the number of per-frame functional groups ({})",
+        number_of_frames,
+        groups
+    ))]
+    FrameCountMismatch {
+        number_of_frames: u32,
+        groups: usize,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Missing Dimension Index Values for frame #{}", frame))]
+    MissingIndexValues { frame: u32, backtrace: Backtrace },
+
+    #[snafu(display(
+        "Frame #{} has {} dimension index values, but {} dimensions are declared",
+        frame,
+        count,
+        dimensions
+    ))]
+    IndexValueCountMismatch {
+        frame: u32,
+        count: usize,
+        dimensions: usize,
+        backtrace: Backtrace,
+    },
+}
+
+pub type Result<T, E = DimensionIndexError> = std::result::Result<T, E>;
+
+/// A dimension declared in the Dimension Index Sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dimension {
+    /// the tag of the attribute which the dimension indexes
+    /// (Dimension Index Pointer)
+    pub index_pointer: Tag,
+    /// the tag of the functional group sequence
+    /// containing the indexed attribute, if any
+    /// (Functional Group Pointer)
+    pub functional_group_pointer: Option<Tag>,
+    /// the dimension organization which this dimension belongs to
+    pub organization_uid: Option<String>,
+    /// the free form description of the dimension
+    pub label: Option<String>,
+}
+
+/// The dimension organization of a multi-frame object,
+/// with the dimension index values of every frame.
+///
+/// Dimensions are identified by their Dimension Index Pointer,
+/// such as [`STACK_ID`](tags::STACK_ID)
+/// or [`IN_STACK_POSITION_NUMBER`](tags::IN_STACK_POSITION_NUMBER).
+/// Note that frames are queried by their dimension _index_ values
+/// (which start at 1),
+/// and not by the values of the indexed attributes.
+/// Frame numbers start at 0.
+///
+/// # Example
+///
+/// ```no_run
+/// # use dicom_object::open_file;
+/// # use dicom_dictionary_std::tags;
+/// use dicom_pixeldata::DimensionIndex;
+///
+/// let obj = open_file("enhanced_mr.dcm")?;
+/// let index = DimensionIndex::from_obj(&obj)?;
+///
+/// // all frames of the second stack, ordered by their position in the stack
+/// let mut frames = index.frames_matching(&[(tags::STACK_ID, 2)]);
+/// index.sort_frames(&mut frames, tags::IN_STACK_POSITION_NUMBER);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DimensionIndex {
+    /// the declared dimension organization UIDs
+    organization_uids: Vec<String>,
+    /// the declared dimensions
+    dimensions: Vec<Dimension>,
+    /// the dimension index values of each frame,
+    /// one per dimension
+    frame_index_values: Vec<Vec<u32>>,
+}
+
+impl DimensionIndex {
+    /// Read the dimension organization of a multi-frame DICOM object.
+    ///
+    /// The number of dimension index values of each frame
+    /// must match the number of declared dimensions,
+    /// and the number of per-frame functional groups
+    /// must match Number of Frames, if present.
+    pub fn from_obj<D>(obj: &InMemDicomObject<D>) -> Result<Self>
+    where
+        D: DataDictionary + Clone,
+    {
+        let organization_uids = obj
+            .get(tags::DIMENSION_ORGANIZATION_SEQUENCE)
+            .and_then(|e| e.items())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|item| item.get(tags::DIMENSION_ORGANIZATION_UID))
+            .map(|e| {
+                e.to_str()
+                    .context(ConvertValueSnafu {
+                        name: "DimensionOrganizationUID",
+                    })
+                    .map(|uid| trim_padding(&uid).to_string())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let dimension_items = obj
+            .get(tags::DIMENSION_INDEX_SEQUENCE)
+            .and_then(|e| e.items())
+            .context(MissingDimensionIndexSequenceSnafu)?;
+
+        let dimensions = dimension_items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| read_dimension(item, i, &organization_uids))
+            .collect::<Result<Vec<_>>>()?;
+
+        let groups = obj
+            .get(tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE)
+            .and_then(|e| e.items())
+            .context(MissingPerFrameFunctionalGroupsSnafu)?;
+
+        if let Some(number_of_frames) = obj.get(tags::NUMBER_OF_FRAMES) {
+            let number_of_frames: u32 = number_of_frames.to_int().context(ConvertValueSnafu {
+                name: "NumberOfFrames",
+            })?;
+            ensure!(
+                number_of_frames as usize == groups.len(),
+                FrameCountMismatchSnafu {
+                    number_of_frames,
+                    groups: groups.len(),
+                }
+            );
+        }
+
+        let frame_index_values = groups
+            .iter()
+            .enumerate()
+            .map(|(frame, group)| -> Result<Vec<u32>> {
+                let frame = frame as u32;
+                let values: Vec<u32> = group
+                    .get(tags::FRAME_CONTENT_SEQUENCE)
+                    .and_then(|e| e.items()?.first()?.get(tags::DIMENSION_INDEX_VALUES))
+                    .context(MissingIndexValuesSnafu { frame })?
+                    .to_multi_int()
+                    .context(ConvertValueSnafu {
+                        name: "DimensionIndexValues",
+                    })?;
+                ensure!(
+                    values.len() == dimensions.len(),
+                    IndexValueCountMismatchSnafu {
+                        frame,
+                        count: values.len(),
+                        dimensions: dimensions.len(),
+                    }
+                );
+                Ok(values)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(DimensionIndex {
+            organization_uids,
+            dimensions,
+            frame_index_values,
+        })
+    }
+
+    /// Obtain the declared dimension organization UIDs.
+    pub fn organization_uids(&self) -> &[String] {
+        &self.organization_uids
+    }
+
+    /// Obtain the declared dimensions, in order.
+    pub fn dimensions(&self) -> &[Dimension] {
+        &self.dimensions
+    }
+
+    /// Obtain the number of frames indexed.
+    pub fn number_of_frames(&self) -> u32 {
+        self.frame_index_values.len() as u32
+    }
+
+    /// Obtain the position of the dimension
+    /// with the given Dimension Index Pointer
+    /// in the list of dimensions.
+    pub fn position(&self, dimension: Tag) -> Option<usize> {
+        self.dimensions
+            .iter()
+            .position(|d| d.index_pointer == dimension)
+    }
+
+    /// Obtain the dimension index values of the given frame,
+    /// one per dimension.
+    pub fn index_values(&self, frame: u32) -> Option<&[u32]> {
+        self.frame_index_values
+            .get(frame as usize)
+            .map(|values| values.as_slice())
+    }
+
+    /// Obtain the dimension index value of the given frame
+    /// in the given dimension.
+    pub fn index_value(&self, frame: u32, dimension: Tag) -> Option<u32> {
+        let i = self.position(dimension)?;
+        self.index_values(frame)?.get(i).copied()
+    }
+
+    /// Obtain the numbers of all frames
+    /// which have the given dimension index value in each given dimension,
+    /// in frame order.
+    ///
+    /// No frames match a criterion on an undeclared dimension.
+    pub fn frames_matching(&self, criteria: &[(Tag, u32)]) -> Vec<u32> {
+        let criteria: Option<Vec<(usize, u32)>> = criteria
+            .iter()
+            .map(|&(dimension, value)| Some((self.position(dimension)?, value)))
+            .collect();
+        let criteria = match criteria {
+            Some(criteria) => criteria,
+            None => return Vec::new(),
+        };
+
+        self.frame_index_values
+            .iter()
+            .enumerate()
+            .filter(|(_, values)| criteria.iter().all(|&(i, value)| values[i] == value))
+            .map(|(frame, _)| frame as u32)
+            .collect()
+    }
+
+    /// Obtain the numbers of all frames,
+    /// sorted by their dimension index value in the given dimension.
+    ///
+    /// Frames with the same index value stay in frame order.
+    /// If the dimension is not declared,
+    /// the frames are returned in frame order.
+    pub fn sort_frames_by(&self, dimension: Tag) -> Vec<u32> {
+        let mut frames: Vec<u32> = (0..self.number_of_frames()).collect();
+        self.sort_frames(&mut frames, dimension);
+        frames
+    }
+
+    /// Sort the given frame numbers
+    /// by their dimension index value in the given dimension.
+    ///
+    /// The sort is stable.
+    /// Frames which do not exist are placed at the end.
+    /// If the dimension is not declared,
+    /// the frames are left unchanged.
+    pub fn sort_frames(&self, frames: &mut [u32], dimension: Tag) {
+        if let Some(i) = self.position(dimension) {
+            frames.sort_by_key(|&frame| {
+                self.index_values(frame)
+                    .map(|values| values[i])
+                    .unwrap_or(u32::MAX)
+            });
+        }
+    }
+}
+
+/// Read a dimension from an item of the Dimension Index Sequence.
+fn read_dimension<D>(
+    item: &InMemDicomObject<D>,
+    dimension: usize,
+    organization_uids: &[String],
+) -> Result<Dimension>
+where
+    D: DataDictionary + Clone,
+{
+    let index_pointer = item
+        .get(tags::DIMENSION_INDEX_POINTER)
+        .context(MissingIndexPointerSnafu { dimension })?
+        .value()
+        .to_tag()
+        .context(CastValueSnafu {
+            name: "DimensionIndexPointer",
+        })?;
+    let functional_group_pointer = item
+        .get(tags::FUNCTIONAL_GROUP_POINTER)
+        .map(|e| e.value().to_tag())
+        .transpose()
+        .context(CastValueSnafu {
+            name: "FunctionalGroupPointer",
+        })?;
+    let organization_uid = item
+        .get(tags::DIMENSION_ORGANIZATION_UID)
+        .map(|e| e.to_str().map(|uid| trim_padding(&uid).to_string()))
+        .transpose()
+        .context(ConvertValueSnafu {
+            name: "DimensionOrganizationUID",
+        })?;
+    if let Some(uid) = &organization_uid {
+        ensure!(
+            organization_uids.is_empty() || organization_uids.contains(uid),
+            UnknownDimensionOrganizationSnafu {
+                dimension,
+                uid: uid.clone(),
+            }
+        );
+    }
+    let label = item
+        .get(tags::DIMENSION_DESCRIPTION_LABEL)
+        .map(|e| e.to_str().map(|label| label.trim().to_string()))
+        .transpose()
+        .context(ConvertValueSnafu {
+            name: "DimensionDescriptionLabel",
+        })?;
+
+    Ok(Dimension {
+        index_pointer,
+        functional_group_pointer,
+        organization_uid,
+        label,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+
+    const ORGANIZATION_UID: &str = "2.25.93711431812547621043093282018745498723";
+
+    /// Create an object with two dimensions (stack ID and in-stack position)
+    /// and one frame per list of dimension index values.
+    fn enhanced_object(frame_index_values: &[&[u32]]) -> InMemDicomObject {
+        let dimension = |pointer: Tag, label: &str| {
+            InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::DIMENSION_ORGANIZATION_UID,
+                    VR::UI,
+                    PrimitiveValue::from(ORGANIZATION_UID),
+                ),
+                DataElement::new(
+                    tags::DIMENSION_INDEX_POINTER,
+                    VR::AT,
+                    dicom_value!(Tags, [pointer]),
+                ),
+                DataElement::new(
+                    tags::FUNCTIONAL_GROUP_POINTER,
+                    VR::AT,
+                    dicom_value!(Tags, [tags::FRAME_CONTENT_SEQUENCE]),
+                ),
+                DataElement::new(
+                    tags::DIMENSION_DESCRIPTION_LABEL,
+                    VR::LO,
+                    PrimitiveValue::from(label),
+                ),
+            ])
+        };
+        let frame = |values: &[u32]| {
+            InMemDicomObject::from_element_iter([DataElement::new(
+                tags::FRAME_CONTENT_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(
+                        tags::DIMENSION_INDEX_VALUES,
+                        VR::UL,
+                        PrimitiveValue::U32(values.iter().copied().collect()),
+                    ),
+                ])]),
+            )])
+        };
+
+        InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::NUMBER_OF_FRAMES,
+                VR::IS,
+                PrimitiveValue::from(frame_index_values.len().to_string()),
+            ),
+            DataElement::new(
+                tags::DIMENSION_ORGANIZATION_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(
+                        tags::DIMENSION_ORGANIZATION_UID,
+                        VR::UI,
+                        dicom_value!(Str, ORGANIZATION_UID),
+                    ),
+                ])]),
+            ),
+            DataElement::new(
+                tags::DIMENSION_INDEX_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![
+                    dimension(tags::STACK_ID, "Stack ID"),
+                    dimension(tags::IN_STACK_POSITION_NUMBER, "In-Stack Position Number"),
+                ]),
+            ),
+            DataElement::new(
+                tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(
+                    frame_index_values
+                        .iter()
+                        .map(|values| frame(values))
+                        .collect::<Vec<_>>(),
+                ),
+            ),
+        ])
+    }
+
+    #[test]
+    fn read_dimensions() {
+        let obj = enhanced_object(&[&[1, 2], &[2, 1], &[1, 1]]);
+        let index = DimensionIndex::from_obj(&obj).unwrap();
+
+        assert_eq!(index.organization_uids(), [ORGANIZATION_UID.to_string()]);
+        assert_eq!(
+            index.dimensions(),
+            [
+                Dimension {
+                    index_pointer: tags::STACK_ID,
+                    functional_group_pointer: Some(tags::FRAME_CONTENT_SEQUENCE),
+                    organization_uid: Some(ORGANIZATION_UID.to_string()),
+                    label: Some("Stack ID".to_string()),
+                },
+                Dimension {
+                    index_pointer: tags::IN_STACK_POSITION_NUMBER,
+                    functional_group_pointer: Some(tags::FRAME_CONTENT_SEQUENCE),
+                    organization_uid: Some(ORGANIZATION_UID.to_string()),
+                    label: Some("In-Stack Position Number".to_string()),
+                },
+            ]
+        );
+        assert_eq!(index.number_of_frames(), 3);
+        assert_eq!(index.index_values(1), Some(&[2, 1][..]));
+        assert_eq!(
+            index.index_value(0, tags::IN_STACK_POSITION_NUMBER),
+            Some(2)
+        );
+        assert_eq!(index.index_values(3), None);
+    }
+
+    #[test]
+    fn filter_and_sort_frames() {
+        let obj = enhanced_object(&[&[2, 3], &[1, 2], &[2, 1], &[1, 3], &[2, 2], &[1, 1]]);
+        let index = DimensionIndex::from_obj(&obj).unwrap();
+
+        // filtering
+        assert_eq!(index.frames_matching(&[(tags::STACK_ID, 2)]), [0, 2, 4]);
+        assert_eq!(index.frames_matching(&[(tags::STACK_ID, 1)]), [1, 3, 5]);
+        assert_eq!(
+            index.frames_matching(&[(tags::STACK_ID, 1), (tags::IN_STACK_POSITION_NUMBER, 3)]),
+            [3]
+        );
+        assert_eq!(
+            index.frames_matching(&[(tags::STACK_ID, 3)]),
+            Vec::<u32>::new()
+        );
+        assert_eq!(
+            index.frames_matching(&[(tags::SLICE_LOCATION, 1)]),
+            Vec::<u32>::new()
+        );
+        assert_eq!(index.frames_matching(&[]), [0, 1, 2, 3, 4, 5]);
+
+        // ordering, stable on ties
+        assert_eq!(
+            index.sort_frames_by(tags::IN_STACK_POSITION_NUMBER),
+            [2, 5, 1, 4, 0, 3]
+        );
+        assert_eq!(index.sort_frames_by(tags::STACK_ID), [1, 3, 5, 0, 2, 4]);
+
+        // frames of stack 2 by in-stack position
+        let mut frames = index.frames_matching(&[(tags::STACK_ID, 2)]);
+        index.sort_frames(&mut frames, tags::IN_STACK_POSITION_NUMBER);
+        assert_eq!(frames, [2, 4, 0]);
+    }
+
+    #[test]
+    fn reject_inconsistent_index_values() {
+        let obj = enhanced_object(&[&[1, 1], &[1], &[1, 3]]);
+        let err = DimensionIndex::from_obj(&obj).unwrap_err();
+        assert!(matches!(
+            err,
+            DimensionIndexError::IndexValueCountMismatch {
+                frame: 1,
+                count: 1,
+                dimensions: 2,
+                ..
+            }
+        ));
+
+        let mut obj = enhanced_object(&[&[1, 1], &[1, 2]]);
+        obj.put(DataElement::new(
+            tags::NUMBER_OF_FRAMES,
+            VR::IS,
+            PrimitiveValue::from("3"),
+        ));
+        let err = DimensionIndex::from_obj(&obj).unwrap_err();
+        assert!(matches!(
+            err,
+            DimensionIndexError::FrameCountMismatch {
+                number_of_frames: 3,
+                groups: 2,
+                ..
+            }
+        ));
+
+        let mut obj = enhanced_object(&[&[1, 1]]);
+        obj.remove_element(tags::DIMENSION_INDEX_SEQUENCE);
+        let err = DimensionIndex::from_obj(&obj).unwrap_err();
+        assert!(matches!(
+            err,
+            DimensionIndexError::MissingDimensionIndexSequence { .. }
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+//! Private module for narrowing 16-bit sample values to 8 bits,
+//! with optional dithering.
+
+/// The 4x4 Bayer threshold matrix for ordered dithering.
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Narrow a 16-bit sample value to the nearest 8-bit value.
+///
+/// This is the inverse of extending an 8-bit value `x` to `x * 257`.
+#[inline]
+pub(crate) fn round(x: u16) -> u8 {
+    ((u32::from(x) + 128) / 257) as u8
+}
+
+/// Narrow a 16-bit sample value at the given position to 8 bits
+/// with ordered dithering.
+#[inline]
+pub(crate) fn ordered(x: u16, col: usize, row: usize) -> u8 {
+    let threshold = (f32::from(BAYER_4X4[row % 4][col % 4]) + 0.5) / 16.;
+    (f32::from(x) / 257. + threshold).floor().min(255.) as u8
+}
+
+/// Narrow the 16-bit sample values of a frame to 8 bits
+/// with Floyd–Steinberg error diffusion.
+///
+/// The samples are processed sequentially in standard order,
+/// since the quantization error of each sample
+/// is distributed to the samples which follow.
+pub(crate) fn floyd_steinberg(data: &[u16], cols: usize) -> Vec<u8> {
+    if cols == 0 {
+        return Vec::new();
+    }
+
+    let mut out = Vec::with_capacity(data.len());
+    // errors to add to the current row and to the next row,
+    // with one extra entry on each side for the borders
+    let mut errors = vec![0_f32; cols + 2];
+    let mut next_errors = vec![0_f32; cols + 2];
+    for row in data.chunks(cols) {
+        for (i, x) in row.iter().enumerate() {
+            let value = f32::from(*x) / 257. + errors[i + 1];
+            let quantized = value.round().clamp(0., 255.);
+            let error = value - quantized;
+            errors[i + 2] += error * 7. / 16.;
+            next_errors[i] += error * 3. / 16.;
+            next_errors[i + 1] += error * 5. / 16.;
+            next_errors[i + 2] += error / 16.;
+            out.push(quantized as u8);
+        }
+        std::mem::swap(&mut errors, &mut next_errors);
+        next_errors.fill(0.);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mean(values: impl IntoIterator<Item = f64>) -> f64 {
+        let (sum, count) = values
+            .into_iter()
+            .fold((0., 0), |(sum, count), v| (sum + v, count + 1));
+        sum / count as f64
+    }
+
+    /// Rounding to the nearest value differs from truncation
+    /// in the upper half of each 8-bit step.
+    #[test]
+    fn round_gradient() {
+        for x in 0..=u16::MAX {
+            let expected = (f64::from(x) / 257.).round() as u8;
+            assert_eq!(round(x), expected, "x = {}", x);
+        }
+
+        // truncation gives 0, 1 and 3
+        assert_eq!(round(255), 1);
+        assert_eq!(round(511), 2);
+        assert_eq!(round(1_023), 4);
+        // both agree on the extremes
+        assert_eq!(round(0), 0);
+        assert_eq!(round(u16::MAX), 255);
+
+        // narrowing reverts extension
+        for x in 0..=u8::MAX {
+            assert_eq!(round(u16::from(x) * 257), x);
+        }
+    }
+
+    /// Dithering preserves the mean intensity of a flat field
+    /// which falls between two 8-bit values.
+    #[test]
+    fn dithering_preserves_mean_of_flat_field() {
+        let cols = 64;
+        let x = 100 * 257 + 100;
+        let data = vec![x; cols * 64];
+        let expected = f64::from(x) / 257.;
+
+        // rounding alone does not
+        assert_eq!(round(x), 100);
+
+        let out: Vec<u8> = data
+            .iter()
+            .enumerate()
+            .map(|(i, x)| ordered(*x, i % cols, i / cols))
+            .collect();
+        assert!(out.iter().all(|v| *v == 100 || *v == 101));
+        let out_mean = mean(out.into_iter().map(f64::from));
+        assert!(
+            (out_mean - expected).abs() < 0.05,
+            "mean was {}, expected {}",
+            out_mean,
+            expected
+        );
+
+        let out = floyd_steinberg(&data, cols);
+        assert_eq!(out.len(), data.len());
+        assert!(out.iter().all(|v| *v == 100 || *v == 101));
+        let out_mean = mean(out.into_iter().map(f64::from));
+        assert!(
+            (out_mean - expected).abs() < 0.05,
+            "mean was {}, expected {}",
+            out_mean,
+            expected
+        );
+    }
+
+    /// Dithering preserves the mean intensity of a smooth gradient.
+    #[test]
+    fn dithering_preserves_mean_of_gradient() {
+        let cols = 256;
+        let rows = 16;
+        // a gradient spanning 8-bit values 20 to 40
+        let data: Vec<u16> = (0..rows)
+            .flat_map(|_| (0..cols).map(|c| (20 * 257 + c * 20) as u16))
+            .collect();
+        let expected = mean(data.iter().map(|x| f64::from(*x) / 257.));
+
+        let out: Vec<u8> = data
+            .iter()
+            .enumerate()
+            .map(|(i, x)| ordered(*x, i % cols, i / cols))
+            .collect();
+        let out_mean = mean(out.into_iter().map(f64::from));
+        assert!(
+            (out_mean - expected).abs() < 0.1,
+            "mean was {}, expected {}",
+            out_mean,
+            expected
+        );
+
+        let out = floyd_steinberg(&data, cols);
+        let out_mean = mean(out.into_iter().map(f64::from));
+        assert!(
+            (out_mean - expected).abs() < 0.1,
+            "mean was {}, expected {}",
+            out_mean,
+            expected
+        );
+    }
+
+    #[test]
+    fn floyd_steinberg_edge_cases() {
+        assert_eq!(floyd_steinberg(&[], 0), Vec::<u8>::new());
+        assert_eq!(floyd_steinberg(&[0, u16::MAX], 1), vec![0, 255]);
+        // an incomplete last row is still converted
+        assert_eq!(floyd_steinberg(&[0, 0, 257], 2), vec![0, 0, 1]);
+    }
+}
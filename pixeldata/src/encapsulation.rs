@@ -1,8 +1,15 @@
 //! DICOM Pixel encapsulation
 //!
-//! This module implements encapsulation for pixel data.
+//! This module implements encapsulation for pixel data,
+//! as well as the retrieval of a frame's data from encapsulated pixel data.
+//! Use [`EncapsulationOptions`] to control
+//! the splitting of frames into fragments
+//! and the contents of the basic offset table.
 use dicom_core::value::fragments::Fragments;
-use dicom_core::value::Value;
+use dicom_core::value::{InMemFragment, PixelFragmentSequence, Value};
+use dicom_encoding::adapters::{FragmentLayout, FrameExtractionStrategy};
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::vec;
 
 /// Encapsulate the pixel data of a list of frames.
@@ -55,6 +62,217 @@ pub fn encapsulate_single_frame(frame: Vec<u8>, fragment_size: u32) -> Value {
     Value::PixelSequence(fragments.into())
 }
 
+/// Options for the encapsulation of pixel data frames.
+///
+/// By default,
+/// each frame is put in a single fragment,
+/// fragments of odd length are padded with a trailing zero,
+/// and the basic offset table is populated
+/// with the position of each frame.
+///
+/// # Example
+/// ```
+/// use dicom_core::DataElement;
+/// use dicom_core::VR::OB;
+/// use dicom_dictionary_std::tags;
+/// use dicom_pixeldata::encapsulation::EncapsulationOptions;
+///
+/// // two frames of 5000 bytes each,
+/// // in fragments of up to 2048 bytes
+/// let frames = vec![vec![0; 5000], vec![1; 5000]];
+/// let seq = EncapsulationOptions::new()
+///     .max_fragment_length(2048)
+///     .encapsulate(frames);
+/// assert_eq!(seq.fragments().len(), 6);
+/// assert_eq!(seq.offset_table(), &[0, 5024]);
+///
+/// let element = DataElement::new(tags::PIXEL_DATA, OB, seq);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EncapsulationOptions {
+    /// the maximum length of each fragment, if any
+    max_fragment_length: Option<u32>,
+    /// whether to pad fragments of odd length
+    pad_odd_fragments: bool,
+    /// whether to populate the basic offset table
+    offset_table: bool,
+}
+
+impl Default for EncapsulationOptions {
+    fn default() -> Self {
+        EncapsulationOptions {
+            max_fragment_length: None,
+            pad_odd_fragments: true,
+            offset_table: true,
+        }
+    }
+}
+
+impl EncapsulationOptions {
+    /// Create a new set of encapsulation options with the default values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum length of each fragment in bytes,
+    /// so that larger frames are split into multiple fragments.
+    ///
+    /// Odd values are rounded down to an even length,
+    /// with a minimum of 2.
+    /// A length of 0 means no limit,
+    /// leaving one fragment per frame.
+    pub fn max_fragment_length(mut self, max_fragment_length: u32) -> Self {
+        self.max_fragment_length = match max_fragment_length {
+            0 => None,
+            len => Some((len & !1).max(2)),
+        };
+        self
+    }
+
+    /// Set whether to pad fragments of odd length
+    /// with a trailing zero byte,
+    /// as required by the standard.
+    ///
+    /// Only the last fragment of a frame may have an odd length.
+    pub fn pad_odd_fragments(mut self, pad_odd_fragments: bool) -> Self {
+        self.pad_odd_fragments = pad_odd_fragments;
+        self
+    }
+
+    /// Set whether to populate the basic offset table
+    /// with the offset of the first fragment of each frame.
+    /// Otherwise, the basic offset table is left empty.
+    ///
+    /// The basic offset table is also left empty
+    /// if the offsets would not fit in 32 bits.
+    pub fn offset_table(mut self, offset_table: bool) -> Self {
+        self.offset_table = offset_table;
+        self
+    }
+
+    /// Encapsulate the given frames into a pixel data fragment sequence,
+    /// ready to be used as the value of the _Pixel Data_ element.
+    ///
+    /// Each frame starts a new fragment.
+    /// An empty frame is encapsulated as one empty fragment.
+    pub fn encapsulate(&self, frames: Vec<Vec<u8>>) -> PixelFragmentSequence<InMemFragment> {
+        let mut offset_table = Vec::with_capacity(frames.len());
+        let mut fragments = Vec::new();
+        let mut offset = Some(0_u32);
+
+        for frame in frames {
+            offset_table.push(offset);
+            let frame_fragments = self.split_frame(frame);
+            for fragment in &frame_fragments {
+                // each fragment is preceded by an 8 byte item header
+                offset = offset
+                    .and_then(|o| o.checked_add(8))
+                    .and_then(|o| o.checked_add(u32::try_from(fragment.len()).ok()?));
+            }
+            fragments.extend(frame_fragments);
+        }
+
+        let offset_table: Vec<u32> = if self.offset_table {
+            offset_table
+                .into_iter()
+                .collect::<Option<_>>()
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        PixelFragmentSequence::new(offset_table, fragments)
+    }
+
+    /// Split the data of a single frame into fragments.
+    fn split_frame(&self, mut frame: Vec<u8>) -> Vec<InMemFragment> {
+        if self.pad_odd_fragments && frame.len() % 2 != 0 {
+            frame.push(0);
+        }
+        match self.max_fragment_length {
+            Some(max) if frame.len() > max as usize => frame
+                .chunks(max as usize)
+                .map(|fragment| fragment.to_vec())
+                .collect(),
+            _ => vec![frame],
+        }
+    }
+}
+
+/// Encapsulate the pixel data of a list of frames
+/// with the given options.
+///
+/// See [`EncapsulationOptions`] for more details.
+///
+/// # Example
+/// ```
+/// use dicom_core::DataElement;
+/// use dicom_core::VR::OB;
+/// use dicom_dictionary_std::tags;
+/// use dicom_pixeldata::encapsulation::{encapsulate_with_options, EncapsulationOptions};
+///
+/// let frames = vec![vec![0; 262144]; 2];
+/// let options = EncapsulationOptions::new()
+///     .max_fragment_length(65536)
+///     .offset_table(false);
+/// let pixel_data = encapsulate_with_options(frames, &options);
+/// let element = DataElement::new(tags::PIXEL_DATA, OB, pixel_data);
+/// ```
+pub fn encapsulate_with_options(frames: Vec<Vec<u8>>, options: &EncapsulationOptions) -> Value {
+    Value::PixelSequence(options.encapsulate(frames))
+}
+
+/// Retrieve the encoded data of a single frame
+/// from an encapsulated pixel data sequence.
+///
+/// This is the inverse of [`encapsulate`].
+/// The fragments which make up the frame
+/// are identified by [`FrameExtractionStrategy`],
+/// and concatenated if the frame spans more than one fragment.
+/// Returns `None` if the frame could not be attributed any fragment.
+///
+/// [`FrameExtractionStrategy`]: dicom_encoding::adapters::FrameExtractionStrategy
+///
+/// # Example
+/// ```
+/// use dicom_core::value::Value;
+/// use dicom_pixeldata::encapsulation::{encapsulate, frame_data};
+///
+/// let pixel_data = encapsulate(vec![vec![1, 2], vec![3, 4]]);
+/// if let Value::PixelSequence(seq) = pixel_data {
+///     assert_eq!(frame_data(&seq, 2, 1).as_deref(), Some(&[3, 4][..]));
+/// }
+/// ```
+pub fn frame_data<P: AsRef<[u8]>>(
+    seq: &PixelFragmentSequence<P>,
+    number_of_frames: u32,
+    frame: u32,
+) -> Option<Cow<'_, [u8]>> {
+    dicom_encoding::adapters::encapsulated_frame_data(
+        seq.fragments(),
+        seq.offset_table(),
+        number_of_frames,
+        frame,
+    )
+}
+
+/// Retrieve the fragments which make up a single frame
+/// of an encapsulated pixel data sequence,
+/// as identified by [`FrameExtractionStrategy`],
+/// without concatenating them.
+/// Returns `None` if the frame could not be attributed any fragment.
+///
+/// [`FrameExtractionStrategy`]: dicom_encoding::adapters::FrameExtractionStrategy
+pub fn frame_fragments<P: AsRef<[u8]>>(
+    seq: &PixelFragmentSequence<P>,
+    number_of_frames: u32,
+    frame: u32,
+) -> Option<&[P]> {
+    let layout =
+        FragmentLayout::from_fragments(number_of_frames, seq.fragments(), seq.offset_table());
+    let range = FrameExtractionStrategy::determine(&layout).frame_fragments(&layout, frame)?;
+    Some(&seq.fragments()[range])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,4 +305,142 @@ mod tests {
             unreachable!("encapsulate should always return a PixelSequence");
         }
     }
+
+    /// A simple linear congruential generator for test data.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as usize) % bound
+        }
+
+        fn frames(&mut self) -> Vec<Vec<u8>> {
+            (0..1 + self.next(5))
+                .map(|_| {
+                    (0..1 + self.next(300))
+                        .map(|_| self.next(256) as u8)
+                        .collect()
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_encapsulate_with_options_reproduces_frames() {
+        let mut rng = Lcg(0x5EED);
+        for _ in 0..200 {
+            let frames = rng.frames();
+            let max_fragment_length = rng.next(64) as u32;
+            let options = EncapsulationOptions::new().max_fragment_length(max_fragment_length);
+            let seq = options.encapsulate(frames.clone());
+
+            let fragments = seq.fragments();
+            let offsets = seq.offset_table();
+            assert_eq!(offsets.len(), frames.len());
+            assert!(fragments.iter().all(|f| f.len() % 2 == 0));
+            if max_fragment_length >= 2 {
+                let max = (max_fragment_length & !1) as usize;
+                assert!(fragments.iter().all(|f| f.len() <= max));
+            }
+
+            // the position of each fragment in the item stream
+            let mut positions = Vec::with_capacity(fragments.len());
+            let mut position = 0;
+            for fragment in fragments {
+                positions.push(position);
+                position += 8 + fragment.len() as u32;
+            }
+
+            for (i, frame) in frames.iter().enumerate() {
+                // offsets point at the start of each frame
+                let start = positions
+                    .iter()
+                    .position(|p| *p == offsets[i])
+                    .expect("offset should point at a fragment");
+                let end = offsets
+                    .get(i + 1)
+                    .map(|o| positions.iter().position(|p| p == o).unwrap())
+                    .unwrap_or(fragments.len());
+
+                // concatenated fragments reproduce the frame, plus padding
+                let data = fragments[start..end].concat();
+                assert_eq!(&data[..frame.len()], &frame[..]);
+                assert_eq!(data.len(), frame.len() + frame.len() % 2);
+                assert_eq!(
+                    frame_data(&seq, frames.len() as u32, i as u32).as_deref(),
+                    Some(&data[..])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_encapsulate_with_options() {
+        // no padding, no offset table
+        let options = EncapsulationOptions::new()
+            .max_fragment_length(3)
+            .pad_odd_fragments(false)
+            .offset_table(false);
+        let seq = options.encapsulate(vec![vec![1, 2, 3, 4, 5], vec![6]]);
+        assert_eq!(seq.fragments(), &[vec![1, 2], vec![3, 4], vec![5], vec![6]]);
+        assert!(seq.offset_table().is_empty());
+
+        // defaults match `encapsulate`
+        let frames = vec![vec![20, 30, 40], vec![50, 60, 70, 80]];
+        assert_eq!(
+            encapsulate_with_options(frames.clone(), &EncapsulationOptions::new()),
+            encapsulate(frames)
+        );
+
+        // empty frames still have a fragment
+        let seq = EncapsulationOptions::new().encapsulate(vec![vec![], vec![1, 2]]);
+        assert_eq!(seq.fragments(), &[vec![], vec![1, 2]]);
+        assert_eq!(seq.offset_table(), &[0, 8]);
+    }
+
+    #[test]
+    fn test_frame_data() {
+        let frames = vec![vec![20, 30, 40, 50], vec![60, 70], vec![80, 90, 100, 110]];
+        if let Value::PixelSequence(enc) = encapsulate(frames.clone()) {
+            for (i, frame) in frames.iter().enumerate() {
+                assert_eq!(frame_data(&enc, 3, i as u32).as_deref(), Some(&frame[..]));
+            }
+            assert_eq!(frame_data(&enc, 3, 3), None);
+        } else {
+            unreachable!("encapsulate should always return a PixelSequence");
+        }
+
+        // a single frame split across multiple fragments
+        if let Value::PixelSequence(enc) = encapsulate_single_frame(vec![1, 2, 3, 4, 5, 6], 2) {
+            assert_eq!(
+                frame_data(&enc, 1, 0).as_deref(),
+                Some(&[1, 2, 3, 4, 5, 6][..])
+            );
+        } else {
+            unreachable!("encapsulate should always return a PixelSequence");
+        }
+    }
+
+    #[test]
+    fn test_frame_fragments() {
+        let frames = vec![vec![20, 30, 40, 50], vec![60, 70]];
+        if let Value::PixelSequence(enc) = encapsulate(frames.clone()) {
+            assert_eq!(frame_fragments(&enc, 2, 1), Some(&frames[1..]));
+            assert_eq!(frame_fragments(&enc, 2, 2), None);
+        } else {
+            unreachable!("encapsulate should always return a PixelSequence");
+        }
+
+        // a single frame split across multiple fragments
+        if let Value::PixelSequence(enc) = encapsulate_single_frame(vec![1, 2, 3, 4, 5, 6], 2) {
+            let fragments = frame_fragments(&enc, 1, 0).unwrap();
+            assert_eq!(fragments, &[vec![1, 2], vec![3, 4], vec![5, 6]]);
+        } else {
+            unreachable!("encapsulate should always return a PixelSequence");
+        }
+    }
 }
@@ -0,0 +1,186 @@
+//! Iteration over the frames of decoded pixel data.
+//!
+//! See [`DecodedPixelData::frames`] for more information.
+
+use crate::{
+    native_stored_frame_size, ConvertOptions, DecodedPixelData, Rescale, Result, SampleState,
+    WindowLevel,
+};
+#[cfg(feature = "image")]
+use image::DynamicImage;
+use num_traits::NumCast;
+use std::convert::TryFrom;
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+/// A view of a single frame of [`DecodedPixelData`],
+/// as yielded by [`DecodedPixelData::frames`].
+///
+/// No pixel data is copied
+/// until one of the conversion methods is called.
+#[derive(Debug, Clone, Copy)]
+pub struct DecodedFrame<'a> {
+    pixel_data: &'a DecodedPixelData<'a>,
+    frame: u32,
+}
+
+impl<'a> DecodedFrame<'a> {
+    /// Retrieve the index of this frame, starting at 0.
+    #[inline]
+    pub fn number(&self) -> u32 {
+        self.frame
+    }
+
+    /// Retrieve the pixel data which this frame belongs to.
+    #[inline]
+    pub fn pixel_data(&self) -> &'a DecodedPixelData<'a> {
+        self.pixel_data
+    }
+
+    /// Retrieve a slice of the frame's raw pixel data samples as bytes,
+    /// irrespective of the expected size of each sample.
+    pub fn data(&self) -> &'a [u8] {
+        self.pixel_data
+            .frame_data(self.frame)
+            .expect("frame should be within the decoded pixel data")
+    }
+
+    /// Retrieve the rescale parameters which apply to this frame.
+    pub fn rescale(&self) -> Result<Rescale> {
+        let rescale = self.pixel_data.rescale()?;
+        Ok(*rescale.get(self.frame as usize).unwrap_or(&rescale[0]))
+    }
+
+    /// Retrieve the alternative window levels
+    /// which apply to this frame, if any.
+    pub fn window(&self) -> Result<Option<&'a [WindowLevel]>> {
+        self.pixel_data.window_for_frame(self.frame)
+    }
+
+    /// Convert the frame into a vector of flat pixels of a given type `T`,
+    /// as in [`DecodedPixelData::to_vec_frame`].
+    pub fn to_vec<T>(&self) -> Result<Vec<T>>
+    where
+        T: NumCast + Send + Sync + Copy + 'static,
+    {
+        self.pixel_data.to_vec_frame(self.frame)
+    }
+
+    /// Convert the frame into a vector of flat pixels of a given type `T`,
+    /// as in [`DecodedPixelData::to_vec_frame_with_options`].
+    pub fn to_vec_with_options<T>(&self, options: &ConvertOptions) -> Result<Vec<T>>
+    where
+        T: NumCast + Send + Sync + Copy + 'static,
+    {
+        self.pixel_data
+            .to_vec_frame_with_options(self.frame, options)
+    }
+
+    /// Convert the frame into a dynamic image,
+    /// as in [`DecodedPixelData::to_dynamic_image`].
+    #[cfg(feature = "image")]
+    pub fn to_dynamic_image(&self) -> Result<DynamicImage> {
+        self.pixel_data.to_dynamic_image(self.frame)
+    }
+
+    /// Convert the frame into a dynamic image,
+    /// as in [`DecodedPixelData::to_dynamic_image_with_options`].
+    #[cfg(feature = "image")]
+    pub fn to_dynamic_image_with_options(&self, options: &ConvertOptions) -> Result<DynamicImage> {
+        self.pixel_data
+            .to_dynamic_image_with_options(self.frame, options)
+    }
+}
+
+/// An iterator over the frames of [`DecodedPixelData`],
+/// created by [`DecodedPixelData::frames`].
+#[derive(Debug, Clone)]
+pub struct Frames<'a> {
+    pixel_data: &'a DecodedPixelData<'a>,
+    frames: Range<u32>,
+}
+
+impl<'a> Frames<'a> {
+    pub(crate) fn new(pixel_data: &'a DecodedPixelData<'a>) -> Self {
+        Frames {
+            pixel_data,
+            frames: 0..available_frames(pixel_data),
+        }
+    }
+
+    fn frame(&self, frame: u32) -> DecodedFrame<'a> {
+        DecodedFrame {
+            pixel_data: self.pixel_data,
+            frame,
+        }
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = DecodedFrame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.frames.next()?;
+        Some(self.frame(frame))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.frames.size_hint()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let frame = self.frames.nth(n)?;
+        Some(self.frame(frame))
+    }
+}
+
+impl DoubleEndedIterator for Frames<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let frame = self.frames.next_back()?;
+        Some(self.frame(frame))
+    }
+}
+
+impl ExactSizeIterator for Frames<'_> {}
+
+impl FusedIterator for Frames<'_> {}
+
+/// Create a parallel iterator over the frames of the given pixel data.
+#[cfg(feature = "rayon")]
+pub(crate) fn par_frames<'a>(
+    pixel_data: &'a DecodedPixelData<'a>,
+) -> impl rayon::iter::IndexedParallelIterator<Item = DecodedFrame<'a>> {
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    (0..available_frames(pixel_data))
+        .into_par_iter()
+        .map(move |frame| DecodedFrame { pixel_data, frame })
+}
+
+/// Determine the number of frames
+/// which are actually available in the decoded samples,
+/// which may be fewer than declared if the pixel data is truncated,
+/// and none if the samples were not decoded.
+fn available_frames(pixel_data: &DecodedPixelData<'_>) -> u32 {
+    if !matches!(pixel_data.samples, SampleState::Decoded) {
+        return 0;
+    }
+    let frame_size = native_stored_frame_size(
+        &pixel_data.photometric_interpretation,
+        pixel_data.bits_allocated,
+        pixel_data.samples_per_pixel,
+        pixel_data.rows,
+        pixel_data.cols,
+        pixel_data.number_of_frames,
+        pixel_data.data.len(),
+    );
+    match frame_size {
+        Some(frame_size) if frame_size > 0 => {
+            let frames = pixel_data.data.len() / frame_size;
+            pixel_data
+                .number_of_frames
+                .min(u32::try_from(frames).unwrap_or(u32::MAX))
+        }
+        _ => 0,
+    }
+}
@@ -1,14 +1,19 @@
 //! Decode pixel data using GDCM when the default features are enabled.
 
 use crate::{
-    DecodePixelDataSnafu, DecodedPixelData, GetAttributeSnafu, InvalidPixelDataSnafu,
-    LengthMismatchRescaleSnafu, LengthMismatchWindowLevelSnafu, PixelDecoder, Rescale, Result,
+    attribute, check_trailing_bytes, decoded_photometric_interpretation, has_pixel_data,
+    native_frame_range, native_frame_size, native_stored_frame_size, packed_len, unpack_bits,
+    DecodePixelDataSnafu, DecodedPixelData, FrameOrder, FrameOutOfRangeSnafu,
+    FrameSizeOverflowSnafu, GetAttributeSnafu, ImagingProperties, InvalidPixelDataSnafu,
+    NoPixelDataSnafu, PhotometricInterpretation, PixelDecoder, PlanarConfiguration, Result,
     UnknownTransferSyntaxSnafu, UnsupportedPhotometricInterpretationSnafu,
-    UnsupportedTransferSyntaxSnafu, VoiLutFunction, WindowLevel,
+    UnsupportedTransferSyntaxSnafu,
 };
 use dicom_core::{DataDictionary, DicomValue};
-use dicom_dictionary_std::tags;
-use dicom_encoding::{adapters::DecodeError, transfer_syntax::TransferSyntaxIndex};
+use dicom_encoding::{
+    adapters::{DecodeError, FragmentLayout, FrameExtractionStrategy, PixelDataObject},
+    transfer_syntax::TransferSyntaxIndex,
+};
 use dicom_object::{FileDicomObject, InMemDicomObject};
 use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
 use gdcm_rs::{
@@ -16,78 +21,51 @@ use gdcm_rs::{
     GDCMPhotometricInterpretation, GDCMTransferSyntax,
 };
 use snafu::{ensure, OptionExt, ResultExt};
-use std::{borrow::Cow, convert::TryFrom, iter::zip, str::FromStr};
+use std::{borrow::Cow, str::FromStr};
 
 impl<D> PixelDecoder for FileDicomObject<InMemDicomObject<D>>
 where
     D: DataDictionary + Clone,
 {
     fn decode_pixel_data(&self) -> Result<DecodedPixelData> {
-        use super::attribute::*;
-
-        let pixel_data = pixel_data(self).context(GetAttributeSnafu)?;
-
-        let cols = cols(self).context(GetAttributeSnafu)?;
-        let rows = rows(self).context(GetAttributeSnafu)?;
-
-        let photometric_interpretation =
-            photometric_interpretation(self).context(GetAttributeSnafu)?;
-        let pi_type = match photometric_interpretation {
-            PhotometricInterpretation::PaletteColor => GDCMPhotometricInterpretation::PALETTE_COLOR,
-            _ => GDCMPhotometricInterpretation::from_str(photometric_interpretation.as_str())
-                .map_err(|_| {
-                    UnsupportedPhotometricInterpretationSnafu {
-                        pi: photometric_interpretation.clone(),
-                    }
-                    .build()
-                })?,
-        };
-
-        let transfer_syntax = &self.meta().transfer_syntax;
-        let registry =
-            TransferSyntaxRegistry
-                .get(transfer_syntax)
-                .context(UnknownTransferSyntaxSnafu {
-                    ts_uid: transfer_syntax,
-                })?;
-        let ts_type = GDCMTransferSyntax::from_str(registry.uid()).map_err(|_| {
-            UnsupportedTransferSyntaxSnafu {
-                ts: transfer_syntax.clone(),
-            }
-            .build()
-        })?;
-
-        let samples_per_pixel = samples_per_pixel(self).context(GetAttributeSnafu)?;
-        let bits_allocated = bits_allocated(self).context(GetAttributeSnafu)?;
-        let bits_stored = bits_stored(self).context(GetAttributeSnafu)?;
-        let high_bit = high_bit(self).context(GetAttributeSnafu)?;
-        let pixel_representation = pixel_representation(self).context(GetAttributeSnafu)?;
-        let rescale_intercept = rescale_intercept(self);
-        let rescale_slope = rescale_slope(self);
-        let number_of_frames = number_of_frames(self).context(GetAttributeSnafu)?;
-        let voi_lut_function: Option<Vec<VoiLutFunction>> =
-            voi_lut_function(self).unwrap_or(None).and_then(|fns| {
-                fns.iter()
-                    .map(|v| VoiLutFunction::try_from((*v).as_str()).ok())
-                    .collect()
-            });
-
-        ensure!(
-            rescale_intercept.len() == rescale_slope.len(),
-            LengthMismatchRescaleSnafu {
-                slope_vm: rescale_slope.len() as u32,
-                intercept_vm: rescale_intercept.len() as u32,
-            }
-        );
-
-        let decoded_pixel_data = match pixel_data.value() {
+        ensure!(has_pixel_data(self), NoPixelDataSnafu);
+        let pixel_data = attribute::pixel_data(self).context(GetAttributeSnafu)?;
+
+        let imaging_properties = ImagingProperties::from_object(self)?;
+        let rescale = imaging_properties.rescale();
+        let ImagingProperties {
+            cols,
+            rows,
+            samples_per_pixel,
+            bits_allocated,
+            bits_stored,
+            high_bit,
+            pixel_representation,
+            sample_format,
+            planar_configuration,
+            photometric_interpretation,
+            number_of_frames,
+            modality_lut,
+            voi_lut_function,
+            window,
+            window_voi_lut_functions,
+            voi_luts,
+            palette,
+            pixel_padding,
+            frame_order,
+            photometric_interpretation_mismatch,
+            value_multiplicity_mismatches,
+            defaulted_attributes,
+            ..
+        } = imaging_properties;
+
+        let (decoded_pixel_data, trailing_bytes) = match pixel_data.value() {
             DicomValue::PixelSequence(v) => {
+                let pi_type = gdcm_photometric_interpretation(&photometric_interpretation)?;
+                let ts_type = gdcm_transfer_syntax(self)?;
+
                 let fragments = v.fragments();
-                let gdcm_error_mapper = |source: GDCMError| DecodeError::Custom {
-                    message: source.to_string(),
-                    source: Some(Box::new(source)),
-                };
-                if fragments.len() > 1 {
+                let data = if fragments.len() > 1 {
                     // Bundle fragments and decode multi-frame dicoms
                     let dims = [cols.into(), rows.into(), number_of_frames];
                     let fragments: Vec<_> = fragments.iter().map(|frag| frag.as_slice()).collect();
@@ -106,8 +84,9 @@ where
                     .context(DecodePixelDataSnafu)?
                     .to_vec()
                 } else {
+                    let fragment = fragments.first().context(InvalidPixelDataSnafu)?;
                     decode_single_frame_compressed(
-                        &fragments[0],
+                        fragment,
                         cols.into(),
                         rows.into(),
                         pi_type,
@@ -121,280 +100,394 @@ where
                     .map_err(gdcm_error_mapper)
                     .context(DecodePixelDataSnafu)?
                     .to_vec()
-                }
+                };
+
+                // pixels are already interpreted,
+                // set new photometric interpretation if necessary
+                let (new_pi, original_photometric_interpretation) =
+                    decoded_photometric_interpretation(
+                        photometric_interpretation,
+                        samples_per_pixel,
+                    );
+
+                return Ok(DecodedPixelData {
+                    data: Cow::from(data),
+                    samples: SampleState::Decoded,
+                    cols: cols.into(),
+                    rows: rows.into(),
+                    number_of_frames,
+                    photometric_interpretation: new_pi,
+                    original_photometric_interpretation,
+                    samples_per_pixel,
+                    planar_configuration: PlanarConfiguration::Standard,
+                    bits_allocated,
+                    bits_stored,
+                    high_bit,
+                    pixel_representation,
+                    sample_format,
+                    rescale,
+                    modality_lut,
+                    voi_lut_function,
+                    window,
+                    window_voi_lut_functions,
+                    voi_luts,
+                    palette,
+                    pixel_padding,
+                    frame_order,
+                    enforce_frame_fg_vm_match: false,
+                    photometric_interpretation_mismatch,
+                    value_multiplicity_mismatches,
+                    defaulted_attributes,
+                    declared_dimensions: None,
+                    trailing_bytes: 0,
+                });
             }
-            DicomValue::Primitive(p) => {
-                // Non-encoded, just return the pixel data of the first frame
-                p.to_bytes().to_vec()
+            DicomValue::Primitive(p) if bits_allocated == 1 => {
+                // packed samples, the frames are measured in bits
+                let data = p.to_bytes();
+                let frame_size =
+                    native_frame_size(bits_allocated, samples_per_pixel, rows.into(), cols.into())
+                        .context(FrameSizeOverflowSnafu)?;
+                let len = frame_size
+                    .checked_mul(number_of_frames as usize)
+                    .context(FrameSizeOverflowSnafu)?;
+                let trailing_bytes = check_trailing_bytes(data.len(), packed_len(len), 1, None)?;
+                (unpack_bits(&data, 0, len), trailing_bytes)
             }
-            DicomValue::Sequence(_) => InvalidPixelDataSnafu.fail()?,
-        };
-
-
-        let rescale = zip(&rescale_intercept, &rescale_slope)
-            .map(|(intercept, slope)| Rescale {
-                intercept: *intercept,
-                slope: *slope,
-            })
-            .collect();
-
-        let window = if let Some(wcs) = window_center(&self) {
-            let width = window_width(&self);
-            if let Some(wws) = width {
-                ensure!(
-                    wcs.len() == wws.len(),
-                    LengthMismatchWindowLevelSnafu {
-                        wc_vm: wcs.len() as u32,
-                        ww_vm: wws.len() as u32,
-                    }
-                );
-                Some(
-                    zip(wcs, wws)
-                        .map(|(wc, ww)| WindowLevel {
-                            center: wc,
-                            width: ww,
-                        })
-                        .collect(),
+            DicomValue::Primitive(p) => {
+                // Non-encoded, just return the pixel data for all frames,
+                // leaving out anything after the last frame
+                let data = p.to_bytes();
+                let frame_size = native_stored_frame_size(
+                    &photometric_interpretation,
+                    bits_allocated,
+                    samples_per_pixel,
+                    rows.into(),
+                    cols.into(),
+                    number_of_frames,
+                    data.len(),
                 )
-            } else {
-                None
+                .context(FrameSizeOverflowSnafu)?;
+                let trailing_bytes =
+                    check_trailing_bytes(data.len(), frame_size, number_of_frames, None)?;
+                (data[..data.len() - trailing_bytes].to_vec(), trailing_bytes)
             }
-        } else {
-            None
+            DicomValue::Sequence(_) => InvalidPixelDataSnafu.fail()?,
         };
 
         Ok(DecodedPixelData {
             data: Cow::from(decoded_pixel_data),
+            samples: SampleState::Decoded,
             cols: cols.into(),
             rows: rows.into(),
             number_of_frames,
             photometric_interpretation,
+            original_photometric_interpretation: None,
             samples_per_pixel,
-            planar_configuration: PlanarConfiguration::Standard,
+            planar_configuration,
             bits_allocated,
             bits_stored,
             high_bit,
             pixel_representation,
+            sample_format,
             rescale,
+            modality_lut,
             voi_lut_function,
             window,
+            window_voi_lut_functions,
+            voi_luts,
+            palette,
+            pixel_padding,
+            frame_order,
             enforce_frame_fg_vm_match: false,
+            photometric_interpretation_mismatch,
+            value_multiplicity_mismatches,
+            defaulted_attributes,
+            declared_dimensions: None,
+            trailing_bytes,
         })
     }
 
     fn decode_pixel_data_frame(&self, frame: u32) -> Result<DecodedPixelData<'_>> {
-        use super::attribute::*;
-
-        let pixel_data = pixel_data(self).context(GetAttributeSnafu)?;
-
-        let cols = cols(self).context(GetAttributeSnafu)?;
-        let rows = rows(self).context(GetAttributeSnafu)?;
-
-        let photometric_interpretation =
-            photometric_interpretation(self).context(GetAttributeSnafu)?;
-        let pi_type = match photometric_interpretation {
-            PhotometricInterpretation::PaletteColor => GDCMPhotometricInterpretation::PALETTE_COLOR,
-            _ => GDCMPhotometricInterpretation::from_str(photometric_interpretation.as_str())
-                .map_err(|_| {
-                    UnsupportedPhotometricInterpretationSnafu {
-                        pi: photometric_interpretation.clone(),
+        ensure!(has_pixel_data(self), NoPixelDataSnafu);
+        let pixel_data = attribute::pixel_data(self).context(GetAttributeSnafu)?;
+
+        let imaging_properties = ImagingProperties::from_object(self)?.for_frame(frame);
+        let rescale = imaging_properties.rescale();
+        let ImagingProperties {
+            cols,
+            rows,
+            samples_per_pixel,
+            bits_allocated,
+            bits_stored,
+            high_bit,
+            pixel_representation,
+            sample_format,
+            planar_configuration,
+            photometric_interpretation,
+            number_of_frames,
+            modality_lut,
+            voi_lut_function,
+            window,
+            window_voi_lut_functions,
+            voi_luts,
+            palette,
+            pixel_padding,
+            photometric_interpretation_mismatch,
+            value_multiplicity_mismatches,
+            defaulted_attributes,
+            ..
+        } = imaging_properties;
+
+        let frame_size =
+            native_frame_size(bits_allocated, samples_per_pixel, rows.into(), cols.into())
+                .context(FrameSizeOverflowSnafu)?;
+        let frame_range = native_frame_range(frame_size, frame).context(FrameSizeOverflowSnafu)?;
+
+        let (decoded_pixel_data, trailing_bytes) = match pixel_data.value() {
+            DicomValue::PixelSequence(v) => {
+                // fail early instead of decoding all frames to no avail
+                ensure!(
+                    frame < number_of_frames,
+                    FrameOutOfRangeSnafu {
+                        frame_number: frame,
                     }
-                    .build()
-                })?,
-        };
-
-        let transfer_syntax = &self.meta().transfer_syntax;
-        let registry =
-            TransferSyntaxRegistry
-                .get(transfer_syntax)
-                .context(UnknownTransferSyntaxSnafu {
-                    ts_uid: transfer_syntax,
-                })?;
-        let ts_type = GDCMTransferSyntax::from_str(registry.uid()).map_err(|_| {
-            UnsupportedTransferSyntaxSnafu {
-                ts: transfer_syntax.clone(),
-            }
-            .build()
-        })?;
+                );
+                let pi_type = gdcm_photometric_interpretation(&photometric_interpretation)?;
+                let ts_type = gdcm_transfer_syntax(self)?;
 
-        let samples_per_pixel = samples_per_pixel(self).context(GetAttributeSnafu)?;
-        let bits_allocated = bits_allocated(self).context(GetAttributeSnafu)?;
-        let bits_stored = bits_stored(self).context(GetAttributeSnafu)?;
-        let high_bit = high_bit(self).context(GetAttributeSnafu)?;
-        let pixel_representation = pixel_representation(self).context(GetAttributeSnafu)?;
-        let planar_configuration = if let Ok(el) = self.element(tags::PLANAR_CONFIGURATION) {
-            el.uint16().unwrap_or(0)
-        } else {
-            0
-        };
-        let rescale_intercept = rescale_intercept(self);
-        let rescale_slope = rescale_slope(self);
-        let number_of_frames = number_of_frames(self).context(GetAttributeSnafu)?;
-        let voi_lut_function: Option<Vec<VoiLutFunction>> =
-            voi_lut_function(self).unwrap_or(None).and_then(|fns| {
-                fns.iter()
-                    .map(|v| VoiLutFunction::try_from((*v).as_str()).ok())
-                    .collect()
-            });
-
-        let decoded_pixel_data = match pixel_data.value() {
-            DicomValue::PixelSequence(v) => {
                 let fragments = v.fragments();
-                let gdcm_error_mapper = |source: GDCMError| DecodeError::Custom {
-                    message: source.to_string(),
-                    source: Some(Box::new(source)),
+                let fragments: Vec<_> = fragments.iter().map(|frag| frag.as_slice()).collect();
+
+                // identify the fragments of the requested frame, if possible
+                let frame_fragments = if number_of_frames > 1 {
+                    let layout = FragmentLayout::from_fragments(
+                        number_of_frames,
+                        &fragments,
+                        v.offset_table(),
+                    )
+                    .with_extended_offset_table(
+                        self.extended_offset_table()
+                            .map(Cow::into_owned)
+                            .unwrap_or_default(),
+                    );
+                    FrameExtractionStrategy::determine_and_warn(&layout)
+                        .frame_fragments(&layout, frame)
+                } else {
+                    None
                 };
 
-                let frame = frame as usize;
-                let data = if number_of_frames == 1 && fragments.len() > 1 {
-                    fragments.iter().flat_map(|frame| frame.to_vec()).collect()
+                let data = if let Some(frame_fragments) = frame_fragments {
+                    // decode only the fragments of the requested frame
+                    decode_multi_frame_compressed(
+                        &fragments[frame_fragments],
+                        &[cols.into(), rows.into(), 1],
+                        pi_type,
+                        ts_type,
+                        samples_per_pixel,
+                        bits_allocated,
+                        bits_stored,
+                        high_bit,
+                        pixel_representation as u16,
+                    )
+                    .map_err(gdcm_error_mapper)
+                    .context(DecodePixelDataSnafu)?
+                    .to_vec()
                 } else {
-                    fragments[frame].to_vec()
+                    // frames cannot be told apart by fragment,
+                    // decode everything and crop the requested frame
+                    let data = decode_multi_frame_compressed(
+                        fragments.as_slice(),
+                        &[cols.into(), rows.into(), number_of_frames],
+                        pi_type,
+                        ts_type,
+                        samples_per_pixel,
+                        bits_allocated,
+                        bits_stored,
+                        high_bit,
+                        pixel_representation as u16,
+                    )
+                    .map_err(gdcm_error_mapper)
+                    .context(DecodePixelDataSnafu)?;
+                    data.get(frame_range)
+                        .context(FrameOutOfRangeSnafu {
+                            frame_number: frame,
+                        })?
+                        .to_vec()
                 };
 
-                match ts_type {
-                    GDCMTransferSyntax::ImplicitVRLittleEndian
-                    | GDCMTransferSyntax::ExplicitVRLittleEndian => {
-                        // This is just in case of encapsulated uncompressed data
-                        let frame_size = cols * rows * samples_per_pixel * (bits_allocated / 8);
-                        data.chunks_exact(frame_size as usize)
-                            .nth(frame)
-                            .map(|frame| frame.to_vec())
-                            .unwrap_or_default()
-                    }
-                    _ => {
-                        let buffer = [data.as_slice()];
-                        let dims = [cols.into(), rows.into(), 1];
-
-                        decode_multi_frame_compressed(
-                            &buffer,
-                            &dims,
-                            pi_type,
-                            ts_type,
-                            samples_per_pixel,
-                            bits_allocated,
-                            bits_stored,
-                            high_bit,
-                            pixel_representation as u16,
-                        )
-                        .map_err(gdcm_error_mapper)
-                        .context(DecodePixelDataSnafu)?
-                        .to_vec()
-                    }
-                }
-            }
-            DicomValue::Primitive(p) => {
-                // Uncompressed data
-                let frame_size = cols as usize
-                    * rows as usize
-                    * samples_per_pixel as usize
-                    * (bits_allocated as usize / 8);
-                p.to_bytes()
-                    .chunks_exact(frame_size)
-                    .nth(frame as usize)
-                    .map(|frame| frame.to_vec())
-                    .unwrap_or_default()
+                // pixels are already interpreted,
+                // set new photometric interpretation if necessary
+                let (new_pi, original_photometric_interpretation) =
+                    decoded_photometric_interpretation(
+                        photometric_interpretation,
+                        samples_per_pixel,
+                    );
+
+                return Ok(DecodedPixelData {
+                    data: Cow::from(data),
+                    samples: SampleState::Decoded,
+                    cols: cols.into(),
+                    rows: rows.into(),
+                    number_of_frames: 1,
+                    photometric_interpretation: new_pi,
+                    original_photometric_interpretation,
+                    samples_per_pixel,
+                    planar_configuration: PlanarConfiguration::Standard,
+                    bits_allocated,
+                    bits_stored,
+                    high_bit,
+                    pixel_representation,
+                    sample_format,
+                    rescale,
+                    modality_lut,
+                    voi_lut_function,
+                    window,
+                    window_voi_lut_functions,
+                    voi_luts,
+                    palette,
+                    pixel_padding,
+                    frame_order: FrameOrder::stored(1),
+                    enforce_frame_fg_vm_match: false,
+                    photometric_interpretation_mismatch,
+                    value_multiplicity_mismatches,
+                    defaulted_attributes,
+                    declared_dimensions: None,
+                    trailing_bytes: 0,
+                });
             }
-            DicomValue::Sequence(_) => InvalidPixelDataSnafu.fail()?,
-        };
-
-        // Convert to PlanarConfiguration::Standard
-        let decoded_pixel_data = if planar_configuration == 1 && samples_per_pixel == 3 {
-            interleave_planes(
-                cols as usize,
-                rows as usize,
-                bits_allocated as usize,
-                decoded_pixel_data,
-            )
-        } else {
-            decoded_pixel_data
-        };
-
-        let window = if let Some(wcs) = window_center(self) {
-            let width = window_width(self);
-            if let Some(wws) = width {
+            DicomValue::Primitive(p) if bits_allocated == 1 => {
+                // packed samples, the frames are measured in bits
+                // and do not necessarily start on a byte boundary
+                let data = p.to_bytes();
+                let len = frame_size
+                    .checked_mul(number_of_frames as usize)
+                    .context(FrameSizeOverflowSnafu)?;
+                let trailing_bytes = check_trailing_bytes(data.len(), packed_len(len), 1, None)?;
                 ensure!(
-                    wcs.len() == wws.len(),
-                    LengthMismatchWindowLevelSnafu {
-                        wc_vm: wcs.len() as u32,
-                        ww_vm: wws.len() as u32,
+                    frame_range.end <= data.len().saturating_mul(8),
+                    FrameOutOfRangeSnafu {
+                        frame_number: frame,
                     }
                 );
-                Some(
-                    zip(wcs, wws)
-                        .map(|(wc, ww)| WindowLevel {
-                            center: wc,
-                            width: ww,
-                        })
-                        .collect(),
+                (
+                    unpack_bits(&data, frame_range.start, frame_size),
+                    trailing_bytes,
+                )
+            }
+            DicomValue::Primitive(p) => {
+                // Non-encoded, just return the pixel data for a single frame
+                let data = p.to_bytes();
+                let frame_size = native_stored_frame_size(
+                    &photometric_interpretation,
+                    bits_allocated,
+                    samples_per_pixel,
+                    rows.into(),
+                    cols.into(),
+                    number_of_frames,
+                    data.len(),
                 )
-            } else {
-                None
+                .context(FrameSizeOverflowSnafu)?;
+                let frame_range =
+                    native_frame_range(frame_size, frame).context(FrameSizeOverflowSnafu)?;
+                let trailing_bytes =
+                    check_trailing_bytes(data.len(), frame_size, number_of_frames, None)?;
+                let data = data
+                    .get(frame_range)
+                    .context(FrameOutOfRangeSnafu {
+                        frame_number: frame,
+                    })?
+                    .to_vec();
+                (data, trailing_bytes)
             }
-        } else {
-            None
+            DicomValue::Sequence(_) => InvalidPixelDataSnafu.fail()?,
         };
-        let rescale = zip(&rescale_intercept, &rescale_slope)
-            .map(|(intercept, slope)| Rescale {
-                intercept: *intercept,
-                slope: *slope,
-            })
-            .collect();
 
         Ok(DecodedPixelData {
             data: Cow::from(decoded_pixel_data),
+            samples: SampleState::Decoded,
             cols: cols.into(),
             rows: rows.into(),
             number_of_frames: 1,
             photometric_interpretation,
+            original_photometric_interpretation: None,
             samples_per_pixel,
-            planar_configuration: PlanarConfiguration::Standard,
+            planar_configuration,
             bits_allocated,
             bits_stored,
             high_bit,
             pixel_representation,
-            rescale: rescale,
+            sample_format,
+            rescale,
+            modality_lut,
             voi_lut_function,
             window,
+            window_voi_lut_functions,
+            voi_luts,
+            palette,
+            pixel_padding,
+            frame_order: FrameOrder::stored(1),
             enforce_frame_fg_vm_match: false,
+            photometric_interpretation_mismatch,
+            value_multiplicity_mismatches,
+            defaulted_attributes,
+            declared_dimensions: None,
+            trailing_bytes,
         })
     }
 }
 
-fn interleave_planes(cols: usize, rows: usize, bits_allocated: usize, data: Vec<u8>) -> Vec<u8> {
-    let frame_size = cols * rows * (bits_allocated / 8);
-    let mut interleaved = Vec::with_capacity(data.len());
-
-    let mut i = 0;
-    while i < frame_size {
-        interleaved.push(data[i]);
-        if bits_allocated > 8 {
-            interleaved.push(data[i + 1])
-        }
-
-        interleaved.push(data[i + frame_size]);
-        if bits_allocated > 8 {
-            interleaved.push(data[i + frame_size + 1])
-        }
+fn gdcm_error_mapper(source: GDCMError) -> DecodeError {
+    DecodeError::Custom {
+        message: source.to_string(),
+        source: Some(Box::new(source)),
+    }
+}
 
-        interleaved.push(data[i + frame_size * 2]);
-        if bits_allocated > 8 {
-            interleaved.push(data[i + frame_size * 2 + 1])
+fn gdcm_photometric_interpretation(
+    photometric_interpretation: &PhotometricInterpretation,
+) -> Result<GDCMPhotometricInterpretation> {
+    match photometric_interpretation {
+        PhotometricInterpretation::PaletteColor => {
+            Ok(GDCMPhotometricInterpretation::PALETTE_COLOR)
         }
-
-        i = if bits_allocated > 8 { i + 2 } else { i + 1 };
+        _ => GDCMPhotometricInterpretation::from_str(photometric_interpretation.as_str()).map_err(
+            |_| {
+                UnsupportedPhotometricInterpretationSnafu {
+                    pi: photometric_interpretation.clone(),
+                }
+                .build()
+                .into()
+            },
+        ),
     }
+}
 
-    interleaved
+fn gdcm_transfer_syntax<D>(obj: &FileDicomObject<InMemDicomObject<D>>) -> Result<GDCMTransferSyntax>
+where
+    D: DataDictionary + Clone,
+{
+    let transfer_syntax = obj.meta().transfer_syntax();
+    let registry = TransferSyntaxRegistry
+        .get(transfer_syntax)
+        .context(UnknownTransferSyntaxSnafu {
+            ts_uid: transfer_syntax,
+        })?;
+    GDCMTransferSyntax::from_str(registry.uid()).map_err(|_| {
+        UnsupportedTransferSyntaxSnafu {
+            ts: transfer_syntax.to_string(),
+            features: &[],
+        }
+        .build()
+        .into()
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    #[cfg(any(feature = "ndarray", feature = "image"))]
     use super::*;
-    #[cfg(any(feature = "ndarray", feature = "image"))]
+    use crate::{Error, InnerError};
     use dicom_object::open_file;
-    #[cfg(feature = "image")]
     use rstest::rstest;
     #[cfg(feature = "image")]
     use std::path::Path;
@@ -488,6 +581,80 @@ mod tests {
         image.save(image_path).unwrap();
     }
 
+    /// Assert that two decoded pixel data objects
+    /// have the same metadata and pixel values.
+    fn assert_same_decoded_pixel_data(native: &DecodedPixelData, gdcm: &DecodedPixelData) {
+        assert_eq!(native.rows(), gdcm.rows());
+        assert_eq!(native.columns(), gdcm.columns());
+        assert_eq!(native.number_of_frames(), gdcm.number_of_frames());
+        assert_eq!(
+            native.photometric_interpretation(),
+            gdcm.photometric_interpretation()
+        );
+        assert_eq!(native.samples_per_pixel(), gdcm.samples_per_pixel());
+        assert_eq!(native.planar_configuration(), gdcm.planar_configuration());
+        assert_eq!(native.bits_allocated(), gdcm.bits_allocated());
+        assert_eq!(native.bits_stored(), gdcm.bits_stored());
+        assert_eq!(native.high_bit(), gdcm.high_bit());
+        assert_eq!(native.pixel_representation(), gdcm.pixel_representation());
+        assert_eq!(native.rescale().unwrap(), gdcm.rescale().unwrap());
+        assert_eq!(
+            native.voi_lut_function().unwrap(),
+            gdcm.voi_lut_function().unwrap()
+        );
+        assert_eq!(native.window().unwrap(), gdcm.window().unwrap());
+        assert!(native.data() == gdcm.data(), "pixel values differ");
+    }
+
+    /// The GDCM backend and the native backend
+    /// produce the same decoded pixel data.
+    #[rstest]
+    #[case("pydicom/CT_small.dcm")]
+    #[case("pydicom/MR_small.dcm")]
+    #[case("pydicom/MR_small_RLE.dcm")]
+    #[case("pydicom/MR_small_jpeg_ls_lossless.dcm")]
+    #[case("pydicom/JPGLosslessP14SV1_1s_1f_8b.dcm")]
+    #[case("pydicom/SC_rgb.dcm")]
+    #[case("pydicom/SC_rgb_16bit.dcm")]
+    #[case("pydicom/SC_rgb_rle.dcm")]
+    #[case("pydicom/SC_rgb_rle_2frame.dcm")]
+    #[case("pydicom/color-pl.dcm")]
+    fn test_parity_with_native_decoding(#[case] value: &str) {
+        let test_file = dicom_test_files::path(value).unwrap();
+        let obj = open_file(test_file).unwrap();
+
+        let native = crate::decode_pixel_data_native(&obj, &Default::default()).unwrap();
+        let gdcm = obj.decode_pixel_data().unwrap();
+        assert_same_decoded_pixel_data(&native, &gdcm);
+
+        for frame in 0..native.number_of_frames() {
+            let native =
+                crate::decode_pixel_data_frame_native(&obj, frame, &Default::default()).unwrap();
+            let gdcm = obj.decode_pixel_data_frame(frame).unwrap();
+            assert_same_decoded_pixel_data(&native, &gdcm);
+        }
+    }
+
+    /// Requesting a frame beyond the number of frames
+    /// fails instead of returning empty pixel data.
+    #[test]
+    fn test_decode_frame_out_of_range() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        assert!(obj.decode_pixel_data_frame(1).is_err());
+
+        // same for encapsulated pixel data, as in the pure Rust path
+        let test_file = dicom_test_files::path("pydicom/SC_rgb_rle_2frame.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        assert!(matches!(
+            obj.decode_pixel_data_frame(2),
+            Err(Error(InnerError::FrameOutOfRange {
+                frame_number: 2,
+                ..
+            }))
+        ));
+    }
+
     #[cfg(feature = "ndarray")]
     #[test]
     fn test_to_ndarray_signed_word_no_lut() {
@@ -0,0 +1,293 @@
+//! Histograms of monochrome sample values.
+//!
+//! This module contains the [`Histogram`] data type,
+//! as produced by [`DecodedPixelData::histogram`](crate::DecodedPixelData::histogram),
+//! as well as the sample value counting
+//! behind the percentile-based VOI LUT option
+//! ([`VoiLutOption::Percentile`](crate::VoiLutOption::Percentile)).
+
+#[cfg(feature = "rayon")]
+use rayon::iter::ParallelIterator;
+#[cfg(feature = "rayon")]
+use rayon::slice::ParallelSlice;
+
+use crate::lut::stored_value;
+
+/// The number of samples counted by each task
+/// when counting stored values in parallel.
+#[cfg(feature = "rayon")]
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// A histogram of the (rescaled) sample values of a monochrome frame.
+///
+/// The bins have equal width,
+/// and span the range between the lowest and highest values found,
+/// both inclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    counts: Vec<u64>,
+    min: f64,
+    max: f64,
+}
+
+impl Histogram {
+    /// Build a histogram with the given number of bins
+    /// out of the number of occurrences of each stored value,
+    /// mapping each stored value through `transform`.
+    ///
+    /// `counts` is indexed by the stored value bits,
+    /// as produced by [`stored_value_counts`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bins` is 0.
+    pub(crate) fn from_stored_value_counts(
+        counts: &[u64],
+        signed: bool,
+        bins: usize,
+        transform: impl Fn(f64) -> f64,
+    ) -> Self {
+        assert!(bins > 0, "histogram must have at least one bin");
+        let size = counts.len();
+        let values: Vec<(f64, u64)> = counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(i, count)| (transform(stored_value(i, size, signed)), *count))
+            .filter(|(v, _)| v.is_finite())
+            .collect();
+
+        let (min, max) = values
+            .iter()
+            .fold((f64::MAX, f64::MIN), |(min, max), (v, _)| {
+                (min.min(*v), max.max(*v))
+            });
+        if values.is_empty() {
+            return Histogram {
+                counts: vec![0; bins],
+                min: 0.,
+                max: 0.,
+            };
+        }
+
+        let mut histogram = vec![0; bins];
+        for (v, count) in values {
+            let bin = if max > min {
+                (((v - min) / (max - min)) * bins as f64) as usize
+            } else {
+                0
+            };
+            histogram[bin.min(bins - 1)] += count;
+        }
+        Histogram {
+            counts: histogram,
+            min,
+            max,
+        }
+    }
+
+    /// Get the number of samples in each bin,
+    /// from the lowest values to the highest.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Get the number of bins.
+    pub fn bins(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Get the lowest value in the histogram.
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    /// Get the highest value in the histogram.
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Get the width of each bin.
+    pub fn bin_width(&self) -> f64 {
+        (self.max - self.min) / self.counts.len() as f64
+    }
+
+    /// Get the range of values covered by the bin at the given index,
+    /// or `None` if there is no such bin.
+    ///
+    /// The start of the range is inclusive.
+    /// The end of the range is exclusive,
+    /// except for the last bin.
+    pub fn bin_range(&self, index: usize) -> Option<(f64, f64)> {
+        if index >= self.counts.len() {
+            return None;
+        }
+        let width = self.bin_width();
+        let start = self.min + width * index as f64;
+        let end = if index + 1 == self.counts.len() {
+            self.max
+        } else {
+            start + width
+        };
+        Some((start, end))
+    }
+
+    /// Get the total number of samples in the histogram.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+/// Count the occurrences of each stored value in the given samples,
+/// leaving out the pixel padding values given.
+///
+/// The output is indexed by the lower `bits_stored` bits of each sample,
+/// so that it lines up with the entries of a [`Lut`](crate::Lut)
+/// of the same bit depth.
+/// The sample type `T` is expected to be either `u8` or `u16`,
+/// even if the sample is meant to be interpreted as signed.
+pub(crate) fn stored_value_counts<T>(
+    bits_stored: u16,
+    signed: bool,
+    samples: &[T],
+    padding: Option<crate::PixelPadding>,
+) -> Vec<u64>
+where
+    T: Copy + Into<usize> + Send + Sync,
+{
+    let size = 1_usize << bits_stored as u32;
+    let count = |samples: &[T]| {
+        let mut counts = vec![0_u64; size];
+        for v in samples {
+            counts[(*v).into() & (size - 1)] += 1;
+        }
+        counts
+    };
+
+    #[cfg(feature = "rayon")]
+    let mut counts = samples.par_chunks(CHUNK_SIZE).map(count).reduce(
+        || vec![0; size],
+        |mut a, b| {
+            a.iter_mut().zip(b).for_each(|(a, b)| *a += b);
+            a
+        },
+    );
+    #[cfg(not(feature = "rayon"))]
+    let mut counts = count(samples);
+
+    if let Some(padding) = padding {
+        for (i, count) in counts.iter_mut().enumerate() {
+            if padding.contains(stored_value(i, size, signed)) {
+                *count = 0;
+            }
+        }
+    }
+    counts
+}
+
+/// Find the stored value at the given percentile (from 0 to 100)
+/// of the samples counted in `counts`,
+/// using the nearest-rank method.
+///
+/// Returns `None` if no samples were counted.
+pub(crate) fn stored_value_percentile(
+    counts: &[u64],
+    signed: bool,
+    percentile: f32,
+) -> Option<f64> {
+    let size = counts.len();
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let percentile = f64::from(percentile.clamp(0., 100.));
+    let rank = ((percentile * total as f64 / 100.).ceil() as u64).clamp(1, total);
+
+    // visit the stored values in ascending order,
+    // which starts at the negative half if signed
+    let (negative, positive) = if signed {
+        (size / 2..size, 0..size / 2)
+    } else {
+        (size..size, 0..size)
+    };
+    let mut cumulative = 0;
+    negative.chain(positive).find_map(|i| {
+        cumulative += counts[i];
+        if cumulative >= rank {
+            Some(stored_value(i, size, signed))
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PixelPadding;
+
+    #[test]
+    fn count_stored_values() {
+        let samples: Vec<u16> = vec![0, 1, 1, 2, 0xFFFF, 0x0FFF, 7];
+        let counts = stored_value_counts(12, true, &samples, None);
+        assert_eq!(counts.len(), 4096);
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts[1], 2);
+        assert_eq!(counts[2], 1);
+        assert_eq!(counts[7], 1);
+        // -1 in 12 bits, regardless of the bits above
+        assert_eq!(counts[0x0FFF], 2);
+        assert_eq!(counts.iter().sum::<u64>(), samples.len() as u64);
+
+        // leave out the padding value -1
+        let counts = stored_value_counts(
+            12,
+            true,
+            &samples,
+            Some(PixelPadding {
+                value: -1,
+                range_limit: None,
+            }),
+        );
+        assert_eq!(counts[0x0FFF], 0);
+        assert_eq!(counts.iter().sum::<u64>(), 5);
+    }
+
+    #[test]
+    fn percentiles_of_signed_values() {
+        // samples -2, -1, 0, 1, 2, 3, 4, 5, 6, 7 in 8 bits
+        let samples: Vec<u8> = (-2_i8..8).map(|v| v as u8).collect();
+        let counts = stored_value_counts(8, true, &samples, None);
+
+        assert_eq!(stored_value_percentile(&counts, true, 0.), Some(-2.));
+        assert_eq!(stored_value_percentile(&counts, true, 10.), Some(-2.));
+        assert_eq!(stored_value_percentile(&counts, true, 15.), Some(-1.));
+        assert_eq!(stored_value_percentile(&counts, true, 50.), Some(2.));
+        assert_eq!(stored_value_percentile(&counts, true, 90.), Some(6.));
+        assert_eq!(stored_value_percentile(&counts, true, 100.), Some(7.));
+
+        // interpreted as unsigned, the negative values come last
+        assert_eq!(stored_value_percentile(&counts, false, 100.), Some(255.));
+
+        assert_eq!(stored_value_percentile(&[0; 256], true, 50.), None);
+    }
+
+    #[test]
+    fn histogram_bins() {
+        let samples: Vec<u8> = vec![0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let counts = stored_value_counts(8, false, &samples, None);
+        // rescale to 10..=30
+        let histogram = Histogram::from_stored_value_counts(&counts, false, 4, |v| v * 2. + 10.);
+
+        assert_eq!(histogram.bins(), 4);
+        assert_eq!(histogram.min(), 10.);
+        assert_eq!(histogram.max(), 30.);
+        assert_eq!(histogram.bin_width(), 5.);
+        assert_eq!(histogram.bin_range(0), Some((10., 15.)));
+        assert_eq!(histogram.bin_range(3), Some((25., 30.)));
+        assert_eq!(histogram.bin_range(4), None);
+        // 10 10 12 14 | 16 18 | 20 22 24 | 26 28 30
+        assert_eq!(histogram.counts(), &[4, 2, 3, 3]);
+        assert_eq!(histogram.total(), 12);
+    }
+}
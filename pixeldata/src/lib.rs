@@ -27,6 +27,11 @@
 //! This conversion includes
 //! eventual Modality and value of interest (VOI) transformations.
 //!
+//! Going the other way,
+//! a [`PixelDataBuilder`] (requires the `ndarray` feature)
+//! writes an array of samples into a DICOM object
+//! as native pixel data.
+//!
 //! # WebAssembly support
 //! This library works in WebAssembly with the following two measures:
 //!  - Ensure that the "gdcm" feature is disabled.
@@ -102,18 +107,15 @@
 //! including the default behavior for each method.
 //!
 
+use crate::transform::ModalityTransform;
 use byteorder::{ByteOrder, NativeEndian};
-#[cfg(not(feature = "gdcm"))]
+use dicom_core::value::InMemFragment;
 use dicom_core::{DataDictionary, DicomValue};
-use dicom_encoding::adapters::DecodeError;
-#[cfg(not(feature = "gdcm"))]
+use dicom_encoding::adapters::{DecodeError, DecodeFrameOptions, DecodedFrameSize};
 use dicom_encoding::transfer_syntax::TransferSyntaxIndex;
-#[cfg(not(feature = "gdcm"))]
 use dicom_encoding::Codec;
-#[cfg(not(feature = "gdcm"))]
 use dicom_object::{FileDicomObject, InMemDicomObject};
-#[cfg(not(feature = "gdcm"))]
-use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+use dicom_transfer_syntax_registry::{decoder_features, TransferSyntaxRegistry};
 #[cfg(feature = "image")]
 use image::{DynamicImage, ImageBuffer, Luma, Rgb};
 #[cfg(feature = "ndarray")]
@@ -123,14 +125,13 @@ use num_traits::NumCast;
 use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 #[cfg(all(feature = "rayon", feature = "image"))]
 use rayon::slice::ParallelSliceMut;
-#[cfg(not(feature = "gdcm"))]
-use snafu::ensure;
-#[cfg(any(not(feature = "gdcm"), feature = "image"))]
-use snafu::OptionExt;
-use snafu::{Backtrace, ResultExt, Snafu};
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
 use std::borrow::Cow;
-#[cfg(not(feature = "gdcm"))]
+use std::convert::TryFrom;
 use std::iter::zip;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 #[cfg(feature = "image")]
 pub use image;
@@ -138,21 +139,71 @@ pub use image;
 pub use ndarray;
 
 mod attribute;
+#[cfg(feature = "ndarray")]
+mod builder;
+mod dimension;
+#[cfg(feature = "image")]
+mod dither;
+mod frame;
+mod histogram;
 mod lut;
+mod order;
 mod transcode;
 
 pub mod encapsulation;
+pub mod overlay;
+pub mod series;
+pub mod suv;
 pub(crate) mod transform;
 
 // re-exports
-pub use attribute::{PhotometricInterpretation, PixelRepresentation, PlanarConfiguration};
+pub use attribute::{
+    AttributeName, PhotometricInterpretation, PixelRepresentation, PlanarConfiguration,
+    SampleFormat,
+};
+#[cfg(feature = "ndarray")]
+pub use builder::{BuildPixelDataError, PixelDataBuilder, PixelSample};
+pub use dimension::{Dimension, DimensionIndex, DimensionIndexError};
+pub use frame::{DecodedFrame, Frames};
+pub use histogram::Histogram;
 pub use lut::{CreateLutError, Lut};
-pub use transcode::{Error as TranscodeError, Result as TranscodeResult, Transcode};
-pub use transform::{Rescale, VoiLutFunction, WindowLevel, WindowLevelTransform};
+pub use order::{frame_order, FrameOrder, FrameOrderSource};
+pub use transcode::{
+    check_pixel_value_range, update_pixel_value_range, Error as TranscodeError, PixelValueRange,
+    Result as TranscodeResult, Transcode,
+};
+pub use transform::{
+    ModalityLut, PaletteColorLut, PixelPadding, Rescale, VoiLut, VoiLutFunction, WindowLevel,
+    WindowLevelTransform, WindowLevels,
+};
 
 #[cfg(feature = "gdcm")]
 mod gdcm;
 
+// the decoded pixel data and everything needed to convert it
+// can be shared across threads
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<DecodedPixelData<'static>>();
+    assert_send_sync::<DecodedFrame<'static>>();
+    assert_send_sync::<ImagingProperties>();
+    assert_send_sync::<DecodeOptions>();
+    assert_send_sync::<ConvertOptions>();
+    assert_send_sync::<Lut<u8>>();
+    assert_send_sync::<Lut<u16>>();
+    assert_send_sync::<Lut<f32>>();
+    assert_send_sync::<Rescale>();
+    assert_send_sync::<ModalityLut>();
+    assert_send_sync::<VoiLutFunction>();
+    assert_send_sync::<WindowLevel>();
+    assert_send_sync::<WindowLevels>();
+    assert_send_sync::<WindowLevelTransform>();
+    assert_send_sync::<VoiLut>();
+    assert_send_sync::<PaletteColorLut>();
+    assert_send_sync::<PixelPadding>();
+};
+
 /// Error type for most pixel data related operations.
 #[derive(Debug, Snafu)]
 pub struct Error(InnerError);
@@ -169,7 +220,12 @@ pub enum InnerError {
     #[snafu(display("PixelData attribute is not a primitive value or pixel sequence"))]
     InvalidPixelData { backtrace: Backtrace },
 
-    #[snafu(display("Invalid BitsAllocated, must be 8 or 16"))]
+    #[snafu(display("Object has no pixel data"))]
+    NoPixelData,
+
+    #[snafu(display(
+        "Invalid BitsAllocated, must be 8 or 16 (or 32 when converting to vectors or arrays)"
+    ))]
     InvalidBitsAllocated { backtrace: Backtrace },
 
     #[snafu(display("Unsupported PhotometricInterpretation `{}`", pi))]
@@ -181,6 +237,23 @@ pub enum InnerError {
     #[snafu(display("Unsupported SamplesPerPixel `{}`", spp))]
     UnsupportedSamplesPerPixel { spp: u16, backtrace: Backtrace },
 
+    #[snafu(display(
+        "PhotometricInterpretation `{}` is inconsistent with SamplesPerPixel `{}`",
+        pi,
+        spp
+    ))]
+    InconsistentPhotometricInterpretation {
+        pi: PhotometricInterpretation,
+        spp: u16,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("{}", mismatch))]
+    InconsistentValueMultiplicity {
+        mismatch: ValueMultiplicityMismatch,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Unsupported {} `{}`", name, value))]
     UnsupportedOther {
         name: &'static str,
@@ -194,8 +267,18 @@ pub enum InnerError {
         backtrace: Backtrace,
     },
 
-    #[snafu(display("Unsupported TransferSyntax `{}`", ts))]
-    UnsupportedTransferSyntax { ts: String, backtrace: Backtrace },
+    #[snafu(display("Unsupported TransferSyntax `{}`{}", ts, feature_suggestion(features)))]
+    UnsupportedTransferSyntax {
+        ts: String,
+        features: &'static [&'static str],
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Pixel data in transfer syntax `{}` was not decoded, no decoder is available",
+        ts
+    ))]
+    NotDecoded { ts: String, backtrace: Backtrace },
 
     #[snafu(display("Invalid buffer when constructing ImageBuffer"))]
     InvalidImageBuffer { backtrace: Backtrace },
@@ -227,45 +310,432 @@ pub enum InnerError {
         frame_number: u32,
         backtrace: Backtrace,
     },
+    #[snafu(display("Pixel data frame size or offset is too large to address"))]
+    FrameSizeOverflow { backtrace: Backtrace },
+    #[snafu(display(
+        "Pixel data has {} bytes after the last frame, more than the {} tolerated",
+        trailing_bytes,
+        max_trailing_bytes
+    ))]
+    ExcessTrailingBytes {
+        trailing_bytes: usize,
+        max_trailing_bytes: usize,
+        backtrace: Backtrace,
+    },
+    #[snafu(display("Number of per-frame window levels must match the number of frames. Expected `{:?}`, found `{:?}`", nr_frames, len))]
+    LengthMismatchWindowLevelFrames {
+        len: u32,
+        nr_frames: u32,
+        backtrace: Backtrace,
+    },
     #[snafu(display("Value multiplicity of VOI LUT Function must match the number of frames. Expected `{:?}`, found `{:?}`", nr_frames, vm))]
     LengthMismatchVoiLutFunction {
         vm: u32,
         nr_frames: u32,
         backtrace: Backtrace,
     },
+    #[snafu(display(
+        "Window level #{} was requested, but only {} window levels are available",
+        index,
+        len
+    ))]
+    WindowLevelOutOfRange {
+        index: usize,
+        len: usize,
+        backtrace: Backtrace,
+    },
     #[snafu(display("Value multiplicity of Rescale Slope/Intercept must match. Found `{:?}` (slope), `{:?}` (intercept)", slope_vm, intercept_vm))]
     LengthMismatchRescale {
         intercept_vm: u32,
         slope_vm: u32,
         backtrace: Backtrace,
     },
-    #[snafu(display("Value multiplicity of Window Center/Width must match. Found `{:?}` (center), `{:?}` (width)", wc_vm, ww_vm))]
-    LengthMismatchWindowLevel {
-        wc_vm: u32,
-        ww_vm: u32,
+
+    #[snafu(display("Cancelled after {} of {} frames", frames_done, frames_total))]
+    Cancelled {
+        frames_done: u32,
+        frames_total: u32,
         backtrace: Backtrace,
     },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+impl Error {
+    /// Whether the operation failed
+    /// because it was cancelled through a cancellation token
+    /// (see [`DecodeOptions::cancel_token`] and [`ConvertOptions::cancel_token`]).
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.0, InnerError::Cancelled { .. })
+    }
+
+    /// Whether the operation failed
+    /// because the pixel data was left in its encoded form
+    /// (see [`SampleState::EncodedOnly`]).
+    pub fn is_not_decoded(&self) -> bool {
+        matches!(self.0, InnerError::NotDecoded { .. })
+    }
+
+    /// Whether the operation failed
+    /// because the object has no pixel data
+    /// (see [`has_pixel_data`]).
+    pub fn is_no_pixel_data(&self) -> bool {
+        matches!(self.0, InnerError::NoPixelData)
+    }
+}
+
+/// Describe the Cargo features to enable for decoding support,
+/// as a suffix to an error message.
+fn feature_suggestion(features: &[&str]) -> String {
+    match features {
+        [] => String::new(),
+        [feature] => format!(" (enable Cargo feature `{}` for decoding support)", feature),
+        [features @ .., last] => {
+            let features: Vec<_> = features.iter().map(|f| format!("`{}`", f)).collect();
+            format!(
+                " (enable Cargo feature {} or `{}` for decoding support)",
+                features.join(", "),
+                last
+            )
+        }
+    }
+}
+
+/// Progress reporting and cancellation hooks
+/// consulted between frames.
+#[derive(Clone, Default)]
+struct FrameHooks {
+    /// called with the number of frames processed and the total number of frames
+    on_progress: Option<Arc<dyn Fn(u32, u32) + Send + Sync>>,
+    /// cancels the operation when set
+    cancel_token: Option<Arc<AtomicBool>>,
+}
+
+impl FrameHooks {
+    /// Whether any hook is set.
+    fn is_set(&self) -> bool {
+        self.on_progress.is_some() || self.cancel_token.is_some()
+    }
+
+    /// Fail if cancellation was requested
+    /// before processing the next frame.
+    fn check_cancelled(&self, frames_done: u32, frames_total: u32) -> Result<()> {
+        match &self.cancel_token {
+            Some(token) if token.load(Ordering::Acquire) => CancelledSnafu {
+                frames_done,
+                frames_total,
+            }
+            .fail()?,
+            _ => Ok(()),
+        }
+    }
+
+    /// Report that a frame was processed.
+    fn report_progress(&self, frames_done: u32, frames_total: u32) {
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(frames_done, frames_total);
+        }
+    }
+}
+
+impl std::fmt::Debug for FrameHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameHooks")
+            .field("on_progress", &self.on_progress.as_ref().map(|_| ".."))
+            .field("cancel_token", &self.cancel_token)
+            .finish()
+    }
+}
+
+/// Hooks are equal if they refer to the same callback and token.
+impl PartialEq for FrameHooks {
+    fn eq(&self, other: &Self) -> bool {
+        fn same<T: ?Sized>(a: &Option<Arc<T>>, b: &Option<Arc<T>>) -> bool {
+            match (a, b) {
+                (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+        }
+        same(&self.on_progress, &other.on_progress) && same(&self.cancel_token, &other.cancel_token)
+    }
+}
+
+/// Option set for decoding pixel data from a DICOM object.
+///
+/// See [`PixelDecoder::decode_pixel_data_with_options`]
+/// for the decoding methods which take these options.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct DecodeOptions {
+    /// Whether to fail with an error
+    /// when _Photometric Interpretation_ disagrees with _Samples per Pixel_,
+    /// or when the number of rescale, window, or VOI LUT function values
+    /// disagrees with the number of frames,
+    /// instead of resolving the disagreement
+    /// as described in [`PhotometricInterpretationMismatch`]
+    /// and [`ValueMultiplicityMismatch`].
+    ///
+    /// This also fails when a required imaging attribute is missing
+    /// instead of taking a default value
+    /// (see [`ImagingProperties::from_object`]).
+    /// The per-frame values of the decoded pixel data
+    /// are then also checked when retrieved,
+    /// such as through [`DecodedPixelData::rescale`].
+    pub strict: bool,
+    /// The number of resolution levels to discard when decoding,
+    /// where each level halves the number of rows and columns.
+    /// The default of 0 decodes the pixel data at full resolution.
+    ///
+    /// This is only a hint, currently honored for JPEG 2000.
+    /// Other transfer syntaxes decode the pixel data at full resolution.
+    /// See [`DecodedPixelData::declared_dimensions`]
+    /// for telling whether the resolution was reduced.
+    pub resolution_level: u32,
+    /// The maximum number of bytes tolerated
+    /// after the last frame of native pixel data,
+    /// which are left out of the decoded pixel data.
+    ///
+    /// If `None`, the default,
+    /// 1 byte of padding to an even length is tolerated,
+    /// as well as anything less than one full frame.
+    /// Decoding through GDCM always uses this default.
+    /// See [`DecodedPixelData::trailing_bytes`].
+    pub max_trailing_bytes: Option<usize>,
+    /// Whether to keep encapsulated pixel data in its encoded form
+    /// when no decoder is available for its transfer syntax,
+    /// instead of failing with an error.
+    ///
+    /// The outcome then holds the imaging attributes
+    /// and the encoded pixel data fragments,
+    /// as described in [`SampleState::EncodedOnly`].
+    /// Decoding through GDCM does not use this fallback.
+    pub encoded_fallback: bool,
+    /// progress reporting and cancellation
+    hooks: FrameHooks,
+}
+
+impl DecodeOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set whether inconsistent imaging attributes
+    /// should result in an error.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Set the number of resolution levels to discard when decoding,
+    /// if supported by the transfer syntax.
+    pub fn resolution_level(mut self, resolution_level: u32) -> Self {
+        self.resolution_level = resolution_level;
+        self
+    }
+
+    /// Set the maximum number of bytes tolerated
+    /// after the last frame of native pixel data.
+    pub fn max_trailing_bytes(mut self, max_trailing_bytes: usize) -> Self {
+        self.max_trailing_bytes = Some(max_trailing_bytes);
+        self
+    }
+
+    /// Set whether to keep encapsulated pixel data in its encoded form
+    /// when no decoder is available for its transfer syntax.
+    pub fn encoded_fallback(mut self, encoded_fallback: bool) -> Self {
+        self.encoded_fallback = encoded_fallback;
+        self
+    }
+
+    /// Set a function to be called after each frame is decoded,
+    /// with the number of frames decoded so far
+    /// and the total number of frames.
+    ///
+    /// Only encapsulated pixel data is decoded frame by frame.
+    /// Pixel data in native form is readily available,
+    /// so no progress is reported for it.
+    pub fn on_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: Fn(u32, u32) + Send + Sync + 'static,
+    {
+        self.hooks.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// Set a flag for cancelling the decoding process.
+    ///
+    /// The flag is checked before decoding each frame.
+    /// Once it is set to `true`,
+    /// decoding stops with an error
+    /// for which [`Error::is_cancelled`] returns `true`,
+    /// and no decoded pixel data is returned.
+    pub fn cancel_token(mut self, cancel_token: Arc<AtomicBool>) -> Self {
+        self.hooks.cancel_token = Some(cancel_token);
+        self
+    }
+}
+
+/// A disagreement between the _Photometric Interpretation_
+/// and the _Samples per Pixel_ declared by a DICOM object,
+/// such as `MONOCHROME2` with 3 samples per pixel.
+///
+/// _Samples per Pixel_ is trusted,
+/// since it determines the layout of the pixel data.
+/// The effective photometric interpretation is then
+/// `MONOCHROME2` for 1 sample per pixel
+/// and `RGB` for 3 samples per pixel.
+/// Any other number of samples per pixel
+/// keeps the declared photometric interpretation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhotometricInterpretationMismatch {
+    /// the photometric interpretation declared by the object
+    pub declared: PhotometricInterpretation,
+    /// the number of samples per pixel declared by the object
+    pub samples_per_pixel: u16,
+    /// the photometric interpretation in effect
+    pub resolved: PhotometricInterpretation,
+}
+
+impl std::fmt::Display for PhotometricInterpretationMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "PhotometricInterpretation `{}` is inconsistent with SamplesPerPixel `{}`, using `{}`",
+            self.declared, self.samples_per_pixel, self.resolved,
+        )
+    }
+}
+
+impl PhotometricInterpretationMismatch {
+    /// Check the given photometric interpretation
+    /// against the number of samples per pixel,
+    /// resolving any disagreement between the two.
+    ///
+    /// Returns `None` if they are consistent
+    /// or if the photometric interpretation is not known.
+    pub fn check(pi: &PhotometricInterpretation, samples_per_pixel: u16) -> Option<Self> {
+        if pi.samples_per_pixel()? == samples_per_pixel {
+            return None;
+        }
+        let resolved = match samples_per_pixel {
+            1 => PhotometricInterpretation::Monochrome2,
+            3 => PhotometricInterpretation::Rgb,
+            _ => pi.clone(),
+        };
+        Some(PhotometricInterpretationMismatch {
+            declared: pi.clone(),
+            samples_per_pixel,
+            resolved,
+        })
+    }
+}
+
+/// A list of imaging attribute values
+/// whose length disagrees with the number of frames
+/// or with the length of its counterpart,
+/// such as 3 _Rescale Slope_ values in an object with 5 frames,
+/// or 2 _Window Center_ values with 3 _Window Width_ values.
+///
+/// A single value is never a mismatch:
+/// it applies to all frames
+/// (or to all values of its counterpart).
+/// Unless decoding in [strict](DecodeOptions::strict) mode,
+/// the values are fitted to the expected length
+/// by dropping the excess values
+/// or by repeating the last value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueMultiplicityMismatch {
+    /// the attribute with an unexpected number of values
+    pub attribute: AttributeName,
+    /// the number of values expected
+    pub expected: u32,
+    /// the number of values found
+    pub found: u32,
+}
+
+impl std::fmt::Display for ValueMultiplicityMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expected {} values of {}, found {}",
+            self.expected, self.attribute, self.found,
+        )
+    }
+}
+
+impl ValueMultiplicityMismatch {
+    /// Fit the given values to the expected length,
+    /// by dropping the excess values or by repeating the last value.
+    ///
+    /// Empty lists, single values,
+    /// and lists which already have the expected length
+    /// are left as is and yield `None`.
+    fn fit<T: Clone>(
+        values: &mut Vec<T>,
+        expected: usize,
+        attribute: AttributeName,
+    ) -> Option<Self> {
+        let found = values.len();
+        if found <= 1 || expected == 0 || found == expected {
+            return None;
+        }
+        let last = values[found - 1].clone();
+        values.resize(expected, last);
+        let mismatch = ValueMultiplicityMismatch {
+            attribute,
+            expected: expected as u32,
+            found: found as u32,
+        };
+        tracing::warn!("{}, fitting to {} values", mismatch, expected);
+        Some(mismatch)
+    }
+}
+
+/// Repeat a single value to the given length,
+/// leaving any other number of values untouched.
+fn broadcast<T: Clone>(values: &mut Vec<T>, len: usize) {
+    if values.len() == 1 && len > 1 {
+        let value = values[0].clone();
+        values.resize(len, value);
+    }
+}
+
 /// Option set for converting decoded pixel data
 /// into other common data structures,
 /// such as a vector, an image, or a multidimensional array.
 ///
 /// Each option listed affects the transformation in this order:
 /// 1. The Modality LUT function (`modality_lut`)
-///    is applied to the raw pixel data sample values.
+///    is applied to the raw pixel data sample values,
+///    once the bits of each sample outside of the bits stored
+///    are cleared (`unused_bits`).
 ///    This is usually an affine function based on the
-///    _Rescale Slope_ and _Rescale Intercept_ attributes.
+///    _Rescale Slope_ and _Rescale Intercept_ attributes,
+///    or a table in the _Modality LUT Sequence_,
+///    which takes precedence if both are present.
 ///    If this option is set to [`None`](ModalityLutOption::None),
 ///    the VOI LUT function is ignored.
 /// 2. The VOI LUT function (`voi_lut`)
 ///    is applied to the rescaled values,
 ///    such as a window level.
+///    Stored values denoted as padding by the _Pixel Padding Value_
+///    can be left out of a min-max normalization
+///    and mapped to the lowest output value (`padding`).
 /// 3. In the case of converting to an image,
 ///    the transformed values are extended or narrowed
 ///    to the range of the target bit depth (`bit_depth`).
+///    When narrowing monochrome images to 8 bits,
+///    the values can be dithered (`dither`).
+///
+/// Pixel data with the _PALETTE COLOR_ photometric interpretation
+/// is instead mapped through the palette color lookup tables
+/// into RGB samples (`palette`),
+/// in which case the Modality LUT and VOI LUT functions do not apply.
+/// Monochrome pixel data with a supplemental palette
+/// is mapped through the palette only for the stored values
+/// from the first value mapped by the palette onwards,
+/// the other values going through the steps above.
 #[derive(Debug, Default, Clone, PartialEq)]
 #[non_exhaustive]
 pub struct ConvertOptions {
@@ -275,6 +745,19 @@ pub struct ConvertOptions {
     pub voi_lut: VoiLutOption,
     /// Output image bit depth
     pub bit_depth: BitDepthOption,
+    /// Dithering when narrowing to 8 bits
+    pub dither: DitherOption,
+    /// Palette color lookup table option
+    pub palette: PaletteOption,
+    /// Pixel padding option
+    pub padding: PaddingOption,
+    /// Option for the bits of each sample outside of the bits stored
+    pub unused_bits: UnusedBitsOption,
+    /// Whether to convert frames in their [order](DecodedPixelData::frame_order)
+    /// instead of the order in which they are stored
+    pub sort_frames: bool,
+    /// progress reporting and cancellation
+    hooks: FrameHooks,
 }
 
 impl ConvertOptions {
@@ -315,6 +798,69 @@ impl ConvertOptions {
         self.bit_depth = BitDepthOption::Force16Bit;
         self
     }
+
+    /// Set the dithering option
+    /// for narrowing monochrome images to 8 bits.
+    pub fn with_dithering(mut self, dither: DitherOption) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Set the palette color lookup table option.
+    pub fn with_palette(mut self, palette: PaletteOption) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Set the pixel padding option.
+    pub fn with_padding(mut self, padding: PaddingOption) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set the option for the bits of each sample
+    /// outside of the bits stored.
+    pub fn with_unused_bits(mut self, unused_bits: UnusedBitsOption) -> Self {
+        self.unused_bits = unused_bits;
+        self
+    }
+
+    /// Set whether to convert frames
+    /// in the order determined by [`frame_order`](crate::frame_order)
+    /// instead of the order in which they are stored.
+    ///
+    /// This only applies to methods converting all frames,
+    /// such as [`to_vec_with_options`](DecodedPixelData::to_vec_with_options)
+    /// and `to_ndarray_with_options`.
+    pub fn with_sorted_frames(mut self, sort_frames: bool) -> Self {
+        self.sort_frames = sort_frames;
+        self
+    }
+
+    /// Set a function to be called after each frame is converted,
+    /// with the number of frames converted so far
+    /// and the total number of frames.
+    ///
+    /// This only applies to methods converting all frames,
+    /// such as [`to_vec_with_options`](DecodedPixelData::to_vec_with_options).
+    pub fn on_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: Fn(u32, u32) + Send + Sync + 'static,
+    {
+        self.hooks.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// Set a flag for cancelling the conversion.
+    ///
+    /// The flag is checked before converting each frame.
+    /// Once it is set to `true`,
+    /// the conversion stops with an error
+    /// for which [`Error::is_cancelled`] returns `true`.
+    pub fn cancel_token(mut self, cancel_token: Arc<AtomicBool>) -> Self {
+        self.hooks.cancel_token = Some(cancel_token);
+        self
+    }
 }
 
 /// Modality LUT function specifier.
@@ -325,7 +871,9 @@ impl ConvertOptions {
 pub enum ModalityLutOption {
     /// _Default behavior:_
     /// rescale the pixel data values
-    /// as described in the decoded pixel data.
+    /// as described in the decoded pixel data,
+    /// or map them through the tabular Modality LUT if one is defined
+    /// (see [`modality_lut`](DecodedPixelData::modality_lut)).
     #[default]
     Default,
     /// Rescale the pixel data values
@@ -333,6 +881,13 @@ pub enum ModalityLutOption {
     Override(Rescale),
     /// Do not rescale nor transform the pixel data value samples.
     ///
+    /// The stored values are provided as they are.
+    /// In particular, signed stored values keep their sign
+    /// without any offset applied,
+    /// so they should be retrieved in a signed type such as `i16`.
+    /// Images are the exception,
+    /// since signed values are shifted into the unsigned range of the image.
+    ///
     /// This also overrides any option to apply VOI LUT transformations
     /// in the decoded pixel data conversion methods.
     /// To assume the identity function for rescaling
@@ -359,7 +914,27 @@ pub enum VoiLutOption {
     Default,
     /// Apply the first VOI LUT function transformation
     /// described in the pixel data.
+    ///
+    /// The first window level is used if available,
+    /// otherwise the first tabular VOI LUT
+    /// in the _VOI LUT Sequence_ is used.
     First,
+    /// Apply the first tabular VOI LUT
+    /// in the _VOI LUT Sequence_ of the pixel data,
+    /// even if window levels are also described.
+    ///
+    /// Behaves like [`First`](Self::First)
+    /// if the pixel data has no tabular VOI LUT.
+    Table,
+    /// Apply the window level at the given index
+    /// among the alternative window levels described in the pixel data,
+    /// along with the VOI LUT function at the same index
+    /// if the _VOI LUT Function_ is multi-valued.
+    ///
+    /// Converting fails if there is no window level at the given index.
+    /// See [`DecodedPixelData::windows`]
+    /// for the window levels available.
+    Index(usize),
     /// Apply a custom window level instead of the one described in the object.
     Custom(WindowLevel),
     /// Apply a custom window level and a custom function instead of the one described in the object.
@@ -368,6 +943,25 @@ pub enum VoiLutOption {
     /// so that the lowest value is 0 and
     /// the highest value is the maximum value of the target type.
     Normalize,
+    /// Apply a window level spanning the given percentiles
+    /// of the rescaled sample values of the frame,
+    /// so that values at or below the `low` percentile become 0
+    /// and values at or above the `high` percentile
+    /// become the maximum value of the target type.
+    ///
+    /// Percentiles are given from 0 to 100.
+    /// For example, `Percentile { low: 1., high: 99. }`
+    /// leaves out the outermost 1% of values on either side,
+    /// which is more robust to outliers
+    /// than a [min-max normalization](Self::Normalize).
+    /// Pixel padding values are handled
+    /// as in [`Normalize`](Self::Normalize).
+    Percentile {
+        /// The percentile mapped to the lowest output value
+        low: f32,
+        /// The percentile mapped to the highest output value
+        high: f32,
+    },
     /// Do not apply any VOI LUT transformation.
     Identity,
 }
@@ -394,6 +988,198 @@ pub enum BitDepthOption {
     Force16Bit,
 }
 
+/// Dithering method specifier
+/// for narrowing 16-bit samples to 8 bits.
+///
+/// This only applies when converting monochrome pixel data
+/// to an image with 8 bits per sample
+/// (see [`BitDepthOption::Force8Bit`]).
+/// Color images are always narrowed without dithering.
+///
+/// See also [`ConvertOptions`].
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DitherOption {
+    /// _Default behavior:_
+    /// round each sample to the nearest 8-bit value.
+    #[default]
+    None,
+    /// Ordered dithering with a 4x4 Bayer matrix.
+    ///
+    /// Each sample is dithered independently,
+    /// so this is done in parallel with the `rayon` feature.
+    Ordered,
+    /// Floyd–Steinberg error diffusion.
+    ///
+    /// This usually gives smoother gradients than ordered dithering,
+    /// but the quantization error of each sample
+    /// is carried over to the following samples,
+    /// so each frame is narrowed on a single thread
+    /// even with the `rayon` feature.
+    FloydSteinberg,
+}
+
+/// Option for converting pixel data
+/// with the _PALETTE COLOR_ photometric interpretation,
+/// or monochrome pixel data with a supplemental palette
+/// (_Supplemental Palette Color Lookup Table_).
+///
+/// This has no effect on pixel data
+/// with any other photometric interpretation.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PaletteOption {
+    /// _Default behavior:_
+    /// map each stored value through the palette color lookup tables,
+    /// so that the output has 3 samples per pixel (red, green, and blue).
+    ///
+    /// When converting to an image,
+    /// palettes with 8-bit entries yield 8 bits per sample
+    /// unless another bit depth is requested.
+    ///
+    /// With a supplemental palette,
+    /// only the stored values from the first value mapped by the palette
+    /// are mapped through it when converting to an image,
+    /// and the values below it are rendered in grayscale
+    /// through the usual Modality LUT and VOI LUT functions.
+    /// Other conversions produce the grayscale values only.
+    #[default]
+    Apply,
+    /// Map every stored value through the palette color lookup tables,
+    /// including the values below the first value mapped
+    /// by a supplemental palette,
+    /// which take the first entry of each table.
+    ///
+    /// This is the same as [`Apply`](Self::Apply)
+    /// for the _PALETTE COLOR_ photometric interpretation.
+    ApplyAll,
+    /// Keep the stored values as palette indices,
+    /// with 1 sample per pixel,
+    /// which are converted as if they were monochrome.
+    ///
+    /// With a supplemental palette,
+    /// all stored values are rendered in grayscale.
+    Indices,
+}
+
+/// Option for handling the stored values
+/// which denote padding rather than image content,
+/// as described by the _Pixel Padding Value_
+/// and the _Pixel Padding Range Limit_.
+///
+/// This has no effect on pixel data without a _Pixel Padding Value_
+/// (see [`DecodedPixelData::pixel_padding`]).
+///
+/// See also [`ConvertOptions`].
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum PaddingOption {
+    /// _Default behavior:_
+    /// treat padding values as any other stored value.
+    #[default]
+    Keep,
+    /// Leave padding values out of the min-max normalization
+    /// (see [`VoiLutOption::Normalize`]),
+    /// so that the output range spans the actual image content.
+    Exclude,
+    /// Leave padding values out of the min-max normalization
+    /// and map them to the lowest output value
+    /// whenever a VOI LUT transformation is applied.
+    Mask,
+}
+
+/// Option for handling the bits of each sample
+/// which are not part of the stored value,
+/// when _Bits Stored_ is less than _Bits Allocated_.
+///
+/// Some sources leave other content in these bits,
+/// such as overlay planes,
+/// which would otherwise distort the sample values.
+///
+/// See also [`ConvertOptions`].
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum UnusedBitsOption {
+    /// _Default behavior:_
+    /// clear the bits above the _High Bit_,
+    /// so that only the stored value is kept,
+    /// extending the sign of signed samples.
+    #[default]
+    Mask,
+    /// Keep the bits above the _High Bit_.
+    ///
+    /// The stored values are still moved to the least significant bits
+    /// if the _High Bit_ is not `bits_stored - 1`.
+    Keep,
+}
+
+/// The state of the pixel data samples in [`DecodedPixelData`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SampleState<'a> {
+    /// The samples are in native form,
+    /// ready to be converted through the `to_*` methods.
+    Decoded,
+    /// The pixel data is encapsulated in a transfer syntax
+    /// for which no decoder is available,
+    /// so the samples were left in their encoded form
+    /// (see [`DecodeOptions::encoded_fallback`]).
+    ///
+    /// The imaging attributes are still available,
+    /// but the raw data accessors yield no samples,
+    /// and the `to_*` conversion methods fail
+    /// (see [`Error::is_not_decoded`]).
+    /// The encoded fragments can be handed to an external decoder instead.
+    EncodedOnly {
+        /// the UID of the transfer syntax of the pixel data
+        ts_uid: String,
+        /// the pixel data fragments,
+        /// only those of the frame of interest
+        /// if a single frame was requested
+        fragments: Cow<'a, [InMemFragment]>,
+        /// the basic offset table, empty if there is none
+        /// or if a single frame was requested
+        offset_table: Cow<'a, [u32]>,
+    },
+}
+
+impl SampleState<'_> {
+    /// Obtain a version of the sample state
+    /// that is independent from the original DICOM object.
+    fn to_owned_state(&self) -> SampleState<'static> {
+        match self {
+            SampleState::Decoded => SampleState::Decoded,
+            SampleState::EncodedOnly {
+                ts_uid,
+                fragments,
+                offset_table,
+            } => SampleState::EncodedOnly {
+                ts_uid: ts_uid.clone(),
+                fragments: Cow::Owned(fragments.to_vec()),
+                offset_table: Cow::Owned(offset_table.to_vec()),
+            },
+        }
+    }
+
+    /// Obtain a version of the sample state
+    /// that is independent from the original DICOM object,
+    /// moving the fragments if they are already owned.
+    fn into_owned_state(self) -> SampleState<'static> {
+        match self {
+            SampleState::Decoded => SampleState::Decoded,
+            SampleState::EncodedOnly {
+                ts_uid,
+                fragments,
+                offset_table,
+            } => SampleState::EncodedOnly {
+                ts_uid,
+                fragments: Cow::Owned(fragments.into_owned()),
+                offset_table: Cow::Owned(offset_table.into_owned()),
+            },
+        }
+    }
+}
+
 /// A blob of decoded pixel data.
 ///
 /// This is the outcome of collecting a DICOM object's imaging-related attributes
@@ -405,10 +1191,24 @@ pub enum BitDepthOption {
 /// can be specified through one of the various `to_*` methods,
 /// such as [`to_dynamic_image`](Self::to_dynamic_image)
 /// and [`to_vec`](Self::to_vec).
+///
+/// # Thread safety
+///
+/// Decoded pixel data is `Send` and `Sync`,
+/// and holds no interior mutability,
+/// so it can be shared across threads
+/// and converted concurrently through a shared reference.
+/// A version which does not borrow from the DICOM object,
+/// such as one to be kept in a cache,
+/// can be obtained with [`to_owned`](Self::to_owned),
+/// or with [`into_owned`](Self::into_owned) to avoid copying the samples.
 #[derive(Debug, Clone)]
 pub struct DecodedPixelData<'a> {
     /// the raw bytes of pixel data
     data: Cow<'a, [u8]>,
+    /// whether the samples were decoded,
+    /// holding the encoded fragments if not
+    samples: SampleState<'a>,
     /// the number of rows
     rows: u32,
     /// the number of columns
@@ -417,11 +1217,15 @@ pub struct DecodedPixelData<'a> {
     number_of_frames: u32,
     /// the photometric interpretation
     photometric_interpretation: PhotometricInterpretation,
+    /// the photometric interpretation declared by the object,
+    /// if the samples were decoded into a different one
+    original_photometric_interpretation: Option<PhotometricInterpretation>,
     /// the number of samples per pixel
     samples_per_pixel: u16,
     /// the planar configuration: 0 for standard, 1 for channel-contiguous
     planar_configuration: PlanarConfiguration,
-    /// the number of bits allocated, as a multiple of 8
+    /// the number of bits allocated, as a multiple of 8,
+    /// or 1 if the samples were unpacked to one byte each
     bits_allocated: u16,
     /// the number of bits stored
     bits_stored: u16,
@@ -429,6 +1233,9 @@ pub struct DecodedPixelData<'a> {
     high_bit: u16,
     /// the pixel representation: 0 for unsigned, 1 for signed
     pixel_representation: PixelRepresentation,
+    /// the numeric format of the samples,
+    /// which may be floating point
+    sample_format: SampleFormat,
     /// Multiframe dicom objects can have rescale information, voi LUT and
     /// window level information once in the shared functional group sequence,
     /// or multiple times in the per-frame functional group sequence. This is a
@@ -436,13 +1243,45 @@ pub struct DecodedPixelData<'a> {
     ///
     /// the pixel value rescale slope and intercept
     rescale: Vec<Rescale>,
+    /// the tabular Modality LUT defined in the Modality LUT Sequence,
+    /// which takes precedence over the rescale parameters
+    modality_lut: Option<ModalityLut>,
     // the VOI LUT function
     voi_lut_function: Option<Vec<VoiLutFunction>>,
-    /// the window level specified via width and center
-    window: Option<Vec<WindowLevel>>,
+    /// the window levels specified via width and center,
+    /// possibly with multiple alternative windows per frame
+    window: Option<WindowLevels>,
+    /// the VOI LUT function of each alternative window level,
+    /// if the VOI LUT Function is multi-valued
+    window_voi_lut_functions: Vec<VoiLutFunction>,
+    /// the tabular VOI LUTs defined in the VOI LUT sequence,
+    /// which are alternatives to the window levels
+    voi_luts: Vec<VoiLut>,
+    /// the palette color lookup tables,
+    /// if the photometric interpretation is _PALETTE COLOR_,
+    /// or the supplemental palette of monochrome pixel data
+    palette: Option<PaletteColorLut>,
+    /// the stored values which denote padding
+    pixel_padding: Option<PixelPadding>,
+    /// the order of the frames,
+    /// which may be applied when converting all of them
+    frame_order: FrameOrder,
 
     /// Enforce frame functional groups VMs match `number_of_frames`
     enforce_frame_fg_vm_match: bool,
+    /// the disagreement between the declared photometric interpretation
+    /// and the number of samples per pixel, if any
+    photometric_interpretation_mismatch: Option<PhotometricInterpretationMismatch>,
+    /// the imaging attributes with an unexpected number of values
+    value_multiplicity_mismatches: Vec<ValueMultiplicityMismatch>,
+    /// the required imaging attributes which were missing
+    /// and took a default value
+    defaulted_attributes: Vec<AttributeName>,
+    /// the rows and columns declared by the object,
+    /// if the pixel data was decoded with different dimensions
+    declared_dimensions: Option<(u32, u32)>,
+    /// the number of bytes found after the last frame of native pixel data
+    trailing_bytes: usize,
 }
 
 impl DecodedPixelData<'_> {
@@ -450,11 +1289,22 @@ impl DecodedPixelData<'_> {
 
     /// Retrieve a slice of all raw pixel data samples as bytes,
     /// irrespective of the expected size of each sample.
+    ///
+    /// The slice is empty if the samples were not decoded
+    /// (see [`samples`](Self::samples)).
     #[inline]
     pub fn data(&self) -> &[u8] {
         &self.data
     }
 
+    /// Retrieve the state of the pixel data samples,
+    /// which holds the encoded pixel data fragments
+    /// if no decoder was available for them.
+    #[inline]
+    pub fn samples(&self) -> &SampleState<'_> {
+        &self.samples
+    }
+
     /// Retrieve a copy of all raw pixel data samples
     /// as unsigned 16-bit integers.
     ///
@@ -467,22 +1317,40 @@ impl DecodedPixelData<'_> {
 
     /// Retrieve a slice of a frame's raw pixel data samples as bytes,
     /// irrespective of the expected size of each sample.
+    ///
+    /// Native pixel data in `YBR_FULL_422` or `YBR_PARTIAL_422`
+    /// is retrieved as stored,
+    /// in groups of `Y Y CB CR` samples for every two pixels in a row.
     pub fn frame_data(&self, frame: u32) -> Result<&[u8]> {
-        let bytes_per_sample = self.bits_allocated as usize / 8;
-        let frame_length = self.rows as usize
-            * self.cols as usize
-            * self.samples_per_pixel as usize
-            * bytes_per_sample;
-        let frame_start = frame_length * frame as usize;
-        let frame_end = frame_start + frame_length;
-        if frame_end > (*self.data).len() {
+        let frame_range = self.frame_range(frame)?;
+        Ok(&self.data[frame_range])
+    }
+
+    /// Obtain the range of bytes of the given frame in the pixel data,
+    /// failing if the frame is out of range
+    /// or if the samples were not decoded.
+    fn frame_range(&self, frame: u32) -> Result<Range<usize>> {
+        if let SampleState::EncodedOnly { ts_uid, .. } = &self.samples {
+            return NotDecodedSnafu { ts: ts_uid }.fail()?;
+        }
+        let frame_size = native_stored_frame_size(
+            &self.photometric_interpretation,
+            self.bits_allocated,
+            self.samples_per_pixel,
+            self.rows,
+            self.cols,
+            self.number_of_frames,
+            self.data.len(),
+        )
+        .context(FrameSizeOverflowSnafu)?;
+        let frame_range = native_frame_range(frame_size, frame).context(FrameSizeOverflowSnafu)?;
+        ensure!(
+            frame_range.end <= self.data.len(),
             FrameOutOfRangeSnafu {
                 frame_number: frame,
             }
-            .fail()?
-        }
-
-        Ok(&self.data[frame_start..frame_end])
+        );
+        Ok(frame_range)
     }
 
     /// Retrieve a copy of a frame's raw pixel data samples
@@ -496,27 +1364,216 @@ impl DecodedPixelData<'_> {
         Ok(bytes_to_vec_u16(data))
     }
 
-    /// Retrieves the number of rows of the pixel data.
-    #[inline]
-    pub fn rows(&self) -> u32 {
-        self.rows
-    }
-
-    /// Retrieves the number of columns of the pixel data.
-    #[inline]
-    pub fn columns(&self) -> u32 {
-        self.cols
+    /// Compute the range of stored sample values in a frame,
+    /// as the lowest and highest values found.
+    ///
+    /// The samples are interpreted
+    /// according to the _Bits Stored_, _High Bit_
+    /// and _Pixel Representation_ of the pixel data,
+    /// without any Modality LUT transformation,
+    /// so that the range can be compared with the
+    /// _Smallest Image Pixel Value_ and _Largest Image Pixel Value_ attributes.
+    /// The samples of all channels are taken into account.
+    ///
+    /// Floating point pixel data is not supported.
+    pub fn value_range(&self, frame: u32) -> Result<(i64, i64)> {
+        ensure!(
+            !self.sample_format.is_float(),
+            UnsupportedOtherSnafu {
+                name: "SampleFormat",
+                value: "floating point",
+            }
+        );
+        let data = self.realign_samples(self.frame_data(frame)?, UnusedBitsOption::Mask)?;
+        let signed = self.pixel_representation == PixelRepresentation::Signed;
+
+        // realigned samples are sign extended to the bits allocated
+        let samples: Box<dyn Iterator<Item = i64>> = match (self.bits_allocated, signed) {
+            // samples of 1 bit were unpacked to one byte each
+            (1 | 8, false) => Box::new(data.iter().map(|v| i64::from(*v))),
+            (1 | 8, true) => Box::new(data.iter().map(|v| i64::from(*v as i8))),
+            (16, false) => Box::new(
+                data.chunks_exact(2)
+                    .map(|b| i64::from(NativeEndian::read_u16(b))),
+            ),
+            (16, true) => Box::new(
+                data.chunks_exact(2)
+                    .map(|b| i64::from(NativeEndian::read_i16(b))),
+            ),
+            (32, false) => Box::new(
+                data.chunks_exact(4)
+                    .map(|b| i64::from(NativeEndian::read_u32(b))),
+            ),
+            (32, true) => Box::new(
+                data.chunks_exact(4)
+                    .map(|b| i64::from(NativeEndian::read_i32(b))),
+            ),
+            _ => InvalidBitsAllocatedSnafu.fail()?,
+        };
+        let (min, max) = samples.fold((i64::MAX, i64::MIN), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+        ensure!(min <= max, NoPixelDataSnafu);
+        Ok((min, max))
     }
 
-    /// Retrieves the photometric interpretation.
-    #[inline]
-    pub fn photometric_interpretation(&self) -> &PhotometricInterpretation {
-        &self.photometric_interpretation
+    /// Compute the range of stored sample values across all frames,
+    /// as the lowest and highest values found.
+    ///
+    /// See [`value_range`](Self::value_range) for more details.
+    pub fn value_range_all(&self) -> Result<(i64, i64)> {
+        let (mut min, mut max) = self.value_range(0)?;
+        for frame in 1..self.number_of_frames {
+            let (frame_min, frame_max) = self.value_range(frame)?;
+            min = min.min(frame_min);
+            max = max.max(frame_max);
+        }
+        Ok((min, max))
     }
 
-    /// Retrieves the planar configuration of the pixel data.
+    /// Obtain an iterator over the frames of the decoded pixel data.
     ///
-    /// The value returned is only meaningful for
+    /// Each frame is a lightweight view into this pixel data,
+    /// which only copies the samples
+    /// once one of its conversion methods is called.
+    /// The iterator is empty if there are no frames,
+    /// or if the samples were not decoded.
+    /// Frames declared beyond the end of truncated pixel data
+    /// are not yielded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dicom_pixeldata::{ConvertOptions, DecodedPixelData, VoiLutOption};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let data: DecodedPixelData = unimplemented!();
+    /// let options = ConvertOptions::new().with_voi_lut(VoiLutOption::First);
+    /// for frame in data.frames() {
+    ///     let pixels: Vec<u8> = frame.to_vec_with_options(&options)?;
+    ///     println!("frame #{}: {} pixels", frame.number(), pixels.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn frames(&self) -> Frames<'_> {
+        Frames::new(self)
+    }
+
+    /// Obtain a parallel iterator over the frames of the decoded pixel data.
+    ///
+    /// This yields the same frames as [`frames`](Self::frames).
+    #[cfg(feature = "rayon")]
+    pub fn par_frames(
+        &self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = DecodedFrame<'_>> + '_ {
+        frame::par_frames(self)
+    }
+
+    /// Retrieves the number of rows of the pixel data.
+    #[inline]
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Retrieves the number of columns of the pixel data.
+    #[inline]
+    pub fn columns(&self) -> u32 {
+        self.cols
+    }
+
+    /// Retrieves the photometric interpretation.
+    ///
+    /// If the photometric interpretation declared by the object
+    /// disagreed with the number of samples per pixel,
+    /// this is the resolved photometric interpretation
+    /// (see [`photometric_interpretation_mismatch`](Self::photometric_interpretation_mismatch)).
+    /// Color samples decoded from encapsulated pixel data are in `RGB`
+    /// (see [`original_photometric_interpretation`](Self::original_photometric_interpretation)).
+    #[inline]
+    pub fn photometric_interpretation(&self) -> &PhotometricInterpretation {
+        &self.photometric_interpretation
+    }
+
+    /// Retrieves the photometric interpretation declared by the object,
+    /// if the samples were decoded into a different one.
+    ///
+    /// This is the case when decoding color samples
+    /// from encapsulated pixel data,
+    /// such as `YBR_ICT` or `YBR_RCT` in JPEG 2000,
+    /// which are then already in `RGB`.
+    #[inline]
+    pub fn original_photometric_interpretation(&self) -> Option<&PhotometricInterpretation> {
+        self.original_photometric_interpretation.as_ref()
+    }
+
+    /// Retrieves the disagreement found
+    /// between the photometric interpretation declared by the object
+    /// and its number of samples per pixel,
+    /// or `None` if they were consistent.
+    #[inline]
+    pub fn photometric_interpretation_mismatch(
+        &self,
+    ) -> Option<&PhotometricInterpretationMismatch> {
+        self.photometric_interpretation_mismatch.as_ref()
+    }
+
+    /// Retrieves the imaging attributes
+    /// whose number of values disagreed with the number of frames
+    /// or with the number of values of their counterpart,
+    /// and which were fitted to the expected length
+    /// (see [`ValueMultiplicityMismatch`]).
+    #[inline]
+    pub fn value_multiplicity_mismatches(&self) -> &[ValueMultiplicityMismatch] {
+        &self.value_multiplicity_mismatches
+    }
+
+    /// Retrieves the required imaging attributes
+    /// which were missing from the object
+    /// and took a default value instead
+    /// (see [`ImagingProperties::from_object`]).
+    #[inline]
+    pub fn defaulted_attributes(&self) -> &[AttributeName] {
+        &self.defaulted_attributes
+    }
+
+    /// Retrieves the number of rows and columns declared by the object
+    /// if the pixel data was decoded at a lower resolution level
+    /// (see [`DecodeOptions::resolution_level`]),
+    /// in which case [`rows`](Self::rows) and [`columns`](Self::columns)
+    /// report the dimensions of the decoded pixel data instead.
+    /// Returns `None` if the pixel data was decoded at full resolution.
+    #[inline]
+    pub fn declared_dimensions(&self) -> Option<(u32, u32)> {
+        self.declared_dimensions
+    }
+
+    /// Retrieves the number of bytes found after the last frame
+    /// of the object's native pixel data,
+    /// such as padding to an even length.
+    ///
+    /// These bytes are not part of the decoded pixel data.
+    /// See [`DecodeOptions::max_trailing_bytes`]
+    /// for the number of bytes tolerated.
+    #[inline]
+    pub fn trailing_bytes(&self) -> usize {
+        self.trailing_bytes
+    }
+
+    /// Retrieves the order of the frames,
+    /// as determined by [`frame_order`](crate::frame_order) from the object.
+    ///
+    /// Pixel data decoded from a single frame
+    /// only has that frame in its order.
+    /// See [`ConvertOptions::with_sorted_frames`]
+    /// for converting all frames in this order.
+    #[inline]
+    pub fn frame_order(&self) -> &FrameOrder {
+        &self.frame_order
+    }
+
+    /// Retrieves the planar configuration of the pixel data.
+    ///
+    /// The value returned is only meaningful for
     /// images with more than 1 sample per pixel.
     #[inline]
     pub fn planar_configuration(&self) -> PlanarConfiguration {
@@ -543,6 +1600,10 @@ impl DecodedPixelData<'_> {
     }
 
     /// Retrieve the number of bits allocated for each sample.
+    ///
+    /// Samples of 1 bit, such as those of a segmentation,
+    /// are unpacked to one byte each when decoded,
+    /// so that each frame starts on a byte boundary.
     #[inline]
     pub fn bits_allocated(&self) -> u16 {
         self.bits_allocated
@@ -560,6 +1621,16 @@ impl DecodedPixelData<'_> {
         self.pixel_representation
     }
 
+    /// Retrieve the numeric format of the pixel data samples.
+    ///
+    /// Floating point samples come from
+    /// _Float Pixel Data_ or _Double Float Pixel Data_,
+    /// which are used when the object has no _Pixel Data_.
+    #[inline]
+    pub fn sample_format(&self) -> SampleFormat {
+        self.sample_format
+    }
+
     /// Retrieve object's rescale parameters.
     #[inline]
     pub fn rescale(&self) -> Result<&[Rescale]> {
@@ -616,34 +1687,145 @@ impl DecodedPixelData<'_> {
         }
     }
 
+    /// Retrieve the window levels defined by the object, if any.
     #[inline]
-    pub fn window(&self) -> Result<Option<&[WindowLevel]>> {
-        if let Some(inner) = &self.window {
-            let res = match &inner.len() {
-                0 => Ok(None),
-                1 => Ok(Some(inner.as_slice())),
-                len => {
-                    if *len == self.number_of_frames as usize {
-                        Ok(Some(inner.as_slice()))
-                    } else {
-                        if self.enforce_frame_fg_vm_match {
-                            LengthMismatchWindowLevelSnafu {
-                                ww_vm: *len as u32,
-                                wc_vm: *len as u32,
-                            }
-                            .fail()?
-                        }
-                        tracing::warn!("Expected `{:?}` Window Levels, found `{:?}`, using first value for all", self.number_of_frames, len);
-                        Ok(Some(&inner[0..1]))
+    pub fn window(&self) -> Result<Option<&WindowLevels>> {
+        match &self.window {
+            Some(WindowLevels::PerFrame(frames))
+                if frames.len() > 1 && frames.len() != self.number_of_frames as usize =>
+            {
+                ensure!(
+                    !self.enforce_frame_fg_vm_match,
+                    LengthMismatchWindowLevelFramesSnafu {
+                        len: frames.len() as u32,
+                        nr_frames: self.number_of_frames,
                     }
-                }
-            };
-            res
-        } else {
+                );
+                Ok(self.window.as_ref())
+            }
+            window => Ok(window.as_ref()),
+        }
+    }
+
+    /// Retrieve the alternative window levels
+    /// applicable to the given frame, if any.
+    ///
+    /// Window levels defined per frame
+    /// which do not match the number of frames
+    /// are ignored in favor of the window levels of the first frame.
+    pub fn window_for_frame(&self, frame: u32) -> Result<Option<&[WindowLevel]>> {
+        let window = match self.window()? {
+            Some(window) => window,
+            None => return Ok(None),
+        };
+        let frame = match window {
+            WindowLevels::PerFrame(frames)
+                if frames.len() > 1 && frames.len() != self.number_of_frames as usize =>
+            {
+                tracing::warn!(
+                    "Expected `{:?}` Window Levels, found `{:?}`, using first value for all",
+                    self.number_of_frames,
+                    frames.len()
+                );
+                0
+            }
+            _ => frame,
+        };
+        let windows = window.for_frame(frame);
+        if windows.is_empty() {
             Ok(None)
+        } else {
+            Ok(Some(windows))
         }
     }
 
+    /// Retrieve all alternative window levels defined by the object,
+    /// which can be selected with [`VoiLutOption::Index`].
+    ///
+    /// Window levels defined per frame
+    /// are those of the first frame.
+    /// See [`window_for_frame`](Self::window_for_frame)
+    /// for the window levels of other frames.
+    pub fn windows(&self) -> &[WindowLevel] {
+        self.window
+            .as_ref()
+            .map(|window| window.for_frame(0))
+            .unwrap_or_default()
+    }
+
+    /// Retrieve the VOI LUT function applicable to the given frame,
+    /// which is linear if not defined by the object.
+    fn voi_lut_function_for_frame(&self, frame: u32) -> Result<VoiLutFunction> {
+        Ok(match self.voi_lut_function()? {
+            Some(lut) => {
+                if lut.len() > 1 {
+                    lut[frame as usize]
+                } else {
+                    lut[0]
+                }
+            }
+            None => VoiLutFunction::Linear,
+        })
+    }
+
+    /// Build the window level transformation
+    /// for the alternative window level at the given index
+    /// applicable to the given frame.
+    fn window_level_at(&self, frame: u32, index: usize) -> Result<WindowLevelTransform> {
+        let windows = self.window_for_frame(frame)?.unwrap_or_default();
+        let window = windows.get(index).context(WindowLevelOutOfRangeSnafu {
+            index,
+            len: windows.len(),
+        })?;
+        let function = match self.window_voi_lut_functions.get(index) {
+            Some(function) => *function,
+            None => self.voi_lut_function_for_frame(frame)?,
+        };
+        Ok(WindowLevelTransform::new(function, *window))
+    }
+
+    /// Retrieve the tabular Modality LUT
+    /// defined in the Modality LUT Sequence, if any,
+    /// which applies to all frames.
+    ///
+    /// When present, it is applied by default
+    /// instead of the rescale parameters.
+    #[inline]
+    pub fn modality_lut(&self) -> Option<&ModalityLut> {
+        self.modality_lut.as_ref()
+    }
+
+    /// Retrieve the tabular VOI LUTs defined in the VOI LUT Sequence,
+    /// which apply to all frames.
+    ///
+    /// Empty if the object does not define a VOI LUT Sequence.
+    #[inline]
+    pub fn voi_luts(&self) -> &[VoiLut] {
+        &self.voi_luts
+    }
+
+    /// Retrieve the palette color lookup tables,
+    /// if the photometric interpretation is _PALETTE COLOR_,
+    /// or the supplemental palette of monochrome pixel data.
+    ///
+    /// `None` if the tables are missing or invalid,
+    /// in which case the stored values can only be retrieved as indices
+    /// (see [`PaletteOption::Indices`]).
+    #[inline]
+    pub fn palette(&self) -> Option<&PaletteColorLut> {
+        self.palette.as_ref()
+    }
+
+    /// Retrieve the stored values which denote padding,
+    /// as described by the _Pixel Padding Value_
+    /// and the _Pixel Padding Range Limit_.
+    ///
+    /// `None` if the object does not define a _Pixel Padding Value_.
+    #[inline]
+    pub fn pixel_padding(&self) -> Option<PixelPadding> {
+        self.pixel_padding
+    }
+
     // converter methods
 
     /// Convert the decoded pixel data of a specific frame into a dynamic image.
@@ -667,6 +1849,8 @@ impl DecodedPixelData<'_> {
     /// according to the attributes of the given object.
     /// Note that certain options may be ignored
     /// if they do not apply.
+    /// Floating point samples are always windowed into the output range,
+    /// and normalized to it if no window is available.
     ///
     /// # Example
     ///
@@ -689,18 +1873,54 @@ impl DecodedPixelData<'_> {
         frame: u32,
         options: &ConvertOptions,
     ) -> Result<DynamicImage> {
+        if let Some(palette) = self.palette_to_apply(options)? {
+            return self.build_palette_color_image(frame, palette, options.bit_depth);
+        }
+        if let Some(palette) = self.supplemental_palette_to_apply(options) {
+            return self.build_supplemental_palette_image(frame, palette, options);
+        }
+
         match self.samples_per_pixel {
+            1 if self.sample_format.is_float()
+                || self.modality_lut_to_apply(&options.modality_lut).is_some() =>
+            {
+                self.build_monochrome_image_per_sample(frame, options)
+            }
             1 => self.build_monochrome_image(frame, options),
+            3 if matches!(
+                self.photometric_interpretation,
+                PhotometricInterpretation::YbrPartial420 | PhotometricInterpretation::YbrPartial422
+            ) =>
+            {
+                // Convert YBR_PARTIAL_420 or YBR_PARTIAL_422 to RGB,
+                // upsampling the chroma samples if necessary
+                match self.bits_allocated {
+                    8 => {
+                        let pixel_array = self.ybr_partial_to_rgb::<u8>(frame)?;
+                        self.rgb_image_with_extend(pixel_array, options.bit_depth)
+                    }
+                    16 => {
+                        let pixel_array = self.ybr_partial_to_rgb::<u16>(frame)?;
+                        self.rgb_image_with_narrow(pixel_array, options.bit_depth)
+                    }
+                    _ => InvalidBitsAllocatedSnafu.fail()?,
+                }
+            }
             3 => {
                 // Modality LUT and VOI LUT
                 // are currently ignored in this case
 
                 // RGB, YBR_FULL or YBR_FULL_422 colors
+                let rows = self.rows as usize;
+                let cols = self.cols as usize;
                 match self.bits_allocated {
                     8 => {
+                        let data = self.frame_data(frame)?;
                         let mut pixel_array = match self.planar_configuration {
-                            PlanarConfiguration::Standard => self.frame_data(frame)?.to_vec(),
-                            PlanarConfiguration::PixelFirst => interleave(self.frame_data(frame)?),
+                            // chroma samples are subsampled in native YBR_FULL_422
+                            _ if data.len() < rows * cols * 3 => upsample_ybr_422(data, rows, cols),
+                            PlanarConfiguration::Standard => data.to_vec(),
+                            PlanarConfiguration::PixelFirst => interleave(data),
                         };
 
                         // Convert YBR_FULL or YBR_FULL_422 to RGB
@@ -719,6 +1939,10 @@ impl DecodedPixelData<'_> {
                     }
                     16 => {
                         let mut pixel_array: Vec<u16> = match self.planar_configuration {
+                            // chroma samples are subsampled in native YBR_FULL_422
+                            _ if self.frame_data(frame)?.len() < rows * cols * 6 => {
+                                upsample_ybr_422(&self.frame_data_ow(frame)?, rows, cols)
+                            }
                             PlanarConfiguration::Standard => self.frame_data_ow(frame)?,
                             PlanarConfiguration::PixelFirst => {
                                 // Would there be a way to avoid copying the data twice
@@ -753,10 +1977,23 @@ impl DecodedPixelData<'_> {
         &self,
         pixel_values: impl IntoIterator<Item = u16>,
         bit_depth: BitDepthOption,
+        dithering: DitherOption,
     ) -> Result<DynamicImage> {
         if bit_depth == BitDepthOption::Force8Bit {
             // user requested 8 bits, narrow
-            let data: Vec<u8> = pixel_values.into_iter().map(|x| (x >> 8) as u8).collect();
+            let cols = (self.cols as usize).max(1);
+            let data: Vec<u8> = match dithering {
+                DitherOption::None => pixel_values.into_iter().map(dither::round).collect(),
+                DitherOption::Ordered => pixel_values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, x)| dither::ordered(x, i % cols, i / cols))
+                    .collect(),
+                DitherOption::FloydSteinberg => {
+                    let values: Vec<u16> = pixel_values.into_iter().collect();
+                    dither::floyd_steinberg(&values, cols)
+                }
+            };
             let image_buffer: ImageBuffer<Luma<u8>, Vec<u8>> =
                 ImageBuffer::from_raw(self.cols, self.rows, data)
                     .context(InvalidImageBufferSnafu)?;
@@ -775,10 +2012,29 @@ impl DecodedPixelData<'_> {
         &self,
         pixel_values: impl ParallelIterator<Item = u16>,
         bit_depth: BitDepthOption,
+        dithering: DitherOption,
     ) -> Result<DynamicImage> {
         if bit_depth == BitDepthOption::Force8Bit {
             // user requested 8 bits, narrow
-            let data: Vec<u8> = pixel_values.map(|x| (x >> 8) as u8).collect();
+            let cols = (self.cols as usize).max(1);
+            let data: Vec<u8> = match dithering {
+                DitherOption::None => pixel_values.map(dither::round).collect(),
+                DitherOption::Ordered => {
+                    use rayon::iter::IndexedParallelIterator;
+
+                    let values: Vec<u16> = pixel_values.collect();
+                    values
+                        .par_iter()
+                        .enumerate()
+                        .map(|(i, x)| dither::ordered(*x, i % cols, i / cols))
+                        .collect()
+                }
+                DitherOption::FloydSteinberg => {
+                    // error diffusion is sequential
+                    let values: Vec<u16> = pixel_values.collect();
+                    dither::floyd_steinberg(&values, cols)
+                }
+            };
             let image_buffer: ImageBuffer<Luma<u8>, Vec<u8>> =
                 ImageBuffer::from_raw(self.cols, self.rows, data)
                     .context(InvalidImageBufferSnafu)?;
@@ -843,6 +2099,64 @@ impl DecodedPixelData<'_> {
         }
     }
 
+    /// Convert the samples of a frame
+    /// in the `YBR_PARTIAL_420` or `YBR_PARTIAL_422` photometric interpretation
+    /// to full range RGB.
+    ///
+    /// The chroma samples may be subsampled,
+    /// in which case they are upsampled to the size of the image,
+    /// or have one sample per pixel already,
+    /// as produced by decoders which upsample them on their own.
+    /// Subsampled 4:2:2 data is expected
+    /// in groups of `Y Y CB CR` samples for every two pixels in a row,
+    /// or in one plane per component
+    /// if the planar configuration says so.
+    /// Subsampled 4:2:0 data is always expected
+    /// in one plane per component.
+    #[cfg(feature = "image")]
+    fn ybr_partial_to_rgb<T>(&self, frame: u32) -> Result<Vec<T>>
+    where
+        T: NumCast + Copy + Default + Send + Sync,
+    {
+        if let SampleState::EncodedOnly { ts_uid, .. } = &self.samples {
+            return NotDecodedSnafu { ts: ts_uid }.fail()?;
+        }
+        let vertical = self.photometric_interpretation == PhotometricInterpretation::YbrPartial420;
+        let rows = self.rows as usize;
+        let cols = self.cols as usize;
+
+        let full_size = native_frame_size(self.bits_allocated, 3, self.rows, self.cols)
+            .context(FrameSizeOverflowSnafu)?;
+        let chroma_rows = if vertical { (rows + 1) / 2 } else { rows };
+        let chroma_len = chroma_rows * ((cols + 1) / 2);
+        let subsampled_size = (rows * cols + 2 * chroma_len) * usize::from(self.bits_allocated / 8);
+
+        // the size of the pixel data tells whether the chroma samples are subsampled
+        let upsampled = full_size
+            .checked_mul(self.number_of_frames as usize)
+            .map_or(false, |len| self.data.len() >= len);
+        let (frame_size, layout) = match (upsampled, self.planar_configuration) {
+            (true, PlanarConfiguration::Standard) => (full_size, YbrLayout::Interleaved),
+            (true, PlanarConfiguration::PixelFirst) => (full_size, YbrLayout::Planar),
+            (false, PlanarConfiguration::Standard) if !vertical => {
+                (subsampled_size, YbrLayout::Pairs)
+            }
+            (false, _) => (subsampled_size, YbrLayout::Subsampled { vertical }),
+        };
+
+        let frame_range = native_frame_range(frame_size, frame).context(FrameSizeOverflowSnafu)?;
+        let data = self.data.get(frame_range).context(FrameOutOfRangeSnafu {
+            frame_number: frame,
+        })?;
+        let samples: Vec<f32> = match self.bits_allocated {
+            8 => data.iter().map(|&v| f32::from(v)).collect(),
+            16 => bytes_to_vec_u16(data).into_iter().map(f32::from).collect(),
+            _ => InvalidBitsAllocatedSnafu.fail()?,
+        };
+        let max = ((1_u32 << self.bits_allocated) - 1) as f32;
+        Ok(convert_ybr_partial(&samples, layout, rows, cols, max))
+    }
+
     #[cfg(feature = "image")]
     fn rgb_image_with_extend(
         &self,
@@ -876,7 +2190,7 @@ impl DecodedPixelData<'_> {
     ) -> Result<DynamicImage> {
         if bit_depth == BitDepthOption::Force8Bit {
             // user requested 8 bits, narrow
-            let data: Vec<u8> = pixels.into_iter().map(|x| (x >> 8) as u8).collect();
+            let data: Vec<u8> = pixels.into_iter().map(dither::round).collect();
             let image_buffer: ImageBuffer<Rgb<u8>, Vec<u8>> =
                 ImageBuffer::from_raw(self.cols, self.rows, data)
                     .context(InvalidImageBufferSnafu)?;
@@ -889,18 +2203,96 @@ impl DecodedPixelData<'_> {
         }
     }
 
+    #[cfg(feature = "image")]
+    fn build_palette_color_image(
+        &self,
+        frame: u32,
+        palette: &PaletteColorLut,
+        bit_depth: BitDepthOption,
+    ) -> Result<DynamicImage> {
+        let indices = self.palette_indices(self.frame_data(frame)?)?;
+        if palette.bits <= 8 {
+            let pixels: Vec<u8> = indices
+                .into_iter()
+                .flat_map(|v| palette.apply(v))
+                .map(|e| e.min(0xFF) as u8)
+                .collect();
+            self.rgb_image_with_extend(pixels, bit_depth)
+        } else {
+            // scale the entries to the full 16-bit range
+            let max = (1_u32 << palette.bits.min(16)) - 1;
+            let pixels: Vec<u16> = indices
+                .into_iter()
+                .flat_map(|v| palette.apply(v))
+                .map(|e| (u32::from(e).min(max) * 0xFFFF / max) as u16)
+                .collect();
+            self.rgb_image_with_narrow(pixels, bit_depth)
+        }
+    }
+
+    /// Build an RGB image from monochrome pixel data with a supplemental palette,
+    /// where stored values from the first value mapped by the palette
+    /// take their palette entries,
+    /// and the other values are rendered in grayscale.
+    #[cfg(feature = "image")]
+    fn build_supplemental_palette_image(
+        &self,
+        frame: u32,
+        palette: &PaletteColorLut,
+        options: &ConvertOptions,
+    ) -> Result<DynamicImage> {
+        let grayscale = self.to_dynamic_image_with_options(
+            frame,
+            &options.clone().with_palette(PaletteOption::Indices),
+        )?;
+        let data = self.realign_samples(self.frame_data(frame)?, options.unused_bits)?;
+        let values = self.palette_indices(&data)?;
+        let max = (1_u32 << palette.bits.clamp(1, 16)) - 1;
+        let entries = |value: i32, out_max: u32| {
+            palette
+                .apply(value)
+                .map(|e| u32::from(e).min(max) * out_max / max)
+        };
+
+        match &grayscale {
+            DynamicImage::ImageLuma8(_) | DynamicImage::ImageRgb8(_) => {
+                let mut image = grayscale.to_rgb8();
+                for (pixel, &value) in image.pixels_mut().zip(&values) {
+                    if value >= palette.first_mapped {
+                        *pixel = Rgb(entries(value, 0xFF).map(|e| e as u8));
+                    }
+                }
+                Ok(DynamicImage::ImageRgb8(image))
+            }
+            _ => {
+                let mut image = grayscale.to_rgb16();
+                for (pixel, &value) in image.pixels_mut().zip(&values) {
+                    if value >= palette.first_mapped {
+                        *pixel = Rgb(entries(value, 0xFFFF).map(|e| e as u16));
+                    }
+                }
+                Ok(DynamicImage::ImageRgb16(image))
+            }
+        }
+    }
+
     #[cfg(feature = "image")]
     fn build_monochrome_image(&self, frame: u32, options: &ConvertOptions) -> Result<DynamicImage> {
         let ConvertOptions {
             modality_lut,
             voi_lut,
             bit_depth,
+            dither,
+            padding,
+            unused_bits,
+            ..
         } = options;
 
-        let mut image = match self.bits_allocated {
-            8 => {
-                let data = self.frame_data(frame)?;
+        let data = self.realign_samples(self.frame_data(frame)?, *unused_bits)?;
 
+        let mut image = match self.bits_allocated {
+            // samples of 1 bit were unpacked to one byte each
+            1 | 8 => {
                 match modality_lut {
                     // simplest one, no transformations
                     ModalityLutOption::None => {
@@ -921,46 +2313,65 @@ impl DecodedPixelData<'_> {
 
                         let signed = self.pixel_representation == PixelRepresentation::Signed;
 
-                        let lut: Lut<u8> = match (voi_lut, self.window()?) {
-                            (VoiLutOption::Identity, _) => {
+                        let lut: Lut<u8> = match (
+                            voi_lut,
+                            self.window_for_frame(frame)?.and_then(|w| w.first()),
+                            self.voi_luts.first(),
+                        ) {
+                            (VoiLutOption::Identity, _, _) => {
                                 Lut::new_rescale(8, false, rescale).context(CreateLutSnafu)?
                             }
-                            (VoiLutOption::Default | VoiLutOption::First, Some(window)) => {
-                                Lut::new_rescale_and_window(
-                                    8,
-                                    signed,
-                                    rescale,
-                                    WindowLevelTransform::new(
-                                        match self.voi_lut_function()? {
-                                            Some(lut) => {
-                                                if lut.len() > 1 {
-                                                    lut[frame as usize]
-                                                } else {
-                                                    lut[0]
-                                                }
-                                            }
-                                            None => VoiLutFunction::Linear,
-                                        },
-                                        if window.len() > 1 {
-                                            window[frame as usize]
-                                        } else {
-                                            window[0]
-                                        },
-                                    ),
-                                )
-                                .context(CreateLutSnafu)?
+                            (VoiLutOption::Table, _, Some(table))
+                            | (VoiLutOption::Default | VoiLutOption::First, None, Some(table)) => {
+                                Lut::new_rescale_and_voi_lut(8, signed, rescale, table)
+                                    .context(CreateLutSnafu)?
                             }
-                            (VoiLutOption::Default | VoiLutOption::First, None) => {
+                            (
+                                VoiLutOption::Default | VoiLutOption::First | VoiLutOption::Table,
+                                Some(window),
+                                _,
+                            ) => Lut::new_rescale_and_window(
+                                8,
+                                signed,
+                                rescale,
+                                WindowLevelTransform::new(
+                                    match self.voi_lut_function()? {
+                                        Some(lut) => {
+                                            if lut.len() > 1 {
+                                                lut[frame as usize]
+                                            } else {
+                                                lut[0]
+                                            }
+                                        }
+                                        None => VoiLutFunction::Linear,
+                                    },
+                                    *window,
+                                ),
+                            )
+                            .context(CreateLutSnafu)?,
+                            (
+                                VoiLutOption::Default | VoiLutOption::First | VoiLutOption::Table,
+                                None,
+                                _,
+                            ) => {
                                 tracing::warn!("Could not find window level for object");
                                 Lut::new_rescale_and_normalize(
                                     8,
                                     signed,
                                     rescale,
                                     data.iter().copied(),
+                                    self.padding_to_exclude(*padding),
                                 )
                                 .context(CreateLutSnafu)?
                             }
-                            (VoiLutOption::Custom(window), _) => Lut::new_rescale_and_window(
+                            (VoiLutOption::Index(index), _, _) => Lut::new_rescale_and_window(
+                                8,
+                                signed,
+                                rescale,
+                                self.window_level_at(frame, *index)?,
+                            )
+                            .context(CreateLutSnafu)?,
+                            (VoiLutOption::Custom(window), _, _) => Lut::new_rescale_and_window(
                                 8,
                                 signed,
                                 rescale,
@@ -979,7 +2390,7 @@ impl DecodedPixelData<'_> {
                                 ),
                             )
                             .context(CreateLutSnafu)?,
-                            (VoiLutOption::CustomWithFunction(window, function), _) => {
+                            (VoiLutOption::CustomWithFunction(window, function), _, _) => {
                                 Lut::new_rescale_and_window(
                                     8,
                                     signed,
@@ -988,13 +2399,33 @@ impl DecodedPixelData<'_> {
                                 )
                                 .context(CreateLutSnafu)?
                             }
-                            (VoiLutOption::Normalize, _) => Lut::new_rescale_and_normalize(
+                            (VoiLutOption::Normalize, _, _) => Lut::new_rescale_and_normalize(
                                 8,
                                 signed,
                                 rescale,
                                 data.iter().copied(),
+                                self.padding_to_exclude(*padding),
                             )
                             .context(CreateLutSnafu)?,
+                            (VoiLutOption::Percentile { low, high }, _, _) => {
+                                Lut::new_rescale_and_percentile(
+                                    8,
+                                    signed,
+                                    rescale,
+                                    &*data,
+                                    self.padding_to_exclude(*padding),
+                                    *low,
+                                    *high,
+                                )
+                                .context(CreateLutSnafu)?
+                            }
+                        };
+
+                        // padding is only masked along with a VOI LUT transformation
+                        let lut = if *voi_lut == VoiLutOption::Identity {
+                            lut
+                        } else {
+                            self.mask_padding(lut, *padding)
                         };
 
                         #[cfg(feature = "rayon")]
@@ -1016,37 +2447,21 @@ impl DecodedPixelData<'_> {
                     // convert to image only after shifting values
                     // to an unsigned scale
                     ModalityLutOption::None => {
-                        let frame_length = self.rows as usize
-                            * self.cols as usize
-                            * 2
-                            * self.samples_per_pixel as usize;
-                        let frame_start = frame_length * frame as usize;
-                        let frame_end = frame_start + frame_length;
-                        if frame_end > (*self.data).len() {
-                            FrameOutOfRangeSnafu {
-                                frame_number: frame,
-                            }
-                            .fail()?
-                        }
+                        let frame_data = &*data;
 
                         let buffer = match self.pixel_representation {
                             // Unsigned 16-bit representation
-                            PixelRepresentation::Unsigned => {
-                                bytes_to_vec_u16(&self.data[frame_start..frame_end])
-                            }
+                            PixelRepresentation::Unsigned => bytes_to_vec_u16(frame_data),
                             // Signed 16-bit representation
                             PixelRepresentation::Signed => {
-                                let mut signed_buffer = vec![0; frame_length / 2];
-                                NativeEndian::read_i16_into(
-                                    &self.data[frame_start..frame_end],
-                                    &mut signed_buffer,
-                                );
+                                let mut signed_buffer = vec![0; frame_data.len() / 2];
+                                NativeEndian::read_i16_into(frame_data, &mut signed_buffer);
                                 // Convert buffer to unsigned by shifting
                                 convert_i16_to_u16(&signed_buffer)
                             }
                         };
 
-                        self.mono_image_with_narrow(buffer.into_iter(), *bit_depth)?
+                        self.mono_image_with_narrow(buffer.into_iter(), *bit_depth, *dither)?
                     }
 
                     ModalityLutOption::Default | ModalityLutOption::Override(..) => {
@@ -1068,38 +2483,53 @@ impl DecodedPixelData<'_> {
                         // Note: samples are not read as `i16` even if signed,
                         // because the LUT takes care of interpreting them properly.
 
-                        let samples = self.frame_data_ow(frame)?;
+                        let samples = bytes_to_vec_u16(&data);
 
                         // use 16-bit precision to prevent possible loss of precision in image
-                        let lut: Lut<u16> = match (voi_lut, self.window()?) {
-                            (VoiLutOption::Identity, _) => {
+                        let lut: Lut<u16> = match (
+                            voi_lut,
+                            self.window_for_frame(frame)?.and_then(|w| w.first()),
+                            self.voi_luts.first(),
+                        ) {
+                            (VoiLutOption::Identity, _, _) => {
                                 Lut::new_rescale(self.bits_stored, signed, rescale)
                             }
-                            (VoiLutOption::Default | VoiLutOption::First, Some(window)) => {
-                                Lut::new_rescale_and_window(
+                            (VoiLutOption::Table, _, Some(table))
+                            | (VoiLutOption::Default | VoiLutOption::First, None, Some(table)) => {
+                                Lut::new_rescale_and_voi_lut(
                                     self.bits_stored,
                                     signed,
                                     rescale,
-                                    WindowLevelTransform::new(
-                                        match self.voi_lut_function()? {
-                                            Some(lut) => {
-                                                if lut.len() > 1 {
-                                                    lut[frame as usize]
-                                                } else {
-                                                    lut[0]
-                                                }
-                                            }
-                                            None => VoiLutFunction::Linear,
-                                        },
-                                        if window.len() > 1 {
-                                            window[frame as usize]
-                                        } else {
-                                            window[0]
-                                        },
-                                    ),
+                                    table,
                                 )
                             }
-                            (VoiLutOption::Default | VoiLutOption::First, None) => {
+                            (
+                                VoiLutOption::Default | VoiLutOption::First | VoiLutOption::Table,
+                                Some(window),
+                                _,
+                            ) => Lut::new_rescale_and_window(
+                                self.bits_stored,
+                                signed,
+                                rescale,
+                                WindowLevelTransform::new(
+                                    match self.voi_lut_function()? {
+                                        Some(lut) => {
+                                            if lut.len() > 1 {
+                                                lut[frame as usize]
+                                            } else {
+                                                lut[0]
+                                            }
+                                        }
+                                        None => VoiLutFunction::Linear,
+                                    },
+                                    *window,
+                                ),
+                            ),
+                            (
+                                VoiLutOption::Default | VoiLutOption::First | VoiLutOption::Table,
+                                None,
+                                _,
+                            ) => {
                                 tracing::warn!("Could not find window level for object");
 
                                 Lut::new_rescale_and_normalize(
@@ -1107,9 +2537,16 @@ impl DecodedPixelData<'_> {
                                     signed,
                                     rescale,
                                     samples.iter().copied(),
+                                    self.padding_to_exclude(*padding),
                                 )
                             }
-                            (VoiLutOption::Custom(window), _) => Lut::new_rescale_and_window(
+                            (VoiLutOption::Index(index), _, _) => Lut::new_rescale_and_window(
+                                self.bits_stored,
+                                signed,
+                                rescale,
+                                self.window_level_at(frame, *index)?,
+                            ),
+                            (VoiLutOption::Custom(window), _, _) => Lut::new_rescale_and_window(
                                 self.bits_stored,
                                 signed,
                                 rescale,
@@ -1127,7 +2564,7 @@ impl DecodedPixelData<'_> {
                                     *window,
                                 ),
                             ),
-                            (VoiLutOption::CustomWithFunction(window, function), _) => {
+                            (VoiLutOption::CustomWithFunction(window, function), _, _) => {
                                 Lut::new_rescale_and_window(
                                     self.bits_stored,
                                     signed,
@@ -1135,24 +2572,43 @@ impl DecodedPixelData<'_> {
                                     WindowLevelTransform::new(*function, *window),
                                 )
                             }
-                            (VoiLutOption::Normalize, _) => Lut::new_rescale_and_normalize(
+                            (VoiLutOption::Normalize, _, _) => Lut::new_rescale_and_normalize(
                                 self.bits_stored,
                                 signed,
                                 rescale,
                                 samples.iter().copied(),
+                                self.padding_to_exclude(*padding),
                             ),
+                            (VoiLutOption::Percentile { low, high }, _, _) => {
+                                Lut::new_rescale_and_percentile(
+                                    self.bits_stored,
+                                    signed,
+                                    rescale,
+                                    &samples[..],
+                                    self.padding_to_exclude(*padding),
+                                    *low,
+                                    *high,
+                                )
+                            }
                         }
                         .context(CreateLutSnafu)?;
 
+                        // padding is only masked along with a VOI LUT transformation
+                        let lut = if *voi_lut == VoiLutOption::Identity {
+                            lut
+                        } else {
+                            self.mask_padding(lut, *padding)
+                        };
+
                         #[cfg(feature = "rayon")]
                         {
                             let pixel_values = lut.map_par_iter(samples.par_iter().copied());
-                            self.mono_image_with_narrow_par(pixel_values, *bit_depth)?
+                            self.mono_image_with_narrow_par(pixel_values, *bit_depth, *dither)?
                         }
                         #[cfg(not(feature = "rayon"))]
                         {
                             let pixel_values = lut.map_iter(samples.iter().copied());
-                            self.mono_image_with_narrow(pixel_values, *bit_depth)?
+                            self.mono_image_with_narrow(pixel_values, *bit_depth, *dither)?
                         }
                     }
                 }
@@ -1166,6 +2622,58 @@ impl DecodedPixelData<'_> {
         Ok(image)
     }
 
+    /// Build a monochrome image out of floating point samples
+    /// or samples mapped through a tabular Modality LUT,
+    /// which are windowed into the 16-bit output range
+    /// (then narrowed if 8 bits were requested).
+    ///
+    /// The samples are normalized if no window is found,
+    /// since they would not fit the output range as is.
+    #[cfg(feature = "image")]
+    fn build_monochrome_image_per_sample(
+        &self,
+        frame: u32,
+        options: &ConvertOptions,
+    ) -> Result<DynamicImage> {
+        let ConvertOptions {
+            modality_lut,
+            voi_lut,
+            bit_depth,
+            dither,
+            padding,
+            unused_bits,
+            ..
+        } = options;
+
+        let data = self.realign_samples(self.frame_data(frame)?, *unused_bits)?;
+        let samples = self.samples_as_f64(&data);
+        let modality = match modality_lut {
+            ModalityLutOption::None => ModalityTransform::Rescale(Rescale::new(1., 0.)),
+            _ => self.modality_for_frame(frame, modality_lut)?,
+        };
+        let voi_lut = match voi_lut {
+            VoiLutOption::Default => &VoiLutOption::First,
+            voi_lut => voi_lut,
+        };
+        let y_max = f64::from(u16::MAX);
+        let transform =
+            self.sample_transform(frame, modality, voi_lut, *padding, &samples, y_max)?;
+        let to_u16 = |v: &f64| transform(*v).round().clamp(0., y_max) as u16;
+
+        #[cfg(feature = "rayon")]
+        let mut image =
+            self.mono_image_with_narrow_par(samples.par_iter().map(to_u16), *bit_depth, *dither)?;
+        #[cfg(not(feature = "rayon"))]
+        let mut image =
+            self.mono_image_with_narrow(samples.iter().map(to_u16), *bit_depth, *dither)?;
+
+        // Convert MONOCHROME1 => MONOCHROME2
+        if self.photometric_interpretation == PhotometricInterpretation::Monochrome1 {
+            image.invert();
+        }
+        Ok(image)
+    }
+
     /// Convert all of the decoded pixel data into a vector of flat pixels
     /// of a given type `T`.
     ///
@@ -1175,7 +2683,11 @@ impl DecodedPixelData<'_> {
     /// The underlying pixel data type is extracted based on
     /// the bits allocated and pixel representation,
     /// which is then converted to the requested type.
-    /// Photometric interpretation is ignored.
+    /// Photometric interpretation is ignored,
+    /// except that _PALETTE COLOR_ indices are mapped to RGB samples
+    /// (see [`PaletteOption`]).
+    /// Floating point samples (see [`sample_format`](Self::sample_format))
+    /// are converted directly, without going through a lookup table.
     ///
     /// The default pixel data process pipeline
     /// applies only the Modality LUT function.
@@ -1215,7 +2727,9 @@ impl DecodedPixelData<'_> {
     /// The underlying pixel data type is extracted based on
     /// the bits allocated and pixel representation,
     /// which is then converted to the requested type.
-    /// Photometric interpretation is ignored.
+    /// Photometric interpretation is ignored,
+    /// except that _PALETTE COLOR_ indices are mapped to RGB samples
+    /// (see [`PaletteOption`]).
     ///
     /// The `options` value allows you to specify
     /// which transformations should be done to the pixel data
@@ -1225,11 +2739,20 @@ impl DecodedPixelData<'_> {
     where
         T: NumCast + Send + Sync + Copy + 'static,
     {
+        let frames: Vec<u32> = if options.sort_frames
+            && self.frame_order.number_of_frames() == self.number_of_frames
+        {
+            self.frame_order.permutation().to_vec()
+        } else {
+            (0..self.number_of_frames).collect()
+        };
         let mut res: Vec<T> = Vec::new();
-        for frame in 0..self.number_of_frames {
+        for (i, frame) in (0..).zip(frames) {
+            options.hooks.check_cancelled(i, self.number_of_frames)?;
             let frame_data: Vec<T> =
                 self.convert_pixel_slice(self.frame_data(frame)?, frame, options)?;
-            res.extend(frame_data)
+            res.extend(frame_data);
+            options.hooks.report_progress(i + 1, self.number_of_frames);
         }
         Ok(res)
     }
@@ -1243,7 +2766,9 @@ impl DecodedPixelData<'_> {
     /// The underlying pixel data type is extracted based on
     /// the bits allocated and pixel representation,
     /// which is then converted to the requested type.
-    /// Photometric interpretation is ignored.
+    /// Photometric interpretation is ignored,
+    /// except that _PALETTE COLOR_ indices are mapped to RGB samples
+    /// (see [`PaletteOption`]).
     ///
     /// The default pixel data process pipeline
     /// applies only the Modality LUT function.
@@ -1305,6 +2830,304 @@ impl DecodedPixelData<'_> {
         self.convert_pixel_slice(self.frame_data(frame)?, frame, options)
     }
 
+    /// Convert the decoded pixel data of a PET image
+    /// into a vector of body weight standardized uptake values (SUVbw)
+    /// of a given type `T`.
+    ///
+    /// The Modality LUT function of each frame
+    /// is composed with the SUV scaling factor of the given parameters,
+    /// so that the stored values are converted directly to SUV.
+    /// No VOI LUT function is applied.
+    ///
+    /// See [`suv`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dicom_object::open_file;
+    /// # use dicom_pixeldata::{suv::SuvParameters, PixelDecoder};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let obj = open_file("pet.dcm")?;
+    /// let suv = SuvParameters::from_obj(&obj)?;
+    /// let values: Vec<f32> = obj.decode_pixel_data()?.to_vec_suv(&suv)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_vec_suv<T>(&self, suv: &suv::SuvParameters) -> Result<Vec<T>>
+    where
+        T: NumCast + Send + Sync + Copy + 'static,
+    {
+        let rescale = self.rescale()?;
+        let mut res: Vec<T> = Vec::new();
+        for frame in 0..self.number_of_frames {
+            let frame_rescale = rescale.get(frame as usize).unwrap_or(&rescale[0]);
+            let options = ConvertOptions::new()
+                .with_modality_lut(ModalityLutOption::Override(suv.rescale(frame_rescale)));
+            let frame_data: Vec<T> =
+                self.convert_pixel_slice(self.frame_data(frame)?, frame, &options)?;
+            res.extend(frame_data)
+        }
+        Ok(res)
+    }
+
+    /// Compute a histogram of the rescaled sample values of a frame,
+    /// with the given number of bins of equal width
+    /// spanning the range of the values.
+    ///
+    /// The Modality LUT transformation of the frame is applied
+    /// (see [`ModalityLutOption::Default`]),
+    /// whereas pixel padding values are counted like any other value.
+    /// Only monochrome pixel data
+    /// with 8 or 16 bits allocated per sample is supported.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use dicom_pixeldata::DecodedPixelData;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let data: DecodedPixelData = unimplemented!();
+    /// let histogram = data.histogram(0, 64)?;
+    /// for (i, count) in histogram.counts().iter().enumerate() {
+    ///     let (start, end) = histogram.bin_range(i).unwrap();
+    ///     println!("[{}, {}): {}", start, end, count);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn histogram(&self, frame: u32, bins: usize) -> Result<Histogram> {
+        ensure!(
+            self.samples_per_pixel == 1,
+            UnsupportedSamplesPerPixelSnafu {
+                spp: self.samples_per_pixel,
+            }
+        );
+        ensure!(
+            self.photometric_interpretation.is_monochrome(),
+            UnsupportedPhotometricInterpretationSnafu {
+                pi: self.photometric_interpretation.clone(),
+            }
+        );
+        ensure!(
+            !self.sample_format.is_float(),
+            UnsupportedOtherSnafu {
+                name: "SampleFormat",
+                value: "floating point",
+            }
+        );
+        ensure!(
+            bins > 0,
+            UnsupportedOtherSnafu {
+                name: "histogram bins",
+                value: "0",
+            }
+        );
+
+        let signed = self.pixel_representation == PixelRepresentation::Signed;
+        let data = self.realign_samples(self.frame_data(frame)?, UnusedBitsOption::Mask)?;
+        let counts = match self.bits_allocated {
+            8 => histogram::stored_value_counts(8, signed, &*data, None),
+            16 => histogram::stored_value_counts(
+                self.bits_stored,
+                signed,
+                &bytes_to_vec_u16(&data)[..],
+                None,
+            ),
+            _ => InvalidBitsAllocatedSnafu.fail()?,
+        };
+        let modality = self.modality_for_frame(frame, &ModalityLutOption::Default)?;
+        Ok(Histogram::from_stored_value_counts(
+            &counts,
+            signed,
+            bins,
+            |v| modality.apply(v),
+        ))
+    }
+
+    /// Retrieve the palette color lookup tables
+    /// which the stored values should be mapped through
+    /// according to the given options,
+    /// or `None` if the stored values are to be converted as they are.
+    fn palette_to_apply(&self, options: &ConvertOptions) -> Result<Option<&PaletteColorLut>> {
+        if self.photometric_interpretation.is_monochrome() {
+            // a supplemental palette applies to all values only on request
+            return Ok(self
+                .palette
+                .as_ref()
+                .filter(|_| options.palette == PaletteOption::ApplyAll));
+        }
+        if self.photometric_interpretation != PhotometricInterpretation::PaletteColor
+            || options.palette == PaletteOption::Indices
+        {
+            return Ok(None);
+        }
+        match &self.palette {
+            Some(palette) => Ok(Some(palette)),
+            None => Err(attribute::missing_required(
+                AttributeName::RedPaletteColorLookupTableDescriptor,
+            ))
+            .context(GetAttributeSnafu)
+            .map_err(Error::from),
+        }
+    }
+
+    /// Retrieve the supplemental palette
+    /// which the stored values from its first mapped value onwards
+    /// should be mapped through when converting to an image,
+    /// or `None` if there is no supplemental palette to merge
+    /// according to the given options.
+    #[cfg(feature = "image")]
+    fn supplemental_palette_to_apply(&self, options: &ConvertOptions) -> Option<&PaletteColorLut> {
+        if !self.photometric_interpretation.is_monochrome()
+            || self.samples_per_pixel != 1
+            || options.palette != PaletteOption::Apply
+        {
+            return None;
+        }
+        self.palette.as_ref()
+    }
+
+    /// Retrieve the pixel padding values
+    /// to leave out of a min-max normalization
+    /// according to the given option.
+    fn padding_to_exclude(&self, padding: PaddingOption) -> Option<PixelPadding> {
+        match padding {
+            PaddingOption::Keep => None,
+            PaddingOption::Exclude | PaddingOption::Mask => self.pixel_padding,
+        }
+    }
+
+    /// Map the pixel padding values in a lookup table
+    /// to the lowest output value if requested by the given option.
+    ///
+    /// This should only be done
+    /// if the lookup table applies a VOI LUT transformation.
+    fn mask_padding<T>(&self, lut: Lut<T>, padding: PaddingOption) -> Lut<T>
+    where
+        T: NumCast + Send + Sync + Copy + 'static,
+    {
+        match (padding, self.pixel_padding) {
+            (PaddingOption::Mask, Some(pixel_padding)) => lut.mask_padding(
+                self.pixel_representation == PixelRepresentation::Signed,
+                pixel_padding,
+            ),
+            _ => lut,
+        }
+    }
+
+    /// Retrieve the number of samples per pixel
+    /// of the pixel data once converted with the given options.
+    #[cfg(feature = "ndarray")]
+    fn converted_samples_per_pixel(&self, options: &ConvertOptions) -> Result<u16> {
+        Ok(match self.palette_to_apply(options)? {
+            Some(_) => 3,
+            None => self.samples_per_pixel,
+        })
+    }
+
+    /// Interpret the stored values of a frame as palette indices.
+    fn palette_indices(&self, data: &[u8]) -> Result<Vec<i32>> {
+        let signed = self.pixel_representation == PixelRepresentation::Signed;
+        match (self.bits_allocated, signed) {
+            (8, false) => Ok(data.iter().map(|v| i32::from(*v)).collect()),
+            (8, true) => Ok(data.iter().map(|v| i32::from(*v as i8)).collect()),
+            (16, false) => Ok(bytes_to_vec_u16(data).into_iter().map(i32::from).collect()),
+            (16, true) => Ok(bytes_to_vec_u16(data)
+                .into_iter()
+                .map(|v| i32::from(v as i16))
+                .collect()),
+            _ => InvalidBitsAllocatedSnafu.fail()?,
+        }
+    }
+
+    /// Move the stored value of each sample in the given pixel data
+    /// to the least significant bits,
+    /// as the _High Bit_ is not always `bits_stored - 1`,
+    /// and clear the bits of each sample outside of the bits stored,
+    /// extending the sign if the samples are signed.
+    ///
+    /// The bits above the high bit are kept if requested by the given option.
+    /// Floating point samples are left as is.
+    ///
+    /// Fails if the high bit does not fit the bits stored and allocated.
+    fn realign_samples<'d>(
+        &self,
+        data: &'d [u8],
+        option: UnusedBitsOption,
+    ) -> Result<Cow<'d, [u8]>> {
+        if self.sample_format.is_float() {
+            return Ok(Cow::Borrowed(data));
+        }
+        let bits_allocated = u32::from(self.bits_allocated);
+        let bits_stored = u32::from(self.bits_stored);
+        let high_bit = u32::from(self.high_bit);
+        ensure!(
+            bits_stored > 0
+                && bits_stored <= bits_allocated
+                && high_bit < bits_allocated
+                && high_bit + 1 >= bits_stored,
+            UnsupportedOtherSnafu {
+                name: "HighBit",
+                value: format!(
+                    "{} (with {} bits stored in {} bits allocated)",
+                    self.high_bit, self.bits_stored, self.bits_allocated
+                ),
+            }
+        );
+
+        // shift the high bit up to the most significant bit,
+        // then back down along with the bits below the stored value
+        let low_bit = high_bit + 1 - bits_stored;
+        let (up, down) = match option {
+            UnusedBitsOption::Mask => (bits_allocated - 1 - high_bit, bits_allocated - bits_stored),
+            UnusedBitsOption::Keep => (0, low_bit),
+        };
+        if up == 0 && down == 0 {
+            return Ok(Cow::Borrowed(data));
+        }
+        let signed = self.pixel_representation == PixelRepresentation::Signed;
+
+        Ok(match bits_allocated {
+            8 => Cow::Owned(
+                data.iter()
+                    .map(|v| {
+                        if signed {
+                            (((*v << up) as i8) >> down) as u8
+                        } else {
+                            (*v << up) >> down
+                        }
+                    })
+                    .collect(),
+            ),
+            16 => {
+                let mut samples = bytes_to_vec_u16(data);
+                for v in &mut samples {
+                    *v = if signed {
+                        (((*v << up) as i16) >> down) as u16
+                    } else {
+                        (*v << up) >> down
+                    };
+                }
+                let mut out = vec![0; data.len()];
+                NativeEndian::write_u16_into(&samples, &mut out);
+                Cow::Owned(out)
+            }
+            32 => {
+                let mut samples = bytes_to_vec_u32(data);
+                for v in &mut samples {
+                    *v = if signed {
+                        (((*v << up) as i32) >> down) as u32
+                    } else {
+                        (*v << up) >> down
+                    };
+                }
+                let mut out = vec![0; data.len()];
+                NativeEndian::write_u32_into(&samples, &mut out);
+                Cow::Owned(out)
+            }
+            _ => Cow::Borrowed(data),
+        })
+    }
+
     fn convert_pixel_slice<T>(
         &self,
         data: &[u8],
@@ -1317,9 +3140,14 @@ impl DecodedPixelData<'_> {
         let ConvertOptions {
             modality_lut,
             voi_lut,
-            bit_depth: _,
+            padding,
+            unused_bits,
+            ..
         } = options;
 
+        let data = self.realign_samples(data, *unused_bits)?;
+        let data = &*data;
+
         if self.samples_per_pixel > 1 && self.planar_configuration != PlanarConfiguration::Standard
         {
             // TODO #129
@@ -1330,8 +3158,24 @@ impl DecodedPixelData<'_> {
             .fail()?;
         }
 
+        if let Some(palette) = self.palette_to_apply(options)? {
+            // 3-channel RGB samples
+            let converted: Option<Vec<T>> = self
+                .palette_indices(data)?
+                .into_iter()
+                .flat_map(|v| palette.apply(v))
+                .map(T::from)
+                .collect();
+            return converted.context(InvalidDataTypeSnafu).map_err(Error::from);
+        }
+
+        if self.sample_format.is_float() || self.modality_lut_to_apply(modality_lut).is_some() {
+            return self.convert_pixel_slice_per_sample(data, frame, options);
+        }
+
         match self.bits_allocated {
-            8 => {
+            // samples of 1 bit were unpacked to one byte each
+            1 | 8 => {
                 match modality_lut {
                     ModalityLutOption::Default | ModalityLutOption::Override(_)
                         if self.photometric_interpretation.is_monochrome() =>
@@ -1348,37 +3192,49 @@ impl DecodedPixelData<'_> {
                         };
                         let signed = self.pixel_representation == PixelRepresentation::Signed;
 
-                        let lut: Lut<T> = match (voi_lut, self.window()?) {
-                            (VoiLutOption::Default | VoiLutOption::Identity, _) => {
+                        let lut: Lut<T> = match (
+                            voi_lut,
+                            self.window_for_frame(frame)?.and_then(|w| w.first()),
+                            self.voi_luts.first(),
+                        ) {
+                            (VoiLutOption::Default | VoiLutOption::Identity, _, _) => {
                                 Lut::new_rescale(8, signed, rescale)
                             }
-                            (VoiLutOption::First, Some(window)) => Lut::new_rescale_and_window(
-                                8,
-                                signed,
-                                rescale,
-                                WindowLevelTransform::new(
-                                    match self.voi_lut_function()? {
-                                        Some(lut) => {
-                                            if lut.len() > 1 {
-                                                lut[frame as usize]
-                                            } else {
-                                                lut[0]
+                            (VoiLutOption::Table, _, Some(table))
+                            | (VoiLutOption::First, None, Some(table)) => {
+                                Lut::new_rescale_and_voi_lut(8, signed, rescale, table)
+                            }
+                            (VoiLutOption::First | VoiLutOption::Table, Some(window), _) => {
+                                Lut::new_rescale_and_window(
+                                    8,
+                                    signed,
+                                    rescale,
+                                    WindowLevelTransform::new(
+                                        match self.voi_lut_function()? {
+                                            Some(lut) => {
+                                                if lut.len() > 1 {
+                                                    lut[frame as usize]
+                                                } else {
+                                                    lut[0]
+                                                }
                                             }
-                                        }
-                                        None => VoiLutFunction::Linear,
-                                    },
-                                    if window.len() > 1 {
-                                        window[frame as usize]
-                                    } else {
-                                        window[0]
-                                    },
-                                ),
-                            ),
-                            (VoiLutOption::First, None) => {
+                                            None => VoiLutFunction::Linear,
+                                        },
+                                        *window,
+                                    ),
+                                )
+                            }
+                            (VoiLutOption::First | VoiLutOption::Table, None, _) => {
                                 tracing::warn!("Could not find window level for object");
                                 Lut::new_rescale(8, signed, rescale)
                             }
-                            (VoiLutOption::Custom(window), _) => Lut::new_rescale_and_window(
+                            (VoiLutOption::Index(index), _, _) => Lut::new_rescale_and_window(
+                                8,
+                                signed,
+                                rescale,
+                                self.window_level_at(frame, *index)?,
+                            ),
+                            (VoiLutOption::Custom(window), _, _) => Lut::new_rescale_and_window(
                                 8,
                                 signed,
                                 rescale,
@@ -1396,7 +3252,7 @@ impl DecodedPixelData<'_> {
                                     *window,
                                 ),
                             ),
-                            (VoiLutOption::CustomWithFunction(window, function), _) => {
+                            (VoiLutOption::CustomWithFunction(window, function), _, _) => {
                                 Lut::new_rescale_and_window(
                                     8,
                                     signed,
@@ -1404,15 +3260,42 @@ impl DecodedPixelData<'_> {
                                     WindowLevelTransform::new(*function, *window),
                                 )
                             }
-                            (VoiLutOption::Normalize, _) => Lut::new_rescale_and_normalize(
+                            (VoiLutOption::Normalize, _, _) => Lut::new_rescale_and_normalize(
                                 8,
                                 signed,
                                 rescale,
                                 data.iter().copied(),
+                                self.padding_to_exclude(*padding),
                             ),
+                            (VoiLutOption::Percentile { low, high }, _, _) => {
+                                Lut::new_rescale_and_percentile(
+                                    8,
+                                    signed,
+                                    rescale,
+                                    data,
+                                    self.padding_to_exclude(*padding),
+                                    *low,
+                                    *high,
+                                )
+                            }
                         }
                         .context(CreateLutSnafu)?;
 
+                        // padding is only masked along with a VOI LUT transformation
+                        let rescale_only = match voi_lut {
+                            VoiLutOption::Default | VoiLutOption::Identity => true,
+                            VoiLutOption::First | VoiLutOption::Table => {
+                                self.window_for_frame(frame)?.map_or(true, |w| w.is_empty())
+                                    && self.voi_luts.is_empty()
+                            }
+                            _ => false,
+                        };
+                        let lut = if rescale_only {
+                            lut
+                        } else {
+                            self.mask_padding(lut, *padding)
+                        };
+
                         #[cfg(feature = "rayon")]
                         let out = lut.map_par_iter(data.par_iter().copied()).collect();
 
@@ -1421,6 +3304,23 @@ impl DecodedPixelData<'_> {
 
                         Ok(out)
                     }
+                    _ if self.pixel_representation == PixelRepresentation::Signed => {
+                        // signed stored values, extending the sign from the bits stored
+                        let shift = 8 - self.bits_stored.clamp(1, 8) as u32;
+                        let extend = |v: &u8| ((*v << shift) as i8) >> shift;
+
+                        #[cfg(feature = "rayon")]
+                        let converted: Result<Vec<T>, _> = data
+                            .par_iter()
+                            .map(|v| T::from(extend(v)).ok_or(snafu::NoneError))
+                            .collect();
+                        #[cfg(not(feature = "rayon"))]
+                        let converted: Result<Vec<T>, _> = data
+                            .iter()
+                            .map(|v| T::from(extend(v)).ok_or(snafu::NoneError))
+                            .collect();
+                        converted.context(InvalidDataTypeSnafu).map_err(Error::from)
+                    }
                     _ => {
                         #[cfg(feature = "rayon")]
                         // 1-channel Grayscale image
@@ -1458,60 +3358,78 @@ impl DecodedPixelData<'_> {
 
                         let signed = self.pixel_representation == PixelRepresentation::Signed;
 
-                        let lut: Lut<T> = match (voi_lut, self.window()?) {
-                            (VoiLutOption::Default | VoiLutOption::Identity, _) => {
+                        let lut: Lut<T> = match (
+                            voi_lut,
+                            self.window_for_frame(frame)?.and_then(|w| w.first()),
+                            self.voi_luts.first(),
+                        ) {
+                            (VoiLutOption::Default | VoiLutOption::Identity, _, _) => {
                                 Lut::new_rescale(self.bits_stored, signed, rescale)
                             }
-                            (VoiLutOption::First, Some(window)) => Lut::new_rescale_and_window(
-                                self.bits_stored,
-                                signed,
-                                rescale,
-                                WindowLevelTransform::new(
-                                    match self.voi_lut_function()? {
-                                        Some(lut) => {
-                                            if lut.len() > 1 {
-                                                lut[frame as usize]
-                                            } else {
-                                                lut[0]
-                                            }
-                                        }
-                                        None => VoiLutFunction::Linear,
-                                    },
-                                    if window.len() > 1 {
-                                        window[frame as usize]
-                                    } else {
-                                        window[0]
-                                    },
-                                ),
-                            ),
-                            (VoiLutOption::First, None) => {
-                                tracing::warn!("Could not find window level for object");
-                                Lut::new_rescale_and_normalize(
+                            (VoiLutOption::Table, _, Some(table))
+                            | (VoiLutOption::First, None, Some(table)) => {
+                                Lut::new_rescale_and_voi_lut(
                                     self.bits_stored,
                                     signed,
                                     rescale,
-                                    samples.iter().copied(),
+                                    table,
                                 )
                             }
-                            (VoiLutOption::Custom(window), _) => Lut::new_rescale_and_window(
-                                self.bits_stored,
-                                signed,
-                                rescale,
-                                WindowLevelTransform::new(
-                                    match self.voi_lut_function()? {
-                                        Some(lut) => {
-                                            if lut.len() > 1 {
-                                                lut[frame as usize]
-                                            } else {
-                                                lut[0]
-                                            }
-                                        }
-                                        None => VoiLutFunction::Linear,
+                            (VoiLutOption::First | VoiLutOption::Table, Some(window), _) => {
+                                Lut::new_rescale_and_window(
+                                    self.bits_stored,
+                                    signed,
+                                    rescale,
+                                    WindowLevelTransform::new(
+                                        match self.voi_lut_function()? {
+                                            Some(lut) => {
+                                                if lut.len() > 1 {
+                                                    lut[frame as usize]
+                                                } else {
+                                                    lut[0]
+                                                }
+                                            }
+                                            None => VoiLutFunction::Linear,
+                                        },
+                                        *window,
+                                    ),
+                                )
+                            }
+                            (VoiLutOption::First | VoiLutOption::Table, None, _) => {
+                                tracing::warn!("Could not find window level for object");
+                                Lut::new_rescale_and_normalize(
+                                    self.bits_stored,
+                                    signed,
+                                    rescale,
+                                    samples.iter().copied(),
+                                    self.padding_to_exclude(*padding),
+                                )
+                            }
+                            (VoiLutOption::Index(index), _, _) => Lut::new_rescale_and_window(
+                                self.bits_stored,
+                                signed,
+                                rescale,
+                                self.window_level_at(frame, *index)?,
+                            ),
+                            (VoiLutOption::Custom(window), _, _) => Lut::new_rescale_and_window(
+                                self.bits_stored,
+                                signed,
+                                rescale,
+                                WindowLevelTransform::new(
+                                    match self.voi_lut_function()? {
+                                        Some(lut) => {
+                                            if lut.len() > 1 {
+                                                lut[frame as usize]
+                                            } else {
+                                                lut[0]
+                                            }
+                                        }
+                                        None => VoiLutFunction::Linear,
                                     },
                                     *window,
                                 ),
                             ),
-                            (VoiLutOption::CustomWithFunction(window, function), _) => {
+                            (VoiLutOption::CustomWithFunction(window, function), _, _) => {
                                 Lut::new_rescale_and_window(
                                     self.bits_stored,
                                     signed,
@@ -1519,15 +3437,35 @@ impl DecodedPixelData<'_> {
                                     WindowLevelTransform::new(*function, *window),
                                 )
                             }
-                            (VoiLutOption::Normalize, _) => Lut::new_rescale_and_normalize(
+                            (VoiLutOption::Normalize, _, _) => Lut::new_rescale_and_normalize(
                                 self.bits_stored,
                                 signed,
                                 rescale,
                                 samples.iter().copied(),
+                                self.padding_to_exclude(*padding),
                             ),
+                            (VoiLutOption::Percentile { low, high }, _, _) => {
+                                Lut::new_rescale_and_percentile(
+                                    self.bits_stored,
+                                    signed,
+                                    rescale,
+                                    &samples[..],
+                                    self.padding_to_exclude(*padding),
+                                    *low,
+                                    *high,
+                                )
+                            }
                         }
                         .context(CreateLutSnafu)?;
 
+                        // padding is only masked along with a VOI LUT transformation
+                        let lut =
+                            if matches!(voi_lut, VoiLutOption::Default | VoiLutOption::Identity) {
+                                lut
+                            } else {
+                                self.mask_padding(lut, *padding)
+                            };
+
                         #[cfg(feature = "rayon")]
                         {
                             Ok(lut.map_par_iter(samples.into_par_iter()).collect())
@@ -1557,20 +3495,24 @@ impl DecodedPixelData<'_> {
                                     .collect();
                                 converted.context(InvalidDataTypeSnafu).map_err(Error::from)
                             }
-                            // Signed 16 bit 2s complement representation
+                            // Signed 16 bit 2s complement representation,
+                            // provided as is without any offset
                             PixelRepresentation::Signed => {
                                 let mut signed_buffer = vec![0; data.len() / 2];
                                 NativeEndian::read_i16_into(data, &mut signed_buffer);
+                                // extend the sign from the bits stored
+                                let shift = 16 - self.bits_stored.clamp(1, 16) as u32;
+                                let extend = |v: &i16| (*v << shift) >> shift;
 
                                 #[cfg(feature = "rayon")]
                                 let converted: Result<Vec<T>, _> = signed_buffer
                                     .par_iter()
-                                    .map(|v| T::from(*v).ok_or(snafu::NoneError))
+                                    .map(|v| T::from(extend(v)).ok_or(snafu::NoneError))
                                     .collect();
                                 #[cfg(not(feature = "rayon"))]
                                 let converted: Result<Vec<T>, _> = signed_buffer
                                     .iter()
-                                    .map(|v| T::from(*v).ok_or(snafu::NoneError))
+                                    .map(|v| T::from(extend(v)).ok_or(snafu::NoneError))
                                     .collect();
                                 converted.context(InvalidDataTypeSnafu).map_err(Error::from)
                             }
@@ -1578,10 +3520,261 @@ impl DecodedPixelData<'_> {
                     }
                 }
             }
+            32 => self.convert_pixel_slice_per_sample(data, frame, options),
             _ => InvalidBitsAllocatedSnafu.fail()?,
         }
     }
 
+    /// Convert a frame of 32-bit or floating point samples,
+    /// or of samples mapped through a tabular Modality LUT.
+    ///
+    /// Since a lookup table over all possible values is not feasible
+    /// (or not needed, in the case of a tabular Modality LUT),
+    /// the Modality LUT and VOI LUT transformations
+    /// are applied to each sample individually.
+    /// Floating point samples are only rescaled by default,
+    /// and are otherwise provided as is.
+    fn convert_pixel_slice_per_sample<T>(
+        &self,
+        data: &[u8],
+        frame: u32,
+        options: &ConvertOptions,
+    ) -> Result<Vec<T>>
+    where
+        T: NumCast + Send + Sync + Copy + 'static,
+    {
+        let ConvertOptions {
+            modality_lut,
+            voi_lut,
+            padding,
+            ..
+        } = options;
+
+        let samples = self.samples_as_f64(data);
+
+        let transform: Box<dyn Fn(f64) -> f64 + Send + Sync + '_> = match modality_lut {
+            ModalityLutOption::Default | ModalityLutOption::Override(_)
+                if self.photometric_interpretation.is_monochrome() =>
+            {
+                let modality = self.modality_for_frame(frame, modality_lut)?;
+                // same output range as the lookup tables for this bit depth
+                let y_max =
+                    ((1_u64 << u32::from(self.bits_stored).next_power_of_two().min(32)) - 1) as f64;
+                let voi_lut = match voi_lut {
+                    VoiLutOption::Default => &VoiLutOption::Identity,
+                    voi_lut => voi_lut,
+                };
+                self.sample_transform(frame, modality, voi_lut, *padding, &samples, y_max)?
+            }
+            // no transformations
+            _ => Box::new(|v: f64| v),
+        };
+
+        #[cfg(feature = "rayon")]
+        let converted: Result<Vec<T>, _> = samples
+            .par_iter()
+            .map(|v| T::from(transform(*v)).ok_or(snafu::NoneError))
+            .collect();
+        #[cfg(not(feature = "rayon"))]
+        let converted: Result<Vec<T>, _> = samples
+            .iter()
+            .map(|v| T::from(transform(*v)).ok_or(snafu::NoneError))
+            .collect();
+        converted.context(InvalidDataTypeSnafu).map_err(Error::from)
+    }
+
+    /// Interpret the raw bytes of a frame as double precision sample values.
+    ///
+    /// As in the lookup tables,
+    /// integer samples are reduced to their bits stored,
+    /// which are interpreted as signed if the pixel representation is signed.
+    fn samples_as_f64(&self, data: &[u8]) -> Vec<f64> {
+        let raw = match (self.sample_format, self.bits_allocated) {
+            (SampleFormat::Float32, _) => {
+                return bytes_to_vec_f32(data).into_iter().map(f64::from).collect()
+            }
+            (SampleFormat::Float64, _) => return bytes_to_vec_f64(data),
+            // samples of 1 bit were unpacked to one byte each
+            (_, 1 | 8) => data.iter().map(|v| u32::from(*v)).collect(),
+            (_, 16) => bytes_to_vec_u16(data).into_iter().map(u32::from).collect(),
+            _ => bytes_to_vec_u32(data),
+        };
+        let shift = 32 - u32::from(self.bits_stored.clamp(1, 32));
+        if self.sample_format == SampleFormat::Signed {
+            raw.into_iter()
+                .map(|v| f64::from(((v << shift) as i32) >> shift))
+                .collect()
+        } else {
+            raw.into_iter()
+                .map(|v| f64::from((v << shift) >> shift))
+                .collect()
+        }
+    }
+
+    /// Retrieve the tabular Modality LUT to apply with the given option,
+    /// which is only the case by default and on monochrome pixel data.
+    fn modality_lut_to_apply(&self, modality_lut: &ModalityLutOption) -> Option<&ModalityLut> {
+        match modality_lut {
+            ModalityLutOption::Default if self.photometric_interpretation.is_monochrome() => {
+                self.modality_lut.as_ref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolve the Modality LUT transformation to apply to the given frame:
+    /// the rescale parameters given in the options,
+    /// or else the tabular Modality LUT if defined,
+    /// or else the rescale parameters of the frame.
+    ///
+    /// Per PS3.3 C.11.1,
+    /// the Modality LUT Sequence takes precedence
+    /// over the rescale attributes if both are present.
+    fn modality_for_frame(
+        &self,
+        frame: u32,
+        modality_lut: &ModalityLutOption,
+    ) -> Result<ModalityTransform<'_>> {
+        if let Some(table) = self.modality_lut_to_apply(modality_lut) {
+            return Ok(ModalityTransform::Table(table));
+        }
+        let default = self.rescale()?;
+        Ok(ModalityTransform::Rescale(
+            if let ModalityLutOption::Override(rescale) = modality_lut {
+                *rescale
+            } else if default.len() > 1 {
+                default[frame as usize]
+            } else {
+                default[0]
+            },
+        ))
+    }
+
+    /// Build the function which applies the given Modality LUT
+    /// and VOI LUT transformations to a single sample,
+    /// with an output range of `0..=y_max` if a VOI LUT is applied.
+    ///
+    /// The samples of the frame are used
+    /// to normalize them into the output range
+    /// or to find the requested percentiles,
+    /// or if no VOI LUT is available,
+    /// leaving out pixel padding values according to `padding`.
+    /// [`VoiLutOption::Default`] is taken as [`VoiLutOption::Identity`].
+    fn sample_transform<'s>(
+        &'s self,
+        frame: u32,
+        modality: ModalityTransform<'s>,
+        voi_lut: &VoiLutOption,
+        padding: PaddingOption,
+        samples: &[f64],
+        y_max: f64,
+    ) -> Result<Box<dyn Fn(f64) -> f64 + Send + Sync + 's>> {
+        let voi_lut_function = || self.voi_lut_function_for_frame(frame);
+        let excluded = self.padding_to_exclude(padding);
+        let rescaled = || {
+            samples
+                .iter()
+                .filter(|v| !excluded.map_or(false, |padding| padding.contains(**v)))
+                .map(|v| modality.apply(*v))
+                .filter(|v| v.is_finite())
+        };
+        let window = |min: f64, max: f64| {
+            if self.sample_format.is_float() {
+                // span the exact range of the samples,
+                // which may be narrower than 1
+                WindowLevelTransform::new(
+                    VoiLutFunction::LinearExact,
+                    WindowLevel {
+                        width: max - min,
+                        center: (min + max) / 2.,
+                    },
+                )
+            } else {
+                WindowLevelTransform::linear(WindowLevel {
+                    width: max - min + 1.,
+                    center: (min + max) / 2.,
+                })
+            }
+        };
+        let normalize = || {
+            let (min, max) = rescaled().fold((f64::MAX, f64::MIN), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+            window(min, max)
+        };
+        let percentile = |low: f32, high: f32| {
+            let mut values: Vec<f64> = rescaled().collect();
+            if values.is_empty() {
+                return window(0., 0.);
+            }
+            values.sort_unstable_by(f64::total_cmp);
+            // nearest-rank method
+            let at = |percentile: f32| {
+                let percentile = f64::from(percentile.clamp(0., 100.));
+                let rank = (percentile * values.len() as f64 / 100.).ceil() as usize;
+                values[rank.clamp(1, values.len()) - 1]
+            };
+            let (low, high) = (at(low), at(high));
+            window(low.min(high), low.max(high))
+        };
+
+        let transform: Box<dyn Fn(f64) -> f64 + Send + Sync + 's> = match (
+            voi_lut,
+            self.window_for_frame(frame)?.and_then(|w| w.first()),
+            self.voi_luts.first(),
+        ) {
+            (VoiLutOption::Default | VoiLutOption::Identity, _, _) => {
+                Box::new(move |v| modality.apply(v))
+            }
+            (VoiLutOption::Table, _, Some(table)) | (VoiLutOption::First, None, Some(table)) => {
+                Box::new(move |v| table.apply(modality.apply(v), y_max))
+            }
+            (VoiLutOption::First | VoiLutOption::Table, Some(window), _) => {
+                let voi = WindowLevelTransform::new(voi_lut_function()?, *window);
+                Box::new(move |v| voi.apply(modality.apply(v), y_max))
+            }
+            (VoiLutOption::First | VoiLutOption::Table, None, _) => {
+                tracing::warn!("Could not find window level for object");
+                let voi = normalize();
+                Box::new(move |v| voi.apply(modality.apply(v), y_max))
+            }
+            (VoiLutOption::Index(index), _, _) => {
+                let voi = self.window_level_at(frame, *index)?;
+                Box::new(move |v| voi.apply(modality.apply(v), y_max))
+            }
+            (VoiLutOption::Custom(window), _, _) => {
+                let voi = WindowLevelTransform::new(voi_lut_function()?, *window);
+                Box::new(move |v| voi.apply(modality.apply(v), y_max))
+            }
+            (VoiLutOption::CustomWithFunction(window, function), _, _) => {
+                let voi = WindowLevelTransform::new(*function, *window);
+                Box::new(move |v| voi.apply(modality.apply(v), y_max))
+            }
+            (VoiLutOption::Normalize, _, _) => {
+                let voi = normalize();
+                Box::new(move |v| voi.apply(modality.apply(v), y_max))
+            }
+            (VoiLutOption::Percentile { low, high }, _, _) => {
+                let voi = percentile(*low, *high);
+                Box::new(move |v| voi.apply(modality.apply(v), y_max))
+            }
+        };
+        match (padding, self.pixel_padding) {
+            (PaddingOption::Mask, Some(pixel_padding))
+                if !matches!(voi_lut, VoiLutOption::Default | VoiLutOption::Identity) =>
+            {
+                Ok(Box::new(move |v| {
+                    if pixel_padding.contains(v) {
+                        0.
+                    } else {
+                        transform(v)
+                    }
+                }))
+            }
+            _ => Ok(transform),
+        }
+    }
+
     /// Convert all of the decoded pixel data
     /// into a four dimensional array of a given type `T`.
     ///
@@ -1597,7 +3790,9 @@ impl DecodedPixelData<'_> {
     /// where `N` is the number of frames,
     /// `R` is the number of rows,
     /// `C` is the number of columns,
-    /// and `S` is the number of samples per pixel.
+    /// and `S` is the number of samples per pixel
+    /// (3 if a palette color lookup table is applied,
+    /// see [`PaletteOption`]).
     ///
     /// The default pixel data process pipeline
     /// applies only the Modality LUT function described in the object,
@@ -1629,7 +3824,9 @@ impl DecodedPixelData<'_> {
     /// where `N` is the number of frames,
     /// `R` is the number of rows,
     /// `C` is the number of columns,
-    /// and `S` is the number of samples per pixel.
+    /// and `S` is the number of samples per pixel
+    /// (3 if a palette color lookup table is applied,
+    /// see [`PaletteOption`]).
     ///
     /// The `options` value allows you to specify
     /// which transformations should be done to the pixel data
@@ -1651,7 +3848,7 @@ impl DecodedPixelData<'_> {
             self.number_of_frames as usize,
             self.rows as usize,
             self.cols as usize,
-            self.samples_per_pixel as usize,
+            self.converted_samples_per_pixel(options)? as usize,
         ];
 
         let converted = self.to_vec_with_options::<T>(options)?;
@@ -1674,7 +3871,9 @@ impl DecodedPixelData<'_> {
     /// The shape of the array will be `[R, C, S]`,
     /// where `R` is the number of rows,
     /// `C` is the number of columns,
-    /// and `S` is the number of samples per pixel.
+    /// and `S` is the number of samples per pixel
+    /// (3 if a palette color lookup table is applied,
+    /// see [`PaletteOption`]).
     ///
     /// The default pixel data process pipeline
     /// applies only the Modality LUT function described in the object,
@@ -1705,7 +3904,9 @@ impl DecodedPixelData<'_> {
     /// The shape of the array will be `[R, C, S]`,
     /// where `R` is the number of rows,
     /// `C` is the number of columns,
-    /// and `S` is the number of samples per pixel.
+    /// and `S` is the number of samples per pixel
+    /// (3 if a palette color lookup table is applied,
+    /// see [`PaletteOption`]).
     ///
     /// The `options` value allows you to specify
     /// which transformations should be done to the pixel data
@@ -1730,7 +3931,7 @@ impl DecodedPixelData<'_> {
         let shape = [
             self.rows as usize,
             self.cols as usize,
-            self.samples_per_pixel as usize,
+            self.converted_samples_per_pixel(options)? as usize,
         ];
 
         let converted = self.to_vec_frame_with_options::<T>(frame, options)?;
@@ -1763,21 +3964,131 @@ impl DecodedPixelData<'_> {
     pub fn to_owned(&self) -> DecodedPixelData<'static> {
         DecodedPixelData {
             data: Cow::Owned(self.data.to_vec()),
+            samples: self.samples.to_owned_state(),
             bits_allocated: self.bits_allocated,
             bits_stored: self.bits_stored,
             high_bit: self.high_bit,
             pixel_representation: self.pixel_representation,
+            sample_format: self.sample_format,
             photometric_interpretation: self.photometric_interpretation.clone(),
+            original_photometric_interpretation: self.original_photometric_interpretation.clone(),
             planar_configuration: self.planar_configuration,
             number_of_frames: self.number_of_frames,
             rows: self.rows,
             cols: self.cols,
             samples_per_pixel: self.samples_per_pixel,
             rescale: self.rescale.to_vec(),
+            modality_lut: self.modality_lut.clone(),
             voi_lut_function: self.voi_lut_function.clone(),
             window: self.window.clone(),
+            window_voi_lut_functions: self.window_voi_lut_functions.clone(),
+            voi_luts: self.voi_luts.clone(),
+            palette: self.palette.clone(),
+            pixel_padding: self.pixel_padding,
+            frame_order: self.frame_order.clone(),
+            enforce_frame_fg_vm_match: self.enforce_frame_fg_vm_match,
+            photometric_interpretation_mismatch: self.photometric_interpretation_mismatch.clone(),
+            value_multiplicity_mismatches: self.value_multiplicity_mismatches.clone(),
+            defaulted_attributes: self.defaulted_attributes.clone(),
+            declared_dimensions: self.declared_dimensions,
+            trailing_bytes: self.trailing_bytes,
+        }
+    }
+
+    /// Convert the decoded pixel data into a version
+    /// that is independent from the original DICOM object,
+    /// consuming it.
+    ///
+    /// Unlike [`to_owned`](Self::to_owned),
+    /// the samples and encoded fragments are moved rather than copied
+    /// if they are already owned,
+    /// which avoids doubling the memory in use for large images.
+    pub fn into_owned(self) -> DecodedPixelData<'static> {
+        DecodedPixelData {
+            data: Cow::Owned(self.data.into_owned()),
+            samples: self.samples.into_owned_state(),
+            bits_allocated: self.bits_allocated,
+            bits_stored: self.bits_stored,
+            high_bit: self.high_bit,
+            pixel_representation: self.pixel_representation,
+            sample_format: self.sample_format,
+            photometric_interpretation: self.photometric_interpretation,
+            original_photometric_interpretation: self.original_photometric_interpretation,
+            planar_configuration: self.planar_configuration,
+            number_of_frames: self.number_of_frames,
+            rows: self.rows,
+            cols: self.cols,
+            samples_per_pixel: self.samples_per_pixel,
+            rescale: self.rescale,
+            modality_lut: self.modality_lut,
+            voi_lut_function: self.voi_lut_function,
+            window: self.window,
+            window_voi_lut_functions: self.window_voi_lut_functions,
+            voi_luts: self.voi_luts,
+            palette: self.palette,
+            pixel_padding: self.pixel_padding,
+            frame_order: self.frame_order,
             enforce_frame_fg_vm_match: self.enforce_frame_fg_vm_match,
+            photometric_interpretation_mismatch: self.photometric_interpretation_mismatch,
+            value_multiplicity_mismatches: self.value_multiplicity_mismatches,
+            defaulted_attributes: self.defaulted_attributes,
+            declared_dimensions: self.declared_dimensions,
+            trailing_bytes: self.trailing_bytes,
+        }
+    }
+
+    /// Take all raw pixel data samples as bytes,
+    /// consuming the decoded pixel data.
+    ///
+    /// The bytes are only copied if they were borrowed
+    /// from the original DICOM object.
+    /// The vector is empty if the samples were not decoded
+    /// (see [`samples`](Self::samples)).
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data.into_owned()
+    }
+
+    /// Split the decoded pixel data into
+    /// all raw pixel data samples as bytes
+    /// and the remaining imaging properties,
+    /// without copying the samples if they are already owned.
+    ///
+    /// The second part retains all of the imaging attributes
+    /// (such as [`rows`](Self::rows) and [`bits_allocated`](Self::bits_allocated)),
+    /// but holds no samples,
+    /// so retrieving or converting frames from it fails.
+    pub fn into_parts(self) -> (Vec<u8>, DecodedPixelData<'static>) {
+        let mut properties = self.into_owned();
+        let data = std::mem::take(&mut properties.data).into_owned();
+        (data, properties)
+    }
+
+    /// Fail if the decoded pixel data does not satisfy the given options.
+    fn check_options(mut self, options: &DecodeOptions) -> Result<Self> {
+        // per-frame values are also checked on retrieval
+        self.enforce_frame_fg_vm_match = options.strict;
+
+        if let (true, Some(mismatch)) = (options.strict, &self.photometric_interpretation_mismatch)
+        {
+            return InconsistentPhotometricInterpretationSnafu {
+                pi: mismatch.declared.clone(),
+                spp: mismatch.samples_per_pixel,
+            }
+            .fail()?;
         }
+        if let (true, Some(mismatch)) = (options.strict, self.value_multiplicity_mismatches.first())
+        {
+            return InconsistentValueMultiplicitySnafu {
+                mismatch: mismatch.clone(),
+            }
+            .fail()?;
+        }
+        if let (true, Some(name)) = (options.strict, self.defaulted_attributes.first()) {
+            return Err(attribute::missing_required(*name))
+                .context(GetAttributeSnafu)
+                .map_err(Error::from);
+        }
+        Ok(self)
     }
 }
 
@@ -1788,6 +4099,27 @@ fn bytes_to_vec_u16(data: &[u8]) -> Vec<u16> {
     pixel_array
 }
 
+fn bytes_to_vec_u32(data: &[u8]) -> Vec<u32> {
+    debug_assert!(data.len() % 4 == 0);
+    let mut pixel_array: Vec<u32> = vec![0; data.len() / 4];
+    NativeEndian::read_u32_into(data, &mut pixel_array);
+    pixel_array
+}
+
+fn bytes_to_vec_f32(data: &[u8]) -> Vec<f32> {
+    debug_assert!(data.len() % 4 == 0);
+    let mut pixel_array: Vec<f32> = vec![0.; data.len() / 4];
+    NativeEndian::read_f32_into(data, &mut pixel_array);
+    pixel_array
+}
+
+fn bytes_to_vec_f64(data: &[u8]) -> Vec<f64> {
+    debug_assert!(data.len() % 8 == 0);
+    let mut pixel_array: Vec<f64> = vec![0.; data.len() / 8];
+    NativeEndian::read_f64_into(data, &mut pixel_array);
+    pixel_array
+}
+
 // Convert u8 pixel array from YBR_FULL or YBR_FULL_422 to RGB
 // Every pixel is replaced with an RGB value
 #[cfg(feature = "image")]
@@ -1820,6 +4152,29 @@ fn convert_colorspace_u8(i: &mut [u8]) {
     });
 }
 
+// Expand samples in groups of `Y Y CB CR` for every two pixels in a row,
+// as stored in native YBR_FULL_422, into `Y CB CR` samples for every pixel,
+// repeating the chroma samples of each group for both of its pixels
+#[cfg(feature = "image")]
+fn upsample_ybr_422<T: Copy + Default>(data: &[T], rows: usize, cols: usize) -> Vec<T> {
+    let row_len = cols + 2 * ((cols + 1) / 2);
+    let mut out = Vec::with_capacity(rows * cols * 3);
+    for row in data.chunks(row_len).take(rows) {
+        // a row with an odd number of columns ends with `Y CB CR`
+        for group in row.chunks(4) {
+            let (luma, chroma) = group.split_at(group.len().saturating_sub(2));
+            let (cb, cr) = match chroma {
+                [cb, cr] => (*cb, *cr),
+                _ => Default::default(),
+            };
+            for y in luma {
+                out.extend_from_slice(&[*y, cb, cr]);
+            }
+        }
+    }
+    out
+}
+
 #[cfg(feature = "image")]
 fn interleave<T: Copy>(data: &[T]) -> Vec<T> {
     debug_assert_eq!(data.len() % 3, 0);
@@ -1866,6 +4221,94 @@ fn convert_colorspace_u16(i: &mut [u16]) {
     });
 }
 
+/// The arrangement of the samples of a frame in Y'CbCr.
+#[cfg(feature = "image")]
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum YbrLayout {
+    /// The Y, CB and CR samples of each pixel are next to each other.
+    Interleaved,
+    /// Each component is in its own plane, with one sample per pixel.
+    Planar,
+    /// Groups of `Y Y CB CR` samples for every two pixels in a row.
+    Pairs,
+    /// Each component is in its own plane,
+    /// with the CB and CR planes subsampled horizontally,
+    /// and vertically as well if `vertical` is true.
+    Subsampled { vertical: bool },
+}
+
+// Convert the samples of a pixel array in YBR_PARTIAL_420 or YBR_PARTIAL_422
+// to RGB samples in the full range of `max`,
+// upsampling the chroma samples by replication where they are subsampled
+#[cfg(feature = "image")]
+fn convert_ybr_partial<T>(
+    samples: &[f32],
+    layout: YbrLayout,
+    rows: usize,
+    cols: usize,
+    max: f32,
+) -> Vec<T>
+where
+    T: NumCast + Copy + Default + Send + Sync,
+{
+    // the partial range is 16 to 235 for Y and 16 to 240 for CB and CR,
+    // scaled up for samples of more than 8 bits
+    let scale = (max + 1.) / 256.;
+    let luma_len = rows * cols;
+    let chroma_cols = (cols + 1) / 2;
+    let chroma_len = match layout {
+        YbrLayout::Subsampled { vertical: true } => (rows + 1) / 2 * chroma_cols,
+        _ => rows * chroma_cols,
+    };
+
+    // the indices of the Y, CB and CR samples of the pixel at the given index
+    let indices = |i: usize| -> [usize; 3] {
+        let (row, col) = (i / cols, i % cols);
+        match layout {
+            YbrLayout::Interleaved => [3 * i, 3 * i + 1, 3 * i + 2],
+            YbrLayout::Planar => [i, luma_len + i, 2 * luma_len + i],
+            YbrLayout::Pairs => {
+                let group = row * (cols + 2 * chroma_cols) + col / 2 * 4;
+                // a row with an odd number of columns ends with a single Y
+                let cb = group + usize::min(2, cols - col / 2 * 2);
+                [group + col % 2, cb, cb + 1]
+            }
+            YbrLayout::Subsampled { vertical } => {
+                let chroma_row = if vertical { row / 2 } else { row };
+                let j = chroma_row * chroma_cols + col / 2;
+                [i, luma_len + j, luma_len + chroma_len + j]
+            }
+        }
+    };
+
+    let mut out = vec![T::default(); luma_len * 3];
+
+    #[cfg(feature = "rayon")]
+    let iter = {
+        use rayon::iter::IndexedParallelIterator;
+        out.par_chunks_mut(3).enumerate()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let iter = out.chunks_mut(3).enumerate();
+
+    iter.for_each(|(i, pixel)| {
+        let [y, cb, cr] = indices(i).map(|k| samples.get(k).copied().unwrap_or(0.));
+        let y = (y - 16. * scale) * max / (219. * scale);
+        let cb = (cb - 128. * scale) * max / (224. * scale);
+        let cr = (cr - 128. * scale) * max / (224. * scale);
+
+        let rgb = [
+            y + 1.402 * cr,
+            y - (0.114 * 1.772 / 0.587) * cb - (0.299 * 1.402 / 0.587) * cr,
+            y + 1.772 * cb,
+        ];
+        for (sample, v) in pixel.iter_mut().zip(rgb) {
+            *sample = T::from(v.round().clamp(0., max)).unwrap_or_default();
+        }
+    });
+    out
+}
+
 /// Convert the i16 vector by shifting it up,
 /// thus maintaining the order between sample values.
 #[cfg(feature = "image")]
@@ -1877,6 +4320,36 @@ fn convert_i16_to_u16(i: &[i16]) -> Vec<u16> {
     iter.map(|p| (*p as i32 + 0x8000) as u16).collect()
 }
 
+/// Check whether a DICOM object has pixel data to decode.
+///
+/// This is the case if its _Pixel Data_ attribute,
+/// or else its _Float Pixel Data_ or _Double Float Pixel Data_ attribute,
+/// is present and not empty.
+/// Objects such as structured reports,
+/// presentation states and key object selection documents
+/// have no pixel data,
+/// and fail to decode with an error
+/// for which [`Error::is_no_pixel_data`] returns `true`.
+pub fn has_pixel_data<D>(obj: &InMemDicomObject<D>) -> bool
+where
+    D: DataDictionary + Clone,
+{
+    use dicom_dictionary_std::tags;
+
+    [
+        tags::PIXEL_DATA,
+        tags::FLOAT_PIXEL_DATA,
+        tags::DOUBLE_FLOAT_PIXEL_DATA,
+    ]
+    .iter()
+    .find_map(|tag| obj.get(*tag))
+    .map_or(false, |elem| match elem.value() {
+        DicomValue::Primitive(value) => value.calculate_byte_len() > 0,
+        DicomValue::PixelSequence(seq) => seq.fragments().iter().any(|f| !f.is_empty()),
+        DicomValue::Sequence(_) => true,
+    })
+}
+
 /// Trait for objects which can be decoded into
 /// blobs of easily consumable pixel data.
 ///
@@ -1919,35 +4392,117 @@ pub trait PixelDecoder {
         let mut px = self.decode_pixel_data()?;
 
         // calculate frame offset and size
-        let frame_size = ((px.bits_allocated + 7) / 8) as usize
-            * px.samples_per_pixel as usize
-            * px.rows as usize
-            * px.cols as usize;
-        let frame_offset = frame_size * frame as usize;
+        let frame_range = px.frame_range(frame)?;
 
         // crop to frame
         match &mut px.data {
-            Cow::Owned(data) => *data = data[frame_offset..frame_offset + frame_size].to_vec(),
+            Cow::Owned(data) => *data = data[frame_range].to_vec(),
             Cow::Borrowed(data) => {
-                *data = &data[frame_offset..frame_offset + frame_size];
+                *data = &data[frame_range];
             }
         }
 
         // reset number of frames
         px.number_of_frames = 1;
+        px.frame_order = FrameOrder::stored(1);
+
+        // keep only the per-frame values of the frame of interest
+        px.rescale = narrow_to_frame(&px.rescale, frame).unwrap_or_default();
+        px.voi_lut_function = px
+            .voi_lut_function
+            .take()
+            .and_then(|inner| narrow_to_frame(&inner, frame));
+        px.window = px
+            .window
+            .take()
+            .map(|inner| WindowLevels::Shared(inner.for_frame(frame).to_vec()));
 
         Ok(px)
     }
+
+    /// Decode the full pixel data in this object
+    /// according to the given decoding options.
+    ///
+    /// The default implementation decodes the pixel data
+    /// with [`decode_pixel_data`](PixelDecoder::decode_pixel_data)
+    /// and then checks the outcome against the options.
+    fn decode_pixel_data_with_options(
+        &self,
+        options: &DecodeOptions,
+    ) -> Result<DecodedPixelData<'_>> {
+        self.decode_pixel_data()?.check_options(options)
+    }
+
+    /// Decode the pixel data of a single frame in this object
+    /// according to the given decoding options.
+    ///
+    /// The default implementation decodes the pixel data
+    /// with [`decode_pixel_data_frame`](PixelDecoder::decode_pixel_data_frame)
+    /// and then checks the outcome against the options.
+    fn decode_pixel_data_frame_with_options(
+        &self,
+        frame: u32,
+        options: &DecodeOptions,
+    ) -> Result<DecodedPixelData<'_>> {
+        self.decode_pixel_data_frame(frame)?.check_options(options)
+    }
+
+    /// Decode the full pixel data in this object,
+    /// keeping encapsulated pixel data in its encoded form
+    /// if no decoder is available for its transfer syntax.
+    ///
+    /// The imaging properties are retrieved either way,
+    /// and the encoded pixel data fragments can be obtained
+    /// through [`DecodedPixelData::samples`]
+    /// (see [`SampleState::EncodedOnly`]).
+    ///
+    /// The default implementation decodes the pixel data
+    /// with [`decode_pixel_data_with_options`](PixelDecoder::decode_pixel_data_with_options)
+    /// and [`DecodeOptions::encoded_fallback`].
+    fn decode_pixel_data_lenient(&self) -> Result<DecodedPixelData<'_>> {
+        self.decode_pixel_data_with_options(&DecodeOptions::new().encoded_fallback(true))
+    }
 }
 
 /// Aggregator of key properties for imaging data,
 /// without the pixel data proper.
 ///
-/// Currently kept private,
-/// might become part of the public API in the future.
-#[derive(Debug)]
-#[cfg(not(feature = "gdcm"))]
-pub(crate) struct ImagingProperties {
+/// These are the imaging attributes which the pixel data decoders
+/// take from the DICOM object,
+/// already validated and normalized:
+/// - a photometric interpretation
+///   which disagrees with the number of samples per pixel
+///   is resolved,
+///   and the disagreement is recorded;
+/// - per-frame attributes (rescale parameters,
+///   VOI LUT functions, and window levels)
+///   are fitted to the number of frames,
+///   recording any value multiplicity mismatch.
+///
+/// Use [`ImagingProperties::from_object`]
+/// to obtain these properties without decoding the pixel data.
+///
+/// # Example
+///
+/// ```no_run
+/// # use dicom_object::open_file;
+/// use dicom_pixeldata::ImagingProperties;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let obj = open_file("dicom.dcm")?;
+/// let props = ImagingProperties::from_object(&obj)?;
+/// println!(
+///     "{}x{}, {} frame(s), {} bits stored",
+///     props.columns(),
+///     props.rows(),
+///     props.number_of_frames(),
+///     props.bits_stored(),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct ImagingProperties {
     pub(crate) cols: u16,
     pub(crate) rows: u16,
     pub(crate) samples_per_pixel: u16,
@@ -1955,105 +4510,234 @@ pub(crate) struct ImagingProperties {
     pub(crate) bits_stored: u16,
     pub(crate) high_bit: u16,
     pub(crate) pixel_representation: PixelRepresentation,
+    pub(crate) sample_format: SampleFormat,
     pub(crate) planar_configuration: PlanarConfiguration,
     pub(crate) photometric_interpretation: PhotometricInterpretation,
     pub(crate) rescale_intercept: Vec<f64>,
     pub(crate) rescale_slope: Vec<f64>,
     pub(crate) number_of_frames: u32,
+    pub(crate) modality_lut: Option<ModalityLut>,
     pub(crate) voi_lut_function: Option<Vec<VoiLutFunction>>,
-    pub(crate) window: Option<Vec<WindowLevel>>,
+    pub(crate) window: Option<WindowLevels>,
+    pub(crate) window_voi_lut_functions: Vec<VoiLutFunction>,
+    pub(crate) voi_luts: Vec<VoiLut>,
+    pub(crate) palette: Option<PaletteColorLut>,
+    pub(crate) pixel_padding: Option<PixelPadding>,
+    pub(crate) frame_order: FrameOrder,
+    pub(crate) photometric_interpretation_mismatch: Option<PhotometricInterpretationMismatch>,
+    pub(crate) value_multiplicity_mismatches: Vec<ValueMultiplicityMismatch>,
+    pub(crate) defaulted_attributes: Vec<AttributeName>,
 }
 
-#[cfg(not(feature = "gdcm"))]
 impl ImagingProperties {
-    fn from_obj<D>(obj: &FileDicomObject<InMemDicomObject<D>>) -> Result<Self>
+    /// Collect the imaging properties of a DICOM object.
+    ///
+    /// The pixel data is neither decoded nor required to be present.
+    /// Fails if a mandatory imaging attribute is missing or invalid,
+    /// except for the following attributes,
+    /// which take a default value when missing:
+    ///
+    /// - _Samples per Pixel_ is derived from the photometric interpretation,
+    ///   or 1 if the photometric interpretation is not known;
+    /// - _Bits Stored_ is the same as _Bits Allocated_;
+    /// - _High Bit_ is one less than _Bits Stored_.
+    ///
+    /// These are recorded in
+    /// [`defaulted_attributes`](Self::defaulted_attributes),
+    /// and rejected when decoding in [strict](DecodeOptions::strict) mode.
+    /// A missing _Planar Configuration_ is always taken as 0
+    /// (interleaved samples),
+    /// since it is only required for more than one sample per pixel.
+    ///
+    /// Objects with _Float Pixel Data_ or _Double Float Pixel Data_
+    /// instead of _Pixel Data_ have floating point samples
+    /// (see [`sample_format`](Self::sample_format)),
+    /// for which _Bits Stored_, _High Bit_ and _Pixel Representation_
+    /// are not applicable and not read.
+    pub fn from_object<D>(obj: &FileDicomObject<InMemDicomObject<D>>) -> Result<Self>
     where
         D: Clone + DataDictionary,
     {
         use attribute::*;
-        use std::convert::TryFrom;
 
         let cols = cols(obj).context(GetAttributeSnafu)?;
         let rows = rows(obj).context(GetAttributeSnafu)?;
         let photometric_interpretation =
             photometric_interpretation(obj).context(GetAttributeSnafu)?;
-        let samples_per_pixel = samples_per_pixel(obj).context(GetAttributeSnafu)?;
+        let mut defaulted_attributes = Vec::new();
+        let samples_per_pixel =
+            or_default(samples_per_pixel(obj), &mut defaulted_attributes, || {
+                photometric_interpretation.samples_per_pixel().unwrap_or(1)
+            })?;
+        // trust the number of samples per pixel
+        // in case the photometric interpretation disagrees with it
+        let photometric_interpretation_mismatch = PhotometricInterpretationMismatch::check(
+            &photometric_interpretation,
+            samples_per_pixel,
+        );
+        let photometric_interpretation = match &photometric_interpretation_mismatch {
+            Some(mismatch) => {
+                tracing::warn!("{}", mismatch);
+                mismatch.resolved.clone()
+            }
+            None => photometric_interpretation,
+        };
         let planar_configuration = planar_configuration(obj).context(GetAttributeSnafu)?;
-        let bits_allocated = bits_allocated(obj).context(GetAttributeSnafu)?;
-        let bits_stored = bits_stored(obj).context(GetAttributeSnafu)?;
-        let high_bit = high_bit(obj).context(GetAttributeSnafu)?;
-        let pixel_representation = pixel_representation(obj).context(GetAttributeSnafu)?;
-        let rescale_intercept = rescale_intercept(obj);
-        let rescale_slope = rescale_slope(obj);
+        let mut bits_allocated = bits_allocated(obj).context(GetAttributeSnafu)?;
+        let float_sample_format = float_sample_format(obj);
+        let (bits_stored, high_bit, pixel_representation) = match float_sample_format
+            .and_then(SampleFormat::float_bits)
+        {
+            // floating point samples take up all of the bits allocated,
+            // and these attributes are not present
+            Some(float_bits) => {
+                if bits_allocated != float_bits {
+                    tracing::warn!(
+                        "Expected {} bits allocated for floating point pixel data, but found {}",
+                        float_bits,
+                        bits_allocated
+                    );
+                    bits_allocated = float_bits;
+                }
+                (
+                    bits_allocated,
+                    bits_allocated - 1,
+                    PixelRepresentation::Signed,
+                )
+            }
+            None => {
+                let bits_stored = or_default(bits_stored(obj), &mut defaulted_attributes, || {
+                    bits_allocated
+                })?;
+                let high_bit = or_default(high_bit(obj), &mut defaulted_attributes, || {
+                    bits_stored.saturating_sub(1)
+                })?;
+                let pixel_representation = pixel_representation(obj).context(GetAttributeSnafu)?;
+                (bits_stored, high_bit, pixel_representation)
+            }
+        };
+        let sample_format = float_sample_format.unwrap_or_else(|| pixel_representation.into());
+        let mut rescale_intercept = rescale_intercept(obj);
+        let mut rescale_slope = rescale_slope(obj);
         let number_of_frames = number_of_frames(obj).context(GetAttributeSnafu)?;
         let voi_lut_function = voi_lut_function(obj).context(GetAttributeSnafu)?;
-        let voi_lut_function: Option<Vec<VoiLutFunction>> = voi_lut_function.and_then(|fns| {
+        let mut voi_lut_function: Option<Vec<VoiLutFunction>> = voi_lut_function.and_then(|fns| {
             fns.iter()
                 .map(|v| VoiLutFunction::try_from((*v).as_str()).ok())
                 .collect()
         });
+        let window_voi_lut_functions: Vec<VoiLutFunction> = window_voi_lut_functions(obj)
+            .and_then(|fns| {
+                fns.iter()
+                    .map(|v| VoiLutFunction::try_from(v.as_str()).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
 
-        ensure!(
-            rescale_intercept.len() == rescale_slope.len(),
-            LengthMismatchRescaleSnafu {
-                slope_vm: rescale_slope.len() as u32,
-                intercept_vm: rescale_intercept.len() as u32,
-            }
-        );
+        // per-frame values are fitted to the number of frames,
+        // single values apply to all frames
+        let nr_frames = number_of_frames as usize;
+        let mut value_multiplicity_mismatches = Vec::new();
 
-        let window = if let Some(wcs) = window_center(obj) {
-            let width = window_width(obj);
-            if let Some(wws) = width {
-                ensure!(
-                    wcs.len() == wws.len(),
-                    LengthMismatchWindowLevelSnafu {
-                        wc_vm: wcs.len() as u32,
-                        ww_vm: wws.len() as u32,
-                    }
-                );
-                Some(
+        value_multiplicity_mismatches.extend(ValueMultiplicityMismatch::fit(
+            &mut rescale_intercept,
+            nr_frames,
+            AttributeName::RescaleIntercept,
+        ));
+        value_multiplicity_mismatches.extend(ValueMultiplicityMismatch::fit(
+            &mut rescale_slope,
+            nr_frames,
+            AttributeName::RescaleSlope,
+        ));
+        broadcast(&mut rescale_intercept, rescale_slope.len());
+        broadcast(&mut rescale_slope, rescale_intercept.len());
+
+        if let Some(voi_lut_function) = &mut voi_lut_function {
+            value_multiplicity_mismatches.extend(ValueMultiplicityMismatch::fit(
+                voi_lut_function,
+                nr_frames,
+                AttributeName::VoiLutFunction,
+            ));
+        }
+
+        let window = match (window_center(obj), window_width(obj)) {
+            (Some(FrameValues::Shared(wcs)), Some(FrameValues::Shared(wws))) => {
+                Some(WindowLevels::Shared(zip_window_levels(
+                    wcs,
+                    wws,
+                    &mut value_multiplicity_mismatches,
+                )))
+            }
+            (Some(wcs), Some(wws)) => {
+                let mut wcs = wcs.into_per_frame();
+                let mut wws = wws.into_per_frame();
+                value_multiplicity_mismatches.extend(ValueMultiplicityMismatch::fit(
+                    &mut wcs,
+                    nr_frames,
+                    AttributeName::WindowCenter,
+                ));
+                value_multiplicity_mismatches.extend(ValueMultiplicityMismatch::fit(
+                    &mut wws,
+                    nr_frames,
+                    AttributeName::WindowWidth,
+                ));
+                broadcast(&mut wcs, wws.len());
+                broadcast(&mut wws, wcs.len());
+                Some(WindowLevels::PerFrame(
                     zip(wcs, wws)
-                        .map(|(wc, ww)| WindowLevel {
-                            center: wc,
-                            width: ww,
+                        .map(|(wc, ww)| {
+                            zip_window_levels(wc, ww, &mut value_multiplicity_mismatches)
                         })
                         .collect(),
-                )
-            } else {
-                None
+                ))
             }
-        } else {
-            None
+            _ => None,
         };
 
-        Ok(Self {
-            cols,
-            rows,
-            samples_per_pixel,
-            bits_allocated,
-            bits_stored,
-            high_bit,
-            pixel_representation,
-            planar_configuration,
-            photometric_interpretation,
-            rescale_intercept,
-            rescale_slope,
-            number_of_frames,
-            voi_lut_function,
-            window,
-        })
-    }
-}
+        // a malformed Modality LUT should not prevent decoding,
+        // since the rescale parameters can be used instead
+        let modality_lut =
+            modality_lut_sequence(obj, pixel_representation == PixelRepresentation::Signed)
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Ignoring invalid Modality LUT Sequence: {}", e);
+                    None
+                });
+
+        // a malformed VOI LUT should not prevent decoding,
+        // since the window levels can be used instead
+        let voi_luts = voi_lut_sequence(obj, pixel_representation == PixelRepresentation::Signed)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid VOI LUT Sequence: {}", e);
+                Vec::new()
+            });
 
-#[cfg(not(feature = "gdcm"))]
-impl<D> PixelDecoder for FileDicomObject<InMemDicomObject<D>>
-where
-    D: DataDictionary + Clone,
-{
-    fn decode_pixel_data(&self) -> Result<DecodedPixelData> {
-        let pixel_data = attribute::pixel_data(self).context(GetAttributeSnafu)?;
+        // a missing or malformed palette should not prevent decoding,
+        // since the stored values can still be retrieved as indices
+        let palette = if photometric_interpretation == PhotometricInterpretation::PaletteColor {
+            palette_color_lut(obj, pixel_representation == PixelRepresentation::Signed)
+                .map_err(|e| tracing::warn!("Ignoring invalid palette color lookup table: {}", e))
+                .ok()
+        } else if photometric_interpretation.is_monochrome()
+            && obj
+                .get(dicom_dictionary_std::tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR)
+                .is_some()
+        {
+            // supplemental palette for the upper range of stored values
+            palette_color_lut(obj, pixel_representation == PixelRepresentation::Signed)
+                .map_err(|e| tracing::warn!("Ignoring invalid supplemental palette: {}", e))
+                .ok()
+        } else {
+            None
+        };
+
+        // padding values do not apply to floating point samples
+        let pixel_padding = if sample_format.is_float() {
+            None
+        } else {
+            pixel_padding(obj, pixel_representation == PixelRepresentation::Signed)
+        };
 
-        let ImagingProperties {
+        Ok(Self {
             cols,
             rows,
             samples_per_pixel,
@@ -2061,466 +4745,3554 @@ where
             bits_stored,
             high_bit,
             pixel_representation,
+            sample_format,
             planar_configuration,
             photometric_interpretation,
             rescale_intercept,
             rescale_slope,
             number_of_frames,
+            modality_lut,
             voi_lut_function,
             window,
-        } = ImagingProperties::from_obj(self)?;
+            window_voi_lut_functions,
+            voi_luts,
+            palette,
+            pixel_padding,
+            frame_order: order::frame_order(obj),
+            photometric_interpretation_mismatch,
+            value_multiplicity_mismatches,
+            defaulted_attributes,
+        })
+    }
 
-        let transfer_syntax = &self.meta().transfer_syntax;
-        let ts = TransferSyntaxRegistry
-            .get(transfer_syntax)
-            .with_context(|| UnknownTransferSyntaxSnafu {
-                ts_uid: transfer_syntax,
-            })?;
+    /// Retrieve the number of rows.
+    #[inline]
+    pub fn rows(&self) -> u16 {
+        self.rows
+    }
 
-        if !ts.can_decode_all() {
-            return UnsupportedTransferSyntaxSnafu {
-                ts: transfer_syntax,
-            }
-            .fail()?;
-        }
+    /// Retrieve the number of columns.
+    #[inline]
+    pub fn columns(&self) -> u16 {
+        self.cols
+    }
 
-        let rescale = zip(&rescale_intercept, &rescale_slope)
-            .map(|(intercept, slope)| Rescale {
-                intercept: *intercept,
-                slope: *slope,
-            })
-            .collect();
+    /// Retrieve the number of samples per pixel.
+    #[inline]
+    pub fn samples_per_pixel(&self) -> u16 {
+        self.samples_per_pixel
+    }
 
-        // Try decoding it using a registered pixel data decoder
-        if let Codec::EncapsulatedPixelData(Some(decoder), _) = ts.codec() {
-            let mut data: Vec<u8> = Vec::new();
-            (*decoder)
-                .decode(self, &mut data)
-                .context(DecodePixelDataSnafu)?;
+    /// Retrieve the number of bits allocated for each sample.
+    #[inline]
+    pub fn bits_allocated(&self) -> u16 {
+        self.bits_allocated
+    }
 
-            // pixels are already interpreted,
-            // set new photometric interpretation if necessary
-            let new_pi = match samples_per_pixel {
-                3 => PhotometricInterpretation::Rgb,
-                _ => photometric_interpretation,
-            };
+    /// Retrieve the number of bits effectively used for each sample.
+    #[inline]
+    pub fn bits_stored(&self) -> u16 {
+        self.bits_stored
+    }
 
-            return Ok(DecodedPixelData {
-                data: Cow::from(data),
-                cols: cols.into(),
-                rows: rows.into(),
-                number_of_frames,
-                photometric_interpretation: new_pi,
-                samples_per_pixel,
-                planar_configuration: PlanarConfiguration::Standard,
-                bits_allocated,
-                bits_stored,
-                high_bit,
-                pixel_representation,
-                rescale,
-                voi_lut_function,
-                window,
-                enforce_frame_fg_vm_match: false,
-            });
-        }
+    /// Retrieve the high bit index of each sample.
+    #[inline]
+    pub fn high_bit(&self) -> u16 {
+        self.high_bit
+    }
 
-        let decoded_pixel_data = match pixel_data.value() {
-            DicomValue::PixelSequence(v) => {
-                // Return all fragments concatenated
-                // (should only happen for Encapsulated Uncompressed)
-                v.fragments().iter().flatten().copied().collect()
-            }
-            DicomValue::Primitive(p) => {
-                // Non-encoded, just return the pixel data for all frames
-                p.to_bytes().to_vec()
-            }
-            DicomValue::Sequence(..) => InvalidPixelDataSnafu.fail()?,
-        };
+    /// Retrieve the pixel representation.
+    #[inline]
+    pub fn pixel_representation(&self) -> PixelRepresentation {
+        self.pixel_representation
+    }
 
-        Ok(DecodedPixelData {
-            data: Cow::from(decoded_pixel_data),
-            cols: cols.into(),
-            rows: rows.into(),
-            number_of_frames,
-            photometric_interpretation,
-            samples_per_pixel,
-            planar_configuration,
-            bits_allocated,
-            bits_stored,
-            high_bit,
-            pixel_representation,
-            rescale,
-            voi_lut_function,
-            window,
-            enforce_frame_fg_vm_match: false,
-        })
+    /// Retrieve the numeric format of the pixel data samples.
+    ///
+    /// Floating point samples come from
+    /// _Float Pixel Data_ or _Double Float Pixel Data_,
+    /// which are used when the object has no _Pixel Data_.
+    #[inline]
+    pub fn sample_format(&self) -> SampleFormat {
+        self.sample_format
     }
 
-    fn decode_pixel_data_frame(&self, frame: u32) -> Result<DecodedPixelData<'_>> {
-        let pixel_data = attribute::pixel_data(self).context(GetAttributeSnafu)?;
+    /// Retrieve the planar configuration.
+    ///
+    /// The value returned is only meaningful for
+    /// images with more than 1 sample per pixel.
+    #[inline]
+    pub fn planar_configuration(&self) -> PlanarConfiguration {
+        self.planar_configuration
+    }
 
-        let ImagingProperties {
-            cols,
-            rows,
-            samples_per_pixel,
-            bits_allocated,
-            bits_stored,
-            high_bit,
-            pixel_representation,
-            planar_configuration,
-            photometric_interpretation,
-            rescale_intercept,
-            rescale_slope,
-            number_of_frames,
-            voi_lut_function,
-            window,
-        } = ImagingProperties::from_obj(self)?;
+    /// Retrieve the photometric interpretation.
+    ///
+    /// If the photometric interpretation declared by the object
+    /// disagreed with the number of samples per pixel,
+    /// this is the resolved photometric interpretation
+    /// (see [`photometric_interpretation_mismatch`](Self::photometric_interpretation_mismatch)).
+    #[inline]
+    pub fn photometric_interpretation(&self) -> &PhotometricInterpretation {
+        &self.photometric_interpretation
+    }
 
-        let transfer_syntax = &self.meta().transfer_syntax;
-        let ts = TransferSyntaxRegistry
-            .get(transfer_syntax)
-            .with_context(|| UnknownTransferSyntaxSnafu {
-                ts_uid: transfer_syntax,
-            })?;
+    /// Retrieve the number of frames.
+    #[inline]
+    pub fn number_of_frames(&self) -> u32 {
+        self.number_of_frames
+    }
 
-        if !ts.can_decode_all() {
-            return UnsupportedTransferSyntaxSnafu {
-                ts: transfer_syntax,
-            }
-            .fail()?;
-        }
+    /// Retrieve the rescale intercept of each frame,
+    /// or a single value applicable to all frames.
+    ///
+    /// Empty if the object does not define a rescale intercept.
+    #[inline]
+    pub fn rescale_intercept(&self) -> &[f64] {
+        &self.rescale_intercept
+    }
+
+    /// Retrieve the rescale slope of each frame,
+    /// or a single value applicable to all frames.
+    ///
+    /// Empty if the object does not define a rescale slope.
+    #[inline]
+    pub fn rescale_slope(&self) -> &[f64] {
+        &self.rescale_slope
+    }
 
-        let rescale_data = zip(&rescale_intercept, &rescale_slope)
+    /// Collect the rescale parameters of all frames,
+    /// pairing each rescale intercept with its rescale slope.
+    pub fn rescale(&self) -> Vec<Rescale> {
+        zip(&self.rescale_intercept, &self.rescale_slope)
             .map(|(intercept, slope)| Rescale {
                 intercept: *intercept,
                 slope: *slope,
             })
-            .collect::<Vec<Rescale>>();
+            .collect()
+    }
 
-        let rescale = rescale_data
-            .get(frame as usize)
-            .or(rescale_data.first())
-            .copied()
-            .map(|inner| vec![inner])
-            .unwrap_or_default();
+    /// Narrow the per-frame properties
+    /// (rescale parameters, VOI LUT functions, and window levels)
+    /// down to the values of the given frame,
+    /// falling back to the values of the first frame
+    /// if there are no values specific to that frame.
+    ///
+    /// The number of frames is kept as declared by the object.
+    pub fn for_frame(mut self, frame: u32) -> Self {
+        self.rescale_intercept =
+            narrow_to_frame(&self.rescale_intercept, frame).unwrap_or_default();
+        self.rescale_slope = narrow_to_frame(&self.rescale_slope, frame).unwrap_or_default();
+        self.voi_lut_function = self
+            .voi_lut_function
+            .take()
+            .and_then(|inner| narrow_to_frame(&inner, frame));
+        self.window = self
+            .window
+            .take()
+            .map(|inner| WindowLevels::Shared(inner.for_frame(frame).to_vec()));
+        self
+    }
 
-        let window = window
-            .and_then(|inner| {
-                inner
-                    .get(frame as usize)
-                    .or(inner.first())
-                    .copied()
-                    .map(|el| vec![el])
-            });
+    /// Retrieve the VOI LUT function of each frame,
+    /// or a single function applicable to all frames,
+    /// if defined by the object.
+    #[inline]
+    pub fn voi_lut_function(&self) -> Option<&[VoiLutFunction]> {
+        self.voi_lut_function.as_deref()
+    }
 
-        let voi_lut_function = voi_lut_function
-            .and_then(|inner| {
-                inner
-                    .get(frame as usize)
-                    .or(inner.first())
-                    .copied()
-                    .map(|el| vec![el])
-            });
+    /// Retrieve the window levels defined by the object, if any.
+    #[inline]
+    pub fn window(&self) -> Option<&WindowLevels> {
+        self.window.as_ref()
+    }
 
-        // Try decoding it using a registered pixel data decoder
-        if let Codec::EncapsulatedPixelData(Some(decoder), _) = ts.codec() {
-            let mut data: Vec<u8> = Vec::new();
-            (*decoder)
-                .decode_frame(self, frame, &mut data)
-                .context(DecodePixelDataSnafu)?;
+    /// Retrieve the tabular Modality LUT
+    /// defined in the Modality LUT Sequence, if any,
+    /// which applies to all frames.
+    ///
+    /// When present, it is applied by default
+    /// instead of the rescale parameters.
+    #[inline]
+    pub fn modality_lut(&self) -> Option<&ModalityLut> {
+        self.modality_lut.as_ref()
+    }
 
-            // pixels are already interpreted,
-            // set new photometric interpretation if necessary
-            let new_pi = match samples_per_pixel {
-                3 => PhotometricInterpretation::Rgb,
-                _ => photometric_interpretation,
-            };
+    /// Retrieve the tabular VOI LUTs defined in the VOI LUT Sequence,
+    /// which apply to all frames.
+    ///
+    /// Empty if the object does not define a VOI LUT Sequence.
+    #[inline]
+    pub fn voi_luts(&self) -> &[VoiLut] {
+        &self.voi_luts
+    }
 
-            return Ok(DecodedPixelData {
-                data: Cow::from(data),
-                cols: cols.into(),
-                rows: rows.into(),
-                number_of_frames: 1,
-                photometric_interpretation: new_pi,
-                samples_per_pixel,
-                planar_configuration: PlanarConfiguration::Standard,
-                bits_allocated,
-                bits_stored,
-                high_bit,
-                pixel_representation,
-                rescale,
-                voi_lut_function,
-                window,
-                enforce_frame_fg_vm_match: false,
-            });
-        }
+    /// Retrieve the palette color lookup tables,
+    /// if the photometric interpretation is _PALETTE COLOR_,
+    /// or the supplemental palette of monochrome pixel data.
+    ///
+    /// `None` if the tables are missing or invalid,
+    /// in which case the stored values can only be retrieved as indices
+    /// (see [`PaletteOption::Indices`]).
+    #[inline]
+    pub fn palette(&self) -> Option<&PaletteColorLut> {
+        self.palette.as_ref()
+    }
 
-        let decoded_pixel_data = match pixel_data.value() {
-            DicomValue::PixelSequence(v) => {
-                let fragments = v.fragments();
-                if number_of_frames as usize == fragments.len() {
-                    // return a single fragment
-                    fragments[frame as usize].to_vec()
-                } else {
-                    // not supported, return an error
-                    InvalidPixelDataSnafu.fail()?
-                }
-            }
-            DicomValue::Primitive(p) => {
-                // Non-encoded, just return the pixel data for a single frame
-                let frame_size = ((bits_allocated + 7) / 8) as usize
-                    * samples_per_pixel as usize
-                    * rows as usize
-                    * cols as usize;
-                let frame_offset = frame_size * frame as usize;
-                let data = p.to_bytes();
-                data[frame_offset..frame_offset + frame_size].to_vec()
-            }
-            DicomValue::Sequence(..) => InvalidPixelDataSnafu.fail()?,
-        };
+    /// Retrieve the stored values which denote padding,
+    /// as described by the _Pixel Padding Value_
+    /// and the _Pixel Padding Range Limit_.
+    ///
+    /// `None` if the object does not define a _Pixel Padding Value_.
+    #[inline]
+    pub fn pixel_padding(&self) -> Option<PixelPadding> {
+        self.pixel_padding
+    }
 
-        Ok(DecodedPixelData {
-            data: Cow::from(decoded_pixel_data),
-            cols: cols.into(),
-            rows: rows.into(),
-            number_of_frames: 1,
-            photometric_interpretation,
-            samples_per_pixel,
-            planar_configuration,
-            bits_allocated,
-            bits_stored,
-            high_bit,
-            pixel_representation,
-            rescale,
-            voi_lut_function,
-            window,
-            enforce_frame_fg_vm_match: false,
-        })
+    /// Retrieve the disagreement found
+    /// between the photometric interpretation declared by the object
+    /// and its number of samples per pixel,
+    /// or `None` if they were consistent.
+    #[inline]
+    pub fn photometric_interpretation_mismatch(
+        &self,
+    ) -> Option<&PhotometricInterpretationMismatch> {
+        self.photometric_interpretation_mismatch.as_ref()
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use dicom_object::open_file;
+    /// Retrieve the imaging attributes
+    /// whose number of values disagreed with the number of frames
+    /// or with the number of values of their counterpart,
+    /// and which were fitted to the expected length
+    /// (see [`ValueMultiplicityMismatch`]).
+    #[inline]
+    pub fn value_multiplicity_mismatches(&self) -> &[ValueMultiplicityMismatch] {
+        &self.value_multiplicity_mismatches
+    }
 
-    fn is_send_and_sync<T>()
-    where
-        T: Send + Sync,
-    {
+    /// Retrieve the required imaging attributes
+    /// which were missing from the object
+    /// and took a default value instead
+    /// (see [`from_object`](Self::from_object)).
+    #[inline]
+    pub fn defaulted_attributes(&self) -> &[AttributeName] {
+        &self.defaulted_attributes
     }
 
-    #[test]
-    fn error_is_send_and_sync() {
-        is_send_and_sync::<Error>();
+    /// Retrieve the order of the frames
+    /// (see [`frame_order`](crate::frame_order)).
+    #[inline]
+    pub fn frame_order(&self) -> &FrameOrder {
+        &self.frame_order
     }
+}
 
-    #[test]
-    fn test_to_vec_rgb() {
-        let test_file = dicom_test_files::path("pydicom/SC_rgb_16bit.dcm").unwrap();
-        let obj = open_file(test_file).unwrap();
-        let decoded = obj.decode_pixel_data().unwrap();
+/// Take the value of a required imaging attribute,
+/// or a default value if it is missing,
+/// recording the attribute in `defaulted`.
+fn or_default<T>(
+    value: attribute::Result<T>,
+    defaulted: &mut Vec<AttributeName>,
+    default: impl FnOnce() -> T,
+) -> Result<T> {
+    match value {
+        Err(attribute::GetAttributeError::MissingRequired { name, .. }) => {
+            tracing::warn!(
+                "Missing required attribute `{}`, using a default value",
+                name
+            );
+            defaulted.push(name);
+            Ok(default())
+        }
+        value => Ok(value.context(GetAttributeSnafu)?),
+    }
+}
 
-        let rows = decoded.rows();
+/// Pair window centers with their respective window widths.
+///
+/// A single center or width is paired with all values of its counterpart.
+/// Otherwise, the widths are fitted to the number of centers,
+/// recording the mismatch.
+fn zip_window_levels(
+    mut wcs: Vec<f64>,
+    mut wws: Vec<f64>,
+    mismatches: &mut Vec<ValueMultiplicityMismatch>,
+) -> Vec<WindowLevel> {
+    broadcast(&mut wcs, wws.len());
+    broadcast(&mut wws, wcs.len());
+    mismatches.extend(ValueMultiplicityMismatch::fit(
+        &mut wws,
+        wcs.len(),
+        AttributeName::WindowWidth,
+    ));
+    zip(wcs, wws)
+        .map(|(center, width)| WindowLevel { center, width })
+        .collect()
+}
 
-        let values = decoded.to_vec::<u16>().unwrap();
-        assert_eq!(values.len(), 30000);
+/// Narrow a list of per-frame values down to the value of a single frame,
+/// falling back to the first value if there is no value specific to that frame.
+fn narrow_to_frame<T: Copy>(values: &[T], frame: u32) -> Option<Vec<T>> {
+    values
+        .get(frame as usize)
+        .or(values.first())
+        .copied()
+        .map(|v| vec![v])
+}
 
-        // 50, 80, 1
-        assert_eq!(values[50 * rows as usize * 3 + 80 * 3 + 1], 32896);
+/// Compare the dimensions of the decoded pixel data
+/// against the rows and columns declared by the object,
+/// yielding the declared ones if they differ.
+fn declared_dimensions(rows: u16, cols: u16, decoded: DecodedFrameSize) -> Option<(u32, u32)> {
+    if decoded.rows == rows && decoded.cols == cols {
+        None
+    } else {
+        Some((rows.into(), cols.into()))
     }
+}
 
-    #[test]
-    #[cfg(feature = "ndarray")]
-    fn test_to_ndarray_rgb() {
-        let test_file = dicom_test_files::path("pydicom/SC_rgb_16bit.dcm").unwrap();
-        let obj = open_file(test_file).unwrap();
-        let ndarray = obj
-            .decode_pixel_data()
-            .unwrap()
-            .to_ndarray::<u16>()
-            .unwrap();
-        assert_eq!(ndarray.shape(), &[1, 100, 100, 3]);
-        assert_eq!(ndarray.len(), 30000);
-        assert_eq!(ndarray[[0, 50, 80, 1]], 32896);
+/// Determine the photometric interpretation of the samples
+/// produced by an encapsulated pixel data decoder,
+/// along with the one declared if they differ.
+///
+/// Decoders yield color samples in RGB.
+/// In particular, the inverse component transformation
+/// of `YBR_ICT` and `YBR_RCT` is part of JPEG 2000 decoding,
+/// so no color conversion is to be applied to these samples.
+pub(crate) fn decoded_photometric_interpretation(
+    photometric_interpretation: PhotometricInterpretation,
+    samples_per_pixel: u16,
+) -> (PhotometricInterpretation, Option<PhotometricInterpretation>) {
+    match photometric_interpretation {
+        PhotometricInterpretation::Rgb => (photometric_interpretation, None),
+        PhotometricInterpretation::YbrIct | PhotometricInterpretation::YbrRct => (
+            PhotometricInterpretation::Rgb,
+            Some(photometric_interpretation),
+        ),
+        _ if samples_per_pixel == 3 => (
+            PhotometricInterpretation::Rgb,
+            Some(photometric_interpretation),
+        ),
+        _ => (photometric_interpretation, None),
     }
+}
 
-    /// to_ndarray fails if the target type cannot represent the transformed values
-    #[cfg(feature = "ndarray")]
-    #[test]
-    fn test_to_ndarray_error() {
-        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
-        let obj = open_file(test_file).unwrap();
-        assert!(matches!(
-            obj.decode_pixel_data().unwrap().to_ndarray::<u8>(),
-            Err(Error(InnerError::InvalidDataType { .. }))
+/// Calculate the size in bytes of a frame of native pixel data.
+///
+/// Samples of 1 bit are taken as one byte each,
+/// as they are once unpacked,
+/// so this is also the size in bits of a frame of packed samples.
+///
+/// Returns `None` if the size cannot be addressed in this platform.
+pub(crate) fn native_frame_size(
+    bits_allocated: u16,
+    samples_per_pixel: u16,
+    rows: u32,
+    cols: u32,
+) -> Option<usize> {
+    let bytes_per_sample = (u64::from(bits_allocated) + 7) / 8;
+    let size = bytes_per_sample
+        .checked_mul(u64::from(samples_per_pixel))?
+        .checked_mul(u64::from(rows))?
+        .checked_mul(u64::from(cols))?;
+    usize::try_from(size).ok()
+}
+
+/// Calculate the size in bytes of a frame of native pixel data
+/// as stored in pixel data of `len` bytes.
+///
+/// This is the same as [`native_frame_size`],
+/// except for `YBR_FULL_422` and `YBR_PARTIAL_422` samples,
+/// which are stored in groups of `Y Y CB CR` samples
+/// for every two pixels in a row.
+/// These are only taken as three samples per pixel
+/// if the pixel data is long enough to hold all of them,
+/// as happens when the chroma samples were upsampled
+/// without updating the photometric interpretation.
+///
+/// Returns `None` if the size cannot be addressed in this platform.
+pub(crate) fn native_stored_frame_size(
+    photometric_interpretation: &PhotometricInterpretation,
+    bits_allocated: u16,
+    samples_per_pixel: u16,
+    rows: u32,
+    cols: u32,
+    number_of_frames: u32,
+    len: usize,
+) -> Option<usize> {
+    let frame_size = native_frame_size(bits_allocated, samples_per_pixel, rows, cols)?;
+    let subsampled = samples_per_pixel == 3
+        && bits_allocated > 1
+        && matches!(
+            photometric_interpretation,
+            PhotometricInterpretation::YbrFull422 | PhotometricInterpretation::YbrPartial422
+        )
+        && frame_size
+            .checked_mul(number_of_frames as usize)
+            .map_or(false, |frames_len| len < frames_len);
+    if !subsampled {
+        return Some(frame_size);
+    }
+
+    // two luma samples and one pair of chroma samples
+    // for every two columns, rounded up
+    let bytes_per_sample = (u64::from(bits_allocated) + 7) / 8;
+    let row_samples = u64::from(cols) + 2 * ((u64::from(cols) + 1) / 2);
+    let size = bytes_per_sample
+        .checked_mul(row_samples)?
+        .checked_mul(u64::from(rows))?;
+    usize::try_from(size).ok()
+}
+
+/// Calculate the number of bytes needed to hold
+/// the given number of packed 1-bit samples.
+pub(crate) fn packed_len(bits: usize) -> usize {
+    bits / 8 + usize::from(bits % 8 != 0)
+}
+
+/// Unpack native pixel data of 1 bit per sample
+/// into one byte (0 or 1) per sample.
+///
+/// Samples are packed from the least significant bit of each byte,
+/// and are unpacked from the given bit offset,
+/// since frames do not necessarily start on a byte boundary.
+/// Fewer than `len` samples are returned if the data ends before them.
+pub(crate) fn unpack_bits(data: &[u8], bit_offset: usize, len: usize) -> Vec<u8> {
+    (bit_offset..bit_offset.saturating_add(len))
+        .map_while(|i| data.get(i / 8).map(|byte| (byte >> (i % 8)) & 1))
+        .collect()
+}
+
+/// Calculate the range of bytes of a frame in native pixel data,
+/// given the size of each frame.
+///
+/// Returns `None` if the range cannot be addressed in this platform.
+pub(crate) fn native_frame_range(frame_size: usize, frame: u32) -> Option<Range<usize>> {
+    let frame_size = frame_size as u64;
+    let start = frame_size.checked_mul(u64::from(frame))?;
+    let end = start.checked_add(frame_size)?;
+    Some(usize::try_from(start).ok()?..usize::try_from(end).ok()?)
+}
+
+/// Obtain the number of bytes in native pixel data
+/// after the last of its frames,
+/// failing if there are more than tolerated.
+///
+/// Unless otherwise specified,
+/// 1 byte of padding or anything less than one full frame is tolerated.
+/// Pixel data shorter than the expected frames has no trailing bytes.
+pub(crate) fn check_trailing_bytes(
+    len: usize,
+    frame_size: usize,
+    number_of_frames: u32,
+    max_trailing_bytes: Option<usize>,
+) -> Result<usize> {
+    // if the frames cannot be addressed, then neither can anything after them
+    let trailing_bytes = frame_size
+        .checked_mul(number_of_frames as usize)
+        .map_or(0, |frames_len| len.saturating_sub(frames_len));
+    if trailing_bytes == 0 {
+        return Ok(0);
+    }
+
+    let max_trailing_bytes =
+        max_trailing_bytes.unwrap_or_else(|| usize::max(1, frame_size.saturating_sub(1)));
+    ensure!(
+        trailing_bytes <= max_trailing_bytes,
+        ExcessTrailingBytesSnafu {
+            trailing_bytes,
+            max_trailing_bytes,
+        }
+    );
+    if trailing_bytes == 1 && len % 2 == 0 {
+        // padding to an even length, as expected
+        tracing::debug!("Ignoring padding byte after the last frame in pixel data");
+    } else {
+        tracing::warn!(
+            "Ignoring {} bytes after the last of {} frames in pixel data",
+            trailing_bytes,
+            number_of_frames
+        );
+    }
+    Ok(trailing_bytes)
+}
+
+/// Decode the full pixel data of an object
+/// using the pure Rust pixel data decoders in the transfer syntax registry.
+///
+/// This is the implementation of [`PixelDecoder::decode_pixel_data`]
+/// when the `gdcm` feature is disabled.
+#[cfg_attr(feature = "gdcm", allow(dead_code))]
+pub(crate) fn decode_pixel_data_native<'a, D>(
+    obj: &'a FileDicomObject<InMemDicomObject<D>>,
+    options: &DecodeOptions,
+) -> Result<DecodedPixelData<'a>>
+where
+    D: DataDictionary + Clone,
+{
+    ensure!(has_pixel_data(obj), NoPixelDataSnafu);
+    let pixel_data = attribute::pixel_data(obj).context(GetAttributeSnafu)?;
+
+    let imaging_properties = ImagingProperties::from_object(obj)?;
+
+    let transfer_syntax = obj.meta().transfer_syntax();
+    let ts = TransferSyntaxRegistry
+        .get(transfer_syntax)
+        .with_context(|| UnknownTransferSyntaxSnafu {
+            ts_uid: transfer_syntax,
+        })?;
+
+    if !ts.can_decode_all() {
+        if let (true, DicomValue::PixelSequence(v)) = (options.encoded_fallback, pixel_data.value())
+        {
+            let number_of_frames = imaging_properties.number_of_frames;
+            let samples = SampleState::EncodedOnly {
+                ts_uid: ts.uid().to_string(),
+                fragments: Cow::Borrowed(v.fragments()),
+                offset_table: Cow::Borrowed(v.offset_table()),
+            };
+            return Ok(encoded_only(imaging_properties, number_of_frames, samples));
+        }
+        return UnsupportedTransferSyntaxSnafu {
+            ts: transfer_syntax,
+            features: decoder_features(transfer_syntax),
+        }
+        .fail()?;
+    }
+
+    let rescale = imaging_properties.rescale();
+    let ImagingProperties {
+        cols,
+        rows,
+        samples_per_pixel,
+        bits_allocated,
+        bits_stored,
+        high_bit,
+        pixel_representation,
+        sample_format,
+        planar_configuration,
+        photometric_interpretation,
+        number_of_frames,
+        modality_lut,
+        voi_lut_function,
+        window,
+        window_voi_lut_functions,
+        voi_luts,
+        palette,
+        pixel_padding,
+        frame_order,
+        photometric_interpretation_mismatch,
+        value_multiplicity_mismatches,
+        defaulted_attributes,
+        ..
+    } = imaging_properties;
+
+    // Try decoding it using a registered pixel data decoder
+    if let Codec::EncapsulatedPixelData(Some(decoder), _) = ts.codec() {
+        let mut data: Vec<u8> = Vec::new();
+        let mut decoded_size = DecodedFrameSize { rows, cols };
+        if options.resolution_level > 0 || options.hooks.is_set() {
+            let frame_options =
+                DecodeFrameOptions::new().resolution_level(options.resolution_level);
+            for frame in 0..number_of_frames {
+                options.hooks.check_cancelled(frame, number_of_frames)?;
+                decoded_size = (*decoder)
+                    .decode_frame_with_options(obj, frame, &frame_options, &mut data)
+                    .context(DecodePixelDataSnafu)?;
+                options.hooks.report_progress(frame + 1, number_of_frames);
+            }
+        } else {
+            (*decoder)
+                .decode(obj, &mut data)
+                .context(DecodePixelDataSnafu)?;
+        }
+        let declared_dimensions = declared_dimensions(rows, cols, decoded_size);
+
+        // pixels are already interpreted,
+        // set new photometric interpretation if necessary
+        let (new_pi, original_photometric_interpretation) =
+            decoded_photometric_interpretation(photometric_interpretation, samples_per_pixel);
+
+        return Ok(DecodedPixelData {
+            data: Cow::from(data),
+            samples: SampleState::Decoded,
+            cols: decoded_size.cols.into(),
+            rows: decoded_size.rows.into(),
+            number_of_frames,
+            photometric_interpretation: new_pi,
+            original_photometric_interpretation,
+            samples_per_pixel,
+            planar_configuration: PlanarConfiguration::Standard,
+            bits_allocated,
+            bits_stored,
+            high_bit,
+            pixel_representation,
+            sample_format,
+            rescale,
+            modality_lut,
+            voi_lut_function,
+            window,
+            window_voi_lut_functions,
+            voi_luts,
+            palette,
+            pixel_padding,
+            frame_order,
+            enforce_frame_fg_vm_match: false,
+            photometric_interpretation_mismatch,
+            value_multiplicity_mismatches,
+            defaulted_attributes,
+            declared_dimensions,
+            trailing_bytes: 0,
+        });
+    }
+
+    let mut decoded_pixel_data = match pixel_data.value() {
+        DicomValue::PixelSequence(v) => {
+            // Return all fragments concatenated
+            // (should only happen for Encapsulated Uncompressed),
+            // which holds the frames in order
+            // regardless of the frame extraction strategy
+            Cow::Owned(v.fragments().iter().flatten().copied().collect())
+        }
+        DicomValue::Primitive(p) => {
+            // Non-encoded, just return the pixel data for all frames
+            // (borrowed from the object whenever possible)
+            p.to_bytes()
+        }
+        DicomValue::Sequence(..) => InvalidPixelDataSnafu.fail()?,
+    };
+
+    // leave out anything after the last frame
+    let frame_size = native_stored_frame_size(
+        &photometric_interpretation,
+        bits_allocated,
+        samples_per_pixel,
+        rows.into(),
+        cols.into(),
+        number_of_frames,
+        decoded_pixel_data.len(),
+    )
+    .context(FrameSizeOverflowSnafu)?;
+    let trailing_bytes = if bits_allocated == 1 {
+        // packed samples, the frames are measured in bits
+        let len = frame_size
+            .checked_mul(number_of_frames as usize)
+            .context(FrameSizeOverflowSnafu)?;
+        let trailing_bytes = check_trailing_bytes(
+            decoded_pixel_data.len(),
+            packed_len(len),
+            1,
+            options.max_trailing_bytes,
+        )?;
+        decoded_pixel_data = Cow::Owned(unpack_bits(&decoded_pixel_data, 0, len));
+        trailing_bytes
+    } else {
+        let trailing_bytes = check_trailing_bytes(
+            decoded_pixel_data.len(),
+            frame_size,
+            number_of_frames,
+            options.max_trailing_bytes,
+        )?;
+        if trailing_bytes > 0 {
+            let len = decoded_pixel_data.len() - trailing_bytes;
+            match &mut decoded_pixel_data {
+                Cow::Borrowed(data) => *data = &data[..len],
+                Cow::Owned(data) => data.truncate(len),
+            }
+        }
+        trailing_bytes
+    };
+
+    Ok(DecodedPixelData {
+        data: decoded_pixel_data,
+        samples: SampleState::Decoded,
+        cols: cols.into(),
+        rows: rows.into(),
+        number_of_frames,
+        photometric_interpretation,
+        original_photometric_interpretation: None,
+        samples_per_pixel,
+        planar_configuration,
+        bits_allocated,
+        bits_stored,
+        high_bit,
+        pixel_representation,
+        sample_format,
+        rescale,
+        modality_lut,
+        voi_lut_function,
+        window,
+        window_voi_lut_functions,
+        voi_luts,
+        palette,
+        pixel_padding,
+        frame_order,
+        enforce_frame_fg_vm_match: false,
+        photometric_interpretation_mismatch,
+        value_multiplicity_mismatches,
+        defaulted_attributes,
+        declared_dimensions: None,
+        trailing_bytes,
+    })
+}
+
+/// Decode a single frame of an object
+/// using the pure Rust pixel data decoders in the transfer syntax registry.
+///
+/// This is the implementation of [`PixelDecoder::decode_pixel_data_frame`]
+/// when the `gdcm` feature is disabled.
+#[cfg_attr(feature = "gdcm", allow(dead_code))]
+pub(crate) fn decode_pixel_data_frame_native<'a, D>(
+    obj: &'a FileDicomObject<InMemDicomObject<D>>,
+    frame: u32,
+    options: &DecodeOptions,
+) -> Result<DecodedPixelData<'a>>
+where
+    D: DataDictionary + Clone,
+{
+    ensure!(has_pixel_data(obj), NoPixelDataSnafu);
+    let pixel_data = attribute::pixel_data(obj).context(GetAttributeSnafu)?;
+
+    let imaging_properties = ImagingProperties::from_object(obj)?.for_frame(frame);
+
+    let transfer_syntax = obj.meta().transfer_syntax();
+    let ts = TransferSyntaxRegistry
+        .get(transfer_syntax)
+        .with_context(|| UnknownTransferSyntaxSnafu {
+            ts_uid: transfer_syntax,
+        })?;
+
+    if !ts.can_decode_all() {
+        if let (true, DicomValue::PixelSequence(v)) = (options.encoded_fallback, pixel_data.value())
+        {
+            let fragments =
+                encapsulation::frame_fragments(v, imaging_properties.number_of_frames, frame)
+                    .context(FrameOutOfRangeSnafu {
+                        frame_number: frame,
+                    })?;
+            let samples = SampleState::EncodedOnly {
+                ts_uid: ts.uid().to_string(),
+                fragments: Cow::Borrowed(fragments),
+                offset_table: Cow::Borrowed(&[]),
+            };
+            return Ok(encoded_only(imaging_properties, 1, samples));
+        }
+        return UnsupportedTransferSyntaxSnafu {
+            ts: transfer_syntax,
+            features: decoder_features(transfer_syntax),
+        }
+        .fail()?;
+    }
+
+    let rescale = imaging_properties.rescale();
+    let ImagingProperties {
+        cols,
+        rows,
+        samples_per_pixel,
+        bits_allocated,
+        bits_stored,
+        high_bit,
+        pixel_representation,
+        sample_format,
+        planar_configuration,
+        photometric_interpretation,
+        number_of_frames,
+        modality_lut,
+        voi_lut_function,
+        window,
+        window_voi_lut_functions,
+        voi_luts,
+        palette,
+        pixel_padding,
+        photometric_interpretation_mismatch,
+        value_multiplicity_mismatches,
+        defaulted_attributes,
+        ..
+    } = imaging_properties;
+
+    // Try decoding it using a registered pixel data decoder
+    if let Codec::EncapsulatedPixelData(Some(decoder), _) = ts.codec() {
+        let mut data: Vec<u8> = Vec::new();
+        let frame_options = DecodeFrameOptions::new().resolution_level(options.resolution_level);
+        options.hooks.check_cancelled(0, 1)?;
+        let decoded_size = (*decoder)
+            .decode_frame_with_options(obj, frame, &frame_options, &mut data)
+            .context(DecodePixelDataSnafu)?;
+        options.hooks.report_progress(1, 1);
+        let declared_dimensions = declared_dimensions(rows, cols, decoded_size);
+
+        // pixels are already interpreted,
+        // set new photometric interpretation if necessary
+        let (new_pi, original_photometric_interpretation) =
+            decoded_photometric_interpretation(photometric_interpretation, samples_per_pixel);
+
+        return Ok(DecodedPixelData {
+            data: Cow::from(data),
+            samples: SampleState::Decoded,
+            cols: decoded_size.cols.into(),
+            rows: decoded_size.rows.into(),
+            number_of_frames: 1,
+            photometric_interpretation: new_pi,
+            original_photometric_interpretation,
+            samples_per_pixel,
+            planar_configuration: PlanarConfiguration::Standard,
+            bits_allocated,
+            bits_stored,
+            high_bit,
+            pixel_representation,
+            sample_format,
+            rescale,
+            modality_lut,
+            voi_lut_function,
+            window,
+            window_voi_lut_functions,
+            voi_luts,
+            palette,
+            pixel_padding,
+            frame_order: FrameOrder::stored(1),
+            enforce_frame_fg_vm_match: false,
+            photometric_interpretation_mismatch,
+            value_multiplicity_mismatches,
+            defaulted_attributes,
+            declared_dimensions,
+            trailing_bytes: 0,
+        });
+    }
+
+    let mut trailing_bytes = 0;
+    let decoded_pixel_data = match pixel_data.value() {
+        DicomValue::PixelSequence(v) => {
+            // return the frame's fragments
+            // (should only happen for Encapsulated Uncompressed)
+            let data = encapsulation::frame_data(v, number_of_frames, frame).context(
+                FrameOutOfRangeSnafu {
+                    frame_number: frame,
+                },
+            )?;
+            Cow::Owned(data.into_owned())
+        }
+        DicomValue::Primitive(p) if bits_allocated == 1 => {
+            // packed samples, the frames are measured in bits
+            // and do not necessarily start on a byte boundary
+            let frame_size =
+                native_frame_size(bits_allocated, samples_per_pixel, rows.into(), cols.into())
+                    .context(FrameSizeOverflowSnafu)?;
+            let bit_range =
+                native_frame_range(frame_size, frame).context(FrameSizeOverflowSnafu)?;
+            let len = frame_size
+                .checked_mul(number_of_frames as usize)
+                .context(FrameSizeOverflowSnafu)?;
+            let data = p.to_bytes();
+            trailing_bytes =
+                check_trailing_bytes(data.len(), packed_len(len), 1, options.max_trailing_bytes)?;
+            ensure!(
+                bit_range.end <= data.len().saturating_mul(8),
+                FrameOutOfRangeSnafu {
+                    frame_number: frame,
+                }
+            );
+            Cow::Owned(unpack_bits(&data, bit_range.start, frame_size))
+        }
+        DicomValue::Primitive(p) => {
+            // Non-encoded, just return the pixel data for a single frame
+            let data = p.to_bytes();
+            let frame_size = native_stored_frame_size(
+                &photometric_interpretation,
+                bits_allocated,
+                samples_per_pixel,
+                rows.into(),
+                cols.into(),
+                number_of_frames,
+                data.len(),
+            )
+            .context(FrameSizeOverflowSnafu)?;
+            let frame_range =
+                native_frame_range(frame_size, frame).context(FrameSizeOverflowSnafu)?;
+            trailing_bytes = check_trailing_bytes(
+                data.len(),
+                frame_size,
+                number_of_frames,
+                options.max_trailing_bytes,
+            )?;
+            ensure!(
+                frame_range.end <= data.len(),
+                FrameOutOfRangeSnafu {
+                    frame_number: frame,
+                }
+            );
+            // borrow the frame from the object whenever possible
+            match data {
+                Cow::Borrowed(data) => Cow::Borrowed(&data[frame_range]),
+                Cow::Owned(data) => Cow::Owned(data[frame_range].to_vec()),
+            }
+        }
+        DicomValue::Sequence(..) => InvalidPixelDataSnafu.fail()?,
+    };
+
+    Ok(DecodedPixelData {
+        data: decoded_pixel_data,
+        samples: SampleState::Decoded,
+        cols: cols.into(),
+        rows: rows.into(),
+        number_of_frames: 1,
+        photometric_interpretation,
+        original_photometric_interpretation: None,
+        samples_per_pixel,
+        planar_configuration,
+        bits_allocated,
+        bits_stored,
+        high_bit,
+        pixel_representation,
+        sample_format,
+        rescale,
+        modality_lut,
+        voi_lut_function,
+        window,
+        window_voi_lut_functions,
+        voi_luts,
+        palette,
+        pixel_padding,
+        frame_order: FrameOrder::stored(1),
+        enforce_frame_fg_vm_match: false,
+        photometric_interpretation_mismatch,
+        value_multiplicity_mismatches,
+        defaulted_attributes,
+        declared_dimensions: None,
+        trailing_bytes,
+    })
+}
+
+/// Gather the imaging properties of an object
+/// together with its pixel data samples in encoded form,
+/// for when no decoder is available
+/// (see [`DecodeOptions::encoded_fallback`]).
+#[cfg_attr(feature = "gdcm", allow(dead_code))]
+fn encoded_only(
+    imaging_properties: ImagingProperties,
+    number_of_frames: u32,
+    samples: SampleState<'_>,
+) -> DecodedPixelData<'_> {
+    let rescale = imaging_properties.rescale();
+    // the order of the frames only applies to all of them
+    let frame_order = if number_of_frames == imaging_properties.number_of_frames {
+        imaging_properties.frame_order.clone()
+    } else {
+        FrameOrder::stored(number_of_frames)
+    };
+    let ImagingProperties {
+        cols,
+        rows,
+        samples_per_pixel,
+        bits_allocated,
+        bits_stored,
+        high_bit,
+        pixel_representation,
+        sample_format,
+        planar_configuration,
+        photometric_interpretation,
+        modality_lut,
+        voi_lut_function,
+        window,
+        window_voi_lut_functions,
+        voi_luts,
+        palette,
+        pixel_padding,
+        photometric_interpretation_mismatch,
+        value_multiplicity_mismatches,
+        defaulted_attributes,
+        ..
+    } = imaging_properties;
+
+    DecodedPixelData {
+        data: Cow::Borrowed(&[]),
+        samples,
+        cols: cols.into(),
+        rows: rows.into(),
+        number_of_frames,
+        photometric_interpretation,
+        original_photometric_interpretation: None,
+        samples_per_pixel,
+        planar_configuration,
+        bits_allocated,
+        bits_stored,
+        high_bit,
+        pixel_representation,
+        sample_format,
+        rescale,
+        modality_lut,
+        voi_lut_function,
+        window,
+        window_voi_lut_functions,
+        voi_luts,
+        palette,
+        pixel_padding,
+        frame_order,
+        enforce_frame_fg_vm_match: false,
+        photometric_interpretation_mismatch,
+        value_multiplicity_mismatches,
+        defaulted_attributes,
+        declared_dimensions: None,
+        trailing_bytes: 0,
+    }
+}
+
+#[cfg(not(feature = "gdcm"))]
+impl<D> PixelDecoder for FileDicomObject<InMemDicomObject<D>>
+where
+    D: DataDictionary + Clone,
+{
+    fn decode_pixel_data(&self) -> Result<DecodedPixelData> {
+        decode_pixel_data_native(self, &DecodeOptions::default())
+    }
+
+    fn decode_pixel_data_frame(&self, frame: u32) -> Result<DecodedPixelData<'_>> {
+        decode_pixel_data_frame_native(self, frame, &DecodeOptions::default())
+    }
+
+    fn decode_pixel_data_with_options(
+        &self,
+        options: &DecodeOptions,
+    ) -> Result<DecodedPixelData<'_>> {
+        decode_pixel_data_native(self, options)?.check_options(options)
+    }
+
+    fn decode_pixel_data_frame_with_options(
+        &self,
+        frame: u32,
+        options: &DecodeOptions,
+    ) -> Result<DecodedPixelData<'_>> {
+        decode_pixel_data_frame_native(self, frame, options)?.check_options(options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_object::{open_file, FileMetaTableBuilder};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Create the file meta group of a test object
+    /// in Explicit VR Little Endian,
+    /// with a SOP instance UID which is unique to each call.
+    fn test_meta(sop_class_uid: &str) -> FileMetaTableBuilder {
+        static NEXT_INSTANCE: AtomicU32 = AtomicU32::new(1);
+        let instance = NEXT_INSTANCE.fetch_add(1, Ordering::Relaxed);
+        FileMetaTableBuilder::new()
+            .transfer_syntax(dicom_dictionary_std::uids::EXPLICIT_VR_LITTLE_ENDIAN)
+            .media_storage_sop_class_uid(sop_class_uid)
+            .media_storage_sop_instance_uid(format!("2.25.{}", instance))
+    }
+
+    fn is_send_and_sync<T>()
+    where
+        T: Send + Sync,
+    {
+    }
+
+    #[test]
+    fn error_is_send_and_sync() {
+        is_send_and_sync::<Error>();
+    }
+
+    #[test]
+    fn pixel_data_is_send_and_sync() {
+        is_send_and_sync::<DecodedPixelData<'static>>();
+        is_send_and_sync::<ConvertOptions>();
+        is_send_and_sync::<Lut<u16>>();
+        is_send_and_sync::<WindowLevelTransform>();
+        is_send_and_sync::<ModalityLut>();
+        is_send_and_sync::<VoiLut>();
+        is_send_and_sync::<PaletteColorLut>();
+    }
+
+    /// Owned pixel data and lookup tables
+    /// can be read from multiple threads at once.
+    #[test]
+    fn test_share_pixel_data_across_threads() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let pixel_data: Arc<DecodedPixelData<'static>> =
+            Arc::new(obj.decode_pixel_data().unwrap().to_owned());
+        drop(obj);
+
+        let lut: Arc<Lut<f32>> = Arc::new(
+            Lut::new_rescale(
+                pixel_data.bits_stored(),
+                true,
+                pixel_data.rescale().unwrap()[0],
+            )
+            .unwrap(),
+        );
+        let expected_mapped: Vec<f32> =
+            lut.map_iter(pixel_data.frame_data_ow(0).unwrap()).collect();
+        let expected: Vec<f32> = pixel_data.to_vec().unwrap();
+        let options = ConvertOptions::new()
+            .with_voi_lut(VoiLutOption::Normalize)
+            .cancel_token(Arc::new(AtomicBool::new(false)));
+        let expected_normalized: Vec<u8> = pixel_data.to_vec_with_options(&options).unwrap();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let pixel_data = Arc::clone(&pixel_data);
+                let lut = Arc::clone(&lut);
+                let options = options.clone();
+                std::thread::spawn(move || {
+                    let mapped: Vec<f32> =
+                        lut.map_iter(pixel_data.frame_data_ow(0).unwrap()).collect();
+                    let values: Vec<f32> = pixel_data.to_vec().unwrap();
+                    let normalized: Vec<u8> = pixel_data.to_vec_with_options(&options).unwrap();
+                    (mapped, values, normalized)
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let (mapped, values, normalized) = handle.join().unwrap();
+            assert_eq!(mapped, expected_mapped);
+            assert_eq!(values, expected);
+            assert_eq!(normalized, expected_normalized);
+        }
+    }
+
+    #[test]
+    fn test_to_vec_rgb() {
+        let test_file = dicom_test_files::path("pydicom/SC_rgb_16bit.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let decoded = obj.decode_pixel_data().unwrap();
+
+        let rows = decoded.rows();
+
+        let values = decoded.to_vec::<u16>().unwrap();
+        assert_eq!(values.len(), 30000);
+
+        // 50, 80, 1
+        assert_eq!(values[50 * rows as usize * 3 + 80 * 3 + 1], 32896);
+    }
+
+    #[test]
+    #[cfg(not(feature = "gdcm"))]
+    fn test_decode_pixel_data_from_bytes() {
+        use dicom_dictionary_std::tags;
+        use dicom_object::OpenFileOptions;
+
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let bytes = std::fs::read(&test_file).unwrap();
+        let obj = OpenFileOptions::new().from_bytes(&bytes).unwrap();
+
+        let decoded = obj.decode_pixel_data().unwrap();
+        // native pixel data is borrowed straight from the object
+        let value = obj.element(tags::PIXEL_DATA).unwrap().value();
+        let value = value.primitive().unwrap().to_bytes();
+        let value = value.as_ptr_range();
+        let data = decoded.data().as_ptr_range();
+        assert!(value.start <= data.start && data.end <= value.end);
+
+        // and so is a single frame
+        let frame = obj.decode_pixel_data_frame(0).unwrap();
+        assert_eq!(frame.data().as_ptr(), decoded.data().as_ptr());
+
+        let expected = open_file(test_file).unwrap().decode_pixel_data().unwrap();
+        assert_eq!(decoded.data(), expected.data());
+    }
+
+    #[test]
+    #[cfg(feature = "ndarray")]
+    fn test_to_ndarray_rgb() {
+        let test_file = dicom_test_files::path("pydicom/SC_rgb_16bit.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let ndarray = obj
+            .decode_pixel_data()
+            .unwrap()
+            .to_ndarray::<u16>()
+            .unwrap();
+        assert_eq!(ndarray.shape(), &[1, 100, 100, 3]);
+        assert_eq!(ndarray.len(), 30000);
+        assert_eq!(ndarray[[0, 50, 80, 1]], 32896);
+    }
+
+    /// to_ndarray fails if the target type cannot represent the transformed values
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_to_ndarray_error() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        assert!(matches!(
+            obj.decode_pixel_data().unwrap().to_ndarray::<u8>(),
+            Err(Error(InnerError::InvalidDataType { .. }))
                 | Err(Error(InnerError::CreateLut { .. }))
         ));
     }
 
-    /// conversion to ndarray in 16-bit
-    /// retains the original data of a 16-bit image
-    #[cfg(feature = "ndarray")]
+    /// conversion to ndarray in 16-bit
+    /// retains the original data of a 16-bit image
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_to_ndarray_16bit() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+
+        let decoded = obj.decode_pixel_data().unwrap();
+        let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+        let ndarray = decoded.to_ndarray_with_options::<u16>(&options).unwrap();
+
+        assert_eq!(ndarray.shape(), &[1, 128, 128, 1]);
+
+        // sample value retrieved from the original image file
+        assert_eq!(ndarray[[0, 127, 127, 0]], 0x038D);
+    }
+
+    /// signed stored values are retrieved as they are
+    /// when no modality LUT is applied
+    #[test]
+    fn test_to_vec_signed_stored_values() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(decoded.pixel_representation(), PixelRepresentation::Signed);
+        assert_eq!(decoded.rescale().unwrap()[0], Rescale::new(1., -1024.));
+
+        let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+        let stored = decoded.to_vec_with_options::<i16>(&options).unwrap();
+        let expected: Vec<i16> = decoded
+            .data()
+            .chunks_exact(2)
+            .map(|v| i16::from_ne_bytes([v[0], v[1]]))
+            .collect();
+        assert_eq!(stored, expected);
+        assert_eq!(stored[128 * 128 - 1], 0x038D);
+
+        // stored values are -1024 away from the Hounsfield units
+        let rescaled = decoded.to_vec::<f32>().unwrap();
+        for (stored, rescaled) in stored.iter().zip(&rescaled) {
+            assert_eq!(f32::from(*stored) - 1024., *rescaled);
+        }
+
+        #[cfg(feature = "ndarray")]
+        {
+            let ndarray = decoded.to_ndarray_with_options::<i16>(&options).unwrap();
+            assert_eq!(ndarray.shape(), &[1, 128, 128, 1]);
+            assert_eq!(ndarray.as_slice().unwrap(), &stored[..]);
+        }
+    }
+
+    /// signed stored values narrower than the bits allocated
+    /// are sign extended when no modality LUT is applied
+    #[test]
+    fn test_to_vec_signed_stored_values_sign_extended() {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let image = |bits_allocated: u16, bits_stored: u16, pixel_data: PrimitiveValue| {
+            InMemDicomObject::from_element_iter([
+                DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+                DataElement::new(
+                    tags::PHOTOMETRIC_INTERPRETATION,
+                    VR::CS,
+                    dicom_value!(Str, "MONOCHROME2"),
+                ),
+                DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [1])),
+                DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [4])),
+                DataElement::new(
+                    tags::BITS_ALLOCATED,
+                    VR::US,
+                    dicom_value!(U16, [bits_allocated]),
+                ),
+                DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [bits_stored])),
+                DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [bits_stored - 1])),
+                DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [1])),
+                DataElement::new(
+                    tags::PIXEL_DATA,
+                    if bits_allocated > 8 { VR::OW } else { VR::OB },
+                    pixel_data,
+                ),
+            ])
+            .with_meta(test_meta(uids::SECONDARY_CAPTURE_IMAGE_STORAGE))
+            .unwrap()
+        };
+        let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+
+        let obj = image(16, 12, dicom_value!(U16, [0x0FFF, 0x0800, 0x07FF, 0x0001]));
+        let decoded = obj.decode_pixel_data().unwrap();
+        let values = decoded.to_vec_with_options::<i16>(&options).unwrap();
+        assert_eq!(values, vec![-1, -2048, 2047, 1]);
+
+        let obj = image(8, 8, PrimitiveValue::from(vec![0xFF_u8, 0x80, 0x7F, 0x01]));
+        let decoded = obj.decode_pixel_data().unwrap();
+        let values = decoded.to_vec_with_options::<i8>(&options).unwrap();
+        assert_eq!(values, vec![-1, -128, 127, 1]);
+    }
+
+    /// bits outside of the bits stored are cleared before conversion,
+    /// unless requested otherwise
+    #[test]
+    fn test_mask_unused_bits() {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let image = |high_bit: u16, signed: bool, samples: [u16; 4]| {
+            InMemDicomObject::from_element_iter([
+                DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+                DataElement::new(
+                    tags::PHOTOMETRIC_INTERPRETATION,
+                    VR::CS,
+                    dicom_value!(Str, "MONOCHROME2"),
+                ),
+                DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [2])),
+                DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [2])),
+                DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [16])),
+                DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [12])),
+                DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [high_bit])),
+                DataElement::new(
+                    tags::PIXEL_REPRESENTATION,
+                    VR::US,
+                    dicom_value!(U16, [signed as u16]),
+                ),
+                DataElement::new(tags::RESCALE_SLOPE, VR::DS, dicom_value!(Str, "1")),
+                DataElement::new(tags::RESCALE_INTERCEPT, VR::DS, dicom_value!(Str, "-1000")),
+                DataElement::new(tags::PIXEL_DATA, VR::OW, PrimitiveValue::from(samples)),
+            ])
+            .with_meta(test_meta(uids::CT_IMAGE_STORAGE))
+            .unwrap()
+        };
+        let stored = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+
+        // overlay bits above the high bit
+        let obj = image(11, false, [0xF123, 0x0123, 0x1FFF, 0x8000]);
+        let decoded = obj.decode_pixel_data().unwrap();
+        let values = decoded.to_vec_with_options::<u16>(&stored).unwrap();
+        assert_eq!(values, vec![0x123, 0x123, 0xFFF, 0]);
+        let values = decoded.to_vec::<f32>().unwrap();
+        assert_eq!(values, vec![-709., -709., 3095., -1000.]);
+        let values = decoded
+            .to_vec_with_options::<u16>(&stored.clone().with_unused_bits(UnusedBitsOption::Keep))
+            .unwrap();
+        assert_eq!(values, vec![0xF123, 0x0123, 0x1FFF, 0x8000]);
+
+        // signed stored values with junk above the high bit
+        let obj = image(11, true, [0xFFFF, 0x0FFF, 0x7800, 0x87FF]);
+        let decoded = obj.decode_pixel_data().unwrap();
+        let values = decoded.to_vec_with_options::<i16>(&stored).unwrap();
+        assert_eq!(values, vec![-1, -1, -2048, 2047]);
+        let values = decoded.to_vec::<f32>().unwrap();
+        assert_eq!(values, vec![-1001., -1001., -3048., 1047.]);
+
+        // stored values in the most significant bits
+        let obj = image(15, false, [0x1230, 0x123F, 0xFFF0, 0x000F]);
+        let decoded = obj.decode_pixel_data().unwrap();
+        let values = decoded.to_vec_with_options::<u16>(&stored).unwrap();
+        assert_eq!(values, vec![0x123, 0x123, 0xFFF, 0]);
+
+        #[cfg(feature = "image")]
+        {
+            let obj = image(11, false, [0xF123, 0x0123, 0x1FFF, 0x8000]);
+            let decoded = obj.decode_pixel_data().unwrap();
+            let image = decoded.to_dynamic_image_with_options(0, &stored).unwrap();
+            assert_eq!(image.to_luma16().into_raw(), vec![0x123, 0x123, 0xFFF, 0]);
+        }
+    }
+
+    /// stored values are moved to the least significant bits
+    /// when the high bit is not `bits_stored - 1`
+    #[test]
+    fn test_non_standard_high_bit() {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let image = |bits: [u16; 3], signed: bool, pixel_data: PrimitiveValue| {
+            let [bits_allocated, bits_stored, high_bit] = bits;
+            InMemDicomObject::from_element_iter([
+                DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+                DataElement::new(
+                    tags::PHOTOMETRIC_INTERPRETATION,
+                    VR::CS,
+                    dicom_value!(Str, "MONOCHROME2"),
+                ),
+                DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [1])),
+                DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [4])),
+                DataElement::new(
+                    tags::BITS_ALLOCATED,
+                    VR::US,
+                    dicom_value!(U16, [bits_allocated]),
+                ),
+                DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [bits_stored])),
+                DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [high_bit])),
+                DataElement::new(
+                    tags::PIXEL_REPRESENTATION,
+                    VR::US,
+                    dicom_value!(U16, [signed as u16]),
+                ),
+                DataElement::new(
+                    tags::PIXEL_DATA,
+                    if bits_allocated > 8 { VR::OW } else { VR::OB },
+                    pixel_data,
+                ),
+            ])
+            .with_meta(test_meta(uids::SECONDARY_CAPTURE_IMAGE_STORAGE))
+            .unwrap()
+        };
+        let stored = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+
+        // 8 bits stored in bits 4 to 11 of a 16-bit container,
+        // surrounded by other content
+        let values: [u16; 4] = [0, 1, 0x7F, 0xFF];
+        let samples = values.map(|v| 0xF00F | (v << 4));
+        let obj = image([16, 8, 11], false, PrimitiveValue::from(samples));
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(decoded.high_bit(), 11);
+        assert_eq!(
+            decoded.to_vec_with_options::<u16>(&stored).unwrap(),
+            values.to_vec()
+        );
+        // the default rescale yields the same values
+        assert_eq!(decoded.to_vec::<u16>().unwrap(), values.to_vec());
+        // the bits above the high bit are kept on request
+        let keep = stored.clone().with_unused_bits(UnusedBitsOption::Keep);
+        assert_eq!(
+            decoded.to_vec_with_options::<u16>(&keep).unwrap(),
+            values.map(|v| 0xF00 | v).to_vec()
+        );
+
+        // signed values in the same layout
+        let values: [i16; 4] = [0, -1, 127, -128];
+        let samples = values.map(|v| 0xF00F | (((v as u16) & 0xFF) << 4));
+        let obj = image([16, 8, 11], true, PrimitiveValue::from(samples));
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(
+            decoded.to_vec_with_options::<i16>(&stored).unwrap(),
+            values.to_vec()
+        );
+        assert_eq!(decoded.to_vec::<f32>().unwrap(), vec![0., -1., 127., -128.]);
+
+        // 4 bits stored in bits 2 to 5 of an 8-bit container
+        let obj = image(
+            [8, 4, 5],
+            true,
+            PrimitiveValue::from([0b1100_0011_u8, 0b0011_1100, 0b0001_1111, 0b1010_0001]),
+        );
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(
+            decoded.to_vec_with_options::<i8>(&stored).unwrap(),
+            vec![0, -1, 7, -8]
+        );
+
+        // a high bit outside of the container cannot be realigned
+        let obj = image([16, 12, 16], false, PrimitiveValue::from([0_u16; 4]));
+        let decoded = obj.decode_pixel_data().unwrap();
+        let result = decoded.to_vec::<u16>();
+        assert!(
+            matches!(
+                result,
+                Err(Error(InnerError::UnsupportedOther {
+                    name: "HighBit",
+                    ..
+                }))
+            ),
+            "unexpected result {:?}",
+            result
+        );
+    }
+
+    /// partial range Y'CbCr samples are converted to full range RGB,
+    /// with the chroma samples upsampled where they are subsampled
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_ybr_partial_to_rgb() {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let image = |pi: &str, size: [u16; 2], planar: u16, pixel_data: PrimitiveValue| {
+            let [rows, cols] = size;
+            let bits = if matches!(pixel_data, PrimitiveValue::U16(_)) {
+                16
+            } else {
+                8
+            };
+            InMemDicomObject::from_element_iter([
+                DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [3])),
+                DataElement::new(
+                    tags::PHOTOMETRIC_INTERPRETATION,
+                    VR::CS,
+                    PrimitiveValue::from(pi),
+                ),
+                DataElement::new(
+                    tags::PLANAR_CONFIGURATION,
+                    VR::US,
+                    dicom_value!(U16, [planar]),
+                ),
+                DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [rows])),
+                DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [cols])),
+                DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [bits])),
+                DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [bits])),
+                DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [bits - 1])),
+                DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+                DataElement::new(
+                    tags::PIXEL_DATA,
+                    if bits == 16 { VR::OW } else { VR::OB },
+                    pixel_data,
+                ),
+            ])
+            .with_meta(test_meta(uids::SECONDARY_CAPTURE_IMAGE_STORAGE))
+            .unwrap()
+        };
+        let to_rgb8 = |obj: &FileDicomObject<InMemDicomObject>| {
+            let decoded = obj.decode_pixel_data().unwrap();
+            decoded.to_dynamic_image(0).unwrap().to_rgb8().into_raw()
+        };
+
+        // black, white, and a red tone (Y = 126, CB = 128, CR = 240)
+        let red = [255, 37, 128];
+
+        // 4:2:2 in groups of Y Y CB CR
+        let obj = image(
+            "YBR_PARTIAL_422",
+            [2, 2],
+            0,
+            PrimitiveValue::from(vec![16_u8, 235, 128, 128, 126, 126, 128, 240]),
+        );
+        assert_eq!(
+            to_rgb8(&obj),
+            [[0, 0, 0], [255, 255, 255], red, red].concat()
+        );
+
+        // 4:2:2 with an odd number of columns
+        let obj = image(
+            "YBR_PARTIAL_422",
+            [1, 3],
+            0,
+            PrimitiveValue::from(vec![16_u8, 235, 128, 128, 126, 128, 240, 0]),
+        );
+        assert_eq!(to_rgb8(&obj), [[0, 0, 0], [255, 255, 255], red].concat());
+
+        // 4:2:2 in planes
+        let obj = image(
+            "YBR_PARTIAL_422",
+            [2, 2],
+            1,
+            PrimitiveValue::from(vec![16_u8, 235, 126, 126, 128, 128, 128, 240]),
+        );
+        assert_eq!(
+            to_rgb8(&obj),
+            [[0, 0, 0], [255, 255, 255], red, red].concat()
+        );
+
+        // 4:2:0 in planes, one chroma sample for all 4 pixels
+        let obj = image(
+            "YBR_PARTIAL_420",
+            [2, 2],
+            0,
+            PrimitiveValue::from(vec![16_u8, 235, 126, 126, 128, 240]),
+        );
+        assert_eq!(
+            to_rgb8(&obj),
+            [[179, 0, 0], [255, 164, 255], red, red].concat()
+        );
+
+        // chroma samples already upsampled by the decoder
+        let obj = image(
+            "YBR_PARTIAL_420",
+            [1, 2],
+            0,
+            PrimitiveValue::from(vec![126_u8, 128, 240, 16, 128, 128]),
+        );
+        assert_eq!(to_rgb8(&obj), [red, [0, 0, 0]].concat());
+
+        // 16-bit samples, with the partial range scaled up
+        let obj = image(
+            "YBR_PARTIAL_422",
+            [1, 2],
+            0,
+            PrimitiveValue::from([16_u16 << 8, 235 << 8, 128 << 8, 128 << 8]),
+        );
+        let decoded = obj.decode_pixel_data().unwrap();
+        let image = decoded.to_dynamic_image(0).unwrap().to_rgb16().into_raw();
+        assert_eq!(image, vec![0, 0, 0, 0xFFFF, 0xFFFF, 0xFFFF]);
+    }
+
+    /// conversion of a 16-bit image to a vector of 16-bit processed pixel values
+    /// takes advantage of the output's full spectrum
+    #[test]
+    fn test_to_vec_16bit_to_window() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+
+        let decoded = obj.decode_pixel_data().unwrap();
+        let options = ConvertOptions::new()
+            .with_modality_lut(ModalityLutOption::Default)
+            .with_voi_lut(VoiLutOption::First);
+        let values = decoded.to_vec_with_options::<u16>(&options).unwrap();
+
+        assert_eq!(values.len(), 128 * 128);
+
+        // values are in the full spectrum
+
+        let max = values.iter().max().unwrap();
+        let min = values.iter().min().unwrap();
+
+        assert_eq!(*max, 0xFFFF, "maximum in window should be 65535");
+        assert_eq!(*min, 0, "minimum in window should be 0");
+    }
+
+    /// Rescaled values of CT_small.dcm in ascending order,
+    /// from which windows are derived independently of the implementation.
+    fn ct_small_sorted_values(decoded: &DecodedPixelData) -> Vec<f64> {
+        let rescale = decoded.rescale().unwrap()[0];
+        let mut values: Vec<f64> = decoded
+            .frame_data_ow(0)
+            .unwrap()
+            .into_iter()
+            .map(|v| rescale.apply(f64::from(v as i16)))
+            .collect();
+        values.sort_unstable_by(f64::total_cmp);
+        values
+    }
+
+    #[test]
+    fn test_histogram_16bit() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let decoded = obj.decode_pixel_data().unwrap();
+        let values = ct_small_sorted_values(&decoded);
+
+        let histogram = decoded.histogram(0, 32).unwrap();
+        assert_eq!(histogram.bins(), 32);
+        assert_eq!(histogram.total(), 128 * 128);
+        assert_eq!(histogram.min(), values[0]);
+        assert_eq!(histogram.max(), values[values.len() - 1]);
+
+        // the first bin holds all values below its end
+        let (_, end) = histogram.bin_range(0).unwrap();
+        let below = values.iter().filter(|v| **v < end).count();
+        assert_eq!(histogram.counts()[0], below as u64);
+
+        assert!(matches!(
+            decoded.histogram(0, 0),
+            Err(Error(InnerError::UnsupportedOther { .. }))
+        ));
+    }
+
+    /// conversion with a percentile VOI LUT option
+    /// is equivalent to applying the window
+    /// spanning those percentiles of the rescaled values
+    #[test]
+    fn test_to_vec_16bit_percentile_window() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let decoded = obj.decode_pixel_data().unwrap();
+        let values = ct_small_sorted_values(&decoded);
+        assert_eq!(values.len(), 128 * 128);
+
+        // nearest-rank percentiles of 16384 values
+        let low = values[(16384_f64 * 0.01).ceil() as usize - 1];
+        let high = values[(16384_f64 * 0.99).ceil() as usize - 1];
+        let window = WindowLevel {
+            width: high - low + 1.,
+            center: (low + high) / 2.,
+        };
+
+        let convert = |voi_lut| {
+            let options = ConvertOptions::new()
+                .with_modality_lut(ModalityLutOption::Default)
+                .with_voi_lut(voi_lut);
+            decoded.to_vec_with_options::<u16>(&options).unwrap()
+        };
+        let percentile = convert(VoiLutOption::Percentile { low: 1., high: 99. });
+        assert_eq!(
+            percentile,
+            convert(VoiLutOption::CustomWithFunction(
+                window,
+                VoiLutFunction::Linear
+            ))
+        );
+        // values outside of the window are clamped
+        assert_eq!(*percentile.iter().min().unwrap(), 0);
+        assert_eq!(*percentile.iter().max().unwrap(), 0xFFFF);
+
+        // the full range is a min-max normalization
+        assert_eq!(
+            convert(VoiLutOption::Percentile {
+                low: 0.,
+                high: 100.,
+            }),
+            convert(VoiLutOption::Normalize)
+        );
+    }
+
+    #[test]
+    fn test_value_range_16bit() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let decoded = obj.decode_pixel_data().unwrap();
+
+        let samples: Vec<i64> = decoded
+            .frame_data_ow(0)
+            .unwrap()
+            .into_iter()
+            .map(|v| i64::from(v as i16))
+            .collect();
+        let expected = (
+            *samples.iter().min().unwrap(),
+            *samples.iter().max().unwrap(),
+        );
+        assert_eq!(decoded.value_range(0).unwrap(), expected);
+        assert_eq!(decoded.value_range_all().unwrap(), expected);
+        assert!(matches!(
+            decoded.value_range(1),
+            Err(Error(InnerError::FrameOutOfRange { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_correct_ri_extracted() {
+        // Rescale Slope and Intercept exist for this scan
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.rescale().unwrap()[0], Rescale::new(1., -1024.));
+    }
+
+    #[test]
+    fn test_correct_rescale_extracted_without_element() {
+        // RescaleIntercept does not exists for this scan
+        let test_file = dicom_test_files::path("pydicom/MR_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.rescale().unwrap()[0], Rescale::new(1., 0.));
+    }
+
+    #[test]
+    fn test_general_properties_from_16bit() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let pixel_data = obj.decode_pixel_data().unwrap();
+
+        assert_eq!(pixel_data.columns(), 128, "Unexpected Columns");
+        assert_eq!(pixel_data.rows(), 128, "Unexpected Rows");
+        assert_eq!(
+            pixel_data.number_of_frames(),
+            1,
+            "Unexpected Number of Frames"
+        );
+        assert_eq!(
+            pixel_data.photometric_interpretation(),
+            &PhotometricInterpretation::Monochrome2,
+            "Unexpected Photometric Interpretation"
+        );
+        assert_eq!(
+            pixel_data.samples_per_pixel(),
+            1,
+            "Unexpected Samples per Pixel"
+        );
+        assert_eq!(pixel_data.bits_allocated(), 16, "Unexpected Bits Allocated");
+        assert_eq!(pixel_data.bits_stored(), 16, "Unexpected Bits Stored");
+        assert_eq!(pixel_data.high_bit(), 15, "Unexpected High Bit");
+        assert_eq!(
+            pixel_data.pixel_representation(),
+            PixelRepresentation::Signed
+        );
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_force_bit_depth_from_16bit() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let pixel_data = obj.decode_pixel_data().unwrap();
+
+        // original image has 16 bits stored
+        {
+            let image = pixel_data
+                .to_dynamic_image(0)
+                .expect("Failed to convert to image");
+
+            assert!(image.as_luma16().is_some());
+        }
+
+        // force to 16 bits
+        {
+            let options = ConvertOptions::new().force_16bit();
+            let image = pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .expect("Failed to convert to image");
+
+            assert!(image.as_luma16().is_some());
+        }
+
+        // force to 8 bits
+        {
+            let options = ConvertOptions::new().force_8bit();
+            let image = pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .expect("Failed to convert to image");
+
+            assert!(image.as_luma8().is_some());
+        }
+    }
+
+    /// Narrowing a monochrome image to 8 bits
+    /// rounds to the nearest value by default,
+    /// and dithering preserves the mean intensity.
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_force_8bit_dithering() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let pixel_data = obj.decode_pixel_data().unwrap();
+
+        let options = ConvertOptions::new().force_16bit();
+        let image = pixel_data
+            .to_dynamic_image_with_options(0, &options)
+            .unwrap()
+            .into_luma16();
+        let expected_mean = image
+            .pixels()
+            .map(|p| f64::from(p.0[0]) / 257.)
+            .sum::<f64>()
+            / image.len() as f64;
+
+        let options = ConvertOptions::new().force_8bit();
+        let rounded = pixel_data
+            .to_dynamic_image_with_options(0, &options)
+            .unwrap()
+            .into_luma8();
+        let mut truncated_differs = 0;
+        for (x, y) in zip(image.pixels(), rounded.pixels()) {
+            let x = x.0[0];
+            assert_eq!(y.0[0], ((u32::from(x) + 128) / 257) as u8);
+            if y.0[0] != (x >> 8) as u8 {
+                truncated_differs += 1;
+            }
+        }
+        assert!(truncated_differs > 0);
+
+        for dither in [DitherOption::Ordered, DitherOption::FloydSteinberg] {
+            let options = ConvertOptions::new().force_8bit().with_dithering(dither);
+            let dithered = pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .unwrap()
+                .into_luma8();
+            assert_eq!(dithered.dimensions(), rounded.dimensions());
+            assert_ne!(dithered, rounded);
+            let mean =
+                dithered.pixels().map(|p| f64::from(p.0[0])).sum::<f64>() / dithered.len() as f64;
+            assert!(
+                (mean - expected_mean).abs() < 0.5,
+                "{:?}: mean was {}, expected {}",
+                dither,
+                mean,
+                expected_mean
+            );
+        }
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_force_bit_depth_from_rgb() {
+        let test_file = dicom_test_files::path("pydicom/color-px.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let pixel_data = obj.decode_pixel_data().unwrap();
+
+        // original image is RGB with 8 bits per sample
+        {
+            let image = pixel_data
+                .to_dynamic_image(0)
+                .expect("Failed to convert to image");
+
+            assert!(image.as_rgb8().is_some());
+        }
+
+        // force to 16 bits
+        {
+            let options = ConvertOptions::new().force_16bit();
+            let image = pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .expect("Failed to convert to image");
+
+            assert!(image.as_rgb16().is_some());
+        }
+
+        // force to 8 bits
+        {
+            let options = ConvertOptions::new().force_8bit();
+            let image = pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .expect("Failed to convert to image");
+
+            assert!(image.as_rgb8().is_some());
+        }
+    }
+
+    /// Multiple window levels in a single frame object
+    /// are retained as alternative windows of that frame.
+    #[test]
+    fn test_single_frame_multiple_windows() {
+        use dicom_core::{dicom_value, DataElement, VR};
+        use dicom_dictionary_std::tags;
+
+        let path =
+            dicom_test_files::path("pydicom/CT_small.dcm").expect("test DICOM file should exist");
+        let mut obj = open_file(&path).unwrap();
+        obj.put(DataElement::new(
+            tags::WINDOW_CENTER,
+            VR::DS,
+            dicom_value!(Strs, ["40", "-600"]),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_WIDTH,
+            VR::DS,
+            dicom_value!(Strs, ["400", "1500"]),
+        ));
+
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let expected = [
+            WindowLevel {
+                center: 40.,
+                width: 400.,
+            },
+            WindowLevel {
+                center: -600.,
+                width: 1500.,
+            },
+        ];
+        assert_eq!(
+            pixel_data.window().unwrap(),
+            Some(&WindowLevels::Shared(expected.to_vec()))
+        );
+        assert_eq!(pixel_data.window_for_frame(0).unwrap(), Some(&expected[..]));
+
+        // converting the image uses the first window
+        pixel_data.to_vec_frame::<u8>(0).unwrap();
+        #[cfg(feature = "image")]
+        pixel_data.to_dynamic_image(0).unwrap();
+    }
+
+    /// An alternative window level can be selected by index,
+    /// along with the VOI LUT function at the same index.
+    #[test]
+    fn test_voi_lut_option_index() {
+        use dicom_core::{dicom_value, DataElement, VR};
+        use dicom_dictionary_std::tags;
+
+        let path =
+            dicom_test_files::path("pydicom/CT_small.dcm").expect("test DICOM file should exist");
+        let mut obj = open_file(&path).unwrap();
+        obj.put(DataElement::new(
+            tags::WINDOW_CENTER,
+            VR::DS,
+            dicom_value!(Strs, ["40", "-600"]),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_WIDTH,
+            VR::DS,
+            dicom_value!(Strs, ["400", "1500"]),
+        ));
+        obj.put(DataElement::new(
+            tags::VOILUT_FUNCTION,
+            VR::CS,
+            dicom_value!(Strs, ["LINEAR", "SIGMOID"]),
+        ));
+
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let windows = [
+            WindowLevel {
+                center: 40.,
+                width: 400.,
+            },
+            WindowLevel {
+                center: -600.,
+                width: 1500.,
+            },
+        ];
+        assert_eq!(pixel_data.windows(), &windows[..]);
+
+        let convert = |voi_lut| {
+            let options = ConvertOptions::new().with_voi_lut(voi_lut);
+            pixel_data.to_vec_frame_with_options::<u8>(0, &options)
+        };
+
+        let first = convert(VoiLutOption::Index(0)).unwrap();
+        assert_eq!(
+            first,
+            convert(VoiLutOption::CustomWithFunction(
+                windows[0],
+                VoiLutFunction::Linear
+            ))
+            .unwrap()
+        );
+        assert_eq!(first, convert(VoiLutOption::First).unwrap());
+
+        let second = convert(VoiLutOption::Index(1)).unwrap();
+        assert_eq!(
+            second,
+            convert(VoiLutOption::CustomWithFunction(
+                windows[1],
+                VoiLutFunction::Sigmoid
+            ))
+            .unwrap()
+        );
+        assert_ne!(first, second);
+
+        // no fallback to another window
+        let err = convert(VoiLutOption::Index(2)).unwrap_err();
+        assert!(
+            err.to_string().contains("Window level #2"),
+            "unexpected error: {}",
+            err
+        );
+        #[cfg(feature = "image")]
+        {
+            let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Index(1));
+            pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .unwrap();
+            let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Index(2));
+            assert!(pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .is_err());
+        }
+    }
+
+    /// Window levels in the per-frame functional groups
+    /// are resolved for each frame.
+    #[test]
+    fn test_multi_frame_per_frame_windows() {
+        use dicom_core::{dicom_value, value::DataSetSequence, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let frame_voi_lut = |wc: &str, ww: &str| {
+            InMemDicomObject::from_element_iter([DataElement::new(
+                tags::FRAME_VOILUT_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(tags::WINDOW_CENTER, VR::DS, dicom_value!(Strs, [wc])),
+                    DataElement::new(tags::WINDOW_WIDTH, VR::DS, dicom_value!(Strs, [ww])),
+                ])]),
+            )])
+        };
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, "MONOCHROME2"),
+            ),
+            DataElement::new(tags::NUMBER_OF_FRAMES, VR::IS, dicom_value!(Str, "2")),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [7])),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(
+                tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![
+                    frame_voi_lut("100", "50"),
+                    frame_voi_lut("20", "10"),
+                ]),
+            ),
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                PrimitiveValue::from(vec![0_u8, 50, 100, 150, 10, 15, 20, 25]),
+            ),
+        ])
+        .with_meta(test_meta(uids::ENHANCED_MR_IMAGE_STORAGE))
+        .unwrap();
+
+        let w0 = WindowLevel {
+            center: 100.,
+            width: 50.,
+        };
+        let w1 = WindowLevel {
+            center: 20.,
+            width: 10.,
+        };
+
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(
+            pixel_data.window().unwrap(),
+            Some(&WindowLevels::PerFrame(vec![vec![w0], vec![w1]]))
+        );
+        assert_eq!(pixel_data.window_for_frame(0).unwrap(), Some(&[w0][..]));
+        assert_eq!(pixel_data.window_for_frame(1).unwrap(), Some(&[w1][..]));
+
+        // decoding a single frame keeps only the windows of that frame
+        let frame = obj.decode_pixel_data_frame(1).unwrap();
+        assert_eq!(
+            frame.window().unwrap(),
+            Some(&WindowLevels::Shared(vec![w1]))
+        );
+        assert_eq!(frame.data(), &[10, 15, 20, 25]);
+    }
+
+    /// A tabular VOI LUT in the VOI LUT Sequence
+    /// is applied when selected.
+    #[test]
+    fn test_voi_lut_sequence() {
+        let test_file = dicom_test_files::path("pydicom/vlut_04.dcm").unwrap();
+        let obj = open_file(test_file).unwrap();
+        let decoded = obj.decode_pixel_data().unwrap();
+
+        let luts = decoded.voi_luts();
+        assert_eq!(luts.len(), 1);
+        assert_eq!(luts[0].first_mapped, 0);
+        assert_eq!(luts[0].bits, 16);
+        assert_eq!(luts[0].data.len(), 256);
+        assert_eq!(luts[0].data[0], 0);
+        assert_eq!(luts[0].data[76], 19532);
+        assert_eq!(luts[0].data[178], 45746);
+        assert_eq!(luts[0].data[255], 65535);
+
+        let cols = decoded.columns() as usize;
+        let at = |row: usize, col: usize| row * cols + col;
+
+        let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Table);
+        let values: Vec<u16> = decoded.to_vec_with_options(&options).unwrap();
+        // entries are scaled to the 8 bits of the samples
+        assert_eq!(values[at(387, 448)], 0);
+        assert_eq!(values[at(178, 126)], 76);
+        assert_eq!(values[at(186, 389)], 178);
+        assert_eq!(values[at(129, 79)], 255);
+
+        #[cfg(feature = "image")]
+        {
+            let options = ConvertOptions::new()
+                .with_voi_lut(VoiLutOption::Table)
+                .force_16bit();
+            let image = decoded
+                .to_dynamic_image_with_options(0, &options)
+                .unwrap()
+                .into_luma16();
+            assert_eq!(image.get_pixel(448, 387).0, [0]);
+            assert_eq!(image.get_pixel(126, 178).0, [19532]);
+            assert_eq!(image.get_pixel(389, 186).0, [45746]);
+            assert_eq!(image.get_pixel(79, 129).0, [65535]);
+        }
+    }
+
+    /// A tabular VOI LUT wins over a window level only when selected,
+    /// or when there is no window level.
+    #[test]
+    fn test_voi_lut_sequence_with_window() {
+        use dicom_core::{dicom_value, value::DataSetSequence, DataElement, VR};
+        use dicom_dictionary_std::tags;
+
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let mut obj = open_file(test_file).unwrap();
+        // a step function around 0 on signed pixel data,
+        // with the first mapped value encoded as unsigned
+        obj.put(DataElement::new(
+            tags::VOILUT_SEQUENCE,
+            VR::SQ,
+            DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                DataElement::new(
+                    tags::LUT_DESCRIPTOR,
+                    VR::US,
+                    dicom_value!(U16, [2, 0xFFFF, 8]),
+                ),
+                DataElement::new(tags::LUT_DATA, VR::US, dicom_value!(U16, [0, 255])),
+            ])]),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_CENTER,
+            VR::DS,
+            dicom_value!(Strs, ["40"]),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_WIDTH,
+            VR::DS,
+            dicom_value!(Strs, ["400"]),
+        ));
+
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(decoded.voi_luts()[0].first_mapped, -1);
+
+        let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Identity);
+        let rescaled: Vec<f64> = decoded.to_vec_with_options(&options).unwrap();
+        let expected: Vec<u16> = rescaled
+            .iter()
+            .map(|v| if *v >= 0. { 0xFFFF } else { 0 })
+            .collect();
+
+        let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Table);
+        let table: Vec<u16> = decoded.to_vec_with_options(&options).unwrap();
+        assert_eq!(table, expected);
+
+        // the window level is preferred otherwise
+        let options = ConvertOptions::new().with_voi_lut(VoiLutOption::First);
+        let first: Vec<u16> = decoded.to_vec_with_options(&options).unwrap();
+        let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Custom(WindowLevel {
+            center: 40.,
+            width: 400.,
+        }));
+        let window: Vec<u16> = decoded.to_vec_with_options(&options).unwrap();
+        assert_eq!(first, window);
+        assert_ne!(first, table);
+
+        // unless the object has no window level
+        obj.remove_element(tags::WINDOW_CENTER);
+        obj.remove_element(tags::WINDOW_WIDTH);
+        let decoded = obj.decode_pixel_data().unwrap();
+        let options = ConvertOptions::new().with_voi_lut(VoiLutOption::First);
+        let first: Vec<u16> = decoded.to_vec_with_options(&options).unwrap();
+        assert_eq!(first, expected);
+    }
+
+    /// A tabular Modality LUT in the Modality LUT Sequence
+    /// takes precedence over the rescale attributes by default.
+    #[test]
+    fn test_modality_lut_sequence() {
+        use dicom_core::{dicom_value, value::DataSetSequence, DataElement, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, "MONOCHROME2"),
+            ),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [16])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [12])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [11])),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(tags::RESCALE_SLOPE, VR::DS, dicom_value!(Str, "2")),
+            DataElement::new(tags::RESCALE_INTERCEPT, VR::DS, dicom_value!(Str, "-10")),
+            // a ramp over the stored values 1 to 4
+            DataElement::new(
+                tags::MODALITY_LUT_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(tags::LUT_DESCRIPTOR, VR::US, dicom_value!(U16, [4, 1, 16])),
+                    DataElement::new(tags::MODALITY_LUT_TYPE, VR::LO, dicom_value!(Str, "OD")),
+                    DataElement::new(
+                        tags::LUT_DATA,
+                        VR::US,
+                        dicom_value!(U16, [1000, 2000, 3000, 4000]),
+                    ),
+                ])]),
+            ),
+            DataElement::new(tags::PIXEL_DATA, VR::OW, dicom_value!(U16, [0, 2, 4, 4095])),
+        ])
+        .with_meta(test_meta(uids::COMPUTED_RADIOGRAPHY_IMAGE_STORAGE))
+        .unwrap();
+
+        let props = ImagingProperties::from_object(&obj).unwrap();
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(props.modality_lut(), decoded.modality_lut());
+        let lut = decoded.modality_lut().unwrap();
+        assert_eq!(lut.first_mapped, 1);
+        assert_eq!(lut.bits, 16);
+        assert_eq!(lut.data, vec![1000, 2000, 3000, 4000]);
+        assert_eq!(lut.lut_type.as_deref(), Some("OD"));
+
+        // values outside of the table are clamped
+        let values: Vec<f64> = decoded.to_vec().unwrap();
+        assert_eq!(values, vec![1000., 2000., 4000., 4000.]);
+        let values: Vec<f64> = decoded.to_vec_frame(0).unwrap();
+        assert_eq!(values, vec![1000., 2000., 4000., 4000.]);
+
+        // the rescale parameters only apply when overridden
+        let options = ConvertOptions::new()
+            .with_modality_lut(ModalityLutOption::Override(Rescale::new(2., -10.)));
+        let values: Vec<f64> = decoded.to_vec_with_options(&options).unwrap();
+        assert_eq!(values, vec![-10., -6., -2., 8180.]);
+
+        let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+        let values: Vec<f64> = decoded.to_vec_with_options(&options).unwrap();
+        assert_eq!(values, vec![0., 2., 4., 4095.]);
+
+        #[cfg(feature = "image")]
+        {
+            // the mapped values are normalized into the output range
+            let image = decoded
+                .to_dynamic_image_with_options(0, &ConvertOptions::new().force_16bit())
+                .unwrap()
+                .into_luma16();
+            assert_eq!(image.get_pixel(0, 0).0, [0]);
+            assert_eq!(image.get_pixel(1, 1).0, [u16::MAX]);
+            assert!(image.get_pixel(1, 0).0[0] < image.get_pixel(0, 1).0[0]);
+            assert_eq!(image.get_pixel(0, 1), image.get_pixel(1, 1));
+        }
+    }
+
+    /// Pixel padding values can be left out of a min-max normalization,
+    /// so that it spans the anatomical range of a CT image.
+    #[test]
+    fn test_normalize_without_pixel_padding() {
+        use dicom_core::{dicom_value, DataElement, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let ct = |padding: bool| {
+            let mut obj = InMemDicomObject::from_element_iter([
+                DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+                DataElement::new(
+                    tags::PHOTOMETRIC_INTERPRETATION,
+                    VR::CS,
+                    dicom_value!(Str, "MONOCHROME2"),
+                ),
+                DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [2])),
+                DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [3])),
+                DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [16])),
+                DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [16])),
+                DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [15])),
+                DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [1])),
+                DataElement::new(tags::RESCALE_SLOPE, VR::DS, dicom_value!(Str, "1")),
+                DataElement::new(tags::RESCALE_INTERCEPT, VR::DS, dicom_value!(Str, "-1024")),
+                // padding outside of the scanned area,
+                // then -1000 HU to 1000 HU
+                DataElement::new(
+                    tags::PIXEL_DATA,
+                    VR::OW,
+                    dicom_value!(
+                        U16,
+                        [-2000_i16 as u16, -2000_i16 as u16, 24, 1024, 2024, 1524]
+                    ),
+                ),
+            ]);
+            if padding {
+                obj.put(DataElement::new(
+                    tags::PIXEL_PADDING_VALUE,
+                    VR::SS,
+                    dicom_value!(I16, [-2000]),
+                ));
+            }
+            obj.with_meta(test_meta(uids::CT_IMAGE_STORAGE)).unwrap()
+        };
+
+        let obj = ct(true);
+        let props = ImagingProperties::from_object(&obj).unwrap();
+        let expected = PixelPadding {
+            value: -2000,
+            range_limit: None,
+        };
+        assert_eq!(props.pixel_padding(), Some(expected));
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(decoded.pixel_padding(), Some(expected));
+
+        let convert = |decoded: &DecodedPixelData, voi_lut, padding| {
+            let options = ConvertOptions::new()
+                .with_voi_lut(voi_lut)
+                .with_padding(padding);
+            decoded.to_vec_with_options::<u16>(&options).unwrap()
+        };
+
+        // padding skews the normalization by default
+        let values = convert(&decoded, VoiLutOption::Normalize, PaddingOption::Keep);
+        assert_eq!(values[0], 0);
+        assert!(values[2] > 20_000, "unexpected value {}", values[2]);
+        assert_eq!(values[4], u16::MAX);
+
+        // the anatomical range spans the output range without padding
+        let values = convert(&decoded, VoiLutOption::Normalize, PaddingOption::Exclude);
+        assert_eq!(values[0], 0);
+        assert_eq!(values[2], 0);
+        assert!(values[3] > 32_000 && values[3] < 33_500);
+        assert_eq!(values[4], u16::MAX);
+
+        // padding is mapped to the lowest value even within a window
+        let window = VoiLutOption::Custom(WindowLevel {
+            center: -3000.,
+            width: 200.,
+        });
+        let values = convert(&decoded, window.clone(), PaddingOption::Exclude);
+        assert!(values[0] > 0);
+        let values = convert(&decoded, window, PaddingOption::Mask);
+        assert_eq!(values[0], 0);
+        assert_eq!(values[2], u16::MAX);
+        // but not when only rescaling
+        let values: Vec<f64> = decoded
+            .to_vec_with_options(&ConvertOptions::new().with_padding(PaddingOption::Mask))
+            .unwrap();
+        assert_eq!(values[0], -3024.);
+
+        #[cfg(feature = "image")]
+        {
+            let options = ConvertOptions::new()
+                .with_voi_lut(VoiLutOption::Normalize)
+                .with_padding(PaddingOption::Exclude);
+            let image = decoded
+                .to_dynamic_image_with_options(0, &options)
+                .unwrap()
+                .into_luma16();
+            assert_eq!(image.get_pixel(0, 0).0, [0]);
+            assert_eq!(image.get_pixel(2, 0).0, [0]);
+            assert_eq!(image.get_pixel(1, 1).0, [u16::MAX]);
+        }
+
+        // no effect without a pixel padding value
+        let obj = ct(false);
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(decoded.pixel_padding(), None);
+        assert_eq!(
+            convert(&decoded, VoiLutOption::Normalize, PaddingOption::Mask),
+            convert(&decoded, VoiLutOption::Normalize, PaddingOption::Keep),
+        );
+    }
+
+    /// Build an 8-bit monochrome object with 3 frames of 2x2 pixels,
+    /// a single rescale intercept of -10,
+    /// and the given rescale slopes in the per-frame functional groups.
+    fn multi_frame_with_rescale_slopes(slopes: &[&str]) -> FileDicomObject<InMemDicomObject> {
+        use dicom_core::{dicom_value, value::DataSetSequence, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let frame_transformation = |slope: &str| {
+            InMemDicomObject::from_element_iter([DataElement::new(
+                tags::PIXEL_VALUE_TRANSFORMATION_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(tags::RESCALE_SLOPE, VR::DS, dicom_value!(Strs, [slope])),
+                ])]),
+            )])
+        };
+
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, "MONOCHROME2"),
+            ),
+            DataElement::new(tags::NUMBER_OF_FRAMES, VR::IS, dicom_value!(Str, "3")),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [7])),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(tags::RESCALE_INTERCEPT, VR::DS, dicom_value!(Strs, ["-10"])),
+            DataElement::new(
+                tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(
+                    slopes
+                        .iter()
+                        .map(|slope| frame_transformation(slope))
+                        .collect::<Vec<_>>(),
+                ),
+            ),
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                PrimitiveValue::from(vec![0_u8, 50, 100, 150, 10, 15, 20, 25, 1, 2, 3, 4]),
+            ),
+        ])
+        .with_meta(test_meta(uids::ENHANCED_CT_IMAGE_STORAGE))
+        .unwrap()
+    }
+
+    /// A single rescale intercept applies to all frames,
+    /// alongside rescale slopes defined for each frame.
+    #[test]
+    fn test_single_rescale_intercept_with_per_frame_slopes() {
+        let obj = multi_frame_with_rescale_slopes(&["1", "2", "3"]);
+
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert!(pixel_data.value_multiplicity_mismatches().is_empty());
+        assert_eq!(
+            pixel_data.rescale().unwrap(),
+            &[
+                Rescale {
+                    intercept: -10.,
+                    slope: 1.
+                },
+                Rescale {
+                    intercept: -10.,
+                    slope: 2.
+                },
+                Rescale {
+                    intercept: -10.,
+                    slope: 3.
+                },
+            ]
+        );
+        assert_eq!(
+            pixel_data.to_vec_frame::<f32>(2).unwrap(),
+            vec![-7., -4., -1., 2.]
+        );
+
+        // consistent, so also accepted in strict mode
+        obj.decode_pixel_data_with_options(&DecodeOptions::new().strict(true))
+            .unwrap();
+
+        let frame = obj.decode_pixel_data_frame(1).unwrap();
+        assert_eq!(
+            frame.rescale().unwrap(),
+            &[Rescale {
+                intercept: -10.,
+                slope: 2.
+            }]
+        );
+    }
+
+    /// Per-frame values which do not match the number of frames
+    /// are fitted by repeating the last value,
+    /// or rejected in strict mode.
+    #[test]
+    fn test_per_frame_rescale_slopes_mismatch() {
+        let obj = multi_frame_with_rescale_slopes(&["1", "2"]);
+
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let expected_mismatch = ValueMultiplicityMismatch {
+            attribute: AttributeName::RescaleSlope,
+            expected: 3,
+            found: 2,
+        };
+        assert_eq!(
+            pixel_data.value_multiplicity_mismatches(),
+            &[expected_mismatch.clone()]
+        );
+        assert_eq!(
+            pixel_data.rescale().unwrap()[2],
+            Rescale {
+                intercept: -10.,
+                slope: 2.
+            }
+        );
+        assert_eq!(
+            pixel_data.to_vec_frame::<f32>(2).unwrap(),
+            vec![-8., -6., -4., -2.]
+        );
+
+        let result = obj.decode_pixel_data_with_options(&DecodeOptions::new().strict(true));
+        match result {
+            Err(Error(InnerError::InconsistentValueMultiplicity { mismatch, .. })) => {
+                assert_eq!(mismatch, expected_mismatch);
+            }
+            _ => panic!("expected a value multiplicity error, got {:?}", result),
+        }
+    }
+
+    /// Decoders which only implement the mandatory methods
+    /// still honor the decoding options.
+    #[test]
+    fn test_decode_options_default_implementation() {
+        struct Decoder(FileDicomObject<InMemDicomObject>);
+
+        impl PixelDecoder for Decoder {
+            fn decode_pixel_data(&self) -> Result<DecodedPixelData<'_>> {
+                decode_pixel_data_native(&self.0, &DecodeOptions::default())
+            }
+        }
+
+        let decoder = Decoder(multi_frame_with_rescale_slopes(&["1", "2"]));
+
+        let pixel_data = decoder
+            .decode_pixel_data_with_options(&DecodeOptions::new())
+            .unwrap();
+        assert_eq!(pixel_data.value_multiplicity_mismatches().len(), 1);
+        let pixel_data = decoder
+            .decode_pixel_data_frame_with_options(2, &DecodeOptions::new())
+            .unwrap();
+        assert_eq!(
+            pixel_data.to_vec::<f32>().unwrap(),
+            vec![-8., -6., -4., -2.]
+        );
+
+        let strict = DecodeOptions::new().strict(true);
+        let err = decoder.decode_pixel_data_with_options(&strict).unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::InconsistentValueMultiplicity { .. }
+        ));
+        let err = decoder
+            .decode_pixel_data_frame_with_options(2, &strict)
+            .unwrap_err();
+        assert!(matches!(
+            err.0,
+            InnerError::InconsistentValueMultiplicity { .. }
+        ));
+
+        // consistent pixel data is accepted in strict mode
+        let decoder = Decoder(multi_frame_with_rescale_slopes(&["1", "2", "3"]));
+        let pixel_data = decoder.decode_pixel_data_with_options(&strict).unwrap();
+        assert_eq!(pixel_data.rescale().unwrap().len(), 3);
+        let pixel_data = decoder
+            .decode_pixel_data_frame_with_options(2, &strict)
+            .unwrap();
+        assert_eq!(
+            pixel_data.rescale().unwrap(),
+            &[Rescale {
+                intercept: -10.,
+                slope: 3.
+            }]
+        );
+        assert_eq!(pixel_data.to_vec::<f32>().unwrap(), vec![-7., -4., -1., 2.]);
+    }
+
+    /// Frames are iterated over as views with their own rescale parameters.
+    #[test]
+    fn test_frames_iterator() {
+        let obj = multi_frame_with_rescale_slopes(&["1", "2", "3"]);
+        let pixel_data = obj.decode_pixel_data().unwrap();
+
+        let frames = pixel_data.frames();
+        assert_eq!(frames.len(), 3);
+        for (i, frame) in frames.enumerate() {
+            assert_eq!(frame.number(), i as u32);
+            assert_eq!(frame.data(), pixel_data.frame_data(i as u32).unwrap());
+            assert_eq!(
+                frame.rescale().unwrap(),
+                Rescale {
+                    intercept: -10.,
+                    slope: (i + 1) as f64
+                }
+            );
+            assert_eq!(
+                frame.to_vec::<f32>().unwrap(),
+                pixel_data.to_vec_frame::<f32>(i as u32).unwrap()
+            );
+        }
+        assert_eq!(
+            pixel_data.frames().last().unwrap().to_vec::<f32>().unwrap(),
+            vec![-7., -4., -1., 2.]
+        );
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::iter::ParallelIterator;
+
+            let numbers: Vec<u32> = pixel_data.par_frames().map(|f| f.number()).collect();
+            assert_eq!(numbers, vec![0, 1, 2]);
+        }
+
+        // a single decoded frame yields a single view
+        let frame = obj.decode_pixel_data_frame(2).unwrap();
+        let frames: Vec<_> = frame.frames().collect();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].to_vec::<f32>().unwrap(), vec![-7., -4., -1., 2.]);
+    }
+
+    /// Frames declared beyond the end of the pixel data are not yielded.
+    #[test]
+    fn test_frames_iterator_truncated() {
+        use dicom_core::{DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::tags;
+
+        let mut obj = multi_frame_with_rescale_slopes(&["1", "2", "3"]);
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OB,
+            PrimitiveValue::from(vec![0_u8, 50, 100, 150, 10, 15]),
+        ));
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let frames: Vec<_> = pixel_data.frames().map(|f| f.data()).collect();
+        assert_eq!(frames, vec![&[0_u8, 50, 100, 150][..]]);
+    }
+
+    /// A single window width is paired with all window centers.
     #[test]
-    fn test_to_ndarray_16bit() {
-        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
-        let obj = open_file(test_file).unwrap();
+    fn test_single_window_width_with_multiple_centers() {
+        use dicom_core::{dicom_value, DataElement, VR};
+        use dicom_dictionary_std::tags;
+
+        let mut obj = multi_frame_with_rescale_slopes(&["1", "1", "1"]);
+        obj.put(DataElement::new(
+            tags::WINDOW_CENTER,
+            VR::DS,
+            dicom_value!(Strs, ["40", "400"]),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_WIDTH,
+            VR::DS,
+            dicom_value!(Strs, ["80"]),
+        ));
 
-        let decoded = obj.decode_pixel_data().unwrap();
-        let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
-        let ndarray = decoded.to_ndarray_with_options::<u16>(&options).unwrap();
+        let pixel_data = obj
+            .decode_pixel_data_with_options(&DecodeOptions::new().strict(true))
+            .unwrap();
+        assert_eq!(
+            pixel_data.window().unwrap(),
+            Some(&WindowLevels::Shared(vec![
+                WindowLevel {
+                    center: 40.,
+                    width: 80.,
+                },
+                WindowLevel {
+                    center: 400.,
+                    width: 80.,
+                },
+            ]))
+        );
+    }
 
-        assert_eq!(ndarray.shape(), &[1, 128, 128, 1]);
+    /// Build an 8-bit image of 2x1 pixels
+    /// with the given photometric interpretation and samples per pixel.
+    fn image_with_color_attributes(
+        pi: &str,
+        spp: u16,
+        data: Vec<u8>,
+    ) -> FileDicomObject<InMemDicomObject> {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [spp])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, pi),
+            ),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [7])),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(data)),
+        ])
+        .with_meta(test_meta(uids::SECONDARY_CAPTURE_IMAGE_STORAGE))
+        .unwrap()
+    }
 
-        // sample value retrieved from the original image file
-        assert_eq!(ndarray[[0, 127, 127, 0]], 0x038D);
+    /// Monochrome images with 3 samples per pixel
+    /// are interpreted as RGB.
+    #[test]
+    fn test_monochrome_with_3_samples_per_pixel() {
+        let obj = image_with_color_attributes("MONOCHROME2", 3, vec![10, 20, 30, 40, 50, 60]);
+
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.samples_per_pixel(), 3);
+        assert_eq!(
+            pixel_data.photometric_interpretation(),
+            &PhotometricInterpretation::Rgb
+        );
+        let mismatch = pixel_data.photometric_interpretation_mismatch().unwrap();
+        assert_eq!(
+            mismatch,
+            &PhotometricInterpretationMismatch {
+                declared: PhotometricInterpretation::Monochrome2,
+                samples_per_pixel: 3,
+                resolved: PhotometricInterpretation::Rgb,
+            }
+        );
+        assert_eq!(
+            mismatch.to_string(),
+            "PhotometricInterpretation `MONOCHROME2` is inconsistent with SamplesPerPixel `3`, using `RGB`"
+        );
+        // the mismatch is kept in owned copies and single frames
+        assert!(pixel_data
+            .to_owned()
+            .photometric_interpretation_mismatch()
+            .is_some());
+        let frame = obj.decode_pixel_data_frame(0).unwrap();
+        assert_eq!(
+            frame.photometric_interpretation(),
+            &PhotometricInterpretation::Rgb
+        );
+
+        #[cfg(feature = "image")]
+        {
+            let image = pixel_data.to_dynamic_image(0).unwrap();
+            assert_eq!(image.to_rgb8().into_raw(), vec![10, 20, 30, 40, 50, 60]);
+        }
+
+        // strict decoding fails instead
+        let options = DecodeOptions::new().strict(true);
+        let result = obj.decode_pixel_data_with_options(&options);
+        assert!(matches!(
+            result,
+            Err(Error(InnerError::InconsistentPhotometricInterpretation {
+                pi: PhotometricInterpretation::Monochrome2,
+                spp: 3,
+                ..
+            }))
+        ));
+        let result = obj.decode_pixel_data_frame_with_options(0, &options);
+        assert!(result.is_err());
     }
 
-    /// conversion of a 16-bit image to a vector of 16-bit processed pixel values
-    /// takes advantage of the output's full spectrum
+    /// Color images with a single sample per pixel
+    /// are interpreted as MONOCHROME2.
     #[test]
-    fn test_to_vec_16bit_to_window() {
-        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
-        let obj = open_file(test_file).unwrap();
+    fn test_rgb_with_1_sample_per_pixel() {
+        let obj = image_with_color_attributes("RGB", 1, vec![10, 200]);
 
-        let decoded = obj.decode_pixel_data().unwrap();
-        let options = ConvertOptions::new()
-            .with_modality_lut(ModalityLutOption::Default)
-            .with_voi_lut(VoiLutOption::First);
-        let values = decoded.to_vec_with_options::<u16>(&options).unwrap();
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.samples_per_pixel(), 1);
+        assert_eq!(
+            pixel_data.photometric_interpretation(),
+            &PhotometricInterpretation::Monochrome2
+        );
+        assert_eq!(
+            pixel_data.photometric_interpretation_mismatch(),
+            Some(&PhotometricInterpretationMismatch {
+                declared: PhotometricInterpretation::Rgb,
+                samples_per_pixel: 1,
+                resolved: PhotometricInterpretation::Monochrome2,
+            })
+        );
+        assert_eq!(pixel_data.data(), &[10, 200]);
 
-        assert_eq!(values.len(), 128 * 128);
+        #[cfg(feature = "image")]
+        {
+            let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Identity);
+            let image = pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .unwrap();
+            assert_eq!(image.color(), image::ColorType::L8);
+        }
 
-        // values are in the full spectrum
+        let options = DecodeOptions::new().strict(true);
+        let result = obj.decode_pixel_data_with_options(&options);
+        assert!(matches!(
+            result,
+            Err(Error(InnerError::InconsistentPhotometricInterpretation {
+                pi: PhotometricInterpretation::Rgb,
+                spp: 1,
+                ..
+            }))
+        ));
 
-        let max = values.iter().max().unwrap();
-        let min = values.iter().min().unwrap();
+        // consistent attributes pass strict decoding
+        let obj = image_with_color_attributes("MONOCHROME2", 1, vec![10, 200]);
+        let pixel_data = obj.decode_pixel_data_with_options(&options).unwrap();
+        assert_eq!(pixel_data.photometric_interpretation_mismatch(), None);
+    }
 
-        assert_eq!(*max, 0xFFFF, "maximum in window should be 65535");
-        assert_eq!(*min, 0, "minimum in window should be 0");
+    /// Build a PALETTE COLOR object of 1x2 pixels
+    /// with palette color lookup tables of 3 entries of the given bit depth,
+    /// starting at stored value 1.
+    fn palette_color_image(bits: u16, red: [u16; 3]) -> FileDicomObject<InMemDicomObject> {
+        use dicom_core::{dicom_value, DataElement, VR};
+        use dicom_dictionary_std::tags;
+
+        let mut obj = image_with_color_attributes("PALETTE COLOR", 1, vec![1, 3]);
+        for tag in [
+            tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+            tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+            tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+        ] {
+            obj.put(DataElement::new(tag, VR::US, dicom_value!(U16, [3, 1, bits])));
+        }
+        // green is fixed, blue is the reverse of red
+        obj.put(DataElement::new(
+            tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+            VR::OW,
+            dicom_value!(U16, [red[0], red[1], red[2]]),
+        ));
+        obj.put(DataElement::new(
+            tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+            VR::OW,
+            dicom_value!(U16, [0, 0, 0]),
+        ));
+        obj.put(DataElement::new(
+            tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+            VR::OW,
+            dicom_value!(U16, [red[2], red[1], red[0]]),
+        ));
+        obj
     }
 
+    /// PALETTE COLOR pixel data is mapped to RGB
+    /// through the palette color lookup tables
     #[test]
-    fn test_correct_ri_extracted() {
-        // Rescale Slope and Intercept exist for this scan
-        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
-        let obj = open_file(test_file).unwrap();
+    fn test_palette_color() {
+        let obj = palette_color_image(8, [10, 128, 255]);
         let pixel_data = obj.decode_pixel_data().unwrap();
-        assert_eq!(pixel_data.rescale().unwrap()[0], Rescale::new(1., -1024.));
+        assert_eq!(
+            pixel_data.photometric_interpretation(),
+            &PhotometricInterpretation::PaletteColor
+        );
+        let palette = pixel_data.palette().unwrap();
+        assert_eq!(palette.first_mapped, 1);
+        assert_eq!(palette.bits, 8);
+        assert!(pixel_data.to_owned().palette().is_some());
+
+        // index 1 is the first entry, index 3 is the last one
+        let values: Vec<u16> = pixel_data.to_vec().unwrap();
+        assert_eq!(values, vec![10, 0, 255, 255, 0, 10]);
+
+        // the palette indices can be retrieved instead
+        let options = ConvertOptions::new().with_palette(PaletteOption::Indices);
+        let values: Vec<u16> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(values, vec![1, 3]);
+
+        #[cfg(feature = "ndarray")]
+        {
+            let array = pixel_data.to_ndarray::<u8>().unwrap();
+            assert_eq!(array.shape(), &[1, 1, 2, 3]);
+            let array = pixel_data.to_ndarray_with_options::<u8>(&options).unwrap();
+            assert_eq!(array.shape(), &[1, 1, 2, 1]);
+        }
+
+        #[cfg(feature = "image")]
+        {
+            let image = pixel_data.to_dynamic_image(0).unwrap();
+            assert_eq!(image.color(), image::ColorType::Rgb8);
+            assert_eq!(image.to_rgb8().into_raw(), vec![10, 0, 255, 255, 0, 10]);
+
+            // 16-bit entries yield a 16-bit image
+            let obj = palette_color_image(16, [0, 0x8000, 0xFFFF]);
+            let pixel_data = obj.decode_pixel_data().unwrap();
+            let image = pixel_data.to_dynamic_image(0).unwrap();
+            assert_eq!(image.color(), image::ColorType::Rgb16);
+            assert_eq!(
+                image.to_rgb16().into_raw(),
+                vec![0, 0, 0xFFFF, 0xFFFF, 0, 0]
+            );
+        }
     }
 
+    /// PALETTE COLOR pixel data without lookup tables
+    /// can be decoded, but not converted to RGB
     #[test]
-    fn test_correct_rescale_extracted_without_element() {
-        // RescaleIntercept does not exists for this scan
-        let test_file = dicom_test_files::path("pydicom/MR_small.dcm").unwrap();
-        let obj = open_file(test_file).unwrap();
+    fn test_palette_color_without_lookup_tables() {
+        let obj = image_with_color_attributes("PALETTE COLOR", 1, vec![1, 3]);
         let pixel_data = obj.decode_pixel_data().unwrap();
-        assert_eq!(pixel_data.rescale().unwrap()[0], Rescale::new(1., 0.));
+        assert!(pixel_data.palette().is_none());
+        assert!(pixel_data.to_vec::<u16>().is_err());
+
+        let options = ConvertOptions::new().with_palette(PaletteOption::Indices);
+        let values: Vec<u16> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(values, vec![1, 3]);
+    }
+
+    /// Build a MONOCHROME2 object of 1x4 pixels
+    /// with stored values on both sides of a supplemental palette
+    /// of 3 entries starting at stored value 100,
+    /// and a window which renders the values below it
+    /// from black to white.
+    fn supplemental_palette_image() -> FileDicomObject<InMemDicomObject> {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::tags;
+
+        let mut obj = image_with_color_attributes("MONOCHROME2", 1, vec![0, 99, 100, 102]);
+        obj.put(DataElement::new(
+            tags::COLUMNS,
+            VR::US,
+            dicom_value!(U16, [4]),
+        ));
+        obj.put(DataElement::new(
+            tags::PIXEL_PRESENTATION,
+            VR::CS,
+            PrimitiveValue::from("MIXED"),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_CENTER,
+            VR::DS,
+            PrimitiveValue::from("50"),
+        ));
+        obj.put(DataElement::new(
+            tags::WINDOW_WIDTH,
+            VR::DS,
+            PrimitiveValue::from("100"),
+        ));
+        for tag in [
+            tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+            tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+            tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DESCRIPTOR,
+        ] {
+            obj.put(DataElement::new(
+                tag,
+                VR::US,
+                dicom_value!(U16, [3, 100, 8]),
+            ));
+        }
+        obj.put(DataElement::new(
+            tags::RED_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+            VR::OW,
+            dicom_value!(U16, [10, 128, 255]),
+        ));
+        obj.put(DataElement::new(
+            tags::GREEN_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+            VR::OW,
+            dicom_value!(U16, [0, 0, 0]),
+        ));
+        obj.put(DataElement::new(
+            tags::BLUE_PALETTE_COLOR_LOOKUP_TABLE_DATA,
+            VR::OW,
+            dicom_value!(U16, [255, 128, 10]),
+        ));
+        obj
     }
 
+    /// Monochrome pixel data with a supplemental palette
+    /// is rendered in grayscale below the first mapped value
+    /// and through the palette from it onwards
     #[test]
-    fn test_general_properties_from_16bit() {
-        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
-        let obj = open_file(test_file).unwrap();
+    fn test_supplemental_palette() {
+        let obj = supplemental_palette_image();
         let pixel_data = obj.decode_pixel_data().unwrap();
-
-        assert_eq!(pixel_data.columns(), 128, "Unexpected Columns");
-        assert_eq!(pixel_data.rows(), 128, "Unexpected Rows");
-        assert_eq!(
-            pixel_data.number_of_frames(),
-            1,
-            "Unexpected Number of Frames"
-        );
         assert_eq!(
             pixel_data.photometric_interpretation(),
-            &PhotometricInterpretation::Monochrome2,
-            "Unexpected Photometric Interpretation"
-        );
-        assert_eq!(
-            pixel_data.samples_per_pixel(),
-            1,
-            "Unexpected Samples per Pixel"
-        );
-        assert_eq!(pixel_data.bits_allocated(), 16, "Unexpected Bits Allocated");
-        assert_eq!(pixel_data.bits_stored(), 16, "Unexpected Bits Stored");
-        assert_eq!(pixel_data.high_bit(), 15, "Unexpected High Bit");
-        assert_eq!(
-            pixel_data.pixel_representation(),
-            PixelRepresentation::Signed
+            &PhotometricInterpretation::Monochrome2
         );
+        let palette = pixel_data.palette().unwrap();
+        assert_eq!(palette.first_mapped, 100);
+
+        // other conversions keep the grayscale values by default
+        let values: Vec<u8> = pixel_data.to_vec().unwrap();
+        assert_eq!(values.len(), 4);
+
+        // or map all values through the palette on request
+        let options = ConvertOptions::new().with_palette(PaletteOption::ApplyAll);
+        let values: Vec<u16> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(values, vec![10, 0, 255, 10, 0, 255, 10, 0, 255, 255, 0, 10]);
+
+        #[cfg(feature = "image")]
+        {
+            // 99 is the last grayscale value, 100 is the first palette entry
+            let image = pixel_data.to_dynamic_image(0).unwrap();
+            assert_eq!(image.color(), image::ColorType::Rgb8);
+            assert_eq!(
+                image.to_rgb8().into_raw(),
+                vec![0, 0, 0, 255, 255, 255, 10, 0, 255, 255, 0, 10]
+            );
+
+            // all grayscale
+            let options = ConvertOptions::new().with_palette(PaletteOption::Indices);
+            let image = pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .unwrap();
+            assert_eq!(image.color(), image::ColorType::L8);
+            assert_eq!(image.to_luma8().into_raw(), vec![0, 255, 255, 255]);
+
+            // all through the palette
+            let options = ConvertOptions::new().with_palette(PaletteOption::ApplyAll);
+            let image = pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .unwrap();
+            assert_eq!(
+                image.to_rgb8().into_raw(),
+                vec![10, 0, 255, 10, 0, 255, 10, 0, 255, 255, 0, 10]
+            );
+
+            // 16-bit output scales the palette entries
+            let options = ConvertOptions::new().force_16bit();
+            let image = pixel_data
+                .to_dynamic_image_with_options(0, &options)
+                .unwrap();
+            assert_eq!(image.color(), image::ColorType::Rgb16);
+            assert_eq!(
+                &image.to_rgb16().into_raw()[6..],
+                &[10 * 257, 0, 0xFFFF, 0xFFFF, 0, 10 * 257]
+            );
+        }
     }
 
-    #[cfg(feature = "image")]
+    /// Build a 32-bit monochrome object with a single frame of 2x2 pixels
+    /// and the given pixel representation.
+    fn monochrome_32bit_image(
+        pixel_representation: u16,
+        samples: [u32; 4],
+    ) -> FileDicomObject<InMemDicomObject> {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, "MONOCHROME2"),
+            ),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [32])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [32])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [31])),
+            DataElement::new(
+                tags::PIXEL_REPRESENTATION,
+                VR::US,
+                dicom_value!(U16, [pixel_representation]),
+            ),
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OB,
+                PrimitiveValue::from(
+                    samples
+                        .iter()
+                        .flat_map(|v| v.to_ne_bytes())
+                        .collect::<Vec<u8>>(),
+                ),
+            ),
+        ])
+        .with_meta(test_meta(uids::RT_DOSE_STORAGE))
+        .unwrap()
+    }
+
+    /// 32-bit samples are converted to vectors and arrays,
+    /// failing if they do not fit the requested type.
     #[test]
-    fn test_force_bit_depth_from_16bit() {
-        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+    fn test_32bit_samples() {
+        // unsigned
+        let obj = monochrome_32bit_image(0, [0, 1, 100_000, 4_000_000_000]);
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.bits_allocated(), 32);
+
+        let values: Vec<u32> = pixel_data.to_vec().unwrap();
+        assert_eq!(values, vec![0, 1, 100_000, 4_000_000_000]);
+        let values: Vec<f64> = pixel_data.to_vec().unwrap();
+        assert_eq!(values, vec![0., 1., 100_000., 4_000_000_000.]);
+        assert!(matches!(
+            pixel_data.to_vec::<i32>(),
+            Err(Error(InnerError::InvalidDataType { .. }))
+        ));
+        assert!(matches!(
+            pixel_data.to_vec::<u16>(),
+            Err(Error(InnerError::InvalidDataType { .. }))
+        ));
+
+        // rescale override
+        let options =
+            ConvertOptions::new().with_modality_lut(ModalityLutOption::Override(Rescale {
+                slope: 0.5,
+                intercept: -1.,
+            }));
+        let values: Vec<f64> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(values, vec![-1., -0.5, 49_999., 1_999_999_999.]);
+
+        // signed
+        let obj = monochrome_32bit_image(1, [-2_i32 as u32, 0, 100_000, i32::MAX as u32]);
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let values: Vec<i32> = pixel_data.to_vec().unwrap();
+        assert_eq!(values, vec![-2, 0, 100_000, i32::MAX]);
+        let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+        let values: Vec<i64> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(values, vec![-2, 0, 100_000, i64::from(i32::MAX)]);
+        assert!(matches!(
+            pixel_data.to_vec::<u32>(),
+            Err(Error(InnerError::InvalidDataType { .. }))
+        ));
+
+        // window level to the full 32-bit range
+        let options = ConvertOptions::new().with_voi_lut(VoiLutOption::Custom(WindowLevel {
+            center: 50_000.,
+            width: 100_000.,
+        }));
+        let values: Vec<u32> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(values[0], 0);
+        assert_eq!(values[3], u32::MAX);
+
+        #[cfg(feature = "ndarray")]
+        {
+            let array = pixel_data.to_ndarray::<f32>().unwrap();
+            assert_eq!(array.shape(), &[1, 2, 2, 1]);
+            assert_eq!(array[[0, 1, 0, 0]], 100_000.);
+        }
+    }
+
+    /// The dose values of an RT Dose object can be obtained in Gy
+    /// by mapping the _Dose Grid Scaling_ through a rescale override.
+    #[test]
+    fn test_rt_dose_in_gray() {
+        use dicom_dictionary_std::tags;
+
+        let test_file = dicom_test_files::path("pydicom/rtdose.dcm").unwrap();
         let obj = open_file(test_file).unwrap();
+        let scaling = obj
+            .element(tags::DOSE_GRID_SCALING)
+            .unwrap()
+            .to_float64()
+            .unwrap();
+        assert!(scaling > 0.);
+
         let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.bits_allocated(), 32);
+        let len = pixel_data.number_of_frames() as usize
+            * pixel_data.rows() as usize
+            * pixel_data.columns() as usize;
 
-        // original image has 16 bits stored
+        let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+        let raw: Vec<u32> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(raw.len(), len);
+
+        let options = ConvertOptions::new()
+            .with_modality_lut(ModalityLutOption::Override(Rescale::new(scaling, 0.)));
+        let dose: Vec<f64> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(dose.len(), len);
+        for (dose, raw) in dose.iter().zip(&raw) {
+            assert!((dose - f64::from(*raw) * scaling).abs() < 1e-9);
+        }
+        assert!(dose.iter().any(|v| *v > 0.));
+
+        #[cfg(feature = "ndarray")]
         {
-            let image = pixel_data
-                .to_dynamic_image(0)
-                .expect("Failed to convert to image");
+            let array = pixel_data.to_ndarray_with_options::<f32>(&options).unwrap();
+            assert_eq!(
+                array.shape(),
+                &[
+                    pixel_data.number_of_frames() as usize,
+                    pixel_data.rows() as usize,
+                    pixel_data.columns() as usize,
+                    1
+                ]
+            );
+        }
+    }
 
-            assert!(image.as_luma16().is_some());
+    /// Build a 2x2 monochrome parametric map
+    /// with the given floating point pixel data element
+    /// and optional rescale parameters.
+    fn parametric_map(
+        pixel_data: dicom_core::DataElement<InMemDicomObject>,
+        bits_allocated: u16,
+        rescale: Option<(&str, &str)>,
+    ) -> FileDicomObject<InMemDicomObject> {
+        use dicom_core::{dicom_value, DataElement, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, "MONOCHROME2"),
+            ),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(
+                tags::BITS_ALLOCATED,
+                VR::US,
+                dicom_value!(U16, [bits_allocated]),
+            ),
+            pixel_data,
+        ]);
+        if let Some((slope, intercept)) = rescale {
+            obj.put(DataElement::new(
+                tags::RESCALE_SLOPE,
+                VR::DS,
+                dicom_value!(Str, slope),
+            ));
+            obj.put(DataElement::new(
+                tags::RESCALE_INTERCEPT,
+                VR::DS,
+                dicom_value!(Str, intercept),
+            ));
         }
+        obj.with_meta(test_meta(uids::PARAMETRIC_MAP_STORAGE))
+            .unwrap()
+    }
 
-        // force to 16 bits
+    /// Float Pixel Data and Double Float Pixel Data
+    /// are decoded in the absence of Pixel Data,
+    /// and their samples are converted without lookup tables.
+    #[test]
+    fn test_float_pixel_data() {
+        use dicom_core::{dicom_value, DataElement, VR};
+        use dicom_dictionary_std::tags;
+
+        let obj = parametric_map(
+            DataElement::new(
+                tags::FLOAT_PIXEL_DATA,
+                VR::OF,
+                dicom_value!(F32, [-1.5, 0., 0.25, 1000.]),
+            ),
+            32,
+            None,
+        );
+        let props = ImagingProperties::from_object(&obj).unwrap();
+        assert_eq!(props.sample_format(), SampleFormat::Float32);
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.sample_format(), SampleFormat::Float32);
+        assert_eq!(pixel_data.bits_allocated(), 32);
+        assert_eq!(pixel_data.defaulted_attributes(), &[]);
+        let values: Vec<f32> = pixel_data.to_vec().unwrap();
+        assert_eq!(values, vec![-1.5, 0., 0.25, 1000.]);
+        assert!(matches!(
+            pixel_data.to_vec::<u16>(),
+            Err(Error(InnerError::InvalidDataType { .. }))
+        ));
+
+        // modality rescale is still applied
+        let obj = parametric_map(
+            DataElement::new(
+                tags::FLOAT_PIXEL_DATA,
+                VR::OF,
+                dicom_value!(F32, [-1.5, 0., 0.25, 1000.]),
+            ),
+            32,
+            Some(("2", "1")),
+        );
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let values: Vec<f32> = pixel_data.to_vec().unwrap();
+        assert_eq!(values, vec![-2., 1., 1.5, 2001.]);
+        let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+        let values: Vec<f32> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(values, vec![-1.5, 0., 0.25, 1000.]);
+
+        #[cfg(feature = "ndarray")]
         {
-            let options = ConvertOptions::new().force_16bit();
+            let array = pixel_data.to_ndarray::<f32>().unwrap();
+            assert_eq!(array.shape(), &[1, 2, 2, 1]);
+            assert_eq!(array[[0, 1, 0, 0]], 1.5);
+        }
+
+        #[cfg(feature = "image")]
+        {
+            // normalized in the absence of a window
+            let image = pixel_data.to_dynamic_image(0).unwrap();
+            let image = image.as_luma16().unwrap();
+            let pixels: Vec<u16> = image.pixels().map(|p| p.0[0]).collect();
+            assert!(pixels.windows(2).all(|w| w[0] < w[1]), "{:?}", pixels);
+            assert_eq!(pixels[0], 0);
+            assert_eq!(pixels[3], u16::MAX);
+
+            let options = ConvertOptions::new()
+                .with_voi_lut(VoiLutOption::Custom(WindowLevel {
+                    center: 1.,
+                    width: 6.,
+                }))
+                .force_8bit();
             let image = pixel_data
                 .to_dynamic_image_with_options(0, &options)
-                .expect("Failed to convert to image");
+                .unwrap();
+            let image = image.as_luma8().unwrap();
+            let pixels: Vec<u8> = image.pixels().map(|p| p.0[0]).collect();
+            assert_eq!(pixels[0], 0);
+            assert!(pixels[1] > 0 && pixels[1] < pixels[2]);
+            assert_eq!(pixels[3], 255);
+        }
+
+        // double precision
+        let obj = parametric_map(
+            DataElement::new(
+                tags::DOUBLE_FLOAT_PIXEL_DATA,
+                VR::OD,
+                dicom_value!(F64, [0.1, -0.2, 1e-9, 1e100]),
+            ),
+            64,
+            None,
+        );
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.sample_format(), SampleFormat::Float64);
+        assert_eq!(pixel_data.bits_allocated(), 64);
+        let values: Vec<f64> = pixel_data.to_vec().unwrap();
+        assert_eq!(values, vec![0.1, -0.2, 1e-9, 1e100]);
+        let frame: Vec<f64> = pixel_data.to_vec_frame(0).unwrap();
+        assert_eq!(frame, values);
+    }
+
+    /// Build a 1-bit monochrome object with 3 frames of 3x3 pixels,
+    /// so that the frames after the first one
+    /// do not start on a byte boundary.
+    fn segmentation_3x3(frames: &[[u8; 9]; 3]) -> FileDicomObject<InMemDicomObject> {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let mut data = vec![0_u8; 4];
+        for (i, bit) in frames.iter().flatten().enumerate() {
+            data[i / 8] |= bit << (i % 8);
+        }
+
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, "MONOCHROME2"),
+            ),
+            DataElement::new(tags::NUMBER_OF_FRAMES, VR::IS, dicom_value!(Str, "3")),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [3])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [3])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(data)),
+        ])
+        .with_meta(test_meta(uids::SEGMENTATION_STORAGE))
+        .unwrap()
+    }
 
-            assert!(image.as_luma16().is_some());
+    /// Samples of 1 bit are unpacked to one byte per sample,
+    /// including frames which do not start on a byte boundary.
+    #[test]
+    fn test_1bit_samples_not_byte_aligned() {
+        let frames = [
+            [1, 0, 0, 0, 1, 0, 0, 0, 1],
+            [0, 1, 1, 1, 0, 0, 1, 0, 1],
+            [1, 1, 1, 0, 0, 0, 0, 1, 1],
+        ];
+        let obj = segmentation_3x3(&frames);
+
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.bits_allocated(), 1);
+        assert_eq!(pixel_data.trailing_bytes(), 0);
+        assert_eq!(pixel_data.data(), frames.concat());
+        for (i, expected) in frames.iter().enumerate() {
+            assert_eq!(pixel_data.frame_data(i as u32).unwrap(), expected);
         }
+        let values: Vec<u8> = pixel_data.to_vec().unwrap();
+        assert_eq!(values, frames.concat());
+
+        for (i, expected) in frames.iter().enumerate() {
+            let pixel_data = obj.decode_pixel_data_frame(i as u32).unwrap();
+            assert_eq!(pixel_data.data(), expected, "frame #{}", i);
+            let values: Vec<u8> = pixel_data.to_vec().unwrap();
+            assert_eq!(&values, expected, "frame #{}", i);
+        }
+        assert!(matches!(
+            obj.decode_pixel_data_frame(3),
+            Err(Error(InnerError::FrameOutOfRange { .. }))
+        ));
 
-        // force to 8 bits
+        #[cfg(feature = "ndarray")]
         {
-            let options = ConvertOptions::new().force_8bit();
-            let image = pixel_data
-                .to_dynamic_image_with_options(0, &options)
-                .expect("Failed to convert to image");
-
-            assert!(image.as_luma8().is_some());
+            let array = pixel_data.to_ndarray::<u8>().unwrap();
+            assert_eq!(array.shape(), &[3, 3, 3, 1]);
+            assert_eq!(array[[1, 0, 1, 0]], 1);
+            assert_eq!(array[[1, 1, 1, 0]], 0);
+            assert_eq!(array[[2, 2, 2, 0]], 1);
         }
     }
 
-    #[cfg(feature = "image")]
+    /// The binary masks of a segmentation are unpacked from 1 bit samples.
     #[test]
-    fn test_force_bit_depth_from_rgb() {
-        let test_file = dicom_test_files::path("pydicom/color-px.dcm").unwrap();
+    fn test_segmentation_1bit() {
+        let test_file = dicom_test_files::path("pydicom/liver.dcm").unwrap();
         let obj = open_file(test_file).unwrap();
         let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.bits_allocated(), 1);
+        assert_eq!(pixel_data.number_of_frames(), 3);
+        assert_eq!(pixel_data.rows(), 512);
+        assert_eq!(pixel_data.columns(), 512);
+
+        let values: Vec<u8> = pixel_data.to_vec().unwrap();
+        assert_eq!(values.len(), 3 * 512 * 512);
+        assert!(values.iter().all(|v| *v <= 1));
+        let row = |frame: usize, row: usize| &values[(frame * 512 + row) * 512..][..512];
+        assert_eq!(row(0, 155)[180..183], [0, 1, 1]);
+        assert_eq!(row(0, 155)[310..314], [1, 0, 1, 0]);
+        assert_eq!(row(0, 254)[78..81], [0, 1, 1]);
+        assert_eq!(row(0, 254)[304..310], [1, 0, 0, 1, 1, 0]);
+        assert_eq!(row(0, 511)[511], 0);
+        assert_eq!(row(1, 511)[511], 0);
+        assert_eq!(row(2, 511)[511], 1);
+
+        // the same values are obtained from a single frame
+        let frame = obj.decode_pixel_data_frame(2).unwrap();
+        let frame_values: Vec<u8> = frame.to_vec().unwrap();
+        assert_eq!(frame_values, &values[2 * 512 * 512..]);
 
-        // original image is RGB with 8 bits per sample
+        #[cfg(feature = "ndarray")]
         {
-            let image = pixel_data
-                .to_dynamic_image(0)
-                .expect("Failed to convert to image");
-
-            assert!(image.as_rgb8().is_some());
+            let array = pixel_data.to_ndarray::<u8>().unwrap();
+            assert_eq!(array.shape(), &[3, 512, 512, 1]);
+            assert_eq!(array[[0, 155, 181, 0]], 1);
+            assert_eq!(array[[2, 511, 511, 0]], 1);
         }
+    }
 
-        // force to 16 bits
-        {
-            let options = ConvertOptions::new().force_16bit();
-            let image = pixel_data
-                .to_dynamic_image_with_options(0, &options)
-                .expect("Failed to convert to image");
+    /// Build an 8-bit monochrome object with 3 frames of 4x8 pixels,
+    /// followed by the given number of trailing bytes.
+    fn multi_frame_with_trailing_bytes(trailing_bytes: usize) -> FileDicomObject<InMemDicomObject> {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let mut data: Vec<u8> = (0..3).flat_map(|frame| vec![frame as u8; 32]).collect();
+        data.resize(data.len() + trailing_bytes, 0xff);
+
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, "MONOCHROME2"),
+            ),
+            DataElement::new(tags::NUMBER_OF_FRAMES, VR::IS, dicom_value!(Str, "3")),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [4])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [7])),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(data)),
+        ])
+        .with_meta(test_meta(uids::SECONDARY_CAPTURE_IMAGE_STORAGE))
+        .unwrap()
+    }
 
-            assert!(image.as_rgb16().is_some());
+    /// Bytes after the last frame are left out of the decoded pixel data
+    #[test]
+    fn test_trailing_bytes_after_last_frame() {
+        for trailing_bytes in [0, 1, 17] {
+            let obj = multi_frame_with_trailing_bytes(trailing_bytes);
+
+            let pixel_data = obj.decode_pixel_data().unwrap();
+            assert_eq!(pixel_data.number_of_frames(), 3);
+            assert_eq!(pixel_data.trailing_bytes(), trailing_bytes);
+            assert_eq!(pixel_data.data().len(), 3 * 32);
+            assert_eq!(pixel_data.frame_data(2).unwrap(), &[2; 32][..]);
+            assert!(pixel_data.frame_data(3).is_err());
+            assert_eq!(pixel_data.to_owned().trailing_bytes(), trailing_bytes);
+
+            let frame = obj.decode_pixel_data_frame(2).unwrap();
+            assert_eq!(frame.trailing_bytes(), trailing_bytes);
+            assert_eq!(frame.data(), &[2; 32][..]);
         }
+    }
 
-        // force to 8 bits
+    /// Consuming the decoded pixel data does not copy owned samples
+    #[test]
+    fn test_into_owned_moves_samples() {
+        let obj = multi_frame_with_trailing_bytes(0);
+
+        // borrowed samples are copied
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let owned = pixel_data.clone().into_owned();
+        assert_eq!(owned.data(), pixel_data.data());
+        assert_eq!(owned.number_of_frames(), 3);
+
+        // owned samples are moved
+        let ptr = owned.data().as_ptr();
+        let owned = owned.into_owned();
+        assert_eq!(owned.data().as_ptr(), ptr);
+        let bytes = owned.clone().into_bytes();
+        assert_eq!(bytes.len(), 3 * 32);
+
+        let (bytes, properties) = owned.into_parts();
+        assert_eq!(bytes.as_ptr(), ptr);
+        assert_eq!(bytes.len(), 3 * 32);
+        assert_eq!(bytes[64..], [2; 32]);
+        assert!(properties.data().is_empty());
+        assert_eq!(properties.rows(), 4);
+        assert_eq!(properties.columns(), 8);
+        assert_eq!(properties.number_of_frames(), 3);
+        assert!(properties.frame_data(0).is_err());
+    }
+
+    /// More bytes after the last frame than tolerated
+    /// result in an error
+    #[test]
+    fn test_excess_trailing_bytes() {
+        // a full frame more than declared
+        let obj = multi_frame_with_trailing_bytes(32);
+        assert!(matches!(
+            obj.decode_pixel_data(),
+            Err(Error(InnerError::ExcessTrailingBytes {
+                trailing_bytes: 32,
+                max_trailing_bytes: 31,
+                ..
+            }))
+        ));
+        assert!(obj.decode_pixel_data_frame(0).is_err());
+
+        #[cfg(not(feature = "gdcm"))]
         {
-            let options = ConvertOptions::new().force_8bit();
-            let image = pixel_data
-                .to_dynamic_image_with_options(0, &options)
-                .expect("Failed to convert to image");
+            let obj = multi_frame_with_trailing_bytes(17);
+            let options = DecodeOptions::new().max_trailing_bytes(1);
+            assert!(matches!(
+                obj.decode_pixel_data_with_options(&options),
+                Err(Error(InnerError::ExcessTrailingBytes {
+                    trailing_bytes: 17,
+                    max_trailing_bytes: 1,
+                    ..
+                }))
+            ));
 
-            assert!(image.as_rgb8().is_some());
+            let options = DecodeOptions::new().max_trailing_bytes(32);
+            let obj = multi_frame_with_trailing_bytes(32);
+            let pixel_data = obj.decode_pixel_data_with_options(&options).unwrap();
+            assert_eq!(pixel_data.trailing_bytes(), 32);
+            assert_eq!(pixel_data.data().len(), 3 * 32);
         }
     }
 
@@ -2545,6 +8317,29 @@ mod tests {
         }
     }
 
+    /// Requesting a lower resolution level
+    /// for pixel data in a transfer syntax which does not support it
+    /// should decode the pixel data at full resolution
+    #[test]
+    fn test_resolution_level_ignored_without_support() {
+        let path =
+            dicom_test_files::path("pydicom/CT_small.dcm").expect("test DICOM file should exist");
+        let obj = open_file(&path).unwrap();
+        let options = DecodeOptions::new().resolution_level(1);
+
+        let pixel_data = obj.decode_pixel_data_with_options(&options).unwrap();
+        assert_eq!(pixel_data.declared_dimensions(), None);
+        assert_eq!(pixel_data.rows(), 128);
+        assert_eq!(pixel_data.columns(), 128);
+
+        let pixel_data = obj
+            .decode_pixel_data_frame_with_options(0, &options)
+            .unwrap();
+        assert_eq!(pixel_data.declared_dimensions(), None);
+        assert_eq!(pixel_data.rows(), 128);
+        assert_eq!(pixel_data.columns(), 128);
+    }
+
     #[cfg(not(feature = "gdcm"))]
     mod not_gdcm {
         #[cfg(feature = "ndarray")]
@@ -2793,10 +8588,13 @@ mod tests {
         //
         // jpeg-ls encoding
         #[cfg_attr(
-            feature = "charls",
+            any(feature = "charls", feature = "jpegls-rust"),
             case("pydicom/emri_small_jpeg_ls_lossless.dcm", 10)
         )]
-        #[cfg_attr(feature = "charls", case("pydicom/MR_small_jpeg_ls_lossless.dcm", 1))]
+        #[cfg_attr(
+            any(feature = "charls", feature = "jpegls-rust"),
+            case("pydicom/MR_small_jpeg_ls_lossless.dcm", 1)
+        )]
         //
         // sample precision of 12 not supported yet
         #[should_panic(expected = "Unsupported(SamplePrecision(12))")]
@@ -2853,8 +8651,14 @@ mod tests {
         #[case("pydicom/SC_rgb_rle_2frame.dcm", 0)]
         #[case("pydicom/SC_rgb_rle_2frame.dcm", 1)]
         #[case("pydicom/JPEG2000_UNC.dcm", 0)]
-        #[cfg_attr(feature = "charls", case("pydicom/emri_small_jpeg_ls_lossless.dcm", 5))]
-        #[cfg_attr(feature = "charls", case("pydicom/MR_small_jpeg_ls_lossless.dcm", 0))]
+        #[cfg_attr(
+            any(feature = "charls", feature = "jpegls-rust"),
+            case("pydicom/emri_small_jpeg_ls_lossless.dcm", 5)
+        )]
+        #[cfg_attr(
+            any(feature = "charls", feature = "jpegls-rust"),
+            case("pydicom/MR_small_jpeg_ls_lossless.dcm", 0)
+        )]
         fn test_decode_pixel_data_individual_frames(#[case] value: &str, #[case] frame: u32) {
             use crate::PixelDecoder as _;
             use std::path::Path;
@@ -2878,6 +8682,355 @@ mod tests {
             ));
             image.save(image_path).unwrap();
         }
+
+        /// JPEG 2000 color samples declared as `YBR_ICT` or `YBR_RCT`
+        /// are already in RGB once decoded,
+        /// on both the full and the per-frame decoding paths
+        #[cfg(all(feature = "image", any(feature = "openjp2", feature = "openjpeg-sys")))]
+        #[rstest]
+        #[case("pydicom/US1_J2KR.dcm", "YBR_RCT", true)]
+        #[case("pydicom/US1_J2KI.dcm", "YBR_ICT", false)]
+        fn test_decode_jpeg2k_ybr_ict_rct(
+            #[case] value: &str,
+            #[case] declared: &str,
+            #[case] lossless: bool,
+        ) {
+            use crate::{ImagingProperties, PhotometricInterpretation, PixelDecoder as _};
+
+            let reference = dicom_test_files::path("pydicom/US1_UNCR.dcm").unwrap();
+            let expected = dicom_object::open_file(reference)
+                .unwrap()
+                .decode_pixel_data()
+                .unwrap()
+                .to_dynamic_image(0)
+                .unwrap()
+                .to_rgb8();
+
+            let test_file = dicom_test_files::path(value).unwrap();
+            let obj = dicom_object::open_file(test_file).unwrap();
+            let props = ImagingProperties::from_object(&obj).unwrap();
+            let declared = PhotometricInterpretation::from(declared);
+            assert!(matches!(
+                declared,
+                PhotometricInterpretation::YbrIct | PhotometricInterpretation::YbrRct
+            ));
+            assert_eq!(props.photometric_interpretation(), &declared);
+
+            for pixel_data in [
+                obj.decode_pixel_data().unwrap(),
+                obj.decode_pixel_data_frame(0).unwrap(),
+            ] {
+                assert_eq!(
+                    pixel_data.photometric_interpretation(),
+                    &PhotometricInterpretation::Rgb
+                );
+                assert_eq!(
+                    pixel_data.original_photometric_interpretation(),
+                    Some(&declared)
+                );
+
+                let image = pixel_data.to_dynamic_image(0).unwrap().to_rgb8();
+                assert_eq!(image.dimensions(), expected.dimensions());
+                if lossless {
+                    assert!(image == expected, "decoded image differs from reference");
+                } else {
+                    // no color conversion on top of the decoder's
+                    // leaves only the error of lossy compression
+                    let total_error: u64 = image
+                        .as_raw()
+                        .iter()
+                        .zip(expected.as_raw())
+                        .map(|(a, b)| u64::from(a.abs_diff(*b)))
+                        .sum();
+                    let mean_error = total_error as f64 / image.as_raw().len() as f64;
+                    assert!(mean_error < 4., "mean error too large: {}", mean_error);
+                }
+            }
+        }
+
+        /// Without a JPEG 2000 decoder,
+        /// strict decoding names the Cargo feature to enable,
+        /// whereas lenient decoding keeps the encoded fragments
+        #[cfg(not(any(feature = "openjp2", feature = "openjpeg-sys")))]
+        #[rstest]
+        #[case("pydicom/emri_small_jpeg_2k_lossless.dcm", 10)]
+        #[case("pydicom/MR_small_jp2klossless.dcm", 1)]
+        fn test_decode_pixel_data_lenient_without_decoder(
+            #[case] value: &str,
+            #[case] frames: u32,
+        ) {
+            use crate::{
+                encapsulation, DecodeOptions, ImagingProperties, PixelDecoder as _, SampleState,
+            };
+            use dicom_core::DicomValue;
+            use dicom_dictionary_std::tags;
+
+            let test_file = dicom_test_files::path(value).unwrap();
+            let obj = dicom_object::open_file(test_file).unwrap();
+            let DicomValue::PixelSequence(seq) = obj.element(tags::PIXEL_DATA).unwrap().value()
+            else {
+                panic!("expected encapsulated pixel data");
+            };
+
+            let e = obj.decode_pixel_data().unwrap_err();
+            assert!(
+                e.to_string()
+                    .contains("enable Cargo feature `openjp2` or `openjpeg-sys`"),
+                "unexpected error: {}",
+                e
+            );
+
+            let pixel_data = obj.decode_pixel_data_lenient().unwrap();
+            let properties = ImagingProperties::from_object(&obj).unwrap();
+            assert_eq!(pixel_data.number_of_frames(), frames);
+            assert_eq!(pixel_data.rows(), u32::from(properties.rows()));
+            assert_eq!(pixel_data.columns(), u32::from(properties.columns()));
+            assert_eq!(pixel_data.bits_stored(), properties.bits_stored());
+            assert_eq!(
+                pixel_data.photometric_interpretation(),
+                properties.photometric_interpretation()
+            );
+            assert!(pixel_data.data().is_empty());
+            match pixel_data.samples() {
+                SampleState::EncodedOnly {
+                    ts_uid,
+                    fragments,
+                    offset_table,
+                } => {
+                    assert_eq!(ts_uid, "1.2.840.10008.1.2.4.90");
+                    assert_eq!(&fragments[..], seq.fragments());
+                    assert_eq!(&offset_table[..], seq.offset_table());
+                }
+                samples => panic!("unexpected sample state {:?}", samples),
+            }
+
+            // the samples cannot be converted
+            assert!(pixel_data.to_vec::<u16>().unwrap_err().is_not_decoded());
+            assert!(pixel_data.frame_data(0).unwrap_err().is_not_decoded());
+            assert_eq!(pixel_data.frames().len(), 0);
+            #[cfg(feature = "image")]
+            assert!(pixel_data.to_dynamic_image(0).unwrap_err().is_not_decoded());
+
+            // the fragments are kept when detached from the object
+            let owned = pixel_data.to_owned();
+            assert_eq!(owned.samples(), pixel_data.samples());
+
+            // a single frame only keeps the fragments of that frame
+            let frame = frames - 1;
+            let options = DecodeOptions::new().encoded_fallback(true);
+            let pixel_data = obj
+                .decode_pixel_data_frame_with_options(frame, &options)
+                .unwrap();
+            assert_eq!(pixel_data.number_of_frames(), 1);
+            match pixel_data.samples() {
+                SampleState::EncodedOnly {
+                    fragments,
+                    offset_table,
+                    ..
+                } => {
+                    assert_eq!(
+                        fragments.concat(),
+                        encapsulation::frame_data(seq, frames, frame).unwrap()[..]
+                    );
+                    assert!(offset_table.is_empty());
+                }
+                samples => panic!("unexpected sample state {:?}", samples),
+            }
+        }
+
+        /// The progress callback is called once per decoded frame
+        #[cfg(feature = "jpeg")]
+        #[test]
+        fn test_decode_pixel_data_with_progress() {
+            use crate::{DecodeOptions, PixelDecoder as _};
+            use std::sync::{Arc, Mutex};
+
+            let test_file = dicom_test_files::path("pydicom/color3d_jpeg_baseline.dcm").unwrap();
+            let obj = dicom_object::open_file(test_file).unwrap();
+
+            let calls = Arc::new(Mutex::new(Vec::new()));
+            let options = DecodeOptions::new().on_progress({
+                let calls = Arc::clone(&calls);
+                move |done, total| calls.lock().unwrap().push((done, total))
+            });
+            let pixel_data = obj.decode_pixel_data_with_options(&options).unwrap();
+            assert_eq!(pixel_data.number_of_frames(), 120);
+
+            let calls = calls.lock().unwrap();
+            assert_eq!(calls.len(), 120);
+            for (i, (done, total)) in calls.iter().enumerate() {
+                assert_eq!(*done, i as u32 + 1);
+                assert_eq!(*total, 120);
+            }
+        }
+
+        /// Setting the cancellation token stops decoding before the next frame
+        #[cfg(feature = "jpeg")]
+        #[test]
+        fn test_decode_pixel_data_cancelled() {
+            use crate::{DecodeOptions, PixelDecoder as _};
+            use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+            use std::sync::Arc;
+
+            let test_file = dicom_test_files::path("pydicom/color3d_jpeg_baseline.dcm").unwrap();
+            let obj = dicom_object::open_file(test_file).unwrap();
+
+            let token = Arc::new(AtomicBool::new(false));
+            let frames_done = Arc::new(AtomicU32::new(0));
+            let options = DecodeOptions::new()
+                .cancel_token(Arc::clone(&token))
+                .on_progress({
+                    let token = Arc::clone(&token);
+                    let frames_done = Arc::clone(&frames_done);
+                    move |done, _| {
+                        frames_done.store(done, Ordering::SeqCst);
+                        if done == 5 {
+                            token.store(true, Ordering::SeqCst);
+                        }
+                    }
+                });
+
+            let err = obj.decode_pixel_data_with_options(&options).unwrap_err();
+            assert!(err.is_cancelled(), "unexpected error: {}", err);
+            assert_eq!(frames_done.load(Ordering::SeqCst), 5);
+
+            // decoding a single frame is also cancelled
+            let err = obj
+                .decode_pixel_data_frame_with_options(0, &options)
+                .unwrap_err();
+            assert!(err.is_cancelled());
+        }
+
+        /// Conversion of all frames reports progress
+        /// and can be cancelled between frames
+        #[cfg(feature = "jpeg")]
+        #[test]
+        fn test_to_vec_with_progress_and_cancellation() {
+            use crate::{ConvertOptions, PixelDecoder as _};
+            use std::sync::atomic::{AtomicBool, Ordering};
+            use std::sync::{Arc, Mutex};
+
+            let test_file = dicom_test_files::path("pydicom/color3d_jpeg_baseline.dcm").unwrap();
+            let obj = dicom_object::open_file(test_file).unwrap();
+            let pixel_data = obj.decode_pixel_data().unwrap();
+
+            let calls = Arc::new(Mutex::new(Vec::new()));
+            let options = ConvertOptions::new().on_progress({
+                let calls = Arc::clone(&calls);
+                move |done, total| calls.lock().unwrap().push((done, total))
+            });
+            let values: Vec<u8> = pixel_data.to_vec_with_options(&options).unwrap();
+            assert_eq!(values.len(), pixel_data.data().len());
+            assert_eq!(calls.lock().unwrap().len(), 120);
+            assert_eq!(calls.lock().unwrap().last(), Some(&(120, 120)));
+
+            let token = Arc::new(AtomicBool::new(false));
+            let options = ConvertOptions::new()
+                .cancel_token(Arc::clone(&token))
+                .on_progress({
+                    let token = Arc::clone(&token);
+                    move |done, _| {
+                        if done == 10 {
+                            token.store(true, Ordering::SeqCst);
+                        }
+                    }
+                });
+            let err = pixel_data.to_vec_with_options::<u8>(&options).unwrap_err();
+            assert!(err.is_cancelled());
+        }
+
+        /// Decoding JPEG 2000 at resolution level 1
+        /// should yield half the dimensions,
+        /// with content close to a downscaled full resolution frame
+        #[cfg(any(feature = "openjp2", feature = "openjpeg-sys"))]
+        #[test]
+        fn test_decode_jpeg2k_frame_at_lower_resolution() {
+            use crate::{ConvertOptions, DecodeOptions, ModalityLutOption, PixelDecoder as _};
+
+            let test_file = dicom_test_files::path("pydicom/693_J2KR.dcm").unwrap();
+            let obj = dicom_object::open_file(test_file).unwrap();
+
+            let full = obj.decode_pixel_data_frame(0).unwrap();
+            assert_eq!(full.declared_dimensions(), None);
+            let (rows, cols) = (full.rows(), full.columns());
+
+            let options = DecodeOptions::new().resolution_level(1);
+            let reduced = obj
+                .decode_pixel_data_frame_with_options(0, &options)
+                .unwrap();
+            assert_eq!(reduced.declared_dimensions(), Some((rows, cols)));
+            assert_eq!(reduced.rows(), (rows + 1) / 2);
+            assert_eq!(reduced.columns(), (cols + 1) / 2);
+            assert_eq!(reduced.number_of_frames(), 1);
+            assert_eq!(reduced.samples_per_pixel(), full.samples_per_pixel());
+
+            let convert_options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+            let full_values = full
+                .to_vec_frame_with_options::<f64>(0, &convert_options)
+                .unwrap();
+            let reduced_values = reduced
+                .to_vec_frame_with_options::<f64>(0, &convert_options)
+                .unwrap();
+            assert_eq!(
+                reduced_values.len(),
+                (reduced.rows() * reduced.columns()) as usize
+            );
+
+            // compare against the mean of each 2x2 block
+            // in the full resolution frame
+            let (rows, cols) = (rows as usize, cols as usize);
+            let mut total_error = 0.;
+            for (i, value) in reduced_values.iter().enumerate() {
+                let y = i / reduced.columns() as usize * 2;
+                let x = i % reduced.columns() as usize * 2;
+                let block: Vec<f64> = [(y, x), (y, x + 1), (y + 1, x), (y + 1, x + 1)]
+                    .iter()
+                    .filter(|(y, x)| *y < rows && *x < cols)
+                    .map(|(y, x)| full_values[y * cols + x])
+                    .collect();
+                let mean = block.iter().sum::<f64>() / block.len() as f64;
+                total_error += (value - mean).abs();
+            }
+            let mean_error = total_error / reduced_values.len() as f64;
+
+            let min = full_values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = full_values
+                .iter()
+                .copied()
+                .fold(f64::NEG_INFINITY, f64::max);
+            assert!(
+                mean_error < (max - min) * 0.05,
+                "mean error {} too large for range {}..={}",
+                mean_error,
+                min,
+                max
+            );
+        }
+
+        /// Decoding all frames of a JPEG 2000 object at resolution level 1
+        /// should yield half the dimensions for every frame
+        #[cfg(any(feature = "openjp2", feature = "openjpeg-sys"))]
+        #[test]
+        fn test_decode_jpeg2k_at_lower_resolution() {
+            use crate::{DecodeOptions, PixelDecoder as _};
+
+            let test_file =
+                dicom_test_files::path("pydicom/emri_small_jpeg_2k_lossless.dcm").unwrap();
+            let obj = dicom_object::open_file(test_file).unwrap();
+
+            let options = DecodeOptions::new().resolution_level(1);
+            let pixel_data = obj.decode_pixel_data_with_options(&options).unwrap();
+            assert_eq!(pixel_data.declared_dimensions(), Some((64, 64)));
+            assert_eq!(pixel_data.rows(), 32);
+            assert_eq!(pixel_data.columns(), 32);
+            assert_eq!(pixel_data.number_of_frames(), 10);
+            assert_eq!(
+                pixel_data.data().len(),
+                10 * 32 * 32 * (pixel_data.bits_allocated() as usize / 8)
+            );
+            // every frame can be retrieved
+            pixel_data.frame_data(9).unwrap();
+        }
     }
 
     /// Loading a MONOCHROME1 image with encapsulated pixel data
@@ -2895,6 +9048,94 @@ mod tests {
         );
     }
 
+    /// Check that the imaging properties of an object
+    /// match those reported by its decoded pixel data.
+    fn assert_imaging_properties_match(props: &ImagingProperties, decoded: &DecodedPixelData) {
+        assert_eq!(u32::from(props.rows()), decoded.rows());
+        assert_eq!(u32::from(props.columns()), decoded.columns());
+        assert_eq!(props.number_of_frames(), decoded.number_of_frames());
+        assert_eq!(props.samples_per_pixel(), decoded.samples_per_pixel());
+        assert_eq!(props.bits_allocated(), decoded.bits_allocated());
+        assert_eq!(props.bits_stored(), decoded.bits_stored());
+        assert_eq!(props.high_bit(), decoded.high_bit());
+        assert_eq!(props.pixel_representation(), decoded.pixel_representation());
+        assert_eq!(props.planar_configuration(), decoded.planar_configuration());
+        assert_eq!(
+            props.photometric_interpretation(),
+            decoded.photometric_interpretation()
+        );
+        assert_eq!(
+            props.photometric_interpretation_mismatch(),
+            decoded.photometric_interpretation_mismatch()
+        );
+        assert_eq!(
+            props.value_multiplicity_mismatches(),
+            decoded.value_multiplicity_mismatches()
+        );
+        assert_eq!(props.defaulted_attributes(), decoded.defaulted_attributes());
+        assert_eq!(props.voi_luts(), decoded.voi_luts());
+        if !props.rescale().is_empty() {
+            assert_eq!(&props.rescale()[..], decoded.rescale().unwrap());
+        }
+        assert_eq!(props.window(), decoded.window().unwrap());
+    }
+
+    /// Imaging properties can be obtained without decoding,
+    /// and agree with the decoded pixel data.
+    #[test]
+    fn test_imaging_properties_match_decoded() {
+        for file in [
+            "pydicom/CT_small.dcm",
+            "pydicom/MR_small.dcm",
+            "pydicom/SC_rgb_16bit.dcm",
+        ] {
+            let path = dicom_test_files::path(file).unwrap();
+            let obj = open_file(path).unwrap();
+            let props = ImagingProperties::from_object(&obj).unwrap();
+            let decoded = obj.decode_pixel_data().unwrap();
+            assert_imaging_properties_match(&props, &decoded);
+        }
+
+        let path = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(path).unwrap();
+        let props = ImagingProperties::from_object(&obj).unwrap();
+        assert_eq!((props.rows(), props.columns()), (128, 128));
+        assert_eq!(props.bits_stored(), 16);
+        assert_eq!(props.pixel_representation(), PixelRepresentation::Signed);
+        assert_eq!(
+            props.photometric_interpretation(),
+            &PhotometricInterpretation::Monochrome2
+        );
+        assert_eq!(props.rescale_intercept(), &[-1024.]);
+        assert_eq!(props.rescale_slope(), &[1.]);
+    }
+
+    /// Per-frame imaging properties can be narrowed down to a single frame,
+    /// like when decoding a single frame.
+    #[test]
+    fn test_imaging_properties_per_frame() {
+        let obj = multi_frame_with_rescale_slopes(&["1", "2", "3"]);
+
+        let props = ImagingProperties::from_object(&obj).unwrap();
+        assert_eq!(props.number_of_frames(), 3);
+        assert_eq!(props.rescale_intercept(), &[-10., -10., -10.]);
+        assert_eq!(props.rescale_slope(), &[1., 2., 3.]);
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_imaging_properties_match(&props, &decoded);
+
+        let props = props.for_frame(1);
+        assert_eq!(
+            props.rescale(),
+            vec![Rescale {
+                intercept: -10.,
+                slope: 2.
+            }]
+        );
+        let frame = obj.decode_pixel_data_frame(1).unwrap();
+        assert_eq!(&props.rescale()[..], frame.rescale().unwrap());
+        assert_eq!(props.window(), frame.window().unwrap());
+    }
+
     #[cfg(feature = "image")]
     #[test]
     fn test_interleave() {
@@ -2906,4 +9147,292 @@ mod tests {
         let interleaved: Vec<u8> = vec![1, 5, 9, 2, 6, 10, 3, 7, 11, 4, 8, 12];
         assert_eq!(interleave(&planar), interleaved);
     }
+
+    #[test]
+    fn native_frame_size_and_range_do_not_overflow() {
+        assert_eq!(native_frame_size(16, 3, 512, 512), Some(512 * 512 * 3 * 2));
+        assert_eq!(native_frame_size(1, 1, 3, 3), Some(9));
+        // would overflow in 16-bit arithmetic
+        assert_eq!(native_frame_size(u16::MAX, 1, 1, 1), Some(8192));
+        // 65535 x 65535 x 3 x 2 bytes does not fit in 32 bits
+        let largest_frame = native_frame_size(16, 3, u16::MAX.into(), u16::MAX.into());
+        if cfg!(target_pointer_width = "64") {
+            assert_eq!(largest_frame, Some(25_769_017_350));
+        } else {
+            assert_eq!(largest_frame, None);
+        }
+        assert_eq!(
+            native_frame_size(u16::MAX, u16::MAX, u32::MAX, u32::MAX),
+            None
+        );
+
+        assert_eq!(native_frame_range(100, 0), Some(0..100));
+        assert_eq!(native_frame_range(100, 3), Some(300..400));
+        assert_eq!(native_frame_range(usize::MAX, 0), Some(0..usize::MAX));
+        assert_eq!(native_frame_range(usize::MAX, 1), None);
+        assert_eq!(native_frame_range(usize::MAX / 2 + 1, 1), None);
+        assert_eq!(native_frame_range(usize::MAX / 4, u32::MAX), None);
+    }
+
+    #[test]
+    fn check_trailing_bytes_with_huge_frames() {
+        // the frames cannot be addressed, so there are no trailing bytes
+        assert_eq!(
+            check_trailing_bytes(12, usize::MAX / 2, 3, None).unwrap(),
+            0
+        );
+        assert_eq!(check_trailing_bytes(12, 4, u32::MAX, None).unwrap(), 0);
+    }
+
+    /// An object declaring many frames of the largest possible dimensions,
+    /// but with only a few bytes of actual pixel data.
+    fn huge_frames_with_tiny_pixel_data(
+        samples_per_pixel: u16,
+    ) -> FileDicomObject<InMemDicomObject> {
+        use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::{tags, uids};
+
+        let pi = if samples_per_pixel == 3 {
+            "RGB"
+        } else {
+            "MONOCHROME2"
+        };
+
+        InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::SAMPLES_PER_PIXEL,
+                VR::US,
+                dicom_value!(U16, [samples_per_pixel]),
+            ),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, pi),
+            ),
+            DataElement::new(tags::PLANAR_CONFIGURATION, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(
+                tags::NUMBER_OF_FRAMES,
+                VR::IS,
+                dicom_value!(Str, "2147483647"),
+            ),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [u16::MAX])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [u16::MAX])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [16])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [16])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [15])),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PIXEL_DATA,
+                VR::OW,
+                PrimitiveValue::from(vec![0_u8, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]),
+            ),
+        ])
+        .with_meta(test_meta(uids::VL_WHOLE_SLIDE_MICROSCOPY_IMAGE_STORAGE))
+        .unwrap()
+    }
+
+    /// Objects without an image,
+    /// such as structured reports,
+    /// have no pixel data to decode.
+    #[test]
+    fn test_no_pixel_data() {
+        use dicom_core::{DataElement, PrimitiveValue, VR};
+        use dicom_dictionary_std::tags;
+
+        let path = dicom_test_files::path("pydicom/test-SR.dcm").unwrap();
+        let obj = open_file(path).unwrap();
+        assert!(!has_pixel_data(&obj));
+        let err = obj.decode_pixel_data().unwrap_err();
+        assert!(err.is_no_pixel_data(), "unexpected error: {}", err);
+        let err = obj.decode_pixel_data_frame(0).unwrap_err();
+        assert!(err.is_no_pixel_data(), "unexpected error: {}", err);
+
+        let path = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let mut obj = open_file(path).unwrap();
+        assert!(has_pixel_data(&obj));
+
+        // empty pixel data is the same as none
+        obj.put(DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OW,
+            PrimitiveValue::Empty,
+        ));
+        assert!(!has_pixel_data(&obj));
+        let err = obj.decode_pixel_data().unwrap_err();
+        assert!(err.is_no_pixel_data(), "unexpected error: {}", err);
+    }
+
+    /// Missing _Bits Stored_, _High Bit_, and _Samples per Pixel_
+    /// take a default value,
+    /// unless decoding in strict mode.
+    #[test]
+    fn test_missing_attributes_take_defaults() {
+        use dicom_dictionary_std::tags;
+
+        let path = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let obj = open_file(path).unwrap();
+        let expected = obj.decode_pixel_data().unwrap();
+        assert!(expected.defaulted_attributes().is_empty());
+        assert_eq!(expected.bits_allocated(), 16);
+        assert_eq!(expected.bits_stored(), 16);
+        assert_eq!(expected.high_bit(), 15);
+        assert_eq!(expected.samples_per_pixel(), 1);
+        assert_eq!(
+            expected.planar_configuration(),
+            PlanarConfiguration::Standard
+        );
+        assert!(obj.get(tags::PLANAR_CONFIGURATION).is_none());
+
+        for (tag, name) in [
+            (tags::BITS_STORED, AttributeName::BitsStored),
+            (tags::HIGH_BIT, AttributeName::HighBit),
+            (tags::SAMPLES_PER_PIXEL, AttributeName::SamplesPerPixel),
+        ] {
+            let mut obj = obj.clone();
+            assert!(obj.remove_element(tag));
+
+            let props = ImagingProperties::from_object(&obj).unwrap();
+            assert_eq!(props.defaulted_attributes(), &[name]);
+
+            let decoded = obj.decode_pixel_data().unwrap();
+            assert_imaging_properties_match(&props, &decoded);
+            assert_eq!(decoded.defaulted_attributes(), &[name]);
+            assert_eq!(decoded.bits_stored(), expected.bits_stored());
+            assert_eq!(decoded.high_bit(), expected.high_bit());
+            assert_eq!(decoded.samples_per_pixel(), expected.samples_per_pixel());
+            assert_eq!(decoded.data(), expected.data());
+            assert_eq!(
+                decoded.to_vec::<i16>().unwrap(),
+                expected.to_vec::<i16>().unwrap()
+            );
+
+            let frame = obj.decode_pixel_data_frame(0).unwrap();
+            assert_eq!(frame.defaulted_attributes(), &[name]);
+
+            // strict mode reports the missing attribute
+            let options = DecodeOptions::new().strict(true);
+            let result = obj.decode_pixel_data_with_options(&options);
+            assert!(matches!(
+                result,
+                Err(Error(InnerError::GetAttribute {
+                    source: attribute::GetAttributeError::MissingRequired { name: n, .. },
+                }))
+                if n == name
+            ));
+            let result = obj.decode_pixel_data_frame_with_options(0, &options);
+            assert!(result.is_err());
+        }
+
+        // high bit follows the default bits stored
+        let mut obj = obj;
+        obj.remove_element(tags::BITS_STORED);
+        obj.remove_element(tags::HIGH_BIT);
+        let props = ImagingProperties::from_object(&obj).unwrap();
+        assert_eq!(
+            props.defaulted_attributes(),
+            &[AttributeName::BitsStored, AttributeName::HighBit]
+        );
+        assert_eq!(props.bits_stored(), 16);
+        assert_eq!(props.high_bit(), 15);
+
+        // bits allocated has no default
+        obj.remove_element(tags::BITS_ALLOCATED);
+        assert!(matches!(
+            ImagingProperties::from_object(&obj),
+            Err(Error(InnerError::GetAttribute {
+                source: attribute::GetAttributeError::MissingRequired {
+                    name: AttributeName::BitsAllocated,
+                    ..
+                },
+            }))
+        ));
+    }
+
+    const HUGE_FRAMES_LAST_FRAME: u32 = 2_147_483_646;
+
+    /// Frames declared far beyond the actual pixel data
+    /// are reported as errors instead of wrapping around.
+    #[test]
+    fn test_huge_rgb_frames_fail_cleanly() {
+        let obj = huge_frames_with_tiny_pixel_data(3);
+
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(decoded.data().len(), 12);
+
+        if cfg!(target_pointer_width = "64") {
+            assert!(matches!(
+                decoded.frame_data(0),
+                Err(Error(InnerError::FrameOutOfRange {
+                    frame_number: 0,
+                    ..
+                }))
+            ));
+            assert!(matches!(
+                decoded.frame_data(1),
+                Err(Error(InnerError::FrameOutOfRange {
+                    frame_number: 1,
+                    ..
+                }))
+            ));
+        } else {
+            assert!(matches!(
+                decoded.frame_data(0),
+                Err(Error(InnerError::FrameSizeOverflow { .. }))
+            ));
+        }
+        // offset of the last frame does not fit in 64 bits
+        assert!(matches!(
+            decoded.frame_data(HUGE_FRAMES_LAST_FRAME),
+            Err(Error(InnerError::FrameSizeOverflow { .. }))
+        ));
+        assert!(matches!(
+            obj.decode_pixel_data_frame(HUGE_FRAMES_LAST_FRAME),
+            Err(Error(InnerError::FrameSizeOverflow { .. }))
+        ));
+        #[cfg(feature = "image")]
+        assert!(matches!(
+            decoded.to_dynamic_image(HUGE_FRAMES_LAST_FRAME),
+            Err(Error(InnerError::FrameSizeOverflow { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_huge_monochrome_frames_fail_cleanly() {
+        let obj = huge_frames_with_tiny_pixel_data(1);
+
+        let decoded = obj.decode_pixel_data().unwrap();
+        assert_eq!(decoded.data().len(), 12);
+
+        // the last frame's offset only fits in 64 bits
+        let check = |result: Result<_>| {
+            if cfg!(target_pointer_width = "64") {
+                assert!(matches!(
+                    result,
+                    Err(Error(InnerError::FrameOutOfRange {
+                        frame_number: HUGE_FRAMES_LAST_FRAME,
+                        ..
+                    }))
+                ));
+            } else {
+                assert!(matches!(
+                    result,
+                    Err(Error(InnerError::FrameSizeOverflow { .. }))
+                ));
+            }
+        };
+        check(decoded.frame_data(HUGE_FRAMES_LAST_FRAME).map(|_| ()));
+        check(
+            obj.decode_pixel_data_frame(HUGE_FRAMES_LAST_FRAME)
+                .map(|_| ()),
+        );
+        #[cfg(feature = "image")]
+        {
+            let options = ConvertOptions::new().with_modality_lut(ModalityLutOption::None);
+            check(
+                decoded
+                    .to_dynamic_image_with_options(HUGE_FRAMES_LAST_FRAME, &options)
+                    .map(|_| ()),
+            );
+        }
+    }
 }
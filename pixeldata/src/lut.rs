@@ -13,7 +13,8 @@ use num_traits::{NumCast, ToPrimitive};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use snafu::{OptionExt, Snafu};
 
-use crate::{Rescale, WindowLevelTransform};
+use crate::histogram::{stored_value_counts, stored_value_percentile};
+use crate::{PixelPadding, Rescale, VoiLut, WindowLevelTransform};
 
 /// The LUT could not be created:
 /// entry #{index} was mapped to {y_value},
@@ -121,13 +122,7 @@ where
 
         let table: Result<Vec<_>, _> = iter
             .map(|i| {
-                // account for signedness to determine input pixel value
-                let x = if signed && i >= size / 2 {
-                    i as f64 - size as f64
-                } else {
-                    i as f64
-                };
-                let value = f(x);
+                let value = f(stored_value(i, size, signed));
                 T::from(value).context(CreateLutSnafu {
                     index: i,
                     y_value: value,
@@ -175,6 +170,7 @@ where
     ///   (_Pixel Representation_ = 1)
     /// - `rescale`: the rescale parameters
     /// - `samples`: the raw pixel data samples expected to be fed to the LUT
+    /// - `padding`: the pixel padding values to leave out of the normalization
     ///
     /// # Panics
     ///
@@ -184,6 +180,7 @@ where
         signed: bool,
         rescale: Rescale,
         samples: I,
+        padding: Option<PixelPadding>,
     ) -> Result<Self, CreateLutError>
     where
         I: IntoIterator,
@@ -193,7 +190,13 @@ where
         I::Item: NumCast,
         I::Item: ToPrimitive,
     {
-        let samples_f64 = samples.into_iter().filter_map(|v| v.to_f64());
+        // interpret the samples as the LUT does
+        let size = 1_usize << bits_stored as u32;
+        let samples_f64 = samples
+            .into_iter()
+            .filter_map(|v| v.to_usize())
+            .map(|v| stored_value(v & (size - 1), size, signed))
+            .filter(|v| !padding.map_or(false, |padding| padding.contains(*v)));
         let min: f64 = samples_f64.clone().fold(f64::MAX, |a, b| a.min(b));
         let max: f64 = samples_f64.fold(f64::MIN, |a, b| a.max(b));
 
@@ -216,6 +219,63 @@ where
         Self::new_rescale_and_window(bits_stored, signed, rescale, voi)
     }
 
+    /// Create a new LUT containing the modality rescale transformation
+    /// and a window level spanning the given percentiles
+    /// of the rescaled values of the raw samples given.
+    /// The sample type `I` is expected to be either `u8` or `u16`,
+    /// even if the sample is meant to be interpreted as signed.
+    ///
+    /// - `bits_stored`:
+    ///   the number of bits effectively used to represent the sample values
+    ///   (the _Bits Stored_ DICOM attribute)
+    /// - `signed`:
+    ///   whether the input sample values are expected to be signed
+    ///   (_Pixel Representation_ = 1)
+    /// - `rescale`: the rescale parameters
+    /// - `samples`: the raw pixel data samples expected to be fed to the LUT
+    /// - `padding`: the pixel padding values to leave out of the distribution
+    /// - `low`, `high`: the percentiles (from 0 to 100)
+    ///   mapped to the lowest and highest output values
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits_stored` is 0 or too large.
+    pub(crate) fn new_rescale_and_percentile<I>(
+        bits_stored: u16,
+        signed: bool,
+        rescale: Rescale,
+        samples: &[I],
+        padding: Option<PixelPadding>,
+        low: f32,
+        high: f32,
+    ) -> Result<Self, CreateLutError>
+    where
+        I: Copy + Into<usize> + Send + Sync,
+    {
+        let counts = stored_value_counts(bits_stored, signed, samples, padding);
+
+        // a negative slope reverses the order of the rescaled values
+        let (low, high) = if rescale.slope < 0. {
+            (100. - high, 100. - low)
+        } else {
+            (low, high)
+        };
+        let min = stored_value_percentile(&counts, signed, low).unwrap_or(0.);
+        let max = stored_value_percentile(&counts, signed, high).unwrap_or(0.);
+
+        let max = max * rescale.slope + rescale.intercept;
+        let min = min * rescale.slope + rescale.intercept;
+        let (min, max) = if min > max { (max, min) } else { (min, max) };
+
+        // create a linear window level transform
+        let voi = WindowLevelTransform::linear(crate::WindowLevel {
+            width: max - min + 1.,
+            center: (min + max) / 2.,
+        });
+
+        Self::new_rescale_and_window(bits_stored, signed, rescale, voi)
+    }
+
     /// Create a new LUT containing the modality rescale transformation
     /// and the VOI transformation defined by a window level.
     ///
@@ -251,6 +311,42 @@ where
         })
     }
 
+    /// Create a new LUT containing
+    /// a modality rescale transformation
+    /// followed by a tabular VOI LUT transformation.
+    ///
+    /// The amplitude of the output values
+    /// goes from 0 to `2^n - 1`, where `n` is the power of two
+    /// which follows `bits_stored` (or itself if it is a power of two).
+    /// The entries of the VOI LUT are scaled to this range
+    /// according to their number of bits.
+    ///
+    /// - `bits_stored`:
+    ///   the number of bits effectively used to represent the sample values
+    ///   (the _Bits Stored_ DICOM attribute)
+    /// - `signed`:
+    ///   whether the input sample values are expected to be signed
+    ///   (_Pixel Representation_ = 1)
+    /// - `rescale`: the rescale parameters
+    /// - `voi`: the VOI LUT table
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits_stored` is 0 or too large.
+    pub fn new_rescale_and_voi_lut(
+        bits_stored: u16,
+        signed: bool,
+        rescale: Rescale,
+        voi: &VoiLut,
+    ) -> Result<Self, CreateLutError> {
+        let bits_allocated = (bits_stored as usize).next_power_of_two();
+        let y_max = ((1 << bits_allocated) - 1) as f64;
+        Self::new_with_fn(bits_stored, signed, |v| {
+            let v = rescale.apply(v);
+            voi.apply(v, y_max)
+        })
+    }
+
     /// Create a new LUT containing
     /// a VOI transformation defined by a window level.
     ///
@@ -281,6 +377,21 @@ where
         Self::new_with_fn(bits_stored, signed, |v| voi.apply(v, y_max))
     }
 
+    /// Map the stored values in the given pixel padding range
+    /// to the lowest output value, 0.
+    pub(crate) fn mask_padding(mut self, signed: bool, padding: PixelPadding) -> Self {
+        let Some(zero) = T::from(0) else {
+            return self;
+        };
+        let size = self.table.len();
+        for (i, y) in self.table.iter_mut().enumerate() {
+            if padding.contains(stored_value(i, size, signed)) {
+                *y = zero;
+            }
+        }
+        self
+    }
+
     /// Apply the transformation to a single pixel sample value.
     ///
     /// Although the input is expected to be one of `u8`, `u16`, or `u32`,
@@ -326,6 +437,18 @@ where
     }
 }
 
+/// Determine the input pixel value at the given index of a LUT
+/// with `size` entries,
+/// accounting for signedness.
+#[inline]
+pub(crate) fn stored_value(i: usize, size: usize, signed: bool) -> f64 {
+    if signed && i >= size / 2 {
+        i as f64 - size as f64
+    } else {
+        i as f64
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{VoiLutFunction, WindowLevel};
@@ -447,6 +570,7 @@ mod tests {
             false,
             Rescale::new(1., -1024.),
             [0_u16, 1, 2, 500, 23].iter().copied(),
+            None,
         )
         .unwrap();
 
@@ -460,4 +584,75 @@ mod tests {
         let y = lut.get(498_u16);
         assert!(y > 0 && y < 0xFFFF);
     }
+
+    #[test]
+    fn lut_rescale_and_normalize_signed_with_padding() {
+        let padding = PixelPadding {
+            value: -2000,
+            range_limit: None,
+        };
+        let samples = [-2000_i16, -2000, -1000, 0, 1000].map(|v| v as u16);
+
+        // samples are between -2000 and 1000
+        let lut: Lut<u16> =
+            Lut::new_rescale_and_normalize(16, true, Rescale::new(1., 0.), samples, None).unwrap();
+        assert_eq!(lut.get(-2000_i16 as u16), 0);
+        assert_eq!(lut.get(1000_u16), 0xFFFF);
+
+        // samples other than padding are between -1000 and 1000
+        let lut: Lut<u16> =
+            Lut::new_rescale_and_normalize(16, true, Rescale::new(1., 0.), samples, Some(padding))
+                .unwrap();
+        assert_eq!(lut.get(-1000_i16 as u16), 0);
+        assert_eq!(lut.get(1000_u16), 0xFFFF);
+        let y = lut.get(0_u16);
+        assert!(y > 32_000 && y < 33_500, "outcome was {}", y);
+
+        // padding is mapped to 0 when masked
+        let lut = lut.mask_padding(
+            true,
+            PixelPadding {
+                value: -10,
+                range_limit: Some(10),
+            },
+        );
+        assert_eq!(lut.get(-10_i16 as u16), 0);
+        assert_eq!(lut.get(0_u16), 0);
+        assert_eq!(lut.get(10_u16), 0);
+        assert!(lut.get(11_u16) > y);
+        assert!(lut.get(-11_i16 as u16) < y);
+    }
+
+    #[test]
+    fn lut_rescale_and_voi_lut_signed() {
+        // 12-bit signed input, identity table starting at -2048
+        let voi = VoiLut {
+            first_mapped: -2048,
+            bits: 12,
+            data: (0..4096).collect(),
+            explanation: None,
+        };
+        let lut: Lut<u16> =
+            Lut::new_rescale_and_voi_lut(12, true, Rescale::new(1., 0.), &voi).unwrap();
+
+        // -2048
+        assert_eq!(lut.get(0x800_u16), 0);
+        // 2047
+        assert_eq!(lut.get(0x7FF_u16), 0xFFFF);
+
+        // -1 is around the middle
+        let val = lut.get(0xFFF_u16);
+        let expected_range = 32_700..=32_800;
+        assert!(
+            expected_range.contains(&val),
+            "outcome was {}, expected to be in {:?}",
+            val,
+            expected_range,
+        );
+
+        // rescaled values beyond the table are clamped
+        let lut: Lut<u16> =
+            Lut::new_rescale_and_voi_lut(12, true, Rescale::new(1., 3000.), &voi).unwrap();
+        assert_eq!(lut.get(0_u16), 0xFFFF);
+    }
 }
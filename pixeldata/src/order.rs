@@ -0,0 +1,509 @@
+//! Ordering of the frames of a multi-frame object.
+//!
+//! The frames of a multi-frame object
+//! are not necessarily stored in spatial or temporal order.
+//! [`frame_order`] determines the order of the frames
+//! from the first of these sources which describes every frame:
+//!
+//! 1. the _Image Position (Patient)_ in the _Plane Position Sequence_
+//!    of each frame's functional groups,
+//!    as found in enhanced multi-frame objects;
+//! 2. the vector attributes referenced by _Frame Increment Pointer_,
+//!    such as _Slice Location Vector_,
+//!    or the _Detector Vector_ and _Time Slot Vector_
+//!    of nuclear medicine images;
+//! 3. the order in which the frames are stored.
+//!
+//! The resulting [`FrameOrder`] can also be applied
+//! when converting all frames of the decoded pixel data,
+//! by enabling [`sort_frames`](crate::ConvertOptions::sort_frames).
+
+use dicom_core::{DataDictionary, DicomValue, PrimitiveValue, Tag};
+use dicom_dictionary_std::tags;
+use dicom_object::InMemDicomObject;
+use std::cmp::Ordering;
+use std::convert::TryInto;
+
+use crate::series::{dot, slice_normal};
+
+/// The source of the order of the frames in a multi-frame object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FrameOrderSource {
+    /// The _Image Position (Patient)_ of each frame
+    /// in the _Plane Position Sequence_ of its functional groups,
+    /// projected onto the normal of the image plane.
+    PlanePosition,
+    /// The vector attributes referenced by _Frame Increment Pointer_
+    /// with one value per frame,
+    /// compared in the order of the pointers.
+    FrameIncrementPointer(Vec<Tag>),
+    /// The order in which the frames are stored.
+    Stored,
+}
+
+/// The order of the frames of a multi-frame object,
+/// as determined by [`frame_order`].
+///
+/// Frame numbers start at 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameOrder {
+    /// where the order came from
+    source: FrameOrderSource,
+    /// the stored frame numbers, in order
+    permutation: Vec<u32>,
+    /// the key values of each frame in `permutation`
+    keys: Vec<Vec<f64>>,
+}
+
+impl FrameOrder {
+    /// Create the order of the given number of frames as they are stored.
+    pub fn stored(number_of_frames: u32) -> Self {
+        FrameOrder {
+            source: FrameOrderSource::Stored,
+            permutation: (0..number_of_frames).collect(),
+            keys: vec![Vec::new(); number_of_frames as usize],
+        }
+    }
+
+    /// Order frames by their key values,
+    /// keeping frames with equal keys in stored order.
+    fn sorted_by(source: FrameOrderSource, keys: Vec<Vec<f64>>) -> Self {
+        let mut permutation: Vec<u32> = (0..keys.len() as u32).collect();
+        permutation.sort_by(|a, b| compare_keys(&keys[*a as usize], &keys[*b as usize]));
+        let keys = permutation
+            .iter()
+            .map(|frame| keys[*frame as usize].clone())
+            .collect();
+        FrameOrder {
+            source,
+            permutation,
+            keys,
+        }
+    }
+
+    /// Obtain the source of this order.
+    pub fn source(&self) -> &FrameOrderSource {
+        &self.source
+    }
+
+    /// Obtain the stored frame numbers, in order.
+    pub fn permutation(&self) -> &[u32] {
+        &self.permutation
+    }
+
+    /// Obtain the key values by which the frames were ordered,
+    /// for each frame in [`permutation`](Self::permutation).
+    ///
+    /// Frames ordered by plane position have a single key,
+    /// their position along the normal of the image plane.
+    /// Frames ordered by _Frame Increment Pointer_
+    /// have the value of each referenced attribute.
+    /// Frames in stored order have no keys.
+    pub fn keys(&self) -> &[Vec<f64>] {
+        &self.keys
+    }
+
+    /// Obtain the number of frames.
+    pub fn number_of_frames(&self) -> u32 {
+        self.permutation.len() as u32
+    }
+
+    /// Check whether the frames are already stored in this order.
+    pub fn is_stored_order(&self) -> bool {
+        self.permutation
+            .iter()
+            .enumerate()
+            .all(|(i, frame)| i as u32 == *frame)
+    }
+}
+
+/// Determine the order of the frames of a multi-frame object.
+///
+/// The order comes from the first source which describes every frame,
+/// in the order listed in [`FrameOrderSource`].
+/// Frames with equal keys remain in the order in which they are stored.
+///
+/// # Example
+///
+/// ```no_run
+/// # use dicom_object::open_file;
+/// use dicom_pixeldata::frame_order;
+///
+/// let obj = open_file("nm_multiframe.dcm")?;
+/// let order = frame_order(&obj);
+/// for (frame, keys) in order.permutation().iter().zip(order.keys()) {
+///     println!("frame #{}: {:?}", frame, keys);
+/// }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn frame_order<D>(obj: &InMemDicomObject<D>) -> FrameOrder
+where
+    D: DataDictionary + Clone,
+{
+    let number_of_frames: u32 = obj
+        .get(tags::NUMBER_OF_FRAMES)
+        .and_then(|e| e.to_int().ok())
+        .unwrap_or(1);
+
+    if let Some(keys) = plane_position_keys(obj, number_of_frames) {
+        return FrameOrder::sorted_by(FrameOrderSource::PlanePosition, keys);
+    }
+    if let Some((pointers, keys)) = frame_increment_keys(obj, number_of_frames) {
+        return FrameOrder::sorted_by(FrameOrderSource::FrameIncrementPointer(pointers), keys);
+    }
+    FrameOrder::stored(number_of_frames)
+}
+
+/// Collect the position of each frame along the normal of the image plane,
+/// if there is a plane position for every frame and an orientation.
+fn plane_position_keys<D>(obj: &InMemDicomObject<D>, number_of_frames: u32) -> Option<Vec<Vec<f64>>>
+where
+    D: DataDictionary + Clone,
+{
+    let groups = obj
+        .get(tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE)?
+        .items()?;
+    if groups.len() != number_of_frames as usize {
+        return None;
+    }
+    let shared = obj
+        .get(tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE)
+        .and_then(|e| e.items()?.first());
+
+    // the orientation of the first frame which declares it
+    let orientation: [f64; 6] = groups
+        .iter()
+        .chain(shared)
+        .find_map(|group| {
+            read_nested(
+                group,
+                tags::PLANE_ORIENTATION_SEQUENCE,
+                tags::IMAGE_ORIENTATION_PATIENT,
+            )
+        })
+        .or_else(|| read_floats(obj, tags::IMAGE_ORIENTATION_PATIENT))?;
+    let normal = slice_normal(&orientation);
+
+    groups
+        .iter()
+        .map(|group| {
+            let position: [f64; 3] = read_nested(
+                group,
+                tags::PLANE_POSITION_SEQUENCE,
+                tags::IMAGE_POSITION_PATIENT,
+            )?;
+            Some(vec![dot(&position, &normal)])
+        })
+        .collect()
+}
+
+/// Collect the values of the attributes referenced by Frame Increment Pointer
+/// which have one value per frame,
+/// along with their tags.
+fn frame_increment_keys<D>(
+    obj: &InMemDicomObject<D>,
+    number_of_frames: u32,
+) -> Option<(Vec<Tag>, Vec<Vec<f64>>)>
+where
+    D: DataDictionary + Clone,
+{
+    let pointers = match obj.get(tags::FRAME_INCREMENT_POINTER)?.value() {
+        DicomValue::Primitive(PrimitiveValue::Tags(pointers)) => pointers,
+        _ => return None,
+    };
+
+    // leave out pointers to attributes which are not vectors,
+    // such as Frame Time
+    let (pointers, vectors): (Vec<Tag>, Vec<Vec<f64>>) = pointers
+        .iter()
+        .filter_map(|tag| {
+            let values = obj.get(*tag)?.to_multi_float64().ok()?;
+            if values.len() == number_of_frames as usize {
+                Some((*tag, values))
+            } else {
+                None
+            }
+        })
+        .unzip();
+    if pointers.is_empty() {
+        return None;
+    }
+
+    let keys = (0..number_of_frames as usize)
+        .map(|frame| vectors.iter().map(|values| values[frame]).collect())
+        .collect();
+    Some((pointers, keys))
+}
+
+/// Compare the key values of two frames in lexicographical order.
+fn compare_keys(a: &[f64], b: &[f64]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| a.total_cmp(b))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Read a fixed number of floating point values from an attribute.
+fn read_floats<D, const N: usize>(obj: &InMemDicomObject<D>, tag: Tag) -> Option<[f64; N]>
+where
+    D: DataDictionary + Clone,
+{
+    obj.get(tag)?.to_multi_float64().ok()?.try_into().ok()
+}
+
+/// Read a fixed number of floating point values from an attribute
+/// in the first item of a functional group sequence.
+fn read_nested<D, const N: usize>(
+    group: &InMemDicomObject<D>,
+    sequence: Tag,
+    tag: Tag,
+) -> Option<[f64; N]>
+where
+    D: DataDictionary + Clone,
+{
+    read_floats(group.get(sequence)?.items()?.first()?, tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConvertOptions, PixelDecoder};
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement, VR};
+    use dicom_dictionary_std::uids;
+    use dicom_object::{FileDicomObject, FileMetaTableBuilder};
+
+    const AXIAL: [f64; 6] = [1., 0., 0., 0., 1., 0.];
+
+    fn ds(values: &[f64]) -> PrimitiveValue {
+        PrimitiveValue::Strs(values.iter().map(|v| v.to_string()).collect())
+    }
+
+    /// Create an object of 1x1 pixel frames,
+    /// with one frame per pixel value.
+    fn multi_frame_object(
+        pixels: &[u8],
+        elements: Vec<DataElement<InMemDicomObject>>,
+    ) -> FileDicomObject<InMemDicomObject> {
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                dicom_value!(Str, "MONOCHROME2"),
+            ),
+            DataElement::new(
+                tags::NUMBER_OF_FRAMES,
+                VR::IS,
+                dicom_value!(Str, pixels.len().to_string()),
+            ),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [8])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [7])),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(pixels)),
+        ]);
+        for elem in elements {
+            obj.put(elem);
+        }
+        obj.with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                .media_storage_sop_class_uid(uids::ENHANCED_CT_IMAGE_STORAGE)
+                .media_storage_sop_instance_uid("2.25.302617512386931425213546279836580426312"),
+        )
+        .unwrap()
+    }
+
+    /// Create an enhanced multi-frame object
+    /// with the given orientation in the shared functional groups
+    /// and a plane position per frame,
+    /// the pixel value of each frame being its position along the Z axis.
+    fn enhanced_object(
+        orientation: [f64; 6],
+        positions: &[Option<f64>],
+    ) -> FileDicomObject<InMemDicomObject> {
+        let item = |sequence: Tag, tag: Tag, values: &[f64]| {
+            InMemDicomObject::from_element_iter([DataElement::new(
+                sequence,
+                VR::SQ,
+                DataSetSequence::from(vec![InMemDicomObject::from_element_iter([
+                    DataElement::new(tag, VR::DS, ds(values)),
+                ])]),
+            )])
+        };
+        let groups: Vec<_> = positions
+            .iter()
+            .map(|position| match position {
+                Some(z) => item(
+                    tags::PLANE_POSITION_SEQUENCE,
+                    tags::IMAGE_POSITION_PATIENT,
+                    &[0., 0., *z],
+                ),
+                None => InMemDicomObject::new_empty(),
+            })
+            .collect();
+        let pixels: Vec<u8> = positions
+            .iter()
+            .map(|position| position.unwrap_or_default() as u8)
+            .collect();
+
+        multi_frame_object(
+            &pixels,
+            vec![
+                DataElement::new(
+                    tags::SHARED_FUNCTIONAL_GROUPS_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(vec![item(
+                        tags::PLANE_ORIENTATION_SEQUENCE,
+                        tags::IMAGE_ORIENTATION_PATIENT,
+                        &orientation,
+                    )]),
+                ),
+                DataElement::new(
+                    tags::PER_FRAME_FUNCTIONAL_GROUPS_SEQUENCE,
+                    VR::SQ,
+                    DataSetSequence::from(groups),
+                ),
+            ],
+        )
+    }
+
+    /// Create a nuclear medicine multi-frame object
+    /// with the given vectors referenced by Frame Increment Pointer.
+    fn nm_object(pixels: &[u8], vectors: &[(Tag, &[u16])]) -> FileDicomObject<InMemDicomObject> {
+        let pointers: Vec<Tag> = vectors.iter().map(|(tag, _)| *tag).collect();
+        let mut elements = vec![DataElement::new(
+            tags::FRAME_INCREMENT_POINTER,
+            VR::AT,
+            PrimitiveValue::Tags(pointers.into()),
+        )];
+        elements.extend(vectors.iter().map(|(tag, values)| {
+            DataElement::new(
+                *tag,
+                VR::US,
+                PrimitiveValue::U16(values.iter().copied().collect()),
+            )
+        }));
+        multi_frame_object(pixels, elements)
+    }
+
+    #[test]
+    fn order_by_plane_position() {
+        let obj = enhanced_object(AXIAL, &[Some(10.), Some(0.), Some(30.), Some(20.)]);
+        let order = frame_order(&obj);
+        assert_eq!(order.source(), &FrameOrderSource::PlanePosition);
+        assert_eq!(order.permutation(), [1, 0, 3, 2]);
+        assert_eq!(order.keys(), [vec![0.], vec![10.], vec![20.], vec![30.]]);
+        assert!(!order.is_stored_order());
+
+        // the normal points the other way, so the order is reversed
+        let obj = enhanced_object(
+            [1., 0., 0., 0., -1., 0.],
+            &[Some(10.), Some(0.), Some(30.), Some(20.)],
+        );
+        let order = frame_order(&obj);
+        assert_eq!(order.permutation(), [2, 3, 0, 1]);
+        assert_eq!(order.keys(), [vec![-30.], vec![-20.], vec![-10.], vec![0.]]);
+    }
+
+    #[test]
+    fn missing_plane_position_falls_back_to_stored_order() {
+        let obj = enhanced_object(AXIAL, &[Some(10.), None, Some(0.)]);
+        let order = frame_order(&obj);
+        assert_eq!(order.source(), &FrameOrderSource::Stored);
+        assert_eq!(order.permutation(), [0, 1, 2]);
+        assert_eq!(order.keys(), vec![Vec::<f64>::new(); 3]);
+        assert!(order.is_stored_order());
+    }
+
+    #[test]
+    fn order_by_frame_increment_pointer() {
+        let obj = nm_object(
+            &[0, 1, 2, 3],
+            &[
+                (tags::DETECTOR_VECTOR, &[2, 1, 2, 1]),
+                (tags::SLICE_VECTOR, &[1, 2, 2, 1]),
+            ],
+        );
+        let order = frame_order(&obj);
+        assert_eq!(
+            order.source(),
+            &FrameOrderSource::FrameIncrementPointer(vec![
+                tags::DETECTOR_VECTOR,
+                tags::SLICE_VECTOR
+            ])
+        );
+        // by detector first, then by slice
+        assert_eq!(order.permutation(), [3, 1, 0, 2]);
+        assert_eq!(
+            order.keys(),
+            [vec![1., 1.], vec![1., 2.], vec![2., 1.], vec![2., 2.]]
+        );
+
+        // Frame Time is not a vector, so it is left out
+        let mut obj = nm_object(&[0, 1, 2], &[(tags::TIME_SLOT_VECTOR, &[3, 1, 2])]);
+        obj.put(DataElement::new(
+            tags::FRAME_INCREMENT_POINTER,
+            VR::AT,
+            PrimitiveValue::Tags(vec![tags::FRAME_TIME, tags::TIME_SLOT_VECTOR].into()),
+        ));
+        obj.put(DataElement::new(
+            tags::FRAME_TIME,
+            VR::DS,
+            dicom_value!(Str, "100"),
+        ));
+        let order = frame_order(&obj);
+        assert_eq!(
+            order.source(),
+            &FrameOrderSource::FrameIncrementPointer(vec![tags::TIME_SLOT_VECTOR])
+        );
+        assert_eq!(order.permutation(), [1, 2, 0]);
+
+        // only Frame Time, so the stored order applies
+        obj.put(DataElement::new(
+            tags::FRAME_INCREMENT_POINTER,
+            VR::AT,
+            PrimitiveValue::Tags(vec![tags::FRAME_TIME].into()),
+        ));
+        let order = frame_order(&obj);
+        assert_eq!(order.source(), &FrameOrderSource::Stored);
+    }
+
+    #[test]
+    fn convert_frames_in_order() {
+        let obj = enhanced_object(AXIAL, &[Some(10.), Some(0.), Some(30.), Some(20.)]);
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        assert_eq!(pixel_data.frame_order().permutation(), [1, 0, 3, 2]);
+
+        let pixels: Vec<u8> = pixel_data
+            .to_vec_with_options(&ConvertOptions::new())
+            .unwrap();
+        assert_eq!(pixels, [10, 0, 30, 20]);
+
+        let options = ConvertOptions::new().with_sorted_frames(true);
+        let pixels: Vec<u8> = pixel_data.to_vec_with_options(&options).unwrap();
+        assert_eq!(pixels, [0, 10, 20, 30]);
+
+        // a single frame is always in order
+        let frame = obj.decode_pixel_data_frame(2).unwrap();
+        assert!(frame.frame_order().is_stored_order());
+        let pixels: Vec<u8> = frame.to_vec_with_options(&options).unwrap();
+        assert_eq!(pixels, [30]);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn convert_frames_in_order_to_ndarray() {
+        let obj = nm_object(&[0, 1, 2], &[(tags::SLICE_VECTOR, &[3, 1, 2])]);
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let options = ConvertOptions::new().with_sorted_frames(true);
+        let array = pixel_data.to_ndarray_with_options::<u8>(&options).unwrap();
+        assert_eq!(array.shape(), [3, 1, 1, 1]);
+        assert_eq!(array.iter().copied().collect::<Vec<_>>(), [1, 2, 0]);
+    }
+}
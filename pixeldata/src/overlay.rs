@@ -0,0 +1,553 @@
+//! Overlay planes of an image,
+//! such as the annotations in legacy CR and XA objects.
+//!
+//! Each of the repeating groups `6000` to `601E`
+//! may hold an overlay plane,
+//! with its bits packed in the _Overlay Data_ attribute `(60xx,3000)`.
+//! [`overlays`] collects these planes into [`Overlay`] values,
+//! which provide the planes as masks of one byte per pixel.
+//! With the `image` feature,
+//! overlays can also be converted to grayscale images
+//! or composited onto an image of the pixel data.
+//!
+//! Overlays embedded in the unused bits of the pixel data,
+//! a retired form of encoding overlays,
+//! are not supported.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use dicom_object::open_file;
+//! use dicom_pixeldata::{overlay::overlays, PixelDecoder};
+//!
+//! let obj = open_file("cr.dcm")?;
+//! let overlays = overlays(&obj)?;
+//! # #[cfg(feature = "image")]
+//! # {
+//! let mut image = obj.decode_pixel_data()?.to_dynamic_image(0)?;
+//! for overlay in &overlays {
+//!     overlay.composite(&mut image, 0);
+//! }
+//! # }
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use dicom_core::dictionary::TagRange;
+use dicom_core::value::{trim_padding, PrimitiveValue};
+use dicom_core::{DataDictionary, Tag};
+use dicom_dictionary_std::tags;
+use dicom_object::InMemDicomObject;
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+use std::convert::TryFrom;
+
+#[cfg(feature = "image")]
+use image::{DynamicImage, GrayImage, ImageBuffer, Pixel, Primitive};
+
+/// The group of the first overlay plane.
+const FIRST_GROUP: u16 = 0x6000;
+/// The group of the last overlay plane.
+const LAST_GROUP: u16 = 0x601E;
+
+/// An error which may occur when reading the overlay planes of an object.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum OverlayError {
+    #[snafu(display("Missing attribute `{}` of overlay group {:04X}", name, group))]
+    MissingAttribute {
+        group: u16,
+        name: &'static str,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Could not convert attribute `{}` of overlay group {:04X}",
+        name,
+        group
+    ))]
+    ConvertValue {
+        group: u16,
+        name: &'static str,
+        #[snafu(source(from(dicom_core::value::ConvertValueError, Box::from)))]
+        source: Box<dicom_core::value::ConvertValueError>,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display(
+        "Overlay group {:04X} is embedded in unused bits of the pixel data, which is not supported",
+        group
+    ))]
+    EmbeddedOverlay { group: u16, backtrace: Backtrace },
+
+    #[snafu(display(
+        "Overlay data of group {:04X} has {} bits, but {} are required",
+        group,
+        actual,
+        expected
+    ))]
+    InsufficientData {
+        group: u16,
+        expected: u64,
+        actual: u64,
+        backtrace: Backtrace,
+    },
+}
+
+/// Type alias for a result from this module.
+pub type Result<T, E = OverlayError> = std::result::Result<T, E>;
+
+/// An overlay plane,
+/// unpacked to one byte per pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overlay {
+    group: u16,
+    rows: u32,
+    columns: u32,
+    origin: (i32, i32),
+    overlay_type: Option<String>,
+    label: Option<String>,
+    number_of_frames: u32,
+    image_frame_origin: u32,
+    mask: Vec<u8>,
+}
+
+impl Overlay {
+    /// The repeating group of this overlay plane,
+    /// from `0x6000` to `0x601E`.
+    pub fn group(&self) -> u16 {
+        self.group
+    }
+
+    /// The number of rows of the overlay plane.
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// The number of columns of the overlay plane.
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    /// The position of the top left pixel of the overlay
+    /// relative to the image, as a `(row, column)` pair.
+    ///
+    /// As declared in _Overlay Origin_,
+    /// the top left pixel of the image is at `(1, 1)`,
+    /// and values may be negative.
+    pub fn origin(&self) -> (i32, i32) {
+        self.origin
+    }
+
+    /// The overlay type,
+    /// `G` for graphics or `R` for a region of interest.
+    pub fn overlay_type(&self) -> Option<&str> {
+        self.overlay_type.as_deref()
+    }
+
+    /// The overlay label, if present.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// The number of frames in the overlay.
+    pub fn number_of_frames(&self) -> u32 {
+        self.number_of_frames
+    }
+
+    /// The image frame to which the first overlay frame applies,
+    /// where the first image frame is `1`.
+    pub fn image_frame_origin(&self) -> u32 {
+        self.image_frame_origin
+    }
+
+    /// Retrieve the overlay frame which applies to the given image frame,
+    /// where the first image frame is `0`.
+    ///
+    /// Returns `None` if the overlay does not apply to the frame.
+    pub fn frame_for_image(&self, image_frame: u32) -> Option<u32> {
+        let frame = image_frame.checked_sub(self.image_frame_origin - 1)?;
+        if frame < self.number_of_frames {
+            Some(frame)
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve the mask of an overlay frame,
+    /// with one byte per pixel in row-major order,
+    /// `1` where the overlay is set and `0` otherwise.
+    ///
+    /// Returns `None` if the frame does not exist.
+    pub fn mask(&self, frame: u32) -> Option<&[u8]> {
+        if frame >= self.number_of_frames {
+            return None;
+        }
+        let frame_size = self.rows as usize * self.columns as usize;
+        let start = frame as usize * frame_size;
+        Some(&self.mask[start..start + frame_size])
+    }
+
+    /// Convert an overlay frame into a grayscale image,
+    /// white where the overlay is set and black otherwise.
+    ///
+    /// Returns `None` if the frame does not exist.
+    #[cfg(feature = "image")]
+    pub fn to_gray_image(&self, frame: u32) -> Option<GrayImage> {
+        let data = self.mask(frame)?.iter().map(|v| v * 255).collect();
+        GrayImage::from_raw(self.columns, self.rows, data)
+    }
+
+    /// Composite the overlay onto an image frame,
+    /// such as one produced by
+    /// [`to_dynamic_image`](crate::DecodedPixelData::to_dynamic_image),
+    /// at the declared origin.
+    ///
+    /// The pixels where the overlay is set
+    /// are given the maximum value of each channel.
+    /// Nothing is drawn if the overlay does not apply to the given frame,
+    /// where the first image frame is `0`.
+    #[cfg(feature = "image")]
+    pub fn composite(&self, image: &mut DynamicImage, image_frame: u32) {
+        let Some(mask) = self
+            .frame_for_image(image_frame)
+            .and_then(|frame| self.mask(frame))
+        else {
+            return;
+        };
+
+        match image {
+            DynamicImage::ImageLuma8(image) => self.composite_buffer(image, mask),
+            DynamicImage::ImageLumaA8(image) => self.composite_buffer(image, mask),
+            DynamicImage::ImageRgb8(image) => self.composite_buffer(image, mask),
+            DynamicImage::ImageRgba8(image) => self.composite_buffer(image, mask),
+            DynamicImage::ImageLuma16(image) => self.composite_buffer(image, mask),
+            DynamicImage::ImageLumaA16(image) => self.composite_buffer(image, mask),
+            DynamicImage::ImageRgb16(image) => self.composite_buffer(image, mask),
+            DynamicImage::ImageRgba16(image) => self.composite_buffer(image, mask),
+            DynamicImage::ImageRgb32F(image) => self.composite_buffer(image, mask),
+            DynamicImage::ImageRgba32F(image) => self.composite_buffer(image, mask),
+            _ => {
+                tracing::warn!("Unsupported image type for compositing overlays");
+            }
+        }
+    }
+
+    #[cfg(feature = "image")]
+    fn composite_buffer<P>(&self, image: &mut ImageBuffer<P, Vec<P::Subpixel>>, mask: &[u8])
+    where
+        P: Pixel,
+    {
+        let max = <P::Subpixel as Primitive>::DEFAULT_MAX_VALUE;
+        let (width, height) = image.dimensions();
+        let columns = self.columns as usize;
+        for (i, _) in mask.iter().enumerate().filter(|(_, v)| **v != 0) {
+            let row = (i / columns) as i64 + i64::from(self.origin.0) - 1;
+            let column = (i % columns) as i64 + i64::from(self.origin.1) - 1;
+            if (0..i64::from(height)).contains(&row) && (0..i64::from(width)).contains(&column) {
+                image
+                    .get_pixel_mut(column as u32, row as u32)
+                    .apply(|_| max);
+            }
+        }
+    }
+}
+
+/// Composite all overlays which apply to an image frame onto the image,
+/// as in [`Overlay::composite`].
+#[cfg(feature = "image")]
+pub fn composite_overlays(image: &mut DynamicImage, overlays: &[Overlay], image_frame: u32) {
+    for overlay in overlays {
+        overlay.composite(image, image_frame);
+    }
+}
+
+/// Collect the overlay planes of an object,
+/// from the repeating groups `6000` to `601E`.
+///
+/// An overlay group is considered present
+/// if it has _Overlay Rows_, _Overlay Columns_, or _Overlay Data_.
+/// Fails with [`OverlayError::EmbeddedOverlay`]
+/// if an overlay is declared without _Overlay Data_,
+/// which means that it is embedded in the pixel data.
+pub fn overlays<D>(obj: &InMemDicomObject<D>) -> Result<Vec<Overlay>>
+where
+    D: DataDictionary + Clone,
+{
+    (FIRST_GROUP..=LAST_GROUP)
+        .step_by(2)
+        .filter_map(|group| read_overlay(obj, group).transpose())
+        .collect()
+}
+
+fn read_overlay<D>(obj: &InMemDicomObject<D>, group: u16) -> Result<Option<Overlay>>
+where
+    D: DataDictionary + Clone,
+{
+    let tag = |range: TagRange| Tag(group, range.inner().element());
+    let data = obj.get(tag(tags::OVERLAY_DATA));
+    if data.is_none()
+        && obj.get(tag(tags::OVERLAY_ROWS)).is_none()
+        && obj.get(tag(tags::OVERLAY_COLUMNS)).is_none()
+    {
+        return Ok(None);
+    }
+
+    let read_int = |range: TagRange, name: &'static str| -> Result<Option<u32>> {
+        obj.get(tag(range))
+            .map(|e| e.to_int::<u32>().context(ConvertValueSnafu { group, name }))
+            .transpose()
+    };
+    let read_str = |range: TagRange| {
+        obj.get(tag(range))
+            .and_then(|e| e.to_str().ok())
+            .map(|v| trim_padding(&v).to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    let data = data.context(EmbeddedOverlaySnafu { group })?;
+    let bits_allocated = read_int(tags::OVERLAY_BITS_ALLOCATED, "OverlayBitsAllocated")?;
+    let bit_position = read_int(tags::OVERLAY_BIT_POSITION, "OverlayBitPosition")?;
+    ensure!(
+        bits_allocated.unwrap_or(1) == 1 && bit_position.unwrap_or(0) == 0,
+        EmbeddedOverlaySnafu { group }
+    );
+
+    let rows = read_int(tags::OVERLAY_ROWS, "OverlayRows")?.context(MissingAttributeSnafu {
+        group,
+        name: "OverlayRows",
+    })?;
+    let columns =
+        read_int(tags::OVERLAY_COLUMNS, "OverlayColumns")?.context(MissingAttributeSnafu {
+            group,
+            name: "OverlayColumns",
+        })?;
+    let origin = match obj.get(tag(tags::OVERLAY_ORIGIN)) {
+        Some(e) => {
+            let origin = e.to_multi_int::<i32>().context(ConvertValueSnafu {
+                group,
+                name: "OverlayOrigin",
+            })?;
+            (
+                origin.first().copied().unwrap_or(1),
+                origin.get(1).copied().unwrap_or(1),
+            )
+        }
+        None => (1, 1),
+    };
+    let number_of_frames = read_int(tags::NUMBER_OF_FRAMES_IN_OVERLAY, "NumberOfFramesInOverlay")?
+        .unwrap_or(1)
+        .max(1);
+    let image_frame_origin = read_int(tags::IMAGE_FRAME_ORIGIN, "ImageFrameOrigin")?
+        .unwrap_or(1)
+        .max(1);
+
+    let len = u64::from(rows) * u64::from(columns) * u64::from(number_of_frames);
+    let mask = unpack_bits(data.value().primitive(), len).context(InsufficientDataSnafu {
+        group,
+        expected: len,
+        actual: data
+            .value()
+            .primitive()
+            .map_or(0, |v| v.to_bytes().len() as u64 * 8),
+    })?;
+
+    Ok(Some(Overlay {
+        group,
+        rows,
+        columns,
+        origin,
+        overlay_type: read_str(tags::OVERLAY_TYPE),
+        label: read_str(tags::OVERLAY_LABEL),
+        number_of_frames,
+        image_frame_origin,
+        mask,
+    }))
+}
+
+/// Unpack the first `len` bits of the overlay data
+/// to one byte per bit.
+///
+/// The bits are packed starting from the least significant bit
+/// of each word.
+/// Returns `None` if the data has fewer bits than requested.
+fn unpack_bits(value: Option<&PrimitiveValue>, len: u64) -> Option<Vec<u8>> {
+    let len = usize::try_from(len).ok()?;
+    match value? {
+        PrimitiveValue::U16(words) => {
+            if words.len() * 16 < len {
+                return None;
+            }
+            Some(
+                (0..len)
+                    .map(|i| ((words[i / 16] >> (i % 16)) & 1) as u8)
+                    .collect(),
+            )
+        }
+        value => {
+            let bytes = value.to_bytes();
+            if bytes.len() * 8 < len {
+                return None;
+            }
+            Some((0..len).map(|i| (bytes[i / 8] >> (i % 8)) & 1).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::{dicom_value, DataElement, VR};
+
+    /// A 3x5 overlay in group 6002,
+    /// set along the diagonal and in the bottom right corner
+    fn overlay_elements(group: u16) -> Vec<DataElement<InMemDicomObject>> {
+        vec![
+            DataElement::new(Tag(group, 0x0010), VR::US, dicom_value!(U16, [3])),
+            DataElement::new(Tag(group, 0x0011), VR::US, dicom_value!(U16, [5])),
+            DataElement::new(Tag(group, 0x0040), VR::CS, PrimitiveValue::from("G")),
+            DataElement::new(Tag(group, 0x0050), VR::SS, dicom_value!(I16, [2, 3])),
+            DataElement::new(Tag(group, 0x0100), VR::US, dicom_value!(U16, [1])),
+            DataElement::new(Tag(group, 0x0102), VR::US, dicom_value!(U16, [0])),
+            // bits 0, 6, 12 and 14
+            DataElement::new(
+                Tag(group, 0x3000),
+                VR::OW,
+                dicom_value!(U16, [0b0101_0000_0100_0001]),
+            ),
+        ]
+    }
+
+    #[rustfmt::skip]
+    static MASK: [u8; 15] = [
+        1, 0, 0, 0, 0,
+        0, 1, 0, 0, 0,
+        0, 0, 1, 0, 1,
+    ];
+
+    #[test]
+    fn read_overlay_planes() {
+        let mut obj = InMemDicomObject::from_element_iter(overlay_elements(0x6002));
+        let overlays = overlays(&obj).unwrap();
+        assert_eq!(overlays.len(), 1);
+        let overlay = &overlays[0];
+        assert_eq!(overlay.group(), 0x6002);
+        assert_eq!(overlay.rows(), 3);
+        assert_eq!(overlay.columns(), 5);
+        assert_eq!(overlay.origin(), (2, 3));
+        assert_eq!(overlay.overlay_type(), Some("G"));
+        assert_eq!(overlay.label(), None);
+        assert_eq!(overlay.number_of_frames(), 1);
+        assert_eq!(overlay.mask(0), Some(&MASK[..]));
+        assert_eq!(overlay.mask(1), None);
+        assert_eq!(overlay.frame_for_image(0), Some(0));
+        assert_eq!(overlay.frame_for_image(1), None);
+
+        // same with overlay data as bytes
+        obj.put(DataElement::new(
+            Tag(0x6002, 0x3000),
+            VR::OB,
+            PrimitiveValue::from(vec![0b0100_0001_u8, 0b0101_0000]),
+        ));
+        let overlays = super::overlays(&obj).unwrap();
+        assert_eq!(overlays[0].mask(0), Some(&MASK[..]));
+
+        // no overlays
+        let obj = InMemDicomObject::new_empty();
+        assert_eq!(super::overlays(&obj).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn read_multiple_overlay_planes() {
+        let mut elements = overlay_elements(0x6000);
+        elements.extend(overlay_elements(0x601E));
+        elements.push(DataElement::new(
+            Tag(0x601E, 0x1500),
+            VR::LO,
+            PrimitiveValue::from("ANNOTATION"),
+        ));
+        let obj = InMemDicomObject::from_element_iter(elements);
+        let overlays = overlays(&obj).unwrap();
+        assert_eq!(overlays.len(), 2);
+        assert_eq!(overlays[0].group(), 0x6000);
+        assert_eq!(overlays[1].group(), 0x601E);
+        assert_eq!(overlays[1].label(), Some("ANNOTATION"));
+    }
+
+    #[test]
+    fn embedded_overlays_are_not_supported() {
+        // no overlay data
+        let mut obj = InMemDicomObject::from_element_iter(overlay_elements(0x6000));
+        obj.remove_element(Tag(0x6000, 0x3000));
+        let err = overlays(&obj).unwrap_err();
+        assert!(matches!(
+            err,
+            OverlayError::EmbeddedOverlay { group: 0x6000, .. }
+        ));
+        assert!(err.to_string().contains("not supported"));
+
+        // overlay in bit 12 of the pixel data
+        let mut obj = InMemDicomObject::from_element_iter(overlay_elements(0x6000));
+        obj.put(DataElement::new(
+            Tag(0x6000, 0x0100),
+            VR::US,
+            dicom_value!(U16, [16]),
+        ));
+        obj.put(DataElement::new(
+            Tag(0x6000, 0x0102),
+            VR::US,
+            dicom_value!(U16, [12]),
+        ));
+        assert!(matches!(
+            overlays(&obj),
+            Err(OverlayError::EmbeddedOverlay { group: 0x6000, .. })
+        ));
+    }
+
+    #[test]
+    fn insufficient_overlay_data() {
+        let mut obj = InMemDicomObject::from_element_iter(overlay_elements(0x6000));
+        obj.put(DataElement::new(
+            Tag(0x6000, 0x0010),
+            VR::US,
+            dicom_value!(U16, [4]),
+        ));
+        assert!(matches!(
+            overlays(&obj),
+            Err(OverlayError::InsufficientData {
+                expected: 20,
+                actual: 16,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn overlay_images() {
+        let obj = InMemDicomObject::from_element_iter(overlay_elements(0x6000));
+        let overlays = overlays(&obj).unwrap();
+        let overlay = &overlays[0];
+
+        let gray = overlay.to_gray_image(0).unwrap();
+        assert_eq!(gray.dimensions(), (5, 3));
+        assert_eq!(gray.get_pixel(0, 0).0, [255]);
+        assert_eq!(gray.get_pixel(1, 0).0, [0]);
+        assert_eq!(gray.get_pixel(4, 2).0, [255]);
+
+        // composite onto a 4x6 image, with the overlay at row 2, column 3
+        let mut image = DynamicImage::ImageLuma8(GrayImage::new(6, 4));
+        composite_overlays(&mut image, &overlays, 0);
+        let image = image.into_luma8();
+        let set: Vec<_> = image
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p.0 == [255])
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        // the bottom right corner of the overlay is outside of the image
+        assert_eq!(set, vec![(2, 1), (3, 2), (4, 3)]);
+
+        // the overlay does not apply to the second frame
+        let mut image = DynamicImage::ImageRgb8(image::RgbImage::new(6, 4));
+        overlay.composite(&mut image, 1);
+        assert!(image.into_rgb8().pixels().all(|p| p.0 == [0, 0, 0]));
+    }
+}
@@ -0,0 +1,660 @@
+//! Validation of the geometry of a series of single-frame images,
+//! such as before reconstructing a volume from a CT or MR series.
+//!
+//! [`SeriesGeometry`] checks whether a set of instances forms a regular volume:
+//! all instances should share the same image orientation,
+//! dimensions, and pixel spacing,
+//! and their positions along the slice normal should be evenly spaced,
+//! without duplicate or missing slices.
+//! The outcome is a [`SeriesReport`],
+//! which lists every problem found
+//! and the order in which the instances form the volume.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use dicom_object::open_file;
+//! use dicom_pixeldata::series::SeriesGeometry;
+//!
+//! let objects = ["1.dcm", "2.dcm", "3.dcm"]
+//!     .iter()
+//!     .map(open_file)
+//!     .collect::<Result<Vec<_>, _>>()?;
+//! let refs: Vec<_> = objects.iter().collect();
+//! let report = SeriesGeometry::analyze(&refs);
+//! for finding in report.findings() {
+//!     eprintln!("{}", finding);
+//! }
+//! // the instances in volume order
+//! let slices: Vec<_> = report
+//!     .sorted_indices()
+//!     .iter()
+//!     .map(|i| &objects[*i])
+//!     .collect();
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use dicom_core::{DataDictionary, Tag};
+use dicom_dictionary_std::tags;
+use dicom_object::{FileDicomObject, InMemDicomObject};
+use std::convert::TryInto;
+use std::fmt;
+
+/// The tolerances for validating the geometry of a series,
+/// which is done by [`analyze`](Self::analyze) or [`check`](Self::check).
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SeriesGeometry {
+    /// The maximum absolute difference
+    /// between each direction cosine of the _Image Orientation (Patient)_
+    /// of an instance and that of the first instance.
+    pub orientation_tolerance: f64,
+    /// The maximum distance in millimeters
+    /// between two slice positions which are taken as the same position.
+    pub position_tolerance: f64,
+    /// The maximum deviation of the pixel spacing
+    /// and of the distance between consecutive slices
+    /// from their expected values,
+    /// relative to those values.
+    pub spacing_tolerance: f64,
+}
+
+impl Default for SeriesGeometry {
+    fn default() -> Self {
+        SeriesGeometry {
+            orientation_tolerance: 1e-3,
+            position_tolerance: 1e-2,
+            spacing_tolerance: 1e-2,
+        }
+    }
+}
+
+/// A problem found in the geometry of a series.
+///
+/// Instances are identified by their index
+/// in the list of objects given for analysis.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SeriesFinding {
+    /// An instance has no valid value for an attribute
+    /// which is needed to place it in the volume.
+    ///
+    /// Instances without a position or orientation
+    /// are left out of the volume.
+    MissingAttribute { index: usize, tag: Tag },
+    /// The number of rows or columns of an instance
+    /// differs from that of the first instance.
+    MixedDimensions {
+        index: usize,
+        rows: u16,
+        columns: u16,
+        expected_rows: u16,
+        expected_columns: u16,
+    },
+    /// The _Pixel Spacing_ of an instance
+    /// differs from that of the first instance.
+    InconsistentPixelSpacing {
+        index: usize,
+        pixel_spacing: [f64; 2],
+        expected: [f64; 2],
+    },
+    /// The _Image Orientation (Patient)_ of an instance
+    /// differs from that of the first instance.
+    ///
+    /// The instance is left out of the volume.
+    InconsistentOrientation {
+        index: usize,
+        orientation: [f64; 6],
+        expected: [f64; 6],
+    },
+    /// An instance is at the same position along the slice normal
+    /// as a previous instance in volume order.
+    DuplicatePosition {
+        index: usize,
+        duplicate_of: usize,
+        position: f64,
+    },
+    /// One or more slices are missing before an instance,
+    /// which is further away from the previous slice
+    /// than the expected slice step.
+    Gap {
+        index: usize,
+        expected_position: f64,
+        found_position: f64,
+        missing_slices: usize,
+    },
+    /// The distance between an instance and the previous slice
+    /// is not a multiple of the expected slice step.
+    IrregularSpacing {
+        index: usize,
+        expected_position: f64,
+        found_position: f64,
+    },
+}
+
+impl SeriesFinding {
+    /// The index of the instance which the finding is about.
+    pub fn index(&self) -> usize {
+        match self {
+            SeriesFinding::MissingAttribute { index, .. }
+            | SeriesFinding::MixedDimensions { index, .. }
+            | SeriesFinding::InconsistentPixelSpacing { index, .. }
+            | SeriesFinding::InconsistentOrientation { index, .. }
+            | SeriesFinding::DuplicatePosition { index, .. }
+            | SeriesFinding::Gap { index, .. }
+            | SeriesFinding::IrregularSpacing { index, .. } => *index,
+        }
+    }
+}
+
+impl fmt::Display for SeriesFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeriesFinding::MissingAttribute { index, tag } => {
+                write!(f, "Instance #{} has no valid attribute {}", index, tag)
+            }
+            SeriesFinding::MixedDimensions {
+                index,
+                rows,
+                columns,
+                expected_rows,
+                expected_columns,
+            } => write!(
+                f,
+                "Instance #{} has {}x{} pixels, expected {}x{}",
+                index, columns, rows, expected_columns, expected_rows
+            ),
+            SeriesFinding::InconsistentPixelSpacing {
+                index,
+                pixel_spacing,
+                expected,
+            } => write!(
+                f,
+                "Instance #{} has pixel spacing {:?}, expected {:?}",
+                index, pixel_spacing, expected
+            ),
+            SeriesFinding::InconsistentOrientation {
+                index,
+                orientation,
+                expected,
+            } => write!(
+                f,
+                "Instance #{} has orientation {:?}, expected {:?}",
+                index, orientation, expected
+            ),
+            SeriesFinding::DuplicatePosition {
+                index,
+                duplicate_of,
+                position,
+            } => write!(
+                f,
+                "Instance #{} is at the same position ({}) as instance #{}",
+                index, position, duplicate_of
+            ),
+            SeriesFinding::Gap {
+                index,
+                expected_position,
+                found_position,
+                missing_slices,
+            } => write!(
+                f,
+                "{} slice(s) missing before instance #{}: expected position {}, found {}",
+                missing_slices, index, expected_position, found_position
+            ),
+            SeriesFinding::IrregularSpacing {
+                index,
+                expected_position,
+                found_position,
+            } => write!(
+                f,
+                "Instance #{} is irregularly spaced: expected position {}, found {}",
+                index, expected_position, found_position
+            ),
+        }
+    }
+}
+
+/// The outcome of validating the geometry of a series.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesReport {
+    /// the indices of the instances in the volume, in volume order
+    sorted_indices: Vec<usize>,
+    /// the position of each instance in `sorted_indices`
+    /// along the slice normal
+    positions: Vec<f64>,
+    /// the slice normal of the first instance with an orientation
+    normal: Option<[f64; 3]>,
+    /// the expected distance between consecutive slices
+    slice_step: Option<f64>,
+    /// the problems found
+    findings: Vec<SeriesFinding>,
+}
+
+impl SeriesReport {
+    /// Obtain the indices of the instances which form the volume,
+    /// ordered by their position along the slice normal.
+    ///
+    /// Instances without a position or orientation,
+    /// or with an inconsistent orientation,
+    /// are not included.
+    /// Instances at duplicate positions are included.
+    pub fn sorted_indices(&self) -> &[usize] {
+        &self.sorted_indices
+    }
+
+    /// Obtain the position along the slice normal
+    /// of each instance in [`sorted_indices`](Self::sorted_indices).
+    pub fn positions(&self) -> &[f64] {
+        &self.positions
+    }
+
+    /// Obtain the slice normal,
+    /// as the cross product of the row and column direction cosines
+    /// of the first instance with a valid orientation.
+    pub fn normal(&self) -> Option<[f64; 3]> {
+        self.normal
+    }
+
+    /// Obtain the expected distance in millimeters between consecutive slices,
+    /// if the volume has at least two distinct slice positions.
+    pub fn slice_step(&self) -> Option<f64> {
+        self.slice_step
+    }
+
+    /// Obtain the problems found in the geometry of the series.
+    pub fn findings(&self) -> &[SeriesFinding] {
+        &self.findings
+    }
+
+    /// Check whether the instances form a regular volume,
+    /// which is the case when there are no findings.
+    pub fn is_regular(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+impl SeriesGeometry {
+    /// Create the default tolerances for validating the geometry of a series.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the maximum absolute difference between direction cosines.
+    pub fn with_orientation_tolerance(mut self, orientation_tolerance: f64) -> Self {
+        self.orientation_tolerance = orientation_tolerance;
+        self
+    }
+
+    /// Set the maximum distance in millimeters
+    /// between two slice positions taken as the same position.
+    pub fn with_position_tolerance(mut self, position_tolerance: f64) -> Self {
+        self.position_tolerance = position_tolerance;
+        self
+    }
+
+    /// Set the maximum relative deviation
+    /// of the pixel spacing and of the slice step.
+    pub fn with_spacing_tolerance(mut self, spacing_tolerance: f64) -> Self {
+        self.spacing_tolerance = spacing_tolerance;
+        self
+    }
+
+    /// Validate the geometry of a series of single-frame instances
+    /// with the default tolerances.
+    ///
+    /// See [`check`](Self::check).
+    pub fn analyze<D>(objects: &[&FileDicomObject<InMemDicomObject<D>>]) -> SeriesReport
+    where
+        D: DataDictionary + Clone,
+    {
+        SeriesGeometry::default().check(objects)
+    }
+
+    /// Validate the geometry of a series of single-frame instances.
+    ///
+    /// The dimensions, pixel spacing, and orientation of each instance
+    /// are compared to those of the first instance which defines them.
+    /// The instances are then ordered by their _Image Position (Patient)_
+    /// along the slice normal.
+    /// The expected slice step is the median distance
+    /// between consecutive distinct positions,
+    /// so that a few missing slices do not affect it.
+    pub fn check<D>(&self, objects: &[&FileDicomObject<InMemDicomObject<D>>]) -> SeriesReport
+    where
+        D: DataDictionary + Clone,
+    {
+        let mut findings = Vec::new();
+        let mut expected_dimensions = None;
+        let mut expected_pixel_spacing: Option<[f64; 2]> = None;
+        let mut expected_orientation: Option<[f64; 6]> = None;
+        let mut placed = Vec::with_capacity(objects.len());
+
+        for (index, &obj) in objects.iter().enumerate() {
+            let rows = read_u16(obj, tags::ROWS);
+            let columns = read_u16(obj, tags::COLUMNS);
+            let pixel_spacing: Option<[f64; 2]> = read_floats(obj, tags::PIXEL_SPACING);
+            let orientation: Option<[f64; 6]> = read_floats(obj, tags::IMAGE_ORIENTATION_PATIENT);
+            let position: Option<[f64; 3]> = read_floats(obj, tags::IMAGE_POSITION_PATIENT);
+            for (present, tag) in [
+                (rows.is_some(), tags::ROWS),
+                (columns.is_some(), tags::COLUMNS),
+                (pixel_spacing.is_some(), tags::PIXEL_SPACING),
+                (orientation.is_some(), tags::IMAGE_ORIENTATION_PATIENT),
+                (position.is_some(), tags::IMAGE_POSITION_PATIENT),
+            ] {
+                if !present {
+                    findings.push(SeriesFinding::MissingAttribute { index, tag });
+                }
+            }
+
+            if let (Some(rows), Some(columns)) = (rows, columns) {
+                let (expected_rows, expected_columns) =
+                    *expected_dimensions.get_or_insert((rows, columns));
+                if (rows, columns) != (expected_rows, expected_columns) {
+                    findings.push(SeriesFinding::MixedDimensions {
+                        index,
+                        rows,
+                        columns,
+                        expected_rows,
+                        expected_columns,
+                    });
+                }
+            }
+
+            if let Some(pixel_spacing) = pixel_spacing {
+                let expected = *expected_pixel_spacing.get_or_insert(pixel_spacing);
+                if pixel_spacing
+                    .iter()
+                    .zip(expected.iter())
+                    .any(|(s, e)| (s - e).abs() > self.spacing_tolerance * e.abs())
+                {
+                    findings.push(SeriesFinding::InconsistentPixelSpacing {
+                        index,
+                        pixel_spacing,
+                        expected,
+                    });
+                }
+            }
+
+            let (Some(orientation), Some(position)) = (orientation, position) else {
+                continue;
+            };
+            let expected = *expected_orientation.get_or_insert(orientation);
+            if orientation
+                .iter()
+                .zip(expected.iter())
+                .any(|(o, e)| (o - e).abs() > self.orientation_tolerance)
+            {
+                findings.push(SeriesFinding::InconsistentOrientation {
+                    index,
+                    orientation,
+                    expected,
+                });
+                continue;
+            }
+            placed.push((index, position));
+        }
+
+        let normal = expected_orientation.map(|orientation| slice_normal(&orientation));
+        let mut slices: Vec<(usize, f64)> = placed
+            .into_iter()
+            .filter_map(|(index, position)| Some((index, dot(&position, &normal?))))
+            .collect();
+        slices.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        // the first slice at each distinct position
+        let mut distinct: Vec<(usize, f64)> = Vec::with_capacity(slices.len());
+        for &(index, position) in &slices {
+            match distinct.last() {
+                Some(&(previous, previous_position))
+                    if position - previous_position <= self.position_tolerance =>
+                {
+                    findings.push(SeriesFinding::DuplicatePosition {
+                        index,
+                        duplicate_of: previous,
+                        position,
+                    });
+                }
+                _ => distinct.push((index, position)),
+            }
+        }
+
+        // the lower median, so that a missing slice does not widen the step
+        let mut steps: Vec<f64> = distinct.windows(2).map(|w| w[1].1 - w[0].1).collect();
+        steps.sort_by(f64::total_cmp);
+        let slice_step = steps.get(steps.len().saturating_sub(1) / 2).copied();
+
+        if let Some(step) = slice_step {
+            let tolerance = self.spacing_tolerance * step;
+            for w in distinct.windows(2) {
+                let ((_, previous_position), (index, position)) = (w[0], w[1]);
+                let distance = position - previous_position;
+                if (distance - step).abs() <= tolerance {
+                    continue;
+                }
+                let expected_position = previous_position + step;
+                let steps = (distance / step).round();
+                if steps >= 2. && (distance - steps * step).abs() <= tolerance * steps {
+                    findings.push(SeriesFinding::Gap {
+                        index,
+                        expected_position,
+                        found_position: position,
+                        missing_slices: steps as usize - 1,
+                    });
+                } else {
+                    findings.push(SeriesFinding::IrregularSpacing {
+                        index,
+                        expected_position,
+                        found_position: position,
+                    });
+                }
+            }
+        }
+
+        SeriesReport {
+            sorted_indices: slices.iter().map(|(index, _)| *index).collect(),
+            positions: slices.iter().map(|(_, position)| *position).collect(),
+            normal,
+            slice_step,
+            findings,
+        }
+    }
+}
+
+/// Read a fixed number of floating point values from an attribute.
+fn read_floats<D, const N: usize>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+    tag: Tag,
+) -> Option<[f64; N]>
+where
+    D: DataDictionary + Clone,
+{
+    obj.get(tag)?.to_multi_float64().ok()?.try_into().ok()
+}
+
+fn read_u16<D>(obj: &FileDicomObject<InMemDicomObject<D>>, tag: Tag) -> Option<u16>
+where
+    D: DataDictionary + Clone,
+{
+    obj.get(tag)?.to_int().ok()
+}
+
+/// The cross product of the row and column direction cosines.
+pub(crate) fn slice_normal(orientation: &[f64; 6]) -> [f64; 3] {
+    let [rx, ry, rz, cx, cy, cz] = *orientation;
+    [ry * cz - rz * cy, rz * cx - rx * cz, rx * cy - ry * cx]
+}
+
+pub(crate) fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::uids;
+    use dicom_object::FileMetaTableBuilder;
+
+    const AXIAL: [f64; 6] = [1., 0., 0., 0., 1., 0.];
+
+    /// Create a single-frame instance of 4x4 pixels
+    /// with the given orientation and position.
+    fn slice(orientation: [f64; 6], position: [f64; 3]) -> FileDicomObject<InMemDicomObject> {
+        let ds =
+            |values: &[f64]| PrimitiveValue::Strs(values.iter().map(|v| v.to_string()).collect());
+        InMemDicomObject::from_element_iter([
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [4])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [4])),
+            DataElement::new(tags::PIXEL_SPACING, VR::DS, ds(&[0.5, 0.5])),
+            DataElement::new(tags::IMAGE_ORIENTATION_PATIENT, VR::DS, ds(&orientation)),
+            DataElement::new(tags::IMAGE_POSITION_PATIENT, VR::DS, ds(&position)),
+        ])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                .media_storage_sop_class_uid(uids::CT_IMAGE_STORAGE)
+                .media_storage_sop_instance_uid("2.25.261405367421785519263932817632546851287"),
+        )
+        .unwrap()
+    }
+
+    fn axial_slices(z: &[f64]) -> Vec<FileDicomObject<InMemDicomObject>> {
+        z.iter().map(|z| slice(AXIAL, [-10., -10., *z])).collect()
+    }
+
+    fn analyze(objects: &[FileDicomObject<InMemDicomObject>]) -> SeriesReport {
+        let refs: Vec<_> = objects.iter().collect();
+        SeriesGeometry::analyze(&refs)
+    }
+
+    #[test]
+    fn regular_volume_is_sorted() {
+        let objects = axial_slices(&[4., 0., 6., 2.]);
+        let report = analyze(&objects);
+
+        assert_eq!(report.findings(), &[]);
+        assert!(report.is_regular());
+        assert_eq!(report.sorted_indices(), &[1, 3, 0, 2]);
+        assert_eq!(report.positions(), &[0., 2., 4., 6.]);
+        assert_eq!(report.normal(), Some([0., 0., 1.]));
+        assert_eq!(report.slice_step(), Some(2.));
+    }
+
+    #[test]
+    fn gap_is_reported() {
+        let objects = axial_slices(&[0., 2., 8., 10., 12.]);
+        let report = analyze(&objects);
+
+        assert_eq!(report.sorted_indices(), &[0, 1, 2, 3, 4]);
+        assert_eq!(report.slice_step(), Some(2.));
+        assert_eq!(
+            report.findings(),
+            &[SeriesFinding::Gap {
+                index: 2,
+                expected_position: 4.,
+                found_position: 8.,
+                missing_slices: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn irregular_spacing_is_reported() {
+        let objects = axial_slices(&[0., 2., 5., 7.]);
+        let report = analyze(&objects);
+
+        assert_eq!(
+            report.findings(),
+            &[SeriesFinding::IrregularSpacing {
+                index: 2,
+                expected_position: 4.,
+                found_position: 5.,
+            }]
+        );
+    }
+
+    #[test]
+    fn duplicate_is_reported() {
+        let objects = axial_slices(&[0., 2., 4., 2.]);
+        let report = analyze(&objects);
+
+        // duplicates are kept in volume order
+        assert_eq!(report.sorted_indices(), &[0, 1, 3, 2]);
+        assert_eq!(report.slice_step(), Some(2.));
+        assert_eq!(
+            report.findings(),
+            &[SeriesFinding::DuplicatePosition {
+                index: 3,
+                duplicate_of: 1,
+                position: 2.,
+            }]
+        );
+    }
+
+    #[test]
+    fn tilted_outlier_is_left_out() {
+        let tilted = [1., 0., 0., 0., 0.9950042, 0.0998334];
+        let objects = vec![
+            slice(AXIAL, [-10., -10., 0.]),
+            slice(AXIAL, [-10., -10., 2.]),
+            slice(tilted, [-10., -10., 4.]),
+            slice(AXIAL, [-10., -10., 4.]),
+        ];
+        let report = analyze(&objects);
+
+        assert_eq!(report.sorted_indices(), &[0, 1, 3]);
+        assert_eq!(
+            report.findings(),
+            &[SeriesFinding::InconsistentOrientation {
+                index: 2,
+                orientation: tilted,
+                expected: AXIAL,
+            }]
+        );
+
+        // within the tolerance, the orientation is consistent
+        let refs: Vec<_> = objects.iter().collect();
+        let report = SeriesGeometry::new()
+            .with_orientation_tolerance(0.2)
+            .check(&refs);
+        assert!(report
+            .findings()
+            .iter()
+            .all(|f| !matches!(f, SeriesFinding::InconsistentOrientation { .. })));
+    }
+
+    #[test]
+    fn mixed_dimensions_and_missing_position_are_reported() {
+        let mut objects = axial_slices(&[0., 2., 4.]);
+        objects[1].put(DataElement::new(
+            tags::COLUMNS,
+            VR::US,
+            dicom_value!(U16, [8]),
+        ));
+        objects[2].remove_element(tags::IMAGE_POSITION_PATIENT);
+        let report = analyze(&objects);
+
+        assert_eq!(report.sorted_indices(), &[0, 1]);
+        assert_eq!(
+            report.findings(),
+            &[
+                SeriesFinding::MixedDimensions {
+                    index: 1,
+                    rows: 4,
+                    columns: 8,
+                    expected_rows: 4,
+                    expected_columns: 4,
+                },
+                SeriesFinding::MissingAttribute {
+                    index: 2,
+                    tag: tags::IMAGE_POSITION_PATIENT,
+                },
+            ]
+        );
+        assert_eq!(
+            report.findings()[1].to_string(),
+            "Instance #2 has no valid attribute (0020,0032)"
+        );
+    }
+}
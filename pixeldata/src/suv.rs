@@ -0,0 +1,492 @@
+//! Standardized Uptake Values (SUV) of PET images.
+//!
+//! PET images stored in Bq/ml (units `BQML`)
+//! can be normalized by the injected activity and the patient's body weight,
+//! resulting in body weight SUV (SUVbw):
+//!
+//! ```text
+//! SUVbw = activity concentration (Bq/ml) × patient weight (g) / injected dose (Bq)
+//! ```
+//!
+//! where the injected dose is decay corrected
+//! to the reference time of the image values.
+//! [`SuvParameters`] collects the attributes needed for this normalization,
+//! and produces a [`Rescale`] which can be passed to
+//! [`ModalityLutOption::Override`](crate::ModalityLutOption::Override),
+//! or used directly through
+//! [`DecodedPixelData::to_vec_suv`](crate::DecodedPixelData::to_vec_suv).
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use dicom_object::open_file;
+//! use dicom_pixeldata::{suv::SuvParameters, PixelDecoder};
+//!
+//! let obj = open_file("pet.dcm")?;
+//! let suv = SuvParameters::from_obj(&obj)?;
+//! let pixel_data = obj.decode_pixel_data()?;
+//! let values: Vec<f32> = pixel_data.to_vec_suv(&suv)?;
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use dicom_core::chrono::NaiveTime;
+use dicom_core::value::{trim_padding, AsRange};
+use dicom_core::{header::HasLength, DataDictionary, Tag};
+use dicom_dictionary_std::tags;
+use dicom_object::{FileDicomObject, InMemDicomObject};
+use snafu::{ensure, Backtrace, OptionExt, ResultExt, Snafu};
+
+use crate::Rescale;
+
+/// The number of seconds in a day,
+/// to account for an injection before midnight.
+const SECONDS_PER_DAY: f64 = 86_400.;
+
+/// An error which may occur when gathering the parameters
+/// for computing standardized uptake values.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum SuvError {
+    /// Missing Radiopharmaceutical Information Sequence
+    MissingRadiopharmaceuticalInformation { backtrace: Backtrace },
+
+    #[snafu(display("Missing attribute `{}`", name))]
+    MissingAttribute {
+        name: &'static str,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Could not convert attribute `{}`", name))]
+    ConvertValue {
+        name: &'static str,
+        #[snafu(source(from(dicom_core::value::ConvertValueError, Box::from)))]
+        source: Box<dicom_core::value::ConvertValueError>,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Invalid time in attribute `{}`", name))]
+    InvalidTime {
+        name: &'static str,
+        source: dicom_core::value::range::Error,
+        backtrace: Backtrace,
+    },
+
+    #[snafu(display("Invalid value {} in attribute `{}`", value, name))]
+    InvalidValue {
+        name: &'static str,
+        value: f64,
+        backtrace: Backtrace,
+    },
+
+    /// Missing or zero patient weight
+    MissingPatientWeight { backtrace: Backtrace },
+
+    #[snafu(display("Unsupported units `{}`, expected `BQML`", units))]
+    UnsupportedUnits { units: String, backtrace: Backtrace },
+
+    #[snafu(display("Unsupported decay correction `{}`", decay_correction))]
+    UnsupportedDecayCorrection {
+        decay_correction: String,
+        backtrace: Backtrace,
+    },
+}
+
+/// Type alias for a result from this module.
+pub type Result<T, E = SuvError> = std::result::Result<T, E>;
+
+/// The reference time to which the image values were decay corrected,
+/// as per the _Decay Correction_ attribute (0054,1102).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DecayCorrection {
+    /// `START`: decay corrected to the start of the acquisition.
+    /// The injected dose is decayed
+    /// from the injection time to the series time.
+    Start,
+    /// `ADMIN`: decay corrected to the administration time.
+    /// The injected dose is used as is.
+    Admin,
+}
+
+/// The parameters for computing body weight standardized uptake values (SUVbw)
+/// of a PET image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuvParameters {
+    /// The patient's weight in kilograms
+    pub patient_weight: f64,
+    /// The total injected dose in becquerels
+    pub injected_dose: f64,
+    /// The radionuclide half life in seconds
+    pub half_life: f64,
+    /// The reference time of the decay correction
+    pub decay_correction: DecayCorrection,
+    /// The time between the injection and the start of the series,
+    /// in seconds
+    pub elapsed: f64,
+}
+
+impl SuvParameters {
+    /// Gather the SUV parameters from the attributes of a PET image.
+    ///
+    /// The following attributes are read:
+    ///
+    /// - _Units_ (0054,1001), which must be `BQML` if present;
+    /// - _Decay Correction_ (0054,1102), which must be `START` or `ADMIN`;
+    /// - _Patient's Weight_ (0010,1030);
+    /// - _Radionuclide Total Dose_,
+    ///   _Radionuclide Half Life_,
+    ///   and _Radiopharmaceutical Start Time_
+    ///   (or _Radiopharmaceutical Start DateTime_)
+    ///   from the first item of the _Radiopharmaceutical Information Sequence_;
+    /// - _Series Time_ (0008,0031),
+    ///   or _Acquisition Time_ (0008,0032) if the former is missing.
+    ///
+    /// An injection time later than the series time
+    /// is taken to have occurred on the day before.
+    pub fn from_obj<D>(obj: &FileDicomObject<InMemDicomObject<D>>) -> Result<Self>
+    where
+        D: DataDictionary + Clone,
+    {
+        if let Some(units) = read_str(obj, tags::UNITS) {
+            ensure!(units == "BQML", UnsupportedUnitsSnafu { units });
+        }
+
+        let decay_correction = match read_str(obj, tags::DECAY_CORRECTION)
+            .context(MissingAttributeSnafu {
+                name: "DecayCorrection",
+            })?
+            .as_str()
+        {
+            "START" => DecayCorrection::Start,
+            "ADMIN" => DecayCorrection::Admin,
+            other => {
+                return UnsupportedDecayCorrectionSnafu {
+                    decay_correction: other,
+                }
+                .fail()
+            }
+        };
+
+        let patient_weight = read_f64(obj, tags::PATIENT_WEIGHT, "PatientWeight")?
+            .filter(|w| *w > 0.)
+            .context(MissingPatientWeightSnafu)?;
+
+        let info = obj
+            .get(tags::RADIOPHARMACEUTICAL_INFORMATION_SEQUENCE)
+            .and_then(|e| e.items())
+            .and_then(|items| items.first())
+            .context(MissingRadiopharmaceuticalInformationSnafu)?;
+
+        let injected_dose = read_f64(info, tags::RADIONUCLIDE_TOTAL_DOSE, "RadionuclideTotalDose")?
+            .context(MissingAttributeSnafu {
+                name: "RadionuclideTotalDose",
+            })?;
+        ensure!(
+            injected_dose > 0.,
+            InvalidValueSnafu {
+                name: "RadionuclideTotalDose",
+                value: injected_dose,
+            }
+        );
+        let half_life = read_f64(info, tags::RADIONUCLIDE_HALF_LIFE, "RadionuclideHalfLife")?
+            .context(MissingAttributeSnafu {
+                name: "RadionuclideHalfLife",
+            })?;
+        ensure!(
+            half_life > 0.,
+            InvalidValueSnafu {
+                name: "RadionuclideHalfLife",
+                value: half_life,
+            }
+        );
+
+        let injection_time = match read_time(
+            info,
+            tags::RADIOPHARMACEUTICAL_START_TIME,
+            "RadiopharmaceuticalStartTime",
+        )? {
+            Some(time) => time,
+            None => read_datetime_time(
+                info,
+                tags::RADIOPHARMACEUTICAL_START_DATE_TIME,
+                "RadiopharmaceuticalStartDateTime",
+            )?
+            .context(MissingAttributeSnafu {
+                name: "RadiopharmaceuticalStartTime",
+            })?,
+        };
+        let series_time = match read_time(obj, tags::SERIES_TIME, "SeriesTime")? {
+            Some(time) => time,
+            None => read_time(obj, tags::ACQUISITION_TIME, "AcquisitionTime")?
+                .context(MissingAttributeSnafu { name: "SeriesTime" })?,
+        };
+
+        let mut elapsed = series_time
+            .signed_duration_since(injection_time)
+            .num_milliseconds() as f64
+            / 1000.;
+        if elapsed < 0. {
+            elapsed += SECONDS_PER_DAY;
+        }
+
+        Ok(SuvParameters {
+            patient_weight,
+            injected_dose,
+            half_life,
+            decay_correction,
+            elapsed,
+        })
+    }
+
+    /// The injected dose in becquerels,
+    /// decay corrected to the reference time of the image values.
+    pub fn decayed_dose(&self) -> f64 {
+        match self.decay_correction {
+            DecayCorrection::Start => self.injected_dose * (-self.elapsed / self.half_life).exp2(),
+            DecayCorrection::Admin => self.injected_dose,
+        }
+    }
+
+    /// The factor which converts activity concentrations in Bq/ml
+    /// to body weight standardized uptake values.
+    pub fn scale_factor(&self) -> f64 {
+        self.patient_weight * 1000. / self.decayed_dose()
+    }
+
+    /// Compose the given modality rescale
+    /// with the conversion to standardized uptake values,
+    /// so that the resulting rescale maps stored values to SUVbw.
+    pub fn rescale(&self, rescale: &Rescale) -> Rescale {
+        let factor = self.scale_factor();
+        Rescale::new(rescale.slope * factor, rescale.intercept * factor)
+    }
+}
+
+/// Read a trimmed string attribute,
+/// treating empty values as missing.
+fn read_str<D>(obj: &InMemDicomObject<D>, tag: Tag) -> Option<String>
+where
+    D: DataDictionary + Clone,
+{
+    obj.get(tag)
+        .and_then(|e| e.to_str().ok())
+        .map(|v| trim_padding(&v).to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn read_f64<D>(obj: &InMemDicomObject<D>, tag: Tag, name: &'static str) -> Result<Option<f64>>
+where
+    D: DataDictionary + Clone,
+{
+    match obj.get(tag).filter(|e| !e.is_empty()) {
+        Some(e) => e.to_float64().context(ConvertValueSnafu { name }).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn read_time<D>(
+    obj: &InMemDicomObject<D>,
+    tag: Tag,
+    name: &'static str,
+) -> Result<Option<NaiveTime>>
+where
+    D: DataDictionary + Clone,
+{
+    match obj.get(tag).filter(|e| !e.is_empty()) {
+        Some(e) => {
+            let time = e.to_time().context(ConvertValueSnafu { name })?;
+            time.earliest().context(InvalidTimeSnafu { name }).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
+fn read_datetime_time<D>(
+    obj: &InMemDicomObject<D>,
+    tag: Tag,
+    name: &'static str,
+) -> Result<Option<NaiveTime>>
+where
+    D: DataDictionary + Clone,
+{
+    match obj.get(tag).filter(|e| !e.is_empty()) {
+        Some(e) => {
+            let datetime = e.to_datetime().context(ConvertValueSnafu { name })?;
+            match datetime.time() {
+                Some(time) => time.earliest().context(InvalidTimeSnafu { name }).map(Some),
+                None => Ok(None),
+            }
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dicom_core::value::DataSetSequence;
+    use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+    use dicom_dictionary_std::uids;
+    use dicom_object::FileMetaTableBuilder;
+
+    /// half life of fluorine-18
+    const HALF_LIFE: f64 = 6586.2;
+
+    fn pet_object(
+        decay_correction: &str,
+        patient_weight: Option<&str>,
+        injection_time: &str,
+        series_time: &str,
+    ) -> FileDicomObject<InMemDicomObject> {
+        let info = InMemDicomObject::from_element_iter([
+            DataElement::new(
+                tags::RADIOPHARMACEUTICAL_START_TIME,
+                VR::TM,
+                PrimitiveValue::from(injection_time),
+            ),
+            DataElement::new(
+                tags::RADIONUCLIDE_TOTAL_DOSE,
+                VR::DS,
+                PrimitiveValue::from("370000000"),
+            ),
+            DataElement::new(
+                tags::RADIONUCLIDE_HALF_LIFE,
+                VR::DS,
+                PrimitiveValue::from(HALF_LIFE.to_string()),
+            ),
+        ]);
+
+        let mut obj = InMemDicomObject::from_element_iter([
+            DataElement::new(tags::SERIES_TIME, VR::TM, PrimitiveValue::from(series_time)),
+            DataElement::new(
+                tags::RADIOPHARMACEUTICAL_INFORMATION_SEQUENCE,
+                VR::SQ,
+                DataSetSequence::from(vec![info]),
+            ),
+            DataElement::new(tags::UNITS, VR::CS, PrimitiveValue::from("BQML")),
+            DataElement::new(
+                tags::DECAY_CORRECTION,
+                VR::CS,
+                PrimitiveValue::from(decay_correction),
+            ),
+            DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(
+                tags::PHOTOMETRIC_INTERPRETATION,
+                VR::CS,
+                PrimitiveValue::from("MONOCHROME2"),
+            ),
+            DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [1])),
+            DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [2])),
+            DataElement::new(tags::BITS_ALLOCATED, VR::US, dicom_value!(U16, [16])),
+            DataElement::new(tags::BITS_STORED, VR::US, dicom_value!(U16, [16])),
+            DataElement::new(tags::HIGH_BIT, VR::US, dicom_value!(U16, [15])),
+            DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+            DataElement::new(tags::RESCALE_SLOPE, VR::DS, PrimitiveValue::from("2")),
+            DataElement::new(tags::RESCALE_INTERCEPT, VR::DS, PrimitiveValue::from("0")),
+            DataElement::new(tags::PIXEL_DATA, VR::OW, dicom_value!(U16, [100, 5000])),
+        ]);
+        if let Some(weight) = patient_weight {
+            obj.put(DataElement::new(
+                tags::PATIENT_WEIGHT,
+                VR::DS,
+                PrimitiveValue::from(weight),
+            ));
+        }
+
+        obj.with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+                .media_storage_sop_class_uid(uids::POSITRON_EMISSION_TOMOGRAPHY_IMAGE_STORAGE)
+                .media_storage_sop_instance_uid("2.25.83562974021564095207351084729163521011"),
+        )
+        .unwrap()
+    }
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!(
+            (value - expected).abs() <= expected.abs() * 1e-9,
+            "{} != {}",
+            value,
+            expected
+        );
+    }
+
+    #[test]
+    fn suv_with_start_decay_correction() {
+        let obj = pet_object("START", Some("70"), "100000", "110000");
+        let suv = SuvParameters::from_obj(&obj).unwrap();
+        assert_eq!(suv.decay_correction, DecayCorrection::Start);
+        assert_eq!(suv.patient_weight, 70.);
+        assert_eq!(suv.injected_dose, 370e6);
+        assert_eq!(suv.elapsed, 3600.);
+
+        let decayed = 370e6 * 0.5_f64.powf(3600. / HALF_LIFE);
+        assert_close(suv.decayed_dose(), decayed);
+        assert_close(suv.scale_factor(), 70_000. / decayed);
+
+        let rescale = suv.rescale(&Rescale::new(2., 1.));
+        assert_close(rescale.slope, 2. * 70_000. / decayed);
+        assert_close(rescale.intercept, 70_000. / decayed);
+    }
+
+    #[test]
+    fn suv_with_admin_decay_correction() {
+        let obj = pet_object("ADMIN", Some("70"), "100000", "110000");
+        let suv = SuvParameters::from_obj(&obj).unwrap();
+        assert_eq!(suv.decay_correction, DecayCorrection::Admin);
+        assert_eq!(suv.decayed_dose(), 370e6);
+        assert_close(suv.scale_factor(), 70_000. / 370e6);
+    }
+
+    #[test]
+    fn suv_injection_before_midnight() {
+        let obj = pet_object("START", Some("70"), "235000", "001000");
+        let suv = SuvParameters::from_obj(&obj).unwrap();
+        assert_eq!(suv.elapsed, 1200.);
+    }
+
+    #[test]
+    fn suv_errors() {
+        let obj = pet_object("START", None, "100000", "110000");
+        assert!(matches!(
+            SuvParameters::from_obj(&obj),
+            Err(SuvError::MissingPatientWeight { .. })
+        ));
+
+        let obj = pet_object("START", Some("0"), "100000", "110000");
+        assert!(matches!(
+            SuvParameters::from_obj(&obj),
+            Err(SuvError::MissingPatientWeight { .. })
+        ));
+
+        let obj = pet_object("NONE", Some("70"), "100000", "110000");
+        assert!(matches!(
+            SuvParameters::from_obj(&obj),
+            Err(SuvError::UnsupportedDecayCorrection { .. })
+        ));
+
+        let mut obj = pet_object("START", Some("70"), "100000", "110000");
+        obj.put(DataElement::new(
+            tags::UNITS,
+            VR::CS,
+            PrimitiveValue::from("CNTS"),
+        ));
+        assert!(matches!(
+            SuvParameters::from_obj(&obj),
+            Err(SuvError::UnsupportedUnits { .. })
+        ));
+    }
+
+    #[test]
+    fn decoded_pixel_data_to_vec_suv() {
+        use crate::PixelDecoder;
+
+        let obj = pet_object("ADMIN", Some("74"), "100000", "110000");
+        let suv = SuvParameters::from_obj(&obj).unwrap();
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let values: Vec<f32> = pixel_data.to_vec_suv(&suv).unwrap();
+        assert_eq!(values.len(), 2);
+        assert!((values[0] - 0.04).abs() < 1e-6);
+        assert!((values[1] - 2.).abs() < 1e-6);
+    }
+}
@@ -7,16 +7,26 @@
 //!
 //! See the [`Transcode`] trait for more information.
 use dicom_core::{
-    ops::ApplyOp, value::PixelFragmentSequence, DataDictionary, DataElement, Length,
-    PrimitiveValue, VR,
+    ops::ApplyOp,
+    value::{trim_padding, PixelFragmentSequence},
+    DataDictionary, DataElement, Length, PrimitiveValue, VR,
+};
+use dicom_dictionary_std::{tags, uids};
+use dicom_encoding::{
+    adapters::{
+        EncodeConversion, EncodeOptions, EncodeProperty, EncodeSourceProperties, FragmentLayout,
+        FrameExtractionStrategy, PixelDataObject, SupportLevel,
+    },
+    Codec, TransferSyntax, TransferSyntaxIndex,
 };
-use dicom_dictionary_std::tags;
-use dicom_encoding::{adapters::EncodeOptions, Codec, TransferSyntax, TransferSyntaxIndex};
 use dicom_object::{FileDicomObject, InMemDicomObject};
-use dicom_transfer_syntax_registry::{entries::EXPLICIT_VR_LITTLE_ENDIAN, TransferSyntaxRegistry};
-use snafu::{OptionExt, ResultExt, Snafu};
+use dicom_transfer_syntax_registry::{
+    entries::EXPLICIT_VR_LITTLE_ENDIAN, EncodingProfile, TransferSyntaxRegistry,
+};
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+use std::convert::TryFrom;
 
-use crate::PixelDecoder;
+use crate::{PixelDecoder, PixelRepresentation, PlanarConfiguration};
 
 /// An error occurred during the object transcoding process.
 #[derive(Debug, Snafu)]
@@ -43,6 +53,37 @@ pub(crate) enum InnerError {
 
     /// Unsupported bits per sample ({bits_allocated})
     UnsupportedBitsAllocated { bits_allocated: u16 },
+
+    /// Target encoder does not support the {property} of the pixel data
+    UnsupportedEncodeProperty { property: EncodeProperty },
+
+    /// Missing image pixel attributes to select a transfer syntax
+    MissingImageAttributes,
+
+    /// No transfer syntax in the profile can encode the pixel data
+    NoSuitableTransferSyntax,
+
+    /// Pixel data in {src} cannot be moved to {dst} without re-encoding
+    IncompatibleRewrap { src: String, dst: String },
+
+    /// Missing encapsulated pixel data to rewrap
+    MissingEncapsulatedPixelData,
+
+    /// Merged pixel data fragment would be too large ({len} bytes)
+    FragmentTooLarge { len: u64 },
+
+    /// Pixel value {value} does not fit in 16 bits
+    PixelValueOutOfRange { value: i64 },
+}
+
+impl Error {
+    /// Whether rewrapping failed
+    /// because the pixel data cannot be moved
+    /// to the target transfer syntax without re-encoding
+    /// (see [`Transcode::transcode_rewrap`]).
+    pub fn is_incompatible_rewrap(&self) -> bool {
+        matches!(self.0, InnerError::IncompatibleRewrap { .. })
+    }
 }
 
 /// Alias for the result of transcoding a DICOM object.
@@ -78,9 +119,15 @@ pub trait Transcode {
     /// This method may replace one or more attributes accordingly,
     /// including the meta group specifying the transfer syntax.
     /// The encoding options only apply if the pixel data needs to be re-encoded.
+    /// These include the quality of lossy encodings
+    /// and the error range of near-lossless encodings such as JPEG-LS
+    /// (see [`EncodeOptions`]),
+    /// and are ignored with a warning by encoders which do not support them.
     ///
     /// If the receiving object's pixel data is encapsulated,
     /// the object might be first decoded into native pixel data.
+    /// This is not done if the pixel data can be rewrapped as is
+    /// (see [`transcode_rewrap`](Transcode::transcode_rewrap)).
     /// In case of an encoding error,
     /// the object may be left in an intermediate state,
     /// which should not be assumed to be consistent.
@@ -101,6 +148,52 @@ pub trait Transcode {
     fn transcode(&mut self, ts: &TransferSyntax) -> Result<()> {
         self.transcode_with_options(ts, EncodeOptions::default())
     }
+
+    /// Convert the receiving object's encapsulated pixel data
+    /// to the transfer syntax specified in `ts`
+    /// by moving its fragments as they are,
+    /// without decoding nor re-encoding the pixel data.
+    ///
+    /// This is only possible if the encoded pixel data
+    /// is also valid in the target transfer syntax,
+    /// such as from _JPEG Baseline_ to _JPEG Extended_,
+    /// or between the fragmentable and non-fragmentable variants
+    /// of the same MPEG transfer syntax,
+    /// and fails otherwise
+    /// (see [`Error::is_incompatible_rewrap`]).
+    /// Since the codestream is untouched,
+    /// there is no generational loss with lossy encodings.
+    ///
+    /// The fragments are merged into a single fragment
+    /// if the target transfer syntax requires it.
+    fn transcode_rewrap(&mut self, ts: &TransferSyntax) -> Result<()>;
+
+    /// Convert the receiving object's transfer syntax
+    /// to the first one in the given encoding profile
+    /// which can encode the object's pixel data,
+    /// according to the given encoding options.
+    ///
+    /// The transfer syntax is selected through
+    /// [`TransferSyntaxRegistry::select_encoder`],
+    /// based on the image pixel attributes currently in the object.
+    /// On success,
+    /// the UID of the transfer syntax selected is returned.
+    fn transcode_with_profile(
+        &mut self,
+        profile: EncodingProfile,
+        options: EncodeOptions,
+    ) -> Result<&'static str>
+    where
+        Self: PixelDataObject + Sized,
+    {
+        let props =
+            EncodeSourceProperties::from_object(&*self).context(MissingImageAttributesSnafu)?;
+        let ts = TransferSyntaxRegistry
+            .select_encoder(profile.preferences(), &props)
+            .context(NoSuitableTransferSyntaxSnafu)?;
+        self.transcode_with_options(ts, options)?;
+        Ok(ts.uid())
+    }
 }
 
 impl<D> Transcode for FileDicomObject<InMemDicomObject<D>>
@@ -118,6 +211,11 @@ where
             return Ok(());
         }
 
+        // move the fragments as they are if the codestream is compatible
+        if rewrap_mode(current_ts_uid, ts.uid()).is_some() {
+            return self.transcode_rewrap(ts);
+        }
+
         // inspect current object TS
         let current_ts = TransferSyntaxRegistry
             .get(current_ts_uid)
@@ -189,12 +287,92 @@ where
                 // decode pixel data
                 let decoded_pixeldata = self.decode_pixel_data().context(DecodePixelDataSnafu)?;
                 let bits_allocated = decoded_pixeldata.bits_allocated();
+                let number_of_frames = decoded_pixeldata.number_of_frames();
+                let mut bits_stored = decoded_pixeldata.bits_stored();
+                let samples_per_pixel = decoded_pixeldata.samples_per_pixel();
+                let mut photometric_interpretation = decoded_pixeldata
+                    .photometric_interpretation()
+                    .as_str()
+                    .to_string();
+
+                // ask the encoder whether it can take the decoded pixel data
+                let props = EncodeSourceProperties {
+                    rows: decoded_pixeldata.rows() as u16,
+                    cols: decoded_pixeldata.columns() as u16,
+                    samples_per_pixel,
+                    bits_allocated,
+                    bits_stored,
+                    photometric_interpretation: photometric_interpretation.clone(),
+                };
+                let conversions = match writer.supports(&props) {
+                    SupportLevel::Supported => Vec::new(),
+                    SupportLevel::Partial(conversions) => conversions,
+                    SupportLevel::Unsupported(property) => {
+                        return UnsupportedEncodePropertySnafu { property }.fail()?
+                    }
+                };
+
+                let mut samples = match bits_allocated {
+                    8 => Samples::U8(decoded_pixeldata.data().to_vec()),
+                    16 => Samples::U16(decoded_pixeldata.data_ow()),
+                    _ => return UnsupportedBitsAllocatedSnafu { bits_allocated }.fail()?,
+                };
+
+                // perform the conversions requested by the encoder
+                let frame_len = decoded_pixeldata.rows() as usize
+                    * decoded_pixeldata.columns() as usize
+                    * samples_per_pixel as usize;
+                let planar =
+                    decoded_pixeldata.planar_configuration() == PlanarConfiguration::PixelFirst;
+                for conversion in conversions {
+                    match conversion {
+                        EncodeConversion::ReduceTo8Bit => {
+                            if let Samples::U16(values) = &samples {
+                                let signed = decoded_pixeldata.pixel_representation()
+                                    == PixelRepresentation::Signed;
+                                samples = Samples::U8(reduce_to_8bit(values, bits_stored, signed));
+                                bits_stored = 8;
+                            }
+                        }
+                        EncodeConversion::RgbToYbrFull => {
+                            if samples_per_pixel != 3 || photometric_interpretation != "RGB" {
+                                return UnsupportedTranscodingSnafu.fail()?;
+                            }
+                            match &mut samples {
+                                Samples::U8(values) => {
+                                    rgb_to_ybr_full(values, frame_len, planar, 8)
+                                }
+                                Samples::U16(values) => {
+                                    rgb_to_ybr_full(values, frame_len, planar, bits_stored)
+                                }
+                            }
+                            photometric_interpretation = "YBR_FULL".to_string();
+                        }
+                        _ => return UnsupportedTranscodingSnafu.fail()?,
+                    }
+                }
 
                 // apply change to pixel data attribute
-                match bits_allocated {
-                    8 => {
+                match samples {
+                    Samples::U8(pixels) => {
                         // 8-bit samples
-                        let pixels = decoded_pixeldata.data().to_vec();
+                        if bits_allocated != 8 {
+                            self.put(DataElement::new(
+                                tags::BITS_ALLOCATED,
+                                VR::US,
+                                PrimitiveValue::from(8_u16),
+                            ));
+                            self.put(DataElement::new(
+                                tags::BITS_STORED,
+                                VR::US,
+                                PrimitiveValue::from(8_u16),
+                            ));
+                            self.put(DataElement::new(
+                                tags::HIGH_BIT,
+                                VR::US,
+                                PrimitiveValue::from(7_u16),
+                            ));
+                        }
                         self.put(DataElement::new_with_len(
                             tags::PIXEL_DATA,
                             VR::OW,
@@ -202,9 +380,8 @@ where
                             PrimitiveValue::from(pixels),
                         ));
                     }
-                    16 => {
+                    Samples::U16(pixels) => {
                         // 16-bit samples
-                        let pixels = decoded_pixeldata.data_ow();
                         self.put(DataElement::new_with_len(
                             tags::PIXEL_DATA,
                             VR::OW,
@@ -212,9 +389,21 @@ where
                             PrimitiveValue::U16(pixels.into()),
                         ));
                     }
-                    _ => return UnsupportedBitsAllocatedSnafu { bits_allocated }.fail()?,
                 };
 
+                // keep the photometric interpretation in line with the samples
+                let current_pi = self
+                    .get(tags::PHOTOMETRIC_INTERPRETATION)
+                    .and_then(|e| e.to_str().ok())
+                    .map(|pi| trim_padding(&pi).to_string());
+                if current_pi.as_deref() != Some(photometric_interpretation.as_str()) {
+                    self.put(DataElement::new(
+                        tags::PHOTOMETRIC_INTERPRETATION,
+                        VR::CS,
+                        photometric_interpretation,
+                    ));
+                }
+
                 // change transfer syntax to Explicit VR little endian
                 self.meta_mut()
                     .set_transfer_syntax(&EXPLICIT_VR_LITTLE_ENDIAN);
@@ -227,9 +416,53 @@ where
                     .encode(&*self, options, &mut fragments, &mut offset_table)
                     .context(EncodePixelDataSnafu)?;
 
-                let num_frames = offset_table.len();
+                // fragment items must have an even length
+                for fragment in &mut fragments {
+                    if fragment.len() % 2 != 0 {
+                        fragment.push(0);
+                    }
+                }
                 let total_pixeldata_len: u64 = fragments.iter().map(|f| f.len() as u64).sum();
 
+                // rebuild the offset tables from the fragments of each frame
+                let fragment_lengths: Vec<u64> = fragments.iter().map(|f| f.len() as u64).collect();
+                let tables = match frame_starts(&fragments, &offset_table, number_of_frames) {
+                    Some(starts) => OffsetTables::new(&fragment_lengths, &starts),
+                    None => {
+                        tracing::warn!(
+                            "Could not tell apart the fragments of each of the {} frames, \
+                             leaving the offset table empty",
+                            number_of_frames
+                        );
+                        OffsetTables::None
+                    }
+                };
+                let offset_table = match tables {
+                    OffsetTables::Basic(offset_table) => {
+                        self.remove_element(tags::EXTENDED_OFFSET_TABLE);
+                        self.remove_element(tags::EXTENDED_OFFSET_TABLE_LENGTHS);
+                        offset_table
+                    }
+                    OffsetTables::Extended { offsets, lengths } => {
+                        self.put(DataElement::new(
+                            tags::EXTENDED_OFFSET_TABLE,
+                            VR::OV,
+                            PrimitiveValue::U64(offsets.into()),
+                        ));
+                        self.put(DataElement::new(
+                            tags::EXTENDED_OFFSET_TABLE_LENGTHS,
+                            VR::OV,
+                            PrimitiveValue::U64(lengths.into()),
+                        ));
+                        Vec::new()
+                    }
+                    OffsetTables::None => {
+                        self.remove_element(tags::EXTENDED_OFFSET_TABLE);
+                        self.remove_element(tags::EXTENDED_OFFSET_TABLE_LENGTHS);
+                        Vec::new()
+                    }
+                };
+
                 self.put(DataElement::new_with_len(
                     tags::PIXEL_DATA,
                     VR::OB,
@@ -240,7 +473,7 @@ where
                 self.put(DataElement::new(
                     tags::NUMBER_OF_FRAMES,
                     VR::IS,
-                    num_frames.to_string(),
+                    number_of_frames.to_string(),
                 ));
 
                 // provide Encapsulated Pixel Data Value Total Length
@@ -267,19 +500,423 @@ where
             }
         }
     }
+
+    fn transcode_rewrap(&mut self, ts: &TransferSyntax) -> Result<()> {
+        let current_ts_uid = self.meta().transfer_syntax();
+        if current_ts_uid == ts.uid() {
+            return Ok(());
+        }
+
+        let mode =
+            rewrap_mode(current_ts_uid, ts.uid()).with_context(|| IncompatibleRewrapSnafu {
+                src: current_ts_uid.to_string(),
+                dst: ts.uid().to_string(),
+            })?;
+
+        let fragments = self
+            .get(tags::PIXEL_DATA)
+            .and_then(|e| e.fragments())
+            .context(MissingEncapsulatedPixelDataSnafu)?;
+
+        if mode == Rewrap::SingleFragment && fragments.len() > 1 {
+            let len: u64 = fragments.iter().map(|f| f.len() as u64).sum();
+            ensure!(len < u64::from(u32::MAX), FragmentTooLargeSnafu { len });
+            let fragment = fragments.concat();
+
+            // the offset tables no longer apply
+            self.put(DataElement::new_with_len(
+                tags::PIXEL_DATA,
+                VR::OB,
+                Length::UNDEFINED,
+                PixelFragmentSequence::new(Vec::<u32>::new(), vec![fragment]),
+            ));
+            self.remove_element(tags::EXTENDED_OFFSET_TABLE);
+            self.remove_element(tags::EXTENDED_OFFSET_TABLE_LENGTHS);
+        }
+
+        self.meta_mut().set_transfer_syntax(ts);
+        Ok(())
+    }
+}
+
+/// The range of stored pixel values of an object,
+/// as declared in the _Smallest Image Pixel Value_ (0028,0106)
+/// and _Largest Image Pixel Value_ (0028,0107) attributes
+/// and as found in the pixel data.
+///
+/// See [`check_pixel_value_range`] and [`update_pixel_value_range`].
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct PixelValueRange {
+    /// The declared _Smallest Image Pixel Value_, if any
+    pub declared_smallest: Option<i64>,
+    /// The declared _Largest Image Pixel Value_, if any
+    pub declared_largest: Option<i64>,
+    /// The smallest stored value found in the pixel data
+    pub smallest: i64,
+    /// The largest stored value found in the pixel data
+    pub largest: i64,
+}
+
+impl PixelValueRange {
+    /// Whether the declared values, where present,
+    /// match the range of values found in the pixel data.
+    pub fn is_consistent(&self) -> bool {
+        self.declared_smallest.map_or(true, |v| v == self.smallest)
+            && self.declared_largest.map_or(true, |v| v == self.largest)
+    }
+}
+
+/// Compare the _Smallest Image Pixel Value_ and _Largest Image Pixel Value_
+/// declared in the object
+/// with the range of stored values found in its pixel data,
+/// across all frames.
+///
+/// The object is left untouched.
+/// Use [`PixelValueRange::is_consistent`] to check for a mismatch.
+pub fn check_pixel_value_range<D>(
+    obj: &FileDicomObject<InMemDicomObject<D>>,
+) -> Result<PixelValueRange>
+where
+    D: Clone + DataDictionary,
+{
+    let decoded = obj.decode_pixel_data().context(DecodePixelDataSnafu)?;
+    let (smallest, largest) = decoded.value_range_all().context(DecodePixelDataSnafu)?;
+    let signed = decoded.pixel_representation() == PixelRepresentation::Signed;
+    let declared = |tag| {
+        obj.get(tag)
+            .and_then(|e| e.to_int::<i64>().ok())
+            // implicit VR may have turned SS values into US
+            .map(|v| {
+                if signed && v > i64::from(i16::MAX) {
+                    i64::from(v as i16)
+                } else {
+                    v
+                }
+            })
+    };
+    Ok(PixelValueRange {
+        declared_smallest: declared(tags::SMALLEST_IMAGE_PIXEL_VALUE),
+        declared_largest: declared(tags::LARGEST_IMAGE_PIXEL_VALUE),
+        smallest,
+        largest,
+    })
+}
+
+/// Set the _Smallest Image Pixel Value_ and _Largest Image Pixel Value_
+/// of the object to the range of stored values found in its pixel data,
+/// such as after transcoding.
+///
+/// The values declared before the update are reported
+/// alongside the new ones,
+/// so that a mismatch can still be told apart.
+/// The attributes are written with the VR _SS_ if the pixel data is signed,
+/// or _US_ otherwise,
+/// which fails if the values do not fit in 16 bits.
+pub fn update_pixel_value_range<D>(
+    obj: &mut FileDicomObject<InMemDicomObject<D>>,
+) -> Result<PixelValueRange>
+where
+    D: Clone + DataDictionary,
+{
+    let range = check_pixel_value_range(obj)?;
+    let signed = obj
+        .get(tags::PIXEL_REPRESENTATION)
+        .and_then(|e| e.to_int::<u16>().ok())
+        == Some(1);
+    let value = |v: i64| -> Result<PrimitiveValue> {
+        let value = if signed {
+            i16::try_from(v).ok().map(PrimitiveValue::from)
+        } else {
+            u16::try_from(v).ok().map(PrimitiveValue::from)
+        };
+        Ok(value.context(PixelValueOutOfRangeSnafu { value: v })?)
+    };
+    let vr = if signed { VR::SS } else { VR::US };
+    let smallest = value(range.smallest)?;
+    let largest = value(range.largest)?;
+    obj.put(DataElement::new(
+        tags::SMALLEST_IMAGE_PIXEL_VALUE,
+        vr,
+        smallest,
+    ));
+    obj.put(DataElement::new(
+        tags::LARGEST_IMAGE_PIXEL_VALUE,
+        vr,
+        largest,
+    ));
+    Ok(range)
+}
+
+/// How the pixel data fragments are moved
+/// when rewrapping them into another transfer syntax.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Rewrap {
+    /// the fragments are kept as they are
+    Verbatim,
+    /// the fragments are merged into a single fragment
+    SingleFragment,
+}
+
+/// Pairs of encapsulated transfer syntaxes (source and target)
+/// in which the encoded pixel data of the source
+/// is also valid in the target.
+///
+/// Most pairs only work in one direction,
+/// since the target admits a wider range of codestreams than the source.
+#[rustfmt::skip]
+static REWRAP_COMPATIBLE: &[(&str, &str, Rewrap)] = &[
+    // still image codestreams
+    (uids::JPEG_BASELINE8_BIT, uids::JPEG_EXTENDED12_BIT, Rewrap::Verbatim),
+    (uids::JPEG_LOSSLESS_SV1, uids::JPEG_LOSSLESS, Rewrap::Verbatim),
+    (uids::JPEGLS_LOSSLESS, uids::JPEGLS_NEAR_LOSSLESS, Rewrap::Verbatim),
+    (uids::JPEG2000_LOSSLESS, uids::JPEG2000, Rewrap::Verbatim),
+    (uids::JPEG2000MC_LOSSLESS, uids::JPEG2000MC, Rewrap::Verbatim),
+    (uids::JPEG2000_LOSSLESS, uids::HTJ2K_LOSSLESS, Rewrap::Verbatim),
+    (uids::JPEG2000_LOSSLESS, uids::HTJ2K, Rewrap::Verbatim),
+    (uids::JPEG2000, uids::HTJ2K, Rewrap::Verbatim),
+    (uids::HTJ2K_LOSSLESS_RPCL, uids::HTJ2K_LOSSLESS, Rewrap::Verbatim),
+    (uids::HTJ2K_LOSSLESS_RPCL, uids::HTJ2K, Rewrap::Verbatim),
+    (uids::HTJ2K_LOSSLESS, uids::HTJ2K, Rewrap::Verbatim),
+    (uids::JPEGXL_LOSSLESS, uids::JPEGXL, Rewrap::Verbatim),
+    (uids::JPEGXLJPEG_RECOMPRESSION, uids::JPEGXL, Rewrap::Verbatim),
+    // the non-fragmentable video transfer syntaxes
+    // require the whole stream in a single fragment
+    (uids::MPEG2MPML, uids::MPEG2MPMLF, Rewrap::Verbatim),
+    (uids::MPEG2MPMLF, uids::MPEG2MPML, Rewrap::SingleFragment),
+    (uids::MPEG2MPHL, uids::MPEG2MPHLF, Rewrap::Verbatim),
+    (uids::MPEG2MPHLF, uids::MPEG2MPHL, Rewrap::SingleFragment),
+    (uids::MPEG4HP41, uids::MPEG4HP41F, Rewrap::Verbatim),
+    (uids::MPEG4HP41F, uids::MPEG4HP41, Rewrap::SingleFragment),
+    (uids::MPEG4HP41BD, uids::MPEG4HP41BDF, Rewrap::Verbatim),
+    (uids::MPEG4HP41BDF, uids::MPEG4HP41BD, Rewrap::SingleFragment),
+    (uids::MPEG4HP422D, uids::MPEG4HP422DF, Rewrap::Verbatim),
+    (uids::MPEG4HP422DF, uids::MPEG4HP422D, Rewrap::SingleFragment),
+    (uids::MPEG4HP423D, uids::MPEG4HP423DF, Rewrap::Verbatim),
+    (uids::MPEG4HP423DF, uids::MPEG4HP423D, Rewrap::SingleFragment),
+    (uids::MPEG4HP42STEREO, uids::MPEG4HP42STEREOF, Rewrap::Verbatim),
+    (uids::MPEG4HP42STEREOF, uids::MPEG4HP42STEREO, Rewrap::SingleFragment),
+];
+
+/// Determine how to rewrap pixel data from one transfer syntax to another,
+/// or `None` if it cannot be done without re-encoding.
+fn rewrap_mode(src_uid: &str, dst_uid: &str) -> Option<Rewrap> {
+    REWRAP_COMPATIBLE
+        .iter()
+        .find(|(src, dst, _)| *src == src_uid && *dst == dst_uid)
+        .map(|(_, _, mode)| *mode)
+}
+
+/// Pixel data samples in native form, as held during transcoding.
+enum Samples {
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+}
+
+/// Reduce 16-bit samples to 8 bits,
+/// keeping the most significant bits of the stored value.
+/// Signed samples keep their sign as 8-bit two's complement values.
+fn reduce_to_8bit(values: &[u16], bits_stored: u16, signed: bool) -> Vec<u8> {
+    let bits_stored = bits_stored.clamp(1, 16);
+    let shift = bits_stored.saturating_sub(8);
+    if signed {
+        // extend the sign of the stored value before shifting
+        let unused = 16 - bits_stored;
+        values
+            .iter()
+            .map(|&v| ((((v << unused) as i16) >> unused >> shift) as i8) as u8)
+            .collect()
+    } else {
+        values
+            .iter()
+            .map(|&v| (v >> shift).min(255) as u8)
+            .collect()
+    }
+}
+
+/// Convert RGB samples to YBR_FULL in place,
+/// frame by frame and according to the planar configuration.
+fn rgb_to_ybr_full<T>(values: &mut [T], frame_len: usize, planar: bool, bits_stored: u16)
+where
+    T: Copy + Into<f64> + num_traits::NumCast,
+{
+    let bits_stored = bits_stored.clamp(1, 16);
+    let max = ((1_u32 << bits_stored) - 1) as f64;
+    let offset = (1_u32 << (bits_stored - 1)) as f64;
+    let convert = |r: T, g: T, b: T| -> (T, T, T) {
+        let (r, g, b): (f64, f64, f64) = (r.into(), g.into(), b.into());
+        let y = 0.299 * r + 0.587 * g + 0.114 * b;
+        let cb = -0.168736 * r - 0.331264 * g + 0.5 * b + offset;
+        let cr = 0.5 * r - 0.418688 * g - 0.081312 * b + offset;
+        let cast = |v: f64| num_traits::cast(v.round().clamp(0., max)).unwrap();
+        (cast(y), cast(cb), cast(cr))
+    };
+
+    if frame_len == 0 {
+        return;
+    }
+    for frame in values.chunks_exact_mut(frame_len) {
+        if planar {
+            let plane_len = frame_len / 3;
+            for i in 0..plane_len {
+                let (y, cb, cr) = convert(frame[i], frame[i + plane_len], frame[i + 2 * plane_len]);
+                frame[i] = y;
+                frame[i + plane_len] = cb;
+                frame[i + 2 * plane_len] = cr;
+            }
+        } else {
+            for pixel in frame.chunks_exact_mut(3) {
+                let (y, cb, cr) = convert(pixel[0], pixel[1], pixel[2]);
+                pixel[0] = y;
+                pixel[1] = cb;
+                pixel[2] = cr;
+            }
+        }
+    }
+}
+
+/// The offset tables of newly encoded pixel data.
+#[derive(Debug, Clone, PartialEq)]
+enum OffsetTables {
+    /// A basic offset table, with one entry per frame.
+    Basic(Vec<u32>),
+    /// An extended offset table and the length of each frame,
+    /// for when the offsets do not fit in a basic offset table.
+    Extended {
+        offsets: Vec<u64>,
+        lengths: Vec<u64>,
+    },
+    /// No offset table.
+    None,
+}
+
+impl OffsetTables {
+    /// Build the offset tables of pixel data
+    /// with fragments of the given (even) lengths,
+    /// given the index of the first fragment of each frame.
+    ///
+    /// The basic offset table is preferred.
+    /// Otherwise, the extended offset table requires
+    /// each frame to be in a single fragment.
+    fn new(fragment_lengths: &[u64], frame_starts: &[usize]) -> Self {
+        // the position of each fragment item relative to the first one
+        let positions: Vec<u64> = fragment_lengths
+            .iter()
+            .scan(0, |position, len| {
+                let current = *position;
+                *position += 8 + len;
+                Some(current)
+            })
+            .collect();
+        let offsets: Vec<u64> = frame_starts.iter().map(|&i| positions[i]).collect();
+
+        if let Ok(offsets) = offsets
+            .iter()
+            .map(|&offset| u32::try_from(offset))
+            .collect::<Result<Vec<_>, _>>()
+        {
+            OffsetTables::Basic(offsets)
+        } else if frame_starts.len() == fragment_lengths.len() {
+            OffsetTables::Extended {
+                offsets,
+                lengths: fragment_lengths.to_vec(),
+            }
+        } else {
+            OffsetTables::None
+        }
+    }
+}
+
+/// Identify the index of the first fragment of each frame
+/// in newly encoded pixel data,
+/// using the offset table provided by the encoder
+/// if there are more fragments than frames.
+///
+/// Returns `None` if the frames cannot be told apart.
+fn frame_starts<F>(
+    fragments: &[F],
+    offset_table: &[u32],
+    number_of_frames: u32,
+) -> Option<Vec<usize>>
+where
+    F: AsRef<[u8]>,
+{
+    let layout = FragmentLayout::from_fragments(number_of_frames, fragments, offset_table);
+    if number_of_frames > 1
+        && fragments.len() != number_of_frames as usize
+        && !layout.offset_table_is_consistent()
+    {
+        return None;
+    }
+    let strategy = FrameExtractionStrategy::determine(&layout);
+    (0..number_of_frames)
+        .map(|frame| {
+            strategy
+                .frame_fragments(&layout, frame)
+                .map(|range| range.start)
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use dicom_dictionary_std::uids;
-    use dicom_object::open_file;
+    use dicom_core::ops::AttributeOp;
+    use dicom_encoding::{
+        adapters::{EncodeResult, PixelDataObject, PixelDataWriter},
+        NeverAdapter, NeverPixelAdapter,
+    };
+    use dicom_object::{open_file, FileMetaTableBuilder};
     #[cfg(feature = "native")]
     use dicom_transfer_syntax_registry::entries::JPEG_BASELINE;
     use dicom_transfer_syntax_registry::entries::{
-        ENCAPSULATED_UNCOMPRESSED_EXPLICIT_VR_LITTLE_ENDIAN, JPEG_EXTENDED,
+        ENCAPSULATED_UNCOMPRESSED_EXPLICIT_VR_LITTLE_ENDIAN,
+        FRAGMENTABLE_MPEG4_AVC_H264_HIGH_PROFILE, JPEG_EXTENDED, MPEG4_AVC_H264_HIGH_PROFILE,
     };
 
+    /// test encoder which only accepts color samples in YBR_FULL
+    #[derive(Debug)]
+    struct YbrFullWriter;
+
+    impl PixelDataWriter for YbrFullWriter {
+        fn supports(&self, props: &EncodeSourceProperties) -> SupportLevel {
+            match (
+                props.samples_per_pixel,
+                props.photometric_interpretation.as_str(),
+            ) {
+                (3, "YBR_FULL") => SupportLevel::Supported,
+                (3, "RGB") => SupportLevel::Partial(vec![EncodeConversion::RgbToYbrFull]),
+                (3, _) => SupportLevel::Unsupported(EncodeProperty::PhotometricInterpretation),
+                _ => SupportLevel::Unsupported(EncodeProperty::SamplesPerPixel),
+            }
+        }
+
+        fn encode_frame(
+            &self,
+            src: &dyn PixelDataObject,
+            frame: u32,
+            _options: EncodeOptions,
+            dst: &mut Vec<u8>,
+        ) -> EncodeResult<Vec<AttributeOp>> {
+            assert_eq!(src.photometric_interpretation(), Some("YBR_FULL"));
+            let frame_size = src.rows().unwrap() as usize * src.cols().unwrap() as usize * 3;
+            let data = &src.raw_pixel_data().unwrap().fragments[0];
+            dst.extend_from_slice(
+                &data[frame_size * frame as usize..frame_size * (frame as usize + 1)],
+            );
+            Ok(vec![])
+        }
+    }
+
+    /// transfer syntax using the YBR_FULL-only test encoder
+    fn ybr_full_ts() -> TransferSyntax {
+        TransferSyntax::<NeverAdapter, NeverPixelAdapter, _>::new_ele(
+            "1.2.826.0.1.3680043.9.5560.3127449359877365688774406533090568532",
+            "YBR_FULL test encoding",
+            Codec::EncapsulatedPixelData(None, Some(YbrFullWriter)),
+        )
+        .erased()
+    }
+
     #[cfg(feature = "native")]
     #[test]
     fn test_transcode_from_jpeg_lossless_to_native_rgb() {
@@ -530,4 +1167,404 @@ mod tests {
         assert_eq!(fragments[0].len(), 100 * 100 * 3);
         assert_eq!(fragments[1].len(), 100 * 100 * 3);
     }
+
+    /// the transcoder converts RGB to YBR_FULL
+    /// when the target encoder asks for it
+    #[test]
+    fn test_transcode_with_rgb_to_ybr_conversion() {
+        let test_file = dicom_test_files::path("pydicom/SC_rgb.dcm").unwrap();
+        let mut obj = open_file(test_file).unwrap();
+
+        let rgb = obj
+            .get(tags::PIXEL_DATA)
+            .unwrap()
+            .to_bytes()
+            .unwrap()
+            .to_vec();
+        assert_eq!(
+            obj.get(tags::PHOTOMETRIC_INTERPRETATION)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .trim(),
+            "RGB"
+        );
+
+        let ts = ybr_full_ts();
+        obj.transcode(&ts)
+            .expect("Should have transcoded successfully");
+
+        assert_eq!(obj.meta().transfer_syntax(), ts.uid());
+        assert_eq!(
+            obj.get(tags::PHOTOMETRIC_INTERPRETATION)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .trim(),
+            "YBR_FULL"
+        );
+
+        let fragments = obj.get(tags::PIXEL_DATA).unwrap().fragments().unwrap();
+        assert_eq!(fragments.len(), 1);
+        let ybr = &fragments[0];
+        assert_eq!(ybr.len(), rgb.len());
+
+        for (rgb, ybr) in rgb.chunks_exact(3).zip(ybr.chunks_exact(3)) {
+            let (r, g, b) = (rgb[0] as f64, rgb[1] as f64, rgb[2] as f64);
+            let y = (0.299 * r + 0.587 * g + 0.114 * b).round();
+            let cb = (-0.168736 * r - 0.331264 * g + 0.5 * b + 128.).round();
+            let cr = (0.5 * r - 0.418688 * g - 0.081312 * b + 128.).round();
+            assert_eq!(ybr, &[y as u8, cb as u8, cr as u8]);
+        }
+    }
+
+    /// test encoder which splits each frame in two fragments,
+    /// optionally providing a basic offset table
+    #[derive(Debug)]
+    struct SplitWriter {
+        offset_table: bool,
+    }
+
+    impl PixelDataWriter for SplitWriter {
+        fn encode(
+            &self,
+            src: &dyn PixelDataObject,
+            options: EncodeOptions,
+            dst: &mut Vec<Vec<u8>>,
+            offset_table: &mut Vec<u32>,
+        ) -> EncodeResult<Vec<AttributeOp>> {
+            let mut offset = 0;
+            for frame in 0..src.number_of_frames().unwrap() {
+                let mut data = Vec::new();
+                self.encode_frame(src, frame, options.clone(), &mut data)?;
+                let (first, second) = data.split_at(data.len() / 2);
+                if self.offset_table {
+                    offset_table.push(offset);
+                }
+                offset += first.len() as u32 + second.len() as u32 + 16;
+                dst.push(first.to_vec());
+                dst.push(second.to_vec());
+            }
+            Ok(vec![])
+        }
+
+        fn encode_frame(
+            &self,
+            src: &dyn PixelDataObject,
+            frame: u32,
+            _options: EncodeOptions,
+            dst: &mut Vec<u8>,
+        ) -> EncodeResult<Vec<AttributeOp>> {
+            let frame_size = src.rows().unwrap() as usize * src.cols().unwrap() as usize * 3;
+            let data = &src.raw_pixel_data().unwrap().fragments[0];
+            dst.extend_from_slice(
+                &data[frame_size * frame as usize..frame_size * (frame as usize + 1)],
+            );
+            Ok(vec![])
+        }
+    }
+
+    /// transfer syntax using the fragment splitting test encoder
+    fn split_ts(offset_table: bool) -> TransferSyntax {
+        TransferSyntax::<NeverAdapter, NeverPixelAdapter, _>::new_ele(
+            "1.2.826.0.1.3680043.9.5560.3127449359877365688774406533090568533",
+            "Split fragments test encoding",
+            Codec::EncapsulatedPixelData(None, Some(SplitWriter { offset_table })),
+        )
+        .erased()
+    }
+
+    /// the basic offset table points to the first fragment of each frame
+    /// when the encoder emits more than one fragment per frame
+    #[test]
+    fn test_transcode_multiple_fragments_per_frame() {
+        let test_file = dicom_test_files::path("pydicom/SC_rgb_2frame.dcm").unwrap();
+        let mut obj = open_file(&test_file).unwrap();
+        let frame_size = 100 * 100 * 3;
+        let native = obj
+            .get(tags::PIXEL_DATA)
+            .unwrap()
+            .to_bytes()
+            .unwrap()
+            .to_vec();
+
+        obj.transcode(&split_ts(true))
+            .expect("Should have transcoded successfully");
+
+        assert_eq!(
+            obj.get(tags::NUMBER_OF_FRAMES)
+                .unwrap()
+                .to_int::<u32>()
+                .unwrap(),
+            2
+        );
+        let seq = obj
+            .get(tags::PIXEL_DATA)
+            .unwrap()
+            .value()
+            .pixel_sequence()
+            .unwrap();
+        assert_eq!(seq.fragments().len(), 4);
+        assert_eq!(seq.offset_table(), &[0, frame_size as u32 + 16]);
+        assert_eq!(
+            crate::encapsulation::frame_data(seq, 2, 1).as_deref(),
+            Some(&native[frame_size..])
+        );
+        assert!(obj.get(tags::EXTENDED_OFFSET_TABLE).is_none());
+
+        // without an offset table from the encoder,
+        // frames cannot be told apart
+        let mut obj = open_file(&test_file).unwrap();
+        obj.transcode(&split_ts(false))
+            .expect("Should have transcoded successfully");
+        let fragments = obj.get(tags::PIXEL_DATA).unwrap().fragments().unwrap();
+        assert_eq!(fragments.len(), 4);
+        let offset_table = obj.get(tags::PIXEL_DATA).unwrap().offset_table().unwrap();
+        assert!(offset_table.is_empty());
+    }
+
+    /// the basic offset table is built for each frame,
+    /// so that a single frame can be decoded
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_transcode_multi_frame_offset_table() {
+        let test_file = dicom_test_files::path("pydicom/color3d_jpeg_baseline.dcm").unwrap();
+        let mut obj = open_file(test_file).unwrap();
+        obj.transcode(&EXPLICIT_VR_LITTLE_ENDIAN.erased())
+            .expect("Should have decoded successfully");
+        obj.transcode(&JPEG_BASELINE.erased())
+            .expect("Should have transcoded successfully");
+
+        let seq = obj
+            .get(tags::PIXEL_DATA)
+            .unwrap()
+            .value()
+            .pixel_sequence()
+            .unwrap();
+        assert_eq!(seq.fragments().len(), 120);
+        let mut offset = 0;
+        for (fragment, &entry) in seq.fragments().iter().zip(seq.offset_table()) {
+            assert_eq!(entry, offset);
+            offset += fragment.len() as u32 + 8;
+        }
+        assert_eq!(seq.offset_table().len(), 120);
+
+        let all = obj.decode_pixel_data().unwrap();
+        let frame = obj.decode_pixel_data_frame(60).unwrap();
+        assert_eq!(frame.number_of_frames(), 1);
+        assert_eq!(frame.data(), all.frame_data(60).unwrap());
+    }
+
+    /// signed samples keep their sign when reduced to 8 bits
+    #[test]
+    fn test_reduce_to_8bit() {
+        assert_eq!(
+            reduce_to_8bit(&[0, 0x0100, 0x0FFF], 12, false),
+            vec![0, 0x10, 0xFF]
+        );
+        // 12-bit signed values, with or without the sign extended
+        assert_eq!(
+            reduce_to_8bit(&[0x07FF, 0x0800, 0xF800, 0x0FFF, 0xFFFF], 12, true),
+            vec![0x7F, 0x80, 0x80, 0xFF, 0xFF]
+        );
+    }
+
+    /// offsets beyond 32 bits are written to the extended offset table
+    #[test]
+    fn test_offset_tables() {
+        // one fragment per frame
+        assert_eq!(
+            OffsetTables::new(&[10, 20, 30], &[0, 1, 2]),
+            OffsetTables::Basic(vec![0, 18, 46])
+        );
+        // two fragments per frame
+        assert_eq!(
+            OffsetTables::new(&[10, 20, 30, 40], &[0, 2]),
+            OffsetTables::Basic(vec![0, 46])
+        );
+
+        let big = 3_000_000_000;
+        assert_eq!(
+            OffsetTables::new(&[big, big, 2], &[0, 1, 2]),
+            OffsetTables::Extended {
+                offsets: vec![0, big + 8, 2 * big + 16],
+                lengths: vec![big, big, 2],
+            }
+        );
+        // the extended offset table requires one fragment per frame
+        assert_eq!(
+            OffsetTables::new(&[big, big, 2, 2], &[0, 2]),
+            OffsetTables::None
+        );
+    }
+
+    /// the declared range of pixel values is compared against the pixel data,
+    /// and the previous declaration is reported on update
+    #[test]
+    fn test_update_pixel_value_range() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let mut obj = open_file(test_file).unwrap();
+        let (smallest, largest) = obj.decode_pixel_data().unwrap().value_range_all().unwrap();
+
+        obj.put(DataElement::new(
+            tags::SMALLEST_IMAGE_PIXEL_VALUE,
+            VR::SS,
+            PrimitiveValue::from(i16::MIN),
+        ));
+        obj.remove_element(tags::LARGEST_IMAGE_PIXEL_VALUE);
+
+        let range = check_pixel_value_range(&obj).unwrap();
+        assert_eq!(
+            range,
+            PixelValueRange {
+                declared_smallest: Some(i64::from(i16::MIN)),
+                declared_largest: None,
+                smallest,
+                largest,
+            }
+        );
+        assert!(!range.is_consistent());
+
+        assert_eq!(update_pixel_value_range(&mut obj).unwrap(), range);
+
+        let element = obj.get(tags::SMALLEST_IMAGE_PIXEL_VALUE).unwrap();
+        assert_eq!(element.vr(), VR::SS);
+        assert_eq!(element.to_int::<i64>().unwrap(), smallest);
+        let element = obj.get(tags::LARGEST_IMAGE_PIXEL_VALUE).unwrap();
+        assert_eq!(element.vr(), VR::SS);
+        assert_eq!(element.to_int::<i64>().unwrap(), largest);
+
+        let range = check_pixel_value_range(&obj).unwrap();
+        assert_eq!(range.declared_smallest, Some(smallest));
+        assert_eq!(range.declared_largest, Some(largest));
+        assert!(range.is_consistent());
+    }
+
+    /// transcoding with a profile selects the first workable transfer syntax
+    #[cfg(feature = "native")]
+    #[test]
+    fn test_transcode_with_profile() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let mut obj = open_file(test_file).unwrap();
+
+        // 16-bit samples are reduced to 8 bits for JPEG baseline
+        let ts_uid = obj
+            .transcode_with_profile(EncodingProfile::LossyWeb, EncodeOptions::default())
+            .expect("Should have transcoded successfully");
+
+        assert_eq!(ts_uid, JPEG_BASELINE.uid());
+        assert_eq!(obj.meta().transfer_syntax(), JPEG_BASELINE.uid());
+        assert_eq!(
+            obj.get(tags::BITS_ALLOCATED)
+                .unwrap()
+                .to_int::<u16>()
+                .unwrap(),
+            8
+        );
+        assert!(obj.get(tags::PIXEL_DATA).unwrap().fragments().is_some());
+    }
+
+    /// the transcoder fails with a typed error
+    /// when the target encoder cannot take the pixel data
+    #[test]
+    fn test_transcode_unsupported_encode_property() {
+        let test_file = dicom_test_files::path("pydicom/CT_small.dcm").unwrap();
+        let mut obj = open_file(test_file).unwrap();
+
+        let err = obj
+            .transcode(&ybr_full_ts())
+            .expect_err("Should not have transcoded successfully");
+        assert!(matches!(
+            err.0,
+            InnerError::UnsupportedEncodeProperty {
+                property: EncodeProperty::SamplesPerPixel
+            }
+        ));
+    }
+
+    /// JPEG baseline fragments can be moved as they are to JPEG extended
+    #[test]
+    fn test_transcode_rewrap_jpeg_baseline_to_extended() {
+        let test_file = dicom_test_files::path("pydicom/color3d_jpeg_baseline.dcm").unwrap();
+        let mut obj = open_file(test_file).unwrap();
+
+        assert_eq!(obj.meta().transfer_syntax(), uids::JPEG_BASELINE8_BIT);
+        let pixel_data = obj.get(tags::PIXEL_DATA).unwrap();
+        let fragments = pixel_data.fragments().unwrap().to_vec();
+        let offset_table = pixel_data.offset_table().unwrap().to_vec();
+
+        obj.transcode_rewrap(&JPEG_EXTENDED.erased())
+            .expect("Should have rewrapped successfully");
+
+        assert_eq!(obj.meta().transfer_syntax(), uids::JPEG_EXTENDED12_BIT);
+        let pixel_data = obj.get(tags::PIXEL_DATA).unwrap();
+        assert_eq!(pixel_data.fragments().unwrap(), &fragments[..]);
+        assert_eq!(pixel_data.offset_table().unwrap(), &offset_table[..]);
+
+        // the other way around requires re-encoding
+        let jpeg_baseline = TransferSyntaxRegistry
+            .get(uids::JPEG_BASELINE8_BIT)
+            .unwrap();
+        let err = obj
+            .transcode_rewrap(jpeg_baseline)
+            .expect_err("Should not have rewrapped successfully");
+        assert!(err.is_incompatible_rewrap());
+        assert_eq!(obj.meta().transfer_syntax(), uids::JPEG_EXTENDED12_BIT);
+    }
+
+    /// regular transcoding does not decode pixel data
+    /// which can be rewrapped
+    #[test]
+    fn test_transcode_prefers_rewrap() {
+        let test_file = dicom_test_files::path("pydicom/color3d_jpeg_baseline.dcm").unwrap();
+        let mut obj = open_file(test_file).unwrap();
+        let fragments = obj
+            .get(tags::PIXEL_DATA)
+            .unwrap()
+            .fragments()
+            .unwrap()
+            .to_vec();
+
+        obj.transcode(&JPEG_EXTENDED.erased())
+            .expect("Should have transcoded successfully");
+
+        assert_eq!(obj.meta().transfer_syntax(), uids::JPEG_EXTENDED12_BIT);
+        assert_eq!(
+            obj.get(tags::PIXEL_DATA).unwrap().fragments().unwrap(),
+            &fragments[..]
+        );
+    }
+
+    /// fragments are merged when rewrapping into a non-fragmentable
+    /// MPEG transfer syntax, and kept as they are in the reverse direction
+    #[test]
+    fn test_transcode_rewrap_mpeg4_fragments() {
+        let mut obj = InMemDicomObject::from_element_iter([DataElement::new_with_len(
+            tags::PIXEL_DATA,
+            VR::OB,
+            Length::UNDEFINED,
+            PixelFragmentSequence::new(Vec::<u32>::new(), vec![vec![1, 2], vec![3, 4, 5, 6]]),
+        )])
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .transfer_syntax(uids::MPEG4HP41F)
+                .media_storage_sop_class_uid(uids::VIDEO_PHOTOGRAPHIC_IMAGE_STORAGE)
+                .media_storage_sop_instance_uid("2.25.142853207932466127917463916231839617802"),
+        )
+        .unwrap();
+
+        obj.transcode_rewrap(&MPEG4_AVC_H264_HIGH_PROFILE.erased())
+            .expect("Should have rewrapped successfully");
+
+        assert_eq!(obj.meta().transfer_syntax(), uids::MPEG4HP41);
+        let fragments = obj.get(tags::PIXEL_DATA).unwrap().fragments().unwrap();
+        assert_eq!(fragments, &[vec![1_u8, 2, 3, 4, 5, 6]]);
+
+        obj.transcode_rewrap(&FRAGMENTABLE_MPEG4_AVC_H264_HIGH_PROFILE.erased())
+            .expect("Should have rewrapped successfully");
+
+        assert_eq!(obj.meta().transfer_syntax(), uids::MPEG4HP41F);
+        let fragments = obj.get(tags::PIXEL_DATA).unwrap().fragments().unwrap();
+        assert_eq!(fragments, &[vec![1_u8, 2, 3, 4, 5, 6]]);
+    }
 }
@@ -26,6 +26,63 @@ impl Rescale {
     }
 }
 
+/// A tabular Modality LUT,
+/// as described by the item of the _Modality LUT Sequence_.
+///
+/// Unlike a [`VoiLut`],
+/// the table entries are the output values themselves,
+/// in the units given by the _Modality LUT Type_.
+/// Stored values below the first mapped value
+/// are mapped to the first entry of the table,
+/// and stored values beyond the last entry
+/// are mapped to the last entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModalityLut {
+    /// The first stored value mapped by the table
+    /// (second value of the _LUT Descriptor_).
+    pub first_mapped: i32,
+    /// The number of bits of each entry in the table
+    /// (third value of the _LUT Descriptor_).
+    pub bits: u16,
+    /// The table entries (_LUT Data_).
+    pub data: Vec<u16>,
+    /// The units of the output values (_Modality LUT Type_), if any.
+    pub lut_type: Option<String>,
+    /// The free form description of the table (_LUT Explanation_), if any.
+    pub explanation: Option<String>,
+}
+
+impl ModalityLut {
+    /// Map a stored value to its entry in the table.
+    pub fn apply(&self, value: f64) -> f64 {
+        let last = match self.data.len().checked_sub(1) {
+            Some(last) => last as f64,
+            None => return 0.,
+        };
+        let index = (value.round() - f64::from(self.first_mapped)).clamp(0., last);
+        f64::from(self.data[index as usize])
+    }
+}
+
+/// The Modality LUT transformation to apply to the samples of a frame,
+/// either by rescaling or through a table.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum ModalityTransform<'a> {
+    Rescale(Rescale),
+    Table(&'a ModalityLut),
+}
+
+impl ModalityTransform<'_> {
+    /// Apply the transformation to a stored value.
+    #[inline]
+    pub(crate) fn apply(&self, value: f64) -> f64 {
+        match self {
+            ModalityTransform::Rescale(rescale) => rescale.apply(value),
+            ModalityTransform::Table(table) => table.apply(value),
+        }
+    }
+}
+
 /// A known DICOM Value of Interest (VOI) LUT function descriptor.
 #[derive(Debug, Default, Copy, Clone, Eq, Hash, PartialEq)]
 pub enum VoiLutFunction {
@@ -70,6 +127,44 @@ pub struct WindowLevel {
     pub center: f64,
 }
 
+/// The window levels defined for an image,
+/// either shared by all frames or specific to each frame.
+///
+/// Multiple window levels for the same frame
+/// are alternative windows for viewing that frame,
+/// as conveyed by the multiplicity of _Window Center_ and _Window Width_.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowLevels {
+    /// Alternative window levels shared by all frames.
+    Shared(Vec<WindowLevel>),
+    /// Alternative window levels for each frame.
+    PerFrame(Vec<Vec<WindowLevel>>),
+}
+
+impl WindowLevels {
+    /// Retrieve the alternative window levels applicable to the given frame.
+    ///
+    /// When window levels are defined per frame
+    /// but not for the given frame,
+    /// the window levels of the first frame are returned.
+    pub fn for_frame(&self, frame: u32) -> &[WindowLevel] {
+        match self {
+            WindowLevels::Shared(windows) => windows,
+            WindowLevels::PerFrame(frames) => frames
+                .get(frame as usize)
+                .or(frames.first())
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Retrieve the first window level applicable to the given frame, if any.
+    #[inline]
+    pub fn first_for_frame(&self, frame: u32) -> Option<WindowLevel> {
+        self.for_frame(frame).first().copied()
+    }
+}
+
 /// A full description of a VOI LUT function transformation
 /// based on a window level.
 #[derive(Debug, PartialEq)]
@@ -135,6 +230,107 @@ impl WindowLevelTransform {
     }
 }
 
+/// A tabular VOI LUT,
+/// as described by an item of the _VOI LUT Sequence_.
+///
+/// Input values below the first mapped value
+/// are mapped to the first entry of the table,
+/// and input values beyond the last entry
+/// are mapped to the last entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoiLut {
+    /// The first input value mapped by the table
+    /// (second value of the _LUT Descriptor_).
+    pub first_mapped: i32,
+    /// The number of bits of each entry in the table
+    /// (third value of the _LUT Descriptor_).
+    pub bits: u16,
+    /// The table entries (_LUT Data_).
+    pub data: Vec<u16>,
+    /// The free form description of the table (_LUT Explanation_), if any.
+    pub explanation: Option<String>,
+}
+
+impl VoiLut {
+    /// Apply the table on a rescaled value,
+    /// into a number between `0` and `y_max`.
+    pub fn apply(&self, value: f64, y_max: f64) -> f64 {
+        let last = match self.data.len().checked_sub(1) {
+            Some(last) => last as f64,
+            None => return 0.,
+        };
+        let index = (value.round() - f64::from(self.first_mapped)).clamp(0., last);
+        let entry_max = ((1_u32 << self.bits.clamp(1, 16)) - 1) as f64;
+        let entry = f64::from(self.data[index as usize]).min(entry_max);
+        entry * y_max / entry_max
+    }
+}
+
+/// The palette color lookup tables
+/// of an image with the _PALETTE COLOR_ photometric interpretation,
+/// which map each stored value to a red, green, and blue entry.
+///
+/// Stored values below the first mapped value
+/// are mapped to the first entry of each table,
+/// and stored values beyond the last entry
+/// are mapped to the last entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteColorLut {
+    /// The first stored value mapped by the tables
+    /// (second value of the _Palette Color Lookup Table Descriptor_).
+    pub first_mapped: i32,
+    /// The number of bits of each entry in the tables, usually 8 or 16
+    /// (third value of the _Palette Color Lookup Table Descriptor_).
+    pub bits: u16,
+    /// The entries of the red table.
+    pub red: Vec<u16>,
+    /// The entries of the green table.
+    pub green: Vec<u16>,
+    /// The entries of the blue table.
+    pub blue: Vec<u16>,
+}
+
+impl PaletteColorLut {
+    /// Map a stored value to its red, green, and blue entries.
+    pub fn apply(&self, value: i32) -> [u16; 3] {
+        let index = (i64::from(value) - i64::from(self.first_mapped)).max(0) as usize;
+        let entry = |table: &[u16]| match table.get(index) {
+            Some(entry) => *entry,
+            None => table.last().copied().unwrap_or(0),
+        };
+        [entry(&self.red), entry(&self.green), entry(&self.blue)]
+    }
+}
+
+/// The stored values which denote padding rather than image content,
+/// as described by the _Pixel Padding Value_
+/// and the _Pixel Padding Range Limit_.
+///
+/// Both values are stored values,
+/// interpreted as signed if the pixel representation is signed.
+#[derive(Debug, Copy, Clone, Eq, Hash, PartialEq)]
+pub struct PixelPadding {
+    /// The _Pixel Padding Value_.
+    pub value: i32,
+    /// The _Pixel Padding Range Limit_, if any,
+    /// which makes all values between it and the padding value
+    /// (inclusive) padding values.
+    pub range_limit: Option<i32>,
+}
+
+impl PixelPadding {
+    /// Check whether the given stored value is a padding value.
+    pub fn contains(&self, value: f64) -> bool {
+        let limit = self.range_limit.unwrap_or(self.value);
+        let (low, high) = if limit < self.value {
+            (limit, self.value)
+        } else {
+            (self.value, limit)
+        };
+        value >= f64::from(low) && value <= f64::from(high)
+    }
+}
+
 fn window_level_linear(value: f64, window_width: f64, window_center: f64, y_max: f64) -> f64 {
     let ww = window_width;
     let wc = window_center;
@@ -237,6 +433,39 @@ mod tests {
         assert!((y - expected_y).abs() < 1e-3);
     }
 
+    /// Window levels are resolved by frame,
+    /// keeping all alternative windows of each frame.
+    #[test]
+    fn window_levels_for_frame() {
+        let w1 = WindowLevel {
+            width: 400.,
+            center: 40.,
+        };
+        let w2 = WindowLevel {
+            width: 1500.,
+            center: -600.,
+        };
+        let w3 = WindowLevel {
+            width: 80.,
+            center: 35.,
+        };
+
+        let shared = WindowLevels::Shared(vec![w1, w2]);
+        assert_eq!(shared.for_frame(0), &[w1, w2]);
+        assert_eq!(shared.for_frame(5), &[w1, w2]);
+        assert_eq!(shared.first_for_frame(5), Some(w1));
+
+        let per_frame = WindowLevels::PerFrame(vec![vec![w1, w2], vec![w3]]);
+        assert_eq!(per_frame.for_frame(0), &[w1, w2]);
+        assert_eq!(per_frame.for_frame(1), &[w3]);
+        assert_eq!(per_frame.first_for_frame(1), Some(w3));
+        // falls back to the first frame
+        assert_eq!(per_frame.for_frame(2), &[w1, w2]);
+
+        let empty = WindowLevels::PerFrame(vec![]);
+        assert_eq!(empty.first_for_frame(0), None);
+    }
+
     /// Applying a linear window level gives us the expected outcome.
     #[test]
     fn window_level_linear_1() {
@@ -265,4 +494,189 @@ mod tests {
         let y = window_level_transform.apply(50., y_max);
         assert!(y > 127. && y < 129.);
     }
+
+    /// Applying an exact linear window level
+    /// gives the same outcome as pydicom's `apply_voi_lut`
+    /// on an 8-bit image.
+    #[test]
+    fn window_level_linear_exact_8bit() {
+        use std::convert::TryFrom;
+
+        let window_level = WindowLevel {
+            width: 100.,
+            center: 128.,
+        };
+        let function = VoiLutFunction::try_from("LINEAR_EXACT").unwrap();
+        assert_eq!(function, VoiLutFunction::LinearExact);
+        let window_level_transform = WindowLevelTransform::new(function, window_level);
+        let y_max = 255.;
+
+        // obtained from pydicom with BitsStored = 8
+        let expected = [
+            (0., 0.),
+            (77., 0.),
+            (78., 0.),
+            (79., 2.55),
+            (100., 56.1),
+            (128., 127.5),
+            (150., 183.6),
+            (177., 252.45),
+            (178., 255.),
+            (179., 255.),
+            (255., 255.),
+        ];
+        for (x, expected_y) in expected {
+            let y = window_level_transform.apply(x, y_max);
+            assert!(
+                (y - expected_y).abs() < 1e-6,
+                "f({}) = {}, expected {}",
+                x,
+                y,
+                expected_y
+            );
+        }
+
+        // differs from LINEAR at the edges of the window
+        let linear = WindowLevelTransform::linear(window_level);
+        assert!(linear.apply(79., y_max) > window_level_transform.apply(79., y_max));
+        assert_eq!(linear.apply(178., y_max), 255.);
+        assert!(linear.apply(177., y_max) > window_level_transform.apply(177., y_max));
+    }
+
+    /// Applying an exact linear window level
+    /// gives the same outcome as pydicom's `apply_voi_lut`
+    /// on a 16-bit image.
+    #[test]
+    fn window_level_linear_exact_16bit() {
+        let window_level = WindowLevel {
+            width: 20_000.,
+            center: 30_000.,
+        };
+        let window_level_transform =
+            WindowLevelTransform::new(VoiLutFunction::LinearExact, window_level);
+        let y_max = 65_535.;
+
+        // obtained from pydicom with BitsStored = 16
+        let expected = [
+            (0., 0.),
+            (20_000., 0.),
+            (20_001., 3.27675),
+            (25_000., 16_383.75),
+            (30_000., 32_767.5),
+            (39_999., 65_531.72325),
+            (40_000., 65_535.),
+            (40_001., 65_535.),
+            (65_535., 65_535.),
+        ];
+        for (x, expected_y) in expected {
+            let y = window_level_transform.apply(x, y_max);
+            assert!(
+                (y - expected_y).abs() < 1e-6,
+                "f({}) = {}, expected {}",
+                x,
+                y,
+                expected_y
+            );
+        }
+    }
+
+    /// Applying a tabular Modality LUT yields the table entries as is,
+    /// clamping stored values outside of the table
+    /// to its first and last entries.
+    #[test]
+    fn modality_lut_table_clamps_outside_range() {
+        let lut = ModalityLut {
+            first_mapped: -2,
+            bits: 16,
+            data: vec![100, 200, 300, 400],
+            lut_type: Some("OD".to_string()),
+            explanation: None,
+        };
+
+        assert_eq!(lut.apply(-2.), 100.);
+        assert_eq!(lut.apply(0.), 300.);
+        assert_eq!(lut.apply(1.), 400.);
+        // out of range
+        assert_eq!(lut.apply(-100.), 100.);
+        assert_eq!(lut.apply(2.), 400.);
+        assert_eq!(lut.apply(1000.), 400.);
+    }
+
+    /// Applying a tabular VOI LUT with a signed first mapped value
+    /// clamps values outside of the table to its first and last entries.
+    #[test]
+    fn voi_lut_table_clamps_outside_range() {
+        let lut = VoiLut {
+            first_mapped: -2,
+            bits: 8,
+            data: vec![0, 51, 102, 153, 204, 255],
+            explanation: None,
+        };
+        let y_max = 255.;
+
+        // before the first mapped value
+        assert_eq!(lut.apply(-100., y_max), 0.);
+        assert_eq!(lut.apply(-2., y_max), 0.);
+        // within the table
+        assert_eq!(lut.apply(-1., y_max), 51.);
+        assert_eq!(lut.apply(0., y_max), 102.);
+        assert_eq!(lut.apply(2.4, y_max), 204.);
+        // beyond the last entry
+        assert_eq!(lut.apply(3., y_max), 255.);
+        assert_eq!(lut.apply(1000., y_max), 255.);
+
+        // entries are scaled to the output range
+        assert_eq!(lut.apply(3., 65_535.), 65_535.);
+        assert_eq!(lut.apply(-1., 65_535.), 51. * 257.);
+    }
+
+    #[test]
+    fn pixel_padding_range() {
+        let padding = PixelPadding {
+            value: -2000,
+            range_limit: None,
+        };
+        assert!(padding.contains(-2000.));
+        assert!(!padding.contains(-1999.));
+        assert!(!padding.contains(-2001.));
+
+        // the range limit can be on either side of the padding value
+        let padding = PixelPadding {
+            value: -2000,
+            range_limit: Some(-1025),
+        };
+        assert!(padding.contains(-2000.));
+        assert!(padding.contains(-1500.));
+        assert!(padding.contains(-1025.));
+        assert!(!padding.contains(-1024.));
+        assert!(!padding.contains(-2001.));
+        let padding = PixelPadding {
+            value: 0,
+            range_limit: Some(-10),
+        };
+        assert!(padding.contains(-10.));
+        assert!(padding.contains(0.));
+        assert!(!padding.contains(1.));
+    }
+
+    #[test]
+    fn palette_color_lut_clamps_outside_range() {
+        let lut = PaletteColorLut {
+            first_mapped: 10,
+            bits: 8,
+            red: vec![255, 128, 0],
+            green: vec![0, 128, 255],
+            blue: vec![0, 0, 64],
+        };
+
+        // before the first mapped value
+        assert_eq!(lut.apply(-1), [255, 0, 0]);
+        assert_eq!(lut.apply(10), [255, 0, 0]);
+        // within the tables
+        assert_eq!(lut.apply(11), [128, 128, 0]);
+        assert_eq!(lut.apply(12), [0, 255, 64]);
+        // beyond the last entry
+        assert_eq!(lut.apply(13), [0, 255, 64]);
+        assert_eq!(lut.apply(i32::MAX), [0, 255, 64]);
+    }
 }
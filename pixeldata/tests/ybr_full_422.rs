@@ -0,0 +1,143 @@
+//! Test suite for native pixel data in _YBR_FULL_422_,
+//! in which every two pixels in a row share their chroma samples,
+//! stored in groups of `Y Y CB CR` samples.
+
+use dicom_core::{dicom_value, DataElement, PrimitiveValue, VR};
+use dicom_dictionary_std::{tags, uids};
+use dicom_object::{FileDicomObject, FileMetaTableBuilder, InMemDicomObject};
+use dicom_pixeldata::PixelDecoder;
+
+const ROWS: u16 = 2;
+// an odd number of columns,
+// so each row ends with a pixel of its own
+const COLUMNS: u16 = 3;
+
+/// Two frames of subsampled samples, as stored in native YBR_FULL_422
+static SUBSAMPLED: [u8; 28] = [
+    // frame #0
+    100, 110, 90, 160, 120, 200, 60, //
+    50, 60, 128, 128, 70, 140, 100, //
+    // frame #1
+    200, 210, 100, 100, 220, 128, 128, //
+    30, 40, 180, 70, 50, 60, 190,
+];
+
+/// The same frames with three samples per pixel, as in YBR_FULL
+static UPSAMPLED: [u8; 36] = [
+    // frame #0
+    100, 90, 160, 110, 90, 160, 120, 200, 60, //
+    50, 128, 128, 60, 128, 128, 70, 140, 100, //
+    // frame #1
+    200, 100, 100, 210, 100, 100, 220, 128, 128, //
+    30, 180, 70, 40, 180, 70, 50, 60, 190,
+];
+
+fn ybr_object(
+    photometric_interpretation: &str,
+    bits_allocated: u16,
+    samples: &[u8],
+) -> FileDicomObject<InMemDicomObject> {
+    let pixel_data = if bits_allocated == 16 {
+        // scale each sample to the full 16-bit range
+        DataElement::new(
+            tags::PIXEL_DATA,
+            VR::OW,
+            PrimitiveValue::U16(samples.iter().map(|&v| u16::from(v) << 8).collect()),
+        )
+    } else {
+        DataElement::new(tags::PIXEL_DATA, VR::OB, PrimitiveValue::from(samples))
+    };
+
+    InMemDicomObject::from_element_iter([
+        DataElement::new(tags::SAMPLES_PER_PIXEL, VR::US, dicom_value!(U16, [3])),
+        DataElement::new(
+            tags::PHOTOMETRIC_INTERPRETATION,
+            VR::CS,
+            dicom_value!(Str, photometric_interpretation),
+        ),
+        DataElement::new(tags::PLANAR_CONFIGURATION, VR::US, dicom_value!(U16, [0])),
+        DataElement::new(tags::NUMBER_OF_FRAMES, VR::IS, dicom_value!(Str, "2")),
+        DataElement::new(tags::ROWS, VR::US, dicom_value!(U16, [ROWS])),
+        DataElement::new(tags::COLUMNS, VR::US, dicom_value!(U16, [COLUMNS])),
+        DataElement::new(
+            tags::BITS_ALLOCATED,
+            VR::US,
+            dicom_value!(U16, [bits_allocated]),
+        ),
+        DataElement::new(
+            tags::BITS_STORED,
+            VR::US,
+            dicom_value!(U16, [bits_allocated]),
+        ),
+        DataElement::new(
+            tags::HIGH_BIT,
+            VR::US,
+            dicom_value!(U16, [bits_allocated - 1]),
+        ),
+        DataElement::new(tags::PIXEL_REPRESENTATION, VR::US, dicom_value!(U16, [0])),
+        pixel_data,
+    ])
+    .with_meta(
+        FileMetaTableBuilder::new()
+            .transfer_syntax(uids::EXPLICIT_VR_LITTLE_ENDIAN)
+            .media_storage_sop_class_uid(uids::VL_PHOTOGRAPHIC_IMAGE_STORAGE)
+            .media_storage_sop_instance_uid("2.25.170352914406478539374958329843201722312"),
+    )
+    .unwrap()
+}
+
+#[test]
+fn ybr_full_422_frames_are_subsampled() {
+    let obj = ybr_object("YBR_FULL_422", 8, &SUBSAMPLED);
+    let pixel_data = obj.decode_pixel_data().unwrap();
+    assert_eq!(pixel_data.number_of_frames(), 2);
+
+    // each frame has 7 samples per row, not 9
+    assert_eq!(pixel_data.frame_data(0).unwrap(), &SUBSAMPLED[..14]);
+    assert_eq!(pixel_data.frame_data(1).unwrap(), &SUBSAMPLED[14..]);
+    assert!(pixel_data.frame_data(2).is_err());
+    assert_eq!(pixel_data.frames().count(), 2);
+
+    // same when decoding a single frame
+    let frame = obj.decode_pixel_data_frame(1).unwrap();
+    assert_eq!(frame.frame_data(0).unwrap(), &SUBSAMPLED[14..]);
+}
+
+#[test]
+fn ybr_full_422_with_upsampled_chroma() {
+    // some objects hold three samples per pixel
+    // while still declaring YBR_FULL_422
+    let obj = ybr_object("YBR_FULL_422", 8, &UPSAMPLED);
+    let pixel_data = obj.decode_pixel_data().unwrap();
+    assert_eq!(pixel_data.frame_data(0).unwrap(), &UPSAMPLED[..18]);
+    assert_eq!(pixel_data.frame_data(1).unwrap(), &UPSAMPLED[18..]);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn ybr_full_422_to_rgb_image() {
+    for bits_allocated in [8, 16] {
+        let obj = ybr_object("YBR_FULL_422", bits_allocated, &SUBSAMPLED);
+        let expected_obj = ybr_object("YBR_FULL", bits_allocated, &UPSAMPLED);
+        let pixel_data = obj.decode_pixel_data().unwrap();
+        let expected_pixel_data = expected_obj.decode_pixel_data().unwrap();
+
+        for frame in 0..2 {
+            let image = pixel_data.to_dynamic_image(frame).unwrap();
+            assert_eq!(image.width(), u32::from(COLUMNS));
+            assert_eq!(image.height(), u32::from(ROWS));
+            let expected = expected_pixel_data.to_dynamic_image(frame).unwrap();
+            assert_eq!(
+                image, expected,
+                "frame #{} of {}-bit samples",
+                frame, bits_allocated
+            );
+        }
+
+        // also when decoding a single frame
+        let frame = obj.decode_pixel_data_frame(1).unwrap();
+        let image = frame.to_dynamic_image(0).unwrap();
+        let expected = expected_pixel_data.to_dynamic_image(1).unwrap();
+        assert_eq!(image, expected);
+    }
+}
@@ -4,7 +4,7 @@ use dicom_dictionary_std::{tags, uids};
 use dicom_encoding::transfer_syntax;
 use dicom_encoding::TransferSyntax;
 use dicom_object::{mem::InMemDicomObject, DefaultDicomObject, StandardDataDictionary};
-use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+use dicom_transfer_syntax_registry::{EncodingProfile, TransferSyntaxRegistry};
 use indicatif::{ProgressBar, ProgressStyle};
 use snafu::prelude::*;
 use snafu::{Report, Whatever};
@@ -60,6 +60,12 @@ struct App {
     // hide option if transcoding is disabled
     #[cfg_attr(not(feature = "transcode"), arg(hide(true)))]
     never_transcode: bool,
+    /// transcode files to the most preferred transfer syntax
+    /// of this encoding profile which the Store SCP accepts
+    #[arg(long = "profile", value_enum, conflicts_with("never_transcode"))]
+    // hide option if transcoding is disabled
+    #[cfg_attr(not(feature = "transcode"), arg(hide(true)))]
+    profile: Option<Profile>,
     /// User Identity username
     #[arg(
         long = "username",
@@ -100,6 +106,24 @@ struct App {
     concurrency: Option<usize>,
 }
 
+/// A curated list of transfer syntaxes to transcode files into
+#[derive(Debug, Copy, Clone, PartialEq, Eq, clap::ValueEnum)]
+enum Profile {
+    /// lossless compression for long term storage
+    LosslessArchive,
+    /// lossy compression for display and transmission over the web
+    LossyWeb,
+}
+
+impl From<Profile> for EncodingProfile {
+    fn from(profile: Profile) -> Self {
+        match profile {
+            Profile::LosslessArchive => EncodingProfile::LosslessArchive,
+            Profile::LossyWeb => EncodingProfile::LossyWeb,
+        }
+    }
+}
+
 struct DicomFile {
     /// File path
     file: PathBuf,
@@ -196,6 +220,7 @@ fn check_files(
     files: Vec<PathBuf>,
     verbose: bool,
     never_transcode: bool,
+    profile: Option<EncodingProfile>,
 ) -> (Vec<DicomFile>, HashSet<(String, String)>) {
     let mut checked_files: Vec<PathBuf> = vec![];
     let mut dicom_files: Vec<DicomFile> = vec![];
@@ -241,6 +266,14 @@ fn check_files(
                     ));
                 }
 
+                // propose the transfer syntaxes of the encoding profile
+                if let Some(profile) = profile {
+                    for ts_uid in profile_transfer_syntaxes(profile) {
+                        presentation_contexts
+                            .insert((dicom_file.sop_class_uid.to_string(), ts_uid.to_string()));
+                    }
+                }
+
                 dicom_files.push(dicom_file);
             }
             Err(_) => {
@@ -268,6 +301,7 @@ fn run(app: App) -> Result<(), Error> {
         max_pdu_length,
         fail_first,
         mut never_transcode,
+        mut profile,
         username,
         password,
         kerberos_service_ticket,
@@ -279,7 +313,9 @@ fn run(app: App) -> Result<(), Error> {
     // never transcode if the feature is disabled
     if cfg!(not(feature = "transcode")) {
         never_transcode = true;
+        profile = None;
     }
+    let profile = profile.map(EncodingProfile::from);
 
     tracing::subscriber::set_global_default(
         tracing_subscriber::FmtSubscriber::builder()
@@ -294,7 +330,8 @@ fn run(app: App) -> Result<(), Error> {
     if verbose {
         info!("Establishing association with '{}'...", &addr);
     }
-    let (mut dicom_files, presentation_contexts) = check_files(files, verbose, never_transcode);
+    let (mut dicom_files, presentation_contexts) =
+        check_files(files, verbose, never_transcode, profile);
 
     let mut scu = get_scu(
         addr,
@@ -315,8 +352,12 @@ fn run(app: App) -> Result<(), Error> {
 
     for file in &mut dicom_files {
         // identify the right transfer syntax to use
-        let r: Result<_, Error> =
-            check_presentation_contexts(file, scu.presentation_contexts(), never_transcode);
+        let r: Result<_, Error> = check_presentation_contexts(
+            file,
+            scu.presentation_contexts(),
+            never_transcode,
+            profile,
+        );
         match r {
             Ok((pc, ts)) => {
                 if verbose {
@@ -385,6 +426,7 @@ async fn run_async() -> Result<(), Error> {
         max_pdu_length,
         fail_first,
         mut never_transcode,
+        mut profile,
         username,
         password,
         kerberos_service_ticket,
@@ -396,7 +438,9 @@ async fn run_async() -> Result<(), Error> {
     // never transcode if the feature is disabled
     if cfg!(not(feature = "transcode")) {
         never_transcode = true;
+        profile = None;
     }
+    let profile = profile.map(EncodingProfile::from);
 
     tracing::subscriber::set_global_default(
         tracing_subscriber::FmtSubscriber::builder()
@@ -412,7 +456,7 @@ async fn run_async() -> Result<(), Error> {
         info!("Establishing association with '{}'...", &addr);
     }
     let (dicom_files, presentation_contexts) =
-        tokio::task::spawn_blocking(move || check_files(files, verbose, never_transcode))
+        tokio::task::spawn_blocking(move || check_files(files, verbose, never_transcode, profile))
             .await
             .unwrap();
     let num_files = dicom_files.len();
@@ -474,6 +518,7 @@ async fn run_async() -> Result<(), Error> {
                     &file,
                     scu.presentation_contexts(),
                     never_transcode,
+                    profile,
                 );
                 match r {
                     Ok((pc, ts)) => {
@@ -564,9 +609,9 @@ fn check_file(file: &Path) -> Result<DicomFile, Error> {
 
     let meta = dicom_file.meta();
 
-    let storage_sop_class_uid = &meta.media_storage_sop_class_uid;
-    let storage_sop_instance_uid = &meta.media_storage_sop_instance_uid;
-    let transfer_syntax_uid = &meta.transfer_syntax.trim_end_matches('\0');
+    let storage_sop_class_uid = meta.media_storage_sop_class_uid();
+    let storage_sop_instance_uid = meta.media_storage_sop_instance_uid();
+    let transfer_syntax_uid = meta.transfer_syntax();
     let ts = TransferSyntaxRegistry
         .get(transfer_syntax_uid)
         .with_context(|| UnsupportedFileTransferSyntaxSnafu {
@@ -586,6 +631,7 @@ fn check_presentation_contexts(
     file: &DicomFile,
     pcs: &[dicom_ul::pdu::PresentationContextResult],
     never_transcode: bool,
+    profile: Option<EncodingProfile>,
 ) -> Result<(dicom_ul::pdu::PresentationContextResult, String), Error> {
     let file_ts = TransferSyntaxRegistry
         .get(&file.file_transfer_syntax)
@@ -593,6 +639,16 @@ fn check_presentation_contexts(
             uid: file.file_transfer_syntax.to_string(),
         })?;
 
+    // With an encoding profile,
+    // take its most preferred transfer syntax which was accepted
+    if let Some(profile) = profile.filter(|_| !never_transcode && file_ts.can_decode_all()) {
+        let profile_pc = profile_transfer_syntaxes(profile)
+            .find_map(|ts_uid| pcs.iter().find(|pc| pc.transfer_syntax == ts_uid));
+        if let Some(pc) = profile_pc {
+            return Ok((pc.clone(), pc.transfer_syntax.clone()));
+        }
+    }
+
     // Try to find an exact match for the file's transfer syntax first
     let exact_match_pc = pcs.iter().find(|pc| pc.transfer_syntax == file_ts.uid());
 
@@ -640,6 +696,18 @@ fn check_presentation_contexts(
     Ok((pc.clone(), String::from(ts.uid())))
 }
 
+/// Obtain the UIDs of the transfer syntaxes in the encoding profile
+/// which can be fully encoded here,
+/// from the most preferred to the least preferred.
+fn profile_transfer_syntaxes(profile: EncodingProfile) -> impl Iterator<Item = &'static str> {
+    profile.preferences().iter().copied().filter(|ts_uid| {
+        TransferSyntaxRegistry
+            .get(ts_uid)
+            .map(|ts| ts.is_fully_supported())
+            .unwrap_or(false)
+    })
+}
+
 // transcoding functions
 
 #[cfg(feature = "transcode")]
@@ -6,8 +6,8 @@ use clap::Parser;
 use dicom_core::prelude::*;
 use dicom_dictionary_std::{tags, uids};
 use dicom_object::{open_file, FileDicomObject, InMemDicomObject};
-use dicom_pixeldata::{ConvertOptions, PixelDecoder};
-use snafu::{OptionExt, Report, ResultExt, Snafu, Whatever};
+use dicom_pixeldata::{encapsulation, ConvertOptions, PixelDecoder};
+use snafu::{ensure, OptionExt, Report, ResultExt, Snafu, Whatever};
 use tracing::{error, warn, Level};
 
 /// Convert DICOM files into image files
@@ -370,55 +370,22 @@ fn convert_single_file(
                     None => 1,
                 };
 
-                if number_of_frames as usize == seq.fragments().len() {
-                    // frame-to-fragment mapping is 1:1
-
-                    // get fragment containing our frame
-                    let fragment =
-                        seq.fragments()
-                            .get(frame_number as usize)
-                            .with_context(|| {
-                                error!(
-                                    "{}: Frame number {} is out of range",
-                                    output.display(),
-                                    frame_number
-                                );
-                                FrameOutOfBoundsSnafu { frame_number }
-                            })?;
-
-                    Cow::Borrowed(&fragment[..])
-                } else {
-                    // In this case we look up the basic offset table
-                    // and gather all of the frame's fragments in a single vector.
-                    // Note: not the most efficient way to do this,
-                    // consider optimizing later with byte chunk readers
-                    let offset_table = seq.offset_table();
-                    let base_offset = offset_table.get(frame_number as usize).copied();
-                    let base_offset = if frame_number == 0 {
-                        base_offset.unwrap_or(0) as usize
-                    } else {
-                        base_offset.context(MissingOffsetEntrySnafu { frame_number })? as usize
-                    };
-                    let next_offset = offset_table.get(frame_number as usize + 1);
-
-                    let mut offset = 0;
-                    let mut frame_data = Vec::new();
-                    for fragment in seq.fragments() {
-                        // include it
-                        if offset >= base_offset {
-                            frame_data.extend_from_slice(fragment);
-                        }
-                        offset += fragment.len() + 8;
-                        if let Some(&next_offset) = next_offset {
-                            if offset >= next_offset as usize {
-                                // next fragment is for the next frame
-                                break;
-                            }
-                        }
-                    }
+                ensure!(
+                    frame_number < number_of_frames,
+                    FrameOutOfBoundsSnafu { frame_number }
+                );
 
-                    Cow::Owned(frame_data)
-                }
+                // gather the fragments of our frame
+                encapsulation::frame_data(seq, number_of_frames, frame_number).with_context(
+                    || {
+                        error!(
+                            "{}: Could not tell which fragments make up frame #{}",
+                            output.display(),
+                            frame_number
+                        );
+                        MissingOffsetEntrySnafu { frame_number }
+                    },
+                )?
             }
             DicomValue::Primitive(v) => {
                 // grab the intended slice based on image properties
@@ -3,14 +3,15 @@
 use dicom_core::ops::{AttributeAction, AttributeOp};
 use dicom_core::{PrimitiveValue, Tag};
 use dicom_encoding::adapters::{
-    decode_error, encode_error, DecodeResult, EncodeOptions, EncodeResult, PixelDataObject,
-    PixelDataReader, PixelDataWriter,
+    decode_error, encode_error, DecodeResult, EncodeConversion, EncodeOptions, EncodeProperty,
+    EncodeResult, EncodeSourceProperties, FragmentLayout, FrameExtractionStrategy,
+    PixelDataObject, PixelDataReader, PixelDataWriter, SupportLevel,
 };
 use dicom_encoding::snafu::prelude::*;
 use jpeg_decoder::Decoder;
 use jpeg_encoder::ColorType;
 use std::borrow::Cow;
-use std::io::Cursor;
+use tracing::warn;
 
 /// Pixel data adapter for JPEG-based transfer syntaxes.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -55,72 +56,75 @@ impl PixelDataReader for JpegAdapter {
             .raw_pixel_data()
             .whatever_context("Expected to have raw pixel data available")?;
 
-        // Some embedded JPEGs might span multiple fragments.
-        // Hence we collect all fragments into single vector
-        // and then iterate a cursor for each frame
-        // Note: not the most efficient way to do this,
-        // consider optimizing later with bytes data structures
-        let fragments: Vec<u8> = raw.fragments.into_iter().flatten().collect();
-
-        let fragments_len = fragments.len() as u64;
-        let mut cursor = Cursor::new(fragments);
-        let mut dst_offset = base_offset;
-
-        'frame_loop: for i in 0..nr_frames {
-            let mut decoder = Decoder::new(&mut cursor);
-            let decoded = decoder
-                .decode()
-                .map_err(|e| Box::new(e) as Box<_>)
-                .with_whatever_context(|_| format!("JPEG decoding failure on frame {}", i))?;
-
-            let decoded_len = decoded.len();
-            dst[dst_offset..(dst_offset + decoded_len)].copy_from_slice(&decoded);
-            dst_offset += decoded_len;
-
-            if next_even(cursor.position()) >= next_even(fragments_len) {
-                break;
-            }
-
-            // stop if there aren't enough bytes to continue
-            if cursor.position() + 2 >= fragments_len {
-                break;
-            }
-            
-            // DICOM fragments should always have an even length,
-            // filling this spacing with padding if it is odd.
-            // Some implementations might add some padding,
-            // whereas other might not.
-            // So we check the next 2 bytes
-            // until we find the SOI marker
-            loop {
-                let Some(next_byte_1) = cursor
-                    .get_ref()
-                    .get(cursor.position() as usize)
-                    .copied()
-                else {
+        let layout = FragmentLayout::from_object(src)
+            .whatever_context("Expected to have raw pixel data available")?;
+        let strategy = FrameExtractionStrategy::determine_and_warn(&layout);
+        let frame_size = samples_per_pixel as usize * stride;
+
+        if strategy == FrameExtractionStrategy::SingleFrameAllFragments && nr_frames > 1 {
+            // The fragments cannot be attributed to each frame,
+            // so we look for the boundaries of each frame's JPEG stream
+            // in all fragments instead
+            let fragments = strategy
+                .frame_data(&raw.fragments[..], &layout, 0)
+                .whatever_context("JPEG decoding failure: no fragments found")?;
+            let mut position = 0;
+
+            for i in 0..nr_frames {
+                // DICOM fragments should always have an even length,
+                // filling this spacing with padding if it is odd.
+                // Some implementations might add some padding,
+                // whereas other might not.
+                // So we look for the SOI marker of the next frame
+                let Some(start) = find_soi(&fragments, position) else {
+                    ensure_whatever!(i > 0, "JPEG decoding failure: no JPEG stream found");
                     // no more frames to read
-                    break 'frame_loop;
+                    break;
                 };
-
-                if next_byte_1 != 0xFF {
-                    cursor.set_position(cursor.position() + 1);
-                    continue;
+                if i > 0 {
+                    warn_trailing_bytes(&fragments[position..start], i - 1);
                 }
 
-                let Some(next_byte_2) = cursor
-                    .get_ref()
-                    .get(cursor.position() as usize + 1)
-                    .copied()
-                else {
-                    // no more frames to read
-                    break 'frame_loop;
+                // scan the stream up to its EOI marker
+                let end = match jpeg_stream_len(&fragments[start..]) {
+                    Some(len) => start + len,
+                    None => fragments.len(),
                 };
 
-                if next_byte_2 == 0xD8 {
-                    // next frame found
-                    break;
-                }
-                cursor.set_position(cursor.position() + 1);
+                let dst_offset = base_offset + i * frame_size;
+                decode_jpeg_stream(
+                    &fragments[start..end],
+                    cols,
+                    rows,
+                    samples_per_pixel,
+                    bytes_per_sample,
+                    &mut dst[dst_offset..dst_offset + frame_size],
+                )
+                .map_err(|e| Box::new(e) as Box<_>)
+                .with_whatever_context(|_| format!("JPEG decoding failure on frame {}", i))?;
+
+                position = end;
+            }
+
+            warn_trailing_bytes(&fragments[position..], nr_frames - 1);
+        } else {
+            for i in 0..nr_frames {
+                let frame_data = strategy
+                    .frame_data(&raw.fragments[..], &layout, i as u32)
+                    .with_whatever_context(|| format!("Missing fragments for frame #{}", i))?;
+
+                let dst_offset = base_offset + i * frame_size;
+                decode_frame_data(
+                    &frame_data,
+                    i,
+                    cols,
+                    rows,
+                    samples_per_pixel,
+                    bytes_per_sample,
+                    &mut dst[dst_offset..dst_offset + frame_size],
+                )
+                .map_err(|e| Box::new(e) as Box<_>)
+                .with_whatever_context(|_| format!("JPEG decoding failure on frame {}", i))?;
             }
         }
 
@@ -174,67 +178,62 @@ impl PixelDataReader for JpegAdapter {
             .raw_pixel_data()
             .whatever_context("Expected to have raw pixel data available")?;
 
-        let frame_data = if raw.fragments.len() == 1 || raw.fragments.len() == nr_frames {
-            // assuming 1:1 frame-to-fragment mapping
-            Cow::Borrowed(
-                raw.fragments
-                    .get(frame as usize)
-                    .with_whatever_context(|| {
-                        format!("Missing fragment #{} for the frame requested", frame)
-                    })?,
-            )
-        } else {
-            // Some embedded JPEGs might span multiple fragments.
-            // In this case we look up the basic offset table
-            // and gather all of the frame's fragments in a single vector.
-            // Note: not the most efficient way to do this,
-            // consider optimizing later with byte chunk readers
-            let base_offset = raw.offset_table.get(frame as usize).copied();
-            let base_offset = if frame == 0 {
-                base_offset.unwrap_or(0) as usize
-            } else {
-                base_offset
-                    .with_whatever_context(|| format!("Missing offset for frame #{}", frame))?
-                    as usize
-            };
-            let next_offset = raw.offset_table.get(frame as usize + 1);
-
-            let mut offset = 0;
-            let mut fragments = Vec::new();
-            for fragment in &raw.fragments {
-                // include it
-                if offset >= base_offset {
-                    fragments.extend_from_slice(fragment);
-                }
-                offset += fragment.len() + 8;
-                if let Some(&next_offset) = next_offset {
-                    if offset >= next_offset as usize {
-                        // next fragment is for the next frame
-                        break;
-                    }
-                }
-            }
+        let layout = FragmentLayout::from_object(src)
+            .whatever_context("Expected to have raw pixel data available")?;
+        let frame_data = raw
+            .frame_data(&layout, frame)
+            .with_whatever_context(|| format!("Missing fragments for frame #{}", frame))?;
+
+        decode_frame_data(
+            &frame_data,
+            frame as usize,
+            cols,
+            rows,
+            samples_per_pixel,
+            bytes_per_sample,
+            &mut dst[base_offset..],
+        )
+        .map_err(|e| Box::new(e) as Box<_>)
+        .whatever_context("JPEG decoder failure")?;
 
-            Cow::Owned(fragments)
-        };
+        Ok(())
+    }
+}
 
-        let mut cursor = Cursor::new(&*frame_data);
-        let dst_offset = base_offset;
+impl PixelDataWriter for JpegAdapter {
+    /// JPEG baseline only encodes 8-bit samples,
+    /// so 16-bit samples need to be reduced to 8 bits first.
+    fn supports(&self, props: &EncodeSourceProperties) -> SupportLevel {
+        if props.rows == 0 || props.cols == 0 {
+            return SupportLevel::Unsupported(EncodeProperty::FrameSize);
+        }
 
-        let mut decoder = Decoder::new(&mut cursor);
-        let decoded = decoder
-            .decode()
-            .map_err(|e| Box::new(e) as Box<_>)
-            .whatever_context("JPEG decoder failure")?;
+        let mut conversions = Vec::new();
+        match props.bits_allocated {
+            8 => {}
+            16 => conversions.push(EncodeConversion::ReduceTo8Bit),
+            _ => return SupportLevel::Unsupported(EncodeProperty::BitsAllocated),
+        }
+        if props.bits_stored < 8 || props.bits_stored > props.bits_allocated {
+            return SupportLevel::Unsupported(EncodeProperty::BitsStored);
+        }
 
-        let decoded_len = decoded.len();
-        dst[dst_offset..(dst_offset + decoded_len)].copy_from_slice(&decoded);
+        match (
+            props.samples_per_pixel,
+            props.photometric_interpretation.as_str(),
+        ) {
+            (1, "MONOCHROME1" | "MONOCHROME2") | (3, "RGB") => {}
+            (1 | 3, _) => return SupportLevel::Unsupported(EncodeProperty::PhotometricInterpretation),
+            _ => return SupportLevel::Unsupported(EncodeProperty::SamplesPerPixel),
+        }
 
-        Ok(())
+        if conversions.is_empty() {
+            SupportLevel::Supported
+        } else {
+            SupportLevel::Partial(conversions)
+        }
     }
-}
 
-impl PixelDataWriter for JpegAdapter {
     fn encode_frame(
         &self,
         src: &dyn PixelDataObject,
@@ -269,6 +268,16 @@ impl PixelDataWriter for JpegAdapter {
 
         let quality = options.quality.unwrap_or(85);
 
+        // warn only once when encoding all frames
+        if frame == 0 {
+            if options.effort.is_some() {
+                warn!("JPEG encoder does not support the effort option, ignoring");
+            }
+            if options.near_lossless.is_some() {
+                warn!("JPEG encoder does not support near-lossless encoding, ignoring");
+            }
+        }
+
         let bytes_per_sample = (bits_allocated / 8) as usize;
         let frame_size =
             cols as usize * rows as usize * samples_per_pixel as usize * bytes_per_sample;
@@ -358,8 +367,152 @@ impl PixelDataWriter for JpegAdapter {
     }
 }
 
-fn next_even(l: u64) -> u64 {
-    (l + 1) & !1
+/// Decode a single JPEG stream into `dst`,
+/// which holds exactly one frame of native pixel data.
+///
+/// The decoded image is copied row by row,
+/// so that a stream with dimensions other than those declared
+/// in the DICOM object does not shear the remaining rows.
+/// Rows or samples missing from the decoded image are left as zeros,
+/// while the excess is discarded.
+fn decode_jpeg_stream(
+    data: &[u8],
+    cols: u16,
+    rows: u16,
+    samples_per_pixel: u16,
+    bytes_per_sample: u16,
+    dst: &mut [u8],
+) -> Result<(), jpeg_decoder::Error> {
+    let mut decoder = Decoder::new(data);
+    let decoded = decoder.decode()?;
+    let info = decoder
+        .info()
+        .expect("image info should be available after decoding");
+
+    let dst_row_len = cols as usize * samples_per_pixel as usize * bytes_per_sample as usize;
+    let src_row_len = info.width as usize * info.pixel_format.pixel_bytes();
+
+    if info.width != cols || info.height != rows || src_row_len != dst_row_len {
+        warn!(
+            "JPEG image is {}x{} ({:?}), expected {}x{} with {} samples per pixel",
+            info.width, info.height, info.pixel_format, cols, rows, samples_per_pixel
+        );
+    }
+    if src_row_len == 0 || dst_row_len == 0 {
+        return Ok(());
+    }
+
+    // the last row may be incomplete if the decoder stopped early
+    for (src_row, dst_row) in decoded.chunks(src_row_len).zip(dst.chunks_mut(dst_row_len)) {
+        let len = src_row.len().min(dst_row.len());
+        dst_row[..len].copy_from_slice(&src_row[..len]);
+    }
+
+    Ok(())
+}
+
+/// Decode the JPEG stream in the encoded data of a single frame,
+/// skipping anything before its SOI marker
+/// and ignoring anything after its EOI marker.
+fn decode_frame_data(
+    data: &[u8],
+    frame: usize,
+    cols: u16,
+    rows: u16,
+    samples_per_pixel: u16,
+    bytes_per_sample: u16,
+    dst: &mut [u8],
+) -> Result<(), jpeg_decoder::Error> {
+    let start = find_soi(data, 0).unwrap_or(0);
+    let end = match jpeg_stream_len(&data[start..]) {
+        Some(len) => start + len,
+        None => data.len(),
+    };
+    warn_trailing_bytes(&data[end..], frame);
+
+    decode_jpeg_stream(
+        &data[start..end],
+        cols,
+        rows,
+        samples_per_pixel,
+        bytes_per_sample,
+        dst,
+    )
+}
+
+/// Find the position of the next SOI marker in `data`,
+/// starting at `from`.
+fn find_soi(data: &[u8], from: usize) -> Option<usize> {
+    data.get(from..)?
+        .windows(2)
+        .position(|w| w == [0xFF, 0xD8])
+        .map(|i| from + i)
+}
+
+/// Determine the length of the JPEG stream at the start of `data`,
+/// up to and including its EOI marker.
+///
+/// Marker segments are skipped over by their declared length,
+/// so that the contents of APPn segments
+/// (such as embedded thumbnails)
+/// are not mistaken for the end of the stream.
+/// In entropy-coded data,
+/// stuffed bytes, restart markers, and fill bytes are skipped.
+///
+/// Returns `None` if `data` does not start with an SOI marker
+/// or if the stream ends before an EOI marker is found.
+fn jpeg_stream_len(data: &[u8]) -> Option<usize> {
+    if data.get(..2)? != [0xFF, 0xD8] {
+        return None;
+    }
+    let mut i = 2;
+    loop {
+        if *data.get(i)? != 0xFF {
+            return None;
+        }
+        let marker = *data.get(i + 1)?;
+        i += 2;
+        match marker {
+            // fill byte
+            0xFF => i -= 1,
+            // EOI
+            0xD9 => return Some(i),
+            // standalone markers: TEM, RSTn
+            0x01 | 0xD0..=0xD7 => {}
+            // nested SOI
+            0xD8 => return None,
+            _ => {
+                let len = u16::from_be_bytes([*data.get(i)?, *data.get(i + 1)?]) as usize;
+                i += len;
+                if marker == 0xDA {
+                    // SOS: skip entropy-coded data up to the next marker
+                    loop {
+                        if *data.get(i)? == 0xFF {
+                            match *data.get(i + 1)? {
+                                0x00 | 0xD0..=0xD7 => i += 2,
+                                0xFF => i += 1,
+                                _ => break,
+                            }
+                        } else {
+                            i += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Warn about the bytes found after the EOI marker of a frame,
+/// unless they are all zeros, as used for padding.
+fn warn_trailing_bytes(bytes: &[u8], frame: usize) {
+    if bytes.iter().any(|&b| b != 0) {
+        warn!(
+            "Ignoring {} trailing bytes after the end of JPEG stream in frame #{}",
+            bytes.len(),
+            frame
+        );
+    }
 }
 
 /// reduce data precision to 8 bits if necessary
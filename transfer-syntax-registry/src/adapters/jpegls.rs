@@ -4,11 +4,11 @@ use charls::{CharLS, FrameInfo};
 use dicom_core::ops::{AttributeAction, AttributeOp};
 use dicom_core::Tag;
 use dicom_encoding::adapters::{
-    decode_error, encode_error, DecodeResult, EncodeResult, PixelDataObject, PixelDataReader,
-    PixelDataWriter,
+    decode_error, encode_error, DecodeResult, EncodeOptions, EncodeProperty, EncodeResult,
+    EncodeSourceProperties, FragmentLayout, PixelDataObject, PixelDataReader, PixelDataWriter,
+    SupportLevel,
 };
 use dicom_encoding::snafu::prelude::*;
-use std::borrow::Cow;
 
 /// Pixel data reader and writer for JPEG-LS transfer syntaxes.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -48,49 +48,11 @@ impl PixelDataReader for JpegLsAdapter {
             .raw_pixel_data()
             .whatever_context("Expected to have raw pixel data available")?;
 
-        let frame_data = if raw.fragments.len() == 1 || raw.fragments.len() == nr_frames {
-            // assuming 1:1 frame-to-fragment mapping
-            Cow::Borrowed(
-                raw.fragments
-                    .get(frame as usize)
-                    .with_whatever_context(|| {
-                        format!("Missing fragment #{} for the frame requested", frame)
-                    })?,
-            )
-        } else {
-            // Some embedded JPEGs might span multiple fragments.
-            // In this case we look up the basic offset table
-            // and gather all of the frame's fragments in a single vector.
-            // Note: not the most efficient way to do this,
-            // consider optimizing later with byte chunk readers
-            let base_offset = raw.offset_table.get(frame as usize).copied();
-            let base_offset = if frame == 0 {
-                base_offset.unwrap_or(0) as usize
-            } else {
-                base_offset
-                    .with_whatever_context(|| format!("Missing offset for frame #{}", frame))?
-                    as usize
-            };
-            let next_offset = raw.offset_table.get(frame as usize + 1);
-
-            let mut offset = 0;
-            let mut fragments = Vec::new();
-            for fragment in &raw.fragments {
-                // include it
-                if offset >= base_offset {
-                    fragments.extend_from_slice(fragment);
-                }
-                offset += fragment.len() + 8;
-                if let Some(&next_offset) = next_offset {
-                    if offset >= next_offset as usize {
-                        // next fragment is for the next frame
-                        break;
-                    }
-                }
-            }
-
-            Cow::Owned(fragments)
-        };
+        let layout = FragmentLayout::from_object(src)
+            .whatever_context("Expected to have raw pixel data available")?;
+        let frame_data = raw
+            .frame_data(&layout, frame)
+            .with_whatever_context(|| format!("Missing fragments for frame #{}", frame))?;
 
         let mut decoded = CharLS::default()
             .decode(&frame_data)
@@ -104,6 +66,28 @@ impl PixelDataReader for JpegLsAdapter {
 }
 
 impl PixelDataWriter for JpegLsAdapter {
+    fn supports(&self, props: &EncodeSourceProperties) -> SupportLevel {
+        if props.rows == 0 || props.cols == 0 {
+            return SupportLevel::Unsupported(EncodeProperty::FrameSize);
+        }
+        if props.bits_allocated != 8 && props.bits_allocated != 16 {
+            return SupportLevel::Unsupported(EncodeProperty::BitsAllocated);
+        }
+        if props.bits_stored < 2 || props.bits_stored > props.bits_allocated {
+            return SupportLevel::Unsupported(EncodeProperty::BitsStored);
+        }
+        match (
+            props.samples_per_pixel,
+            props.photometric_interpretation.as_str(),
+        ) {
+            (1, "MONOCHROME1" | "MONOCHROME2" | "PALETTE COLOR") | (3, "RGB") => {
+                SupportLevel::Supported
+            }
+            (1 | 3, _) => SupportLevel::Unsupported(EncodeProperty::PhotometricInterpretation),
+            _ => SupportLevel::Unsupported(EncodeProperty::SamplesPerPixel),
+        }
+    }
+
     fn encode_frame(
         &self,
         src: &dyn PixelDataObject,
@@ -166,18 +150,23 @@ impl PixelDataWriter for JpegLsAdapter {
         };
 
         // prefer lossless encoding by default
-        let mut quality = options.quality.map(|q| q.clamp(0, 100)).unwrap_or(100);
+        let quality = options.quality.map(|q| q.clamp(0, 100)).unwrap_or(100);
+
+        // warn only once when encoding all frames
+        if frame == 0 && options.effort.is_some() {
+            tracing::warn!("JPEG-LS encoder does not support the effort option, ignoring");
+        }
 
         let pmi = src.photometric_interpretation();
 
-        if pmi == Some("PALETTE COLOR") {
+        // calculate the maximum acceptable error range,
+        // as requested or based on the requested quality and bit depth
+        let near = match options.near_lossless {
             // force lossless encoding of palette color samples
-            quality = 100;
-        }
-
-        // calculate the maximum acceptable error range
-        // based on the requested quality and bit depth
-        let near = ((1 << (bits_stored - 4)) * (100 - quality as i32) / 100).min(4096);
+            _ if pmi == Some("PALETTE COLOR") => 0,
+            Some(near) => i32::from(near),
+            None => ((1 << (bits_stored - 4)) * (100 - quality as i32) / 100).min(4096),
+        };
 
         let compressed_data = encoder
             .encode(frame_info, near, frame_data)
@@ -233,15 +222,19 @@ impl PixelDataWriter for JpegLsAdapter {
 }
 
 impl PixelDataWriter for JpegLsLosslessWriter {    
+    fn supports(&self, props: &EncodeSourceProperties) -> SupportLevel {
+        JpegLsAdapter.supports(props)
+    }
+
     fn encode_frame(
         &self,
         src: &dyn PixelDataObject,
         frame: u32,
-        mut options: dicom_encoding::adapters::EncodeOptions,
+        options: dicom_encoding::adapters::EncodeOptions,
         dst: &mut Vec<u8>,
     ) -> EncodeResult<Vec<AttributeOp>> {
         // override quality and defer to the main adapter
-        options.quality = Some(100);
+        let options = lossless_options(options, frame == 0);
         JpegLsAdapter.encode_frame(src, frame, options, dst)
     }
     
@@ -253,8 +246,18 @@ impl PixelDataWriter for JpegLsLosslessWriter {
         offset_table: &mut Vec<u32>,
     ) -> EncodeResult<Vec<AttributeOp>> {
         // override quality and defer to the main adapter
-        let mut options = options;
-        options.quality = Some(100);
+        let options = lossless_options(options, true);
         JpegLsAdapter.encode(src, options, dst, offset_table)
     }
 }
+
+/// Adjust the given encoding options for lossless JPEG-LS,
+/// warning about a non-zero near-lossless error range if `warn` is true.
+fn lossless_options(mut options: EncodeOptions, warn: bool) -> EncodeOptions {
+    if warn && options.near_lossless.map_or(false, |near| near > 0) {
+        tracing::warn!("Near-lossless error range is not applicable to JPEG-LS lossless, ignoring");
+    }
+    options.quality = Some(100);
+    options.near_lossless = Some(0);
+    options
+}
@@ -0,0 +1,1068 @@
+//! Support for JPEG-LS image decoding in pure Rust.
+//!
+//! This is a decoder for the baseline JPEG-LS process
+//! (ITU-T T.87 | ISO/IEC 14495-1),
+//! both lossless and near-lossless,
+//! for 2 to 16 bits per sample
+//! and any interleave mode.
+//! Mapping tables, point transforms and restart intervals
+//! are not supported.
+//!
+//! When the `charls` feature is also enabled,
+//! the CharLS-based adapter is registered instead,
+//! since it also provides encoding.
+#![cfg_attr(feature = "charls", allow(dead_code))]
+
+use dicom_encoding::adapters::{
+    decode_error, DecodeResult, FragmentLayout, PixelDataObject, PixelDataReader,
+};
+use dicom_encoding::snafu::prelude::*;
+
+/// Pixel data reader for JPEG-LS transfer syntaxes,
+/// implemented in pure Rust.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JpegLsRustAdapter;
+
+impl PixelDataReader for JpegLsRustAdapter {
+    /// Decode a single frame in JPEG-LS from a DICOM object.
+    fn decode_frame(
+        &self,
+        src: &dyn PixelDataObject,
+        frame: u32,
+        dst: &mut Vec<u8>,
+    ) -> DecodeResult<()> {
+        let cols = src
+            .cols()
+            .context(decode_error::MissingAttributeSnafu { name: "Columns" })?;
+        let rows = src
+            .rows()
+            .context(decode_error::MissingAttributeSnafu { name: "Rows" })?;
+        let samples_per_pixel =
+            src.samples_per_pixel()
+                .context(decode_error::MissingAttributeSnafu {
+                    name: "SamplesPerPixel",
+                })?;
+        let bits_allocated = src
+            .bits_allocated()
+            .context(decode_error::MissingAttributeSnafu {
+                name: "BitsAllocated",
+            })?;
+
+        ensure_whatever!(
+            bits_allocated == 8 || bits_allocated == 16,
+            "BitsAllocated other than 8 or 16 is not supported"
+        );
+
+        let nr_frames = src.number_of_frames().unwrap_or(1) as usize;
+
+        ensure!(
+            nr_frames > frame as usize,
+            decode_error::FrameRangeOutOfBoundsSnafu
+        );
+
+        let raw = src
+            .raw_pixel_data()
+            .whatever_context("Expected to have raw pixel data available")?;
+
+        let layout = FragmentLayout::from_object(src)
+            .whatever_context("Expected to have raw pixel data available")?;
+        let frame_data = raw
+            .frame_data(&layout, frame)
+            .with_whatever_context(|| format!("Missing fragments for frame #{}", frame))?;
+
+        let image = decode(&frame_data, usize::from(cols), usize::from(rows))?;
+
+        ensure_whatever!(
+            image.components == usize::from(samples_per_pixel),
+            "JPEG-LS image with {} components does not match the pixel data attributes",
+            image.components
+        );
+        ensure_whatever!(
+            bits_allocated == 16 || image.bits_per_sample <= 8,
+            "JPEG-LS image of {} bits per sample does not fit in BitsAllocated of 8",
+            image.bits_per_sample
+        );
+
+        if bits_allocated == 8 {
+            dst.extend(image.samples.iter().map(|v| *v as u8));
+        } else {
+            dst.reserve(image.samples.len() * 2);
+            for v in &image.samples {
+                dst.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Run length orders (ITU-T T.87 A.7.1.1)
+const J: [u32; 32] = [
+    0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 9, 10, 11, 12, 13,
+    14, 15,
+];
+
+/// Number of regular mode contexts (ITU-T T.87 A.3.1)
+const CONTEXTS: usize = 365;
+
+/// Bounds of the bias correction value
+const MIN_C: i32 = -128;
+const MAX_C: i32 = 127;
+
+/// A decoded JPEG-LS image.
+#[derive(Debug)]
+struct Image {
+    width: usize,
+    height: usize,
+    components: usize,
+    bits_per_sample: u8,
+    /// samples of all components, interleaved by pixel
+    samples: Vec<u16>,
+}
+
+/// The frame header (SOF55 marker segment).
+#[derive(Debug)]
+struct FrameHeader {
+    bits_per_sample: u8,
+    height: usize,
+    width: usize,
+    component_ids: Vec<u8>,
+}
+
+/// Preset coding parameters (LSE marker segment with ID 1),
+/// where 0 stands for the default value.
+#[derive(Debug, Default, Copy, Clone)]
+struct PresetParameters {
+    maxval: i32,
+    t1: i32,
+    t2: i32,
+    t3: i32,
+    reset: i32,
+}
+
+/// Decode a complete JPEG-LS code stream
+/// of an image with the expected dimensions.
+///
+/// The dimensions in the code stream are checked
+/// before any samples are allocated.
+fn decode(data: &[u8], expected_width: usize, expected_height: usize) -> DecodeResult<Image> {
+    ensure_whatever!(
+        data.starts_with(&[0xFF, 0xD8]),
+        "Missing JPEG-LS start of image marker"
+    );
+    let mut pos = 2;
+    let mut frame: Option<FrameHeader> = None;
+    let mut preset = PresetParameters::default();
+    let mut samples = Vec::new();
+    let mut scanned = Vec::new();
+
+    while let Some(marker) = next_marker(data, &mut pos)? {
+        match marker {
+            // EOI
+            0xD9 => break,
+            // SOF55
+            0xF7 => {
+                let segment = segment(data, &mut pos)?;
+                ensure_whatever!(frame.is_none(), "Multiple JPEG-LS frame headers");
+                ensure_whatever!(segment.len() >= 6, "Invalid JPEG-LS frame header");
+                let bits_per_sample = segment[0];
+                let height = usize::from(u16::from_be_bytes([segment[1], segment[2]]));
+                let width = usize::from(u16::from_be_bytes([segment[3], segment[4]]));
+                let components = usize::from(segment[5]);
+                ensure_whatever!(
+                    (2..=16).contains(&bits_per_sample),
+                    "Unsupported JPEG-LS sample precision {}",
+                    bits_per_sample
+                );
+                ensure_whatever!(
+                    components > 0 && segment.len() >= 6 + components * 3,
+                    "Invalid JPEG-LS frame header"
+                );
+                let component_ids = (0..components).map(|i| segment[6 + i * 3]).collect();
+                frame = Some(FrameHeader {
+                    bits_per_sample,
+                    height,
+                    width,
+                    component_ids,
+                });
+            }
+            // LSE
+            0xF8 => {
+                let segment = segment(data, &mut pos)?;
+                ensure_whatever!(!segment.is_empty(), "Invalid JPEG-LS preset parameters");
+                match segment[0] {
+                    // preset coding parameters
+                    1 => {
+                        ensure_whatever!(segment.len() >= 11, "Invalid JPEG-LS preset parameters");
+                        let value =
+                            |i: usize| i32::from(u16::from_be_bytes([segment[i], segment[i + 1]]));
+                        preset = PresetParameters {
+                            maxval: value(1),
+                            t1: value(3),
+                            t2: value(5),
+                            t3: value(7),
+                            reset: value(9),
+                        };
+                    }
+                    // mapping table specification and continuation,
+                    // only used by components which select them
+                    2 | 3 => {}
+                    // oversize image dimensions
+                    4 => {
+                        let frame = frame
+                            .as_mut()
+                            .whatever_context("JPEG-LS image dimensions before frame header")?;
+                        ensure_whatever!(segment.len() >= 2, "Invalid JPEG-LS image dimensions");
+                        let size = usize::from(segment[1]);
+                        ensure_whatever!(
+                            (2..=4).contains(&size) && segment.len() >= 2 + size * 2,
+                            "Invalid JPEG-LS image dimensions"
+                        );
+                        let value = |bytes: &[u8]| {
+                            bytes
+                                .iter()
+                                .fold(0_usize, |acc, b| (acc << 8) | usize::from(*b))
+                        };
+                        frame.height = value(&segment[2..2 + size]);
+                        frame.width = value(&segment[2 + size..2 + size * 2]);
+                    }
+                    id => whatever!("Unsupported JPEG-LS preset parameters type {}", id),
+                }
+            }
+            // SOS
+            0xDA => {
+                let segment = segment(data, &mut pos)?;
+                let frame = frame
+                    .as_ref()
+                    .whatever_context("JPEG-LS scan before frame header")?;
+                ensure_whatever!(
+                    frame.width > 0 && frame.height > 0,
+                    "Unsupported JPEG-LS image size of {}x{}",
+                    frame.width,
+                    frame.height
+                );
+                ensure_whatever!(
+                    frame.width == expected_width && frame.height == expected_height,
+                    "JPEG-LS image of {}x{} does not match the pixel data attributes",
+                    frame.width,
+                    frame.height
+                );
+                if samples.is_empty() {
+                    let len = frame
+                        .width
+                        .checked_mul(frame.height)
+                        .and_then(|len| len.checked_mul(frame.component_ids.len()))
+                        .with_whatever_context(|| {
+                            format!(
+                                "JPEG-LS image of {}x{} is too large",
+                                frame.width, frame.height
+                            )
+                        })?;
+                    samples = vec![0; len];
+                    scanned = vec![false; frame.component_ids.len()];
+                }
+
+                ensure_whatever!(!segment.is_empty(), "Invalid JPEG-LS scan header");
+                let count = usize::from(segment[0]);
+                ensure_whatever!(
+                    count > 0 && segment.len() >= 1 + count * 2 + 3,
+                    "Invalid JPEG-LS scan header"
+                );
+                let mut components = Vec::with_capacity(count);
+                for i in 0..count {
+                    let id = segment[1 + i * 2];
+                    let table = segment[2 + i * 2];
+                    let index = frame
+                        .component_ids
+                        .iter()
+                        .position(|c| *c == id)
+                        .with_whatever_context(|| {
+                            format!("Unknown JPEG-LS scan component {}", id)
+                        })?;
+                    ensure_whatever!(table == 0, "JPEG-LS mapping tables are not supported");
+                    ensure_whatever!(!scanned[index], "JPEG-LS component {} scanned twice", id);
+                    scanned[index] = true;
+                    components.push(index);
+                }
+                let near = i32::from(segment[1 + count * 2]);
+                let interleave = segment[2 + count * 2];
+                let point_transform = segment[3 + count * 2] & 0x0F;
+                ensure_whatever!(
+                    point_transform == 0,
+                    "JPEG-LS point transform is not supported"
+                );
+                let interleave = match (count, interleave) {
+                    (1, _) => Interleave::None,
+                    (_, 1) => Interleave::Line,
+                    (_, 2) => Interleave::Sample,
+                    _ => whatever!("Invalid JPEG-LS interleave mode {}", interleave),
+                };
+
+                let params = CodingParameters::new(frame.bits_per_sample, &preset, near)?;
+                let mut scan = ScanDecoder::new(&data[pos..], params);
+                scan.decode(
+                    frame.width,
+                    frame.height,
+                    frame.component_ids.len(),
+                    &components,
+                    interleave,
+                    &mut samples,
+                )?;
+                pos += scan.reader.end_of_data();
+            }
+            // DRI
+            0xDD => {
+                let segment = segment(data, &mut pos)?;
+                ensure_whatever!(
+                    segment.iter().all(|b| *b == 0),
+                    "JPEG-LS restart intervals are not supported"
+                );
+            }
+            // other frame types
+            0xC0..=0xCF if marker != 0xC4 && marker != 0xC8 && marker != 0xCC => {
+                whatever!(
+                    "Expected JPEG-LS code stream, found SOF marker {:02X}",
+                    marker
+                )
+            }
+            // anything else (application data, comments, ...)
+            _ => {
+                segment(data, &mut pos)?;
+            }
+        }
+    }
+
+    let frame = frame.whatever_context("Missing JPEG-LS frame header")?;
+    ensure_whatever!(
+        !scanned.is_empty() && scanned.iter().all(|s| *s),
+        "Missing JPEG-LS scans"
+    );
+    Ok(Image {
+        width: frame.width,
+        height: frame.height,
+        components: frame.component_ids.len(),
+        bits_per_sample: frame.bits_per_sample,
+        samples,
+    })
+}
+
+/// Move to the next marker, returning its code,
+/// or `None` at the end of the data.
+fn next_marker(data: &[u8], pos: &mut usize) -> DecodeResult<Option<u8>> {
+    if *pos >= data.len() {
+        return Ok(None);
+    }
+    ensure_whatever!(
+        data[*pos] == 0xFF,
+        "Expected JPEG-LS marker at position {}",
+        *pos
+    );
+    // skip fill bytes
+    while *pos < data.len() && data[*pos] == 0xFF {
+        *pos += 1;
+    }
+    let marker = data.get(*pos).copied();
+    *pos += 1;
+    Ok(marker)
+}
+
+/// Read the marker segment at the given position,
+/// returning its contents without the length.
+fn segment<'a>(data: &'a [u8], pos: &mut usize) -> DecodeResult<&'a [u8]> {
+    let len = data
+        .get(*pos..*pos + 2)
+        .map(|len| usize::from(u16::from_be_bytes([len[0], len[1]])))
+        .whatever_context("Unexpected end of JPEG-LS data")?;
+    ensure_whatever!(len >= 2, "Invalid JPEG-LS marker segment length");
+    let segment = data
+        .get(*pos + 2..*pos + len)
+        .whatever_context("Unexpected end of JPEG-LS data")?;
+    *pos += len;
+    Ok(segment)
+}
+
+/// The interleave mode of a scan.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Interleave {
+    /// a single component
+    None,
+    /// one line of each component at a time
+    Line,
+    /// all components of each pixel at a time
+    Sample,
+}
+
+/// The parameters of a scan (ITU-T T.87 A.2.1 and C.2.4.1.1).
+#[derive(Debug, Copy, Clone)]
+struct CodingParameters {
+    maxval: i32,
+    near: i32,
+    range: i32,
+    qbpp: u32,
+    limit: u32,
+    t1: i32,
+    t2: i32,
+    t3: i32,
+    reset: i32,
+}
+
+impl CodingParameters {
+    fn new(bits_per_sample: u8, preset: &PresetParameters, near: i32) -> DecodeResult<Self> {
+        let maxval = if preset.maxval > 0 {
+            preset.maxval
+        } else {
+            (1 << bits_per_sample) - 1
+        };
+        ensure_whatever!(
+            near <= (maxval / 2).min(255),
+            "Invalid JPEG-LS NEAR parameter {}",
+            near
+        );
+        let range = (maxval + 2 * near) / (2 * near + 1) + 1;
+        let bpp = ceil_log2(maxval + 1).max(2);
+        let qbpp = ceil_log2(range);
+        let limit = 2 * (bpp + bpp.max(8));
+
+        // default thresholds
+        let clamp = |i: i32, j: i32| if i > maxval || i < j { j } else { i };
+        let (t1, t2, t3) = if maxval >= 128 {
+            let factor = (maxval.min(4095) + 128) / 256;
+            let t1 = clamp(factor * (3 - 2) + 2 + 3 * near, near + 1);
+            let t2 = clamp(factor * (7 - 3) + 3 + 5 * near, t1);
+            let t3 = clamp(factor * (21 - 4) + 4 + 7 * near, t2);
+            (t1, t2, t3)
+        } else {
+            let factor = 256 / (maxval + 1);
+            let t1 = clamp((3 / factor + 3 * near).max(2), near + 1);
+            let t2 = clamp((7 / factor + 5 * near).max(3), t1);
+            let t3 = clamp((21 / factor + 7 * near).max(4), t2);
+            (t1, t2, t3)
+        };
+        let or_default = |value: i32, default: i32| if value > 0 { value } else { default };
+
+        Ok(CodingParameters {
+            maxval,
+            near,
+            range,
+            qbpp,
+            limit,
+            t1: or_default(preset.t1, t1),
+            t2: or_default(preset.t2, t2),
+            t3: or_default(preset.t3, t3),
+            reset: or_default(preset.reset, 64),
+        })
+    }
+}
+
+/// The smallest number of bits to represent values up to `x - 1`
+fn ceil_log2(x: i32) -> u32 {
+    32 - ((x - 1) as u32).leading_zeros()
+}
+
+/// The state of a regular mode context (ITU-T T.87 A.2.2).
+#[derive(Debug, Copy, Clone)]
+struct Context {
+    a: i32,
+    b: i32,
+    c: i32,
+    n: i32,
+}
+
+/// The state of a run interruption context (ITU-T T.87 A.2.2).
+#[derive(Debug, Copy, Clone)]
+struct RunContext {
+    a: i32,
+    n: i32,
+    nn: i32,
+    run_interruption_type: i32,
+}
+
+/// Reader of the entropy coded data of a scan,
+/// skipping the bits stuffed after each 0xFF byte.
+#[derive(Debug)]
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u64,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            pos: 0,
+            acc: 0,
+            count: 0,
+        }
+    }
+
+    /// Load more bytes into the bit accumulator,
+    /// stopping at the end of the entropy coded data.
+    fn fill(&mut self) {
+        while self.count <= 56 {
+            let Some(&byte) = self.data.get(self.pos) else {
+                return;
+            };
+            if byte == 0xFF && self.data.get(self.pos + 1).map_or(true, |b| *b >= 0x80) {
+                // marker
+                return;
+            }
+            if self.pos > 0 && self.data[self.pos - 1] == 0xFF {
+                // the most significant bit is stuffed
+                self.acc = (self.acc << 7) | u64::from(byte & 0x7F);
+                self.count += 7;
+            } else {
+                self.acc = (self.acc << 8) | u64::from(byte);
+                self.count += 8;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Read `n` bits (up to 32) as an unsigned integer.
+    fn read_bits(&mut self, n: u32) -> DecodeResult<u32> {
+        if n == 0 {
+            return Ok(0);
+        }
+        if self.count < n {
+            self.fill();
+            ensure_whatever!(self.count >= n, "Unexpected end of JPEG-LS scan data");
+        }
+        self.count -= n;
+        Ok(((self.acc >> self.count) & ((1 << n) - 1)) as u32)
+    }
+
+    fn read_bit(&mut self) -> DecodeResult<bool> {
+        self.read_bits(1).map(|b| b == 1)
+    }
+
+    /// Read the number of 0 bits before the next 1 bit,
+    /// failing if there are more than `max`.
+    fn read_unary(&mut self, max: u32) -> DecodeResult<u32> {
+        let mut zeros = 0;
+        loop {
+            if self.count == 0 {
+                self.fill();
+                ensure_whatever!(self.count > 0, "Unexpected end of JPEG-LS scan data");
+            }
+            let bits = self.acc << (64 - self.count);
+            if bits == 0 {
+                zeros += self.count;
+                self.count = 0;
+            } else {
+                let leading = bits.leading_zeros();
+                zeros += leading;
+                self.count -= leading + 1;
+            }
+            ensure_whatever!(zeros <= max, "Invalid JPEG-LS code");
+            if bits != 0 {
+                return Ok(zeros);
+            }
+        }
+    }
+
+    /// The position of the marker which follows the entropy coded data.
+    fn end_of_data(&self) -> usize {
+        let mut pos = self.pos;
+        while pos < self.data.len()
+            && !(self.data[pos] == 0xFF && self.data.get(pos + 1).map_or(true, |b| *b >= 0x80))
+        {
+            pos += 1;
+        }
+        pos
+    }
+}
+
+/// Decoder of the samples in a scan (ITU-T T.87 Annex A).
+#[derive(Debug)]
+struct ScanDecoder<'a> {
+    reader: BitReader<'a>,
+    params: CodingParameters,
+    contexts: Vec<Context>,
+    run_contexts: [RunContext; 2],
+}
+
+impl<'a> ScanDecoder<'a> {
+    fn new(data: &'a [u8], params: CodingParameters) -> Self {
+        let a = ((params.range + 32) / 64).max(2);
+        let context = Context {
+            a,
+            b: 0,
+            c: 0,
+            n: 1,
+        };
+        let run_context = |run_interruption_type| RunContext {
+            a,
+            n: 1,
+            nn: 0,
+            run_interruption_type,
+        };
+        ScanDecoder {
+            reader: BitReader::new(data),
+            params,
+            contexts: vec![context; CONTEXTS],
+            run_contexts: [run_context(0), run_context(1)],
+        }
+    }
+
+    /// Decode all lines of the scan,
+    /// writing the samples of the given frame components
+    /// into `samples`, interleaved by pixel.
+    fn decode(
+        &mut self,
+        width: usize,
+        height: usize,
+        frame_components: usize,
+        components: &[usize],
+        interleave: Interleave,
+        samples: &mut [u16],
+    ) -> DecodeResult<()> {
+        let n = components.len();
+        if interleave == Interleave::Sample {
+            let mut prev = vec![0; (width + 2) * n];
+            let mut cur = vec![0; (width + 2) * n];
+            let mut run_index = 0;
+            for y in 0..height {
+                prev.copy_within(width * n..(width + 1) * n, (width + 1) * n);
+                cur[..n].copy_from_slice(&prev[n..2 * n]);
+                self.decode_line_interleaved(&prev, &mut cur, n, &mut run_index)?;
+                for x in 0..width {
+                    for (k, c) in components.iter().enumerate() {
+                        samples[(y * width + x) * frame_components + c] =
+                            cur[(x + 1) * n + k] as u16;
+                    }
+                }
+                std::mem::swap(&mut prev, &mut cur);
+            }
+        } else {
+            // one pair of lines and run index per component
+            let mut prev = vec![vec![0; width + 2]; n];
+            let mut cur = vec![vec![0; width + 2]; n];
+            let mut run_index = vec![0; n];
+            for y in 0..height {
+                for (k, c) in components.iter().enumerate() {
+                    let (prev, cur) = (&mut prev[k], &mut cur[k]);
+                    prev[width + 1] = prev[width];
+                    cur[0] = prev[1];
+                    self.decode_line(prev, cur, &mut run_index[k])?;
+                    for x in 0..width {
+                        samples[(y * width + x) * frame_components + c] = cur[x + 1] as u16;
+                    }
+                    std::mem::swap(prev, cur);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode a line of a single component.
+    ///
+    /// Both lines are padded with one sample on each side.
+    fn decode_line(
+        &mut self,
+        prev: &[i32],
+        cur: &mut [i32],
+        run_index: &mut usize,
+    ) -> DecodeResult<()> {
+        let width = cur.len() - 2;
+        let mut i = 1;
+        while i <= width {
+            let (ra, rb, rc, rd) = (cur[i - 1], prev[i], prev[i - 1], prev[i + 1]);
+            let qs = self.context_id(rd - rb, rb - rc, rc - ra);
+            if qs != 0 {
+                cur[i] = self.decode_regular(qs, ra, rb, rc)?;
+                i += 1;
+                continue;
+            }
+
+            let run = self.decode_run_length(run_index, width + 1 - i)?;
+            cur[i..i + run].fill(ra);
+            i += run;
+            if i <= width {
+                cur[i] = self.decode_run_interruption(ra, prev[i], *run_index)?;
+                *run_index = run_index.saturating_sub(1);
+                i += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode a line of `n` components interleaved by sample.
+    ///
+    /// Both lines are padded with one pixel on each side.
+    fn decode_line_interleaved(
+        &mut self,
+        prev: &[i32],
+        cur: &mut [i32],
+        n: usize,
+        run_index: &mut usize,
+    ) -> DecodeResult<()> {
+        let width = cur.len() / n - 2;
+        let mut qs = vec![0; n];
+        let mut i = 1;
+        while i <= width {
+            for (c, qs) in qs.iter_mut().enumerate() {
+                let (ra, rb, rc, rd) = (
+                    cur[(i - 1) * n + c],
+                    prev[i * n + c],
+                    prev[(i - 1) * n + c],
+                    prev[(i + 1) * n + c],
+                );
+                *qs = self.context_id(rd - rb, rb - rc, rc - ra);
+            }
+            if qs.iter().any(|q| *q != 0) {
+                for (c, qs) in qs.iter().enumerate() {
+                    let (ra, rb, rc) =
+                        (cur[(i - 1) * n + c], prev[i * n + c], prev[(i - 1) * n + c]);
+                    cur[i * n + c] = self.decode_regular(*qs, ra, rb, rc)?;
+                }
+                i += 1;
+                continue;
+            }
+
+            let run = self.decode_run_length(run_index, width + 1 - i)?;
+            for j in i..i + run {
+                cur.copy_within((i - 1) * n..i * n, j * n);
+            }
+            i += run;
+            if i <= width {
+                // all components use the run interruption context of type 0
+                for c in 0..n {
+                    let (ra, rb) = (cur[(i - 1) * n + c], prev[i * n + c]);
+                    let error = self.decode_run_interruption_error(0, *run_index)?;
+                    let sign = if rb < ra { -1 } else { 1 };
+                    cur[i * n + c] = self.reconstruct(rb, error * sign);
+                }
+                *run_index = run_index.saturating_sub(1);
+                i += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Quantize the local gradients into a signed context ID,
+    /// which is 0 if run mode applies.
+    fn context_id(&self, d1: i32, d2: i32, d3: i32) -> i32 {
+        (self.quantize(d1) * 9 + self.quantize(d2)) * 9 + self.quantize(d3)
+    }
+
+    fn quantize(&self, d: i32) -> i32 {
+        let CodingParameters {
+            near, t1, t2, t3, ..
+        } = self.params;
+        if d <= -t3 {
+            -4
+        } else if d <= -t2 {
+            -3
+        } else if d <= -t1 {
+            -2
+        } else if d < -near {
+            -1
+        } else if d <= near {
+            0
+        } else if d < t1 {
+            1
+        } else if d < t2 {
+            2
+        } else if d < t3 {
+            3
+        } else {
+            4
+        }
+    }
+
+    /// Decode a sample in regular mode (ITU-T T.87 A.4 to A.6).
+    fn decode_regular(&mut self, qs: i32, ra: i32, rb: i32, rc: i32) -> DecodeResult<i32> {
+        let sign = if qs < 0 { -1 } else { 1 };
+        let q = (qs * sign) as usize;
+        let Context { a, b, c, n } = self.contexts[q];
+
+        // median edge detector
+        let predicted = if rc >= ra.max(rb) {
+            ra.min(rb)
+        } else if rc <= ra.min(rb) {
+            ra.max(rb)
+        } else {
+            ra + rb - rc
+        };
+        let predicted = (predicted + sign * c).clamp(0, self.params.maxval);
+
+        let k = golomb_parameter(n, a);
+        let mapped = self.decode_value(k, self.params.limit)?;
+        let mut error = if mapped & 1 == 0 {
+            mapped >> 1
+        } else {
+            -((mapped + 1) >> 1)
+        };
+        if k == 0 && self.params.near == 0 && 2 * b + n - 1 < 0 {
+            // inverse of the bias dependent error mapping
+            error = -error - 1;
+        }
+
+        self.update_context(q, error);
+        Ok(self.reconstruct(predicted, error * sign))
+    }
+
+    /// Update a regular mode context with the error of a sample
+    /// (ITU-T T.87 A.6).
+    fn update_context(&mut self, q: usize, error: i32) {
+        let CodingParameters { near, reset, .. } = self.params;
+        let ctx = &mut self.contexts[q];
+        ctx.a += error.abs();
+        ctx.b += error * (2 * near + 1);
+        if ctx.n == reset {
+            ctx.a >>= 1;
+            ctx.b >>= 1;
+            ctx.n >>= 1;
+        }
+        ctx.n += 1;
+
+        // bias correction
+        if ctx.b + ctx.n <= 0 {
+            ctx.b += ctx.n;
+            if ctx.b <= -ctx.n {
+                ctx.b = -ctx.n + 1;
+            }
+            if ctx.c > MIN_C {
+                ctx.c -= 1;
+            }
+        } else if ctx.b > 0 {
+            ctx.b -= ctx.n;
+            if ctx.b > 0 {
+                ctx.b = 0;
+            }
+            if ctx.c < MAX_C {
+                ctx.c += 1;
+            }
+        }
+    }
+
+    /// Decode the length of a run of samples equal to the previous one,
+    /// up to the end of the line (ITU-T T.87 A.7.1).
+    fn decode_run_length(
+        &mut self,
+        run_index: &mut usize,
+        remaining: usize,
+    ) -> DecodeResult<usize> {
+        let mut length = 0;
+        while self.reader.read_bit()? {
+            let segment = 1 << J[*run_index];
+            let count = segment.min(remaining - length);
+            length += count;
+            if count == segment {
+                *run_index = (*run_index + 1).min(J.len() - 1);
+            }
+            if length == remaining {
+                break;
+            }
+        }
+        if length != remaining {
+            // the run was interrupted before the end of the line
+            length += self.reader.read_bits(J[*run_index])? as usize;
+        }
+        ensure_whatever!(length <= remaining, "Invalid JPEG-LS run length");
+        Ok(length)
+    }
+
+    /// Decode the sample which interrupts a run (ITU-T T.87 A.7.2).
+    fn decode_run_interruption(&mut self, ra: i32, rb: i32, run_index: usize) -> DecodeResult<i32> {
+        if (ra - rb).abs() <= self.params.near {
+            let error = self.decode_run_interruption_error(1, run_index)?;
+            Ok(self.reconstruct(ra, error))
+        } else {
+            let error = self.decode_run_interruption_error(0, run_index)?;
+            let sign = if rb < ra { -1 } else { 1 };
+            Ok(self.reconstruct(rb, error * sign))
+        }
+    }
+
+    /// Decode the prediction error of a run interruption sample
+    /// and update its context.
+    fn decode_run_interruption_error(
+        &mut self,
+        run_interruption_type: usize,
+        run_index: usize,
+    ) -> DecodeResult<i32> {
+        let RunContext {
+            a,
+            n,
+            nn,
+            run_interruption_type: ri,
+        } = self.run_contexts[run_interruption_type];
+        let k = golomb_parameter(n, a + (n >> 1) * ri);
+        let mapped = self.decode_value(k, self.params.limit - J[run_index] - 1)?;
+
+        let value = mapped + ri;
+        let map = value & 1 == 1;
+        let magnitude = (value + 1) >> 1;
+        let error = if (k != 0 || 2 * nn >= n) == map {
+            -magnitude
+        } else {
+            magnitude
+        };
+
+        let reset = self.params.reset;
+        let ctx = &mut self.run_contexts[run_interruption_type];
+        if error < 0 {
+            ctx.nn += 1;
+        }
+        ctx.a += (mapped + 1 - ri) >> 1;
+        if ctx.n == reset {
+            ctx.a >>= 1;
+            ctx.n >>= 1;
+            ctx.nn >>= 1;
+        }
+        ctx.n += 1;
+        Ok(error)
+    }
+
+    /// Decode a mapped error value
+    /// in limited length Golomb code (ITU-T T.87 A.5.3).
+    fn decode_value(&mut self, k: u32, limit: u32) -> DecodeResult<i32> {
+        let qbpp = self.params.qbpp;
+        let escape = limit - qbpp - 1;
+        let high = self.reader.read_unary(escape)?;
+        let value = if high == escape {
+            u64::from(self.reader.read_bits(qbpp)?) + 1
+        } else {
+            (u64::from(high) << k) | u64::from(self.reader.read_bits(k)?)
+        };
+        ensure_whatever!(
+            value <= 2 * self.params.range as u64,
+            "Invalid JPEG-LS code"
+        );
+        Ok(value as i32)
+    }
+
+    /// Reconstruct a sample from its prediction and (quantized) error
+    /// (ITU-T T.87 A.4.4).
+    fn reconstruct(&self, predicted: i32, error: i32) -> i32 {
+        let CodingParameters {
+            maxval,
+            near,
+            range,
+            ..
+        } = self.params;
+        let mut value = predicted + error * (2 * near + 1);
+        if value < -near {
+            value += range * (2 * near + 1);
+        } else if value > maxval + near {
+            value -= range * (2 * near + 1);
+        }
+        value.clamp(0, maxval)
+    }
+}
+
+/// Compute the Golomb coding parameter `k`
+/// for a context with `n` occurrences and an accumulated error of `a`.
+fn golomb_parameter(n: i32, a: i32) -> u32 {
+    let mut k = 0;
+    while (n << k) < a && k < 31 {
+        k += 1;
+    }
+    k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal code stream of 8 bit samples
+    /// with the given entropy coded data.
+    fn code_stream(width: u8, height: u8, scan_data: &[u8]) -> Vec<u8> {
+        // SOI
+        let mut data = vec![0xFF, 0xD8];
+        // SOF55: 8 bits, height, width, 1 component
+        data.extend_from_slice(&[0xFF, 0xF7, 0x00, 0x0B, 0x08, 0x00, height, 0x00, width]);
+        data.extend_from_slice(&[0x01, 0x01, 0x11, 0x00]);
+        // SOS: 1 component, NEAR = 0, no interleave, no point transform
+        data.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00]);
+        data.extend_from_slice(scan_data);
+        // EOI
+        data.extend_from_slice(&[0xFF, 0xD9]);
+        data
+    }
+
+    #[test]
+    fn default_coding_parameters() {
+        let params = CodingParameters::new(8, &PresetParameters::default(), 0).unwrap();
+        assert_eq!(params.maxval, 255);
+        assert_eq!(params.range, 256);
+        assert_eq!(params.qbpp, 8);
+        assert_eq!(params.limit, 32);
+        assert_eq!((params.t1, params.t2, params.t3), (3, 7, 21));
+        assert_eq!(params.reset, 64);
+
+        let params = CodingParameters::new(16, &PresetParameters::default(), 0).unwrap();
+        assert_eq!(params.range, 65536);
+        assert_eq!(params.qbpp, 16);
+        assert_eq!(params.limit, 64);
+        assert_eq!((params.t1, params.t2, params.t3), (18, 67, 276));
+
+        // near-lossless
+        let params = CodingParameters::new(8, &PresetParameters::default(), 3).unwrap();
+        assert_eq!(params.range, 38);
+        assert_eq!((params.t1, params.t2, params.t3), (12, 22, 42));
+
+        assert!(CodingParameters::new(8, &PresetParameters::default(), 128).is_err());
+    }
+
+    #[test]
+    fn bit_reader_skips_stuffed_bits() {
+        // 0xFF is followed by 7 bits of data
+        let mut reader = BitReader::new(&[0xFF, 0x7F, 0x80, 0xFF, 0xD9]);
+        assert_eq!(reader.read_bits(8).unwrap(), 0xFF);
+        assert_eq!(reader.read_bits(7).unwrap(), 0x7F);
+        assert_eq!(reader.read_unary(8).unwrap(), 0);
+        assert_eq!(reader.read_bits(7).unwrap(), 0);
+        // stops at the marker
+        assert!(reader.read_bit().is_err());
+        assert_eq!(reader.end_of_data(), 3);
+    }
+
+    /// A line of identical samples is coded as a single run
+    #[test]
+    fn decode_run() {
+        // 4 full run segments of length 1
+        let data = code_stream(4, 1, &[0b1111_0000]);
+        let image = decode(&data, 4, 1).unwrap();
+        assert_eq!(image.width, 4);
+        assert_eq!(image.height, 1);
+        assert_eq!(image.components, 1);
+        assert_eq!(image.bits_per_sample, 8);
+        assert_eq!(image.samples, vec![0; 4]);
+
+        // the next line starts a new run with the run index at 4,
+        // where segments are 2 samples long
+        let data = code_stream(4, 2, &[0b1111_1100]);
+        let image = decode(&data, 4, 2).unwrap();
+        assert_eq!(image.samples, vec![0; 8]);
+    }
+
+    #[test]
+    fn reject_other_processes() {
+        let mut data = code_stream(4, 1, &[0b1111_0000]);
+        // turn SOF55 into SOF3 (lossless JPEG)
+        data[3] = 0xC3;
+        assert!(decode(&data, 4, 1).is_err());
+
+        assert!(decode(&[0xFF, 0xD8, 0xFF, 0xD9], 4, 1).is_err());
+        assert!(decode(&[0x00, 0x01], 4, 1).is_err());
+    }
+
+    #[test]
+    fn reject_unexpected_dimensions() {
+        let data = code_stream(4, 1, &[0b1111_0000]);
+        assert!(decode(&data, 4, 2).is_err());
+        assert!(decode(&data, 2, 1).is_err());
+
+        // LSE oversize image dimensions of 2^32 - 1 x 2^32 - 1
+        let mut data = code_stream(4, 1, &[0b1111_0000]);
+        let lse = [
+            0xFF, 0xF8, 0x00, 0x0C, 0x04, 0x04, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+        ];
+        data.splice(15..15, lse);
+        assert!(decode(&data, 4, 1).is_err());
+    }
+}
@@ -3,7 +3,8 @@
 use dicom_core::ops::{AttributeAction, AttributeOp};
 use dicom_core::Tag;
 use dicom_encoding::adapters::{
-    decode_error, encode_error, DecodeResult, PixelDataObject, PixelDataReader, PixelDataWriter,
+    decode_error, encode_error, DecodeResult, EncodeProperty, EncodeSourceProperties,
+    PixelDataObject, PixelDataReader, PixelDataWriter, SupportLevel,
 };
 use dicom_encoding::snafu::prelude::*;
 use jxl_oxide::JxlImage;
@@ -150,6 +151,28 @@ impl PixelDataReader for JpegXlAdapter {
 }
 
 impl PixelDataWriter for JpegXlAdapter {
+    fn supports(&self, props: &EncodeSourceProperties) -> SupportLevel {
+        if props.rows == 0 || props.cols == 0 {
+            return SupportLevel::Unsupported(EncodeProperty::FrameSize);
+        }
+        if props.bits_allocated != 8 && props.bits_allocated != 16 {
+            return SupportLevel::Unsupported(EncodeProperty::BitsAllocated);
+        }
+        if props.bits_stored == 0 || props.bits_stored > props.bits_allocated {
+            return SupportLevel::Unsupported(EncodeProperty::BitsStored);
+        }
+        match (
+            props.samples_per_pixel,
+            props.photometric_interpretation.as_str(),
+        ) {
+            (1, "MONOCHROME1" | "MONOCHROME2" | "PALETTE COLOR") | (3, "RGB") => {
+                SupportLevel::Supported
+            }
+            (1 | 3, _) => SupportLevel::Unsupported(EncodeProperty::PhotometricInterpretation),
+            _ => SupportLevel::Unsupported(EncodeProperty::SamplesPerPixel),
+        }
+    }
+
     fn encode_frame(
         &self,
         src: &dyn PixelDataObject,
@@ -184,9 +207,15 @@ impl PixelDataWriter for JpegXlAdapter {
         let dicom_encoding::adapters::EncodeOptions {
             quality,
             effort,
+            near_lossless,
             ..
         } = options;
 
+        // warn only once when encoding all frames
+        if frame == 0 && near_lossless.is_some() {
+            tracing::warn!("JPEG XL encoder does not support near-lossless encoding, ignoring");
+        }
+
         let bytes_per_sample = (bits_allocated / 8) as usize;
         let frame_size =
             cols as usize * rows as usize * samples_per_pixel as usize * bytes_per_sample;
@@ -277,6 +306,10 @@ impl PixelDataWriter for JpegXlAdapter {
 }
 
 impl PixelDataWriter for JpegXlLosslessEncoder {
+    fn supports(&self, props: &EncodeSourceProperties) -> SupportLevel {
+        JpegXlAdapter.supports(props)
+    }
+
     fn encode_frame(
         &self,
         src: &dyn PixelDataObject,
@@ -17,12 +17,21 @@
 //!   to statically link to the OpenJPEG reference implementation.
 //!   `openjp2` is enabled by the feature `native`.
 //!   To build on Windows, enable `native_windows` instead.
+//! - [`jpegls`](jpegls) provides JPEG-LS decoding and encoding
+//!   by linking to [CharLS].
+//!   Requires the `charls` feature.
+//! - [`jpegls_rust`](jpegls_rust) provides native JPEG-LS decoding.
+//!   Requires the `jpegls-rust` feature,
+//!   enabled by default.
+//!   When `charls` is also enabled,
+//!   the CharLS-based adapter is used instead.
 //! - [`jpegxl`](jpegxl) provides JPEG XL decoding and encoding,
 //!   through `jxl-oxide` and `zune-jpegxl`, respectively.
 //! - [`rle_lossless`](rle_lossless) provides native RLE lossless decoding.
 //!   Requires the `rle` feature,
 //!   enabled by default.
 //!
+//! [CharLS]: https://github.com/team-charls/charls
 //! [OpenJPEG]: https://github.com/uclouvain/openjpeg
 //! [OpenJPEG-rs]: https://crates.io/crates/openjp2
 #[cfg(feature = "jpeg")]
@@ -31,6 +40,8 @@ pub mod jpeg;
 pub mod jpeg2k;
 #[cfg(feature = "charls")]
 pub mod jpegls;
+#[cfg(feature = "jpegls-rust")]
+pub mod jpegls_rust;
 #[cfg(feature = "jpegxl")]
 pub mod jpegxl;
 #[cfg(feature = "rle")]
@@ -58,6 +69,11 @@ pub mod rle {}
 #[cfg(not(feature = "charls"))]
 pub mod jpegls {}
 
+/// **Note:** This module is a stub.
+/// Enable the `jpegls-rust` feature to use this module.
+#[cfg(not(feature = "jpegls-rust"))]
+pub mod jpegls_rust {}
+
 /// **Note:** This module is a stub.
 /// Enable the `jpegxl` feature to use this module.
 #[cfg(not(feature = "jpegxl"))]
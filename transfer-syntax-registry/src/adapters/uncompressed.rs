@@ -37,17 +37,16 @@ impl PixelDataReader for UncompressedAdapter {
         frame: u32,
         dst: &mut Vec<u8>,
     ) -> DecodeResult<()> {
-        // just copy the specific fragment into the output vector
+        // just copy the frame's fragments into the output vector
         let pixeldata = src
             .raw_pixel_data()
             .context(decode_error::MissingAttributeSnafu { name: "Pixel Data" })?;
 
-        let fragment = pixeldata
-            .fragments
-            .get(frame as usize)
+        let frame_data = pixeldata
+            .frame_data(src.number_of_frames().unwrap_or(1), frame)
             .context(decode_error::FrameRangeOutOfBoundsSnafu)?;
 
-        dst.extend_from_slice(fragment);
+        dst.extend_from_slice(&frame_data);
 
         Ok(())
     }
@@ -27,15 +27,26 @@ use dicom_encoding::transfer_syntax::{AdapterFreeTransferSyntax as Ts, Codec};
 
 use dicom_encoding::transfer_syntax::{NeverAdapter, TransferSyntax};
 
-#[cfg(any(feature = "rle", feature = "openjp2", feature = "openjpeg-sys"))]
+#[cfg(any(
+    feature = "deflate",
+    feature = "rle",
+    feature = "openjp2",
+    feature = "openjpeg-sys",
+    all(feature = "jpegls-rust", not(feature = "charls"))
+))]
 use dicom_encoding::NeverPixelAdapter;
 
+#[cfg(feature = "deflate")]
+use crate::deflate::FlateAdapter;
+
 #[cfg(feature = "jpeg")]
 use crate::adapters::jpeg::JpegAdapter;
 #[cfg(any(feature = "openjp2", feature = "openjpeg-sys"))]
 use crate::adapters::jpeg2k::Jpeg2000Adapter;
 #[cfg(feature = "charls")]
 use crate::adapters::jpegls::{JpegLsAdapter, JpegLsLosslessWriter};
+#[cfg(all(feature = "jpegls-rust", not(feature = "charls")))]
+use crate::adapters::jpegls_rust::JpegLsRustAdapter;
 #[cfg(feature = "jpegxl")]
 use crate::adapters::jpegxl::{JpegXlAdapter, JpegXlLosslessEncoder};
 #[cfg(feature = "rle")]
@@ -181,9 +192,27 @@ pub const JPEG_LOSSLESS_NON_HIERARCHICAL_FIRST_ORDER_PREDICTION: Ts = create_ts_
     "JPEG Lossless, Non-Hierarchical, First-Order Prediction",
 );
 
+/// **Fully implemented:** Deflated Explicit VR Little Endian
+#[cfg(feature = "deflate")]
+pub const DEFLATED_EXPLICIT_VR_LITTLE_ENDIAN: TransferSyntax<
+    FlateAdapter,
+    NeverPixelAdapter,
+    NeverPixelAdapter,
+> = TransferSyntax::new(
+    "1.2.840.10008.1.2.1.99",
+    "Deflated Explicit VR Little Endian",
+    Endianness::Little,
+    true,
+    Codec::Dataset(Some(FlateAdapter)),
+);
+
 // --- stub transfer syntaxes, known but not supported ---
 
 /// **Stub descriptor:** Deflated Explicit VR Little Endian
+///
+/// An implementation is available
+/// by enabling the `deflate` Cargo feature.
+#[cfg(not(feature = "deflate"))]
 pub const DEFLATED_EXPLICIT_VR_LITTLE_ENDIAN: Ts = Ts::new_ele(
     "1.2.840.10008.1.2.1.99",
     "Deflated Explicit VR Little Endian",
@@ -328,8 +357,21 @@ pub const JPEG_LS_LOSSLESS_IMAGE_COMPRESSION: JpegLSTs<JpegLsLosslessWriter> = T
     Codec::EncapsulatedPixelData(Some(JpegLsAdapter), Some(JpegLsLosslessWriter)),
 );
 
+/// An alias for a transfer syntax specifier with [`JpegLsRustAdapter`] as the decoder
+/// and no encoder
+#[cfg(all(feature = "jpegls-rust", not(feature = "charls")))]
+type JpegLSRustTs = TransferSyntax<NeverAdapter, JpegLsRustAdapter, NeverPixelAdapter>;
+
+/// **Decoder Implementation:** JPEG-LS Lossless Image Compression
+#[cfg(all(feature = "jpegls-rust", not(feature = "charls")))]
+pub const JPEG_LS_LOSSLESS_IMAGE_COMPRESSION: JpegLSRustTs = TransferSyntax::new_ele(
+    "1.2.840.10008.1.2.4.80",
+    "JPEG-LS Lossless Image Compression",
+    Codec::EncapsulatedPixelData(Some(JpegLsRustAdapter), None),
+);
+
 /// **Stub descriptor:** JPEG-LS Lossless Image Compression
-#[cfg(not(feature = "charls"))]
+#[cfg(not(any(feature = "charls", feature = "jpegls-rust")))]
 pub const JPEG_LS_LOSSLESS_IMAGE_COMPRESSION: Ts = create_ts_stub(
     "1.2.840.10008.1.2.4.80",
     "JPEG-LS Lossless Image Compression",
@@ -343,6 +385,14 @@ pub const JPEG_LS_LOSSY_IMAGE_COMPRESSION: JpegLSTs<JpegLsAdapter> = TransferSyn
     Codec::EncapsulatedPixelData(Some(JpegLsAdapter), Some(JpegLsAdapter)),
 );
 
+/// **Decoder Implementation:** JPEG-LS Lossy (Near-Lossless) Image Compression
+#[cfg(all(feature = "jpegls-rust", not(feature = "charls")))]
+pub const JPEG_LS_LOSSY_IMAGE_COMPRESSION: JpegLSRustTs = TransferSyntax::new_ele(
+    "1.2.840.10008.1.2.4.81",
+    "JPEG-LS Lossy (Near-Lossless) Image Compression",
+    Codec::EncapsulatedPixelData(Some(JpegLsRustAdapter), None),
+);
+
 // --- JPEG XL support ---
 
 /// An alias for a transfer syntax specifier with [`JpegXLAdapter`]
@@ -395,7 +445,7 @@ pub const JPEG_XL: Ts = create_ts_stub(
 );
 
 /// **Stub descriptor:** JPEG-LS Lossy (Near-Lossless) Image Compression
-#[cfg(not(feature = "charls"))]
+#[cfg(not(any(feature = "charls", feature = "jpegls-rust")))]
 pub const JPEG_LS_LOSSY_IMAGE_COMPRESSION: Ts = create_ts_stub(
     "1.2.840.10008.1.2.4.81",
     "JPEG-LS Lossy (Near-Lossless) Image Compression",
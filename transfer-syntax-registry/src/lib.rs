@@ -35,6 +35,11 @@
 //!
 //! All registered TSes will be readily available
 //! through the [`TransferSyntaxRegistry`] type.
+//! When the target transfer syntax for encoding is flexible,
+//! [`TransferSyntaxRegistry::select_encoder`]
+//! picks the first one in a list of preferences
+//! which can encode the pixel data at hand
+//! (see the [`selection`] module).
 //!
 //! This registry is intended to be used in the development of higher level APIs,
 //! which should learn to negotiate and resolve the expected
@@ -48,6 +53,8 @@
 //! _Explicit VR Little Endian_,
 //! and _Explicit VR Big Endian_
 //! are fully supported.
+//! _Deflated Explicit VR Little Endian_ is also fully supported
+//! with the Cargo feature `deflate`.
 //! Support may vary for transfer syntaxes which rely on encapsulated pixel data.
 //!
 //! | transfer syntax               | decoding support     | encoding support |
@@ -56,8 +63,8 @@
 //! | JPEG Extended (Process 2 & 4) | Cargo feature `jpeg` | x |
 //! | JPEG Lossless, Non-Hierarchical (Process 14) | Cargo feature `jpeg` | x |
 //! | JPEG Lossless, Non-Hierarchical, First-Order Prediction (Process 14 [Selection Value 1]) | Cargo feature `jpeg` | x |
-//! | JPEG-LS Lossless              | Cargo feature `charls` | ✓ |
-//! | JPEG-LS Lossy (Near-Lossless) | Cargo feature `charls` | ✓ |
+//! | JPEG-LS Lossless              | Cargo feature `jpegls-rust` or `charls` | ✓ (`charls` only) |
+//! | JPEG-LS Lossy (Near-Lossless) | Cargo feature `jpegls-rust` or `charls` | ✓ (`charls` only) |
 //! | JPEG 2000 (Lossless Only)     | Cargo feature `openjp2` or `openjpeg-sys` | x |
 //! | JPEG 2000                     | Cargo feature `openjp2` or `openjpeg-sys` | x |
 //! | JPEG 2000 Part 2 Multi-component Image Compression (Lossless Only) | Cargo feature `openjp2` or `openjpeg-sys` | x |
@@ -70,7 +77,7 @@
 //! | JPEG XL                       | Cargo feature `jpegxl` | ✓ |
 //! | RLE Lossless                  | Cargo feature `rle` | x |
 //!
-//! Cargo features behind `native` (`jpeg`, `rle`) are added by default.
+//! Cargo features behind `native` (`jpeg`, `jpegls-rust`, `rle`) are added by default.
 //! They provide implementations that are written in pure Rust
 //! and are likely available in all supported platforms without issues.
 //! Additional codecs are opt-in by enabling Cargo features,
@@ -80,7 +87,9 @@
 //! - `charls` provides support for JPEG-LS
 //!   by linking to the CharLS reference implementation,
 //!   which is written in C++.
-//!   No alternative JPEG-LS implementations are available at the moment. 
+//!   Unlike the native decoder behind `jpegls-rust`,
+//!   it also supports encoding and is likely to perform better.
+//!   When both features are enabled, CharLS is used.
 //! - `openjpeg-sys` provides a binding to the OpenJPEG reference implementation,
 //!   which is written in C and is statically linked.
 //!   It may offer better performance than the pure Rust implementation,
@@ -91,6 +100,8 @@
 //!   it might not work on all modern platforms.
 //! - `jpegxl` adds JPEG XL support using `jxl-oxide` for decoding
 //!   and `zune-jpegxl` for encoding.
+//! - `deflate` adds support for data sets in
+//!   _Deflated Explicit VR Little Endian_ using `flate2`.
 //!
 //! Transfer syntaxes which are not supported,
 //! either due to being unable to read the data set
@@ -102,6 +113,7 @@
 //!
 //! [inventory]: https://docs.rs/inventory/0.3.15/inventory
 
+use dicom_encoding::adapters::EncodeSourceProperties;
 use dicom_encoding::transfer_syntax::{
     AdapterFreeTransferSyntax as Ts, Codec, TransferSyntaxIndex,
 };
@@ -112,8 +124,13 @@ use std::fmt;
 
 pub use dicom_encoding::TransferSyntax;
 pub mod entries;
+pub mod selection;
+
+pub use selection::EncodingProfile;
 
 mod adapters;
+#[cfg(feature = "deflate")]
+mod deflate;
 
 #[cfg(feature = "inventory-registry")]
 pub use dicom_encoding::inventory;
@@ -216,6 +233,21 @@ impl TransferSyntaxRegistry {
     pub fn iter(&self) -> impl Iterator<Item = &TransferSyntax> {
         get_registry().iter()
     }
+
+    /// Select the first transfer syntax in `preferences`
+    /// which has an encoder able to encode
+    /// pixel data with the given properties.
+    ///
+    /// Curated preference lists can be obtained from
+    /// [`EncodingProfile::preferences`].
+    /// See [`selection::select_encoder`] for more details.
+    pub fn select_encoder(
+        &self,
+        preferences: &[&str],
+        source_props: &EncodeSourceProperties,
+    ) -> Option<&'static TransferSyntax> {
+        selection::select_encoder(get_registry(), preferences, source_props)
+    }
 }
 
 /// Zero-sized representative of the main transfer syntax registry.
@@ -332,6 +364,45 @@ pub fn default() -> Ts {
     entries::IMPLICIT_VR_LITTLE_ENDIAN
 }
 
+/// Retrieve the Cargo features of this crate,
+/// any of which provides a pixel data decoder
+/// for the transfer syntax with the given UID.
+///
+/// This serves to suggest how to obtain decoding support
+/// when a transfer syntax is recognized
+/// but its pixel data cannot be decoded,
+/// regardless of whether the features are currently enabled.
+/// The slice is empty if no such feature exists,
+/// which is also the case for unknown transfer syntaxes
+/// and those which do not require a pixel data decoder.
+pub fn decoder_features(uid: &str) -> &'static [&'static str] {
+    let uid = uid.trim_end_matches(|c: char| c.is_whitespace() || c == '\0');
+    match uid {
+        // JPEG Baseline, JPEG Extended, JPEG Lossless
+        "1.2.840.10008.1.2.4.50"
+        | "1.2.840.10008.1.2.4.51"
+        | "1.2.840.10008.1.2.4.57"
+        | "1.2.840.10008.1.2.4.70" => &["jpeg"],
+        // JPEG-LS
+        "1.2.840.10008.1.2.4.80" | "1.2.840.10008.1.2.4.81" => &["jpegls-rust", "charls"],
+        // JPEG 2000, High-Throughput JPEG 2000
+        "1.2.840.10008.1.2.4.90"
+        | "1.2.840.10008.1.2.4.91"
+        | "1.2.840.10008.1.2.4.92"
+        | "1.2.840.10008.1.2.4.93"
+        | "1.2.840.10008.1.2.4.201"
+        | "1.2.840.10008.1.2.4.202"
+        | "1.2.840.10008.1.2.4.203" => &["openjp2", "openjpeg-sys"],
+        // JPEG XL
+        "1.2.840.10008.1.2.4.110" | "1.2.840.10008.1.2.4.111" | "1.2.840.10008.1.2.4.112" => {
+            &["jpegxl"]
+        }
+        // RLE Lossless
+        "1.2.840.10008.1.2.5" => &["rle"],
+        _ => &[],
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use dicom_encoding::TransferSyntaxIndex;
@@ -377,4 +448,28 @@ mod tests {
         assert!(all_tss.iter().any(|ts| ts.uid() == "1.2.840.10008.1.2"));
         assert!(all_tss.iter().any(|ts| ts.uid() == "1.2.840.10008.1.2.1"));
     }
+
+    #[test]
+    fn suggests_decoder_features() {
+        use crate::decoder_features;
+
+        // JPEG-LS Lossless
+        assert_eq!(
+            decoder_features("1.2.840.10008.1.2.4.80"),
+            &["jpegls-rust", "charls"]
+        );
+        // JPEG 2000, with trailing null character
+        assert_eq!(
+            decoder_features("1.2.840.10008.1.2.4.91\0"),
+            &["openjp2", "openjpeg-sys"]
+        );
+        // RLE Lossless
+        assert_eq!(decoder_features("1.2.840.10008.1.2.5"), &["rle"]);
+
+        // Explicit VR Little Endian does not need a decoder
+        assert!(decoder_features("1.2.840.10008.1.2.1").is_empty());
+        // no decoder available for MPEG2
+        assert!(decoder_features("1.2.840.10008.1.2.4.100").is_empty());
+        assert!(decoder_features("1.2.3.4").is_empty());
+    }
 }
@@ -0,0 +1,115 @@
+//! Selection of a target transfer syntax for encoding pixel data.
+//!
+//! Not all transfer syntaxes have a pixel data encoder available,
+//! as this depends on the features enabled in this crate,
+//! and an encoder may not support every kind of pixel data.
+//! Rather than failing when the preferred transfer syntax
+//! cannot be used,
+//! applications can express an ordered list of preferences
+//! and take the first one which is workable for the pixel data at hand,
+//! via [`select_encoder`]
+//! or [`TransferSyntaxRegistry::select_encoder`](crate::TransferSyntaxRegistry::select_encoder).
+//!
+//! A few curated preference lists are available through [`EncodingProfile`].
+use dicom_encoding::adapters::{EncodeSourceProperties, SupportLevel};
+use dicom_encoding::transfer_syntax::{Codec, TransferSyntaxIndex};
+use dicom_encoding::TransferSyntax;
+
+/// A curated list of transfer syntax preferences
+/// for a typical purpose.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EncodingProfile {
+    /// Lossless compression for long term storage,
+    /// preferring better compression ratios.
+    ///
+    /// Falls back to _Explicit VR Little Endian_,
+    /// so that a transfer syntax is always available.
+    LosslessArchive,
+    /// Lossy compression for display and transmission over the web,
+    /// preferring widely supported encodings.
+    ///
+    /// Falls back to _Explicit VR Little Endian_,
+    /// so that a transfer syntax is always available.
+    LossyWeb,
+}
+
+impl EncodingProfile {
+    /// Obtain the transfer syntax UIDs of this profile,
+    /// from the most preferred to the least preferred.
+    pub fn preferences(self) -> &'static [&'static str] {
+        match self {
+            EncodingProfile::LosslessArchive => &[
+                // JPEG-LS Lossless Image Compression
+                "1.2.840.10008.1.2.4.80",
+                // JPEG XL Lossless
+                "1.2.840.10008.1.2.4.110",
+                // High-Throughput JPEG 2000 Image Compression (Lossless Only)
+                "1.2.840.10008.1.2.4.201",
+                // JPEG 2000 Image Compression (Lossless Only)
+                "1.2.840.10008.1.2.4.90",
+                // JPEG Lossless, Non-Hierarchical, First-Order Prediction
+                "1.2.840.10008.1.2.4.70",
+                // RLE Lossless
+                "1.2.840.10008.1.2.5",
+                // Explicit VR Little Endian
+                "1.2.840.10008.1.2.1",
+            ],
+            EncodingProfile::LossyWeb => &[
+                // JPEG Baseline (Process 1)
+                "1.2.840.10008.1.2.4.50",
+                // JPEG Extended (Process 2 & 4)
+                "1.2.840.10008.1.2.4.51",
+                // High-Throughput JPEG 2000 Image Compression
+                "1.2.840.10008.1.2.4.203",
+                // JPEG 2000 Image Compression
+                "1.2.840.10008.1.2.4.91",
+                // JPEG XL
+                "1.2.840.10008.1.2.4.112",
+                // Explicit VR Little Endian
+                "1.2.840.10008.1.2.1",
+            ],
+        }
+    }
+}
+
+/// Select the first transfer syntax in `preferences`
+/// which is registered in the given index
+/// and can be used to encode pixel data with the given properties.
+///
+/// A transfer syntax is considered workable if:
+/// - it has native pixel data and no data set codec;
+/// - it has a data set codec which is available;
+/// - or it has a pixel data encoder available,
+///   which reports the pixel data as
+///   [supported](SupportLevel::Supported),
+///   or [partially supported](SupportLevel::Partial)
+///   after a few conversions.
+///
+/// Returns `None` if no transfer syntax in the list is workable.
+pub fn select_encoder<'a, I>(
+    index: &'a I,
+    preferences: &[&str],
+    source_props: &EncodeSourceProperties,
+) -> Option<&'a TransferSyntax>
+where
+    I: TransferSyntaxIndex + ?Sized,
+{
+    preferences
+        .iter()
+        .filter_map(|uid| index.get(uid))
+        .find(|ts| can_encode(ts, source_props))
+}
+
+/// Check whether the given transfer syntax can be used
+/// to encode pixel data with the given properties.
+fn can_encode(ts: &TransferSyntax, source_props: &EncodeSourceProperties) -> bool {
+    match ts.codec() {
+        Codec::None => true,
+        Codec::Dataset(adapter) => adapter.is_some(),
+        Codec::EncapsulatedPixelData(_, Some(writer)) => {
+            !matches!(writer.supports(source_props), SupportLevel::Unsupported(_))
+        }
+        Codec::EncapsulatedPixelData(_, None) => false,
+    }
+}
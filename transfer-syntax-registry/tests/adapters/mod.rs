@@ -2,12 +2,13 @@
 use std::borrow::Cow;
 
 use dicom_core::value::{InMemFragment, PixelFragmentSequence};
-use dicom_encoding::adapters::{PixelDataObject, RawPixelData};
+use dicom_encoding::adapters::{EncodeSourceProperties, PixelDataObject, RawPixelData};
 
 /// A test data object.
 ///
 /// Can be used to test pixel data adapters
 /// without having to open a real DICOM file using `dicom_object`.
+#[allow(dead_code)]
 #[derive(Debug)]
 pub(crate) struct TestDataObject {
     pub ts_uid: String,
@@ -106,3 +107,22 @@ impl PixelDataObject for TestDataObject {
         }
     }
 }
+
+/// Describe a 64x64 image to be encoded,
+/// for querying the support of a pixel data writer.
+#[allow(dead_code)]
+pub(crate) fn source_properties(
+    bits_allocated: u16,
+    bits_stored: u16,
+    samples_per_pixel: u16,
+    photometric_interpretation: &str,
+) -> EncodeSourceProperties {
+    EncodeSourceProperties {
+        rows: 64,
+        cols: 64,
+        samples_per_pixel,
+        bits_allocated,
+        bits_stored,
+        photometric_interpretation: photometric_interpretation.to_string(),
+    }
+}
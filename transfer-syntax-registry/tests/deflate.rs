@@ -0,0 +1,49 @@
+//! Test suite for Deflated Explicit VR Little Endian data set adaptation
+#![cfg(feature = "deflate")]
+
+use std::io::{Cursor, Read};
+
+use dicom_encoding::transfer_syntax::{adapt_bytes, TransferSyntaxIndex};
+use dicom_encoding::Codec;
+use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
+
+static DEFLATED_EXPLICIT_VR_LE: &str = "1.2.840.10008.1.2.1.99";
+
+#[test]
+fn deflated_ts_is_fully_supported() {
+    let ts = TransferSyntaxRegistry
+        .get(DEFLATED_EXPLICIT_VR_LE)
+        .expect("Registry did not provide the deflated TS");
+    assert_eq!(ts.name(), "Deflated Explicit VR Little Endian");
+    assert!(ts.is_fully_supported());
+    assert!(ts.can_decode_dataset());
+    assert!(!ts.is_unsupported());
+}
+
+#[test]
+fn deflate_and_inflate_data_set() {
+    let ts = TransferSyntaxRegistry.get(DEFLATED_EXPLICIT_VR_LE).unwrap();
+    let Codec::Dataset(Some(adapter)) = ts.codec() else {
+        panic!("deflated TS should have a data set adapter");
+    };
+
+    // (0010,0010) PN "Doe^John", then a long run of zeros
+    let mut data = b"\x10\x00\x10\x00PN\x08\x00Doe^John".to_vec();
+    data.extend(b"\x09\x10\x10\x00OB\x00\x00\x00\x40\x00\x00");
+    data.resize(data.len() + 0x4000, 0);
+
+    let deflated = adapt_bytes(adapter, &data).unwrap();
+    assert!(!deflated.is_empty());
+    assert!(
+        deflated.len() < data.len() / 10,
+        "deflated data set is too large ({} bytes)",
+        deflated.len()
+    );
+
+    let mut inflated = Vec::new();
+    adapter
+        .adapt_reader(Box::new(Cursor::new(deflated)))
+        .read_to_end(&mut inflated)
+        .unwrap();
+    assert_eq!(inflated, data);
+}
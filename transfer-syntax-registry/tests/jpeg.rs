@@ -9,10 +9,13 @@ use std::{
     path::Path,
 };
 
-use adapters::TestDataObject;
+use adapters::{source_properties, TestDataObject};
 use dicom_core::value::PixelFragmentSequence;
 use dicom_encoding::{
-    adapters::{EncodeOptions, PixelDataReader, PixelDataWriter},
+    adapters::{
+        EncodeConversion, EncodeOptions, EncodeProperty, EncodeSourceProperties,
+        PixelDataReader, PixelDataWriter, SupportLevel,
+    },
     Codec,
 };
 use dicom_transfer_syntax_registry::entries::JPEG_BASELINE;
@@ -313,3 +316,181 @@ fn test_decode_jpeg_trailing_bytes() {
 
     assert_eq!(dest.len(), 30_000);
 }
+
+/// Synthesize a grayscale JPEG baseline stream
+/// with a restart interval of one MCU
+/// and an application segment containing marker-like bytes,
+/// returning the source samples and the encoded stream.
+fn synthesize_jpeg(columns: u16, rows: u16, frame: u8) -> (Vec<u8>, Vec<u8>) {
+    let samples: Vec<u8> = (0..rows)
+        .flat_map(|y| (0..columns).map(move |x| (x * 3 + y * 2) as u8 + frame * 40))
+        .collect();
+
+    let mut encoded = vec![];
+    let mut encoder = jpeg_encoder::Encoder::new(&mut encoded, 95);
+    encoder.set_restart_interval(1);
+    encoder
+        .encode(&samples, columns, rows, jpeg_encoder::ColorType::Luma)
+        .expect("JPEG encoding failed");
+
+    // insert an APP1 segment right after SOI,
+    // with bytes resembling the EOI and SOI markers
+    let payload = [0xFF, 0xD9, 0xFF, 0xD8, 0xFF, 0xD9, 0x00, 0x00];
+    let mut stream = encoded[..2].to_vec();
+    stream.extend_from_slice(&[0xFF, 0xE1]);
+    stream.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+    stream.extend_from_slice(&payload);
+    stream.extend_from_slice(&encoded[2..]);
+
+    // restart markers were written
+    assert!(stream.windows(2).any(|w| w == [0xFF, 0xDD]));
+    assert!(stream.windows(2).any(|w| w == [0xFF, 0xD0]));
+
+    (samples, stream)
+}
+
+fn check_samples_approx(decoded: &[u8], expected: &[u8], margin: u8) {
+    assert_eq!(decoded.len(), expected.len(), "pixel data length mismatch");
+    for (i, (&got, &expected)) in decoded.iter().zip(expected).enumerate() {
+        assert!(
+            got.abs_diff(expected) <= margin,
+            "pixel sample mismatch at #{}: {} vs {}",
+            i,
+            got,
+            expected
+        );
+    }
+}
+
+/// multi-frame JPEG streams with APP segments, restart markers
+/// and junk after each EOI marker are decoded frame by frame
+#[test]
+fn test_decode_jpeg_app_segments_restart_markers_and_junk() {
+    // not a multiple of the MCU size
+    let rows: u16 = 20;
+    let columns: u16 = 36;
+    let nr_frames = 3;
+
+    let junk = [0x12, 0x34, 0xFF, 0x00, 0xFF, 0xD9];
+
+    let mut all_samples = vec![];
+    let mut fragments = vec![];
+    for frame in 0..nr_frames {
+        let (samples, mut encoded) = synthesize_jpeg(columns, rows, frame);
+        encoded.extend_from_slice(&junk);
+        all_samples.extend(samples);
+        fragments.push(encoded);
+    }
+
+    let obj = |fragments: Vec<Vec<u8>>| TestDataObject {
+        // JPEG baseline (Process 1)
+        ts_uid: "1.2.840.10008.1.2.4.50".to_string(),
+        rows,
+        columns,
+        bits_allocated: 8,
+        bits_stored: 8,
+        samples_per_pixel: 1,
+        photometric_interpretation: "MONOCHROME2",
+        number_of_frames: nr_frames as u32,
+        flat_pixel_data: None,
+        pixel_data_sequence: Some(PixelFragmentSequence::new(vec![], fragments)),
+    };
+
+    let Codec::EncapsulatedPixelData(Some(adapter), _) = JPEG_BASELINE.codec() else {
+        panic!("JPEG pixel data reader not found")
+    };
+
+    let frame_size = rows as usize * columns as usize;
+    let err_margin = 6;
+
+    // one fragment per frame
+    let obj_fragments = obj(fragments.clone());
+
+    let mut dest = vec![];
+    adapter
+        .decode(&obj_fragments, &mut dest)
+        .expect("JPEG decoding failed");
+    check_samples_approx(&dest, &all_samples, err_margin);
+
+    for frame in 0..nr_frames as usize {
+        let mut dest = vec![];
+        adapter
+            .decode_frame(&obj_fragments, frame as u32, &mut dest)
+            .expect("JPEG frame decoding failed");
+        check_samples_approx(
+            &dest,
+            &all_samples[frame * frame_size..(frame + 1) * frame_size],
+            err_margin,
+        );
+    }
+
+    // all frames in a single fragment
+    let obj_single = obj(vec![fragments.concat()]);
+
+    let mut dest = vec![];
+    adapter
+        .decode(&obj_single, &mut dest)
+        .expect("JPEG decoding failed");
+    check_samples_approx(&dest, &all_samples, err_margin);
+}
+
+/// the JPEG baseline encoder reports which pixel data it can encode
+#[test]
+fn jpeg_baseline_encoder_capabilities() {
+    let Codec::EncapsulatedPixelData(_, Some(writer)) = JPEG_BASELINE.codec() else {
+        panic!("JPEG pixel data writer not found")
+    };
+
+    // 8-bit monochrome and RGB are supported as is
+    assert_eq!(
+        writer.supports(&source_properties(8, 8, 1, "MONOCHROME2")),
+        SupportLevel::Supported
+    );
+    assert_eq!(
+        writer.supports(&source_properties(8, 8, 1, "MONOCHROME1")),
+        SupportLevel::Supported
+    );
+    assert_eq!(
+        writer.supports(&source_properties(8, 8, 3, "RGB")),
+        SupportLevel::Supported
+    );
+
+    // 16-bit samples need to be reduced to 8 bits
+    assert_eq!(
+        writer.supports(&source_properties(16, 12, 1, "MONOCHROME2")),
+        SupportLevel::Partial(vec![EncodeConversion::ReduceTo8Bit])
+    );
+    assert_eq!(
+        writer.supports(&source_properties(16, 16, 3, "RGB")),
+        SupportLevel::Partial(vec![EncodeConversion::ReduceTo8Bit])
+    );
+
+    // unsupported
+    assert_eq!(
+        writer.supports(&source_properties(32, 32, 1, "MONOCHROME2")),
+        SupportLevel::Unsupported(EncodeProperty::BitsAllocated)
+    );
+    assert_eq!(
+        writer.supports(&source_properties(8, 1, 1, "MONOCHROME2")),
+        SupportLevel::Unsupported(EncodeProperty::BitsStored)
+    );
+    assert_eq!(
+        writer.supports(&source_properties(8, 8, 4, "ARGB")),
+        SupportLevel::Unsupported(EncodeProperty::SamplesPerPixel)
+    );
+    assert_eq!(
+        writer.supports(&source_properties(8, 8, 1, "PALETTE COLOR")),
+        SupportLevel::Unsupported(EncodeProperty::PhotometricInterpretation)
+    );
+    assert_eq!(
+        writer.supports(&source_properties(8, 8, 3, "YBR_FULL")),
+        SupportLevel::Unsupported(EncodeProperty::PhotometricInterpretation)
+    );
+    assert_eq!(
+        writer.supports(&EncodeSourceProperties {
+            rows: 0,
+            ..source_properties(8, 8, 1, "MONOCHROME2")
+        }),
+        SupportLevel::Unsupported(EncodeProperty::FrameSize)
+    );
+}
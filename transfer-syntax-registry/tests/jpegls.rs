@@ -1,5 +1,5 @@
 //! Test suite for JPEG-LS pixel data reading and writing
-#![cfg(feature = "charls")]
+#![cfg(any(feature = "charls", feature = "jpegls-rust"))]
 
 mod adapters;
 
@@ -9,12 +9,15 @@ use std::{
     path::Path,
 };
 
+#[cfg(feature = "charls")]
+use adapters::source_properties;
 use adapters::TestDataObject;
 use dicom_core::value::PixelFragmentSequence;
-use dicom_encoding::{
-    adapters::{EncodeOptions, PixelDataReader, PixelDataWriter},
-    Codec,
+#[cfg(feature = "charls")]
+use dicom_encoding::adapters::{
+    EncodeOptions, EncodeProperty, EncodeSourceProperties, PixelDataWriter, SupportLevel,
 };
+use dicom_encoding::{adapters::PixelDataReader, Codec};
 use dicom_transfer_syntax_registry::entries::{
     JPEG_LS_LOSSLESS_IMAGE_COMPRESSION, JPEG_LS_LOSSY_IMAGE_COMPRESSION,
 };
@@ -169,6 +172,7 @@ fn read_jpeg_ls_lossless_1() {
 }
 
 /// writing to JPEG-LS and back should yield approximately the same pixel data
+#[cfg(feature = "charls")]
 #[test]
 fn write_and_read_jpeg_ls() {
     let rows: u16 = 256;
@@ -271,3 +275,149 @@ fn write_and_read_jpeg_ls() {
         );
     }
 }
+
+/// an explicit near-lossless error range is honored by the lossy encoder
+/// and ignored by the lossless encoder
+#[cfg(feature = "charls")]
+#[test]
+fn write_jpeg_ls_near_lossless() {
+    use dicom_core::{ops::AttributeAction, Tag};
+
+    let rows: u16 = 64;
+    let columns: u16 = 64;
+
+    // a gradient with some ripples
+    let samples: Vec<u8> = (0..rows as usize * columns as usize)
+        .map(|i| ((i % columns as usize) * 3 + (i * 7919) % 13) as u8)
+        .collect();
+
+    let obj = TestDataObject {
+        // Explicit VR Little Endian
+        ts_uid: "1.2.840.10008.1.2.1".to_string(),
+        rows,
+        columns,
+        bits_allocated: 8,
+        bits_stored: 8,
+        samples_per_pixel: 1,
+        photometric_interpretation: "MONOCHROME2",
+        number_of_frames: 1,
+        flat_pixel_data: Some(samples.clone()),
+        pixel_data_sequence: None,
+    };
+
+    let mut options = EncodeOptions::default();
+    // would have been lossless
+    options.quality = Some(100);
+    options.near_lossless = Some(3);
+
+    for (ts, max_error) in [
+        (JPEG_LS_LOSSY_IMAGE_COMPRESSION.erased(), 3),
+        (JPEG_LS_LOSSLESS_IMAGE_COMPRESSION.erased(), 0),
+    ] {
+        let Codec::EncapsulatedPixelData(Some(reader), Some(writer)) = ts.codec() else {
+            panic!("JPEG-LS pixel data adapters not found")
+        };
+
+        let mut encoded = vec![];
+        let ops = writer
+            .encode_frame(&obj, 0, options.clone(), &mut encoded)
+            .expect("JPEG-LS frame encoding failed");
+
+        // lossy image compression is declared accordingly
+        let lossy = ops
+            .iter()
+            .find(|op| op.selector == Tag(0x0028, 0x2110).into())
+            .map(|op| op.action.clone());
+        let has_ratio = ops
+            .iter()
+            .any(|op| op.selector == Tag(0x0028, 0x2112).into());
+        if max_error > 0 {
+            assert_eq!(lossy, Some(AttributeAction::SetStr("01".into())));
+            assert!(has_ratio);
+        } else {
+            assert_eq!(lossy, Some(AttributeAction::SetIfMissing("00".into())));
+            assert!(!has_ratio);
+        }
+
+        let encoded_obj = TestDataObject {
+            ts_uid: ts.uid().to_string(),
+            rows,
+            columns,
+            bits_allocated: 8,
+            bits_stored: 8,
+            samples_per_pixel: 1,
+            photometric_interpretation: "MONOCHROME2",
+            number_of_frames: 1,
+            flat_pixel_data: None,
+            pixel_data_sequence: Some(PixelFragmentSequence::new(vec![], vec![encoded])),
+        };
+        let mut decoded = vec![];
+        reader
+            .decode_frame(&encoded_obj, 0, &mut decoded)
+            .expect("JPEG-LS frame decoding failed");
+
+        assert_eq!(samples.len(), decoded.len(), "pixel data length mismatch");
+        for (src_sample, decoded_sample) in samples.iter().zip(&decoded) {
+            assert!(
+                src_sample.abs_diff(*decoded_sample) <= max_error,
+                "pixel sample mismatch: {} vs {}",
+                src_sample,
+                decoded_sample
+            );
+        }
+    }
+}
+
+/// the JPEG-LS encoders report which pixel data they can encode
+#[cfg(feature = "charls")]
+#[test]
+fn jpeg_ls_encoder_capabilities() {
+    for ts in [JPEG_LS_LOSSLESS_IMAGE_COMPRESSION.erased(), JPEG_LS_LOSSY_IMAGE_COMPRESSION.erased()] {
+        let Codec::EncapsulatedPixelData(_, Some(writer)) = ts.codec() else {
+            panic!("JPEG-LS pixel data writer not found")
+        };
+
+        // supported
+        for props in [
+            source_properties(8, 8, 1, "MONOCHROME2"),
+            source_properties(16, 12, 1, "MONOCHROME1"),
+            source_properties(16, 16, 1, "MONOCHROME2"),
+            source_properties(8, 8, 1, "PALETTE COLOR"),
+            source_properties(8, 8, 3, "RGB"),
+            source_properties(16, 16, 3, "RGB"),
+            source_properties(8, 2, 1, "MONOCHROME2"),
+        ] {
+            assert_eq!(
+                writer.supports(&props),
+                SupportLevel::Supported,
+                "{:?} should be supported",
+                props
+            );
+        }
+
+        // unsupported
+        assert_eq!(
+            writer.supports(&source_properties(32, 32, 1, "MONOCHROME2")),
+            SupportLevel::Unsupported(EncodeProperty::BitsAllocated)
+        );
+        assert_eq!(
+            writer.supports(&source_properties(8, 1, 1, "MONOCHROME2")),
+            SupportLevel::Unsupported(EncodeProperty::BitsStored)
+        );
+        assert_eq!(
+            writer.supports(&source_properties(8, 8, 2, "MONOCHROME2")),
+            SupportLevel::Unsupported(EncodeProperty::SamplesPerPixel)
+        );
+        assert_eq!(
+            writer.supports(&source_properties(8, 8, 3, "YBR_FULL")),
+            SupportLevel::Unsupported(EncodeProperty::PhotometricInterpretation)
+        );
+        assert_eq!(
+            writer.supports(&EncodeSourceProperties {
+                cols: 0,
+                ..source_properties(8, 8, 1, "MONOCHROME2")
+            }),
+            SupportLevel::Unsupported(EncodeProperty::FrameSize)
+        );
+    }
+}
@@ -9,10 +9,13 @@ use std::{
     path::Path,
 };
 
-use adapters::TestDataObject;
+use adapters::{source_properties, TestDataObject};
 use dicom_core::value::PixelFragmentSequence;
 use dicom_encoding::{
-    adapters::{EncodeOptions, PixelDataReader, PixelDataWriter},
+    adapters::{
+        EncodeOptions, EncodeProperty, EncodeSourceProperties, PixelDataReader,
+        PixelDataWriter, SupportLevel,
+    },
     Codec,
 };
 use dicom_transfer_syntax_registry::entries::{JPEG_XL, JPEG_XL_LOSSLESS};
@@ -256,3 +259,56 @@ fn write_and_read_jpeg_xl() {
     // compare pixels, lossless encoding should yield exactly the same data 
     assert_eq!(samples, decoded , "pixel data mismatch");
 }
+
+/// the JPEG XL encoders report which pixel data they can encode
+#[test]
+fn jpeg_xl_encoder_capabilities() {
+    for ts in [JPEG_XL_LOSSLESS.erased(), JPEG_XL.erased()] {
+        let Codec::EncapsulatedPixelData(_, Some(writer)) = ts.codec() else {
+            panic!("JPEG XL pixel data writer not found")
+        };
+
+        // supported
+        for props in [
+            source_properties(8, 8, 1, "MONOCHROME2"),
+            source_properties(16, 12, 1, "MONOCHROME1"),
+            source_properties(16, 16, 1, "MONOCHROME2"),
+            source_properties(8, 8, 1, "PALETTE COLOR"),
+            source_properties(8, 8, 3, "RGB"),
+            source_properties(16, 16, 3, "RGB"),
+            source_properties(8, 1, 1, "MONOCHROME2"),
+        ] {
+            assert_eq!(
+                writer.supports(&props),
+                SupportLevel::Supported,
+                "{:?} should be supported",
+                props
+            );
+        }
+
+        // unsupported
+        assert_eq!(
+            writer.supports(&source_properties(32, 32, 1, "MONOCHROME2")),
+            SupportLevel::Unsupported(EncodeProperty::BitsAllocated)
+        );
+        assert_eq!(
+            writer.supports(&source_properties(8, 0, 1, "MONOCHROME2")),
+            SupportLevel::Unsupported(EncodeProperty::BitsStored)
+        );
+        assert_eq!(
+            writer.supports(&source_properties(8, 8, 2, "MONOCHROME2")),
+            SupportLevel::Unsupported(EncodeProperty::SamplesPerPixel)
+        );
+        assert_eq!(
+            writer.supports(&source_properties(8, 8, 3, "YBR_FULL")),
+            SupportLevel::Unsupported(EncodeProperty::PhotometricInterpretation)
+        );
+        assert_eq!(
+            writer.supports(&EncodeSourceProperties {
+                cols: 0,
+                ..source_properties(8, 8, 1, "MONOCHROME2")
+            }),
+            SupportLevel::Unsupported(EncodeProperty::FrameSize)
+        );
+    }
+}
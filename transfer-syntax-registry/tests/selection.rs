@@ -0,0 +1,178 @@
+//! Test suite for the selection of a target transfer syntax for encoding.
+mod adapters;
+
+use std::collections::HashMap;
+
+use adapters::source_properties;
+use dicom_core::ops::AttributeOp;
+use dicom_encoding::adapters::{
+    EncodeError, EncodeOptions, EncodeProperty, EncodeResult, EncodeSourceProperties,
+    NeverPixelAdapter, PixelDataObject, PixelDataWriter, SupportLevel,
+};
+use dicom_encoding::transfer_syntax::{Codec, NeverAdapter, TransferSyntaxIndex};
+use dicom_encoding::TransferSyntax;
+use dicom_transfer_syntax_registry::entries::EXPLICIT_VR_LITTLE_ENDIAN;
+use dicom_transfer_syntax_registry::selection::{select_encoder, EncodingProfile};
+
+/// A pixel data writer which only supports up to a certain bit depth
+#[derive(Debug)]
+struct MockWriter {
+    max_bits_allocated: u16,
+}
+
+impl PixelDataWriter for MockWriter {
+    fn supports(&self, props: &EncodeSourceProperties) -> SupportLevel {
+        if props.bits_allocated > self.max_bits_allocated {
+            SupportLevel::Unsupported(EncodeProperty::BitsAllocated)
+        } else {
+            SupportLevel::Supported
+        }
+    }
+
+    fn encode_frame(
+        &self,
+        _src: &dyn PixelDataObject,
+        _frame: u32,
+        _options: EncodeOptions,
+        _dst: &mut Vec<u8>,
+    ) -> EncodeResult<Vec<AttributeOp>> {
+        Err(EncodeError::Custom {
+            message: "mock writer does not encode pixel data".to_string(),
+            source: None,
+        })
+    }
+}
+
+/// A transfer syntax index with a custom set of transfer syntaxes,
+/// simulating different combinations of enabled features
+struct MockRegistry(HashMap<&'static str, TransferSyntax>);
+
+impl MockRegistry {
+    fn new() -> Self {
+        let mut m = HashMap::new();
+        m.insert("1.2.840.10008.1.2.1", EXPLICIT_VR_LITTLE_ENDIAN.erased());
+        MockRegistry(m)
+    }
+
+    /// register a transfer syntax with a pixel data encoder
+    /// for up to `max_bits_allocated` bits
+    fn with_encoder(mut self, uid: &'static str, max_bits_allocated: u16) -> Self {
+        let ts = TransferSyntax::<NeverAdapter, NeverPixelAdapter, _>::new_ele(
+            uid,
+            "Mock",
+            Codec::EncapsulatedPixelData(None, Some(MockWriter { max_bits_allocated })),
+        );
+        self.0.insert(uid, ts.erased());
+        self
+    }
+
+    /// register a transfer syntax stub without a pixel data encoder
+    fn with_stub(mut self, uid: &'static str) -> Self {
+        let ts = TransferSyntax::<NeverAdapter, NeverPixelAdapter, NeverPixelAdapter>::new_ele(
+            uid,
+            "Mock",
+            Codec::EncapsulatedPixelData(None, None),
+        );
+        self.0.insert(uid, ts.erased());
+        self
+    }
+}
+
+impl TransferSyntaxIndex for MockRegistry {
+    fn get(&self, uid: &str) -> Option<&TransferSyntax> {
+        self.0.get(uid)
+    }
+}
+
+/// Describe a 64x64 monochrome image to be encoded
+fn monochrome(bits_allocated: u16) -> EncodeSourceProperties {
+    source_properties(bits_allocated, bits_allocated, 1, "MONOCHROME2")
+}
+
+const JPEG_BASELINE: &str = "1.2.840.10008.1.2.4.50";
+const JPEG_EXTENDED: &str = "1.2.840.10008.1.2.4.51";
+const JPEG_2000_LOSSLESS: &str = "1.2.840.10008.1.2.4.90";
+const HTJ2K_LOSSLESS: &str = "1.2.840.10008.1.2.4.201";
+
+#[test]
+fn selects_first_available_encoder() {
+    // only HTJ2K is available
+    let registry = MockRegistry::new()
+        .with_stub(JPEG_2000_LOSSLESS)
+        .with_encoder(HTJ2K_LOSSLESS, 16);
+
+    let ts = select_encoder(
+        &registry,
+        &[JPEG_2000_LOSSLESS, HTJ2K_LOSSLESS],
+        &monochrome(16),
+    )
+    .unwrap();
+    assert_eq!(ts.uid(), HTJ2K_LOSSLESS);
+
+    // both are available
+    let registry = registry.with_encoder(JPEG_2000_LOSSLESS, 16);
+    let ts = select_encoder(
+        &registry,
+        &[JPEG_2000_LOSSLESS, HTJ2K_LOSSLESS],
+        &monochrome(16),
+    )
+    .unwrap();
+    assert_eq!(ts.uid(), JPEG_2000_LOSSLESS);
+}
+
+#[test]
+fn selects_by_encoder_capability() {
+    // JPEG Extended is not available,
+    // JPEG Baseline only takes 8-bit data
+    let registry = MockRegistry::new().with_encoder(JPEG_BASELINE, 8);
+    let preferences = [JPEG_EXTENDED, JPEG_BASELINE];
+
+    let ts = select_encoder(&registry, &preferences, &monochrome(8)).unwrap();
+    assert_eq!(ts.uid(), JPEG_BASELINE);
+
+    assert!(select_encoder(&registry, &preferences, &monochrome(16)).is_none());
+
+    // unregistered transfer syntaxes are skipped
+    let registry = MockRegistry::new();
+    assert!(select_encoder(&registry, &preferences, &monochrome(8)).is_none());
+}
+
+#[test]
+fn profiles_fall_back_to_uncompressed() {
+    // no encoders at all
+    let registry = MockRegistry::new().with_stub(JPEG_BASELINE);
+
+    for profile in [EncodingProfile::LosslessArchive, EncodingProfile::LossyWeb] {
+        let ts = select_encoder(&registry, profile.preferences(), &monochrome(16)).unwrap();
+        assert_eq!(ts.uid(), "1.2.840.10008.1.2.1");
+    }
+
+    // lossy profile prefers JPEG baseline over uncompressed
+    let registry = registry.with_encoder(JPEG_BASELINE, 8);
+    let ts = select_encoder(
+        &registry,
+        EncodingProfile::LossyWeb.preferences(),
+        &monochrome(8),
+    )
+    .unwrap();
+    assert_eq!(ts.uid(), JPEG_BASELINE);
+
+    // lossless profile does not include JPEG baseline
+    let ts = select_encoder(
+        &registry,
+        EncodingProfile::LosslessArchive.preferences(),
+        &monochrome(8),
+    )
+    .unwrap();
+    assert_eq!(ts.uid(), "1.2.840.10008.1.2.1");
+
+    // but takes JPEG 2000 lossless if available
+    let registry = registry.with_encoder(JPEG_2000_LOSSLESS, 16);
+    let ts = select_encoder(
+        &registry,
+        EncodingProfile::LosslessArchive.preferences(),
+        &monochrome(16),
+    )
+    .unwrap();
+    assert_eq!(ts.uid(), JPEG_2000_LOSSLESS);
+}
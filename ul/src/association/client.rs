@@ -9,7 +9,7 @@ use std::{
     borrow::Cow,
     convert::TryInto,
     io::{BufRead, BufReader, Cursor, Read, Write},
-    net::{TcpStream, ToSocketAddrs},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
     time::Duration,
 };
 
@@ -26,10 +26,16 @@ use snafu::{ensure, Backtrace, ResultExt, Snafu};
 
 use bytes::Buf;
 
+#[cfg(feature = "tls")]
+use super::tls::{self, rustls};
 use super::{
-    pdata::{PDataReader, PDataWriter},
+    negotiation::request_relational_queries,
+    pdata::{dataset_adapter, dataset_adapters, PDataReader, PDataWriter},
     uid::trim_uid,
+    wire_log::{WireLog, WireTap},
 };
+#[cfg(feature = "tls")]
+use std::sync::Arc;
 
 #[derive(Debug, Snafu)]
 #[non_exhaustive]
@@ -49,6 +55,27 @@ pub enum Error {
         backtrace: Backtrace,
     },
 
+    /// could not create socket
+    CreateSocket {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    /// could not bind socket to local address {address}
+    Bind {
+        address: SocketAddr,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    /// could not bind socket to network interface `{device}`
+    #[cfg(all(feature = "bind-device", target_os = "linux"))]
+    BindDevice {
+        device: String,
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
     /// Could not set tcp read timeout
     SetReadTimeout {
         source: std::io::Error,
@@ -133,6 +160,19 @@ pub enum Error {
 
     #[snafu(display("Connection closed by peer"))]
     ConnectionClosed,
+
+    /// failed to take the underlying TCP stream
+    TakeStream {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+
+    /// could not establish TLS connection
+    #[cfg(feature = "tls")]
+    Tls {
+        #[snafu(backtrace)]
+        source: super::tls::TlsError,
+    },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -178,6 +218,45 @@ where
     Ok(msg)
 }
 
+/// Check whether the remote address has the same IP version
+/// as the local address to bind to, if any.
+fn matches_ip_version(address: &SocketAddr, bind_address: Option<SocketAddr>) -> bool {
+    bind_address
+        .map(|bind_address| bind_address.is_ipv4() == address.is_ipv4())
+        .unwrap_or(true)
+}
+
+/// Create a TCP socket for connecting to the given remote address,
+/// bound to the given local address and/or network interface.
+fn bound_socket(
+    address: &SocketAddr,
+    bind_address: Option<SocketAddr>,
+    bind_device: Option<&str>,
+) -> Result<socket2::Socket> {
+    let socket = socket2::Socket::new(
+        socket2::Domain::for_address(*address),
+        socket2::Type::STREAM,
+        Some(socket2::Protocol::TCP),
+    )
+    .context(CreateSocketSnafu)?;
+
+    #[cfg(all(feature = "bind-device", target_os = "linux"))]
+    if let Some(device) = bind_device {
+        socket
+            .bind_device(Some(device.as_bytes()))
+            .context(BindDeviceSnafu { device })?;
+    }
+    #[cfg(not(all(feature = "bind-device", target_os = "linux")))]
+    let _ = bind_device;
+
+    if let Some(bind_address) = bind_address {
+        socket.bind(&bind_address.into()).context(BindSnafu {
+            address: bind_address,
+        })?;
+    }
+    Ok(socket)
+}
+
 /// A DICOM association builder for a client node.
 /// The final outcome is a [`ClientAssociation`].
 ///
@@ -275,12 +354,20 @@ pub struct ClientAssociationOptions<'a> {
     saml_assertion: Option<Cow<'a, str>>,
     /// User identity JWT
     jwt: Option<Cow<'a, str>>,
+    /// whether to request relational queries for query/retrieve SOP classes
+    relational_queries: bool,
+    /// local socket address to bind to before connecting
+    bind_address: Option<SocketAddr>,
+    /// network interface to bind to before connecting
+    bind_device: Option<Cow<'a, str>>,
     /// TCP read timeout
     read_timeout: Option<Duration>,
     /// TCP write timeout
     write_timeout: Option<Duration>,
     /// TCP connection timeout
     connection_timeout: Option<Duration>,
+    /// where to capture the raw bytes of the association
+    wire_log: Option<WireLog>,
 }
 
 impl Default for ClientAssociationOptions<'_> {
@@ -302,9 +389,13 @@ impl Default for ClientAssociationOptions<'_> {
             kerberos_service_ticket: None,
             saml_assertion: None,
             jwt: None,
+            relational_queries: false,
+            bind_address: None,
+            bind_device: None,
             read_timeout: None,
             write_timeout: None,
             connection_timeout: None,
+            wire_log: None,
         }
     }
 }
@@ -501,6 +592,19 @@ impl<'a> ClientAssociationOptions<'a> {
         self
     }
 
+    /// Override whether to request relational queries
+    /// for the proposed query/retrieve SOP classes
+    /// through SOP class extended negotiation.
+    ///
+    /// Whether the acceptor granted them can be checked afterwards with
+    /// [`RelationalQuerySupport::from_user_variables`](super::RelationalQuerySupport::from_user_variables)
+    /// on the association's [user variables](ClientAssociation::user_variables).
+    /// The default is `false`.
+    pub fn relational_queries(mut self, relational_queries: bool) -> Self {
+        self.relational_queries = relational_queries;
+        self
+    }
+
     /// Initiate the TCP connection to the given address
     /// and request a new DICOM association,
     /// negotiating the presentation contexts in the process.
@@ -545,6 +649,31 @@ impl<'a> ClientAssociationOptions<'a> {
         }
     }
 
+    /// Initiate the TCP connection to the given address,
+    /// perform a TLS handshake on top of it,
+    /// and request a new DICOM association,
+    /// negotiating the presentation contexts in the process.
+    ///
+    /// The certificate presented by the association acceptor
+    /// is verified against `server_name`
+    /// as per the given client configuration.
+    /// A failed handshake results in [`Error::Tls`].
+    /// See the [`tls`](super::tls) module for more details.
+    ///
+    /// Requires the `tls` Cargo feature.
+    #[cfg(feature = "tls")]
+    pub fn establish_tls<A: ToSocketAddrs>(
+        self,
+        address: A,
+        config: Arc<rustls::ClientConfig>,
+        server_name: &str,
+    ) -> Result<ClientAssociation<tls::ClientTlsStream>> {
+        let server_name = tls::server_name(server_name).context(TlsSnafu)?;
+        let socket = self.connect(&AeAddr::new_socket_addr(address))?;
+        let socket = tls::connect(socket, config, server_name).context(TlsSnafu)?;
+        self.negotiate(socket, None)
+    }
+
     /// Set the read timeout for the underlying TCP socket
     ///
     /// This is used to set both the read and write timeout.
@@ -571,12 +700,143 @@ impl<'a> ClientAssociationOptions<'a> {
         }
     }
 
+    /// Bind the underlying TCP socket to the given local address
+    /// before connecting,
+    /// so that the connection originates from a specific IP address
+    /// and/or port.
+    /// A port number of 0 lets the operating system pick the port.
+    ///
+    /// Only remote addresses of the same IP version are tried.
+    /// A failure to bind results in [`Error::Bind`],
+    /// distinct from connection failures.
+    /// The local address in use can be retrieved
+    /// from the established association.
+    pub fn bind_address(self, address: SocketAddr) -> Self {
+        Self {
+            bind_address: Some(address),
+            ..self
+        }
+    }
+
+    /// Capture all raw bytes sent and received through the association
+    /// into the given wire log,
+    /// starting with the association request.
+    ///
+    /// See the [`wire_log`](super::wire_log) module
+    /// for the format of the log and how to read it back.
+    /// By default, nothing is captured.
+    pub fn wire_log(self, wire_log: WireLog) -> Self {
+        Self {
+            wire_log: Some(wire_log),
+            ..self
+        }
+    }
+
+    /// Bind the underlying TCP socket to the given network interface
+    /// (such as `eth0`) before connecting,
+    /// so that only packets from that interface are used.
+    ///
+    /// This requires the `bind-device` Cargo feature,
+    /// is only available on Linux,
+    /// and usually requires elevated privileges
+    /// (`CAP_NET_RAW`).
+    #[cfg(all(feature = "bind-device", target_os = "linux"))]
+    pub fn bind_device<T>(mut self, device: T) -> Self
+    where
+        T: Into<Cow<'a, str>>,
+    {
+        let device = device.into();
+        if device.is_empty() {
+            self.bind_device = None;
+        } else {
+            self.bind_device = Some(device);
+        }
+        self
+    }
+
     fn establish_impl<T>(
         self,
         ae_address: AeAddr<T>,
     ) -> Result<ClientAssociation<std::net::TcpStream>>
     where
         T: ToSocketAddrs,
+    {
+        let socket = self.connect(&ae_address)?;
+        self.negotiate(socket, ae_address.ae_title())
+    }
+
+    /// Open the TCP connection to the given address
+    /// as configured by these options.
+    fn connect<T>(&self, ae_address: &AeAddr<T>) -> Result<TcpStream>
+    where
+        T: ToSocketAddrs,
+    {
+        // fail if no presentation contexts were provided: they represent intent,
+        // should not be omitted by the user
+        ensure!(
+            !self.presentation_contexts.is_empty(),
+            MissingAbstractSyntaxSnafu
+        );
+
+        let bind_address = self.bind_address;
+        let bind_device = self.bind_device.as_deref();
+        let connection_timeout = self.connection_timeout;
+
+        let conn_result: Result<TcpStream> = if bind_address.is_some() || bind_device.is_some() {
+            let addresses = ae_address.to_socket_addrs().context(ToAddressSnafu)?;
+
+            let mut result: Result<TcpStream, std::io::Error> =
+                Result::Err(std::io::Error::from(std::io::ErrorKind::AddrNotAvailable));
+
+            for address in addresses.filter(|a| matches_ip_version(a, bind_address)) {
+                let socket = bound_socket(&address, bind_address, bind_device)?;
+                let connected = if let Some(timeout) = connection_timeout {
+                    socket.connect_timeout(&address.into(), timeout)
+                } else {
+                    socket.connect(&address.into())
+                };
+                result = connected.map(|_| socket.into());
+                if result.is_ok() {
+                    break;
+                }
+            }
+            result.context(ConnectSnafu)
+        } else if let Some(timeout) = connection_timeout {
+            let addresses = ae_address.to_socket_addrs().context(ToAddressSnafu)?;
+
+            let mut result: Result<TcpStream, std::io::Error> =
+                Result::Err(std::io::Error::from(std::io::ErrorKind::AddrNotAvailable));
+
+            for address in addresses {
+                result = std::net::TcpStream::connect_timeout(&address, timeout);
+                if result.is_ok() {
+                    break;
+                }
+            }
+            result.context(ConnectSnafu)
+        } else {
+            std::net::TcpStream::connect(ae_address).context(ConnectSnafu)
+        };
+
+        let socket = conn_result?;
+        socket
+            .set_read_timeout(self.read_timeout)
+            .context(SetReadTimeoutSnafu)?;
+        socket
+            .set_write_timeout(self.write_timeout)
+            .context(SetWriteTimeoutSnafu)?;
+        Ok(socket)
+    }
+
+    /// Request a new DICOM association over the given connection,
+    /// negotiating the presentation contexts in the process.
+    ///
+    /// `ae_title` is the called AE title which came with the address,
+    /// if any.
+    fn negotiate<S>(self, socket: S, ae_title: Option<&str>) -> Result<ClientAssociation<S>>
+    where
+        S: SyncSocket,
+        ClientAssociation<S>: Release,
     {
         let ClientAssociationOptions {
             calling_ae_title,
@@ -591,20 +851,15 @@ impl<'a> ClientAssociationOptions<'a> {
             kerberos_service_ticket,
             saml_assertion,
             jwt,
+            relational_queries,
             read_timeout,
             write_timeout,
-            connection_timeout,
+            wire_log,
+            ..
         } = self;
 
-        // fail if no presentation contexts were provided: they represent intent,
-        // should not be omitted by the user
-        ensure!(
-            !presentation_contexts.is_empty(),
-            MissingAbstractSyntaxSnafu
-        );
-
         // choose called AE title
-        let called_ae_title: &str = match (&called_ae_title, ae_address.ae_title()) {
+        let called_ae_title: &str = match (&called_ae_title, ae_title) {
             (Some(aec), Some(_)) => {
                 tracing::warn!(
                     "Option `called_ae_title` overrides the AE title to `{}`",
@@ -630,6 +885,10 @@ impl<'a> ClientAssociationOptions<'a> {
                     .collect(),
             })
             .collect();
+        let abstract_syntaxes = presentation_contexts
+            .iter()
+            .map(|pc| (pc.id, pc.abstract_syntax.clone()))
+            .collect();
 
         let mut user_variables = vec![
             UserVariableItem::MaxLength(max_pdu_length),
@@ -647,6 +906,14 @@ impl<'a> ClientAssociationOptions<'a> {
             user_variables.push(UserVariableItem::UserIdentityItem(user_identity));
         }
 
+        if relational_queries {
+            user_variables.extend(request_relational_queries(
+                presentation_contexts
+                    .iter()
+                    .map(|pc| pc.abstract_syntax.as_str()),
+            ));
+        }
+
         let msg = Pdu::AssociationRQ(AssociationRQ {
             protocol_version,
             calling_ae_title: calling_ae_title.to_string(),
@@ -656,30 +923,7 @@ impl<'a> ClientAssociationOptions<'a> {
             user_variables,
         });
 
-        let conn_result: Result<TcpStream> = if let Some(timeout) = connection_timeout {
-            let addresses = ae_address.to_socket_addrs().context(ToAddressSnafu)?;
-
-            let mut result: Result<TcpStream, std::io::Error> =
-                Result::Err(std::io::Error::from(std::io::ErrorKind::AddrNotAvailable));
-
-            for address in addresses {
-                result = std::net::TcpStream::connect_timeout(&address, timeout);
-                if result.is_ok() {
-                    break;
-                }
-            }
-            result.context(ConnectSnafu)
-        } else {
-            std::net::TcpStream::connect(ae_address).context(ConnectSnafu)
-        };
-
-        let mut socket = conn_result?;
-        socket
-            .set_read_timeout(read_timeout)
-            .context(SetReadTimeoutSnafu)?;
-        socket
-            .set_write_timeout(write_timeout)
-            .context(SetWriteTimeoutSnafu)?;
+        let mut socket = WireTap::new(socket, wire_log.clone());
         let mut buffer: Vec<u8> = Vec::with_capacity(max_pdu_length as usize);
         // send request
 
@@ -691,7 +935,7 @@ impl<'a> ClientAssociationOptions<'a> {
         // more data may live in `buf` which may be lost,
         // corrupting the PDU reader stream.
         let mut buf = BytesMut::with_capacity(MAXIMUM_PDU_SIZE as usize);
-        let msg = get_client_pdu(&mut socket, &mut buf, MAXIMUM_PDU_SIZE, self.strict)?;
+        let msg = get_client_pdu(&mut socket, &mut buf, MAXIMUM_PDU_SIZE, strict)?;
         if !buf.is_empty() {
             tracing::warn!(
                 "Received more data than expected in the first PDU, further issues may arise"
@@ -748,15 +992,18 @@ impl<'a> ClientAssociationOptions<'a> {
                 }
                 Ok(ClientAssociation {
                     presentation_contexts,
+                    abstract_syntaxes,
                     requestor_max_pdu_length: max_pdu_length,
                     acceptor_max_pdu_length,
-                    socket,
+                    socket: socket.into_inner(),
                     buffer,
                     strict,
                     read_buffer: BytesMut::with_capacity(MAXIMUM_PDU_SIZE as usize),
                     read_timeout,
                     write_timeout,
                     user_variables,
+                    wire_log,
+                    detached: false,
                 })
             }
             Pdu::AssociationRJ(association_rj) => RejectedSnafu { association_rj }.fail(),
@@ -859,12 +1106,25 @@ impl CloseSocket for std::net::TcpStream {
     }
 }
 
+/// Trait for blocking streams over which an association can be established,
+/// such as a plain TCP stream or a TLS stream on top of one.
+pub trait SyncSocket: Read + Write + CloseSocket {
+    /// Retrieve the local socket address of the underlying TCP stream.
+    fn local_addr(&self) -> std::io::Result<SocketAddr>;
+}
+
+impl SyncSocket for std::net::TcpStream {
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        std::net::TcpStream::local_addr(self)
+    }
+}
+
 /// Trait to release association
 pub trait Release {
     fn release(&mut self) -> Result<()>;
 }
 
-impl Release for ClientAssociation<std::net::TcpStream> {
+impl<S: SyncSocket> Release for ClientAssociation<S> {
     fn release(&mut self) -> Result<()> {
         self.release_impl()
     }
@@ -895,6 +1155,9 @@ where
     /// The presentation contexts accorded with the acceptor application entity,
     /// without the rejected ones.
     presentation_contexts: Vec<PresentationContextResult>,
+    /// The abstract syntax of each presentation context proposed,
+    /// by presentation context ID
+    abstract_syntaxes: Vec<(u8, String)>,
     /// The maximum PDU length that this application entity is expecting to receive
     requestor_max_pdu_length: u32,
     /// The maximum PDU length that the remote application entity accepts
@@ -913,6 +1176,11 @@ where
     read_buffer: BytesMut,
     /// User variables that were taken from the server
     user_variables: Vec<UserVariableItem>,
+    /// Where to capture the raw bytes of the association
+    wire_log: Option<WireLog>,
+    /// Whether the TCP stream was handed over to the user,
+    /// in which case the association is not released on drop
+    detached: bool,
 }
 
 impl<S: CloseSocket> ClientAssociation<S>
@@ -934,6 +1202,16 @@ where
         &self.presentation_contexts
     }
 
+    /// Retrieve the abstract syntax UID
+    /// which was proposed for the presentation context with the given ID,
+    /// or `None` if no such presentation context was proposed.
+    pub fn abstract_syntax(&self, presentation_context_id: u8) -> Option<&str> {
+        self.abstract_syntaxes
+            .iter()
+            .find(|(id, _)| *id == presentation_context_id)
+            .map(|(_, uid)| uid.as_str())
+    }
+
     /// Retrieve the maximum PDU length
     /// admitted by the association acceptor.
     pub fn acceptor_max_pdu_length(&self) -> u32 {
@@ -959,10 +1237,7 @@ where
     }
 }
 
-impl ClientAssociation<std::net::TcpStream>
-where
-    ClientAssociation<std::net::TcpStream>: Release,
-{
+impl<S: SyncSocket> ClientAssociation<S> {
     /// Send a PDU message to the other intervenient.
     pub fn send(&mut self, msg: &Pdu) -> Result<()> {
         self.buffer.clear();
@@ -973,14 +1248,16 @@ where
             }
             .fail();
         }
-        self.socket.write_all(&self.buffer).context(WireSendSnafu)
+        WireTap::new(&mut self.socket, self.wire_log.clone())
+            .write_all(&self.buffer)
+            .context(WireSendSnafu)
     }
 
     /// Read a PDU message from the other intervenient.
     pub fn receive(&mut self) -> Result<Pdu> {
         use std::io::{BufRead, BufReader, Cursor};
 
-        let mut reader = BufReader::new(&mut self.socket);
+        let mut reader = BufReader::new(WireTap::new(&mut self.socket, self.wire_log.clone()));
 
         loop {
             let mut buf = Cursor::new(&self.read_buffer[..]);
@@ -1012,7 +1289,7 @@ where
     /// and then shutting down the TCP connection.
     pub fn release(mut self) -> Result<()> {
         let out = self.release_impl();
-        let _ = self.socket.shutdown(std::net::Shutdown::Both);
+        let _ = self.socket.close();
         out
     }
 
@@ -1023,7 +1300,7 @@ where
             source: AbortRQSource::ServiceUser,
         };
         let out = self.send(&pdu);
-        let _ = self.socket.shutdown(std::net::Shutdown::Both);
+        let _ = self.socket.close();
         out
     }
 
@@ -1036,24 +1313,35 @@ where
     /// **Note:** reading and writing should be done with care
     /// to avoid inconsistencies in the association state.
     /// Do not call `send` and `receive` while not in a PDU boundary.
-    pub fn inner_stream(&mut self) -> &mut std::net::TcpStream {
+    pub fn inner_stream(&mut self) -> &mut S {
         &mut self.socket
     }
 
+    /// Retrieve the local socket address of the underlying TCP stream,
+    /// such as the one bound via
+    /// [`bind_address`](ClientAssociationOptions::bind_address).
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
     /// Prepare a P-Data writer for sending
     /// one or more data items.
     ///
     /// Returns a writer which automatically
     /// splits the inner data into separate PDUs if necessary.
-    pub fn send_pdata(
-        &mut self,
-        presentation_context_id: u8,
-    ) -> PDataWriter<&mut std::net::TcpStream> {
+    /// If the transfer syntax of the presentation context
+    /// compresses data sets,
+    /// the data set written is compressed before sending.
+    pub fn send_pdata(&mut self, presentation_context_id: u8) -> PDataWriter<WireTap<&mut S>> {
         PDataWriter::new(
-            &mut self.socket,
+            WireTap::new(&mut self.socket, self.wire_log.clone()),
             presentation_context_id,
             self.acceptor_max_pdu_length,
         )
+        .with_dataset_adapter(dataset_adapter(
+            &self.presentation_contexts,
+            presentation_context_id,
+        ))
     }
 
     /// Prepare a P-Data reader for receiving
@@ -1061,12 +1349,16 @@ where
     ///
     /// Returns a reader which automatically
     /// receives more data PDUs once the bytes collected are consumed.
-    pub fn receive_pdata(&mut self) -> PDataReader<&mut std::net::TcpStream> {
+    /// If the transfer syntax of the presentation context
+    /// compresses data sets,
+    /// the data set received is decompressed.
+    pub fn receive_pdata(&mut self) -> PDataReader<WireTap<&mut S>> {
         PDataReader::new(
-            &mut self.socket,
+            WireTap::new(&mut self.socket, self.wire_log.clone()),
             self.requestor_max_pdu_length,
             &mut self.read_buffer,
         )
+        .with_dataset_adapters(dataset_adapters(&self.presentation_contexts))
     }
 
     /// Release implementation function,
@@ -1093,6 +1385,44 @@ where
     }
 }
 
+impl ClientAssociation<std::net::TcpStream> {
+    /// Gracefully terminate the association by exchanging release messages,
+    /// then hand over the TCP stream instead of shutting it down.
+    ///
+    /// This allows the same connection to be reused
+    /// for other protocols after the DICOM association is released.
+    /// Returns the TCP stream
+    /// and any bytes which were already read from it
+    /// but are not part of the release exchange
+    /// (see [`into_parts`](Self::into_parts)).
+    ///
+    /// If the release exchange fails,
+    /// the TCP connection is shut down like in [`release`](Self::release).
+    pub fn release_and_take_stream(mut self) -> Result<(std::net::TcpStream, Vec<u8>)> {
+        if let Err(e) = self.release_impl() {
+            let _ = self.socket.shutdown(std::net::Shutdown::Both);
+            return Err(e);
+        }
+        self.into_parts()
+    }
+
+    /// Take the underlying TCP stream out of the association,
+    /// alongside any bytes which were already read from the stream
+    /// but not consumed as part of a PDU.
+    ///
+    /// No release or abort messages are sent,
+    /// and the TCP connection is not shut down.
+    /// The association should be regarded as terminated
+    /// from the perspective of this application entity,
+    /// and the caller becomes responsible for the connection.
+    pub fn into_parts(mut self) -> Result<(std::net::TcpStream, Vec<u8>)> {
+        let socket = self.socket.try_clone().context(TakeStreamSnafu)?;
+        let read_buffer = self.read_buffer.split().to_vec();
+        self.detached = true;
+        Ok((socket, read_buffer))
+    }
+}
+
 /// Automatically release the association and shut down the connection.
 impl<T> Drop for ClientAssociation<T>
 where
@@ -1100,6 +1430,9 @@ where
     ClientAssociation<T>: Release,
 {
     fn drop(&mut self) {
+        if self.detached {
+            return;
+        }
         let _ = self.release();
         let _ = self.socket.close();
     }
@@ -1112,12 +1445,18 @@ pub mod non_blocking {
     use crate::{
         association::{
             client::{
-                ConnectSnafu, ConnectionClosedSnafu, MissingAbstractSyntaxSnafu,
-                NoAcceptedPresentationContextsSnafu, ProtocolVersionMismatchSnafu,
-                ReceiveResponseSnafu, ReceiveSnafu, RejectedSnafu, SendRequestSnafu,
-                ToAddressSnafu, UnexpectedResponseSnafu, UnknownResponseSnafu, WireSendSnafu,
+                bound_socket, matches_ip_version, ConnectSnafu, ConnectionClosedSnafu,
+                CreateSocketSnafu, MissingAbstractSyntaxSnafu, NoAcceptedPresentationContextsSnafu,
+                ProtocolVersionMismatchSnafu, ReceiveResponseSnafu, ReceiveSnafu, RejectedSnafu,
+                SendRequestSnafu, ToAddressSnafu, UnexpectedResponseSnafu, UnknownResponseSnafu,
+                WireSendSnafu,
+            },
+            negotiation::request_relational_queries,
+            pdata::{
+                dataset_adapter, dataset_adapters,
+                non_blocking::{AsyncPDataWriter, PDataReader},
             },
-            pdata::non_blocking::{AsyncPDataWriter, PDataReader},
+            wire_log::WireTap,
         },
         pdu::{
             AbortRQSource, AssociationAC, AssociationRQ, PresentationContextProposed,
@@ -1127,13 +1466,19 @@ pub mod non_blocking {
         read_pdu, write_pdu, AeAddr, Pdu, IMPLEMENTATION_CLASS_UID, IMPLEMENTATION_VERSION_NAME,
     };
 
+    #[cfg(feature = "async-tls")]
+    use super::TlsSnafu;
     use super::{
         ClientAssociation, ClientAssociationOptions, CloseSocket, Release, Result,
         SendTooLongPduSnafu, TimeoutSnafu,
     };
+    #[cfg(feature = "async-tls")]
+    use crate::association::tls::{self, rustls};
     use bytes::{Buf, BytesMut};
     use snafu::{ensure, ResultExt};
-    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+    #[cfg(feature = "async-tls")]
+    use std::sync::Arc;
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
     pub async fn get_client_pdu_async<R: AsyncRead + Unpin>(
         reader: &mut R,
@@ -1188,6 +1533,93 @@ pub mod non_blocking {
         ) -> Result<ClientAssociation<tokio::net::TcpStream>>
         where
             T: tokio::net::ToSocketAddrs,
+        {
+            let socket = self.connect_async(&ae_address).await?;
+            self.negotiate_async(socket, ae_address.ae_title()).await
+        }
+
+        /// Open the TCP connection to the given address
+        /// as configured by these options.
+        async fn connect_async<T>(&self, ae_address: &AeAddr<T>) -> Result<tokio::net::TcpStream>
+        where
+            T: tokio::net::ToSocketAddrs,
+        {
+            // fail if no presentation contexts were provided: they represent intent,
+            // should not be omitted by the user
+            ensure!(
+                !self.presentation_contexts.is_empty(),
+                MissingAbstractSyntaxSnafu
+            );
+
+            let bind_address = self.bind_address;
+            let bind_device = self.bind_device.as_deref();
+            let connection_timeout = self.connection_timeout;
+
+            if bind_address.is_some() || bind_device.is_some() {
+                let addresses = tokio::net::lookup_host(ae_address.socket_addr())
+                    .await
+                    .context(ToAddressSnafu)?;
+
+                let mut result: Result<tokio::net::TcpStream, std::io::Error> =
+                    Result::Err(std::io::Error::from(std::io::ErrorKind::AddrNotAvailable));
+
+                for address in addresses.filter(|a| matches_ip_version(a, bind_address)) {
+                    let socket = bound_socket(&address, bind_address, bind_device)?;
+                    socket.set_nonblocking(true).context(CreateSocketSnafu)?;
+                    let socket = tokio::net::TcpSocket::from_std_stream(socket.into());
+                    result = if let Some(timeout) = connection_timeout {
+                        match tokio::time::timeout(timeout, socket.connect(address)).await {
+                            Ok(inner) => inner,
+                            Err(_) => Err(std::io::Error::from(std::io::ErrorKind::TimedOut)),
+                        }
+                    } else {
+                        socket.connect(address).await
+                    };
+                    if result.is_ok() {
+                        break;
+                    }
+                }
+                result.context(ConnectSnafu)
+            } else if let Some(timeout) = connection_timeout {
+                let addresses = tokio::net::lookup_host(ae_address.socket_addr())
+                    .await
+                    .context(ToAddressSnafu)?;
+
+                let mut result: Result<tokio::net::TcpStream, std::io::Error> =
+                    Result::Err(std::io::Error::from(std::io::ErrorKind::AddrNotAvailable));
+
+                for address in addresses {
+                    result = match tokio::time::timeout(
+                        timeout,
+                        tokio::net::TcpStream::connect(&address),
+                    )
+                    .await
+                    {
+                        Ok(inner) => inner,
+                        Err(_) => result,
+                    };
+                    if result.is_ok() {
+                        break;
+                    }
+                }
+                result.context(ConnectSnafu)
+            } else {
+                tokio::net::TcpStream::connect(ae_address.socket_addr())
+                    .await
+                    .context(ConnectSnafu)
+            }
+        }
+
+        /// Request a new DICOM association over the given connection,
+        /// negotiating the presentation contexts in the process.
+        async fn negotiate_async<S>(
+            self,
+            socket: S,
+            ae_title: Option<&str>,
+        ) -> Result<ClientAssociation<S>>
+        where
+            S: AsyncSocket,
+            ClientAssociation<S>: Release,
         {
             let ClientAssociationOptions {
                 calling_ae_title,
@@ -1202,20 +1634,15 @@ pub mod non_blocking {
                 kerberos_service_ticket,
                 saml_assertion,
                 jwt,
+                relational_queries,
                 read_timeout,
                 write_timeout,
-                connection_timeout,
+                wire_log,
+                ..
             } = self;
 
-            // fail if no presentation contexts were provided: they represent intent,
-            // should not be omitted by the user
-            ensure!(
-                !presentation_contexts.is_empty(),
-                MissingAbstractSyntaxSnafu
-            );
-
             // choose called AE title
-            let called_ae_title: &str = match (&called_ae_title, ae_address.ae_title()) {
+            let called_ae_title: &str = match (&called_ae_title, ae_title) {
                 (Some(aec), Some(_)) => {
                     tracing::warn!(
                         "Option `called_ae_title` overrides the AE title to `{}`",
@@ -1241,6 +1668,10 @@ pub mod non_blocking {
                         .collect(),
                 })
                 .collect();
+            let abstract_syntaxes = presentation_contexts
+                .iter()
+                .map(|pc| (pc.id, pc.abstract_syntax.clone()))
+                .collect();
 
             let mut user_variables = vec![
                 UserVariableItem::MaxLength(max_pdu_length),
@@ -1260,6 +1691,14 @@ pub mod non_blocking {
                 user_variables.push(UserVariableItem::UserIdentityItem(user_identity));
             }
 
+            if relational_queries {
+                user_variables.extend(request_relational_queries(
+                    presentation_contexts
+                        .iter()
+                        .map(|pc| pc.abstract_syntax.as_str()),
+                ));
+            }
+
             let msg = Pdu::AssociationRQ(AssociationRQ {
                 protocol_version,
                 calling_ae_title: calling_ae_title.to_string(),
@@ -1268,37 +1707,7 @@ pub mod non_blocking {
                 presentation_contexts,
                 user_variables,
             });
-            let conn_result: Result<tokio::net::TcpStream> =
-                if let Some(timeout) = connection_timeout {
-                    let addresses = tokio::net::lookup_host(ae_address.socket_addr())
-                        .await
-                        .context(ToAddressSnafu)?;
-
-                    let mut result: Result<tokio::net::TcpStream, std::io::Error> =
-                        Result::Err(std::io::Error::from(std::io::ErrorKind::AddrNotAvailable));
-
-                    for address in addresses {
-                        result = match tokio::time::timeout(
-                            timeout,
-                            tokio::net::TcpStream::connect(&address),
-                        )
-                        .await
-                        {
-                            Ok(inner) => inner,
-                            Err(_) => result,
-                        };
-                        if result.is_ok() {
-                            break;
-                        }
-                    }
-                    result.context(ConnectSnafu)
-                } else {
-                    tokio::net::TcpStream::connect(ae_address.socket_addr())
-                        .await
-                        .context(ConnectSnafu)
-                };
-
-            let mut socket = conn_result?;
+            let mut socket = WireTap::new(socket, wire_log.clone());
             let mut buffer: Vec<u8> = Vec::with_capacity(max_pdu_length as usize);
 
             // send request
@@ -1367,15 +1776,18 @@ pub mod non_blocking {
                     }
                     Ok(ClientAssociation {
                         presentation_contexts,
+                        abstract_syntaxes,
                         requestor_max_pdu_length: max_pdu_length,
                         acceptor_max_pdu_length,
-                        socket,
+                        socket: socket.into_inner(),
                         buffer,
                         strict,
                         read_timeout,
                         write_timeout,
                         read_buffer: BytesMut::with_capacity(MAXIMUM_PDU_SIZE as usize),
-                        user_variables
+                        user_variables,
+                        wire_log,
+                        detached: false,
                     })
                 }
                 Pdu::AssociationRJ(association_rj) => RejectedSnafu { association_rj }.fail(),
@@ -1463,171 +1875,235 @@ pub mod non_blocking {
                 }
             }
         }
-    }
 
-    impl ClientAssociation<tokio::net::TcpStream>
-    where
-        ClientAssociation<tokio::net::TcpStream>: Release,
-    {
-        /// Send a PDU message to the other intervenient.
-        pub async fn send(&mut self, msg: &Pdu) -> Result<()> {
-            self.buffer.clear();
-            write_pdu(&mut self.buffer, msg).context(SendRequestSnafu)?;
-            if self.buffer.len() > self.acceptor_max_pdu_length as usize {
-                return SendTooLongPduSnafu {
-                    length: self.buffer.len(),
-                }
-                .fail();
-            }
-            timeout(self.write_timeout, async {
-                self.socket
-                    .write_all(&self.buffer)
+        /// Initiate the TCP connection to the given address,
+        /// perform a TLS handshake on top of it,
+        /// and request a new DICOM association,
+        /// negotiating the presentation contexts in the process.
+        ///
+        /// The certificate presented by the association acceptor
+        /// is verified against `server_name`
+        /// as per the given client configuration.
+        /// A failed handshake results in [`Error::Tls`](super::Error::Tls).
+        /// See the [`tls`](crate::association::tls) module for more details.
+        ///
+        /// Requires the `async-tls` Cargo feature.
+        #[cfg(feature = "async-tls")]
+        pub async fn establish_tls_async<A: tokio::net::ToSocketAddrs>(
+            self,
+            address: A,
+            config: Arc<rustls::ClientConfig>,
+            server_name: &str,
+        ) -> Result<ClientAssociation<tls::non_blocking::ClientTlsStream>> {
+            let server_name = tls::server_name(server_name).context(TlsSnafu)?;
+            let socket = self
+                .connect_async(&AeAddr::new_socket_addr(address))
+                .await?;
+            let socket = timeout(self.read_timeout, async {
+                tls::non_blocking::connect(socket, config, server_name)
                     .await
-                    .context(WireSendSnafu)
+                    .context(TlsSnafu)
             })
-            .await
+            .await?;
+            self.negotiate_async(socket, None).await
         }
+    }
 
-        /// Read a PDU message from the other intervenient.
-        pub async fn receive(&mut self) -> Result<Pdu> {
-            timeout(self.read_timeout, async {
-                loop {
-                    let mut buf = Cursor::new(&self.read_buffer[..]);
-                    match read_pdu(&mut buf, self.requestor_max_pdu_length, self.strict)
-                        .context(ReceiveResponseSnafu)?
-                    {
-                        Some(pdu) => {
-                            self.read_buffer.advance(buf.position() as usize);
-                            return Ok(pdu);
-                        }
-                        None => {
-                            // Reset position
-                            buf.set_position(0)
+    /// Implement the async association API
+    /// for an association requester over the given stream type.
+    macro_rules! impl_async_client_association {
+        ($stream:ty) => {
+            impl ClientAssociation<$stream>
+            where
+                ClientAssociation<$stream>: Release,
+            {
+                /// Send a PDU message to the other intervenient.
+                pub async fn send(&mut self, msg: &Pdu) -> Result<()> {
+                    self.buffer.clear();
+                    write_pdu(&mut self.buffer, msg).context(SendRequestSnafu)?;
+                    if self.buffer.len() > self.acceptor_max_pdu_length as usize {
+                        return SendTooLongPduSnafu {
+                            length: self.buffer.len(),
                         }
+                        .fail();
                     }
-                    let recv = self
-                        .socket
-                        .read_buf(&mut self.read_buffer)
-                        .await
-                        .context(ReadPduSnafu)
-                        .context(ReceiveSnafu)?;
-                    ensure!(recv > 0, ConnectionClosedSnafu);
+                    timeout(self.write_timeout, async {
+                        WireTap::new(&mut self.socket, self.wire_log.clone())
+                            .write_all(&self.buffer)
+                            .await
+                            .context(WireSendSnafu)
+                    })
+                    .await
                 }
-            })
-            .await
-        }
 
-        /// Gracefully terminate the association by exchanging release messages
-        /// and then shutting down the TCP connection.
-        pub async fn release(mut self) -> Result<()> {
-            timeout(self.write_timeout, async {
-                let out = self.release_impl().await;
-                let _ = self.socket.shutdown().await;
-                out
-            })
-            .await
-        }
+                /// Read a PDU message from the other intervenient.
+                pub async fn receive(&mut self) -> Result<Pdu> {
+                    timeout(self.read_timeout, async {
+                        loop {
+                            let mut buf = Cursor::new(&self.read_buffer[..]);
+                            match read_pdu(&mut buf, self.requestor_max_pdu_length, self.strict)
+                                .context(ReceiveResponseSnafu)?
+                            {
+                                Some(pdu) => {
+                                    self.read_buffer.advance(buf.position() as usize);
+                                    return Ok(pdu);
+                                }
+                                None => {
+                                    // Reset position
+                                    buf.set_position(0)
+                                }
+                            }
+                            let recv = WireTap::new(&mut self.socket, self.wire_log.clone())
+                                .read_buf(&mut self.read_buffer)
+                                .await
+                                .context(ReadPduSnafu)
+                                .context(ReceiveSnafu)?;
+                            ensure!(recv > 0, ConnectionClosedSnafu);
+                        }
+                    })
+                    .await
+                }
 
-        /// Send an abort message and shut down the TCP connection,
-        /// terminating the association.
-        pub async fn abort(mut self) -> Result<()> {
-            timeout(self.write_timeout, async {
-                let pdu = Pdu::AbortRQ {
-                    source: AbortRQSource::ServiceUser,
-                };
-                let out = self.send(&pdu).await;
-                let _ = self.socket.shutdown().await;
-                out
-            })
-            .await
-        }
+                /// Gracefully terminate the association by exchanging release messages
+                /// and then shutting down the TCP connection.
+                pub async fn release(mut self) -> Result<()> {
+                    timeout(self.write_timeout, async {
+                        let out = self.release_impl().await;
+                        let _ = self.socket.shutdown().await;
+                        out
+                    })
+                    .await
+                }
 
-        /// Prepare a P-Data writer for sending
-        /// one or more data items.
-        ///
-        /// Returns a writer which automatically
-        /// splits the inner data into separate PDUs if necessary.
-        pub async fn send_pdata(
-            &mut self,
-            presentation_context_id: u8,
-        ) -> AsyncPDataWriter<&mut tokio::net::TcpStream> {
-            AsyncPDataWriter::new(
-                &mut self.socket,
-                presentation_context_id,
-                self.acceptor_max_pdu_length,
-            )
-        }
+                /// Send an abort message and shut down the TCP connection,
+                /// terminating the association.
+                pub async fn abort(mut self) -> Result<()> {
+                    timeout(self.write_timeout, async {
+                        let pdu = Pdu::AbortRQ {
+                            source: AbortRQSource::ServiceUser,
+                        };
+                        let out = self.send(&pdu).await;
+                        let _ = self.socket.shutdown().await;
+                        out
+                    })
+                    .await
+                }
 
-        /// Prepare a P-Data reader for receiving
-        /// one or more data item PDUs.
-        ///
-        /// Returns a reader which automatically
-        /// receives more data PDUs once the bytes collected are consumed.
-        #[cfg(feature = "async")]
-        pub fn receive_pdata(&mut self) -> PDataReader<&mut tokio::net::TcpStream> {
-            PDataReader::new(
-                &mut self.socket,
-                self.requestor_max_pdu_length,
-                &mut self.read_buffer,
-            )
-        }
+                /// Prepare a P-Data writer for sending
+                /// one or more data items.
+                ///
+                /// Returns a writer which automatically
+                /// splits the inner data into separate PDUs if necessary.
+                /// If the transfer syntax of the presentation context
+                /// compresses data sets,
+                /// the data set written is compressed before sending.
+                pub async fn send_pdata(
+                    &mut self,
+                    presentation_context_id: u8,
+                ) -> AsyncPDataWriter<WireTap<&mut $stream>> {
+                    AsyncPDataWriter::new(
+                        WireTap::new(&mut self.socket, self.wire_log.clone()),
+                        presentation_context_id,
+                        self.acceptor_max_pdu_length,
+                    )
+                    .with_dataset_adapter(dataset_adapter(
+                        &self.presentation_contexts,
+                        presentation_context_id,
+                    ))
+                }
 
-        /// Release implementation function,
-        /// which tries to send a release request and receive a release response.
-        /// This is in a separate private function because
-        /// terminating a connection should still close the connection
-        /// if the exchange fails.
-        async fn release_impl(&mut self) -> Result<()> {
-            let pdu = Pdu::ReleaseRQ;
-            self.send(&pdu).await?;
-            use tokio::io::AsyncReadExt;
-            let mut read_buffer = BytesMut::with_capacity(MAXIMUM_PDU_SIZE as usize);
-
-            let pdu = loop {
-                if let Ok(Some(pdu)) = read_pdu(&mut read_buffer, MAXIMUM_PDU_SIZE, self.strict) {
-                    break pdu;
+                /// Prepare a P-Data reader for receiving
+                /// one or more data item PDUs.
+                ///
+                /// Returns a reader which automatically
+                /// receives more data PDUs once the bytes collected are consumed.
+                /// If the transfer syntax of the presentation context
+                /// compresses data sets,
+                /// the data set received is decompressed.
+                #[cfg(feature = "async")]
+                pub fn receive_pdata(&mut self) -> PDataReader<WireTap<&mut $stream>> {
+                    PDataReader::new(
+                        WireTap::new(&mut self.socket, self.wire_log.clone()),
+                        self.requestor_max_pdu_length,
+                        &mut self.read_buffer,
+                    )
+                    .with_dataset_adapters(dataset_adapters(&self.presentation_contexts))
+                }
+
+                /// Release implementation function,
+                /// which tries to send a release request and receive a release response.
+                /// This is in a separate private function because
+                /// terminating a connection should still close the connection
+                /// if the exchange fails.
+                async fn release_impl(&mut self) -> Result<()> {
+                    let pdu = Pdu::ReleaseRQ;
+                    self.send(&pdu).await?;
+                    use tokio::io::AsyncReadExt;
+                    let mut read_buffer = BytesMut::with_capacity(MAXIMUM_PDU_SIZE as usize);
+
+                    let pdu = loop {
+                        if let Ok(Some(pdu)) =
+                            read_pdu(&mut read_buffer, MAXIMUM_PDU_SIZE, self.strict)
+                        {
+                            break pdu;
+                        }
+                        let recv = WireTap::new(&mut self.socket, self.wire_log.clone())
+                            .read_buf(&mut read_buffer)
+                            .await
+                            .context(ReadPduSnafu)
+                            .context(ReceiveSnafu)?;
+                        ensure!(recv > 0, ConnectionClosedSnafu);
+                    };
+                    match pdu {
+                        Pdu::ReleaseRP => {}
+                        pdu @ Pdu::AbortRQ { .. }
+                        | pdu @ Pdu::AssociationAC { .. }
+                        | pdu @ Pdu::AssociationRJ { .. }
+                        | pdu @ Pdu::AssociationRQ { .. }
+                        | pdu @ Pdu::PData { .. }
+                        | pdu @ Pdu::ReleaseRQ { .. } => {
+                            return UnexpectedResponseSnafu { pdu }.fail()
+                        }
+                        pdu @ Pdu::Unknown { .. } => return UnknownResponseSnafu { pdu }.fail(),
+                    }
+                    Ok(())
+                }
+                /// Obtain access to the inner TCP stream
+                /// connected to the association acceptor.
+                ///
+                /// This can be used to send the PDU in semantic fragments of the message,
+                /// thus using less memory.
+                ///
+                /// **Note:** reading and writing should be done with care
+                /// to avoid inconsistencies in the association state.
+                /// Do not call `send` and `receive` while not in a PDU boundary.
+                pub fn inner_stream(&mut self) -> &mut $stream {
+                    &mut self.socket
+                }
+
+                /// Retrieve the local socket address of the underlying TCP stream,
+                /// such as the one bound via
+                /// [`bind_address`](ClientAssociationOptions::bind_address).
+                pub fn local_addr(&self) -> std::io::Result<std::net::SocketAddr> {
+                    AsyncSocket::local_addr(&self.socket)
                 }
-                let recv = self
-                    .socket
-                    .read_buf(&mut read_buffer)
-                    .await
-                    .context(ReadPduSnafu)
-                    .context(ReceiveSnafu)?;
-                ensure!(recv > 0, ConnectionClosedSnafu);
-            };
-            match pdu {
-                Pdu::ReleaseRP => {}
-                pdu @ Pdu::AbortRQ { .. }
-                | pdu @ Pdu::AssociationAC { .. }
-                | pdu @ Pdu::AssociationRJ { .. }
-                | pdu @ Pdu::AssociationRQ { .. }
-                | pdu @ Pdu::PData { .. }
-                | pdu @ Pdu::ReleaseRQ { .. } => return UnexpectedResponseSnafu { pdu }.fail(),
-                pdu @ Pdu::Unknown { .. } => return UnknownResponseSnafu { pdu }.fail(),
             }
-            Ok(())
-        }
-        /// Obtain access to the inner TCP stream
-        /// connected to the association acceptor.
-        ///
-        /// This can be used to send the PDU in semantic fragments of the message,
-        /// thus using less memory.
-        ///
-        /// **Note:** reading and writing should be done with care
-        /// to avoid inconsistencies in the association state.
-        /// Do not call `send` and `receive` while not in a PDU boundary.
-        pub fn inner_stream(&mut self) -> &mut tokio::net::TcpStream {
-            &mut self.socket
-        }
-    }
 
-    impl Release for ClientAssociation<tokio::net::TcpStream> {
-        fn release(&mut self) -> super::Result<()> {
-            tokio::task::block_in_place(move || {
-                tokio::runtime::Handle::current().block_on(async move { self.release_impl().await })
-            })
-        }
+            impl Release for ClientAssociation<$stream> {
+                fn release(&mut self) -> super::Result<()> {
+                    tokio::task::block_in_place(move || {
+                        tokio::runtime::Handle::current()
+                            .block_on(async move { self.release_impl().await })
+                    })
+                }
+            }
+        };
     }
+
+    impl_async_client_association!(tokio::net::TcpStream);
+    #[cfg(feature = "async-tls")]
+    impl_async_client_association!(crate::association::tls::non_blocking::ClientTlsStream);
+
     /// Automatically release the association and shut down the connection.
     impl CloseSocket for tokio::net::TcpStream {
         fn close(&mut self) -> std::io::Result<()> {
@@ -1636,4 +2112,17 @@ pub mod non_blocking {
             })
         }
     }
+
+    /// Trait for async streams over which an association can be established,
+    /// such as a plain TCP stream or a TLS stream on top of one.
+    pub trait AsyncSocket: AsyncRead + AsyncWrite + Unpin + CloseSocket {
+        /// Retrieve the local socket address of the underlying TCP stream.
+        fn local_addr(&self) -> std::io::Result<std::net::SocketAddr>;
+    }
+
+    impl AsyncSocket for tokio::net::TcpStream {
+        fn local_addr(&self) -> std::io::Result<std::net::SocketAddr> {
+            tokio::net::TcpStream::local_addr(self)
+        }
+    }
 }
@@ -14,17 +14,46 @@
 //! a newly created [TCP stream][1] can be passed to
 //! a previously prepared [`ServerAssociationOptions`].
 //!
+//! Relational queries for the query/retrieve service class
+//! are negotiated through the types in [`negotiation`].
+//!
+//! Code which should work with both the blocking and the async
+//! association requester can be written against the traits in [`scu`].
+//!
+//! The raw bytes exchanged through an association
+//! can be captured with the utilities in [`wire_log`].
+//!
+//! Data sets sent and received through the P-Data writers and readers
+//! (`send_pdata` and `receive_pdata`)
+//! are compressed and decompressed transparently
+//! when the presentation context negotiated
+//! _Deflated Explicit VR Little Endian_
+//! (requires the `deflate` Cargo feature, enabled by default).
+//! Command sets are never compressed.
+//!
+//! With the `tls` Cargo feature,
+//! associations can also be established over TLS
+//! (see the `tls` module).
 //!
 //! [1]: std::net::TcpStream
 pub mod client;
+pub mod negotiation;
+pub mod scu;
 pub mod server;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod wire_log;
 
+mod reassembly;
 mod uid;
+mod verification;
 
 pub(crate) mod pdata;
 
 pub use client::{ClientAssociation, ClientAssociationOptions};
+pub use negotiation::RelationalQuerySupport;
 #[cfg(feature = "async")]
 pub use pdata::non_blocking::AsyncPDataWriter;
 pub use pdata::{PDataReader, PDataWriter};
+pub use reassembly::{PDataFragmenter, PDataMessage, PDataReassembler, ReassemblyError};
 pub use server::{ServerAssociation, ServerAssociationOptions};
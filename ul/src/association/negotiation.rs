@@ -0,0 +1,278 @@
+//! SOP class extended negotiation for the query/retrieve service class.
+//!
+//! An association requester may ask for extra behavior
+//! of a query/retrieve SOP class
+//! through a SOP Class Extended Negotiation sub-item,
+//! whose service-class-application-information field
+//! is defined in PS3.4 C.5.
+//! Its first byte states whether relational queries are requested,
+//! which the acceptor grants by replying with the same byte set to 1.
+//! Without this negotiation,
+//! relational queries are not supported.
+//!
+//! See [`ClientAssociationOptions::relational_queries`]
+//! and [`ServerAssociationOptions::relational_queries`]
+//! for requesting and granting this support.
+//!
+//! [`ClientAssociationOptions::relational_queries`]: super::ClientAssociationOptions::relational_queries
+//! [`ServerAssociationOptions::relational_queries`]: super::ServerAssociationOptions::relational_queries
+
+use crate::pdu::UserVariableItem;
+
+use super::uid::trim_uid;
+
+/// The SOP classes of the query/retrieve information models
+/// (C-FIND, C-MOVE, and C-GET),
+/// which admit relational queries through extended negotiation.
+pub const QUERY_RETRIEVE_SOP_CLASSES: &[&str] = &[
+    // Patient Root Query/Retrieve Information Model - FIND
+    "1.2.840.10008.5.1.4.1.2.1.1",
+    // Patient Root Query/Retrieve Information Model - MOVE
+    "1.2.840.10008.5.1.4.1.2.1.2",
+    // Patient Root Query/Retrieve Information Model - GET
+    "1.2.840.10008.5.1.4.1.2.1.3",
+    // Study Root Query/Retrieve Information Model - FIND
+    "1.2.840.10008.5.1.4.1.2.2.1",
+    // Study Root Query/Retrieve Information Model - MOVE
+    "1.2.840.10008.5.1.4.1.2.2.2",
+    // Study Root Query/Retrieve Information Model - GET
+    "1.2.840.10008.5.1.4.1.2.2.3",
+    // Patient/Study Only Query/Retrieve Information Model - FIND (retired)
+    "1.2.840.10008.5.1.4.1.2.3.1",
+    // Patient/Study Only Query/Retrieve Information Model - MOVE (retired)
+    "1.2.840.10008.5.1.4.1.2.3.2",
+    // Patient/Study Only Query/Retrieve Information Model - GET (retired)
+    "1.2.840.10008.5.1.4.1.2.3.3",
+];
+
+/// Check whether the given SOP class UID
+/// belongs to a query/retrieve information model.
+pub fn is_query_retrieve_sop_class(sop_class_uid: &str) -> bool {
+    QUERY_RETRIEVE_SOP_CLASSES.contains(&trim_uid(sop_class_uid.into()).as_ref())
+}
+
+/// Whether relational queries are supported
+/// for a query/retrieve SOP class in an association.
+///
+/// The default is [`NotSupported`](Self::NotSupported),
+/// which applies whenever no extended negotiation took place.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RelationalQuerySupport {
+    /// Only hierarchical queries are supported
+    #[default]
+    NotSupported,
+    /// Relational queries are supported
+    Supported,
+}
+
+impl RelationalQuerySupport {
+    /// Check whether relational queries are supported.
+    pub fn is_supported(self) -> bool {
+        self == RelationalQuerySupport::Supported
+    }
+
+    /// Interpret the service-class-application-information field
+    /// of a query/retrieve SOP Class Extended Negotiation sub-item.
+    ///
+    /// An empty field means that relational queries are not supported.
+    pub fn from_application_info(application_info: &[u8]) -> Self {
+        match application_info.first() {
+            Some(1) => RelationalQuerySupport::Supported,
+            _ => RelationalQuerySupport::NotSupported,
+        }
+    }
+
+    /// Encode this flag as the service-class-application-information field
+    /// of a query/retrieve SOP Class Extended Negotiation sub-item.
+    pub fn to_application_info(self) -> Vec<u8> {
+        vec![self.is_supported() as u8]
+    }
+
+    /// Look up the relational query support for the given SOP class
+    /// in a list of user variables from an association request or response.
+    ///
+    /// Returns [`NotSupported`](Self::NotSupported)
+    /// if there is no extended negotiation sub-item for the SOP class.
+    pub fn from_user_variables(user_variables: &[UserVariableItem], sop_class_uid: &str) -> Self {
+        let sop_class_uid = trim_uid(sop_class_uid.into());
+        user_variables
+            .iter()
+            .find_map(|item| match item {
+                UserVariableItem::SopClassExtendedNegotiationSubItem(uid, application_info)
+                    if trim_uid(uid.into()) == sop_class_uid =>
+                {
+                    Some(Self::from_application_info(application_info))
+                }
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Build the extended negotiation sub-items requesting relational queries
+/// for each query/retrieve SOP class in the given abstract syntaxes.
+pub(crate) fn request_relational_queries<'a>(
+    abstract_syntaxes: impl IntoIterator<Item = &'a str>,
+) -> Vec<UserVariableItem> {
+    let mut sop_classes: Vec<&str> = Vec::new();
+    for uid in abstract_syntaxes {
+        if is_query_retrieve_sop_class(uid) && !sop_classes.contains(&uid) {
+            sop_classes.push(uid);
+        }
+    }
+    sop_classes
+        .into_iter()
+        .map(|uid| {
+            UserVariableItem::SopClassExtendedNegotiationSubItem(
+                uid.to_string(),
+                RelationalQuerySupport::Supported.to_application_info(),
+            )
+        })
+        .collect()
+}
+
+/// Build the extended negotiation sub-items of an association response
+/// for the query/retrieve SOP classes which the requester negotiated
+/// and which were accepted in `accepted_contexts`
+/// (pairs of presentation context ID and abstract syntax UID).
+///
+/// Relational queries are granted if requested and `grant` is true.
+/// Any other field of the service-class-application-information
+/// is replied with 0 (not supported).
+/// Also returns the IDs of the accepted presentation contexts
+/// for which relational queries were granted.
+pub(crate) fn respond_relational_queries(
+    request_user_variables: &[UserVariableItem],
+    accepted_contexts: &[(u8, String)],
+    grant: bool,
+) -> (Vec<UserVariableItem>, Vec<u8>) {
+    let mut response = Vec::new();
+    let mut granted_context_ids = Vec::new();
+
+    for item in request_user_variables {
+        let (uid, application_info) = match item {
+            UserVariableItem::SopClassExtendedNegotiationSubItem(uid, application_info) => {
+                (trim_uid(uid.into()), application_info)
+            }
+            _ => continue,
+        };
+        if !is_query_retrieve_sop_class(&uid) {
+            continue;
+        }
+        let context_ids: Vec<u8> = accepted_contexts
+            .iter()
+            .filter(|(_, abstract_syntax)| *abstract_syntax == uid)
+            .map(|(id, _)| *id)
+            .collect();
+        if context_ids.is_empty() {
+            continue;
+        }
+
+        let support = if grant {
+            RelationalQuerySupport::from_application_info(application_info)
+        } else {
+            RelationalQuerySupport::NotSupported
+        };
+        if support.is_supported() {
+            granted_context_ids.extend(context_ids);
+        }
+
+        let mut application_info = vec![0; application_info.len().max(1)];
+        application_info[0] = support.is_supported() as u8;
+        response.push(UserVariableItem::SopClassExtendedNegotiationSubItem(
+            uid.into_owned(),
+            application_info,
+        ));
+    }
+
+    (response, granted_context_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STUDY_ROOT_FIND: &str = "1.2.840.10008.5.1.4.1.2.2.1";
+    const STUDY_ROOT_MOVE: &str = "1.2.840.10008.5.1.4.1.2.2.2";
+    const CT_IMAGE_STORAGE: &str = "1.2.840.10008.5.1.4.1.1.2";
+
+    #[test]
+    fn relational_query_support_from_application_info() {
+        use RelationalQuerySupport::*;
+        assert_eq!(RelationalQuerySupport::default(), NotSupported);
+        assert_eq!(
+            RelationalQuerySupport::from_application_info(&[]),
+            NotSupported
+        );
+        assert_eq!(
+            RelationalQuerySupport::from_application_info(&[0]),
+            NotSupported
+        );
+        assert_eq!(
+            RelationalQuerySupport::from_application_info(&[1]),
+            Supported
+        );
+        assert_eq!(
+            RelationalQuerySupport::from_application_info(&[1, 0, 1]),
+            Supported
+        );
+        assert_eq!(Supported.to_application_info(), vec![1]);
+        assert_eq!(NotSupported.to_application_info(), vec![0]);
+    }
+
+    #[test]
+    fn negotiate_relational_queries() {
+        let mut request = vec![UserVariableItem::MaxLength(16_384)];
+        request.extend(request_relational_queries([
+            STUDY_ROOT_FIND,
+            CT_IMAGE_STORAGE,
+            STUDY_ROOT_FIND,
+            STUDY_ROOT_MOVE,
+        ]));
+        // extra fields beyond relational queries
+        request.push(UserVariableItem::SopClassExtendedNegotiationSubItem(
+            "1.2.840.10008.5.1.4.1.2.1.1".to_string(),
+            vec![1, 1, 1],
+        ));
+        assert_eq!(request.len(), 4);
+        assert_eq!(
+            RelationalQuerySupport::from_user_variables(&request, STUDY_ROOT_FIND),
+            RelationalQuerySupport::Supported
+        );
+        assert_eq!(
+            RelationalQuerySupport::from_user_variables(&request, CT_IMAGE_STORAGE),
+            RelationalQuerySupport::NotSupported
+        );
+
+        // study root MOVE was not accepted
+        let accepted = [
+            (1, STUDY_ROOT_FIND.to_string()),
+            (3, CT_IMAGE_STORAGE.to_string()),
+            (5, "1.2.840.10008.5.1.4.1.2.1.1".to_string()),
+            (7, STUDY_ROOT_FIND.to_string()),
+        ];
+        let (response, context_ids) = respond_relational_queries(&request, &accepted, true);
+        assert_eq!(
+            response,
+            vec![
+                UserVariableItem::SopClassExtendedNegotiationSubItem(
+                    STUDY_ROOT_FIND.to_string(),
+                    vec![1]
+                ),
+                UserVariableItem::SopClassExtendedNegotiationSubItem(
+                    "1.2.840.10008.5.1.4.1.2.1.1".to_string(),
+                    vec![1, 0, 0]
+                ),
+            ]
+        );
+        assert_eq!(context_ids, vec![1, 7, 5]);
+
+        let (response, context_ids) = respond_relational_queries(&request, &accepted, false);
+        assert_eq!(
+            RelationalQuerySupport::from_user_variables(&response, STUDY_ROOT_FIND),
+            RelationalQuerySupport::NotSupported
+        );
+        assert!(context_ids.is_empty());
+        assert_eq!(response.len(), 2);
+    }
+}
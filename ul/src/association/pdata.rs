@@ -4,34 +4,80 @@ use std::{
 };
 
 use bytes::{Buf, BytesMut};
+use dicom_encoding::transfer_syntax::{adapt_bytes, DynDataRWAdapter, TransferSyntaxIndex};
+use dicom_encoding::Codec;
+use dicom_transfer_syntax_registry::TransferSyntaxRegistry;
 use tracing::warn;
 
-use crate::{pdu::PDU_HEADER_SIZE, read_pdu, Pdu};
-
-/// Set up the P-Data PDU header for sending.
-fn setup_pdata_header(buffer: &mut [u8], is_last: bool) {
-    let data_len = (buffer.len() - 12) as u32;
-
-    // full PDU length (minus PDU type and reserved byte)
-    let pdu_len = data_len + 4 + 2;
-    let pdu_len_bytes = pdu_len.to_be_bytes();
+use crate::{
+    pdu::{read_pdu_split_pdv, PDataValueType, PresentationContextResult},
+    read_pdu, write_pdu, Pdu,
+};
 
-    buffer[2] = pdu_len_bytes[0];
-    buffer[3] = pdu_len_bytes[1];
-    buffer[4] = pdu_len_bytes[2];
-    buffer[5] = pdu_len_bytes[3];
+use super::reassembly::{PDataFragmenter, PDataMessage, PDataReassembler};
+
+/// The data set adapter of a transfer syntax,
+/// which compresses or otherwise transforms whole data sets
+/// (as in _Deflated Explicit VR Little Endian_).
+pub(crate) type DatasetAdapter = &'static DynDataRWAdapter;
+
+/// Look up the data set adapter of the transfer syntax
+/// negotiated for the given presentation context,
+/// if the transfer syntax registry provides one.
+pub(crate) fn dataset_adapter(
+    presentation_contexts: &[PresentationContextResult],
+    presentation_context_id: u8,
+) -> Option<DatasetAdapter> {
+    let pc = presentation_contexts
+        .iter()
+        .find(|pc| pc.id == presentation_context_id)?;
+    let registry: &'static TransferSyntaxRegistry = &TransferSyntaxRegistry;
+    match registry.get(&pc.transfer_syntax)?.codec() {
+        Codec::Dataset(Some(adapter)) => Some(adapter),
+        _ => None,
+    }
+}
 
-    // presentation data length (data + 2 properties below)
-    let pdv_data_len = data_len + 2;
-    let data_len_bytes = pdv_data_len.to_be_bytes();
+/// Look up the data set adapters of all given presentation contexts
+/// which have one.
+pub(crate) fn dataset_adapters(
+    presentation_contexts: &[PresentationContextResult],
+) -> Vec<(u8, DatasetAdapter)> {
+    presentation_contexts
+        .iter()
+        .filter_map(|pc| Some((pc.id, dataset_adapter(presentation_contexts, pc.id)?)))
+        .collect()
+}
 
-    buffer[6] = data_len_bytes[0];
-    buffer[7] = data_len_bytes[1];
-    buffer[8] = data_len_bytes[2];
-    buffer[9] = data_len_bytes[3];
+/// Pass the bytes of a whole data set received
+/// through the reader of a data set adapter,
+/// obtaining the original data set.
+fn adapt_incoming(adapter: DatasetAdapter, data: Vec<u8>) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    adapter
+        .adapt_reader(Box::new(Cursor::new(data)))
+        .read_to_end(&mut out)?;
+    Ok(out)
+}
 
-    // message control header
-    buffer[11] = if is_last { 0x02 } else { 0x00 };
+/// Encode the P-Data PDUs which carry a part of a data set,
+/// as split by a [`PDataFragmenter`].
+fn encode_pdata_part(
+    presentation_context_id: u8,
+    max_pdu_length: u32,
+    data: Vec<u8>,
+    is_last: bool,
+) -> std::io::Result<Vec<u8>> {
+    let message = PDataMessage {
+        presentation_context_id,
+        value_type: PDataValueType::Data,
+        data,
+    };
+    let mut out = Vec::new();
+    for pdu in PDataFragmenter::new_part(message, max_pdu_length, is_last) {
+        write_pdu(&mut out, &pdu).map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    }
+    Ok(out)
 }
 
 /// A P-Data value writer.
@@ -42,6 +88,13 @@ fn setup_pdata_header(buffer: &mut [u8], is_last: bool) {
 /// will automatically split the incoming bytes
 /// into separate PDUs if they do not fit in a single one.
 ///
+/// When the transfer syntax negotiated for the presentation context
+/// has a data set adapter,
+/// such as _Deflated Explicit VR Little Endian_,
+/// the bytes written are passed through the adapter before sending.
+/// In this case,
+/// the data set is kept in memory until the writer is finished.
+///
 /// # Example
 ///
 /// Use an association's `send_pdata` method
@@ -79,9 +132,18 @@ fn setup_pdata_header(buffer: &mut [u8], is_last: bool) {
 /// # }
 #[must_use]
 pub struct PDataWriter<W: Write> {
+    /// the data of the next fragment
     buffer: Vec<u8>,
     stream: W,
-    max_data_len: u32,
+    presentation_context_id: u8,
+    max_pdu_length: u32,
+    max_data_len: usize,
+    /// whether the last fragment was already sent
+    finished: bool,
+    /// the data set adapter to pass the data set through, if any
+    adapter: Option<DatasetAdapter>,
+    /// the data set held back until finished, if adapted
+    unadapted: Vec<u8>,
 }
 
 impl<W> PDataWriter<W>
@@ -92,36 +154,26 @@ where
     ///
     /// `max_pdu_length` is the maximum value of the PDU-length property.
     pub(crate) fn new(stream: W, presentation_context_id: u8, max_pdu_length: u32) -> Self {
-        let max_data_length = calculate_max_data_len_single(max_pdu_length);
-        let mut buffer = Vec::with_capacity((max_data_length + PDU_HEADER_SIZE) as usize);
-        // initial buffer set up
-        buffer.extend([
-            // PDU-type + reserved byte
-            0x04,
-            0x00,
-            // full PDU length, unknown at this point
-            0xFF,
-            0xFF,
-            0xFF,
-            0xFF,
-            // presentation data length, unknown at this point
-            0xFF,
-            0xFF,
-            0xFF,
-            0xFF,
-            // presentation context id
-            presentation_context_id,
-            // message control header, unknown at this point
-            0xFF,
-        ]);
-
+        let max_data_len = PDataFragmenter::max_data_length(max_pdu_length);
         PDataWriter {
+            buffer: Vec::with_capacity(max_data_len),
             stream,
-            max_data_len: max_data_length,
-            buffer,
+            presentation_context_id,
+            max_pdu_length,
+            max_data_len,
+            finished: false,
+            adapter: None,
+            unadapted: Vec::new(),
         }
     }
 
+    /// Pass the data set through the given data set adapter before sending,
+    /// as mandated by the transfer syntax of the presentation context.
+    pub(crate) fn with_dataset_adapter(mut self, adapter: Option<DatasetAdapter>) -> Self {
+        self.adapter = adapter;
+        self
+    }
+
     /// Declare to have finished sending P-Data fragments,
     /// thus emitting the last P-Data fragment PDU.
     ///
@@ -132,31 +184,30 @@ where
     }
 
     fn finish_impl(&mut self) -> std::io::Result<()> {
-        if !self.buffer.is_empty() {
-            // send last PDU
-            setup_pdata_header(&mut self.buffer, true);
-            self.stream.write_all(&self.buffer[..])?;
-            // clear buffer so that subsequent calls to `finish_impl`
+        if let Some(adapter) = self.adapter.take() {
+            let data = adapt_bytes(adapter, &std::mem::take(&mut self.unadapted))?;
+            self.write_all(&data)?;
+        }
+        if !self.finished {
+            // send last PDU,
+            // so that subsequent calls to `finish_impl`
             // do not send any more PDUs
-            self.buffer.clear();
+            self.finished = true;
+            self.dispatch_pdu(true)?;
         }
         Ok(())
     }
 
-    /// Use the current state of the buffer to send new PDUs
-    ///
-    /// Pre-condition:
-    /// buffer must have enough data for one P-Data-tf PDU
-    fn dispatch_pdu(&mut self) -> std::io::Result<()> {
-        debug_assert!(self.buffer.len() >= 12);
-        // send PDU now
-        setup_pdata_header(&mut self.buffer, false);
-        self.stream.write_all(&self.buffer)?;
-
-        // back to just the header
-        self.buffer.truncate(12);
-
-        Ok(())
+    /// Send the data in the buffer as the next fragment.
+    fn dispatch_pdu(&mut self, is_last: bool) -> std::io::Result<()> {
+        let data = std::mem::replace(&mut self.buffer, Vec::with_capacity(self.max_data_len));
+        let bytes = encode_pdata_part(
+            self.presentation_context_id,
+            self.max_pdu_length,
+            data,
+            is_last,
+        )?;
+        self.stream.write_all(&bytes)
     }
 }
 
@@ -165,20 +216,24 @@ where
     W: Write,
 {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        let total_len = self.max_data_len as usize + 12;
-        if self.buffer.len() + buf.len() <= total_len {
-            // accumulate into buffer, do nothing
-            self.buffer.extend(buf);
-            Ok(buf.len())
-        } else {
-            // fill in the rest of the buffer, send PDU,
-            // and leave out the rest for subsequent writes
-            let buf = &buf[..total_len - self.buffer.len()];
-            self.buffer.extend(buf);
-            debug_assert_eq!(self.buffer.len(), total_len);
-            self.dispatch_pdu()?;
-            Ok(buf.len())
+        if self.adapter.is_some() {
+            // hold back until finished
+            self.unadapted.extend_from_slice(buf);
+            return Ok(buf.len());
         }
+
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.buffer.len() == self.max_data_len {
+            // more data to come, so the buffer is not the last fragment
+            self.dispatch_pdu(false)?;
+        }
+        // accumulate into buffer,
+        // and leave out the rest for subsequent writes
+        let len = buf.len().min(self.max_data_len - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        Ok(len)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
@@ -209,6 +264,19 @@ where
 /// even if they reside in separate PDUs,
 /// until the last message is received.
 ///
+/// When a data set arrives on a presentation context
+/// whose transfer syntax has a data set adapter,
+/// such as _Deflated Explicit VR Little Endian_,
+/// the whole data set is received
+/// and passed through the adapter before any bytes are provided.
+/// Commands are provided as received.
+///
+/// By default, the reader also copes with peers
+/// which split a presentation data value item
+/// across consecutive P-DATA-TF PDUs,
+/// in violation of the standard.
+/// See [`tolerate_split_pdv`](Self::tolerate_split_pdv).
+///
 /// # Example
 ///
 /// Use an association's `receive_pdata` method
@@ -239,9 +307,22 @@ pub struct PDataReader<'a, R> {
     buffer: VecDeque<u8>,
     stream: R,
     presentation_context_id: Option<u8>,
+    value_type: Option<PDataValueType>,
     max_data_length: u32,
     last_pdu: bool,
     read_buffer: &'a mut BytesMut,
+    /// the data set adapters of each presentation context which has one
+    dataset_adapters: Vec<(u8, DatasetAdapter)>,
+    /// whether the buffer already went through a data set adapter
+    adapted: bool,
+    /// whether to accept items split across P-DATA-TF PDUs
+    tolerate_split_pdv: bool,
+    /// the bytes of an item not yet completed by the next P-DATA-TF PDU
+    pending_pdv: Vec<u8>,
+    /// the number of P-DATA-TF PDUs which ended with an incomplete item
+    split_pdv_count: usize,
+    /// the reassembler of the P-Data value fragments received
+    reassembler: PDataReassembler,
 }
 
 impl<'a, R> PDataReader<'a, R> {
@@ -250,9 +331,130 @@ impl<'a, R> PDataReader<'a, R> {
             buffer: VecDeque::with_capacity(max_data_length as usize),
             stream,
             presentation_context_id: None,
+            value_type: None,
             max_data_length,
             last_pdu: false,
             read_buffer: remaining,
+            dataset_adapters: Vec::new(),
+            adapted: false,
+            tolerate_split_pdv: true,
+            pending_pdv: Vec::new(),
+            split_pdv_count: 0,
+            reassembler: PDataReassembler::new(),
+        }
+    }
+
+    /// Define whether to accept presentation data value items
+    /// split across consecutive P-DATA-TF PDUs.
+    ///
+    /// The standard requires each PDU to contain whole items,
+    /// but some peers send the header or the value of an item
+    /// across two PDUs.
+    /// When enabled (the default),
+    /// such items are reassembled,
+    /// and the event is logged and counted in
+    /// [`split_pdv_count`](Self::split_pdv_count).
+    /// When disabled, they result in a read error.
+    pub fn tolerate_split_pdv(mut self, tolerate: bool) -> Self {
+        self.tolerate_split_pdv = tolerate;
+        self
+    }
+
+    /// Retrieve the number of P-DATA-TF PDUs received so far
+    /// which ended with an incomplete presentation data value item.
+    pub fn split_pdv_count(&self) -> usize {
+        self.split_pdv_count
+    }
+
+    /// Record whether the last PDU received
+    /// ended with an incomplete item.
+    fn check_split_pdv(&mut self) {
+        if !self.pending_pdv.is_empty() {
+            self.split_pdv_count += 1;
+            warn!(
+                "P-DATA-TF PDU ended with an incomplete presentation data value item, {} bytes carried over",
+                self.pending_pdv.len()
+            );
+        }
+    }
+
+    /// Pass data sets through the data set adapter
+    /// of their presentation context, if it has one.
+    pub(crate) fn with_dataset_adapters(
+        mut self,
+        dataset_adapters: Vec<(u8, DatasetAdapter)>,
+    ) -> Self {
+        self.dataset_adapters = dataset_adapters;
+        self
+    }
+
+    /// Retrieve the data set adapter still to be applied
+    /// to the data received.
+    fn pending_adapter(&self) -> Option<DatasetAdapter> {
+        if self.adapted || self.value_type != Some(PDataValueType::Data) {
+            return None;
+        }
+        let presentation_context_id = self.presentation_context_id?;
+        self.dataset_adapters
+            .iter()
+            .find(|(id, _)| *id == presentation_context_id)
+            .map(|(_, adapter)| *adapter)
+    }
+
+    /// Pass the whole data set received through the given data set adapter.
+    fn adapt_buffer(&mut self, adapter: DatasetAdapter) -> std::io::Result<()> {
+        let data = Vec::from(std::mem::take(&mut self.buffer));
+        self.buffer = adapt_incoming(adapter, data)?.into();
+        self.adapted = true;
+        Ok(())
+    }
+
+    /// Collect the P-Data values of a PDU into the buffer.
+    fn push_pdu(&mut self, msg: Pdu) -> std::io::Result<()> {
+        match msg {
+            Pdu::PData { data } => {
+                for mut pdata_value in data {
+                    let cid = match self.presentation_context_id {
+                        None => pdata_value.presentation_context_id,
+                        Some(cid) if cid == pdata_value.presentation_context_id => cid,
+                        Some(cid) => {
+                            warn!(
+                                "Received PData value of presentation context {}, but should be {}",
+                                pdata_value.presentation_context_id, cid
+                            );
+                            // collect it as part of the same message
+                            pdata_value.presentation_context_id = cid;
+                            cid
+                        }
+                    };
+                    self.presentation_context_id = Some(cid);
+                    if self.value_type.is_none() {
+                        self.value_type = Some(pdata_value.value_type.clone());
+                    }
+                    let is_last = pdata_value.is_last;
+                    // provide the data as soon as it arrives
+                    // instead of holding back the whole message
+                    match self.reassembler.push(pdata_value) {
+                        Ok(Some(message)) => self.buffer.extend(message.data),
+                        Ok(None) => self.buffer.extend(self.reassembler.take_pending_data(cid)),
+                        Err(e) => {
+                            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+                        }
+                    }
+                    self.last_pdu = is_last;
+                }
+                if self.last_pdu && !self.pending_pdv.is_empty() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Incomplete presentation data value item after the last fragment",
+                    ));
+                }
+                Ok(())
+            }
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Unexpected PDU type",
+            )),
         }
     }
 
@@ -267,78 +469,87 @@ impl<'a, R> PDataReader<'a, R> {
     }
 }
 
-impl<R> Read for PDataReader<'_, R>
+impl<R> PDataReader<'_, R>
 where
     R: Read,
 {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        if self.buffer.is_empty() {
-            if self.last_pdu {
-                // reached the end of PData stream
-                return Ok(0);
-            }
-
-            let mut reader = BufReader::new(&mut self.stream);
-            let msg = loop {
-                let mut buf = Cursor::new(&self.read_buffer[..]);
-                match read_pdu(&mut buf, self.max_data_length, false)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?
-                {
-                    Some(pdu) => {
-                        self.read_buffer.advance(buf.position() as usize);
-                        break pdu;
-                    }
-                    None => {
-                        // Reset position
-                        buf.set_position(0)
-                    }
-                }
-                let recv = reader.fill_buf()?.to_vec();
-                reader.consume(recv.len());
-                self.read_buffer.extend_from_slice(&recv);
-                if recv.is_empty() {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        "Connection closed by peer",
-                    ));
-                }
+    /// Receive the next PDU into the buffer.
+    fn receive_pdu(&mut self) -> std::io::Result<()> {
+        let mut reader = BufReader::new(&mut self.stream);
+        let msg = loop {
+            let mut buf = Cursor::new(&self.read_buffer[..]);
+            let pending = if self.tolerate_split_pdv {
+                Some(&mut self.pending_pdv)
+            } else {
+                None
             };
-
-            match msg {
-                Pdu::PData { data } => {
-                    for pdata_value in data {
-                        self.presentation_context_id = match self.presentation_context_id {
-                            None => Some(pdata_value.presentation_context_id),
-                            Some(cid) if cid == pdata_value.presentation_context_id => Some(cid),
-                            Some(cid) => {
-                                warn!("Received PData value of presentation context {}, but should be {}", pdata_value.presentation_context_id, cid);
-                                Some(cid)
-                            }
-                        };
-                        self.buffer.extend(pdata_value.data);
-                        self.last_pdu = pdata_value.is_last;
-                    }
+            match read_pdu_pending(&mut buf, self.max_data_length, pending)? {
+                Some(pdu) => {
+                    self.read_buffer.advance(buf.position() as usize);
+                    break pdu;
                 }
-                _ => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::UnexpectedEof,
-                        "Unexpected PDU type",
-                    ))
+                None => {
+                    // Reset position
+                    buf.set_position(0)
                 }
             }
+            let recv = reader.fill_buf()?.to_vec();
+            reader.consume(recv.len());
+            self.read_buffer.extend_from_slice(&recv);
+            if recv.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "Connection closed by peer",
+                ));
+            }
+        };
+        self.check_split_pdv();
+        self.push_pdu(msg)
+    }
+}
+
+impl<R> Read for PDataReader<'_, R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // a PDU may carry no complete item
+        while self.buffer.is_empty() && !self.last_pdu {
+            self.receive_pdu()?;
+        }
+        if let Some(adapter) = self.pending_adapter() {
+            // the whole data set is needed before adapting it
+            while !self.last_pdu {
+                self.receive_pdu()?;
+            }
+            self.adapt_buffer(adapter)?;
         }
         Read::read(&mut self.buffer, buf)
     }
 }
 
+/// Read the next PDU from the given bytes,
+/// keeping incomplete presentation data value items in `pending` if given.
+fn read_pdu_pending(
+    buf: &mut Cursor<&[u8]>,
+    max_pdu_length: u32,
+    pending: Option<&mut Vec<u8>>,
+) -> std::io::Result<Option<Pdu>> {
+    match pending {
+        Some(pending) => read_pdu_split_pdv(buf, max_pdu_length, false, pending),
+        None => read_pdu(buf, max_pdu_length, false),
+    }
+    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+}
+
 /// Determine the maximum length of actual PDV data
 /// when encapsulated in a PDU with the given length property.
 /// Does not account for the first 2 bytes (type + reserved).
 #[inline]
-fn calculate_max_data_len_single(pdu_len: u32) -> u32 {
+pub(crate) fn calculate_max_data_len_single(pdu_len: u32) -> u32 {
     // data length: 4 bytes
     // control header: 2 bytes
-    pdu_len - 4 - 2
+    pdu_len.saturating_sub(4 + 2)
 }
 
 #[cfg(feature = "async")]
@@ -353,12 +564,11 @@ pub mod non_blocking {
     use tokio::io::{
         AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf,
     };
-    use tracing::warn;
-
-    use crate::{pdu::PDU_HEADER_SIZE, read_pdu, Pdu};
 
     pub use super::PDataReader;
-    use super::{calculate_max_data_len_single, setup_pdata_header};
+    use super::{
+        adapt_bytes, encode_pdata_part, read_pdu_pending, DatasetAdapter, PDataFragmenter,
+    };
 
     /// Enum representing state of the Async Writer
     enum WriteState {
@@ -376,6 +586,13 @@ pub mod non_blocking {
     /// will automatically split the incoming bytes
     /// into separate PDUs if they do not fit in a single one.
     ///
+    /// When the transfer syntax negotiated for the presentation context
+    /// has a data set adapter,
+    /// such as _Deflated Explicit VR Little Endian_,
+    /// the bytes written are passed through the adapter before sending.
+    /// In this case,
+    /// the data set is kept in memory until the writer is finished.
+    ///
     /// # Example
     ///
     /// Use an association's `send_pdata` method
@@ -417,10 +634,21 @@ pub mod non_blocking {
     /// ```
     #[must_use]
     pub struct AsyncPDataWriter<W: AsyncWrite + Unpin> {
+        /// the data of the next fragment
         buffer: Vec<u8>,
+        /// the encoded PDU being written
+        encoded: Vec<u8>,
         stream: W,
-        max_data_len: u32,
+        presentation_context_id: u8,
+        max_pdu_length: u32,
+        max_data_len: usize,
+        /// whether the last fragment was already sent
+        finished: bool,
         state: WriteState,
+        /// the data set adapter to pass the data set through, if any
+        adapter: Option<DatasetAdapter>,
+        /// the data set held back until finished, if adapted
+        unadapted: Vec<u8>,
     }
 
     #[cfg(feature = "async")]
@@ -432,37 +660,28 @@ pub mod non_blocking {
         ///
         /// `max_pdu_length` is the maximum value of the PDU-length property.
         pub(crate) fn new(stream: W, presentation_context_id: u8, max_pdu_length: u32) -> Self {
-            let max_data_length = calculate_max_data_len_single(max_pdu_length);
-            let mut buffer = Vec::with_capacity((max_data_length + PDU_HEADER_SIZE) as usize);
-            // initial buffer set up
-            buffer.extend([
-                // PDU-type + reserved byte
-                0x04,
-                0x00,
-                // full PDU length, unknown at this point
-                0xFF,
-                0xFF,
-                0xFF,
-                0xFF,
-                // presentation data length, unknown at this point
-                0xFF,
-                0xFF,
-                0xFF,
-                0xFF,
-                // presentation context id
-                presentation_context_id,
-                // message control header, unknown at this point
-                0xFF,
-            ]);
-
+            let max_data_len = PDataFragmenter::max_data_length(max_pdu_length);
             AsyncPDataWriter {
+                buffer: Vec::with_capacity(max_data_len),
+                encoded: Vec::new(),
                 stream,
-                max_data_len: max_data_length,
-                buffer,
+                presentation_context_id,
+                max_pdu_length,
+                max_data_len,
+                finished: false,
                 state: WriteState::Ready,
+                adapter: None,
+                unadapted: Vec::new(),
             }
         }
 
+        /// Pass the data set through the given data set adapter before sending,
+        /// as mandated by the transfer syntax of the presentation context.
+        pub(crate) fn with_dataset_adapter(mut self, adapter: Option<DatasetAdapter>) -> Self {
+            self.adapter = adapter;
+            self
+        }
+
         /// Declare to have finished sending P-Data fragments,
         /// thus emitting the last P-Data fragment PDU.
         ///
@@ -473,15 +692,30 @@ pub mod non_blocking {
         }
 
         async fn finish_impl(&mut self) -> std::io::Result<()> {
-            if !self.buffer.is_empty() {
-                // send last PDU
-                setup_pdata_header(&mut self.buffer, true);
-                if let Err(e) = self.stream.write_all(&self.buffer[..]).await {
-                    println!("Error: {:?}", e);
+            if let Some(adapter) = self.adapter.take() {
+                let data = adapt_bytes(adapter, &std::mem::take(&mut self.unadapted))?;
+                self.write_all(&data).await?;
+            }
+            if !self.finished {
+                // send the rest of a PDU interrupted mid-write
+                if let WriteState::Writing(pos) = self.state {
+                    let encoded = std::mem::take(&mut self.encoded);
+                    self.state = WriteState::Ready;
+                    self.stream.write_all(&encoded[pos..]).await?;
                 }
-                // clear buffer so that subsequent calls to `finish_impl`
+                // send last PDU,
+                // so that subsequent calls to `finish_impl`
                 // do not send any more PDUs
-                self.buffer.clear();
+                self.finished = true;
+                let encoded = encode_pdata_part(
+                    self.presentation_context_id,
+                    self.max_pdu_length,
+                    std::mem::take(&mut self.buffer),
+                    true,
+                )?;
+                if let Err(e) = self.stream.write_all(&encoded).await {
+                    println!("Error: {:?}", e);
+                }
             }
             Ok(())
         }
@@ -497,68 +731,59 @@ pub mod non_blocking {
             cx: &mut Context<'_>,
             buf: &[u8],
         ) -> Poll<std::result::Result<usize, std::io::Error>> {
+            if self.adapter.is_some() {
+                // hold back until finished
+                self.unadapted.extend_from_slice(buf);
+                return Poll::Ready(Ok(buf.len()));
+            }
+
             // Each call to `poll_write` on the underlying stream may or may not
-            // write the whole of `self.buffer`, therefore we need to keep track
+            // write the whole of `self.encoded`, therefore we need to keep track
             // of how much we've written, this is done in `self.state`
-            match self.state {
-                WriteState::Ready => {
-                    // If we're in ready state, we can prepare another PDU
-                    let total_len = self.max_data_len as usize + 12;
-                    if self.buffer.len() + buf.len() <= total_len {
-                        // Still have space in `self.buffer`, accumulate into buffer
-                        self.buffer.extend(buf);
-                        Poll::Ready(Ok(buf.len()))
-                    } else {
-                        // `self.buffer` is full, fill in the rest of the
-                        // buffer, prepare to send PDU
-                        let slice = &buf[..total_len - self.buffer.len()];
-                        self.buffer.extend(slice);
-                        debug_assert_eq!(self.buffer.len(), total_len);
-                        setup_pdata_header(&mut self.buffer, false);
-                        let this = self.get_mut();
-                        // Attempt to send PDU on wire
-                        match Pin::new(&mut this.stream).poll_write(cx, &this.buffer) {
+            let this = self.get_mut();
+            loop {
+                match this.state {
+                    WriteState::Ready => {
+                        if this.buffer.len() < this.max_data_len || buf.is_empty() {
+                            // Still have space in `self.buffer`, accumulate into buffer
+                            let len = buf.len().min(this.max_data_len - this.buffer.len());
+                            this.buffer.extend_from_slice(&buf[..len]);
+                            return Poll::Ready(Ok(len));
+                        }
+                        // `self.buffer` is full and more data is coming,
+                        // prepare to send it as a fragment which is not the last one
+                        this.encoded = encode_pdata_part(
+                            this.presentation_context_id,
+                            this.max_pdu_length,
+                            std::mem::replace(
+                                &mut this.buffer,
+                                Vec::with_capacity(this.max_data_len),
+                            ),
+                            false,
+                        )?;
+                        this.state = WriteState::Writing(0);
+                    }
+                    WriteState::Writing(pos) => {
+                        // Continue writing to stream from current position
+                        match Pin::new(&mut this.stream).poll_write(cx, &this.encoded[pos..]) {
+                            Poll::Ready(Ok(0)) => {
+                                return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+                            }
                             Poll::Ready(Ok(n)) => {
-                                if n == this.buffer.len() {
-                                    // If we wrote the whole buffer, reset `self.buffer`
-                                    this.buffer.truncate(12);
-                                    Poll::Ready(Ok(slice.len()))
+                                if n + pos == this.encoded.len() {
+                                    // If we wrote the whole PDU, change state back to ready
+                                    this.encoded.clear();
+                                    this.state = WriteState::Ready;
                                 } else {
-                                    // Otherwise keep track of how much we wrote and change state to Writing
-                                    this.state = WriteState::Writing(n);
-                                    Poll::Pending
+                                    // Otherwise add to current position
+                                    this.state = WriteState::Writing(n + pos);
                                 }
                             }
-                            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
-                            Poll::Pending => {
-                                // Nothing was written yet, change state to writing at position 0
-                                this.state = WriteState::Writing(0);
-                                Poll::Pending
-                            }
+                            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                            Poll::Pending => return Poll::Pending,
                         }
                     }
                 }
-                WriteState::Writing(pos) => {
-                    // Continue writing to stream from current position
-                    let buflen = self.buffer.len();
-                    let this = self.get_mut();
-                    match Pin::new(&mut this.stream).poll_write(cx, &this.buffer[pos..]) {
-                        Poll::Ready(Ok(n)) => {
-                            if (n + pos) == this.buffer.len() {
-                                // If we wrote the whole buffer, reset `self.buffer` and change state back to ready
-                                this.buffer.truncate(12);
-                                this.state = WriteState::Ready;
-                                Poll::Ready(Ok(buflen - 12))
-                            } else {
-                                // Otherwise add to current position
-                                this.state = WriteState::Writing(n + pos);
-                                Poll::Pending
-                            }
-                        }
-                        Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
-                        Poll::Pending => Poll::Pending,
-                    }
-                }
             }
         }
 
@@ -594,78 +819,69 @@ pub mod non_blocking {
         }
     }
 
+    impl<R> PDataReader<'_, R>
+    where
+        R: AsyncRead + Unpin,
+    {
+        /// Receive the next PDU into the buffer.
+        fn poll_receive_pdu(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            let mut reader = BufReader::new(&mut self.stream);
+            let msg = loop {
+                let mut buf = Cursor::new(&self.read_buffer[..]);
+                let pending = if self.tolerate_split_pdv {
+                    Some(&mut self.pending_pdv)
+                } else {
+                    None
+                };
+                match read_pdu_pending(&mut buf, self.max_data_length, pending)? {
+                    Some(pdu) => {
+                        self.read_buffer.advance(buf.position() as usize);
+                        break pdu;
+                    }
+                    None => {
+                        // Reset position
+                        buf.set_position(0)
+                    }
+                }
+                let recv = ready!(Pin::new(&mut reader).poll_fill_buf(cx))?.to_vec();
+                reader.consume(recv.len());
+                self.read_buffer.extend_from_slice(&recv);
+                if recv.is_empty() {
+                    return Poll::Ready(Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        "Connection closed by peer",
+                    )));
+                }
+            };
+            self.check_split_pdv();
+            Poll::Ready(self.push_pdu(msg))
+        }
+    }
+
     impl<R> AsyncRead for PDataReader<'_, R>
     where
         R: AsyncRead + Unpin,
     {
         fn poll_read(
-            mut self: Pin<&mut Self>,
+            self: Pin<&mut Self>,
             cx: &mut Context<'_>,
             buf: &mut ReadBuf,
         ) -> Poll<std::io::Result<()>> {
-            if self.buffer.is_empty() {
-                if self.last_pdu {
-                    return Poll::Ready(Ok(()));
-                }
-                let Self {
-                    ref mut stream,
-                    ref mut read_buffer,
-                    ref max_data_length,
-                    ..
-                } = &mut *self;
-                let mut reader = BufReader::new(stream);
-                let msg = loop {
-                    let mut buf = Cursor::new(&read_buffer[..]);
-                    match read_pdu(&mut buf, *max_data_length, false)
-                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?
-                    {
-                        Some(pdu) => {
-                            read_buffer.advance(buf.position() as usize);
-                            break pdu;
-                        }
-                        None => {
-                            // Reset position
-                            buf.set_position(0)
-                        }
-                    }
-                    let recv = ready!(Pin::new(&mut reader).poll_fill_buf(cx))?.to_vec();
-                    reader.consume(recv.len());
-                    read_buffer.extend_from_slice(&recv);
-                    if recv.is_empty() {
-                        return Poll::Ready(Err(std::io::Error::new(
-                            std::io::ErrorKind::Other,
-                            "Connection closed by peer",
-                        )));
-                    }
-                };
-                match msg {
-                    Pdu::PData { data } => {
-                        for pdata_value in data {
-                            self.presentation_context_id = match self.presentation_context_id {
-                                None => Some(pdata_value.presentation_context_id),
-                                Some(cid) if cid == pdata_value.presentation_context_id => {
-                                    Some(cid)
-                                }
-                                Some(cid) => {
-                                    warn!("Received PData value of presentation context {}, but should be {}", pdata_value.presentation_context_id, cid);
-                                    Some(cid)
-                                }
-                            };
-                            self.buffer.extend(pdata_value.data);
-                            self.last_pdu = pdata_value.is_last;
-                        }
-                    }
-                    _ => {
-                        return Poll::Ready(Err(std::io::Error::new(
-                            std::io::ErrorKind::UnexpectedEof,
-                            "Unexpected PDU type",
-                        )))
-                    }
+            let this = self.get_mut();
+            // a PDU may carry no complete item
+            while this.buffer.is_empty() && !this.last_pdu {
+                ready!(this.poll_receive_pdu(cx))?;
+            }
+            if let Some(adapter) = this.pending_adapter() {
+                // the whole data set is needed before adapting it
+                while !this.last_pdu {
+                    ready!(this.poll_receive_pdu(cx))?;
                 }
+                this.adapt_buffer(adapter)?;
             }
-            let len = std::cmp::min(self.buffer.len(), buf.remaining());
+            let len = std::cmp::min(this.buffer.len(), buf.remaining());
             for _ in 0..len {
-                buf.put_u8(self.buffer.pop_front().unwrap());
+                buf.put_u8(this.buffer.pop_front().unwrap());
             }
             Poll::Ready(Ok(()))
         }
@@ -677,7 +893,10 @@ mod tests {
     use std::io::{Read, Write};
 
     use crate::association::pdata::PDataWriter;
-    use crate::pdu::{read_pdu, Pdu, MINIMUM_PDU_SIZE, PDU_HEADER_SIZE};
+    use crate::pdu::{
+        read_pdu, read_pdu_split_pdv, Pdu, ReadError, MAXIMUM_PDU_SIZE, MINIMUM_PDU_SIZE,
+        PDU_HEADER_SIZE,
+    };
     use crate::pdu::{PDataValue, PDataValueType};
     use crate::write_pdu;
 
@@ -722,6 +941,61 @@ mod tests {
         assert_eq!(cursor.len(), 0);
     }
 
+    #[test]
+    fn test_write_pdata_without_pdu_length_limit() {
+        let my_data: Vec<_> = (0..10_000).map(|x: u32| x as u8).collect();
+
+        let mut buf = Vec::new();
+        {
+            let mut writer = PDataWriter::new(&mut buf, 1, 0);
+            writer.write_all(&my_data).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let mut cursor = &buf[..];
+        match read_pdu(&mut cursor, MAXIMUM_PDU_SIZE, true).unwrap() {
+            Some(Pdu::PData { data }) => {
+                assert_eq!(data.len(), 1);
+                assert!(data[0].is_last);
+                assert_eq!(data[0].data, my_data);
+            }
+            pdu => panic!("Expected PData, got {:?}", pdu),
+        }
+        assert_eq!(cursor.len(), 0);
+    }
+
+    #[test]
+    fn test_read_pdata_with_mixed_value_types() {
+        let mut pdu_stream = Vec::new();
+        write_pdu(
+            &mut pdu_stream,
+            &Pdu::PData {
+                data: vec![
+                    PDataValue {
+                        value_type: PDataValueType::Command,
+                        data: vec![1, 2],
+                        presentation_context_id: 1,
+                        is_last: false,
+                    },
+                    PDataValue {
+                        value_type: PDataValueType::Data,
+                        data: vec![3, 4],
+                        presentation_context_id: 1,
+                        is_last: true,
+                    },
+                ],
+            },
+        )
+        .unwrap();
+
+        let mut buf = Vec::new();
+        let mut read_buf = BytesMut::new();
+        let err = PDataReader::new(&pdu_stream[..], MINIMUM_PDU_SIZE, &mut read_buf)
+            .read_to_end(&mut buf)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
     #[cfg(feature = "async")]
     #[tokio::test(flavor = "multi_thread")]
     async fn test_async_write_pdata_and_finish() {
@@ -1002,4 +1276,241 @@ mod tests {
         }
         assert_eq!(buf, my_data);
     }
+
+    /// Encode a presentation data value item.
+    fn pdv_item(presentation_context_id: u8, header: u8, data: &[u8]) -> Vec<u8> {
+        let mut item = ((data.len() + 2) as u32).to_be_bytes().to_vec();
+        item.push(presentation_context_id);
+        item.push(header);
+        item.extend_from_slice(data);
+        item
+    }
+
+    /// Encode P-DATA-TF PDUs with the given bytes as their items,
+    /// regardless of item boundaries.
+    fn pdata_pdus<'a>(bodies: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+        let mut out = Vec::new();
+        for body in bodies {
+            out.extend_from_slice(&[0x04, 0x00]);
+            out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            out.extend_from_slice(body);
+        }
+        out
+    }
+
+    #[test]
+    fn test_read_pdata_with_split_items() {
+        let my_data: Vec<_> = (0..300).map(|x: u32| x as u8).collect();
+        let mut items = pdv_item(1, 0x00, &my_data[..100]);
+        items.extend(pdv_item(1, 0x00, &my_data[100..200]));
+        items.extend(pdv_item(1, 0x02, &my_data[200..]));
+
+        // split the header of the second item (starts at 106),
+        // then the value of the third item (starts at 212),
+        // with a PDU entirely within it
+        let stream = pdata_pdus(vec![
+            &items[..109],
+            &items[109..250],
+            &items[250..260],
+            &items[260..],
+        ]);
+
+        let mut buf = Vec::new();
+        let mut read_buf = BytesMut::new();
+        let mut reader = PDataReader::new(&stream[..], MINIMUM_PDU_SIZE, &mut read_buf);
+        reader.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, my_data);
+        assert_eq!(reader.split_pdv_count(), 3);
+    }
+
+    #[test]
+    fn test_read_pdata_with_split_items_strict() {
+        let my_data: Vec<_> = (0..200).map(|x: u32| x as u8).collect();
+        let mut items = pdv_item(1, 0x00, &my_data[..100]);
+        items.extend(pdv_item(1, 0x02, &my_data[100..]));
+        let stream = pdata_pdus(vec![&items[..109], &items[109..]]);
+
+        // the stand-alone PDU reader rejects the first PDU
+        let mut cursor = &stream[..];
+        assert!(matches!(
+            read_pdu(&mut cursor, MINIMUM_PDU_SIZE, true),
+            Err(ReadError::IncompletePdvItem { length: 3, .. })
+        ));
+
+        let mut buf = Vec::new();
+        let mut read_buf = BytesMut::new();
+        let err = PDataReader::new(&stream[..], MINIMUM_PDU_SIZE, &mut read_buf)
+            .tolerate_split_pdv(false)
+            .read_to_end(&mut buf)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+    }
+
+    #[test]
+    fn test_read_pdata_with_incomplete_item_after_last() {
+        let my_data: Vec<_> = (0..200).map(|x: u32| x as u8).collect();
+        let mut items = pdv_item(1, 0x02, &my_data[..100]);
+        items.extend(pdv_item(1, 0x00, &my_data[100..]));
+        let stream = pdata_pdus(vec![&items[..120]]);
+
+        let mut buf = Vec::new();
+        let mut read_buf = BytesMut::new();
+        let err = PDataReader::new(&stream[..], MINIMUM_PDU_SIZE, &mut read_buf)
+            .read_to_end(&mut buf)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_read_split_item_followed_by_other_pdu() {
+        let items = pdv_item(1, 0x02, &[0; 100]);
+        let mut stream = pdata_pdus(vec![&items[..50]]);
+        stream.extend_from_slice(&[0x05, 0x00, 0, 0, 0, 4, 0, 0, 0, 0]);
+
+        let mut pending = Vec::new();
+        let mut cursor = &stream[..];
+        assert_eq!(
+            read_pdu_split_pdv(&mut cursor, MINIMUM_PDU_SIZE, true, &mut pending).unwrap(),
+            Some(Pdu::PData { data: vec![] })
+        );
+        assert_eq!(pending, &items[..50]);
+        assert!(matches!(
+            read_pdu_split_pdv(&mut cursor, MINIMUM_PDU_SIZE, true, &mut pending),
+            Err(ReadError::IncompletePdvItem { length: 50, .. })
+        ));
+    }
+
+    #[test]
+    fn test_read_split_item_too_large() {
+        // an item claiming almost 4 GiB, of which only a few bytes are sent
+        let mut item = u32::MAX.to_be_bytes().to_vec();
+        item.extend_from_slice(&[1, 0x00, 0, 0, 0, 0]);
+        let stream = pdata_pdus(vec![&item[..]]);
+
+        let mut pending = Vec::new();
+        let mut cursor = &stream[..];
+        assert!(matches!(
+            read_pdu_split_pdv(&mut cursor, MINIMUM_PDU_SIZE, false, &mut pending),
+            Err(ReadError::PdvItemTooLarge {
+                length: u32::MAX,
+                max_pdu_length: MAXIMUM_PDU_SIZE,
+                ..
+            })
+        ));
+
+        // an item which fits in a PDU may be completed later
+        let mut item = (MAXIMUM_PDU_SIZE - 4).to_be_bytes().to_vec();
+        item.extend_from_slice(&[1, 0x00, 0, 0, 0, 0]);
+        let stream = pdata_pdus(vec![&item[..]]);
+        let mut cursor = &stream[..];
+        assert_eq!(
+            read_pdu_split_pdv(&mut cursor, MINIMUM_PDU_SIZE, false, &mut pending).unwrap(),
+            Some(Pdu::PData { data: vec![] })
+        );
+        assert_eq!(pending, item);
+    }
+
+    #[test]
+    fn test_dataset_adapter_lookup() {
+        use crate::pdu::{PresentationContextResult, PresentationContextResultReason};
+
+        let presentation_contexts = [
+            PresentationContextResult {
+                id: 1,
+                reason: PresentationContextResultReason::Acceptance,
+                transfer_syntax: "1.2.840.10008.1.2".to_string(),
+            },
+            PresentationContextResult {
+                id: 3,
+                reason: PresentationContextResultReason::Acceptance,
+                transfer_syntax: "1.2.840.10008.1.2.1.99\0".to_string(),
+            },
+        ];
+
+        assert!(super::dataset_adapter(&presentation_contexts, 1).is_none());
+        assert!(super::dataset_adapter(&presentation_contexts, 5).is_none());
+        let adapter = super::dataset_adapter(&presentation_contexts, 3);
+        let adapters = super::dataset_adapters(&presentation_contexts);
+        if cfg!(feature = "deflate") {
+            assert!(adapter.is_some());
+            assert_eq!(adapters.len(), 1);
+            assert_eq!(adapters[0].0, 3);
+        } else {
+            assert!(adapter.is_none());
+            assert!(adapters.is_empty());
+        }
+    }
+
+    /// Data sets are deflated when written and inflated when read,
+    /// but commands are read as received
+    #[cfg(feature = "deflate")]
+    #[test]
+    fn test_write_and_read_deflated_pdata() {
+        use crate::pdu::{PresentationContextResult, PresentationContextResultReason};
+
+        let presentation_context_id = 3;
+        let adapter = super::dataset_adapter(
+            &[PresentationContextResult {
+                id: presentation_context_id,
+                reason: PresentationContextResultReason::Acceptance,
+                transfer_syntax: "1.2.840.10008.1.2.1.99".to_string(),
+            }],
+            presentation_context_id,
+        )
+        .unwrap();
+
+        let my_data: Vec<_> = (0..9000).map(|x: u32| (x / 100) as u8).collect();
+
+        let mut pdu_stream = Vec::new();
+        write_pdu(
+            &mut pdu_stream,
+            &Pdu::PData {
+                data: vec![PDataValue {
+                    value_type: PDataValueType::Command,
+                    data: vec![1, 2, 3, 4],
+                    presentation_context_id,
+                    is_last: true,
+                }],
+            },
+        )
+        .unwrap();
+        {
+            let mut writer =
+                PDataWriter::new(&mut pdu_stream, presentation_context_id, MINIMUM_PDU_SIZE)
+                    .with_dataset_adapter(Some(adapter));
+            writer.write_all(&my_data).unwrap();
+            writer.finish().unwrap();
+        }
+
+        // the data set was compressed into a single PDU
+        let mut cursor = &pdu_stream[..];
+        let _command = read_pdu(&mut cursor, MINIMUM_PDU_SIZE, true).unwrap();
+        match read_pdu(&mut cursor, MINIMUM_PDU_SIZE, true).unwrap() {
+            Some(Pdu::PData { data }) => {
+                assert_eq!(data.len(), 1);
+                assert_eq!(data[0].value_type, PDataValueType::Data);
+                assert!(data[0].is_last);
+                assert!(data[0].data.len() < my_data.len() / 10);
+            }
+            pdu => panic!("Expected PData, got {:?}", pdu),
+        }
+        assert!(cursor.is_empty());
+
+        let mut read_buf = BytesMut::new();
+        let mut stream = &pdu_stream[..];
+
+        let mut command = Vec::new();
+        PDataReader::new(&mut stream, MINIMUM_PDU_SIZE, &mut read_buf)
+            .with_dataset_adapters(vec![(presentation_context_id, adapter)])
+            .read_to_end(&mut command)
+            .unwrap();
+        assert_eq!(command, [1, 2, 3, 4]);
+
+        let mut buf = Vec::new();
+        PDataReader::new(&mut stream, MINIMUM_PDU_SIZE, &mut read_buf)
+            .with_dataset_adapters(vec![(presentation_context_id, adapter)])
+            .read_to_end(&mut buf)
+            .unwrap();
+        assert_eq!(buf, my_data);
+    }
 }
@@ -0,0 +1,686 @@
+//! Pure building blocks for exchanging whole messages
+//! over P-Data PDUs.
+//!
+//! A DIMSE message (either a command set or a data set)
+//! may be split into multiple presentation data values (PDVs),
+//! which in turn may be spread across several P-DATA-TF PDUs.
+//! [`PDataReassembler`] collects these fragments back into complete messages,
+//! while [`PDataFragmenter`] does the opposite,
+//! splitting a message into PDUs which respect a maximum PDU length.
+//!
+//! Neither of them performs any I/O,
+//! so they can be combined with any transport.
+
+use snafu::{ensure, Backtrace, Snafu};
+
+use crate::pdu::{PDataValue, PDataValueType, Pdu, MAXIMUM_PDU_SIZE};
+
+use super::pdata::calculate_max_data_len_single;
+
+/// An error which may occur when reassembling P-Data value fragments.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum ReassemblyError {
+    /// A fragment arrived on a presentation context
+    /// before the last fragment of the message pending on that context.
+    #[snafu(display(
+        "received {:?} fragment on presentation context {} before the last {:?} fragment",
+        received,
+        presentation_context_id,
+        pending
+    ))]
+    UnexpectedFragment {
+        /// the presentation context of the fragment
+        presentation_context_id: u8,
+        /// the value type of the incomplete message
+        pending: PDataValueType,
+        /// the value type of the fragment received
+        received: PDataValueType,
+        backtrace: Backtrace,
+    },
+    /// The message would exceed the maximum message length.
+    #[snafu(display(
+        "message on presentation context {} exceeds the maximum length of {} bytes",
+        presentation_context_id,
+        max_length
+    ))]
+    MessageTooLong {
+        /// the presentation context of the message
+        presentation_context_id: u8,
+        /// the maximum message length configured
+        max_length: usize,
+        backtrace: Backtrace,
+    },
+}
+
+type Result<T, E = ReassemblyError> = std::result::Result<T, E>;
+
+/// A complete command or data set message
+/// exchanged via P-Data PDUs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PDataMessage {
+    /// the presentation context of the message
+    pub presentation_context_id: u8,
+    /// whether the message is a command or a data set
+    pub value_type: PDataValueType,
+    /// the full message data
+    pub data: Vec<u8>,
+}
+
+impl PDataMessage {
+    /// Check whether this message is a command.
+    pub fn is_command(&self) -> bool {
+        self.value_type == PDataValueType::Command
+    }
+}
+
+/// A reassembler of P-Data value fragments into complete messages.
+///
+/// Fragments are fed one by one via [`push`](Self::push),
+/// usually taken from the data of [`Pdu::PData`] in the order received.
+/// Messages on different presentation contexts may be interleaved,
+/// but only one message can be pending on each presentation context,
+/// so a command must be complete
+/// before the data set on the same context begins, and vice versa.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_ul::association::PDataReassembler;
+/// # use dicom_ul::pdu::{PDataValue, PDataValueType};
+/// let mut reassembler = PDataReassembler::new().max_message_length(1024);
+///
+/// let message = reassembler.push(PDataValue {
+///     presentation_context_id: 1,
+///     value_type: PDataValueType::Data,
+///     is_last: false,
+///     data: vec![1, 2],
+/// })?;
+/// assert!(message.is_none());
+///
+/// let message = reassembler.push(PDataValue {
+///     presentation_context_id: 1,
+///     value_type: PDataValueType::Data,
+///     is_last: true,
+///     data: vec![3, 4],
+/// })?;
+/// let message = message.unwrap();
+/// assert_eq!(message.presentation_context_id, 1);
+/// assert!(!message.is_command());
+/// assert_eq!(message.data, [1, 2, 3, 4]);
+/// # Ok::<_, dicom_ul::association::ReassemblyError>(())
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct PDataReassembler {
+    /// the maximum length of a single message
+    max_message_length: Option<usize>,
+    /// the incomplete messages, at most one per presentation context
+    pending: Vec<PDataMessage>,
+}
+
+impl PDataReassembler {
+    /// Create a new reassembler without a message length limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define the maximum length of a reassembled message in bytes.
+    ///
+    /// Fragments which would make a message longer than this
+    /// are rejected with [`ReassemblyError::MessageTooLong`].
+    pub fn max_message_length(mut self, max_message_length: usize) -> Self {
+        self.max_message_length = Some(max_message_length);
+        self
+    }
+
+    /// Feed the next P-Data value fragment.
+    ///
+    /// Returns the complete message
+    /// if this was the last fragment of a message.
+    ///
+    /// On error,
+    /// the message pending on the fragment's presentation context
+    /// is discarded along with the fragment,
+    /// and messages on other presentation contexts are kept.
+    pub fn push(&mut self, value: PDataValue) -> Result<Option<PDataMessage>> {
+        let PDataValue {
+            presentation_context_id,
+            value_type,
+            is_last,
+            data,
+        } = value;
+
+        let mut message = match self
+            .pending
+            .iter()
+            .position(|m| m.presentation_context_id == presentation_context_id)
+        {
+            Some(i) => self.pending.swap_remove(i),
+            None => PDataMessage {
+                presentation_context_id,
+                value_type: value_type.clone(),
+                data: Vec::new(),
+            },
+        };
+
+        ensure!(
+            message.value_type == value_type,
+            UnexpectedFragmentSnafu {
+                presentation_context_id,
+                pending: message.value_type,
+                received: value_type,
+            }
+        );
+        if let Some(max_length) = self.max_message_length {
+            ensure!(
+                message.data.len() + data.len() <= max_length,
+                MessageTooLongSnafu {
+                    presentation_context_id,
+                    max_length,
+                }
+            );
+        }
+
+        if message.data.is_empty() {
+            message.data = data;
+        } else {
+            message.data.extend(data);
+        }
+
+        if is_last {
+            Ok(Some(message))
+        } else {
+            self.pending.push(message);
+            Ok(None)
+        }
+    }
+
+    /// Feed all P-Data value fragments of a PDU in order,
+    /// collecting the messages completed by them.
+    ///
+    /// Stops at the first error,
+    /// in which case the remaining fragments are not processed.
+    pub fn push_all<I>(&mut self, values: I) -> Result<Vec<PDataMessage>>
+    where
+        I: IntoIterator<Item = PDataValue>,
+    {
+        let mut messages = Vec::new();
+        for value in values {
+            if let Some(message) = self.push(value)? {
+                messages.push(message);
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Check whether there are no incomplete messages.
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Discard all incomplete messages.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Take the data collected so far
+    /// for the message pending on the given presentation context,
+    /// which remains pending.
+    pub(crate) fn take_pending_data(&mut self, presentation_context_id: u8) -> Vec<u8> {
+        self.pending
+            .iter_mut()
+            .find(|m| m.presentation_context_id == presentation_context_id)
+            .map(|m| std::mem::take(&mut m.data))
+            .unwrap_or_default()
+    }
+}
+
+/// An iterator which splits a message into P-Data PDUs
+/// ready to be sent.
+///
+/// Each PDU contains a single P-Data value,
+/// and the PDU length property never exceeds the given maximum PDU length.
+/// A maximum PDU length of 0 means that there is no limit,
+/// in which case the maximum size admitted by the standard is used.
+/// The last fragment is flagged as such.
+/// An empty message is sent as a single empty fragment.
+///
+/// # Example
+///
+/// ```
+/// # use dicom_ul::association::{PDataFragmenter, PDataMessage};
+/// # use dicom_ul::pdu::{PDataValueType, Pdu, MINIMUM_PDU_SIZE};
+/// let message = PDataMessage {
+///     presentation_context_id: 1,
+///     value_type: PDataValueType::Data,
+///     data: vec![0; 10_000],
+/// };
+/// let pdus: Vec<Pdu> = PDataFragmenter::new(message, MINIMUM_PDU_SIZE).collect();
+/// assert_eq!(pdus.len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PDataFragmenter {
+    /// the message to send
+    message: PDataMessage,
+    /// the maximum length of the data in each fragment
+    max_data_length: usize,
+    /// the position of the next fragment in the message data
+    position: usize,
+    /// whether the last fragment was already produced
+    done: bool,
+    /// whether the message ends with the last fragment
+    is_last_part: bool,
+}
+
+impl PDataFragmenter {
+    /// Create a new fragmenter of the given message.
+    ///
+    /// `max_pdu_length` is the maximum value of the PDU-length property,
+    /// as negotiated with the receiving node.
+    pub fn new(message: PDataMessage, max_pdu_length: u32) -> Self {
+        Self::new_part(message, max_pdu_length, true)
+    }
+
+    /// Create a new fragmenter of a part of a message,
+    /// so that the last fragment is only flagged as such
+    /// if `is_last_part` is true.
+    pub(crate) fn new_part(message: PDataMessage, max_pdu_length: u32, is_last_part: bool) -> Self {
+        PDataFragmenter {
+            message,
+            max_data_length: Self::max_data_length(max_pdu_length),
+            position: 0,
+            done: false,
+            is_last_part,
+        }
+    }
+
+    /// Determine the maximum length of the data in each fragment
+    /// for the given maximum PDU length.
+    pub(crate) fn max_data_length(max_pdu_length: u32) -> usize {
+        // treat 0 as the maximum size admitted by the standard
+        let max_pdu_length = if max_pdu_length == 0 {
+            MAXIMUM_PDU_SIZE
+        } else {
+            max_pdu_length
+        };
+        calculate_max_data_len_single(max_pdu_length).max(1) as usize
+    }
+
+    fn remaining_fragments(&self) -> usize {
+        if self.done {
+            0
+        } else {
+            let remaining = self.message.data.len() - self.position;
+            ((remaining + self.max_data_length - 1) / self.max_data_length).max(1)
+        }
+    }
+}
+
+impl Iterator for PDataFragmenter {
+    type Item = Pdu;
+
+    fn next(&mut self) -> Option<Pdu> {
+        if self.done {
+            return None;
+        }
+        let end = self
+            .message
+            .data
+            .len()
+            .min(self.position + self.max_data_length);
+        let data = self.message.data[self.position..end].to_vec();
+        self.position = end;
+        self.done = end == self.message.data.len();
+
+        Some(Pdu::PData {
+            data: vec![PDataValue {
+                presentation_context_id: self.message.presentation_context_id,
+                value_type: self.message.value_type.clone(),
+                is_last: self.done && self.is_last_part,
+                data,
+            }],
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining_fragments();
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for PDataFragmenter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdu::{MINIMUM_PDU_SIZE, PDU_HEADER_SIZE};
+    use crate::write_pdu;
+
+    fn data_value(
+        presentation_context_id: u8,
+        value_type: PDataValueType,
+        is_last: bool,
+        data: &[u8],
+    ) -> PDataValue {
+        PDataValue {
+            presentation_context_id,
+            value_type,
+            is_last,
+            data: data.to_vec(),
+        }
+    }
+
+    /// Enumerate all ways to split `len` bytes
+    /// into non-empty consecutive fragments,
+    /// as lists of fragment end positions.
+    fn all_splits(len: usize) -> Vec<Vec<usize>> {
+        (0..1_u32 << (len - 1))
+            .map(|mask| {
+                (1..len)
+                    .filter(|i| mask & (1 << (i - 1)) != 0)
+                    .chain(std::iter::once(len))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Enumerate all interleavings of two sequences of the given lengths,
+    /// as lists of which sequence to take from next.
+    fn all_interleavings(a: usize, b: usize) -> Vec<Vec<bool>> {
+        if a == 0 {
+            return vec![vec![false; b]];
+        }
+        if b == 0 {
+            return vec![vec![true; a]];
+        }
+        let mut out = Vec::new();
+        for mut rest in all_interleavings(a - 1, b) {
+            rest.insert(0, true);
+            out.push(rest);
+        }
+        for mut rest in all_interleavings(a, b - 1) {
+            rest.insert(0, false);
+            out.push(rest);
+        }
+        out
+    }
+
+    fn fragments(
+        presentation_context_id: u8,
+        value_type: PDataValueType,
+        data: &[u8],
+        ends: &[usize],
+    ) -> Vec<PDataValue> {
+        let mut start = 0;
+        ends.iter()
+            .map(|&end| {
+                let value = data_value(
+                    presentation_context_id,
+                    value_type.clone(),
+                    end == data.len(),
+                    &data[start..end],
+                );
+                start = end;
+                value
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reassemble_all_fragment_boundaries() {
+        let data: Vec<u8> = (1..=9).collect();
+        let splits = all_splits(data.len());
+        assert_eq!(splits.len(), 256);
+
+        for ends in splits {
+            let mut reassembler = PDataReassembler::new();
+            let values = fragments(3, PDataValueType::Command, &data, &ends);
+            let count = values.len();
+            for (i, value) in values.into_iter().enumerate() {
+                let message = reassembler.push(value).unwrap();
+                if i + 1 < count {
+                    assert_eq!(message, None, "early message with splits {:?}", ends);
+                    assert!(!reassembler.is_idle());
+                } else {
+                    assert_eq!(
+                        message,
+                        Some(PDataMessage {
+                            presentation_context_id: 3,
+                            value_type: PDataValueType::Command,
+                            data: data.clone(),
+                        }),
+                        "bad message with splits {:?}",
+                        ends
+                    );
+                }
+            }
+            assert!(reassembler.is_idle());
+        }
+    }
+
+    #[test]
+    fn reassemble_interleaved_contexts() {
+        let data_a: Vec<u8> = (0..5).collect();
+        let data_b: Vec<u8> = (100..104).collect();
+
+        for ends_a in all_splits(data_a.len()) {
+            for ends_b in all_splits(data_b.len()) {
+                let values_a = fragments(1, PDataValueType::Data, &data_a, &ends_a);
+                let values_b = fragments(3, PDataValueType::Command, &data_b, &ends_b);
+                for order in all_interleavings(values_a.len(), values_b.len()) {
+                    let mut values_a = values_a.iter().cloned();
+                    let mut values_b = values_b.iter().cloned();
+                    let values = order.iter().map(|&take_a| {
+                        if take_a {
+                            values_a.next().unwrap()
+                        } else {
+                            values_b.next().unwrap()
+                        }
+                    });
+
+                    let mut reassembler = PDataReassembler::new();
+                    let messages = reassembler.push_all(values).unwrap();
+                    assert!(reassembler.is_idle());
+                    assert_eq!(messages.len(), 2);
+                    // messages come out in the order in which they were completed
+                    let (a, b) = if order.last() == Some(&true) {
+                        (&messages[1], &messages[0])
+                    } else {
+                        (&messages[0], &messages[1])
+                    };
+                    assert_eq!(a.presentation_context_id, 1);
+                    assert!(!a.is_command());
+                    assert_eq!(a.data, data_a);
+                    assert_eq!(b.presentation_context_id, 3);
+                    assert!(b.is_command());
+                    assert_eq!(b.data, data_b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reassemble_command_then_data() {
+        let mut reassembler = PDataReassembler::new();
+        let messages = reassembler
+            .push_all([
+                data_value(1, PDataValueType::Command, false, &[1, 2]),
+                data_value(1, PDataValueType::Command, true, &[3]),
+                data_value(1, PDataValueType::Data, false, &[4]),
+                data_value(1, PDataValueType::Data, true, &[5, 6]),
+            ])
+            .unwrap();
+        assert_eq!(
+            messages,
+            vec![
+                PDataMessage {
+                    presentation_context_id: 1,
+                    value_type: PDataValueType::Command,
+                    data: vec![1, 2, 3],
+                },
+                PDataMessage {
+                    presentation_context_id: 1,
+                    value_type: PDataValueType::Data,
+                    data: vec![4, 5, 6],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_fragment_before_last() {
+        let mut reassembler = PDataReassembler::new();
+        assert_eq!(
+            reassembler
+                .push(data_value(1, PDataValueType::Command, false, &[1, 2]))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            reassembler
+                .push(data_value(3, PDataValueType::Data, false, &[9]))
+                .unwrap(),
+            None
+        );
+
+        // data on context 1 before the command was complete
+        let err = reassembler
+            .push(data_value(1, PDataValueType::Data, true, &[3, 4]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ReassemblyError::UnexpectedFragment {
+                presentation_context_id: 1,
+                pending: PDataValueType::Command,
+                received: PDataValueType::Data,
+                ..
+            }
+        ));
+
+        // the pending command was discarded,
+        // the message on context 3 was kept
+        let message = reassembler
+            .push(data_value(1, PDataValueType::Command, true, &[5]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.data, [5]);
+        let message = reassembler
+            .push(data_value(3, PDataValueType::Data, true, &[10]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.data, [9, 10]);
+        assert!(reassembler.is_idle());
+    }
+
+    #[test]
+    fn reject_message_too_long() {
+        let mut reassembler = PDataReassembler::new().max_message_length(4);
+
+        // exactly at the limit
+        let messages = reassembler
+            .push_all([
+                data_value(1, PDataValueType::Data, false, &[1, 2]),
+                data_value(1, PDataValueType::Data, true, &[3, 4]),
+            ])
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+
+        // over the limit
+        assert_eq!(
+            reassembler
+                .push(data_value(1, PDataValueType::Data, false, &[1, 2, 3]))
+                .unwrap(),
+            None
+        );
+        let err = reassembler
+            .push(data_value(1, PDataValueType::Data, true, &[4, 5]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ReassemblyError::MessageTooLong {
+                presentation_context_id: 1,
+                max_length: 4,
+                ..
+            }
+        ));
+        assert!(reassembler.is_idle());
+    }
+
+    #[test]
+    fn fragment_and_reassemble() {
+        let max_pdu_length = MINIMUM_PDU_SIZE;
+        let max_data_length = (max_pdu_length - 6) as usize;
+
+        for len in [
+            0,
+            1,
+            max_data_length - 1,
+            max_data_length,
+            max_data_length + 1,
+            3 * max_data_length + 5,
+        ] {
+            let message = PDataMessage {
+                presentation_context_id: 5,
+                value_type: PDataValueType::Data,
+                data: (0..len).map(|i| i as u8).collect(),
+            };
+            let fragmenter = PDataFragmenter::new(message.clone(), max_pdu_length);
+            let expected_count = ((len + max_data_length - 1) / max_data_length).max(1);
+            assert_eq!(fragmenter.len(), expected_count);
+
+            let pdus: Vec<_> = fragmenter.collect();
+            assert_eq!(pdus.len(), expected_count);
+
+            let mut reassembler = PDataReassembler::new();
+            let mut messages = Vec::new();
+            for (i, pdu) in pdus.into_iter().enumerate() {
+                // check the encoded PDU length
+                let mut bytes = Vec::new();
+                write_pdu(&mut bytes, &pdu).unwrap();
+                assert!(bytes.len() <= (max_pdu_length + PDU_HEADER_SIZE) as usize);
+
+                let data = match pdu {
+                    Pdu::PData { data } => data,
+                    pdu => panic!("Expected PData, got {:?}", pdu),
+                };
+                assert_eq!(data.len(), 1);
+                assert_eq!(data[0].is_last, i + 1 == expected_count);
+                messages.extend(reassembler.push_all(data).unwrap());
+            }
+            assert_eq!(messages, vec![message]);
+        }
+    }
+
+    #[test]
+    fn fragment_without_pdu_length_limit() {
+        let message = PDataMessage {
+            presentation_context_id: 1,
+            value_type: PDataValueType::Data,
+            data: vec![0; 100_000],
+        };
+        let pdus: Vec<_> = PDataFragmenter::new(message, 0).collect();
+        assert_eq!(pdus.len(), 1);
+        match &pdus[0] {
+            Pdu::PData { data } => {
+                assert!(data[0].is_last);
+                assert_eq!(data[0].data.len(), 100_000);
+            }
+            pdu => panic!("Expected PData, got {:?}", pdu),
+        }
+    }
+
+    #[test]
+    fn fragment_message_part() {
+        let message = PDataMessage {
+            presentation_context_id: 1,
+            value_type: PDataValueType::Data,
+            data: vec![0; 10],
+        };
+        let pdus: Vec<_> = PDataFragmenter::new_part(message, MINIMUM_PDU_SIZE, false).collect();
+        assert_eq!(pdus.len(), 1);
+        match &pdus[0] {
+            Pdu::PData { data } => assert!(!data[0].is_last),
+            pdu => panic!("Expected PData, got {:?}", pdu),
+        }
+    }
+}
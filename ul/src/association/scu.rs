@@ -0,0 +1,430 @@
+//! Runtime-agnostic interface for the association requester
+//!
+//! The blocking and the async variants of [`ClientAssociation`]
+//! expose the same operations,
+//! with the same semantics and the same [error type](super::client::Error).
+//! This module captures them in two traits,
+//! so that service class user (SCU) logic
+//! can be written against either one generically:
+//!
+//! - [`Association`] is implemented by
+//!   `ClientAssociation<std::net::TcpStream>`,
+//!   as obtained through [`establish`](super::ClientAssociationOptions::establish).
+//! - [`AsyncAssociation`] is implemented by
+//!   `ClientAssociation<tokio::net::TcpStream>`,
+//!   as obtained through `establish_async`.
+//!   It is only available with the `async` feature.
+//!
+//! The methods of both traits have the same names and parameters,
+//! the async ones returning a boxed future instead of the result.
+//! Helpers which need to work with both runtimes
+//! can keep the message handling in plain functions
+//! and only write the send/receive loop once for each runtime,
+//! as done here: [`echo`] and [`echo_async`]
+//! only differ in the loop which drives the same request and response handling.
+//!
+//! Sending a sequence of files or data sets via C-STORE,
+//! with the outcome of each one, is covered by [`StoreBatch`].
+//!
+//! ```no_run
+//! # use dicom_ul::association::client::ClientAssociationOptions;
+//! use dicom_ul::association::scu::{echo, Association};
+//!
+//! /// Check that the remote node is alive
+//! fn ping<A: Association>(association: &mut A) -> Result<bool, Box<dyn std::error::Error>> {
+//!     let pc_id = association.presentation_contexts()[0].id;
+//!     Ok(echo(association, pc_id, 1)? == 0)
+//! }
+//!
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut association = ClientAssociationOptions::new()
+//!     .with_abstract_syntax("1.2.840.10008.1.1")
+//!     .establish("129.168.0.5:104")?;
+//! assert!(ping(&mut association)?);
+//! association.release()?;
+//! # Ok(())
+//! # }
+//! ```
+use std::net::TcpStream;
+
+use snafu::{ensure, OptionExt, ResultExt, Snafu};
+
+use crate::pdu::{PDataValue, PDataValueType, Pdu, PresentationContextResult, MINIMUM_PDU_SIZE};
+
+use super::client::{self, ClientAssociation};
+use super::reassembly::{PDataReassembler, ReassemblyError};
+use super::verification::{echo_request_command, parse_echo_response, CommandResponse};
+
+mod store;
+
+pub use self::store::{
+    StoreBatch, StoreBatchOptions, StoreItem, StoreItemError, StoreItemResult, StorePriority,
+    StoreReport, StoreSource, Transcoder,
+};
+
+#[cfg(feature = "async")]
+pub use self::non_blocking::{echo_async, AssociationFuture, AsyncAssociation};
+
+/// An error which may occur in an SCU helper operation.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum Error {
+    /// failed to exchange messages through the association
+    Association {
+        #[snafu(backtrace)]
+        source: client::Error,
+    },
+
+    #[snafu(display("unexpected PDU from the remote node `{:?}`", pdu))]
+    #[non_exhaustive]
+    UnexpectedPdu {
+        /// the PDU obtained from the remote node
+        pdu: Box<Pdu>,
+    },
+
+    /// failed to reassemble the response message
+    Reassemble {
+        #[snafu(backtrace)]
+        source: ReassemblyError,
+    },
+
+    /// response is not a valid C-ECHO-RSP
+    InvalidEchoResponse { backtrace: snafu::Backtrace },
+
+    /// response is not a valid C-STORE-RSP
+    InvalidStoreResponse { backtrace: snafu::Backtrace },
+
+    #[snafu(display("response is for message {} instead of message {}", got, expected))]
+    MessageIdMismatch {
+        expected: u16,
+        got: u16,
+        backtrace: snafu::Backtrace,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The operations of an established association
+/// from the perspective of the requesting application entity,
+/// in blocking mode.
+///
+/// See the [module-level documentation](self) for more details.
+pub trait Association {
+    /// Retrieve the list of negotiated presentation contexts.
+    fn presentation_contexts(&self) -> &[PresentationContextResult];
+
+    /// Retrieve the abstract syntax proposed
+    /// for the presentation context with the given ID.
+    ///
+    /// The default implementation returns `None`,
+    /// meaning that it is not known.
+    fn abstract_syntax(&self, _presentation_context_id: u8) -> Option<&str> {
+        None
+    }
+
+    /// Retrieve the maximum PDU length
+    /// admitted by the association acceptor.
+    ///
+    /// The default implementation returns the minimum PDU size.
+    fn acceptor_max_pdu_length(&self) -> u32 {
+        MINIMUM_PDU_SIZE
+    }
+
+    /// Send a PDU message to the other intervenient.
+    fn send(&mut self, msg: &Pdu) -> client::Result<()>;
+
+    /// Read a PDU message from the other intervenient.
+    fn receive(&mut self) -> client::Result<Pdu>;
+
+    /// Gracefully terminate the association by exchanging release messages
+    /// and then shutting down the TCP connection.
+    fn release(self) -> client::Result<()>
+    where
+        Self: Sized;
+
+    /// Send an abort message and shut down the TCP connection,
+    /// terminating the association.
+    fn abort(self) -> client::Result<()>
+    where
+        Self: Sized;
+}
+
+impl Association for ClientAssociation<TcpStream> {
+    fn presentation_contexts(&self) -> &[PresentationContextResult] {
+        ClientAssociation::presentation_contexts(self)
+    }
+
+    fn abstract_syntax(&self, presentation_context_id: u8) -> Option<&str> {
+        ClientAssociation::abstract_syntax(self, presentation_context_id)
+    }
+
+    fn acceptor_max_pdu_length(&self) -> u32 {
+        ClientAssociation::acceptor_max_pdu_length(self)
+    }
+
+    fn send(&mut self, msg: &Pdu) -> client::Result<()> {
+        ClientAssociation::<TcpStream>::send(self, msg)
+    }
+
+    fn receive(&mut self) -> client::Result<Pdu> {
+        ClientAssociation::<TcpStream>::receive(self)
+    }
+
+    fn release(self) -> client::Result<()> {
+        ClientAssociation::<TcpStream>::release(self)
+    }
+
+    fn abort(self) -> client::Result<()> {
+        ClientAssociation::<TcpStream>::abort(self)
+    }
+}
+
+/// Send a C-ECHO request through the given association
+/// and wait for its response,
+/// returning the response status code.
+///
+/// The presentation context identified by `presentation_context_id`
+/// should be one negotiated for the Verification SOP class.
+/// A status of `0` means success.
+pub fn echo<A>(association: &mut A, presentation_context_id: u8, message_id: u16) -> Result<u16>
+where
+    A: Association + ?Sized,
+{
+    exchange(
+        association,
+        [echo_request(presentation_context_id, message_id)],
+        message_id,
+        parse_echo,
+    )
+}
+
+/// Build the P-DATA-TF PDU of a C-ECHO request.
+fn echo_request(presentation_context_id: u8, message_id: u16) -> Pdu {
+    Pdu::PData {
+        data: vec![PDataValue {
+            presentation_context_id,
+            value_type: PDataValueType::Command,
+            is_last: true,
+            data: echo_request_command(message_id),
+        }],
+    }
+}
+
+/// Interpret the command set received in response to a C-ECHO request.
+fn parse_echo(command: &[u8]) -> Result<CommandResponse> {
+    parse_echo_response(command).context(InvalidEchoResponseSnafu)
+}
+
+/// Send the PDUs of a request through the given association
+/// and wait for the response command,
+/// returning its status code.
+///
+/// The response command set is interpreted by `parse_response`.
+pub(crate) fn exchange<A, I, P>(
+    association: &mut A,
+    request: I,
+    message_id: u16,
+    parse_response: P,
+) -> Result<u16>
+where
+    A: Association + ?Sized,
+    I: IntoIterator<Item = Pdu>,
+    P: Fn(&[u8]) -> Result<CommandResponse>,
+{
+    for pdu in request {
+        association.send(&pdu).context(AssociationSnafu)?;
+    }
+
+    let mut reassembler = PDataReassembler::new();
+    loop {
+        let pdu = association.receive().context(AssociationSnafu)?;
+        if let Some(status) = handle_response(&mut reassembler, pdu, message_id, &parse_response)? {
+            return Ok(status);
+        }
+    }
+}
+
+/// Process a PDU received in response to a request.
+///
+/// Returns the response status once the full command has been received,
+/// or `None` if more PDUs are needed.
+fn handle_response<P>(
+    reassembler: &mut PDataReassembler,
+    pdu: Pdu,
+    message_id: u16,
+    parse_response: P,
+) -> Result<Option<u16>>
+where
+    P: Fn(&[u8]) -> Result<CommandResponse>,
+{
+    let data = match pdu {
+        Pdu::PData { data } => data,
+        pdu => {
+            return UnexpectedPduSnafu { pdu: Box::new(pdu) }.fail();
+        }
+    };
+
+    let messages = reassembler.push_all(data).context(ReassembleSnafu)?;
+    let message = match messages.into_iter().find(|message| message.is_command()) {
+        Some(message) => message,
+        None => return Ok(None),
+    };
+
+    let response = parse_response(&message.data)?;
+    ensure!(
+        response.message_id_being_responded_to == message_id,
+        MessageIdMismatchSnafu {
+            expected: message_id,
+            got: response.message_id_being_responded_to,
+        }
+    );
+    Ok(Some(response.status))
+}
+
+#[cfg(feature = "async")]
+mod non_blocking {
+    use std::{future::Future, pin::Pin};
+
+    use snafu::ResultExt;
+    use tokio::net::TcpStream;
+
+    use super::{
+        echo_request, handle_response, parse_echo, AssociationSnafu, ClientAssociation,
+        CommandResponse, PDataReassembler, Result,
+    };
+    use crate::association::client;
+    use crate::pdu::{Pdu, PresentationContextResult, MINIMUM_PDU_SIZE};
+
+    /// The future returned by the methods of [`AsyncAssociation`].
+    pub type AssociationFuture<'a, T> =
+        Pin<Box<dyn Future<Output = client::Result<T>> + Send + 'a>>;
+
+    /// The operations of an established association
+    /// from the perspective of the requesting application entity,
+    /// in async mode.
+    ///
+    /// The methods mirror those of [`Association`](super::Association).
+    /// See the [module-level documentation](super) for more details.
+    pub trait AsyncAssociation {
+        /// Retrieve the list of negotiated presentation contexts.
+        fn presentation_contexts(&self) -> &[PresentationContextResult];
+
+        /// Retrieve the abstract syntax proposed
+        /// for the presentation context with the given ID.
+        ///
+        /// The default implementation returns `None`,
+        /// meaning that it is not known.
+        fn abstract_syntax(&self, _presentation_context_id: u8) -> Option<&str> {
+            None
+        }
+
+        /// Retrieve the maximum PDU length
+        /// admitted by the association acceptor.
+        ///
+        /// The default implementation returns the minimum PDU size.
+        fn acceptor_max_pdu_length(&self) -> u32 {
+            MINIMUM_PDU_SIZE
+        }
+
+        /// Send a PDU message to the other intervenient.
+        fn send<'a>(&'a mut self, msg: &'a Pdu) -> AssociationFuture<'a, ()>;
+
+        /// Read a PDU message from the other intervenient.
+        fn receive(&mut self) -> AssociationFuture<'_, Pdu>;
+
+        /// Gracefully terminate the association by exchanging release messages
+        /// and then shutting down the TCP connection.
+        fn release(self) -> AssociationFuture<'static, ()>
+        where
+            Self: Sized;
+
+        /// Send an abort message and shut down the TCP connection,
+        /// terminating the association.
+        fn abort(self) -> AssociationFuture<'static, ()>
+        where
+            Self: Sized;
+    }
+
+    impl AsyncAssociation for ClientAssociation<TcpStream> {
+        fn presentation_contexts(&self) -> &[PresentationContextResult] {
+            ClientAssociation::presentation_contexts(self)
+        }
+
+        fn abstract_syntax(&self, presentation_context_id: u8) -> Option<&str> {
+            ClientAssociation::abstract_syntax(self, presentation_context_id)
+        }
+
+        fn acceptor_max_pdu_length(&self) -> u32 {
+            ClientAssociation::acceptor_max_pdu_length(self)
+        }
+
+        fn send<'a>(&'a mut self, msg: &'a Pdu) -> AssociationFuture<'a, ()> {
+            Box::pin(ClientAssociation::<TcpStream>::send(self, msg))
+        }
+
+        fn receive(&mut self) -> AssociationFuture<'_, Pdu> {
+            Box::pin(ClientAssociation::<TcpStream>::receive(self))
+        }
+
+        fn release(self) -> AssociationFuture<'static, ()> {
+            Box::pin(ClientAssociation::<TcpStream>::release(self))
+        }
+
+        fn abort(self) -> AssociationFuture<'static, ()> {
+            Box::pin(ClientAssociation::<TcpStream>::abort(self))
+        }
+    }
+
+    /// Send a C-ECHO request through the given async association
+    /// and wait for its response,
+    /// returning the response status code.
+    ///
+    /// This is the async counterpart of [`echo`](super::echo).
+    pub async fn echo_async<A>(
+        association: &mut A,
+        presentation_context_id: u8,
+        message_id: u16,
+    ) -> Result<u16>
+    where
+        A: AsyncAssociation + ?Sized,
+    {
+        exchange_async(
+            association,
+            [echo_request(presentation_context_id, message_id)],
+            message_id,
+            parse_echo,
+        )
+        .await
+    }
+
+    /// Send the PDUs of a request through the given async association
+    /// and wait for the response command,
+    /// returning its status code.
+    ///
+    /// This is the async counterpart of [`exchange`](super::exchange).
+    pub(crate) async fn exchange_async<A, I, P>(
+        association: &mut A,
+        request: I,
+        message_id: u16,
+        parse_response: P,
+    ) -> Result<u16>
+    where
+        A: AsyncAssociation + ?Sized,
+        I: IntoIterator<Item = Pdu>,
+        P: Fn(&[u8]) -> Result<CommandResponse>,
+    {
+        for pdu in request {
+            association.send(&pdu).await.context(AssociationSnafu)?;
+        }
+
+        let mut reassembler = PDataReassembler::new();
+        loop {
+            let pdu = association.receive().await.context(AssociationSnafu)?;
+            if let Some(status) =
+                handle_response(&mut reassembler, pdu, message_id, &parse_response)?
+            {
+                return Ok(status);
+            }
+        }
+    }
+}
@@ -0,0 +1,876 @@
+//! Batch C-STORE requests
+//!
+//! See [`StoreBatch`] for sending a sequence of files or data sets
+//! through an established association.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dicom_object::FileMetaTable;
+use snafu::{OptionExt, ResultExt, Snafu};
+
+use super::{exchange, Association, CommandResponse, InvalidStoreResponseSnafu, Result};
+use crate::association::uid::trim_uid;
+use crate::association::verification::{
+    command_set, parse_response, uid_value, write_element, AFFECTED_SOP_CLASS_UID,
+    COMMAND_DATA_SET_TYPE, COMMAND_FIELD, MESSAGE_ID,
+};
+use crate::association::{PDataFragmenter, PDataMessage};
+use crate::pdu::{PDataValueType, Pdu, PresentationContextResult};
+
+/// Priority (0000,0700)
+const PRIORITY: u16 = 0x0700;
+/// Affected SOP Instance UID (0000,1000)
+const AFFECTED_SOP_INSTANCE_UID: u16 = 0x1000;
+
+/// Command Field value of a C-STORE-RQ
+const C_STORE_RQ: u16 = 0x0001;
+/// Command Field value of a C-STORE-RSP
+const C_STORE_RSP: u16 = 0x8001;
+/// Command Data Set Type value announcing a data set
+const DATA_SET_PRESENT: u16 = 0x0000;
+
+static EXPLICIT_VR_LE: &str = "1.2.840.10008.1.2.1";
+static IMPLICIT_VR_LE: &str = "1.2.840.10008.1.2";
+
+/// An error which prevented an item of a batch from being stored.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum StoreItemError {
+    #[snafu(display("could not read file {}", path.display()))]
+    ReadFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[snafu(display("{} is not a DICOM file with a valid file meta group", path.display()))]
+    InvalidFile { path: PathBuf },
+
+    #[snafu(display(
+        "no presentation context accepted for SOP class {} in transfer syntax {}",
+        sop_class_uid,
+        transfer_syntax
+    ))]
+    NoPresentationContext {
+        sop_class_uid: String,
+        transfer_syntax: String,
+    },
+
+    #[snafu(display("could not transcode data set to transfer syntax {}", transfer_syntax))]
+    Transcode {
+        transfer_syntax: String,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+
+    /// failed to exchange the C-STORE messages
+    Exchange {
+        #[snafu(backtrace)]
+        source: super::Error,
+    },
+}
+
+/// A data set to be sent in a C-STORE request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreItem {
+    /// the SOP class UID of the data set
+    pub sop_class_uid: String,
+    /// the SOP instance UID of the data set
+    pub sop_instance_uid: String,
+    /// the UID of the transfer syntax in which the data set is encoded
+    pub transfer_syntax: String,
+    /// the encoded data set, without a file meta group
+    pub data: Vec<u8>,
+}
+
+impl StoreItem {
+    /// Create a new item out of a data set
+    /// which is already encoded in the given transfer syntax.
+    pub fn new<C, I, T>(
+        sop_class_uid: C,
+        sop_instance_uid: I,
+        transfer_syntax: T,
+        data: Vec<u8>,
+    ) -> Self
+    where
+        C: Into<String>,
+        I: Into<String>,
+        T: Into<String>,
+    {
+        let uid = |uid: String| trim_uid(Cow::Owned(uid)).into_owned();
+        StoreItem {
+            sop_class_uid: uid(sop_class_uid.into()),
+            sop_instance_uid: uid(sop_instance_uid.into()),
+            transfer_syntax: uid(transfer_syntax.into()),
+            data,
+        }
+    }
+
+    /// Read an item from a DICOM file.
+    ///
+    /// The SOP class, SOP instance and transfer syntax
+    /// are taken from the file meta group,
+    /// and the rest of the file is sent as is.
+    pub fn open_file<P>(path: P) -> Result<Self, StoreItemError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).context(ReadFileSnafu { path })?;
+        Self::from_file_bytes(bytes).context(InvalidFileSnafu { path })
+    }
+
+    /// Split the contents of a DICOM file
+    /// into the relevant file meta group attributes
+    /// and the data set.
+    fn from_file_bytes(mut bytes: Vec<u8>) -> Option<Self> {
+        let start = if bytes.get(128..132) == Some(b"DICM") {
+            132
+        } else if bytes.starts_with(b"DICM") {
+            4
+        } else {
+            return None;
+        };
+
+        let (meta, meta_len) = FileMetaTable::read_from_bytes(&bytes[start..]).ok()?;
+        if meta.media_storage_sop_class_uid().is_empty()
+            || meta.media_storage_sop_instance_uid().is_empty()
+        {
+            return None;
+        }
+
+        let data = bytes.split_off(start + meta_len);
+        Some(StoreItem::new(
+            meta.media_storage_sop_class_uid(),
+            meta.media_storage_sop_instance_uid(),
+            meta.transfer_syntax(),
+            data,
+        ))
+    }
+}
+
+/// The source of an item to store in a batch:
+/// either a DICOM file or a data set already in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreSource {
+    /// a DICOM file, read when its turn comes
+    File(PathBuf),
+    /// an encoded data set
+    Item(StoreItem),
+}
+
+impl StoreSource {
+    /// The path to the file, if this source is a file.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            StoreSource::File(path) => Some(path),
+            StoreSource::Item(_) => None,
+        }
+    }
+
+    fn load(self) -> Result<StoreItem, StoreItemError> {
+        match self {
+            StoreSource::File(path) => StoreItem::open_file(path),
+            StoreSource::Item(item) => Ok(item),
+        }
+    }
+}
+
+impl From<PathBuf> for StoreSource {
+    fn from(path: PathBuf) -> Self {
+        StoreSource::File(path)
+    }
+}
+
+impl From<&PathBuf> for StoreSource {
+    fn from(path: &PathBuf) -> Self {
+        StoreSource::File(path.clone())
+    }
+}
+
+impl From<&Path> for StoreSource {
+    fn from(path: &Path) -> Self {
+        StoreSource::File(path.to_path_buf())
+    }
+}
+
+impl From<StoreItem> for StoreSource {
+    fn from(item: StoreItem) -> Self {
+        StoreSource::Item(item)
+    }
+}
+
+/// The priority of a C-STORE request.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StorePriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl StorePriority {
+    /// The value of the Priority command element.
+    fn value(self) -> u16 {
+        match self {
+            StorePriority::Low => 0x0002,
+            StorePriority::Medium => 0x0000,
+            StorePriority::High => 0x0001,
+        }
+    }
+}
+
+/// A function converting an item to another transfer syntax,
+/// returning the data set encoded in that transfer syntax.
+///
+/// See [`StoreBatchOptions::transcoder`].
+pub type Transcoder = dyn Fn(&StoreItem, &str) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>
+    + Send
+    + Sync;
+
+/// Options for sending a batch of C-STORE requests.
+#[derive(Clone)]
+pub struct StoreBatchOptions {
+    /// whether to stop at the first item which could not be stored
+    stop_on_failure: bool,
+    /// the priority of each request
+    priority: StorePriority,
+    /// the message ID of the first request
+    message_id: u16,
+    /// how to convert items when their transfer syntax was not accepted
+    transcoder: Option<Arc<Transcoder>>,
+}
+
+impl Default for StoreBatchOptions {
+    fn default() -> Self {
+        StoreBatchOptions {
+            stop_on_failure: false,
+            priority: StorePriority::Medium,
+            message_id: 1,
+            transcoder: None,
+        }
+    }
+}
+
+impl fmt::Debug for StoreBatchOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreBatchOptions")
+            .field("stop_on_failure", &self.stop_on_failure)
+            .field("priority", &self.priority)
+            .field("message_id", &self.message_id)
+            .field("transcoder", &self.transcoder.as_ref().map(|_| "Fn"))
+            .finish()
+    }
+}
+
+impl StoreBatchOptions {
+    /// Create a new set of options with the default values:
+    /// continue after failures, medium priority,
+    /// message IDs starting at 1,
+    /// and no transcoding.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define whether to stop sending items
+    /// at the first one which could not be stored.
+    /// By default, all items are attempted.
+    pub fn stop_on_failure(mut self, stop_on_failure: bool) -> Self {
+        self.stop_on_failure = stop_on_failure;
+        self
+    }
+
+    /// Define the priority of the C-STORE requests.
+    pub fn priority(mut self, priority: StorePriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Define the message ID of the first C-STORE request,
+    /// incremented for each item.
+    pub fn message_id(mut self, message_id: u16) -> Self {
+        self.message_id = message_id;
+        self
+    }
+
+    /// Define how to convert an item to another transfer syntax
+    /// when no presentation context was accepted
+    /// for its SOP class in its own transfer syntax.
+    ///
+    /// The function receives the item and the UID of the target transfer syntax,
+    /// preferably _Explicit VR Little Endian_,
+    /// then _Implicit VR Little Endian_,
+    /// then any other transfer syntax accepted for the SOP class.
+    /// Without a transcoder,
+    /// such items fail with [`StoreItemError::NoPresentationContext`].
+    pub fn transcoder<F>(mut self, transcoder: F) -> Self
+    where
+        F: Fn(&StoreItem, &str) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.transcoder = Some(Arc::new(transcoder));
+        self
+    }
+}
+
+/// The outcome of sending one item of a batch.
+#[derive(Debug)]
+pub struct StoreItemResult {
+    /// the position of the item in the batch, starting at 0
+    pub index: usize,
+    /// the file which the item was read from, if any
+    pub path: Option<PathBuf>,
+    /// the SOP instance UID of the item, if known
+    pub sop_instance_uid: Option<String>,
+    /// the status code of the C-STORE response, if one was received
+    pub status: Option<u16>,
+    /// the error which prevented the item from being stored, if any
+    pub error: Option<StoreItemError>,
+    /// the time taken to read and send the item
+    /// and to receive the response
+    pub elapsed: Duration,
+    /// the number of data set bytes sent
+    pub bytes: u64,
+}
+
+impl StoreItemResult {
+    /// Check whether the item was stored,
+    /// possibly with a warning status.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none() && self.status.map_or(false, is_success_status)
+    }
+}
+
+/// Whether a C-STORE response status means that the data set was stored.
+fn is_success_status(status: u16) -> bool {
+    // success, or one of the warnings
+    status == 0x0000 || status == 0x0001 || status & 0xF000 == 0xB000
+}
+
+/// The outcome of sending a batch of items.
+#[derive(Debug, Default)]
+pub struct StoreReport {
+    /// the outcome of each item attempted, in order
+    pub results: Vec<StoreItemResult>,
+    /// whether the batch stopped before attempting all items,
+    /// either due to a failure with
+    /// [`stop_on_failure`](StoreBatchOptions::stop_on_failure)
+    /// or due to a failure in the association
+    pub interrupted: bool,
+    /// the time taken to send the whole batch
+    pub elapsed: Duration,
+}
+
+impl StoreReport {
+    /// The number of items stored.
+    pub fn succeeded(&self) -> usize {
+        self.results.iter().filter(|r| r.is_success()).count()
+    }
+
+    /// The number of items attempted but not stored.
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.succeeded()
+    }
+
+    /// The total number of data set bytes sent.
+    pub fn bytes(&self) -> u64 {
+        self.results.iter().map(|r| r.bytes).sum()
+    }
+
+    /// Check whether all items were stored.
+    pub fn is_success(&self) -> bool {
+        !self.interrupted && self.results.iter().all(|r| r.is_success())
+    }
+}
+
+/// A helper for sending a sequence of files or data sets
+/// through an established association via C-STORE requests,
+/// collecting the outcome of each one.
+///
+/// For each item,
+/// the presentation context is selected by SOP class and transfer syntax,
+/// transcoding it with the [transcoder](StoreBatchOptions::transcoder) if necessary.
+/// An item which fails to be stored does not stop the batch,
+/// unless [`stop_on_failure`](StoreBatchOptions::stop_on_failure) is set
+/// or the association itself fails.
+///
+/// # Example
+///
+/// ```no_run
+/// # use dicom_ul::association::client::ClientAssociationOptions;
+/// use dicom_ul::association::scu::{StoreBatch, StoreBatchOptions};
+/// use std::path::PathBuf;
+///
+/// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let files = vec![PathBuf::from("1.dcm"), PathBuf::from("2.dcm")];
+/// let mut association = ClientAssociationOptions::new()
+///     .with_presentation_context("1.2.840.10008.5.1.4.1.1.2", vec!["1.2.840.10008.1.2.1"])
+///     .establish("129.168.0.5:104")?;
+///
+/// let report = StoreBatch::new(StoreBatchOptions::new())
+///     .on_progress(|result| println!("#{}: {:?}", result.index, result.status))
+///     .send(&mut association, files);
+/// association.release()?;
+///
+/// println!("{} stored, {} failed", report.succeeded(), report.failed());
+/// # Ok(())
+/// # }
+/// ```
+pub struct StoreBatch<'a> {
+    options: StoreBatchOptions,
+    on_progress: Option<Box<dyn FnMut(&StoreItemResult) + Send + 'a>>,
+}
+
+impl fmt::Debug for StoreBatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoreBatch")
+            .field("options", &self.options)
+            .field("on_progress", &self.on_progress.as_ref().map(|_| "FnMut"))
+            .finish()
+    }
+}
+
+impl<'a> StoreBatch<'a> {
+    /// Create a new batch sender with the given options.
+    pub fn new(options: StoreBatchOptions) -> Self {
+        StoreBatch {
+            options,
+            on_progress: None,
+        }
+    }
+
+    /// Define a function to be called with the outcome of each item,
+    /// as soon as it is known.
+    pub fn on_progress<F>(mut self, on_progress: F) -> Self
+    where
+        F: FnMut(&StoreItemResult) + Send + 'a,
+    {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    /// Send all items through the given association,
+    /// one C-STORE request at a time.
+    ///
+    /// The association is left open.
+    pub fn send<A, I>(&mut self, association: &mut A, items: I) -> StoreReport
+    where
+        A: Association + ?Sized,
+        I: IntoIterator,
+        I::Item: Into<StoreSource>,
+    {
+        let start = Instant::now();
+        let mut report = StoreReport::default();
+        let mut items = items.into_iter().enumerate().peekable();
+
+        while let Some((index, source)) = items.next() {
+            let item_start = Instant::now();
+            let (result, request) = self.prepare_item(
+                index,
+                source.into(),
+                association.presentation_contexts(),
+                |id, sop_class_uid| {
+                    association
+                        .abstract_syntax(id)
+                        .map_or(true, |uid| uid == sop_class_uid)
+                },
+            );
+            let outcome = match request {
+                Ok(request) => {
+                    let message_id = request.message_id;
+                    let pdus = request.into_pdus(association.acceptor_max_pdu_length());
+                    exchange(association, pdus, message_id, parse_store_response)
+                        .context(ExchangeSnafu)
+                }
+                Err(e) => Err(e),
+            };
+
+            if self.record(&mut report, result, outcome, item_start) {
+                report.interrupted = items.peek().is_some();
+                break;
+            }
+        }
+
+        report.elapsed = start.elapsed();
+        report
+    }
+
+    /// Send all items through the given async association,
+    /// one C-STORE request at a time.
+    ///
+    /// This is the async counterpart of [`send`](Self::send).
+    #[cfg(feature = "async")]
+    pub async fn send_async<A, I>(&mut self, association: &mut A, items: I) -> StoreReport
+    where
+        A: super::AsyncAssociation + ?Sized,
+        I: IntoIterator,
+        I::Item: Into<StoreSource>,
+    {
+        let start = Instant::now();
+        let mut report = StoreReport::default();
+        let mut items = items.into_iter().enumerate().peekable();
+
+        while let Some((index, source)) = items.next() {
+            let item_start = Instant::now();
+            let (result, request) = self.prepare_item(
+                index,
+                source.into(),
+                association.presentation_contexts(),
+                |id, sop_class_uid| {
+                    association
+                        .abstract_syntax(id)
+                        .map_or(true, |uid| uid == sop_class_uid)
+                },
+            );
+            let outcome = match request {
+                Ok(request) => {
+                    let message_id = request.message_id;
+                    let pdus = request.into_pdus(association.acceptor_max_pdu_length());
+                    super::non_blocking::exchange_async(
+                        association,
+                        pdus,
+                        message_id,
+                        parse_store_response,
+                    )
+                    .await
+                    .context(ExchangeSnafu)
+                }
+                Err(e) => Err(e),
+            };
+
+            if self.record(&mut report, result, outcome, item_start) {
+                report.interrupted = items.peek().is_some();
+                break;
+            }
+        }
+
+        report.elapsed = start.elapsed();
+        report
+    }
+
+    /// Load the item at the given position of the batch
+    /// and prepare its C-STORE request,
+    /// starting its result.
+    ///
+    /// `accepts` tells whether the presentation context with the given ID
+    /// may be used for the given SOP class.
+    fn prepare_item(
+        &self,
+        index: usize,
+        source: StoreSource,
+        presentation_contexts: &[PresentationContextResult],
+        accepts: impl Fn(u8, &str) -> bool,
+    ) -> (StoreItemResult, Result<StoreRequest, StoreItemError>) {
+        let mut result = StoreItemResult {
+            index,
+            path: source.path().map(Path::to_path_buf),
+            sop_instance_uid: None,
+            status: None,
+            error: None,
+            elapsed: Duration::ZERO,
+            bytes: 0,
+        };
+        // one message ID per item, even if it is not sent
+        let message_id = self.options.message_id.wrapping_add(index as u16);
+        let request = source.load().and_then(|item| {
+            result.sop_instance_uid = Some(item.sop_instance_uid.clone());
+            prepare_request(
+                &self.options,
+                item,
+                presentation_contexts,
+                accepts,
+                message_id,
+            )
+        });
+        if let Ok(request) = &request {
+            result.bytes = request.data.len() as u64;
+        }
+        (result, request)
+    }
+
+    /// Complete the result of an item,
+    /// report it and add it to the batch report.
+    ///
+    /// Returns whether to stop sending items.
+    fn record(
+        &mut self,
+        report: &mut StoreReport,
+        mut result: StoreItemResult,
+        outcome: Result<u16, StoreItemError>,
+        start: Instant,
+    ) -> bool {
+        // the association cannot be trusted after a failed exchange
+        let fatal = matches!(outcome, Err(StoreItemError::Exchange { .. }));
+        match outcome {
+            Ok(status) => result.status = Some(status),
+            Err(e) => result.error = Some(e),
+        }
+        result.elapsed = start.elapsed();
+
+        let failed = !result.is_success();
+        if let Some(on_progress) = &mut self.on_progress {
+            on_progress(&result);
+        }
+        report.results.push(result);
+        fatal || (failed && self.options.stop_on_failure)
+    }
+}
+
+/// A C-STORE request ready to be sent.
+#[derive(Debug)]
+struct StoreRequest {
+    presentation_context_id: u8,
+    message_id: u16,
+    command: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl StoreRequest {
+    /// Split the request into P-DATA-TF PDUs,
+    /// the command first and then the data set.
+    fn into_pdus(self, max_pdu_length: u32) -> impl Iterator<Item = Pdu> {
+        let command = PDataFragmenter::new(
+            PDataMessage {
+                presentation_context_id: self.presentation_context_id,
+                value_type: PDataValueType::Command,
+                data: self.command,
+            },
+            max_pdu_length,
+        );
+        let data = PDataFragmenter::new(
+            PDataMessage {
+                presentation_context_id: self.presentation_context_id,
+                value_type: PDataValueType::Data,
+                data: self.data,
+            },
+            max_pdu_length,
+        );
+        command.chain(data)
+    }
+}
+
+/// Select the presentation context for an item
+/// and encode the C-STORE request,
+/// transcoding the data set if necessary.
+///
+/// `accepts` tells whether the presentation context with the given ID
+/// may be used for the given SOP class.
+fn prepare_request(
+    options: &StoreBatchOptions,
+    item: StoreItem,
+    presentation_contexts: &[PresentationContextResult],
+    accepts: impl Fn(u8, &str) -> bool,
+    message_id: u16,
+) -> Result<StoreRequest, StoreItemError> {
+    let candidates = || {
+        presentation_contexts
+            .iter()
+            .filter(|pc| accepts(pc.id, &item.sop_class_uid))
+    };
+    let same_ts = |pc: &&PresentationContextResult, uid: &str| {
+        trim_uid(Cow::from(pc.transfer_syntax.as_str())) == uid
+    };
+
+    if let Some(pc) = candidates().find(|pc| same_ts(pc, &item.transfer_syntax)) {
+        return Ok(StoreRequest {
+            presentation_context_id: pc.id,
+            message_id,
+            command: store_request_command(&item, message_id, options.priority),
+            data: item.data,
+        });
+    }
+
+    let transcoder = options
+        .transcoder
+        .as_ref()
+        .context(NoPresentationContextSnafu {
+            sop_class_uid: &item.sop_class_uid,
+            transfer_syntax: &item.transfer_syntax,
+        })?;
+    let pc = candidates()
+        .find(|pc| same_ts(pc, EXPLICIT_VR_LE))
+        .or_else(|| candidates().find(|pc| same_ts(pc, IMPLICIT_VR_LE)))
+        .or_else(|| candidates().next())
+        .context(NoPresentationContextSnafu {
+            sop_class_uid: &item.sop_class_uid,
+            transfer_syntax: &item.transfer_syntax,
+        })?;
+    let transfer_syntax = trim_uid(Cow::from(pc.transfer_syntax.as_str()));
+    let data = transcoder(&item, &transfer_syntax).context(TranscodeSnafu {
+        transfer_syntax: &*transfer_syntax,
+    })?;
+
+    Ok(StoreRequest {
+        presentation_context_id: pc.id,
+        message_id,
+        command: store_request_command(&item, message_id, options.priority),
+        data,
+    })
+}
+
+/// Interpret the command set received in response to a C-STORE request.
+fn parse_store_response(command: &[u8]) -> Result<CommandResponse> {
+    parse_response(command, C_STORE_RSP).context(InvalidStoreResponseSnafu)
+}
+
+/// Encode the command set of a C-STORE request
+/// for the given item in Implicit VR Little Endian.
+fn store_request_command(item: &StoreItem, message_id: u16, priority: StorePriority) -> Vec<u8> {
+    let mut body = Vec::with_capacity(160);
+    write_element(
+        &mut body,
+        AFFECTED_SOP_CLASS_UID,
+        &uid_value(item.sop_class_uid.as_bytes()),
+    );
+    write_element(&mut body, COMMAND_FIELD, &C_STORE_RQ.to_le_bytes());
+    write_element(&mut body, MESSAGE_ID, &message_id.to_le_bytes());
+    write_element(&mut body, PRIORITY, &priority.value().to_le_bytes());
+    write_element(
+        &mut body,
+        COMMAND_DATA_SET_TYPE,
+        &DATA_SET_PRESENT.to_le_bytes(),
+    );
+    write_element(
+        &mut body,
+        AFFECTED_SOP_INSTANCE_UID,
+        &uid_value(item.sop_instance_uid.as_bytes()),
+    );
+    command_set(&body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::association::verification::{
+        command_elements, COMMAND_GROUP_LENGTH, MESSAGE_ID_BEING_RESPONDED_TO, STATUS,
+    };
+    use crate::pdu::PresentationContextResultReason;
+
+    fn pc(id: u8, transfer_syntax: &str) -> PresentationContextResult {
+        PresentationContextResult {
+            id,
+            reason: PresentationContextResultReason::Acceptance,
+            transfer_syntax: transfer_syntax.to_string(),
+        }
+    }
+
+    fn item(transfer_syntax: &str) -> StoreItem {
+        StoreItem::new(
+            "1.2.840.10008.5.1.4.1.1.2",
+            "1.2.3.4.5",
+            transfer_syntax,
+            vec![1, 2, 3, 4],
+        )
+    }
+
+    #[test]
+    fn read_item_from_file() {
+        let mut file = vec![0; 128];
+        file.extend_from_slice(b"DICM");
+        let mut meta_element = |element: u16, vr: &[u8], value: &[u8]| {
+            file.extend_from_slice(&0x0002_u16.to_le_bytes());
+            file.extend_from_slice(&element.to_le_bytes());
+            file.extend_from_slice(vr);
+            if vr == b"OB" {
+                file.extend_from_slice(&[0, 0]);
+                file.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            } else {
+                file.extend_from_slice(&(value.len() as u16).to_le_bytes());
+            }
+            file.extend_from_slice(value);
+        };
+        meta_element(0x0001, b"OB", &[0, 1]);
+        meta_element(0x0002, b"UI", b"1.2.840.10008.5.1.4.1.1.2\0");
+        meta_element(0x0003, b"UI", b"1.2.3.4.5\0");
+        meta_element(0x0010, b"UI", b"1.2.840.10008.1.2.1\0");
+        file.extend_from_slice(&[0x08, 0x00, 0x16, 0x00]);
+
+        let item = StoreItem::from_file_bytes(file).unwrap();
+        assert_eq!(
+            item,
+            StoreItem {
+                sop_class_uid: "1.2.840.10008.5.1.4.1.1.2".to_string(),
+                sop_instance_uid: "1.2.3.4.5".to_string(),
+                transfer_syntax: "1.2.840.10008.1.2.1".to_string(),
+                data: vec![0x08, 0x00, 0x16, 0x00],
+            }
+        );
+
+        // no magic code
+        assert_eq!(StoreItem::from_file_bytes(vec![0; 200]), None);
+    }
+
+    #[test]
+    fn select_presentation_context() {
+        let pcs = [
+            pc(1, IMPLICIT_VR_LE),
+            pc(3, EXPLICIT_VR_LE),
+            pc(5, "1.2.840.10008.1.2.4.50"),
+        ];
+        let options = StoreBatchOptions::new();
+        let any = |_: u8, _: &str| true;
+
+        // exact match
+        let request = prepare_request(&options, item(EXPLICIT_VR_LE), &pcs, any, 7).unwrap();
+        assert_eq!(request.presentation_context_id, 3);
+        assert_eq!(request.message_id, 7);
+        assert_eq!(request.data, [1, 2, 3, 4]);
+
+        // only contexts for the SOP class are considered
+        let err =
+            prepare_request(&options, item(EXPLICIT_VR_LE), &pcs, |id, _| id != 3, 7).unwrap_err();
+        assert!(matches!(err, StoreItemError::NoPresentationContext { .. }));
+
+        // transcode to explicit VR little endian
+        let options = options.transcoder(|item, ts| {
+            assert_eq!(ts, EXPLICIT_VR_LE);
+            Ok(item.data.iter().rev().copied().collect())
+        });
+        let request =
+            prepare_request(&options, item("1.2.840.10008.1.2.4.70"), &pcs, any, 8).unwrap();
+        assert_eq!(request.presentation_context_id, 3);
+        assert_eq!(request.data, [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn store_command_round_trip() {
+        let command = store_request_command(&item(EXPLICIT_VR_LE), 9, StorePriority::High);
+        let elements: Vec<_> = command_elements(&command).collect();
+        assert_eq!(
+            elements,
+            vec![
+                (COMMAND_GROUP_LENGTH, &92_u32.to_le_bytes()[..]),
+                (AFFECTED_SOP_CLASS_UID, &b"1.2.840.10008.5.1.4.1.1.2\0"[..]),
+                (COMMAND_FIELD, &[0x01, 0x00][..]),
+                (MESSAGE_ID, &[9, 0][..]),
+                (PRIORITY, &[1, 0][..]),
+                (COMMAND_DATA_SET_TYPE, &[0, 0][..]),
+                (AFFECTED_SOP_INSTANCE_UID, &b"1.2.3.4.5\0"[..]),
+            ]
+        );
+        // a request is not a response
+        assert!(parse_store_response(&command).is_err());
+
+        let mut response = Vec::new();
+        write_element(&mut response, COMMAND_FIELD, &C_STORE_RSP.to_le_bytes());
+        write_element(
+            &mut response,
+            MESSAGE_ID_BEING_RESPONDED_TO,
+            &9_u16.to_le_bytes(),
+        );
+        write_element(&mut response, STATUS, &0xB000_u16.to_le_bytes());
+        assert_eq!(
+            parse_store_response(&response).unwrap(),
+            CommandResponse {
+                message_id_being_responded_to: 9,
+                status: 0xB000,
+            }
+        );
+        assert!(is_success_status(0xB000));
+        assert!(!is_success_status(0xA700));
+    }
+}
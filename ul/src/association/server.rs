@@ -24,10 +24,19 @@ use crate::{
     IMPLEMENTATION_CLASS_UID, IMPLEMENTATION_VERSION_NAME,
 };
 
+#[cfg(feature = "tls")]
+use super::tls::{self, rustls};
 use super::{
-    pdata::{PDataReader, PDataWriter},
+    client::SyncSocket,
+    negotiation::{respond_relational_queries, RelationalQuerySupport},
+    pdata::{dataset_adapter, dataset_adapters, PDataReader, PDataWriter},
+    reassembly::PDataFragmenter,
     uid::trim_uid,
+    verification::{auto_echo_response, VERIFICATION_SOP_CLASS},
+    wire_log::{WireLog, WireTap},
 };
+#[cfg(feature = "tls")]
+use std::sync::Arc;
 
 #[derive(Debug, Snafu)]
 #[non_exhaustive]
@@ -109,6 +118,13 @@ pub enum Error {
         source: std::io::Error,
         backtrace: Backtrace,
     },
+
+    /// could not establish TLS connection
+    #[cfg(feature = "tls")]
+    Tls {
+        #[snafu(backtrace)]
+        source: super::tls::TlsError,
+    },
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -286,6 +302,8 @@ pub struct ServerAssociationOptions<'a, A> {
     abstract_syntax_uids: Vec<Cow<'a, str>>,
     /// the list of requested transfer syntaxes
     transfer_syntax_uids: Vec<Cow<'a, str>>,
+    /// the order of preference among the transfer syntaxes to accept
+    transfer_syntax_preference: Vec<Cow<'a, str>>,
     /// the expected protocol version
     protocol_version: u16,
     /// the maximum PDU length
@@ -294,8 +312,14 @@ pub struct ServerAssociationOptions<'a, A> {
     strict: bool,
     /// whether to accept unknown abstract syntaxes
     promiscuous: bool,
+    /// whether to respond to verification requests automatically
+    auto_verification: bool,
+    /// whether to grant relational queries to query/retrieve SOP classes
+    relational_queries: bool,
     /// Timeout for individual send/receive operations
     timeout: Option<std::time::Duration>,
+    /// where to capture the raw bytes of each association
+    wire_log: Option<WireLog>,
 }
 
 impl Default for ServerAssociationOptions<'_, AcceptAny> {
@@ -306,11 +330,15 @@ impl Default for ServerAssociationOptions<'_, AcceptAny> {
             application_context_name: "1.2.840.10008.3.1.1.1".into(),
             abstract_syntax_uids: Vec::new(),
             transfer_syntax_uids: Vec::new(),
+            transfer_syntax_preference: Vec::new(),
             protocol_version: 1,
             max_pdu_length: DEFAULT_MAX_PDU,
             strict: true,
             promiscuous: false,
+            auto_verification: false,
+            relational_queries: false,
             timeout: None,
+            wire_log: None,
         }
     }
 }
@@ -356,12 +384,16 @@ where
             application_context_name,
             abstract_syntax_uids,
             transfer_syntax_uids,
+            transfer_syntax_preference,
             protocol_version,
             max_pdu_length,
             strict,
             promiscuous,
+            auto_verification,
+            relational_queries,
             ae_access_control: _,
             timeout,
+            wire_log,
         } = self;
 
         ServerAssociationOptions {
@@ -370,11 +402,15 @@ where
             application_context_name,
             abstract_syntax_uids,
             transfer_syntax_uids,
+            transfer_syntax_preference,
             protocol_version,
             max_pdu_length,
             strict,
             promiscuous,
+            auto_verification,
+            relational_queries,
             timeout,
+            wire_log,
         }
     }
 
@@ -410,6 +446,29 @@ where
         self
     }
 
+    /// Define the order of preference among the transfer syntaxes
+    /// which both sides support in each presentation context,
+    /// from most to least preferred.
+    ///
+    /// For each presentation context,
+    /// the transfer syntax accepted is the first one in this list
+    /// to be proposed by the requester and supported by this node.
+    /// If none of them applies,
+    /// the first supported transfer syntax in the requester's proposal is accepted.
+    /// This is also the behavior when no preference is defined,
+    /// which is the default.
+    ///
+    /// This does not extend the transfer syntaxes which are accepted,
+    /// see [`with_transfer_syntax`](Self::with_transfer_syntax).
+    pub fn with_transfer_syntax_preference<I, T>(mut self, order: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<Cow<'a, str>>,
+    {
+        self.transfer_syntax_preference = order.into_iter().map(|ts| trim_uid(ts.into())).collect();
+        self
+    }
+
     /// Override the maximum expected PDU length.
     pub fn max_pdu_length(mut self, value: u32) -> Self {
         self.max_pdu_length = value;
@@ -431,6 +490,36 @@ where
         self
     }
 
+    /// Override automatic verification mode:
+    /// whether to accept the Verification SOP class
+    /// and respond to C-ECHO requests automatically.
+    ///
+    /// When enabled, C-ECHO requests received
+    /// through [`ServerAssociation::receive`]
+    /// on a verification presentation context
+    /// are answered with a successful C-ECHO response
+    /// and are not passed on to the application.
+    /// All other messages are received as usual.
+    pub fn auto_verification(mut self, auto_verification: bool) -> Self {
+        self.auto_verification = auto_verification;
+        self
+    }
+
+    /// Override whether to grant relational queries
+    /// to the query/retrieve SOP classes
+    /// for which the requester asks them
+    /// through SOP class extended negotiation.
+    ///
+    /// The outcome is sent back to the requester
+    /// and can be checked per presentation context through
+    /// [`ServerAssociation::relational_query_support`].
+    /// The default is `false`,
+    /// so that only hierarchical queries are supported.
+    pub fn relational_queries(mut self, relational_queries: bool) -> Self {
+        self.relational_queries = relational_queries;
+        self
+    }
+
     /// Set the timeout for the underlying TCP socket
     pub fn timeout(self, timeout: Duration) -> Self {
         Self {
@@ -439,20 +528,68 @@ where
         }
     }
 
+    /// Capture all raw bytes sent and received
+    /// through the associations established with these options
+    /// into the given wire log,
+    /// starting with the association request.
+    ///
+    /// The records of concurrent associations sharing the same log
+    /// may be interleaved.
+    /// See the [`wire_log`](super::wire_log) module
+    /// for the format of the log and how to read it back.
+    /// By default, nothing is captured.
+    pub fn wire_log(self, wire_log: WireLog) -> Self {
+        Self {
+            wire_log: Some(wire_log),
+            ..self
+        }
+    }
+
     /// Negotiate an association with the given TCP stream.
-    pub fn establish(&self, mut socket: TcpStream) -> Result<ServerAssociation<TcpStream>> {
-        ensure!(
-            !self.abstract_syntax_uids.is_empty() || self.promiscuous,
-            MissingAbstractSyntaxSnafu
-        );
+    pub fn establish(&self, socket: TcpStream) -> Result<ServerAssociation<TcpStream>> {
+        self.set_timeouts(&socket)?;
+        self.establish_impl(socket)
+    }
 
-        let max_pdu_length = self.max_pdu_length;
+    /// Perform a TLS handshake on the given TCP stream
+    /// and negotiate an association on top of it.
+    ///
+    /// A failed handshake results in [`Error::Tls`],
+    /// in which case the requester is not notified
+    /// through an association rejection.
+    /// Whether the requester must present a certificate
+    /// is decided by the given server configuration.
+    /// See the [`tls`](super::tls) module for more details.
+    ///
+    /// Requires the `tls` Cargo feature.
+    #[cfg(feature = "tls")]
+    pub fn establish_tls(
+        &self,
+        socket: TcpStream,
+        config: Arc<rustls::ServerConfig>,
+    ) -> Result<ServerAssociation<tls::ServerTlsStream>> {
+        self.set_timeouts(&socket)?;
+        let socket = tls::accept(socket, config).context(TlsSnafu)?;
+        self.establish_impl(socket)
+    }
+
+    fn set_timeouts(&self, socket: &TcpStream) -> Result<()> {
         socket
             .set_read_timeout(self.timeout)
             .context(SetReadTimeoutSnafu)?;
         socket
             .set_write_timeout(self.timeout)
-            .context(SetWriteTimeoutSnafu)?;
+            .context(SetWriteTimeoutSnafu)
+    }
+
+    fn establish_impl<S: SyncSocket>(&self, socket: S) -> Result<ServerAssociation<S>> {
+        ensure!(
+            !self.abstract_syntax_uids.is_empty() || self.promiscuous || self.auto_verification,
+            MissingAbstractSyntaxSnafu
+        );
+
+        let max_pdu_length = self.max_pdu_length;
+        let mut socket = WireTap::new(socket, self.wire_log.clone());
 
         let mut read_buffer = BytesMut::with_capacity(MAXIMUM_PDU_SIZE as usize);
         let mut reader = BufReader::new(&mut socket);
@@ -563,13 +700,17 @@ where
                     requestor_max_pdu_length
                 };
 
+                let mut verification_context_ids = Vec::new();
+                let mut accepted_contexts = Vec::new();
                 let presentation_contexts: Vec<_> = presentation_contexts
                     .into_iter()
                     .map(|pc| {
-                        if !self
-                            .abstract_syntax_uids
-                            .contains(&trim_uid(Cow::from(pc.abstract_syntax)))
+                        let abstract_syntax = trim_uid(Cow::from(pc.abstract_syntax));
+                        let is_verification =
+                            self.auto_verification && abstract_syntax == VERIFICATION_SOP_CLASS;
+                        if !self.abstract_syntax_uids.contains(&abstract_syntax)
                             && !self.promiscuous
+                            && !is_verification
                         {
                             return PresentationContextResult {
                                 id: pc.id,
@@ -588,6 +729,15 @@ where
                                 )
                             });
 
+                        if is_verification && reason == PresentationContextResultReason::Acceptance
+                        {
+                            verification_context_ids.push(pc.id);
+                        }
+
+                        if reason == PresentationContextResultReason::Acceptance {
+                            accepted_contexts.push((pc.id, abstract_syntax.into_owned()));
+                        }
+
                         PresentationContextResult {
                             id: pc.id,
                             reason,
@@ -596,6 +746,21 @@ where
                     })
                     .collect();
 
+                let (extended_negotiation, relational_query_context_ids) =
+                    respond_relational_queries(
+                        &user_variables,
+                        &accepted_contexts,
+                        self.relational_queries,
+                    );
+                let mut response_user_variables = vec![
+                    UserVariableItem::MaxLength(max_pdu_length),
+                    UserVariableItem::ImplementationClassUID(IMPLEMENTATION_CLASS_UID.to_string()),
+                    UserVariableItem::ImplementationVersionName(
+                        IMPLEMENTATION_VERSION_NAME.to_string(),
+                    ),
+                ];
+                response_user_variables.extend(extended_negotiation);
+
                 write_pdu(
                     &mut buffer,
                     &Pdu::AssociationAC(AssociationAC {
@@ -604,15 +769,7 @@ where
                         presentation_contexts: presentation_contexts.clone(),
                         calling_ae_title: calling_ae_title.clone(),
                         called_ae_title,
-                        user_variables: vec![
-                            UserVariableItem::MaxLength(max_pdu_length),
-                            UserVariableItem::ImplementationClassUID(
-                                IMPLEMENTATION_CLASS_UID.to_string(),
-                            ),
-                            UserVariableItem::ImplementationVersionName(
-                                IMPLEMENTATION_VERSION_NAME.to_string(),
-                            ),
-                        ],
+                        user_variables: response_user_variables,
                     }),
                 )
                 .context(SendResponseSnafu)?;
@@ -622,12 +779,15 @@ where
                     presentation_contexts,
                     requestor_max_pdu_length,
                     acceptor_max_pdu_length: max_pdu_length,
-                    socket,
+                    socket: socket.into_inner(),
                     client_ae_title: calling_ae_title,
                     buffer,
                     strict: self.strict,
                     read_buffer: BytesMut::with_capacity(MAXIMUM_PDU_SIZE as usize),
                     timeout: self.timeout,
+                    verification_context_ids,
+                    relational_query_context_ids,
+                    wire_log: self.wire_log.clone(),
                 })
             }
             Pdu::ReleaseRQ => {
@@ -651,22 +811,34 @@ where
     ///
     /// If the options' list is empty,
     /// accept the first transfer syntax supported.
+    /// Among these, the one ranked highest
+    /// in the options' order of preference is chosen, if any.
     fn choose_ts<I, T>(&self, it: I) -> Option<T>
     where
         I: IntoIterator<Item = T>,
         T: AsRef<str>,
     {
-        if self.transfer_syntax_uids.is_empty() {
-            return choose_supported(it);
-        }
-
-        it.into_iter().find(|ts| {
+        let mut candidates = it.into_iter().filter(|ts| {
             let ts = ts.as_ref();
             if self.transfer_syntax_uids.is_empty() {
-                ts.trim_end_matches(|c: char| c.is_whitespace() || c == '\0') == "1.2.840.10008.1.2"
+                is_supported(ts)
             } else {
                 self.transfer_syntax_uids.contains(&trim_uid(ts.into())) && is_supported(ts)
             }
+        });
+
+        if self.transfer_syntax_preference.is_empty() {
+            return candidates.next();
+        }
+
+        // transfer syntaxes without a preference come last,
+        // ties keep the requester's order
+        candidates.min_by_key(|ts| {
+            let ts = trim_uid(ts.as_ref().into());
+            self.transfer_syntax_preference
+                .iter()
+                .position(|preferred| *preferred == ts)
+                .unwrap_or(usize::MAX)
         })
     }
 }
@@ -702,6 +874,14 @@ pub struct ServerAssociation<S> {
     read_buffer: bytes::BytesMut,
     /// Timeout for individual send/receive operations
     timeout: Option<std::time::Duration>,
+    /// The accepted presentation contexts
+    /// on which verification requests are answered automatically
+    verification_context_ids: Vec<u8>,
+    /// The accepted presentation contexts
+    /// for which relational queries were negotiated
+    relational_query_context_ids: Vec<u8>,
+    /// Where to capture the raw bytes of the association
+    wire_log: Option<WireLog>,
 }
 
 impl<S> ServerAssociation<S> {
@@ -710,13 +890,49 @@ impl<S> ServerAssociation<S> {
         &self.presentation_contexts
     }
 
+    /// Obtain the transfer syntax accepted
+    /// for the presentation context with the given ID,
+    /// or `None` if the presentation context was not accepted.
+    pub fn transfer_syntax(&self, presentation_context_id: u8) -> Option<&str> {
+        self.presentation_contexts
+            .iter()
+            .find(|pc| {
+                pc.id == presentation_context_id
+                    && pc.reason == PresentationContextResultReason::Acceptance
+            })
+            .map(|pc| pc.transfer_syntax.as_str())
+    }
+
     /// Obtain the remote DICOM node's application entity title.
     pub fn client_ae_title(&self) -> &str {
         &self.client_ae_title
     }
+
+    /// Check whether relational queries were negotiated
+    /// for the query/retrieve SOP class
+    /// of the given presentation context.
+    ///
+    /// Handlers of C-FIND, C-MOVE, and C-GET requests
+    /// can use this to decide whether to admit queries
+    /// without the unique keys of the levels above the one queried.
+    /// Returns [`NotSupported`](RelationalQuerySupport::NotSupported)
+    /// if the requester did not ask for relational queries,
+    /// if they were not granted
+    /// (see [`ServerAssociationOptions::relational_queries`]),
+    /// or if the presentation context was not accepted.
+    pub fn relational_query_support(&self, presentation_context_id: u8) -> RelationalQuerySupport {
+        if self
+            .relational_query_context_ids
+            .contains(&presentation_context_id)
+        {
+            RelationalQuerySupport::Supported
+        } else {
+            RelationalQuerySupport::NotSupported
+        }
+    }
 }
 
-impl ServerAssociation<TcpStream> {
+impl<S: SyncSocket> ServerAssociation<S> {
     /// Send a PDU message to the other intervenient.
     pub fn send(&mut self, msg: &Pdu) -> Result<()> {
         self.buffer.clear();
@@ -727,14 +943,37 @@ impl ServerAssociation<TcpStream> {
             }
             .fail();
         }
-        self.socket.write_all(&self.buffer).context(WireSendSnafu)
+        WireTap::new(&mut self.socket, self.wire_log.clone())
+            .write_all(&self.buffer)
+            .context(WireSendSnafu)
     }
 
     /// Read a PDU message from the other intervenient.
+    ///
+    /// If [automatic verification][1] is enabled,
+    /// C-ECHO requests are answered here
+    /// and the next message is received instead.
+    ///
+    /// [1]: ServerAssociationOptions::auto_verification
     pub fn receive(&mut self) -> Result<Pdu> {
+        loop {
+            let pdu = self.receive_pdu()?;
+            match auto_echo_response(&pdu, &self.verification_context_ids) {
+                Some(response) => {
+                    for pdu in PDataFragmenter::new(response, self.requestor_max_pdu_length) {
+                        self.send(&pdu)?;
+                    }
+                }
+                None => return Ok(pdu),
+            }
+        }
+    }
+
+    /// Read the next PDU message from the other intervenient as is.
+    fn receive_pdu(&mut self) -> Result<Pdu> {
         use std::io::{BufRead, BufReader, Cursor};
 
-        let mut reader = BufReader::new(&mut self.socket);
+        let mut reader = BufReader::new(WireTap::new(&mut self.socket, self.wire_log.clone()));
 
         loop {
             let mut buf = Cursor::new(&self.read_buffer[..]);
@@ -772,7 +1011,7 @@ impl ServerAssociation<TcpStream> {
             ),
         };
         let out = self.send(&pdu);
-        let _ = self.socket.shutdown(std::net::Shutdown::Both);
+        let _ = self.socket.close();
         out
     }
 
@@ -781,12 +1020,19 @@ impl ServerAssociation<TcpStream> {
     ///
     /// Returns a writer which automatically
     /// splits the inner data into separate PDUs if necessary.
-    pub fn send_pdata(&mut self, presentation_context_id: u8) -> PDataWriter<&mut TcpStream> {
+    /// If the transfer syntax of the presentation context
+    /// compresses data sets,
+    /// the data set written is compressed before sending.
+    pub fn send_pdata(&mut self, presentation_context_id: u8) -> PDataWriter<WireTap<&mut S>> {
         PDataWriter::new(
-            &mut self.socket,
+            WireTap::new(&mut self.socket, self.wire_log.clone()),
             presentation_context_id,
             self.requestor_max_pdu_length,
         )
+        .with_dataset_adapter(dataset_adapter(
+            &self.presentation_contexts,
+            presentation_context_id,
+        ))
     }
 
     /// Prepare a P-Data reader for receiving
@@ -794,12 +1040,16 @@ impl ServerAssociation<TcpStream> {
     ///
     /// Returns a reader which automatically
     /// receives more data PDUs once the bytes collected are consumed.
-    pub fn receive_pdata(&mut self) -> PDataReader<&mut TcpStream> {
+    /// If the transfer syntax of the presentation context
+    /// compresses data sets,
+    /// the data set received is decompressed.
+    pub fn receive_pdata(&mut self) -> PDataReader<WireTap<&mut S>> {
         PDataReader::new(
-            &mut self.socket,
+            WireTap::new(&mut self.socket, self.wire_log.clone()),
             self.acceptor_max_pdu_length,
             &mut self.read_buffer,
         )
+        .with_dataset_adapters(dataset_adapters(&self.presentation_contexts))
     }
 
     /// Obtain access to the inner TCP stream
@@ -811,11 +1061,53 @@ impl ServerAssociation<TcpStream> {
     /// **Note:** reading and writing should be done with care
     /// to avoid inconsistencies in the association state.
     /// Do not call `send` and `receive` while not in a PDU boundary.
-    pub fn inner_stream(&mut self) -> &mut TcpStream {
+    pub fn inner_stream(&mut self) -> &mut S {
         &mut self.socket
     }
 }
 
+impl ServerAssociation<TcpStream> {
+    /// Wait for a release request from the association requester
+    /// and reply with a release response,
+    /// then hand over the TCP stream instead of shutting it down.
+    ///
+    /// This allows the same connection to be reused
+    /// for other protocols after the DICOM association is released.
+    /// Returns the TCP stream
+    /// and any bytes which were already read from it
+    /// but are not part of the release exchange
+    /// (see [`into_parts`](Self::into_parts)).
+    pub fn release_and_take_stream(mut self) -> Result<(TcpStream, Vec<u8>)> {
+        match self.receive()? {
+            Pdu::ReleaseRQ => {}
+            pdu @ Pdu::AssociationRQ { .. }
+            | pdu @ Pdu::AssociationAC { .. }
+            | pdu @ Pdu::AssociationRJ { .. }
+            | pdu @ Pdu::PData { .. }
+            | pdu @ Pdu::ReleaseRP
+            | pdu @ Pdu::AbortRQ { .. } => return UnexpectedRequestSnafu { pdu }.fail(),
+            pdu @ Pdu::Unknown { .. } => return UnknownRequestSnafu { pdu }.fail(),
+        }
+        self.send(&Pdu::ReleaseRP)?;
+        Ok(self.into_parts())
+    }
+
+    /// Take the underlying TCP stream out of the association,
+    /// alongside any bytes which were already read from the stream
+    /// but not consumed as part of a PDU.
+    ///
+    /// No release or abort messages are sent,
+    /// and the TCP connection is not shut down.
+    pub fn into_parts(self) -> (TcpStream, Vec<u8>) {
+        let ServerAssociation {
+            socket,
+            read_buffer,
+            ..
+        } = self;
+        (socket, read_buffer.to_vec())
+    }
+}
+
 /// Check that a transfer syntax repository
 /// supports the given transfer syntax,
 /// meaning that it can parse and decode DICOM data sets.
@@ -880,22 +1172,30 @@ pub mod non_blocking {
     use bytes::{Buf, BytesMut};
     use snafu::{ensure, ResultExt};
     use tokio::{
-        io::{AsyncReadExt, AsyncWriteExt},
+        io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
         net::TcpStream,
     };
 
+    #[cfg(feature = "async-tls")]
+    use super::TlsSnafu;
     use super::{
         AccessControl, Result, SendSnafu, SendTooLongPduSnafu, ServerAssociation,
         ServerAssociationOptions, WireSendSnafu,
     };
+    #[cfg(feature = "async-tls")]
+    use crate::association::tls::{self, rustls};
     use crate::{
         association::{
+            negotiation::respond_relational_queries,
+            reassembly::PDataFragmenter,
             server::{
                 AbortedSnafu, ConnectionClosedSnafu, MissingAbstractSyntaxSnafu,
                 ReceiveRequestSnafu, ReceiveSnafu, RejectedSnafu, SendResponseSnafu,
                 UnexpectedRequestSnafu, UnknownRequestSnafu, WireReadSnafu,
             },
             uid::trim_uid,
+            verification::{auto_echo_response, VERIFICATION_SOP_CLASS},
+            wire_log::WireTap,
         },
         pdu::{
             AbortRQServiceProviderReason, AbortRQSource, AssociationAC, AssociationRJ,
@@ -905,6 +1205,8 @@ pub mod non_blocking {
         },
         read_pdu, write_pdu, Pdu, IMPLEMENTATION_CLASS_UID, IMPLEMENTATION_VERSION_NAME,
     };
+    #[cfg(feature = "async-tls")]
+    use std::sync::Arc;
 
     impl<A> ServerAssociationOptions<'_, A>
     where
@@ -913,13 +1215,54 @@ pub mod non_blocking {
         /// Negotiate an association with the given TCP stream.
         pub async fn establish_async(
             &self,
-            mut socket: TcpStream,
+            socket: TcpStream,
         ) -> Result<ServerAssociation<TcpStream>> {
+            self.establish_impl_async(socket).await
+        }
+
+        /// Perform a TLS handshake on the given TCP stream
+        /// and negotiate an association on top of it.
+        ///
+        /// A failed handshake results in [`Error::Tls`](super::Error::Tls),
+        /// in which case the requester is not notified
+        /// through an association rejection.
+        /// Whether the requester must present a certificate
+        /// is decided by the given server configuration.
+        /// See the [`tls`](crate::association::tls) module for more details.
+        ///
+        /// Requires the `async-tls` Cargo feature.
+        #[cfg(feature = "async-tls")]
+        pub async fn establish_tls_async(
+            &self,
+            socket: TcpStream,
+            config: Arc<rustls::ServerConfig>,
+        ) -> Result<ServerAssociation<tls::non_blocking::ServerTlsStream>> {
+            let task = async {
+                tls::non_blocking::accept(socket, config)
+                    .await
+                    .context(TlsSnafu)
+            };
+            let socket = if let Some(timeout) = self.timeout {
+                tokio::time::timeout(timeout, task)
+                    .await
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::TimedOut, err))
+                    .context(WireReadSnafu)?
+            } else {
+                task.await
+            }?;
+            self.establish_impl_async(socket).await
+        }
+
+        async fn establish_impl_async<S>(&self, socket: S) -> Result<ServerAssociation<S>>
+        where
+            S: AsyncRead + AsyncWrite + Unpin,
+        {
             ensure!(
-                !self.abstract_syntax_uids.is_empty() || self.promiscuous,
+                !self.abstract_syntax_uids.is_empty() || self.promiscuous || self.auto_verification,
                 MissingAbstractSyntaxSnafu
             );
             let timeout = self.timeout;
+            let mut socket = WireTap::new(socket, self.wire_log.clone());
             let task = async {
                 let max_pdu_length = self.max_pdu_length;
                 let mut read_buffer = BytesMut::with_capacity(MAXIMUM_PDU_SIZE as usize);
@@ -1030,13 +1373,17 @@ pub mod non_blocking {
                             requestor_max_pdu_length
                         };
 
+                        let mut verification_context_ids = Vec::new();
+                        let mut accepted_contexts = Vec::new();
                         let presentation_contexts: Vec<_> = presentation_contexts
                             .into_iter()
                             .map(|pc| {
-                                if !self
-                                    .abstract_syntax_uids
-                                    .contains(&trim_uid(Cow::from(pc.abstract_syntax)))
+                                let abstract_syntax = trim_uid(Cow::from(pc.abstract_syntax));
+                                let is_verification =
+                                    self.auto_verification && abstract_syntax == VERIFICATION_SOP_CLASS;
+                                if !self.abstract_syntax_uids.contains(&abstract_syntax)
                                     && !self.promiscuous
+                                    && !is_verification
                                 {
                                     return PresentationContextResult {
                                         id: pc.id,
@@ -1055,6 +1402,14 @@ pub mod non_blocking {
                                         )
                                     });
 
+                                if is_verification && reason == PresentationContextResultReason::Acceptance {
+                                    verification_context_ids.push(pc.id);
+                                }
+
+                                if reason == PresentationContextResultReason::Acceptance {
+                                    accepted_contexts.push((pc.id, abstract_syntax.into_owned()));
+                                }
+
                                 PresentationContextResult {
                                     id: pc.id,
                                     reason,
@@ -1063,6 +1418,23 @@ pub mod non_blocking {
                             })
                             .collect();
 
+                        let (extended_negotiation, relational_query_context_ids) =
+                            respond_relational_queries(
+                                &user_variables,
+                                &accepted_contexts,
+                                self.relational_queries,
+                            );
+                        let mut response_user_variables = vec![
+                            UserVariableItem::MaxLength(max_pdu_length),
+                            UserVariableItem::ImplementationClassUID(
+                                IMPLEMENTATION_CLASS_UID.to_string(),
+                            ),
+                            UserVariableItem::ImplementationVersionName(
+                                IMPLEMENTATION_VERSION_NAME.to_string(),
+                            ),
+                        ];
+                        response_user_variables.extend(extended_negotiation);
+
                         write_pdu(
                             &mut buffer,
                             &Pdu::AssociationAC(AssociationAC {
@@ -1071,15 +1443,7 @@ pub mod non_blocking {
                                 presentation_contexts: presentation_contexts.clone(),
                                 calling_ae_title: calling_ae_title.clone(),
                                 called_ae_title,
-                                user_variables: vec![
-                                    UserVariableItem::MaxLength(max_pdu_length),
-                                    UserVariableItem::ImplementationClassUID(
-                                        IMPLEMENTATION_CLASS_UID.to_string(),
-                                    ),
-                                    UserVariableItem::ImplementationVersionName(
-                                        IMPLEMENTATION_VERSION_NAME.to_string(),
-                                    ),
-                                ],
+                                user_variables: response_user_variables,
                             }),
                         )
                         .context(SendResponseSnafu)?;
@@ -1089,12 +1453,15 @@ pub mod non_blocking {
                             presentation_contexts,
                             requestor_max_pdu_length,
                             acceptor_max_pdu_length: max_pdu_length,
-                            socket,
+                            socket: socket.into_inner(),
                             client_ae_title: calling_ae_title,
                             buffer,
                             strict: self.strict,
                             read_buffer: BytesMut::with_capacity(MAXIMUM_PDU_SIZE as usize),
                             timeout,
+                            verification_context_ids,
+                            relational_query_context_ids,
+                            wire_log: self.wire_log.clone(),
                         })
                     }
                     Pdu::ReleaseRQ => {
@@ -1121,101 +1488,132 @@ pub mod non_blocking {
         }
     }
 
-    impl ServerAssociation<TcpStream> {
-        /// Send a PDU message to the other intervenient.
-        pub async fn send(&mut self, msg: &Pdu) -> Result<()> {
-            let timeout = self.timeout;
-            let task = async {
-                self.buffer.clear();
-                write_pdu(&mut self.buffer, msg).context(SendSnafu)?;
-                if self.buffer.len() > self.requestor_max_pdu_length as usize {
-                    return SendTooLongPduSnafu {
-                        length: self.buffer.len(),
+    /// Implement the async association API
+    /// for an association acceptor over the given stream type.
+    macro_rules! impl_async_server_association {
+        ($stream:ty) => {
+            impl ServerAssociation<$stream> {
+                /// Send a PDU message to the other intervenient.
+                pub async fn send(&mut self, msg: &Pdu) -> Result<()> {
+                    let timeout = self.timeout;
+                    let task = async {
+                        self.buffer.clear();
+                        write_pdu(&mut self.buffer, msg).context(SendSnafu)?;
+                        if self.buffer.len() > self.requestor_max_pdu_length as usize {
+                            return SendTooLongPduSnafu {
+                                length: self.buffer.len(),
+                            }
+                            .fail();
+                        }
+                        WireTap::new(&mut self.socket, self.wire_log.clone())
+                            .write_all(&self.buffer)
+                            .await
+                            .context(WireSendSnafu)
+                    };
+                    if let Some(timeout) = timeout {
+                        tokio::time::timeout(timeout, task)
+                            .await
+                            .map_err(|err| std::io::Error::new(std::io::ErrorKind::TimedOut, err))
+                            .context(WireSendSnafu)?
+                    } else {
+                        task.await
                     }
-                    .fail();
                 }
-                self.socket
-                    .write_all(&self.buffer)
-                    .await
-                    .context(WireSendSnafu)
-            };
-            if let Some(timeout) = timeout {
-                tokio::time::timeout(timeout, task)
-                    .await
-                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::TimedOut, err))
-                    .context(WireSendSnafu)?
-            } else {
-                task.await
-            }
-        }
 
-        /// Read a PDU message from the other intervenient.
-        pub async fn receive(&mut self) -> Result<Pdu> {
-            let timeout = self.timeout;
-            let task = async {
-                loop {
-                    let mut buf = Cursor::new(&self.read_buffer[..]);
-                    match read_pdu(&mut buf, self.requestor_max_pdu_length, self.strict)
-                        .context(ReceiveRequestSnafu)?
-                    {
-                        Some(pdu) => {
-                            self.read_buffer.advance(buf.position() as usize);
-                            return Ok(pdu);
+                /// Read a PDU message from the other intervenient.
+                ///
+                /// If [automatic verification][1] is enabled,
+                /// C-ECHO requests are answered here
+                /// and the next message is received instead.
+                ///
+                /// [1]: ServerAssociationOptions::auto_verification
+                pub async fn receive(&mut self) -> Result<Pdu> {
+                    loop {
+                        let pdu = self.receive_pdu().await?;
+                        match auto_echo_response(&pdu, &self.verification_context_ids) {
+                            Some(response) => {
+                                let max_pdu_length = self.requestor_max_pdu_length;
+                                for pdu in PDataFragmenter::new(response, max_pdu_length) {
+                                    self.send(&pdu).await?;
+                                }
+                            }
+                            None => return Ok(pdu),
                         }
-                        None => {
-                            // Reset position
-                            buf.set_position(0)
+                    }
+                }
+
+                /// Read the next PDU message from the other intervenient as is.
+                async fn receive_pdu(&mut self) -> Result<Pdu> {
+                    let timeout = self.timeout;
+                    let task = async {
+                        loop {
+                            let mut buf = Cursor::new(&self.read_buffer[..]);
+                            match read_pdu(&mut buf, self.requestor_max_pdu_length, self.strict)
+                                .context(ReceiveRequestSnafu)?
+                            {
+                                Some(pdu) => {
+                                    self.read_buffer.advance(buf.position() as usize);
+                                    return Ok(pdu);
+                                }
+                                None => {
+                                    // Reset position
+                                    buf.set_position(0)
+                                }
+                            }
+                            let recv = WireTap::new(&mut self.socket, self.wire_log.clone())
+                                .read_buf(&mut self.read_buffer)
+                                .await
+                                .context(ReadPduSnafu)
+                                .context(ReceiveSnafu)?;
+                            ensure!(recv > 0, ConnectionClosedSnafu);
                         }
+                    };
+                    if let Some(timeout) = timeout {
+                        tokio::time::timeout(timeout, task)
+                            .await
+                            .map_err(|err| std::io::Error::new(std::io::ErrorKind::TimedOut, err))
+                            .context(ReadPduSnafu)
+                            .context(ReceiveSnafu)?
+                    } else {
+                        task.await
                     }
-                    let recv = self
-                        .socket
-                        .read_buf(&mut self.read_buffer)
-                        .await
-                        .context(ReadPduSnafu)
-                        .context(ReceiveSnafu)?;
-                    ensure!(recv > 0, ConnectionClosedSnafu);
                 }
-            };
-            if let Some(timeout) = timeout {
-                tokio::time::timeout(timeout, task)
-                    .await
-                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::TimedOut, err))
-                    .context(ReadPduSnafu)
-                    .context(ReceiveSnafu)?
-            } else {
-                task.await
-            }
-        }
 
-        /// Send a provider initiated abort message
-        /// and shut down the TCP connection,
-        /// terminating the association.
-        pub async fn abort(mut self) -> Result<()> {
-            let timeout = self.timeout;
-            let task = async {
-                let pdu = Pdu::AbortRQ {
-                    source: AbortRQSource::ServiceProvider(
-                        AbortRQServiceProviderReason::ReasonNotSpecified,
-                    ),
-                };
-                let out = self.send(&pdu).await;
-                let _ = self.socket.shutdown().await;
-                out
-            };
-            if let Some(timeout) = timeout {
-                tokio::time::timeout(timeout, task)
-                    .await
-                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::TimedOut, err))
-                    .context(WireSendSnafu)?
-            } else {
-                task.await
-            }
-        }
+                /// Send a provider initiated abort message
+                /// and shut down the TCP connection,
+                /// terminating the association.
+                pub async fn abort(mut self) -> Result<()> {
+                    let timeout = self.timeout;
+                    let task = async {
+                        let pdu = Pdu::AbortRQ {
+                            source: AbortRQSource::ServiceProvider(
+                                AbortRQServiceProviderReason::ReasonNotSpecified,
+                            ),
+                        };
+                        let out = self.send(&pdu).await;
+                        let _ = self.socket.shutdown().await;
+                        out
+                    };
+                    if let Some(timeout) = timeout {
+                        tokio::time::timeout(timeout, task)
+                            .await
+                            .map_err(|err| std::io::Error::new(std::io::ErrorKind::TimedOut, err))
+                            .context(WireSendSnafu)?
+                    } else {
+                        task.await
+                    }
+                }
 
-        pub fn inner_stream(&mut self) -> &mut TcpStream {
-            &mut self.socket
-        }
+                pub fn inner_stream(&mut self) -> &mut $stream {
+                    &mut self.socket
+                }
+            }
+        };
     }
+
+    impl_async_server_association!(TcpStream);
+    #[cfg(feature = "async-tls")]
+    impl_async_server_association!(crate::association::tls::non_blocking::ServerTlsStream);
 }
 
 #[cfg(test)]
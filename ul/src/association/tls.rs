@@ -0,0 +1,257 @@
+//! Support for associations over TLS,
+//! as described in PS3.15 B.1 (Basic TLS Secure Transport Connection Profile).
+//!
+//! With the `tls` Cargo feature,
+//! an association can be established over a TLS connection
+//! by passing a [`rustls`] configuration
+//! to [`ClientAssociationOptions::establish_tls`]
+//! or [`ServerAssociationOptions::establish_tls`]
+//! (or their async counterparts with the `async-tls` feature).
+//! The TLS handshake takes place before the association is negotiated,
+//! and a failed handshake is reported as a [`TlsError`].
+//! The identity of the association acceptor
+//! is verified against the server name given by the requester,
+//! as per the requester's client configuration.
+//!
+//! Releasing or aborting the association
+//! sends a TLS `close_notify` alert before shutting down the TCP connection,
+//! so that the peer does not regard the stream as truncated.
+//!
+//! No cryptography provider is installed as the process default.
+//! Configurations should be built with the [`ring`][1] provider,
+//! which is always available through the re-exported [`rustls`] crate.
+//!
+//! A [wire log](super::wire_log) installed on the association
+//! captures the plaintext bytes of the association,
+//! not the TLS records.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use dicom_ul::association::client::ClientAssociationOptions;
+//! # use dicom_ul::association::tls::rustls;
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! # let ca_certificate: rustls::pki_types::CertificateDer = unimplemented!();
+//! let mut roots = rustls::RootCertStore::empty();
+//! roots.add(ca_certificate)?;
+//! let provider = Arc::new(rustls::crypto::ring::default_provider());
+//! let config = rustls::ClientConfig::builder_with_provider(provider)
+//!     .with_safe_default_protocol_versions()?
+//!     .with_root_certificates(roots)
+//!     .with_no_client_auth();
+//!
+//! let association = ClientAssociationOptions::new()
+//!     .with_abstract_syntax("1.2.840.10008.1.1")
+//!     .establish_tls("pacs.example.com:2762", Arc::new(config), "pacs.example.com")?;
+//! association.release()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ClientAssociationOptions::establish_tls`]: super::ClientAssociationOptions::establish_tls
+//! [`ServerAssociationOptions::establish_tls`]: super::ServerAssociationOptions::establish_tls
+//! [1]: rustls::crypto::ring
+use std::convert::TryFrom;
+use std::io;
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::ops::DerefMut;
+use std::sync::Arc;
+
+use rustls::pki_types::{InvalidDnsNameError, ServerName};
+use rustls::{
+    ClientConfig, ClientConnection, ConnectionCommon, ServerConfig, ServerConnection, SideData,
+    StreamOwned,
+};
+use snafu::{Backtrace, ResultExt, Snafu};
+
+use super::client::{CloseSocket, SyncSocket};
+
+pub use rustls;
+
+/// A blocking TLS stream from an association requester to the acceptor.
+pub type ClientTlsStream = StreamOwned<ClientConnection, TcpStream>;
+
+/// A blocking TLS stream from an association acceptor to the requester.
+pub type ServerTlsStream = StreamOwned<ServerConnection, TcpStream>;
+
+/// An error which may occur when establishing a TLS connection.
+#[derive(Debug, Snafu)]
+#[non_exhaustive]
+pub enum TlsError {
+    /// invalid TLS server name `{server_name}`
+    InvalidServerName {
+        server_name: String,
+        source: InvalidDnsNameError,
+        backtrace: Backtrace,
+    },
+
+    /// could not set up TLS connection
+    Setup {
+        source: rustls::Error,
+        backtrace: Backtrace,
+    },
+
+    /// TLS handshake failed
+    Handshake {
+        source: rustls::Error,
+        backtrace: Backtrace,
+    },
+
+    /// TLS handshake failed due to an I/O error
+    HandshakeIo {
+        source: io::Error,
+        backtrace: Backtrace,
+    },
+}
+
+pub type Result<T, E = TlsError> = std::result::Result<T, E>;
+
+/// Interpret the given name as the TLS server name to verify.
+pub(crate) fn server_name(server_name: &str) -> Result<ServerName<'static>> {
+    ServerName::try_from(server_name)
+        .map(|name| name.to_owned())
+        .context(InvalidServerNameSnafu { server_name })
+}
+
+/// Perform the client side of a TLS handshake over the given TCP stream.
+pub(crate) fn connect(
+    socket: TcpStream,
+    config: Arc<ClientConfig>,
+    server_name: ServerName<'static>,
+) -> Result<ClientTlsStream> {
+    let conn = ClientConnection::new(config, server_name).context(SetupSnafu)?;
+    handshake(conn, socket)
+}
+
+/// Perform the server side of a TLS handshake over the given TCP stream.
+pub(crate) fn accept(socket: TcpStream, config: Arc<ServerConfig>) -> Result<ServerTlsStream> {
+    let conn = ServerConnection::new(config).context(SetupSnafu)?;
+    handshake(conn, socket)
+}
+
+fn handshake<C, D>(mut conn: C, mut socket: TcpStream) -> Result<StreamOwned<C, TcpStream>>
+where
+    C: DerefMut<Target = ConnectionCommon<D>>,
+    D: SideData,
+{
+    while conn.is_handshaking() {
+        conn.complete_io(&mut socket).map_err(handshake_error)?;
+    }
+    Ok(StreamOwned::new(conn, socket))
+}
+
+/// Recover the TLS error which caused a handshake to fail, if any.
+pub(crate) fn handshake_error(e: io::Error) -> TlsError {
+    let source = e
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+        .cloned();
+    match source {
+        Some(source) => HandshakeSnafu.into_error(source),
+        None => HandshakeIoSnafu.into_error(e),
+    }
+}
+
+/// Send a TLS `close_notify` alert and shut down the TCP connection.
+impl<C, D> CloseSocket for StreamOwned<C, TcpStream>
+where
+    C: DerefMut<Target = ConnectionCommon<D>>,
+    D: SideData,
+{
+    fn close(&mut self) -> io::Result<()> {
+        self.conn.send_close_notify();
+        while self.conn.wants_write() {
+            self.conn.write_tls(&mut self.sock)?;
+        }
+        self.sock.shutdown(Shutdown::Both)
+    }
+}
+
+impl<C, D> SyncSocket for StreamOwned<C, TcpStream>
+where
+    C: DerefMut<Target = ConnectionCommon<D>>,
+    D: SideData,
+{
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.sock.local_addr()
+    }
+}
+
+#[cfg(feature = "async-tls")]
+pub mod non_blocking {
+    //! Async TLS streams,
+    //! available with the `async-tls` Cargo feature.
+    use std::io;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    use rustls::pki_types::ServerName;
+    use rustls::{ClientConfig, ServerConfig};
+    use tokio::io::AsyncWriteExt;
+    use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+    use super::{handshake_error, Result};
+    use crate::association::client::{non_blocking::AsyncSocket, CloseSocket};
+
+    pub use tokio_rustls;
+
+    /// An async TLS stream from an association requester to the acceptor.
+    pub type ClientTlsStream = tokio_rustls::client::TlsStream<tokio::net::TcpStream>;
+
+    /// An async TLS stream from an association acceptor to the requester.
+    pub type ServerTlsStream = tokio_rustls::server::TlsStream<tokio::net::TcpStream>;
+
+    /// Perform the client side of a TLS handshake over the given TCP stream.
+    pub(crate) async fn connect(
+        socket: tokio::net::TcpStream,
+        config: Arc<ClientConfig>,
+        server_name: ServerName<'static>,
+    ) -> Result<ClientTlsStream> {
+        TlsConnector::from(config)
+            .connect(server_name, socket)
+            .await
+            .map_err(handshake_error)
+    }
+
+    /// Perform the server side of a TLS handshake over the given TCP stream.
+    pub(crate) async fn accept(
+        socket: tokio::net::TcpStream,
+        config: Arc<ServerConfig>,
+    ) -> Result<ServerTlsStream> {
+        TlsAcceptor::from(config)
+            .accept(socket)
+            .await
+            .map_err(handshake_error)
+    }
+
+    /// Send a TLS `close_notify` alert and shut down the TCP connection.
+    impl CloseSocket for ClientTlsStream {
+        fn close(&mut self) -> io::Result<()> {
+            tokio::task::block_in_place(move || {
+                tokio::runtime::Handle::current().block_on(async move { self.shutdown().await })
+            })
+        }
+    }
+
+    /// Send a TLS `close_notify` alert and shut down the TCP connection.
+    impl CloseSocket for ServerTlsStream {
+        fn close(&mut self) -> io::Result<()> {
+            tokio::task::block_in_place(move || {
+                tokio::runtime::Handle::current().block_on(async move { self.shutdown().await })
+            })
+        }
+    }
+
+    impl AsyncSocket for ClientTlsStream {
+        fn local_addr(&self) -> io::Result<SocketAddr> {
+            self.get_ref().0.local_addr()
+        }
+    }
+
+    impl AsyncSocket for ServerTlsStream {
+        fn local_addr(&self) -> io::Result<SocketAddr> {
+            self.get_ref().0.local_addr()
+        }
+    }
+}
@@ -0,0 +1,321 @@
+//! Minimal built-in support for the Verification service class,
+//! used by [`ServerAssociation`](super::ServerAssociation)
+//! to answer C-ECHO requests automatically
+//! and by the [`scu`](super::scu) helpers to issue them.
+//!
+//! DIMSE command sets are always encoded in Implicit VR Little Endian,
+//! regardless of the transfer syntax negotiated for the presentation context,
+//! so only the few command elements needed here are parsed and written.
+
+use super::reassembly::{PDataMessage, PDataReassembler};
+use crate::pdu::{PDataValueType, Pdu};
+
+/// The Verification SOP Class UID
+pub(crate) const VERIFICATION_SOP_CLASS: &str = "1.2.840.10008.1.1";
+
+/// Command Group Length (0000,0000)
+pub(super) const COMMAND_GROUP_LENGTH: u16 = 0x0000;
+/// Affected SOP Class UID (0000,0002)
+pub(super) const AFFECTED_SOP_CLASS_UID: u16 = 0x0002;
+/// Command Field (0000,0100)
+pub(super) const COMMAND_FIELD: u16 = 0x0100;
+/// Message ID (0000,0110)
+pub(super) const MESSAGE_ID: u16 = 0x0110;
+/// Message ID Being Responded To (0000,0120)
+pub(super) const MESSAGE_ID_BEING_RESPONDED_TO: u16 = 0x0120;
+/// Command Data Set Type (0000,0800)
+pub(super) const COMMAND_DATA_SET_TYPE: u16 = 0x0800;
+/// Status (0000,0900)
+pub(super) const STATUS: u16 = 0x0900;
+
+/// Command Field value of a C-ECHO-RQ
+const C_ECHO_RQ: u16 = 0x0030;
+/// Command Field value of a C-ECHO-RSP
+const C_ECHO_RSP: u16 = 0x8030;
+/// Command Data Set Type value for no data set
+const NO_DATA_SET: u16 = 0x0101;
+
+/// The parts of a C-ECHO request which are relevant for responding to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EchoRequest<'a> {
+    /// the affected SOP class UID, including padding
+    pub affected_sop_class_uid: &'a [u8],
+    /// the message ID
+    pub message_id: u16,
+}
+
+/// Iterate over the elements of a command set in Implicit VR Little Endian,
+/// yielding the element number and value of each one.
+///
+/// Iteration stops at the first element
+/// which does not belong to the command group
+/// or which is not complete.
+pub(super) fn command_elements(command: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut rest = command;
+    std::iter::from_fn(move || {
+        if rest.len() < 8 {
+            return None;
+        }
+        let group = u16::from_le_bytes([rest[0], rest[1]]);
+        let element = u16::from_le_bytes([rest[2], rest[3]]);
+        let len = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        if group != 0x0000 || rest.len() - 8 < len {
+            return None;
+        }
+        let value = &rest[8..8 + len];
+        rest = &rest[8 + len..];
+        Some((element, value))
+    })
+}
+
+pub(super) fn read_us(value: &[u8]) -> Option<u16> {
+    match value {
+        [a, b] => Some(u16::from_le_bytes([*a, *b])),
+        _ => None,
+    }
+}
+
+/// Interpret a full command set as a C-ECHO request.
+///
+/// Returns `None` if the command is not a C-ECHO-RQ
+/// or if it announces a data set.
+pub(crate) fn parse_echo_request(command: &[u8]) -> Option<EchoRequest<'_>> {
+    let mut command_field = None;
+    let mut message_id = None;
+    let mut data_set_type = None;
+    let mut affected_sop_class_uid: &[u8] = VERIFICATION_SOP_CLASS.as_bytes();
+
+    for (element, value) in command_elements(command) {
+        match element {
+            AFFECTED_SOP_CLASS_UID => affected_sop_class_uid = value,
+            COMMAND_FIELD => command_field = read_us(value),
+            MESSAGE_ID => message_id = read_us(value),
+            COMMAND_DATA_SET_TYPE => data_set_type = read_us(value),
+            _ => {}
+        }
+    }
+
+    if command_field != Some(C_ECHO_RQ) || data_set_type != Some(NO_DATA_SET) {
+        return None;
+    }
+
+    Some(EchoRequest {
+        affected_sop_class_uid,
+        message_id: message_id?,
+    })
+}
+
+pub(super) fn write_element(out: &mut Vec<u8>, element: u16, value: &[u8]) {
+    out.extend_from_slice(&0x0000_u16.to_le_bytes());
+    out.extend_from_slice(&element.to_le_bytes());
+    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value);
+}
+
+/// Encode a UID as a command element value,
+/// padded to even length.
+pub(super) fn uid_value(uid: &[u8]) -> Vec<u8> {
+    let mut value = uid.to_vec();
+    if value.len() % 2 != 0 {
+        value.push(0);
+    }
+    value
+}
+
+/// Complete a command set
+/// by prepending the Command Group Length element
+/// to the encoded elements in `body`.
+pub(super) fn command_set(body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 12);
+    write_element(
+        &mut out,
+        COMMAND_GROUP_LENGTH,
+        &(body.len() as u32).to_le_bytes(),
+    );
+    out.extend_from_slice(body);
+    out
+}
+
+/// Encode the command set of a successful C-ECHO response
+/// to the given request in Implicit VR Little Endian.
+pub(crate) fn echo_response_command(request: &EchoRequest) -> Vec<u8> {
+    let mut body = Vec::with_capacity(64);
+    write_element(
+        &mut body,
+        AFFECTED_SOP_CLASS_UID,
+        &uid_value(request.affected_sop_class_uid),
+    );
+    write_element(&mut body, COMMAND_FIELD, &C_ECHO_RSP.to_le_bytes());
+    write_element(
+        &mut body,
+        MESSAGE_ID_BEING_RESPONDED_TO,
+        &request.message_id.to_le_bytes(),
+    );
+    write_element(&mut body, COMMAND_DATA_SET_TYPE, &NO_DATA_SET.to_le_bytes());
+    // success
+    write_element(&mut body, STATUS, &0x0000_u16.to_le_bytes());
+    command_set(&body)
+}
+
+/// Encode the command set of a C-ECHO request
+/// on the Verification SOP class in Implicit VR Little Endian.
+pub(crate) fn echo_request_command(message_id: u16) -> Vec<u8> {
+    let mut body = Vec::with_capacity(56);
+    write_element(
+        &mut body,
+        AFFECTED_SOP_CLASS_UID,
+        &uid_value(VERIFICATION_SOP_CLASS.as_bytes()),
+    );
+    write_element(&mut body, COMMAND_FIELD, &C_ECHO_RQ.to_le_bytes());
+    write_element(&mut body, MESSAGE_ID, &message_id.to_le_bytes());
+    write_element(&mut body, COMMAND_DATA_SET_TYPE, &NO_DATA_SET.to_le_bytes());
+    command_set(&body)
+}
+
+/// The parts of a DIMSE response which are relevant to the requester.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct CommandResponse {
+    /// the ID of the request message
+    pub message_id_being_responded_to: u16,
+    /// the status code of the operation
+    pub status: u16,
+}
+
+/// Interpret a full command set as a C-ECHO response.
+///
+/// Returns `None` if the command is not a C-ECHO-RSP
+/// or if any of the relevant elements is missing.
+pub(crate) fn parse_echo_response(command: &[u8]) -> Option<CommandResponse> {
+    parse_response(command, C_ECHO_RSP)
+}
+
+/// Interpret a full command set as a response
+/// with the given command field.
+///
+/// Returns `None` if the command has another command field
+/// or if any of the relevant elements is missing.
+pub(super) fn parse_response(
+    command: &[u8],
+    expected_command_field: u16,
+) -> Option<CommandResponse> {
+    let mut command_field = None;
+    let mut message_id_being_responded_to = None;
+    let mut status = None;
+
+    for (element, value) in command_elements(command) {
+        match element {
+            COMMAND_FIELD => command_field = read_us(value),
+            MESSAGE_ID_BEING_RESPONDED_TO => message_id_being_responded_to = read_us(value),
+            STATUS => status = read_us(value),
+            _ => {}
+        }
+    }
+
+    if command_field != Some(expected_command_field) {
+        return None;
+    }
+
+    Some(CommandResponse {
+        message_id_being_responded_to: message_id_being_responded_to?,
+        status: status?,
+    })
+}
+
+/// Produce the response to a received PDU
+/// if it is a complete C-ECHO request
+/// on one of the given presentation contexts.
+///
+/// Only requests sent in a single P-DATA-TF PDU are recognized,
+/// everything else is left for the application to handle.
+pub(crate) fn auto_echo_response(pdu: &Pdu, context_ids: &[u8]) -> Option<PDataMessage> {
+    let data = match pdu {
+        Pdu::PData { data } => data,
+        _ => return None,
+    };
+
+    if !context_ids.contains(&data.first()?.presentation_context_id) {
+        return None;
+    }
+
+    // the PDU must hold exactly one full command and nothing else
+    let mut reassembler = PDataReassembler::new();
+    let mut messages = reassembler.push_all(data.iter().cloned()).ok()?;
+    if messages.len() != 1 || !reassembler.is_idle() {
+        return None;
+    }
+    let message = messages.pop()?;
+    if !message.is_command() {
+        return None;
+    }
+    let request = parse_echo_request(&message.data)?;
+
+    Some(PDataMessage {
+        presentation_context_id: message.presentation_context_id,
+        value_type: PDataValueType::Command,
+        data: echo_response_command(&request),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdu::PDataValue;
+
+    #[test]
+    fn parse_and_respond_to_echo() {
+        let command = echo_request_command(7);
+        let request = parse_echo_request(&command).unwrap();
+        assert_eq!(request.message_id, 7);
+        assert_eq!(request.affected_sop_class_uid, b"1.2.840.10008.1.1\0");
+
+        let response = echo_response_command(&request);
+        let elements: Vec<_> = command_elements(&response).collect();
+        assert_eq!(
+            elements,
+            vec![
+                (COMMAND_GROUP_LENGTH, &66_u32.to_le_bytes()[..]),
+                (AFFECTED_SOP_CLASS_UID, &b"1.2.840.10008.1.1\0"[..]),
+                (COMMAND_FIELD, &[0x30, 0x80][..]),
+                (MESSAGE_ID_BEING_RESPONDED_TO, &[7, 0][..]),
+                (COMMAND_DATA_SET_TYPE, &[0x01, 0x01][..]),
+                (STATUS, &[0, 0][..]),
+            ]
+        );
+
+        assert_eq!(
+            parse_echo_response(&response),
+            Some(CommandResponse {
+                message_id_being_responded_to: 7,
+                status: 0,
+            })
+        );
+        // a request is not a response
+        assert_eq!(parse_echo_response(&command), None);
+    }
+
+    #[test]
+    fn ignore_other_commands() {
+        // C-STORE-RQ
+        let mut command = Vec::new();
+        write_element(&mut command, COMMAND_FIELD, &0x0001_u16.to_le_bytes());
+        write_element(&mut command, MESSAGE_ID, &1_u16.to_le_bytes());
+        write_element(
+            &mut command,
+            COMMAND_DATA_SET_TYPE,
+            &0x0000_u16.to_le_bytes(),
+        );
+        assert_eq!(parse_echo_request(&command), None);
+
+        // C-ECHO-RQ on a context which is not verification
+        let pdu = Pdu::PData {
+            data: vec![PDataValue {
+                presentation_context_id: 3,
+                value_type: PDataValueType::Command,
+                is_last: true,
+                data: echo_request_command(1),
+            }],
+        };
+        assert_eq!(auto_echo_response(&pdu, &[1]), None);
+        assert!(auto_echo_response(&pdu, &[3]).is_some());
+    }
+}
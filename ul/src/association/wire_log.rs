@@ -0,0 +1,470 @@
+//! Byte-level capture of association traffic.
+//!
+//! A [`WireLog`] can be installed on an association requester
+//! or acceptor via
+//! [`ClientAssociationOptions::wire_log`](super::ClientAssociationOptions::wire_log)
+//! or [`ServerAssociationOptions::wire_log`](super::ServerAssociationOptions::wire_log),
+//! so that all raw bytes sent and received through the association
+//! are copied to the log,
+//! from the association request until the stream is released.
+//! This is useful for diagnosing interoperability issues
+//! with other DICOM nodes.
+//!
+//! The log starts with the 8-byte magic code [`MAGIC`],
+//! followed by a sequence of records.
+//! Each record is made of a direction byte
+//! (`1` for bytes sent, `2` for bytes received),
+//! the time of capture in microseconds since the Unix epoch
+//! as a 64-bit big endian integer,
+//! the length of the data as a 32-bit big endian integer,
+//! and the data itself.
+//! A log can be read back with [`WireLogReader`].
+//!
+//! Bytes written or read directly through the inner stream
+//! (e.g. via `inner_stream`) are not captured.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use dicom_ul::association::client::ClientAssociationOptions;
+//! # use dicom_ul::association::wire_log::{WireLog, WireLogReader};
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let wire_log = WireLog::create("association.wirelog")?;
+//! let association = ClientAssociationOptions::new()
+//!     .with_abstract_syntax("1.2.840.10008.1.1")
+//!     .wire_log(wire_log)
+//!     .establish("129.168.0.5:104")?;
+//! association.release()?;
+//!
+//! let file = std::io::BufReader::new(std::fs::File::open("association.wirelog")?);
+//! for record in WireLogReader::new(file)? {
+//!     let record = record?;
+//!     println!("{:?} {} bytes", record.direction, record.data.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::convert::TryInto;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The magic code at the start of every wire log.
+pub const MAGIC: &[u8; 8] = b"DCMWLOG1";
+
+/// The direction of the bytes in a wire log record,
+/// from the perspective of the application entity capturing them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Bytes sent to the other node
+    Sent,
+    /// Bytes received from the other node
+    Received,
+}
+
+impl Direction {
+    fn to_byte(self) -> u8 {
+        match self {
+            Direction::Sent => 1,
+            Direction::Received => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Direction::Sent),
+            2 => Some(Direction::Received),
+            _ => None,
+        }
+    }
+}
+
+/// A single record of a wire log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireRecord {
+    /// whether the bytes were sent or received
+    pub direction: Direction,
+    /// the time at which the bytes were captured
+    pub timestamp: SystemTime,
+    /// the raw bytes
+    pub data: Vec<u8>,
+}
+
+/// A shared destination for capturing the raw bytes of associations.
+///
+/// The handle is cheap to clone,
+/// and all clones write to the same destination.
+/// When the same log is used by multiple associations at once,
+/// each record is written in full before the next one,
+/// but records of different associations may be interleaved.
+/// Failing to write to the log does not interrupt the association.
+#[derive(Clone)]
+pub struct WireLog {
+    writer: Arc<Mutex<dyn Write + Send>>,
+    /// the contents of the log if it is kept in memory
+    memory: Option<Arc<Mutex<Vec<u8>>>>,
+}
+
+impl fmt::Debug for WireLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WireLog").finish_non_exhaustive()
+    }
+}
+
+impl WireLog {
+    /// Create a wire log writing to the given destination.
+    ///
+    /// The magic code is written immediately.
+    pub fn new<W>(mut writer: W) -> io::Result<Self>
+    where
+        W: Write + Send + 'static,
+    {
+        writer.write_all(MAGIC)?;
+        Ok(WireLog {
+            writer: Arc::new(Mutex::new(writer)),
+            memory: None,
+        })
+    }
+
+    /// Create a wire log which keeps its contents in memory,
+    /// so that they can be inspected later with [`records`](Self::records).
+    pub fn in_memory() -> Self {
+        let memory = Arc::new(Mutex::new(MAGIC.to_vec()));
+        WireLog {
+            writer: memory.clone(),
+            memory: Some(memory),
+        }
+    }
+
+    /// Create a new wire log file at the given path,
+    /// truncating it if it already exists.
+    ///
+    /// Writes are buffered,
+    /// and flushed when the last handle to the log is dropped
+    /// or when [`flush`](Self::flush) is called.
+    pub fn create<P>(path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = std::fs::File::create(path)?;
+        Self::new(io::BufWriter::new(file))
+    }
+
+    /// Read back all records written so far
+    /// to a log created with [`in_memory`](Self::in_memory).
+    ///
+    /// Fails with [`Unsupported`](io::ErrorKind::Unsupported)
+    /// if the log is written to another destination.
+    pub fn records(&self) -> io::Result<Vec<WireRecord>> {
+        let memory = self.memory.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Unsupported, "wire log is not kept in memory")
+        })?;
+        let bytes = memory
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        WireLogReader::new(&bytes[..])?.collect()
+    }
+
+    /// Flush the underlying destination.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        writer.flush()
+    }
+
+    /// Write a record with the given bytes to the log,
+    /// timestamped with the current time.
+    ///
+    /// Nothing is written if `data` is empty.
+    pub fn record(&self, direction: Direction, data: &[u8]) -> io::Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+
+        let mut writer = self
+            .writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // records larger than 4 GiB are split
+        for chunk in data.chunks(u32::MAX as usize) {
+            let mut header = [0; 13];
+            header[0] = direction.to_byte();
+            header[1..9].copy_from_slice(&timestamp.to_be_bytes());
+            header[9..].copy_from_slice(&(chunk.len() as u32).to_be_bytes());
+            writer.write_all(&header)?;
+            writer.write_all(chunk)?;
+        }
+        Ok(())
+    }
+
+    fn record_or_warn(&self, direction: Direction, data: &[u8]) {
+        if let Err(e) = self.record(direction, data) {
+            tracing::warn!("Could not write to wire log: {}", e);
+        }
+    }
+}
+
+/// A reader of wire log records.
+///
+/// Iterating over this reader yields each record in the log.
+#[derive(Debug)]
+pub struct WireLogReader<R> {
+    reader: R,
+    done: bool,
+}
+
+impl<R> WireLogReader<R>
+where
+    R: Read,
+{
+    /// Start reading a wire log,
+    /// checking its magic code.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a DICOM wire log",
+            ));
+        }
+        Ok(WireLogReader {
+            reader,
+            done: false,
+        })
+    }
+
+    /// Read the next record,
+    /// or `None` if the end of the log was reached.
+    pub fn read_record(&mut self) -> io::Result<Option<WireRecord>> {
+        let mut direction = [0];
+        if self.reader.read(&mut direction)? == 0 {
+            return Ok(None);
+        }
+        let direction = Direction::from_byte(direction[0]).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid wire log record direction {}", direction[0]),
+            )
+        })?;
+
+        let mut header = [0; 12];
+        self.reader.read_exact(&mut header)?;
+        let micros = u64::from_be_bytes(header[..8].try_into().unwrap());
+        let len = u32::from_be_bytes(header[8..].try_into().unwrap());
+
+        let mut data = Vec::new();
+        (&mut self.reader)
+            .take(u64::from(len))
+            .read_to_end(&mut data)?;
+        if data.len() != len as usize {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(Some(WireRecord {
+            direction,
+            timestamp: UNIX_EPOCH + Duration::from_micros(micros),
+            data,
+        }))
+    }
+
+    /// Retrieve the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R> Iterator for WireLogReader<R>
+where
+    R: Read,
+{
+    type Item = io::Result<WireRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let out = self.read_record().transpose();
+        if !matches!(out, Some(Ok(_))) {
+            self.done = true;
+        }
+        out
+    }
+}
+
+/// A stream wrapper which copies the bytes read from and written to it
+/// into a [`WireLog`], if any.
+///
+/// This is the stream type of the P-Data readers and writers
+/// obtained from an association.
+#[derive(Debug)]
+pub struct WireTap<S> {
+    inner: S,
+    log: Option<WireLog>,
+}
+
+impl<S> WireTap<S> {
+    /// Wrap the given stream,
+    /// capturing its traffic into `log` if it is not `None`.
+    pub fn new(inner: S, log: Option<WireLog>) -> Self {
+        WireTap { inner, log }
+    }
+
+    /// Retrieve a reference to the inner stream.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Retrieve a mutable reference to the inner stream.
+    ///
+    /// Bytes read or written through this reference are not captured.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Retrieve the inner stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Read for WireTap<S>
+where
+    S: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if let Some(log) = &self.log {
+            log.record_or_warn(Direction::Received, &buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+impl<S> Write for WireTap<S>
+where
+    S: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        if let Some(log) = &self.log {
+            log.record_or_warn(Direction::Sent, &buf[..n]);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "async")]
+mod non_blocking {
+    use super::{Direction, WireTap};
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    impl<S> AsyncRead for WireTap<S>
+    where
+        S: AsyncRead + Unpin,
+    {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            let this = self.get_mut();
+            let filled = buf.filled().len();
+            let out = Pin::new(&mut this.inner).poll_read(cx, buf);
+            if let (Poll::Ready(Ok(())), Some(log)) = (&out, &this.log) {
+                log.record_or_warn(Direction::Received, &buf.filled()[filled..]);
+            }
+            out
+        }
+    }
+
+    impl<S> AsyncWrite for WireTap<S>
+    where
+        S: AsyncWrite + Unpin,
+    {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            let this = self.get_mut();
+            let out = Pin::new(&mut this.inner).poll_write(cx, buf);
+            if let (Poll::Ready(Ok(n)), Some(log)) = (&out, &this.log) {
+                log.record_or_warn(Direction::Sent, &buf[..*n]);
+            }
+            out
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wire_log_roundtrip() {
+        let log = WireLog::in_memory();
+
+        let sent = b"\x07\x00\x00\x00\x00\x04\x00\x00\x00\x00";
+        let mut tap = WireTap::new(Vec::new(), Some(log.clone()));
+        tap.write_all(sent).unwrap();
+        assert_eq!(tap.into_inner(), sent);
+        let mut received = [0; 6];
+        WireTap::new(&b"abcdef"[..], Some(log.clone()))
+            .read_exact(&mut received)
+            .unwrap();
+        // empty reads are not recorded
+        let _ = WireTap::new(&b""[..], Some(log.clone()))
+            .read(&mut received)
+            .unwrap();
+
+        let records = log.records().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].direction, Direction::Sent);
+        assert_eq!(records[0].data, sent);
+        assert_eq!(records[1].direction, Direction::Received);
+        assert_eq!(records[1].data, b"abcdef");
+        assert!(records[0].timestamp <= records[1].timestamp);
+    }
+
+    #[test]
+    fn wire_log_reader_rejects_bad_input() {
+        assert!(WireLogReader::new(&b"DICM\0\0\0\0"[..]).is_err());
+
+        // truncated record
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 4, 0xAB]);
+        let mut reader = WireLogReader::new(&bytes[..]).unwrap();
+        assert!(matches!(reader.next(), Some(Err(_))));
+        assert!(reader.next().is_none());
+
+        // bad direction
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&[9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let mut reader = WireLogReader::new(&bytes[..]).unwrap();
+        assert!(matches!(reader.next(), Some(Err(_))));
+    }
+}
@@ -20,6 +20,16 @@
 //! ## Features
 //! * `async`: Enables a fully async implementation of the upper layer protocol.
 //!   See [`ClientAssociationOptions`] and [`ServerAssociationOptions`] for details
+//! * `tls`: Enables establishing associations over TLS with [`rustls`][1].
+//!   See the `association::tls` module for details
+//! * `async-tls`: Enables the async counterparts of the TLS support,
+//!   implying both `async` and `tls`
+//! * `deflate` (default): Enables support for
+//!   _Deflated Explicit VR Little Endian_,
+//!   so that data sets are compressed and decompressed
+//!   when sent and received through P-Data writers and readers
+//!
+//! [1]: https://crates.io/crates/rustls
 
 pub mod address;
 pub mod association;
@@ -9,7 +9,7 @@ pub mod writer;
 
 use std::fmt::Display;
 
-pub use reader::read_pdu;
+pub use reader::{read_pdu, read_pdu_split_pdv};
 use snafu::{Backtrace, Snafu};
 pub use writer::{write_pdu, WriteChunkError};
 
@@ -96,6 +96,20 @@ pub enum ReadError {
     #[snafu(display("Invalid item length {} (must be >=2)", length))]
     InvalidItemLength { length: u32 },
 
+    #[snafu(display("Incomplete presentation data value item ({} bytes left over)", length))]
+    IncompletePdvItem { length: usize, backtrace: Backtrace },
+
+    #[snafu(display(
+        "Presentation data value item of {} bytes does not fit in a PDU of up to {} bytes",
+        length,
+        max_pdu_length
+    ))]
+    PdvItemTooLarge {
+        length: u32,
+        max_pdu_length: u32,
+        backtrace: Backtrace,
+    },
+
     #[snafu(display("Could not read {} reserved bytes", bytes))]
     ReadReserved {
         bytes: u32,
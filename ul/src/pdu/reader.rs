@@ -31,7 +31,38 @@ pub const MAXIMUM_PDU_SIZE: u32 = crate::pdu::MAXIMUM_PDU_SIZE;
 pub const PDU_HEADER_SIZE: u32 = crate::pdu::PDU_HEADER_SIZE;
 
 /// Read a PDU from the given byte buffer.
-pub fn read_pdu(mut buf: impl Buf, max_pdu_length: u32, strict: bool) -> Result<Option<Pdu>> {
+pub fn read_pdu(buf: impl Buf, max_pdu_length: u32, strict: bool) -> Result<Option<Pdu>> {
+    read_pdu_impl(buf, max_pdu_length, strict, None)
+}
+
+/// Read a PDU from the given byte buffer,
+/// tolerating presentation data value items
+/// which are split across consecutive P-DATA-TF PDUs.
+///
+/// The standard requires each P-DATA-TF PDU to contain whole items,
+/// but not all peers abide by this.
+/// Here, the bytes of an incomplete item at the end of a P-DATA-TF PDU
+/// are moved to `pending`,
+/// to be completed by the next P-DATA-TF PDU
+/// read with the same `pending` buffer.
+/// Reading any other PDU except for an A-ABORT
+/// while bytes are still pending is an error,
+/// and so is an item which would not fit in a single PDU.
+pub fn read_pdu_split_pdv(
+    buf: impl Buf,
+    max_pdu_length: u32,
+    strict: bool,
+    pending: &mut Vec<u8>,
+) -> Result<Option<Pdu>> {
+    read_pdu_impl(buf, max_pdu_length, strict, Some(pending))
+}
+
+fn read_pdu_impl(
+    mut buf: impl Buf,
+    max_pdu_length: u32,
+    strict: bool,
+    pending: Option<&mut Vec<u8>>,
+) -> Result<Option<Pdu>> {
     ensure!(
         (super::MINIMUM_PDU_SIZE..=super::MAXIMUM_PDU_SIZE).contains(&max_pdu_length),
         InvalidMaxPduSnafu { max_pdu_length }
@@ -81,6 +112,17 @@ pub fn read_pdu(mut buf: impl Buf, max_pdu_length: u32, strict: bool) -> Result<
     let mut bytes = buf.copy_to_bytes(pdu_length as usize);
     let codec = DefaultCharacterSetCodec;
 
+    // only P-DATA-TF can complete a pending item,
+    // whereas an abort takes precedence over it
+    if let Some(pending) = &pending {
+        ensure!(
+            pdu_type == 0x04 || pdu_type == 0x07 || pending.is_empty(),
+            IncompletePdvItemSnafu {
+                length: pending.len()
+            }
+        );
+    }
+
     match pdu_type {
         0x01 => {
             // A-ASSOCIATE-RQ PDU Structure
@@ -302,59 +344,41 @@ pub fn read_pdu(mut buf: impl Buf, max_pdu_length: u32, strict: bool) -> Result<
             // or more Presentation-data-value Items(s). For a complete description of the use of
             // this field see Section 9.3.5.1
             let mut values = vec![];
-            while bytes.has_remaining() {
-                // Presentation Data Value Item Structure
-
-                // 1-4 - Item-length - This Item-length shall be the number of bytes from the first
-                // byte of the following field to the last byte of the Presentation-data-value
-                // field. It shall be encoded as an unsigned binary number.
-                if bytes.remaining() < 4 + 1 + 1 {
-                    return Ok(None);
-                }
-                let item_length = bytes.get_u32();
-
-                ensure!(
-                    item_length >= 2,
-                    InvalidItemLengthSnafu {
-                        length: item_length
+            match pending {
+                Some(pending) => {
+                    let mut items = std::mem::take(pending);
+                    items.extend_from_slice(&bytes);
+                    let consumed = read_pdv_items(&items, &mut values)?;
+                    items.drain(..consumed);
+                    // the incomplete item must still fit in a single PDU,
+                    // so that the pending bytes do not grow without bounds
+                    if let Some(header) = items.get(..4) {
+                        let length =
+                            u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+                        let max_pdu_length = if strict {
+                            max_pdu_length
+                        } else {
+                            super::MAXIMUM_PDU_SIZE
+                        };
+                        ensure!(
+                            length <= max_pdu_length - 4,
+                            PdvItemTooLargeSnafu {
+                                length,
+                                max_pdu_length
+                            }
+                        );
                     }
-                );
-
-                // 5 - Presentation-context-ID - Presentation-context-ID values shall be odd
-                // integers between 1 and 255, encoded as an unsigned binary number. For a complete
-                // description of the use of this field see Section 7.1.1.13.
-                let presentation_context_id = bytes.get_u8();
-
-                // 6-xxx - Presentation-data-value - This Presentation-data-value field shall
-                // contain DICOM message information (command and/or data set) with a message
-                // control header. For a complete description of the use of this field see Annex E.
-
-                // The Message Control Header shall be made of one byte with the least significant
-                // bit (bit 0) taking one of the following values: If bit 0 is set
-                // to 1, the following fragment shall contain Message Command information.
-                // If bit 0 is set to 0, the following fragment shall contain Message Data Set
-                // information. The next least significant bit (bit 1) shall be
-                // defined by the following rules: If bit 1 is set to 1, the
-                // following fragment shall contain the last fragment of a Message Data Set or of a
-                // Message Command. If bit 1 is set to 0, the following fragment
-                // does not contain the last fragment of a Message Data Set or of a Message Command.
-                let header = bytes.get_u8();
-
-                let value_type = if header & 0x01 > 0 {
-                    PDataValueType::Command
-                } else {
-                    PDataValueType::Data
-                };
-                let is_last = (header & 0x02) > 0;
-                if bytes.remaining() < (item_length - 2) as usize {
-                    return Ok(None);
+                    *pending = items;
+                }
+                None => {
+                    let consumed = read_pdv_items(&bytes, &mut values)?;
+                    ensure!(
+                        consumed == bytes.len(),
+                        IncompletePdvItemSnafu {
+                            length: bytes.len() - consumed
+                        }
+                    );
                 }
-                values.push(PDataValue {
-                    presentation_context_id,
-                    value_type,
-                    is_last,
-                    data: bytes.copy_to_bytes((item_length - 2) as usize).to_vec(),
-                });
             }
 
             Ok(Some(Pdu::PData { data: values }))
@@ -426,6 +450,69 @@ pub fn read_pdu(mut buf: impl Buf, max_pdu_length: u32, strict: bool) -> Result<
     }
 }
 
+/// Read the presentation data value items of a P-DATA-TF PDU
+/// into `values`, up to the first incomplete item.
+///
+/// Returns the number of bytes consumed.
+fn read_pdv_items(bytes: &[u8], values: &mut Vec<PDataValue>) -> Result<usize> {
+    let mut buf = bytes;
+    while buf.len() >= 4 {
+        // Presentation Data Value Item Structure
+
+        // 1-4 - Item-length - This Item-length shall be the number of bytes from the first
+        // byte of the following field to the last byte of the Presentation-data-value
+        // field. It shall be encoded as an unsigned binary number.
+        let item_length = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]);
+
+        ensure!(
+            item_length >= 2,
+            InvalidItemLengthSnafu {
+                length: item_length
+            }
+        );
+        // compare without adding to the item length, which could overflow
+        if buf.len() - 4 < item_length as usize {
+            break;
+        }
+        buf.advance(4);
+
+        // 5 - Presentation-context-ID - Presentation-context-ID values shall be odd
+        // integers between 1 and 255, encoded as an unsigned binary number. For a complete
+        // description of the use of this field see Section 7.1.1.13.
+        let presentation_context_id = buf.get_u8();
+
+        // 6-xxx - Presentation-data-value - This Presentation-data-value field shall
+        // contain DICOM message information (command and/or data set) with a message
+        // control header. For a complete description of the use of this field see Annex E.
+
+        // The Message Control Header shall be made of one byte with the least significant
+        // bit (bit 0) taking one of the following values: If bit 0 is set
+        // to 1, the following fragment shall contain Message Command information.
+        // If bit 0 is set to 0, the following fragment shall contain Message Data Set
+        // information. The next least significant bit (bit 1) shall be
+        // defined by the following rules: If bit 1 is set to 1, the
+        // following fragment shall contain the last fragment of a Message Data Set or of a
+        // Message Command. If bit 1 is set to 0, the following fragment
+        // does not contain the last fragment of a Message Data Set or of a Message Command.
+        let header = buf.get_u8();
+
+        let value_type = if header & 0x01 > 0 {
+            PDataValueType::Command
+        } else {
+            PDataValueType::Data
+        };
+        let is_last = (header & 0x02) > 0;
+        values.push(PDataValue {
+            presentation_context_id,
+            value_type,
+            is_last,
+            data: buf.copy_to_bytes((item_length - 2) as usize).to_vec(),
+        });
+    }
+
+    Ok(bytes.len() - buf.len())
+}
+
 fn read_pdu_variable(mut buf: impl Buf, codec: &dyn TextCodec) -> Result<Option<PduVariableItem>> {
     // 1 - Item-type - XXH
     if buf.remaining() < 1 {
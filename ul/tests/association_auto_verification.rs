@@ -0,0 +1,118 @@
+use dicom_ul::{
+    association::client::ClientAssociationOptions,
+    pdu::{PDataValue, PDataValueType, Pdu},
+};
+
+mod common;
+
+static SCU_AE_TITLE: &str = "ECHO-SCU";
+static SCP_AE_TITLE: &str = "AUTO-ECHO-SCP";
+
+static IMPLICIT_VR_LE: &str = "1.2.840.10008.1.2";
+static EXPLICIT_VR_LE: &str = "1.2.840.10008.1.2.1";
+static VERIFICATION_SOP_CLASS: &str = "1.2.840.10008.1.1";
+
+/// Write a command element in Implicit VR Little Endian
+fn put_element(out: &mut Vec<u8>, element: u16, value: &[u8]) {
+    out.extend_from_slice(&0x0000_u16.to_le_bytes());
+    out.extend_from_slice(&element.to_le_bytes());
+    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value);
+}
+
+/// Read a command element value in Implicit VR Little Endian
+fn get_element(mut command: &[u8], element: u16) -> Option<&[u8]> {
+    while command.len() >= 8 {
+        let e = u16::from_le_bytes([command[2], command[3]]);
+        let len = u32::from_le_bytes([command[4], command[5], command[6], command[7]]) as usize;
+        let value = command.get(8..8 + len)?;
+        if e == element {
+            return Some(value);
+        }
+        command = &command[8 + len..];
+    }
+    None
+}
+
+fn echo_request(message_id: u16) -> Vec<u8> {
+    let mut body = Vec::new();
+    // Affected SOP Class UID
+    put_element(&mut body, 0x0002, b"1.2.840.10008.1.1\0");
+    // Command Field: C-ECHO-RQ
+    put_element(&mut body, 0x0100, &0x0030_u16.to_le_bytes());
+    // Message ID
+    put_element(&mut body, 0x0110, &message_id.to_le_bytes());
+    // Command Data Set Type: no data set
+    put_element(&mut body, 0x0800, &0x0101_u16.to_le_bytes());
+
+    let mut command = Vec::new();
+    // Command Group Length
+    put_element(&mut command, 0x0000, &(body.len() as u32).to_le_bytes());
+    command.extend_from_slice(&body);
+    command
+}
+
+/// Run an SCP with automatic verification
+/// and send C-ECHO requests to it.
+#[test]
+fn scu_scp_auto_verification() {
+    let (scp_handle, scp_addr) = common::spawn_auto_verification_scp(SCP_AE_TITLE).unwrap();
+
+    let mut association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(VERIFICATION_SOP_CLASS, vec![EXPLICIT_VR_LE, IMPLICIT_VR_LE])
+        .establish(scp_addr)
+        .unwrap();
+
+    let pc = association
+        .presentation_contexts()
+        .first()
+        .expect("verification presentation context should be accepted")
+        .clone();
+
+    for message_id in 1..=2 {
+        association
+            .send(&Pdu::PData {
+                data: vec![PDataValue {
+                    presentation_context_id: pc.id,
+                    value_type: PDataValueType::Command,
+                    is_last: true,
+                    data: echo_request(message_id),
+                }],
+            })
+            .unwrap();
+
+        let data = match association.receive().unwrap() {
+            Pdu::PData { data } => data,
+            pdu => panic!("Unexpected PDU {:?}", pdu),
+        };
+        assert_eq!(data.len(), 1);
+        let pdv = &data[0];
+        assert_eq!(pdv.presentation_context_id, pc.id);
+        assert_eq!(pdv.value_type, PDataValueType::Command);
+        assert!(pdv.is_last);
+
+        // C-ECHO-RSP
+        assert_eq!(
+            get_element(&pdv.data, 0x0100),
+            Some(&0x8030_u16.to_le_bytes()[..])
+        );
+        // Message ID Being Responded To
+        assert_eq!(
+            get_element(&pdv.data, 0x0120),
+            Some(&message_id.to_le_bytes()[..])
+        );
+        // Status: success
+        assert_eq!(get_element(&pdv.data, 0x0900), Some(&[0, 0][..]));
+    }
+
+    association
+        .release()
+        .expect("did not have a peaceful release");
+
+    scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+}
@@ -0,0 +1,159 @@
+use dicom_ul::{
+    association::client::{ClientAssociationOptions, Error},
+    association::server::ServerAssociationOptions,
+    pdu::Pdu,
+};
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+static SCU_AE_TITLE: &str = "ECHO-SCU";
+static SCP_AE_TITLE: &str = "ECHO-SCP";
+
+static VERIFICATION_SOP_CLASS: &str = "1.2.840.10008.1.1";
+
+/// Spawn an SCP on 127.0.0.1 which reports the address of the requester.
+fn spawn_scp() -> Result<(std::thread::JoinHandle<Result<SocketAddr>>, SocketAddr)> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(VERIFICATION_SOP_CLASS);
+
+    let h = std::thread::spawn(move || -> Result<SocketAddr> {
+        let (stream, peer_addr) = listener.accept()?;
+        let mut association = scp.establish(stream)?;
+
+        let pdu = association.receive()?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP)?;
+
+        Ok(peer_addr)
+    });
+    Ok((h, addr))
+}
+
+/// Establish an association from the given local address,
+/// checking that the connection originates from it.
+fn run_scu_bound(scp_addr: SocketAddr, bind_address: SocketAddr) -> SocketAddr {
+    let association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(VERIFICATION_SOP_CLASS)
+        .bind_address(bind_address)
+        .establish(scp_addr)
+        .unwrap();
+
+    let local_addr = association.local_addr().unwrap();
+    assert_eq!(local_addr.ip(), bind_address.ip());
+    assert_ne!(local_addr.port(), 0);
+    if bind_address.port() != 0 {
+        assert_eq!(local_addr.port(), bind_address.port());
+    }
+
+    association.release().unwrap();
+    local_addr
+}
+
+#[test]
+fn scu_binds_to_ephemeral_port() {
+    let (scp_handle, scp_addr) = spawn_scp().unwrap();
+
+    let local_addr = run_scu_bound(
+        scp_addr,
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
+    );
+
+    let peer_addr = scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+    assert_eq!(peer_addr, local_addr);
+}
+
+/// The whole 127.0.0.0/8 block is bound to the loopback interface on Linux
+#[cfg(target_os = "linux")]
+#[test]
+fn scu_binds_to_other_loopback_address() {
+    let (scp_handle, scp_addr) = spawn_scp().unwrap();
+
+    let local_addr = run_scu_bound(
+        scp_addr,
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)), 0),
+    );
+
+    let peer_addr = scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+    assert_eq!(peer_addr, local_addr);
+    assert_eq!(peer_addr.ip(), IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
+}
+
+#[test]
+fn scu_bind_failure_is_not_a_connect_failure() {
+    // nothing will be accepted here
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let scp_addr = listener.local_addr().unwrap();
+
+    // address from TEST-NET-1, not assigned to any local interface
+    let bind_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)), 0);
+    let result = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(VERIFICATION_SOP_CLASS)
+        .bind_address(bind_address)
+        .establish(scp_addr);
+
+    match result {
+        Err(Error::Bind { address, .. }) => assert_eq!(address, bind_address),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("association should not have been established"),
+    }
+}
+
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread")]
+async fn scu_binds_to_ephemeral_port_async() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let scp_addr = listener.local_addr().unwrap();
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(VERIFICATION_SOP_CLASS);
+
+    let scp_handle = tokio::spawn(async move {
+        let (stream, peer_addr) = listener.accept().await?;
+        let mut association = scp.establish_async(stream).await?;
+
+        let pdu = association.receive().await?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP).await?;
+
+        Result::Ok(peer_addr)
+    });
+
+    let bind_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+    let association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(VERIFICATION_SOP_CLASS)
+        .bind_address(bind_address)
+        .establish_async(scp_addr)
+        .await
+        .unwrap();
+
+    let local_addr = association.local_addr().unwrap();
+    assert_eq!(local_addr.ip(), bind_address.ip());
+    assert_ne!(local_addr.port(), 0);
+
+    association.release().await.unwrap();
+
+    let peer_addr = scp_handle
+        .await
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+    assert_eq!(peer_addr, local_addr);
+}
@@ -0,0 +1,248 @@
+//! Exchange of data sets over an association
+//! which negotiated Deflated Explicit VR Little Endian.
+#![cfg(feature = "deflate")]
+
+use dicom_ul::{
+    association::client::ClientAssociationOptions,
+    association::server::ServerAssociationOptions,
+    association::wire_log::{Direction, WireLog, WireRecord},
+    pdu::{PDataValue, PDataValueType, Pdu},
+    read_pdu,
+};
+
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+static SCU_AE_TITLE: &str = "DEFLATE-SCU";
+static SCP_AE_TITLE: &str = "DEFLATE-SCP";
+
+static DEFLATED_EXPLICIT_VR_LE: &str = "1.2.840.10008.1.2.1.99";
+static SECONDARY_CAPTURE_IMAGE_STORAGE: &str = "1.2.840.10008.5.1.4.1.1.7";
+
+/// A C-STORE-RQ command set, to be sent as is
+static COMMAND: &[u8] = b"\x00\x00\x00\x00\x04\x00\x00\x00\x12\x00\x00\x00\
+\x00\x00\x00\x01\x02\x00\x00\x00\x01\x00";
+
+/// An explicit VR little endian data set with plenty of redundancy
+fn data_set() -> Vec<u8> {
+    let mut data = Vec::new();
+    // (0008,0060) CS Modality
+    data.extend(b"\x08\x00\x60\x00CS\x02\x00OT");
+    // (0010,0010) PN Patient's Name
+    data.extend(b"\x10\x00\x10\x00PN\x08\x00Doe^John");
+    // (7FE0,0010) OB Pixel Data, 64 KiB
+    data.extend(b"\xE0\x7F\x10\x00OB\x00\x00\x00\x00\x01\x00");
+    data.extend((0..0x1_0000).map(|i: u32| (i / 256) as u8));
+    data
+}
+
+/// Collect all P-Data values sent in the given wire log records
+fn pdata_values_sent(records: &[WireRecord]) -> Vec<PDataValue> {
+    let bytes: Vec<u8> = records
+        .iter()
+        .filter(|record| record.direction == Direction::Sent)
+        .flat_map(|record| record.data.iter().copied())
+        .collect();
+    let mut bytes = &bytes[..];
+    let mut values = Vec::new();
+    while !bytes.is_empty() {
+        let pdu = read_pdu(&mut bytes, 16_384, true)
+            .unwrap()
+            .expect("incomplete PDU in wire log");
+        if let Pdu::PData { data } = pdu {
+            values.extend(data);
+        }
+    }
+    values
+}
+
+/// Check that the command was sent as is
+/// and that the data set was sent in compressed form
+fn check_pdata_sent(records: &[WireRecord]) {
+    let values = pdata_values_sent(records);
+
+    let command: Vec<u8> = values
+        .iter()
+        .filter(|v| v.value_type == PDataValueType::Command)
+        .flat_map(|v| v.data.iter().copied())
+        .collect();
+    assert_eq!(command, COMMAND);
+
+    let data_len: usize = values
+        .iter()
+        .filter(|v| v.value_type == PDataValueType::Data)
+        .map(|v| v.data.len())
+        .sum();
+    let original_len = data_set().len();
+    assert!(
+        data_len < original_len / 10,
+        "data set sent with {} bytes, originally {} bytes",
+        data_len,
+        original_len
+    );
+}
+
+fn command_pdu(presentation_context_id: u8) -> Pdu {
+    Pdu::PData {
+        data: vec![PDataValue {
+            presentation_context_id,
+            value_type: PDataValueType::Command,
+            is_last: true,
+            data: COMMAND.to_vec(),
+        }],
+    }
+}
+
+/// Run an SCP which receives a command and a data set,
+/// then sends both back.
+fn spawn_scp(wire_log: WireLog) -> Result<(std::thread::JoinHandle<Result<()>>, SocketAddr)> {
+    let listener = std::net::TcpListener::bind("localhost:0")?;
+    let addr = listener.local_addr()?;
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(SECONDARY_CAPTURE_IMAGE_STORAGE)
+        .wire_log(wire_log);
+
+    let h = std::thread::spawn(move || -> Result<()> {
+        let (stream, _addr) = listener.accept()?;
+        let mut association = scp.establish(stream)?;
+
+        let pc = &association.presentation_contexts()[0];
+        assert_eq!(pc.transfer_syntax, DEFLATED_EXPLICIT_VR_LE);
+        let pc_id = pc.id;
+
+        // command arrives as is
+        let pdu = association.receive()?;
+        assert_eq!(pdu, command_pdu(pc_id));
+
+        // data set arrives decompressed
+        let mut data = Vec::new();
+        association.receive_pdata().read_to_end(&mut data)?;
+        assert_eq!(data, data_set());
+
+        // send both back
+        association.send(&command_pdu(pc_id))?;
+        let mut writer = association.send_pdata(pc_id);
+        writer.write_all(&data)?;
+        writer.finish()?;
+
+        let pdu = association.receive()?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP)?;
+
+        Ok(())
+    });
+    Ok((h, addr))
+}
+
+/// Send a data set through an association in deflated form
+/// and receive it back.
+#[test]
+fn scu_scp_deflated_data_set() {
+    let scu_log = WireLog::in_memory();
+    let scp_log = WireLog::in_memory();
+
+    let (scp_handle, scp_addr) = spawn_scp(scp_log.clone()).unwrap();
+
+    let mut association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(
+            SECONDARY_CAPTURE_IMAGE_STORAGE,
+            vec![DEFLATED_EXPLICIT_VR_LE],
+        )
+        .wire_log(scu_log.clone())
+        .establish(scp_addr)
+        .unwrap();
+
+    let pc = &association.presentation_contexts()[0];
+    assert_eq!(pc.transfer_syntax, DEFLATED_EXPLICIT_VR_LE);
+    let pc_id = pc.id;
+
+    association.send(&command_pdu(pc_id)).unwrap();
+    {
+        let mut writer = association.send_pdata(pc_id);
+        writer.write_all(&data_set()).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let pdu = association.receive().unwrap();
+    assert_eq!(pdu, command_pdu(pc_id));
+    let mut data = Vec::new();
+    association.receive_pdata().read_to_end(&mut data).unwrap();
+    assert_eq!(data, data_set());
+
+    association
+        .release()
+        .expect("did not have a peaceful release");
+
+    scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+
+    check_pdata_sent(&scu_log.records().unwrap());
+    check_pdata_sent(&scp_log.records().unwrap());
+}
+
+/// Send a data set through an async association in deflated form
+/// and receive it back.
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread")]
+async fn scu_scp_deflated_data_set_async() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let scu_log = WireLog::in_memory();
+    let scp_log = WireLog::in_memory();
+
+    let (scp_handle, scp_addr) = spawn_scp(scp_log.clone()).unwrap();
+
+    let mut association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(
+            SECONDARY_CAPTURE_IMAGE_STORAGE,
+            vec![DEFLATED_EXPLICIT_VR_LE],
+        )
+        .wire_log(scu_log.clone())
+        .establish_async(scp_addr)
+        .await
+        .unwrap();
+
+    let pc = &association.presentation_contexts()[0];
+    assert_eq!(pc.transfer_syntax, DEFLATED_EXPLICIT_VR_LE);
+    let pc_id = pc.id;
+
+    association.send(&command_pdu(pc_id)).await.unwrap();
+    {
+        let mut writer = association.send_pdata(pc_id).await;
+        writer.write_all(&data_set()).await.unwrap();
+        writer.finish().await.unwrap();
+    }
+
+    let pdu = association.receive().await.unwrap();
+    assert_eq!(pdu, command_pdu(pc_id));
+    let mut data = Vec::new();
+    association
+        .receive_pdata()
+        .read_to_end(&mut data)
+        .await
+        .unwrap();
+    assert_eq!(data, data_set());
+
+    association
+        .release()
+        .await
+        .expect("did not have a peaceful release");
+
+    scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+
+    check_pdata_sent(&scu_log.records().unwrap());
+    check_pdata_sent(&scp_log.records().unwrap());
+}
@@ -0,0 +1,142 @@
+use dicom_ul::{
+    association::client::ClientAssociationOptions,
+    association::server::ServerAssociationOptions,
+    association::RelationalQuerySupport,
+    pdu::{Pdu, UserVariableItem},
+};
+
+use std::net::SocketAddr;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+static SCU_AE_TITLE: &str = "FIND-SCU";
+static SCP_AE_TITLE: &str = "FIND-SCP";
+
+static IMPLICIT_VR_LE: &str = "1.2.840.10008.1.2";
+static EXPLICIT_VR_LE: &str = "1.2.840.10008.1.2.1";
+static STUDY_ROOT_FIND: &str = "1.2.840.10008.5.1.4.1.2.2.1";
+static CT_IMAGE_STORAGE: &str = "1.2.840.10008.5.1.4.1.1.2";
+
+/// Spawn an SCP which accepts study root C-FIND and CT image storage,
+/// granting relational queries or not,
+/// and reports the relational query support of each presentation context.
+fn spawn_scp(
+    relational_queries: bool,
+) -> Result<(
+    std::thread::JoinHandle<Result<Vec<(u8, RelationalQuerySupport)>>>,
+    SocketAddr,
+)> {
+    let listener = std::net::TcpListener::bind("localhost:0")?;
+    let addr = listener.local_addr()?;
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(STUDY_ROOT_FIND)
+        .with_abstract_syntax(CT_IMAGE_STORAGE)
+        .relational_queries(relational_queries);
+
+    let h = std::thread::spawn(move || -> Result<_> {
+        let (stream, _addr) = listener.accept()?;
+        let mut association = scp.establish(stream)?;
+
+        let support = association
+            .presentation_contexts()
+            .iter()
+            .map(|pc| (pc.id, association.relational_query_support(pc.id)))
+            .collect();
+
+        let pdu = association.receive()?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP)?;
+
+        Ok(support)
+    });
+    Ok((h, addr))
+}
+
+/// Establish an association with the given SCP,
+/// returning the relational query support granted for C-FIND.
+fn run_scu(scp_addr: SocketAddr, relational_queries: bool) -> RelationalQuerySupport {
+    let association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(STUDY_ROOT_FIND, vec![EXPLICIT_VR_LE, IMPLICIT_VR_LE])
+        .with_presentation_context(CT_IMAGE_STORAGE, vec![EXPLICIT_VR_LE, IMPLICIT_VR_LE])
+        .relational_queries(relational_queries)
+        .establish(scp_addr)
+        .unwrap();
+
+    // only query/retrieve SOP classes take part in the negotiation
+    let negotiated: Vec<_> = association
+        .user_variables()
+        .iter()
+        .filter_map(|item| match item {
+            UserVariableItem::SopClassExtendedNegotiationSubItem(uid, _) => Some(uid.as_str()),
+            _ => None,
+        })
+        .collect();
+    if relational_queries {
+        assert_eq!(negotiated, vec![STUDY_ROOT_FIND]);
+    } else {
+        assert!(negotiated.is_empty());
+    }
+
+    let support =
+        RelationalQuerySupport::from_user_variables(association.user_variables(), STUDY_ROOT_FIND);
+    association.release().unwrap();
+    support
+}
+
+#[test]
+fn scu_scp_relational_queries_granted() {
+    let (scp_handle, scp_addr) = spawn_scp(true).unwrap();
+
+    let support = run_scu(scp_addr, true);
+    assert_eq!(support, RelationalQuerySupport::Supported);
+
+    let scp_support = scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+    // C-FIND context has relational queries, storage context does not
+    assert_eq!(
+        scp_support,
+        vec![
+            (1, RelationalQuerySupport::Supported),
+            (3, RelationalQuerySupport::NotSupported),
+        ]
+    );
+}
+
+#[test]
+fn scu_scp_relational_queries_not_requested() {
+    let (scp_handle, scp_addr) = spawn_scp(true).unwrap();
+
+    let support = run_scu(scp_addr, false);
+    assert_eq!(support, RelationalQuerySupport::NotSupported);
+
+    let scp_support = scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+    assert!(scp_support
+        .iter()
+        .all(|(_, support)| !support.is_supported()));
+}
+
+#[test]
+fn scu_scp_relational_queries_not_granted() {
+    let (scp_handle, scp_addr) = spawn_scp(false).unwrap();
+
+    // the SCP replies to the negotiation, but without relational queries
+    let support = run_scu(scp_addr, true);
+    assert_eq!(support, RelationalQuerySupport::NotSupported);
+
+    let scp_support = scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+    assert!(scp_support
+        .iter()
+        .all(|(_, support)| !support.is_supported()));
+}
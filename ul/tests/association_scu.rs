@@ -0,0 +1,73 @@
+use dicom_ul::{
+    association::client::ClientAssociationOptions,
+    association::scu::{echo, Association},
+};
+
+mod common;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+static SCU_AE_TITLE: &str = "ECHO-SCU";
+static SCP_AE_TITLE: &str = "AUTO-ECHO-SCP";
+
+static IMPLICIT_VR_LE: &str = "1.2.840.10008.1.2";
+static EXPLICIT_VR_LE: &str = "1.2.840.10008.1.2.1";
+static VERIFICATION_SOP_CLASS: &str = "1.2.840.10008.1.1";
+
+fn client_options() -> ClientAssociationOptions<'static> {
+    ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(VERIFICATION_SOP_CLASS, vec![EXPLICIT_VR_LE, IMPLICIT_VR_LE])
+}
+
+/// Helper code written once against the blocking association trait
+fn echo_twice_and_release<A: Association>(mut association: A) -> Result<()> {
+    let pc_id = association.presentation_contexts()[0].id;
+    for message_id in 1..=2 {
+        let status = echo(&mut association, pc_id, message_id)?;
+        assert_eq!(status, 0);
+    }
+    association.release()?;
+    Ok(())
+}
+
+#[test]
+fn scu_echo_generic() {
+    let (scp_handle, scp_addr) = common::spawn_auto_verification_scp(SCP_AE_TITLE).unwrap();
+
+    let association = client_options().establish(scp_addr).unwrap();
+    echo_twice_and_release(association).unwrap();
+
+    scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+}
+
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread")]
+async fn scu_echo_generic_async() {
+    use dicom_ul::association::scu::{echo_async, AsyncAssociation};
+
+    /// The same helper written against the async association trait
+    async fn echo_twice_and_release<A: AsyncAssociation>(mut association: A) -> Result<()> {
+        let pc_id = association.presentation_contexts()[0].id;
+        for message_id in 1..=2 {
+            let status = echo_async(&mut association, pc_id, message_id).await?;
+            assert_eq!(status, 0);
+        }
+        association.release().await?;
+        Ok(())
+    }
+
+    let (scp_handle, scp_addr) = common::spawn_auto_verification_scp(SCP_AE_TITLE).unwrap();
+
+    let association = client_options().establish_async(scp_addr).await.unwrap();
+    echo_twice_and_release(association).await.unwrap();
+
+    scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+}
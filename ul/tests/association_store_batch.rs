@@ -0,0 +1,299 @@
+use dicom_core::{dicom_value, DataElement, VR};
+use dicom_dictionary_std::tags;
+use dicom_object::{FileMetaTableBuilder, InMemDicomObject};
+use dicom_transfer_syntax_registry::entries::{
+    EXPLICIT_VR_LITTLE_ENDIAN, IMPLICIT_VR_LITTLE_ENDIAN,
+};
+use dicom_ul::{
+    association::client::ClientAssociationOptions,
+    association::scu::{StoreBatch, StoreBatchOptions, StoreItemError},
+    association::server::ServerAssociationOptions,
+    association::PDataReassembler,
+    pdu::{PDataValue, PDataValueType, Pdu},
+};
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+static SCU_AE_TITLE: &str = "STORE-SCU";
+static SCP_AE_TITLE: &str = "STORE-SCP";
+
+static EXPLICIT_VR_LE: &str = "1.2.840.10008.1.2.1";
+static CT_IMAGE_STORAGE: &str = "1.2.840.10008.5.1.4.1.1.2";
+static SECONDARY_CAPTURE_IMAGE_STORAGE: &str = "1.2.840.10008.5.1.4.1.1.7";
+
+/// A data set received by the SCP
+#[derive(Debug, PartialEq)]
+struct Stored {
+    message_id: u16,
+    sop_class_uid: String,
+    sop_instance_uid: String,
+    data: Vec<u8>,
+}
+
+/// Read the value of a UID element in a command.
+fn uid(command: &InMemDicomObject, tag: dicom_object::Tag) -> Result<String> {
+    Ok(command
+        .element(tag)?
+        .to_str()?
+        .trim_end_matches('\0')
+        .to_string())
+}
+
+/// Build a C-STORE response with the given status.
+fn store_response(sop_class_uid: &str, message_id: u16, status: u16) -> Result<Vec<u8>> {
+    let command = InMemDicomObject::command_from_element_iter([
+        DataElement::new(
+            tags::AFFECTED_SOP_CLASS_UID,
+            VR::UI,
+            dicom_value!(Str, sop_class_uid),
+        ),
+        DataElement::new(tags::COMMAND_FIELD, VR::US, dicom_value!(U16, [0x8001])),
+        DataElement::new(
+            tags::MESSAGE_ID_BEING_RESPONDED_TO,
+            VR::US,
+            dicom_value!(U16, [message_id]),
+        ),
+        DataElement::new(
+            tags::COMMAND_DATA_SET_TYPE,
+            VR::US,
+            dicom_value!(U16, [0x0101]),
+        ),
+        DataElement::new(tags::STATUS, VR::US, dicom_value!(U16, [status])),
+    ]);
+    let mut out = Vec::new();
+    command.write_dataset_with_ts(&mut out, &IMPLICIT_VR_LITTLE_ENDIAN.erased())?;
+    Ok(out)
+}
+
+/// Spawn an SCP which stores CT images
+/// until the association is released,
+/// returning the data sets received.
+fn spawn_scp() -> Result<(std::thread::JoinHandle<Result<Vec<Stored>>>, SocketAddr)> {
+    let listener = std::net::TcpListener::bind("localhost:0")?;
+    let addr = listener.local_addr()?;
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(CT_IMAGE_STORAGE);
+
+    let h = std::thread::spawn(move || -> Result<Vec<Stored>> {
+        let (stream, _addr) = listener.accept()?;
+        let mut association = scp.establish(stream)?;
+
+        let mut stored = Vec::new();
+        let mut reassembler = PDataReassembler::new();
+        let mut command = None;
+        loop {
+            let data = match association.receive()? {
+                Pdu::PData { data } => data,
+                Pdu::ReleaseRQ => {
+                    association.send(&Pdu::ReleaseRP)?;
+                    return Ok(stored);
+                }
+                pdu => panic!("unexpected PDU {:?}", pdu),
+            };
+            for message in reassembler.push_all(data)? {
+                if message.is_command() {
+                    command = Some(message.data);
+                    continue;
+                }
+                let command = command.take().expect("data set without a command");
+                let command = InMemDicomObject::read_dataset_with_ts(
+                    &command[..],
+                    &IMPLICIT_VR_LITTLE_ENDIAN.erased(),
+                )?;
+                let item = Stored {
+                    message_id: command.element(tags::MESSAGE_ID)?.to_int()?,
+                    sop_class_uid: uid(&command, tags::AFFECTED_SOP_CLASS_UID)?,
+                    sop_instance_uid: uid(&command, tags::AFFECTED_SOP_INSTANCE_UID)?,
+                    data: message.data,
+                };
+                association.send(&Pdu::PData {
+                    data: vec![PDataValue {
+                        presentation_context_id: message.presentation_context_id,
+                        value_type: PDataValueType::Command,
+                        is_last: true,
+                        data: store_response(&item.sop_class_uid, item.message_id, 0)?,
+                    }],
+                })?;
+                stored.push(item);
+            }
+        }
+    });
+    Ok((h, addr))
+}
+
+fn client_options() -> ClientAssociationOptions<'static> {
+    ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(CT_IMAGE_STORAGE, vec![EXPLICIT_VR_LE])
+        .with_presentation_context(SECONDARY_CAPTURE_IMAGE_STORAGE, vec![EXPLICIT_VR_LE])
+}
+
+/// The data set written to each test file
+fn data_set(n: u8) -> InMemDicomObject {
+    InMemDicomObject::from_element_iter([DataElement::new(
+        tags::INSTANCE_NUMBER,
+        VR::IS,
+        dicom_value!(Str, n.to_string()),
+    )])
+}
+
+/// The data set written to each test file,
+/// as sent to the SCP
+fn data_set_bytes(n: u8) -> Vec<u8> {
+    let mut out = Vec::new();
+    data_set(n)
+        .write_dataset_with_ts(&mut out, &EXPLICIT_VR_LITTLE_ENDIAN.erased())
+        .unwrap();
+    out
+}
+
+/// Write a DICOM file in Explicit VR Little Endian
+/// into the given directory.
+fn write_file(
+    dir: &Path,
+    name: &str,
+    sop_class_uid: &str,
+    sop_instance_uid: &str,
+    n: u8,
+) -> PathBuf {
+    let file = data_set(n)
+        .with_meta(
+            FileMetaTableBuilder::new()
+                .media_storage_sop_class_uid(sop_class_uid)
+                .media_storage_sop_instance_uid(sop_instance_uid)
+                .transfer_syntax(EXPLICIT_VR_LE),
+        )
+        .unwrap();
+    let path = dir.join(name);
+    file.write_to_file(&path).unwrap();
+    path
+}
+
+/// Create a temporary directory with two CT files
+/// and a secondary capture file in between,
+/// removed when the directory is dropped.
+fn write_files() -> (TempDir, Vec<PathBuf>) {
+    let dir = tempfile::tempdir().unwrap();
+    let files = vec![
+        write_file(dir.path(), "1.dcm", CT_IMAGE_STORAGE, "1.2.3.1", 1),
+        write_file(
+            dir.path(),
+            "2.dcm",
+            SECONDARY_CAPTURE_IMAGE_STORAGE,
+            "1.2.3.2",
+            2,
+        ),
+        write_file(dir.path(), "3.dcm", CT_IMAGE_STORAGE, "1.2.3.3", 3),
+    ];
+    (dir, files)
+}
+
+#[test]
+fn scu_store_batch() {
+    let (_dir, files) = write_files();
+    let (scp_handle, scp_addr) = spawn_scp().unwrap();
+
+    let mut association = client_options().establish(scp_addr).unwrap();
+    let mut progress = Vec::new();
+    let report = StoreBatch::new(StoreBatchOptions::new())
+        .on_progress(|result| progress.push((result.index, result.is_success())))
+        .send(&mut association, &files);
+    association.release().unwrap();
+
+    assert!(!report.interrupted);
+    assert!(!report.is_success());
+    assert_eq!(report.results.len(), 3);
+    assert_eq!(report.succeeded(), 2);
+    assert_eq!(report.failed(), 1);
+    assert_eq!(report.bytes(), 20);
+
+    let first = &report.results[0];
+    assert_eq!(first.path.as_deref(), Some(files[0].as_path()));
+    assert_eq!(first.sop_instance_uid.as_deref(), Some("1.2.3.1"));
+    assert_eq!(first.status, Some(0));
+    assert!(first.error.is_none());
+
+    let second = &report.results[1];
+    assert_eq!(second.status, None);
+    assert!(matches!(
+        second.error,
+        Some(StoreItemError::NoPresentationContext { .. })
+    ));
+
+    assert_eq!(report.results[2].status, Some(0));
+    assert_eq!(progress, vec![(0, true), (1, false), (2, true)]);
+
+    let stored = scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+    assert_eq!(
+        stored,
+        vec![
+            Stored {
+                message_id: 1,
+                sop_class_uid: CT_IMAGE_STORAGE.to_string(),
+                sop_instance_uid: "1.2.3.1".to_string(),
+                data: data_set_bytes(1),
+            },
+            Stored {
+                message_id: 3,
+                sop_class_uid: CT_IMAGE_STORAGE.to_string(),
+                sop_instance_uid: "1.2.3.3".to_string(),
+                data: data_set_bytes(3),
+            },
+        ]
+    );
+}
+
+#[test]
+fn scu_store_batch_stop_on_failure() {
+    let (_dir, files) = write_files();
+    let (scp_handle, scp_addr) = spawn_scp().unwrap();
+
+    let mut association = client_options().establish(scp_addr).unwrap();
+    let report = StoreBatch::new(StoreBatchOptions::new().stop_on_failure(true))
+        .send(&mut association, &files);
+    association.release().unwrap();
+
+    assert!(report.interrupted);
+    assert_eq!(report.results.len(), 2);
+    assert_eq!(report.succeeded(), 1);
+
+    let stored = scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+    assert_eq!(stored.len(), 1);
+}
+
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread")]
+async fn scu_store_batch_async() {
+    let (_dir, files) = write_files();
+    let (scp_handle, scp_addr) = spawn_scp().unwrap();
+
+    let mut association = client_options().establish_async(scp_addr).await.unwrap();
+    let report = StoreBatch::new(StoreBatchOptions::new().message_id(10))
+        .send_async(&mut association, &files)
+        .await;
+    association.release().await.unwrap();
+
+    assert!(!report.interrupted);
+    assert_eq!(report.succeeded(), 2);
+    assert_eq!(report.failed(), 1);
+
+    let stored = scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+    let message_ids: Vec<_> = stored.iter().map(|item| item.message_id).collect();
+    assert_eq!(message_ids, vec![10, 12]);
+}
@@ -0,0 +1,79 @@
+use dicom_ul::{
+    association::client::ClientAssociationOptions,
+    association::server::ServerAssociationOptions,
+};
+
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+static SCU_AE_TITLE: &str = "TAKE-SCU";
+static SCP_AE_TITLE: &str = "TAKE-SCP";
+
+static IMPLICIT_VR_LE: &str = "1.2.840.10008.1.2";
+static VERIFICATION_SOP_CLASS: &str = "1.2.840.10008.1.1";
+
+static CUSTOM_REQUEST: &[u8] = b"HELLO-VENDOR";
+static CUSTOM_RESPONSE: &[u8] = b"WELCOME";
+
+fn spawn_scp() -> Result<(std::thread::JoinHandle<Result<()>>, SocketAddr)> {
+    let listener = std::net::TcpListener::bind("localhost:0")?;
+    let addr = listener.local_addr()?;
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(VERIFICATION_SOP_CLASS);
+
+    let h = std::thread::spawn(move || -> Result<()> {
+        let (stream, _addr) = listener.accept()?;
+        let association = scp.establish(stream)?;
+
+        // release the association, but keep the connection
+        let (mut stream, leftover) = association.release_and_take_stream()?;
+
+        // custom exchange over the same connection
+        let mut request = leftover;
+        while request.len() < CUSTOM_REQUEST.len() {
+            let mut buf = [0; 64];
+            let n = stream.read(&mut buf)?;
+            assert!(n > 0, "connection closed before custom request");
+            request.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(request, CUSTOM_REQUEST);
+        stream.write_all(CUSTOM_RESPONSE)?;
+
+        Ok(())
+    });
+    Ok((h, addr))
+}
+
+/// Release an association and reuse the same TCP connection
+/// for a custom exchange on both ends.
+#[test]
+fn scu_scp_release_and_take_stream() {
+    let (scp_handle, scp_addr) = spawn_scp().unwrap();
+
+    let association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(VERIFICATION_SOP_CLASS, vec![IMPLICIT_VR_LE])
+        .establish(scp_addr)
+        .unwrap();
+
+    let (mut stream, leftover) = association
+        .release_and_take_stream()
+        .expect("did not have a peaceful release");
+    assert!(leftover.is_empty());
+
+    stream.write_all(CUSTOM_REQUEST).unwrap();
+
+    let mut response = vec![0; CUSTOM_RESPONSE.len()];
+    stream.read_exact(&mut response).unwrap();
+    assert_eq!(response, CUSTOM_RESPONSE);
+
+    scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+}
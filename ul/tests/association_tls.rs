@@ -0,0 +1,294 @@
+//! Associations over TLS,
+//! with a certificate chain generated for each test.
+#![cfg(feature = "tls")]
+use dicom_ul::{
+    association::client::{self, ClientAssociationOptions},
+    association::server::{self, ServerAssociationOptions},
+    association::tls::{rustls, TlsError},
+    pdu::{PDataValue, PDataValueType, Pdu},
+};
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, KeyPair};
+use rustls::{
+    crypto::CryptoProvider,
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+    server::WebPkiClientVerifier,
+    ClientConfig, RootCertStore, ServerConfig,
+};
+
+use std::io::Read;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+static SCU_AE_TITLE: &str = "TLS-SCU";
+static SCP_AE_TITLE: &str = "TLS-SCP";
+
+static IMPLICIT_VR_LE: &str = "1.2.840.10008.1.2";
+static VERIFICATION_SOP_CLASS: &str = "1.2.840.10008.1.1";
+
+static DATA: &[u8] = b"\x08\x00\x00\x00\x02\x00\x00\x00\x30\x00";
+
+/// A certificate authority for issuing test certificates
+struct TestPki {
+    ca_cert: rcgen::Certificate,
+    ca_key: KeyPair,
+}
+
+impl TestPki {
+    fn new() -> Self {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "DICOM-rs Test CA");
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_cert = params.self_signed(&ca_key).unwrap();
+        TestPki { ca_cert, ca_key }
+    }
+
+    /// A root certificate store trusting only this authority
+    fn roots(&self) -> RootCertStore {
+        let mut roots = RootCertStore::empty();
+        roots.add(self.ca_cert.der().clone()).unwrap();
+        roots
+    }
+
+    /// Issue a certificate for the given DNS name,
+    /// returning the certificate chain and the private key
+    fn issue(&self, name: &str) -> (Vec<CertificateDer<'static>>, PrivateKeyDer<'static>) {
+        let key = KeyPair::generate().unwrap();
+        let params = CertificateParams::new(vec![name.to_string()]).unwrap();
+        let cert = params.signed_by(&key, &self.ca_cert, &self.ca_key).unwrap();
+        (
+            vec![cert.der().clone(), self.ca_cert.der().clone()],
+            PrivatePkcs8KeyDer::from(key.serialize_der()).into(),
+        )
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+/// Server configuration with a certificate for `localhost`,
+/// requiring client certificates if `require_client_auth` is true
+fn server_config(pki: &TestPki, require_client_auth: bool) -> Arc<ServerConfig> {
+    let builder = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap();
+    let builder = if require_client_auth {
+        let verifier =
+            WebPkiClientVerifier::builder_with_provider(Arc::new(pki.roots()), provider())
+                .build()
+                .unwrap();
+        builder.with_client_cert_verifier(verifier)
+    } else {
+        builder.with_no_client_auth()
+    };
+    let (chain, key) = pki.issue("localhost");
+    Arc::new(builder.with_single_cert(chain, key).unwrap())
+}
+
+/// Client configuration trusting the test authority,
+/// without a client certificate
+fn client_config(pki: &TestPki) -> Arc<ClientConfig> {
+    let config = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(pki.roots())
+        .with_no_client_auth();
+    Arc::new(config)
+}
+
+fn echo_pdu(presentation_context_id: u8) -> Pdu {
+    Pdu::PData {
+        data: vec![PDataValue {
+            presentation_context_id,
+            value_type: PDataValueType::Data,
+            is_last: true,
+            data: DATA.to_vec(),
+        }],
+    }
+}
+
+/// Spawn an SCP which accepts one association over TLS,
+/// echoes back the first PDU received,
+/// and then waits for a release request.
+fn spawn_scp(
+    config: Arc<ServerConfig>,
+) -> (std::thread::JoinHandle<server::Result<()>>, SocketAddr) {
+    let listener = std::net::TcpListener::bind("localhost:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(VERIFICATION_SOP_CLASS);
+
+    let h = std::thread::spawn(move || -> server::Result<()> {
+        let (stream, _addr) = listener.accept().unwrap();
+        let mut association = scp.establish_tls(stream, config)?;
+        assert_eq!(association.client_ae_title(), SCU_AE_TITLE);
+
+        let pdu = association.receive()?;
+        association.send(&pdu)?;
+
+        let pdu = association.receive()?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP)?;
+
+        // the requester should close the TLS session cleanly
+        let mut rest = Vec::new();
+        association
+            .inner_stream()
+            .read_to_end(&mut rest)
+            .expect("TLS stream should end with close_notify");
+        assert!(rest.is_empty());
+        Ok(())
+    });
+    (h, addr)
+}
+
+#[test]
+fn scu_scp_association_over_tls() {
+    let pki = TestPki::new();
+    let (scp_handle, scp_addr) = spawn_scp(server_config(&pki, false));
+
+    let mut association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(VERIFICATION_SOP_CLASS, vec![IMPLICIT_VR_LE])
+        .establish_tls(scp_addr, client_config(&pki), "localhost")
+        .unwrap();
+
+    let pdu = echo_pdu(association.presentation_contexts()[0].id);
+    association.send(&pdu).unwrap();
+    assert_eq!(association.receive().unwrap(), pdu);
+
+    association
+        .release()
+        .expect("did not have a peaceful release");
+
+    scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+}
+
+#[test]
+fn scu_rejects_wrong_server_name() {
+    let pki = TestPki::new();
+    let (scp_handle, scp_addr) = spawn_scp(server_config(&pki, false));
+
+    let result = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(VERIFICATION_SOP_CLASS, vec![IMPLICIT_VR_LE])
+        .establish_tls(scp_addr, client_config(&pki), "pacs.example.com");
+
+    match result {
+        Err(client::Error::Tls {
+            source:
+                TlsError::Handshake {
+                    source: rustls::Error::InvalidCertificate(_),
+                    ..
+                },
+        }) => {}
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("association should not have been established"),
+    }
+
+    let scp_result = scp_handle.join().expect("SCP panicked");
+    assert!(
+        matches!(scp_result, Err(server::Error::Tls { .. })),
+        "unexpected SCP result: {:?}",
+        scp_result
+    );
+}
+
+#[test]
+fn scp_rejects_scu_without_client_certificate() {
+    let pki = TestPki::new();
+    let (scp_handle, scp_addr) = spawn_scp(server_config(&pki, true));
+
+    // with TLS 1.3, the requester only learns about the rejection
+    // after its side of the handshake is complete
+    let result = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(VERIFICATION_SOP_CLASS, vec![IMPLICIT_VR_LE])
+        .establish_tls(scp_addr, client_config(&pki), "localhost");
+    assert!(
+        result.is_err(),
+        "association should not have been established"
+    );
+
+    let scp_result = scp_handle.join().expect("SCP panicked");
+    match scp_result {
+        Err(server::Error::Tls {
+            source:
+                TlsError::Handshake {
+                    source: rustls::Error::NoCertificatesPresented,
+                    ..
+                },
+        }) => {}
+        other => panic!("unexpected SCP result: {:?}", other),
+    }
+}
+
+#[cfg(feature = "async-tls")]
+#[tokio::test(flavor = "multi_thread")]
+async fn scu_scp_association_over_tls_async() {
+    use tokio::io::AsyncReadExt;
+
+    let pki = TestPki::new();
+    let config = server_config(&pki, false);
+    let listener = tokio::net::TcpListener::bind("localhost:0").await.unwrap();
+    let scp_addr = listener.local_addr().unwrap();
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(VERIFICATION_SOP_CLASS);
+
+    let scp_handle = tokio::spawn(async move {
+        let (stream, _addr) = listener.accept().await.unwrap();
+        let mut association = scp.establish_tls_async(stream, config).await?;
+
+        let pdu = association.receive().await?;
+        association.send(&pdu).await?;
+
+        let pdu = association.receive().await?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP).await?;
+
+        // the requester should close the TLS session cleanly
+        let mut rest = Vec::new();
+        association
+            .inner_stream()
+            .read_to_end(&mut rest)
+            .await
+            .expect("TLS stream should end with close_notify");
+        assert!(rest.is_empty());
+        server::Result::Ok(())
+    });
+
+    let mut association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(VERIFICATION_SOP_CLASS, vec![IMPLICIT_VR_LE])
+        .establish_tls_async(scp_addr, client_config(&pki), "localhost")
+        .await
+        .unwrap();
+
+    let pdu = echo_pdu(association.presentation_contexts()[0].id);
+    association.send(&pdu).await.unwrap();
+    assert_eq!(association.receive().await.unwrap(), pdu);
+
+    association
+        .release()
+        .await
+        .expect("did not have a peaceful release");
+
+    scp_handle
+        .await
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+}
@@ -0,0 +1,166 @@
+//! A test suite involving an SCP
+//! with an order of preference among transfer syntaxes
+
+use dicom_ul::{
+    association::client::ClientAssociationOptions,
+    pdu::{Pdu, PresentationContextResult, PresentationContextResultReason},
+};
+use std::net::SocketAddr;
+
+use dicom_ul::association::server::{AcceptCalledAeTitle, ServerAssociationOptions};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+static SCU_AE_TITLE: &str = "GET-SCU";
+static SCP_AE_TITLE: &str = "GET-SCP";
+
+static EXPLICIT_VR_LE: &str = "1.2.840.10008.1.2.1";
+static IMPLICIT_VR_LE: &str = "1.2.840.10008.1.2";
+static RLE_LOSSLESS: &str = "1.2.840.10008.1.2.5";
+static CT_IMAGE_STORAGE: &str = "1.2.840.10008.5.1.4.1.1.2";
+static MR_IMAGE_STORAGE: &str = "1.2.840.10008.5.1.4.1.1.4";
+static US_IMAGE_STORAGE: &str = "1.2.840.10008.5.1.4.1.1.6.1";
+
+fn scp_options() -> ServerAssociationOptions<'static, AcceptCalledAeTitle> {
+    ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(CT_IMAGE_STORAGE)
+        .with_abstract_syntax(MR_IMAGE_STORAGE)
+        .with_abstract_syntax(US_IMAGE_STORAGE)
+        .with_transfer_syntax_preference(vec![RLE_LOSSLESS.to_string(), EXPLICIT_VR_LE.to_string()])
+}
+
+fn expected_presentation_contexts() -> Vec<PresentationContextResult> {
+    vec![
+        // the preferred one, even though it was proposed last
+        PresentationContextResult {
+            id: 1,
+            reason: PresentationContextResultReason::Acceptance,
+            transfer_syntax: RLE_LOSSLESS.to_string(),
+        },
+        // the next one in the order of preference
+        PresentationContextResult {
+            id: 3,
+            reason: PresentationContextResultReason::Acceptance,
+            transfer_syntax: EXPLICIT_VR_LE.to_string(),
+        },
+        // none of the preferred ones were proposed,
+        // so the requester's order applies
+        PresentationContextResult {
+            id: 5,
+            reason: PresentationContextResultReason::Acceptance,
+            transfer_syntax: IMPLICIT_VR_LE.to_string(),
+        },
+    ]
+}
+
+fn client_options() -> ClientAssociationOptions<'static> {
+    ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(CT_IMAGE_STORAGE, vec![EXPLICIT_VR_LE, RLE_LOSSLESS])
+        .with_presentation_context(MR_IMAGE_STORAGE, vec![IMPLICIT_VR_LE, EXPLICIT_VR_LE])
+        .with_presentation_context(US_IMAGE_STORAGE, vec![IMPLICIT_VR_LE])
+}
+
+fn spawn_scp() -> Result<(std::thread::JoinHandle<Result<()>>, SocketAddr)> {
+    let listener = std::net::TcpListener::bind("localhost:0")?;
+    let addr = listener.local_addr()?;
+    let scp = scp_options();
+
+    let h = std::thread::spawn(move || -> Result<()> {
+        let (stream, _addr) = listener.accept()?;
+        let mut association = scp.establish(stream)?;
+
+        assert_eq!(
+            association.presentation_contexts(),
+            &expected_presentation_contexts()[..],
+        );
+        assert_eq!(association.transfer_syntax(1), Some(RLE_LOSSLESS));
+        assert_eq!(association.transfer_syntax(3), Some(EXPLICIT_VR_LE));
+        assert_eq!(association.transfer_syntax(7), None);
+
+        // handle one release request
+        let pdu = association.receive()?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP)?;
+
+        Ok(())
+    });
+    Ok((h, addr))
+}
+
+#[cfg(feature = "async")]
+async fn spawn_scp_async() -> Result<(tokio::task::JoinHandle<Result<()>>, SocketAddr)> {
+    let listener = tokio::net::TcpListener::bind("localhost:0").await?;
+    let addr = listener.local_addr()?;
+    let scp = scp_options();
+
+    let h = tokio::task::spawn(async move {
+        let (stream, _addr) = listener.accept().await?;
+        let mut association = scp.establish_async(stream).await?;
+
+        assert_eq!(
+            association.presentation_contexts(),
+            &expected_presentation_contexts()[..],
+        );
+        assert_eq!(association.transfer_syntax(1), Some(RLE_LOSSLESS));
+
+        // handle one release request
+        let pdu = association.receive().await?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP).await?;
+
+        Ok(())
+    });
+    Ok((h, addr))
+}
+
+/// Run an SCP and an SCU concurrently,
+/// negotiate an association in which the SCP
+/// picks transfer syntaxes by its own order of preference,
+/// and release it.
+#[test]
+fn scu_scp_association_transfer_syntax_preference() {
+    let (scp_handle, scp_addr) = spawn_scp().unwrap();
+
+    let association = client_options().establish(scp_addr).unwrap();
+
+    assert_eq!(
+        association.presentation_contexts(),
+        &expected_presentation_contexts()[..],
+    );
+
+    association
+        .release()
+        .expect("did not have a peaceful release");
+
+    scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+}
+
+#[cfg(feature = "async")]
+#[tokio::test(flavor = "multi_thread")]
+async fn scu_scp_association_transfer_syntax_preference_async() {
+    let (scp_handle, scp_addr) = spawn_scp_async().await.unwrap();
+
+    let association = client_options().establish_async(scp_addr).await.unwrap();
+
+    assert_eq!(
+        association.presentation_contexts(),
+        &expected_presentation_contexts()[..],
+    );
+
+    association
+        .release()
+        .await
+        .expect("did not have a peaceful release");
+
+    scp_handle
+        .await
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+}
@@ -0,0 +1,123 @@
+use dicom_ul::{
+    association::client::ClientAssociationOptions,
+    association::server::ServerAssociationOptions,
+    association::wire_log::{Direction, WireLog, WireRecord},
+    pdu::Pdu,
+    read_pdu,
+};
+
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+static SCU_AE_TITLE: &str = "TAP-SCU";
+static SCP_AE_TITLE: &str = "TAP-SCP";
+
+static IMPLICIT_VR_LE: &str = "1.2.840.10008.1.2";
+static VERIFICATION_SOP_CLASS: &str = "1.2.840.10008.1.1";
+
+static DATA: &[u8] = b"\x08\x00\x00\x00\x02\x00\x00\x00\x30\x00";
+
+/// Concatenate the bytes of all records in the given direction
+fn bytes_in(records: &[WireRecord], direction: Direction) -> Vec<u8> {
+    records
+        .iter()
+        .filter(|record| record.direction == direction)
+        .flat_map(|record| record.data.iter().copied())
+        .collect()
+}
+
+/// Parse all PDUs in the given bytes
+fn parse_pdus(mut bytes: &[u8]) -> Vec<Pdu> {
+    let mut pdus = Vec::new();
+    while !bytes.is_empty() {
+        let pdu = read_pdu(&mut bytes, 16_384, true)
+            .unwrap()
+            .expect("incomplete PDU in wire log");
+        pdus.push(pdu);
+    }
+    pdus
+}
+
+fn spawn_scp(wire_log: WireLog) -> Result<(std::thread::JoinHandle<Result<()>>, SocketAddr)> {
+    let listener = std::net::TcpListener::bind("localhost:0")?;
+    let addr = listener.local_addr()?;
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(SCP_AE_TITLE)
+        .with_abstract_syntax(VERIFICATION_SOP_CLASS)
+        .wire_log(wire_log);
+
+    let h = std::thread::spawn(move || -> Result<()> {
+        let (stream, _addr) = listener.accept()?;
+        let mut association = scp.establish(stream)?;
+
+        let mut data = Vec::new();
+        association.receive_pdata().read_to_end(&mut data)?;
+        assert_eq!(data, DATA);
+
+        let pdu = association.receive()?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP)?;
+
+        Ok(())
+    });
+    Ok((h, addr))
+}
+
+/// Capture the traffic of an association on both ends,
+/// checking that the bytes sent by one node
+/// are the bytes received by the other one.
+#[test]
+fn scu_scp_wire_log() {
+    let scu_log = WireLog::in_memory();
+    let scp_log = WireLog::in_memory();
+
+    let (scp_handle, scp_addr) = spawn_scp(scp_log.clone()).unwrap();
+
+    let mut association = ClientAssociationOptions::new()
+        .calling_ae_title(SCU_AE_TITLE)
+        .called_ae_title(SCP_AE_TITLE)
+        .with_presentation_context(VERIFICATION_SOP_CLASS, vec![IMPLICIT_VR_LE])
+        .wire_log(scu_log.clone())
+        .establish(scp_addr)
+        .unwrap();
+
+    let pc_id = association.presentation_contexts()[0].id;
+    {
+        let mut writer = association.send_pdata(pc_id);
+        writer.write_all(DATA).unwrap();
+        writer.finish().unwrap();
+    }
+
+    association
+        .release()
+        .expect("did not have a peaceful release");
+
+    scp_handle
+        .join()
+        .expect("SCP panicked")
+        .expect("Error at the SCP");
+
+    let scu_records = scu_log.records().unwrap();
+    let scp_records = scp_log.records().unwrap();
+
+    let scu_sent = bytes_in(&scu_records, Direction::Sent);
+    let scu_received = bytes_in(&scu_records, Direction::Received);
+    let scp_sent = bytes_in(&scp_records, Direction::Sent);
+    let scp_received = bytes_in(&scp_records, Direction::Received);
+    assert_eq!(scu_sent, scp_received);
+    assert_eq!(scu_received, scp_sent);
+
+    let requests = parse_pdus(&scu_sent);
+    assert_eq!(requests.len(), 3);
+    assert!(matches!(requests[0], Pdu::AssociationRQ(_)));
+    assert!(matches!(requests[1], Pdu::PData { .. }));
+    assert_eq!(requests[2], Pdu::ReleaseRQ);
+
+    let responses = parse_pdus(&scp_sent);
+    assert_eq!(responses.len(), 2);
+    assert!(matches!(responses[0], Pdu::AssociationAC(_)));
+    assert_eq!(responses[1], Pdu::ReleaseRP);
+}
@@ -0,0 +1,39 @@
+//! Utility module shared by the association tests.
+use dicom_ul::{association::server::ServerAssociationOptions, pdu::Pdu};
+
+use std::net::SocketAddr;
+use std::thread::JoinHandle;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+/// Spawn an SCP with the given AE title
+/// which answers C-ECHO requests automatically
+/// until the association is released.
+///
+/// No abstract syntaxes are declared:
+/// verification is accepted because of automatic verification.
+#[allow(dead_code)]
+pub(crate) fn spawn_auto_verification_scp(
+    ae_title: &'static str,
+) -> Result<(JoinHandle<Result<()>>, SocketAddr)> {
+    let listener = std::net::TcpListener::bind("localhost:0")?;
+    let addr = listener.local_addr()?;
+    let scp = ServerAssociationOptions::new()
+        .accept_called_ae_title()
+        .ae_title(ae_title)
+        .auto_verification(true);
+
+    let h = std::thread::spawn(move || -> Result<()> {
+        let (stream, _addr) = listener.accept()?;
+        let mut association = scp.establish(stream)?;
+
+        // the echo requests never reach the application,
+        // the next message is the release request
+        let pdu = association.receive()?;
+        assert_eq!(pdu, Pdu::ReleaseRQ);
+        association.send(&Pdu::ReleaseRP)?;
+
+        Ok(())
+    });
+    Ok((h, addr))
+}
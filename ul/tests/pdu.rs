@@ -1,8 +1,8 @@
 use dicom_ul::pdu::reader::read_pdu;
 use dicom_ul::pdu::writer::write_pdu;
 use dicom_ul::pdu::{
-    AssociationRQ, PDataValue, PDataValueType, Pdu, PresentationContextProposed, UserIdentity,
-    UserIdentityType, UserVariableItem, DEFAULT_MAX_PDU,
+    AssociationRQ, PDataValue, PDataValueType, Pdu, PresentationContextProposed, ReadError,
+    UserIdentity, UserIdentityType, UserVariableItem, DEFAULT_MAX_PDU,
 };
 use matches::matches;
 use std::io::Cursor;
@@ -213,3 +213,18 @@ fn can_read_write_pdata() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+fn read_pdata_with_oversized_item_length() {
+    // P-DATA-TF with a single item claiming almost 4 GiB
+    let mut bytes = vec![0x04, 0x00];
+    bytes.extend_from_slice(&10_u32.to_be_bytes());
+    bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+    bytes.extend_from_slice(&[3, 0x03, 0, 0, 0, 0]);
+
+    let result = read_pdu(&mut Cursor::new(&bytes), DEFAULT_MAX_PDU, true);
+    assert!(matches!(
+        result,
+        Err(ReadError::IncompletePdvItem { length: 10, .. })
+    ));
+}